@@ -86,7 +86,7 @@ fn main() {
         let iters = if size >= 100_000 { 20 } else { 50 };
         let result = benchmark(
             || {
-                let _ = match_sorter(&items, "item_5", MatchSorterOptions::default());
+                let _ = match_sorter(&items, "item_5", &MatchSorterOptions::default());
             },
             iters,
             10,
@@ -99,35 +99,35 @@ fn main() {
 
     let exact = benchmark(
         || {
-            let _ = match_sorter(&items_10k, "item_500", MatchSorterOptions::default());
+            let _ = match_sorter(&items_10k, "item_500", &MatchSorterOptions::default());
         },
         50,
         10,
     );
     let prefix = benchmark(
         || {
-            let _ = match_sorter(&items_10k, "item_", MatchSorterOptions::default());
+            let _ = match_sorter(&items_10k, "item_", &MatchSorterOptions::default());
         },
         50,
         10,
     );
     let substring = benchmark(
         || {
-            let _ = match_sorter(&items_10k, "_50", MatchSorterOptions::default());
+            let _ = match_sorter(&items_10k, "_50", &MatchSorterOptions::default());
         },
         50,
         10,
     );
     let fuzzy = benchmark(
         || {
-            let _ = match_sorter(&items_10k, "im5", MatchSorterOptions::default());
+            let _ = match_sorter(&items_10k, "im5", &MatchSorterOptions::default());
         },
         50,
         10,
     );
     let no_match = benchmark(
         || {
-            let _ = match_sorter(&items_10k, "zzzzz", MatchSorterOptions::default());
+            let _ = match_sorter(&items_10k, "zzzzz", &MatchSorterOptions::default());
         },
         50,
         10,
@@ -137,7 +137,7 @@ fn main() {
     let diacritics_items = generate_diacritics_items(10_000);
     let strip = benchmark(
         || {
-            let _ = match_sorter(&diacritics_items, "cafe", MatchSorterOptions::default());
+            let _ = match_sorter(&diacritics_items, "cafe", &MatchSorterOptions::default());
         },
         50,
         10,
@@ -148,7 +148,7 @@ fn main() {
                 keep_diacritics: true,
                 ..Default::default()
             };
-            let _ = match_sorter(&diacritics_items, "cafe", opts);
+            let _ = match_sorter(&diacritics_items, "cafe", &opts);
         },
         50,
         10,
@@ -157,7 +157,7 @@ fn main() {
     // 4. Threshold filtering
     let thresh_default = benchmark(
         || {
-            let _ = match_sorter(&items_10k, "item_5", MatchSorterOptions::default());
+            let _ = match_sorter(&items_10k, "item_5", &MatchSorterOptions::default());
         },
         50,
         10,
@@ -168,7 +168,7 @@ fn main() {
                 threshold: Ranking::Contains,
                 ..Default::default()
             };
-            let _ = match_sorter(&items_10k, "item_5", opts);
+            let _ = match_sorter(&items_10k, "item_5", &opts);
         },
         50,
         10,
@@ -178,14 +178,14 @@ fn main() {
     let items_100 = generate_items(100);
     let empty_100 = benchmark(
         || {
-            let _ = match_sorter(&items_100, "", MatchSorterOptions::default());
+            let _ = match_sorter(&items_100, "", &MatchSorterOptions::default());
         },
         50,
         10,
     );
     let empty_10k = benchmark(
         || {
-            let _ = match_sorter(&items_10k, "", MatchSorterOptions::default());
+            let _ = match_sorter(&items_10k, "", &MatchSorterOptions::default());
         },
         50,
         10,
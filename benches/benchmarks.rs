@@ -2,8 +2,8 @@ use std::borrow::Cow;
 
 use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
 use matchsorter::{
-    MatchSorterOptions, RankedItem, Ranking, default_base_sort, get_match_ranking, match_sorter,
-    sort_ranked_values,
+    FuzzyMode, MatchSorterOptions, RankedItem, Ranking, default_base_sort, get_match_ranking,
+    match_sorter, ranking::PreparedQuery, sort_ranked_values, unicode_base_sort,
 };
 
 // ---------------------------------------------------------------------------
@@ -28,6 +28,20 @@ fn generate_diacritics_items(n: usize) -> Vec<String> {
         .collect()
 }
 
+/// Generate a dataset of `n` two-word items whose words are separated by a
+/// no-break space (U+00A0) on every other entry and an ASCII space otherwise.
+fn generate_spaced_items(n: usize) -> Vec<String> {
+    (0..n)
+        .map(|i| {
+            if i % 2 == 0 {
+                format!("item\u{00a0}word_{i}")
+            } else {
+                format!("item word_{i}")
+            }
+        })
+        .collect()
+}
+
 /// Build a `Vec<RankedItem>` suitable for benchmarking the sort step in
 /// isolation. Items are assigned ranks in a round-robin pattern across
 /// several tiers to exercise the three-level comparator.
@@ -51,6 +65,10 @@ fn generate_ranked_items(items: &[String]) -> Vec<RankedItem<'_, String>> {
             ranked_value: Cow::Owned(item.clone()),
             key_index: i % 3,
             key_threshold: None,
+            is_soft_match: false,
+            matched_portion: None,
+            matched_key_alias: None,
+            tie_priority: None,
         })
         .collect()
 }
@@ -99,7 +117,7 @@ fn bench_throughput(c: &mut Criterion) {
                 match_sorter(
                     black_box(items),
                     black_box("item_5"),
-                    MatchSorterOptions::default(),
+                    &MatchSorterOptions::default(),
                 )
             });
         });
@@ -122,7 +140,7 @@ fn bench_query_types(c: &mut Criterion) {
             match_sorter(
                 black_box(&items),
                 black_box("item_500"),
-                MatchSorterOptions::default(),
+                &MatchSorterOptions::default(),
             )
         });
     });
@@ -133,7 +151,7 @@ fn bench_query_types(c: &mut Criterion) {
             match_sorter(
                 black_box(&items),
                 black_box("item_"),
-                MatchSorterOptions::default(),
+                &MatchSorterOptions::default(),
             )
         });
     });
@@ -144,7 +162,7 @@ fn bench_query_types(c: &mut Criterion) {
             match_sorter(
                 black_box(&items),
                 black_box("_50"),
-                MatchSorterOptions::default(),
+                &MatchSorterOptions::default(),
             )
         });
     });
@@ -155,7 +173,7 @@ fn bench_query_types(c: &mut Criterion) {
             match_sorter(
                 black_box(&items),
                 black_box("im5"),
-                MatchSorterOptions::default(),
+                &MatchSorterOptions::default(),
             )
         });
     });
@@ -166,7 +184,7 @@ fn bench_query_types(c: &mut Criterion) {
             match_sorter(
                 black_box(&items),
                 black_box("zzzzz"),
-                MatchSorterOptions::default(),
+                &MatchSorterOptions::default(),
             )
         });
     });
@@ -188,7 +206,7 @@ fn bench_diacritics(c: &mut Criterion) {
             match_sorter(
                 black_box(&items),
                 black_box("cafe"),
-                MatchSorterOptions::default(),
+                &MatchSorterOptions::default(),
             )
         });
     });
@@ -200,7 +218,40 @@ fn bench_diacritics(c: &mut Criterion) {
                 keep_diacritics: true,
                 ..Default::default()
             };
-            match_sorter(black_box(&items), black_box("cafe"), opts)
+            match_sorter(black_box(&items), black_box("cafe"), &opts)
+        });
+    });
+
+    group.finish();
+}
+
+// ---------------------------------------------------------------------------
+// 5. Unicode word boundary overhead
+// ---------------------------------------------------------------------------
+
+fn bench_word_boundaries(c: &mut Criterion) {
+    let mut group = c.benchmark_group("word_boundaries");
+    let items = generate_spaced_items(10_000);
+
+    // Default: only the ASCII space byte starts a word
+    group.bench_function("ascii_boundaries", |b| {
+        b.iter(|| {
+            match_sorter(
+                black_box(&items),
+                black_box("word"),
+                &MatchSorterOptions::default(),
+            )
+        });
+    });
+
+    // Decode multi-byte predecessors and test `char::is_whitespace`
+    group.bench_function("unicode_boundaries", |b| {
+        b.iter(|| {
+            let opts = MatchSorterOptions {
+                unicode_word_boundaries: true,
+                ..Default::default()
+            };
+            match_sorter(black_box(&items), black_box("word"), &opts)
         });
     });
 
@@ -208,7 +259,64 @@ fn bench_diacritics(c: &mut Criterion) {
 }
 
 // ---------------------------------------------------------------------------
-// 5. Sort overhead
+// 6. Greedy vs optimal fuzzy matching
+// ---------------------------------------------------------------------------
+
+fn bench_fuzzy_mode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fuzzy_mode");
+    let items = generate_items(10_000);
+
+    // Only fuzzy candidates reach the closeness step with this query
+    for (name, mode) in [
+        ("greedy", FuzzyMode::Greedy),
+        ("optimal", FuzzyMode::Optimal),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let opts = MatchSorterOptions {
+                    fuzzy_mode: mode,
+                    ..Default::default()
+                };
+                match_sorter(black_box(&items), black_box("im9"), &opts)
+            });
+        });
+    }
+
+    group.finish();
+}
+
+// ---------------------------------------------------------------------------
+// 7. quick_match pre-filter on a no-match dataset
+// ---------------------------------------------------------------------------
+
+fn bench_quick_match(c: &mut Criterion) {
+    let mut group = c.benchmark_group("quick_match");
+    let items = generate_items(10_000);
+
+    // "zqx" shares no characters with "item_N", so every item is a non-match
+    group.bench_function("full_ranking", |b| {
+        b.iter(|| {
+            black_box(&items)
+                .iter()
+                .filter(|item| get_match_ranking(item, black_box("zqx"), false) != Ranking::NoMatch)
+                .count()
+        });
+    });
+    group.bench_function("quick_match", |b| {
+        let pq = PreparedQuery::new("zqx", false);
+        b.iter(|| {
+            black_box(&items)
+                .iter()
+                .filter(|item| pq.quick_match(item))
+                .count()
+        });
+    });
+
+    group.finish();
+}
+
+// ---------------------------------------------------------------------------
+// 8. Sort overhead
 // ---------------------------------------------------------------------------
 
 fn bench_sort(c: &mut Criterion) {
@@ -227,6 +335,109 @@ fn bench_sort(c: &mut Criterion) {
         );
     });
 
+    // Byte comparison vs NFC comparison, on ASCII values (the fast path)
+    // and on values with diacritics in mixed NFC/NFD form.
+    let accented: Vec<String> = generate_diacritics_items(10_000)
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| {
+            if i % 4 == 0 {
+                s.replace('\u{e9}', "e\u{301}")
+            } else {
+                s
+            }
+        })
+        .collect();
+    let accented_ranked = generate_ranked_items(&accented);
+    for (data_name, data) in [("ascii", &ranked), ("accented", &accented_ranked)] {
+        group.bench_function(BenchmarkId::new("base_sort_bytes", data_name), |b| {
+            b.iter_batched(
+                || data.clone(),
+                |mut data| {
+                    data.sort_by(|a, b| sort_ranked_values(a, b, &default_base_sort));
+                    data
+                },
+                criterion::BatchSize::LargeInput,
+            );
+        });
+        group.bench_function(BenchmarkId::new("base_sort_nfc", data_name), |b| {
+            b.iter_batched(
+                || data.clone(),
+                |mut data| {
+                    data.sort_by(|a, b| sort_ranked_values(a, b, &unicode_base_sort));
+                    data
+                },
+                criterion::BatchSize::LargeInput,
+            );
+        });
+    }
+
+    group.finish();
+}
+
+// ---------------------------------------------------------------------------
+// 9. Batch ranking with a shared PreparedQuery
+// ---------------------------------------------------------------------------
+
+fn bench_apply_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("apply_batch");
+    let items = generate_items(10_000);
+    let candidates: Vec<&str> = items.iter().map(String::as_str).collect();
+
+    group.bench_function("get_match_ranking_loop", |b| {
+        b.iter(|| {
+            black_box(&candidates)
+                .iter()
+                .map(|candidate| get_match_ranking(candidate, black_box("item_5"), false))
+                .collect::<Vec<_>>()
+        });
+    });
+    group.bench_function("apply_batch_into", |b| {
+        let pq = PreparedQuery::new("item_5", false);
+        let mut output = Vec::with_capacity(candidates.len());
+        b.iter(|| {
+            pq.apply_batch_into(black_box(&candidates), &mut output);
+            output.len()
+        });
+    });
+
+    group.finish();
+}
+
+// ---------------------------------------------------------------------------
+// 10. memchr prefilter on a mostly non-matching dataset
+// ---------------------------------------------------------------------------
+
+fn bench_prefilter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prefilter");
+    group.sample_size(20);
+    // Only every twentieth item contains a 'z', the query's first char.
+    let items: Vec<String> = (0..100_000)
+        .map(|i| {
+            if i % 20 == 0 {
+                format!("zone_{i}")
+            } else {
+                format!("item_{i}")
+            }
+        })
+        .collect();
+
+    for prefilter in [false, true] {
+        group.bench_with_input(
+            BenchmarkId::new("100k_5pct_first_char", prefilter),
+            &prefilter,
+            |b, &prefilter| {
+                b.iter(|| {
+                    let opts = MatchSorterOptions {
+                        prefilter,
+                        ..Default::default()
+                    };
+                    match_sorter(&items, black_box("zn1"), &opts).len()
+                });
+            },
+        );
+    }
+
     group.finish();
 }
 
@@ -240,6 +451,11 @@ criterion_group!(
     bench_throughput,
     bench_query_types,
     bench_diacritics,
+    bench_word_boundaries,
+    bench_fuzzy_mode,
+    bench_quick_match,
     bench_sort,
+    bench_apply_batch,
+    bench_prefilter,
 );
 criterion_main!(benches);
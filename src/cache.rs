@@ -0,0 +1,399 @@
+//! Least-recently-used cache of `(candidate, query)` rankings.
+//!
+//! Interactive search often ranks the same candidate against the same query
+//! again, for example when a dropdown is reopened. [`RankingCache`] remembers
+//! recent results of [`get_match_ranking`] so that repeat evaluations skip
+//! the ranking algorithm. Share one between `match_sorter` calls through
+//! [`MatchSorterOptions::ranking_cache`](crate::MatchSorterOptions::ranking_cache).
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::ranking::{DiacriticsMode, Ranking, get_match_ranking, prepare_value_for_comparison};
+
+/// Marks the absence of a neighbor in the recency list.
+const NIL: usize = usize::MAX;
+
+/// A bounded cache of [`get_match_ranking`] results with least-recently-used
+/// eviction.
+///
+/// Entries are keyed on the candidate, the query and the diacritics setting,
+/// with the query
+/// normalized the same way the ranking algorithm normalizes it. Once
+/// `capacity` entries are stored, inserting another evicts the entry that was
+/// looked up or inserted longest ago. A capacity of zero disables caching.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{Ranking, RankingCache};
+///
+/// let mut cache = RankingCache::new(2);
+/// assert_eq!(cache.get_or_compute("Greenland", "green", false), Ranking::StartsWith);
+/// assert_eq!(cache.get_or_compute("Greenland", "green", false), Ranking::StartsWith);
+/// assert_eq!(cache.len(), 1);
+/// ```
+pub struct RankingCache {
+    /// Maximum number of entries.
+    capacity: usize,
+
+    /// Maps each key to its slot in `entries`.
+    index: HashMap<EntryKey, usize>,
+
+    /// Entry storage; slots are reused after eviction.
+    entries: Vec<Entry>,
+
+    /// Most recently used slot, or `NIL` when empty.
+    head: usize,
+
+    /// Least recently used slot, or `NIL` when empty.
+    tail: usize,
+}
+
+/// One cached ranking and its links in the recency list.
+struct Entry {
+    key: EntryKey,
+    rank: Ranking,
+    /// Next more recently used slot.
+    prev: usize,
+    /// Next less recently used slot.
+    next: usize,
+}
+
+/// Owned cache key: `(candidate, normalized query, diacritics mode)`.
+#[derive(Clone)]
+struct EntryKey(String, String, DiacriticsMode);
+
+/// Key view shared by owned and borrowed keys, so lookups need not allocate.
+trait KeyView {
+    fn parts(&self) -> (&str, &str, DiacriticsMode);
+}
+
+impl KeyView for EntryKey {
+    fn parts(&self) -> (&str, &str, DiacriticsMode) {
+        (&self.0, &self.1, self.2)
+    }
+}
+
+impl KeyView for (&str, &str, DiacriticsMode) {
+    fn parts(&self) -> (&str, &str, DiacriticsMode) {
+        *self
+    }
+}
+
+impl<'a> Borrow<dyn KeyView + 'a> for EntryKey {
+    fn borrow(&self) -> &(dyn KeyView + 'a) {
+        self
+    }
+}
+
+impl Hash for dyn KeyView + '_ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.parts().hash(state);
+    }
+}
+
+impl PartialEq for dyn KeyView + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl Eq for dyn KeyView + '_ {}
+
+// Must hash and compare exactly like `dyn KeyView` for `Borrow` lookups.
+impl Hash for EntryKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.parts().hash(state);
+    }
+}
+
+impl PartialEq for EntryKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl Eq for EntryKey {}
+
+impl RankingCache {
+    /// Create an empty cache holding at most `capacity` rankings.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            index: HashMap::with_capacity(capacity.min(1024)),
+            entries: Vec::new(),
+            head: NIL,
+            tail: NIL,
+        }
+    }
+
+    /// Return the ranking of `candidate` against `query`, computing it with
+    /// [`get_match_ranking`] and caching it on a miss.
+    ///
+    /// # Arguments
+    ///
+    /// * `candidate` - The string being evaluated
+    /// * `query` - The search query
+    /// * `keep_diacritics` - If `true`, skip diacritics stripping
+    pub fn get_or_compute(
+        &mut self,
+        candidate: &str,
+        query: &str,
+        keep_diacritics: bool,
+    ) -> Ranking {
+        let query = prepare_value_for_comparison(query, keep_diacritics);
+        let diacritics = DiacriticsMode::from_keep_diacritics(keep_diacritics);
+        self.get_or_insert_with(candidate, &query, diacritics, || {
+            get_match_ranking(candidate, &query, keep_diacritics)
+        })
+    }
+
+    /// Return the cached ranking for the key, or store and return the result
+    /// of `compute`. `query` must already be normalized with
+    /// [`prepare_value_with_mode`](crate::ranking::prepare_value_with_mode) under `diacritics`.
+    pub(crate) fn get_or_insert_with(
+        &mut self,
+        candidate: &str,
+        query: &str,
+        diacritics: DiacriticsMode,
+        compute: impl FnOnce() -> Ranking,
+    ) -> Ranking {
+        let view: &dyn KeyView = &(candidate, query, diacritics);
+        if let Some(&slot) = self.index.get(view) {
+            self.touch(slot);
+            return self.entries[slot].rank;
+        }
+        let rank = compute();
+        if self.capacity > 0 {
+            let key = EntryKey(candidate.to_owned(), query.to_owned(), diacritics);
+            self.insert(key, rank);
+        }
+        rank
+    }
+
+    /// Number of cached rankings.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns `true` if no rankings are cached.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Maximum number of cached rankings.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Remove every cached ranking.
+    pub fn clear(&mut self) {
+        self.index.clear();
+        self.entries.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+
+    /// Store a new entry as the most recently used, evicting the least
+    /// recently used one when full.
+    fn insert(&mut self, key: EntryKey, rank: Ranking) {
+        let slot = if self.index.len() < self.capacity {
+            self.entries.push(Entry {
+                key: key.clone(),
+                rank,
+                prev: NIL,
+                next: NIL,
+            });
+            self.entries.len() - 1
+        } else {
+            // Reuse the least recently used slot.
+            let slot = self.tail;
+            self.unlink(slot);
+            let old = std::mem::replace(&mut self.entries[slot].key, key.clone());
+            self.index.remove(&old);
+            self.entries[slot].rank = rank;
+            slot
+        };
+        self.index.insert(key, slot);
+        self.push_front(slot);
+    }
+
+    /// Mark `slot` as the most recently used.
+    fn touch(&mut self, slot: usize) {
+        if self.head != slot {
+            self.unlink(slot);
+            self.push_front(slot);
+        }
+    }
+
+    /// Detach `slot` from the recency list.
+    fn unlink(&mut self, slot: usize) {
+        let Entry { prev, next, .. } = self.entries[slot];
+        if prev == NIL {
+            self.head = next;
+        } else {
+            self.entries[prev].next = next;
+        }
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            self.entries[next].prev = prev;
+        }
+    }
+
+    /// Attach a detached `slot` as the most recently used.
+    fn push_front(&mut self, slot: usize) {
+        self.entries[slot].prev = NIL;
+        self.entries[slot].next = self.head;
+        if self.head == NIL {
+            self.tail = slot;
+        } else {
+            self.entries[self.head].prev = slot;
+        }
+        self.head = slot;
+    }
+}
+
+impl fmt::Debug for RankingCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RankingCache")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::sync::{Arc, Mutex};
+
+    fn counted(cache: &mut RankingCache, candidate: &str, calls: &Cell<usize>) -> Ranking {
+        cache.get_or_insert_with(candidate, "app", DiacriticsMode::StripAll, || {
+            calls.set(calls.get() + 1);
+            get_match_ranking(candidate, "app", false)
+        })
+    }
+
+    #[test]
+    fn hit_skips_recomputation() {
+        let mut cache = RankingCache::new(4);
+        let calls = Cell::new(0);
+        assert_eq!(counted(&mut cache, "apple", &calls), Ranking::StartsWith);
+        assert_eq!(counted(&mut cache, "apple", &calls), Ranking::StartsWith);
+        assert_eq!(calls.get(), 1);
+        assert_eq!(counted(&mut cache, "pineapple", &calls), Ranking::Contains);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = RankingCache::new(2);
+        let calls = Cell::new(0);
+        counted(&mut cache, "a1", &calls);
+        counted(&mut cache, "a2", &calls);
+        // Touch a1 so that a2 becomes the eviction candidate.
+        counted(&mut cache, "a1", &calls);
+        counted(&mut cache, "a3", &calls);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(calls.get(), 3);
+
+        counted(&mut cache, "a1", &calls);
+        counted(&mut cache, "a3", &calls);
+        assert_eq!(calls.get(), 3);
+        counted(&mut cache, "a2", &calls);
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn zero_capacity_never_caches() {
+        let mut cache = RankingCache::new(0);
+        let calls = Cell::new(0);
+        counted(&mut cache, "apple", &calls);
+        counted(&mut cache, "apple", &calls);
+        assert_eq!(calls.get(), 2);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn key_includes_query_and_diacritics_flag() {
+        let mut cache = RankingCache::new(8);
+        assert_eq!(
+            cache.get_or_compute("caf\u{e9}", "cafe", false),
+            Ranking::CaseSensitiveEqual
+        );
+        assert_eq!(
+            cache.get_or_compute("caf\u{e9}", "cafe", true),
+            Ranking::NoMatch
+        );
+        assert_eq!(
+            cache.get_or_compute("caf\u{e9}", "ca", false),
+            Ranking::StartsWith
+        );
+        assert_eq!(cache.len(), 3);
+        // The query is normalized, so an accented query shares the entry.
+        cache.get_or_compute("caf\u{e9}", "caf\u{e9}", false);
+        assert_eq!(cache.len(), 3);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.capacity(), 8);
+    }
+
+    #[test]
+    fn cached_results_match_uncached_for_every_tier() {
+        let cases = [
+            ("Green", "Green", Ranking::CaseSensitiveEqual),
+            ("Green", "green", Ranking::Equal),
+            ("Greenland", "green", Ranking::StartsWith),
+            ("San Francisco", "fran", Ranking::WordStartsWith),
+            ("evergreen", "green", Ranking::Contains),
+            ("North-West Airlines", "nwa", Ranking::Acronym),
+            (
+                "playground",
+                "plgnd",
+                get_match_ranking("playground", "plgnd", false),
+            ),
+            ("abc", "xyz", Ranking::NoMatch),
+        ];
+        let mut cache = RankingCache::new(4);
+        for _ in 0..2 {
+            for (candidate, query, expected) in cases {
+                assert_eq!(cache.get_or_compute(candidate, query, false), expected);
+                assert_eq!(expected, get_match_ranking(candidate, query, false));
+            }
+        }
+        assert!(matches!(
+            cache.get_or_compute("playground", "plgnd", false),
+            Ranking::Matches(_)
+        ));
+    }
+
+    #[test]
+    fn shared_across_threads() {
+        let cache = Arc::new(Mutex::new(RankingCache::new(64)));
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let cache = Arc::clone(&cache);
+                std::thread::spawn(move || {
+                    for i in 0..100 {
+                        let candidate = format!("item {}", (i + t) % 32);
+                        let rank = cache
+                            .lock()
+                            .unwrap()
+                            .get_or_compute(&candidate, "item 1", false);
+                        assert_eq!(rank, get_match_ranking(&candidate, "item 1", false));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(cache.lock().unwrap().len(), 32);
+    }
+}
@@ -0,0 +1,435 @@
+//! Incremental re-ranking for type-ahead search.
+//!
+//! When a user types `"a"`, then `"ap"`, then `"app"`, every longer query is
+//! a refinement of the previous one. [`IncrementalContext`] remembers the
+//! previous query and its matches so that
+//! [`match_sorter_incremental`](crate::match_sorter_incremental) only
+//! re-ranks those matches instead of every item, whenever doing so provably
+//! yields the same result as a full search.
+
+use crate::no_keys::AsMatchStr;
+use crate::options::{MatchSorterOptions, QueryMode, RankedItem};
+use crate::ranking::Ranking;
+use crate::{Ranker, sort_ranked};
+
+/// Remembers the previous query and its matches for
+/// [`match_sorter_incremental`](crate::match_sorter_incremental).
+///
+/// A search re-ranks only the previous matches when all of the following
+/// hold, and falls back to ranking every item otherwise:
+///
+/// - the same item slice is searched again;
+/// - the new query, once prepared (lowercased, diacritics stripped, stemmed),
+///   starts with the previous prepared query;
+/// - the exclude terms and exclude threshold are unchanged;
+/// - the effective global threshold has not decreased and the previous one
+///   was at most [`Ranking::StartsWith`] (a longer query can reach `Equal`
+///   where its prefix only reaches `StartsWith`);
+/// - no key has a per-key threshold (on the key or in
+///   `key_thresholds`), a score modifier, an inversion or a
+///   [query-dependent extractor](crate::Key::from_fn_lazy), and no custom
+///   `closeness_scorer`, `item_score_modifier`, exact-acronym mode or
+///   [`QueryMode::AnyToken`] is in effect, since each of those can rank a
+///   longer query above its prefix;
+/// - no
+///   [`discard_items_slower_than`](MatchSorterOptions::discard_items_slower_than)
+///   limit is set, since an item dropped for being slow once may be kept
+///   the next time.
+///
+/// Keys and other options holding closures cannot be compared, so call
+/// [`reset`](Self::reset) after changing them.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{IncrementalContext, MatchSorterOptions, match_sorter_incremental};
+///
+/// let items = ["apple", "apricot", "grape", "banana"];
+/// let opts = MatchSorterOptions::default();
+/// let mut ctx = IncrementalContext::new();
+///
+/// let _ = match_sorter_incremental(&items, "a", &opts, &mut ctx);
+/// // Only the matches for "a" are re-ranked for "ap".
+/// let results = match_sorter_incremental(&items, "ap", &opts, &mut ctx);
+/// assert_eq!(results, vec![&"apple", &"apricot", &"grape"]);
+/// ```
+pub struct IncrementalContext<'a, T> {
+    /// The previous search, or `None` before the first search or after a
+    /// reset.
+    previous: Option<PreviousSearch<'a, T>>,
+
+    /// Whether the most recent search re-ranked only previous matches.
+    last_was_incremental: bool,
+}
+
+/// Inputs and results of the previous incremental search.
+struct PreviousSearch<'a, T> {
+    /// The searched items.
+    items: &'a [T],
+    /// The prepared (lowercased, normalized) query.
+    query: String,
+    /// The lowest rank an item needed to be included.
+    floor: Ranking,
+    /// The exclude terms and threshold the matches were filtered with.
+    exclude_terms: Vec<String>,
+    exclude_threshold: Ranking,
+    /// Original indices of the matches, in ascending order so that they
+    /// are re-ranked in input order, as a full search would.
+    matches: Vec<usize>,
+}
+
+impl<'a, T> IncrementalContext<'a, T> {
+    /// Create an empty context. The first search always ranks every item.
+    pub fn new() -> Self {
+        Self {
+            previous: None,
+            last_was_incremental: false,
+        }
+    }
+
+    /// Forget the previous search, forcing the next one to rank every item.
+    pub fn reset(&mut self) {
+        self.previous = None;
+        self.last_was_incremental = false;
+    }
+
+    /// Run a search, re-ranking only the previous matches when that is
+    /// known to give the same result as a full search.
+    pub(crate) fn search(
+        &mut self,
+        items: &'a [T],
+        value: &str,
+        options: &MatchSorterOptions<T>,
+    ) -> Vec<RankedItem<'a, T>>
+    where
+        T: AsMatchStr,
+    {
+        let mut ranker = Ranker::new(value, options);
+        let query = ranker.single_query_lower().unwrap_or_default().to_owned();
+        let floor = ranker.global_floor();
+
+        let refinable = self
+            .previous
+            .as_ref()
+            .filter(|prev| {
+                std::ptr::eq(prev.items, items)
+                    && query.starts_with(&prev.query)
+                    && floor >= prev.floor
+                    && prev.floor <= Ranking::StartsWith
+                    && prev.exclude_terms == options.exclude_terms
+                    && prev.exclude_threshold == options.exclude_threshold
+                    && refinement_is_monotonic(options)
+            })
+            .map(|prev| &prev.matches);
+
+        let mut ranked = Vec::new();
+        match refinable {
+            Some(matches) => {
+                for &index in matches {
+                    ranked.extend(ranker.rank(index, &items[index]));
+                }
+            }
+            None => {
+                for (index, item) in items.iter().enumerate() {
+                    if ranker.should_stop() {
+                        break;
+                    }
+                    ranked.extend(ranker.rank(index, item));
+                }
+            }
+        }
+        self.last_was_incremental = refinable.is_some();
+
+        let ranked = sort_ranked(ranked, options);
+        let mut matches: Vec<usize> = ranked.iter().map(|ri| ri.index).collect();
+        matches.sort_unstable();
+        self.previous = Some(PreviousSearch {
+            items,
+            query,
+            floor,
+            exclude_terms: options.exclude_terms.clone(),
+            exclude_threshold: options.exclude_threshold,
+            matches,
+        });
+        ranked
+    }
+}
+
+/// Returns whether, under `options`, every item a query matches is also
+/// matched by each prefix of that query (given a non-decreasing threshold
+/// of at most `StartsWith`).
+fn refinement_is_monotonic<T>(options: &MatchSorterOptions<T>) -> bool {
+    options.query_mode == QueryMode::Single
+        && options.closeness_scorer.is_none()
+        && !options.acronym_exact_match
+        && options.key_thresholds.is_empty()
+        && options.item_score_modifier.is_none()
+        && options.early_stop_criteria.is_none()
+        && options.discard_items_slower_than.is_none()
+        && options.keys.iter().all(|key| {
+            key.threshold.is_none()
+                && !key.has_score_modifier()
+                && !key.has_inversion()
+                && !key.depends_on_query()
+        })
+}
+
+impl<T> Default for IncrementalContext<'_, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Key, match_sorter, match_sorter_incremental};
+
+    fn words() -> Vec<String> {
+        [
+            "apple",
+            "Apple pie",
+            "apricot",
+            "grape",
+            "pineapple",
+            "snap",
+            "banana",
+            "a-p-p",
+            "application",
+            "map",
+        ]
+        .iter()
+        .map(|s| (*s).to_owned())
+        .collect()
+    }
+
+    #[test]
+    fn incremental_results_match_full_results() {
+        let items = words();
+        let opts = MatchSorterOptions::default();
+        let mut ctx = IncrementalContext::new();
+        for query in ["", "a", "ap", "app", "appl", "appli"] {
+            let incremental = match_sorter_incremental(&items, query, &opts, &mut ctx);
+            let full = match_sorter(&items, query, &MatchSorterOptions::default());
+            assert_eq!(incremental, full, "query {query:?}");
+        }
+        assert!(ctx.last_was_incremental);
+    }
+
+    #[test]
+    fn incremental_ties_keep_input_order() {
+        // "ax" ranks first for "a" by its first key; for "ab" both items
+        // tie on their second key and must come back in input order.
+        let items = vec!["ba|cab".to_owned(), "ax|cab".to_owned()];
+        let opts = || MatchSorterOptions {
+            keys: vec![
+                Key::from_fn(|s: &String| s.split('|').next().unwrap_or_default()),
+                Key::from_fn(|s: &String| s.split('|').nth(1).unwrap_or_default()),
+            ],
+            threshold: Ranking::Contains,
+            ..Default::default()
+        };
+        let mut ctx = IncrementalContext::new();
+        let _ = match_sorter_incremental(&items, "a", &opts(), &mut ctx);
+        let results = match_sorter_incremental(&items, "ab", &opts(), &mut ctx);
+        assert!(ctx.last_was_incremental);
+        assert_eq!(results, match_sorter(&items, "ab", &opts()));
+        assert_eq!(results, vec![&items[0], &items[1]]);
+    }
+
+    #[test]
+    fn incremental_respects_case_and_diacritics() {
+        let items = vec![
+            "Caf\u{00e9}".to_owned(),
+            "cafeteria".to_owned(),
+            "scaffold".to_owned(),
+        ];
+        let opts = MatchSorterOptions::default();
+        let mut ctx = IncrementalContext::new();
+        let _ = match_sorter_incremental(&items, "CA", &opts, &mut ctx);
+        let incremental = match_sorter_incremental(&items, "caf\u{00e9}", &opts, &mut ctx);
+        assert!(ctx.last_was_incremental);
+        assert_eq!(
+            incremental,
+            match_sorter(&items, "caf\u{00e9}", &MatchSorterOptions::default())
+        );
+    }
+
+    #[test]
+    fn threshold_decrease_bypasses_shortcut() {
+        let items = words();
+        let strict = MatchSorterOptions {
+            threshold: Ranking::Contains,
+            ..Default::default()
+        };
+        let loose = MatchSorterOptions::default();
+        let mut ctx = IncrementalContext::new();
+        let _ = match_sorter_incremental(&items, "ap", &strict, &mut ctx);
+        let results = match_sorter_incremental(&items, "app", &loose, &mut ctx);
+        assert!(!ctx.last_was_incremental);
+        assert_eq!(
+            results,
+            match_sorter(&items, "app", &MatchSorterOptions::default())
+        );
+    }
+
+    #[test]
+    fn threshold_above_starts_with_bypasses_shortcut() {
+        let items = vec!["a".to_owned(), "ab".to_owned(), "abc".to_owned()];
+        let opts = MatchSorterOptions {
+            threshold: Ranking::Equal,
+            ..Default::default()
+        };
+        let mut ctx = IncrementalContext::new();
+        let _ = match_sorter_incremental(&items, "a", &opts, &mut ctx);
+        let results = match_sorter_incremental(&items, "ab", &opts, &mut ctx);
+        assert!(!ctx.last_was_incremental);
+        assert_eq!(results, vec![&"ab".to_owned()]);
+    }
+
+    #[test]
+    fn non_prefix_query_bypasses_shortcut() {
+        let items = words();
+        let opts = MatchSorterOptions::default();
+        let mut ctx = IncrementalContext::new();
+        let _ = match_sorter_incremental(&items, "app", &opts, &mut ctx);
+        let results = match_sorter_incremental(&items, "ban", &opts, &mut ctx);
+        assert!(!ctx.last_was_incremental);
+        assert_eq!(
+            results,
+            match_sorter(&items, "ban", &MatchSorterOptions::default())
+        );
+
+        // Deleting a character is not a refinement either.
+        let _ = match_sorter_incremental(&items, "ba", &opts, &mut ctx);
+        assert!(!ctx.last_was_incremental);
+    }
+
+    #[test]
+    fn key_threshold_bypasses_shortcut() {
+        let items = words();
+        let opts = MatchSorterOptions {
+            keys: vec![Key::from_fn(|s: &String| s.as_str()).threshold(Ranking::Contains)],
+            ..Default::default()
+        };
+        let mut ctx = IncrementalContext::new();
+        let _ = match_sorter_incremental(&items, "a", &opts, &mut ctx);
+        let _ = match_sorter_incremental(&items, "ap", &opts, &mut ctx);
+        assert!(!ctx.last_was_incremental);
+    }
+
+    #[test]
+    fn discard_items_slower_than_bypasses_shortcut() {
+        let items = words();
+        let opts = MatchSorterOptions {
+            discard_items_slower_than: Some(std::time::Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let mut ctx = IncrementalContext::new();
+        let _ = match_sorter_incremental(&items, "a", &opts, &mut ctx);
+        let _ = match_sorter_incremental(&items, "ap", &opts, &mut ctx);
+        assert!(!ctx.last_was_incremental);
+    }
+
+    #[test]
+    fn key_thresholds_map_bypasses_shortcut() {
+        let items = words();
+        let opts = MatchSorterOptions {
+            keys: vec![Key::from_fn(|s: &String| s.as_str())],
+            ..Default::default()
+        }
+        .key_threshold(0, Ranking::Contains);
+        let mut ctx = IncrementalContext::new();
+        let _ = match_sorter_incremental(&items, "a", &opts, &mut ctx);
+        let _ = match_sorter_incremental(&items, "ap", &opts, &mut ctx);
+        assert!(!ctx.last_was_incremental);
+    }
+
+    #[test]
+    fn score_modifiers_bypass_shortcut() {
+        let items = words();
+        let keyed = MatchSorterOptions {
+            keys: vec![Key::from_fn(|s: &String| s.as_str()).with_score_modifier(|_, rank| rank)],
+            ..Default::default()
+        };
+        let global = MatchSorterOptions {
+            item_score_modifier: Some(Box::new(|_, rank| rank)),
+            ..Default::default()
+        };
+        for opts in [keyed, global] {
+            let mut ctx = IncrementalContext::new();
+            let _ = match_sorter_incremental(&items, "a", &opts, &mut ctx);
+            let _ = match_sorter_incremental(&items, "ap", &opts, &mut ctx);
+            assert!(!ctx.last_was_incremental);
+        }
+    }
+
+    #[test]
+    fn inverted_key_bypasses_shortcut() {
+        let items = words();
+        let opts = MatchSorterOptions {
+            keys: vec![Key::from_fn(|s: &String| s.as_str()).invert()],
+            ..Default::default()
+        };
+        let mut ctx = IncrementalContext::new();
+        let _ = match_sorter_incremental(&items, "a", &opts, &mut ctx);
+        let results = match_sorter_incremental(&items, "ap", &opts, &mut ctx);
+        assert!(!ctx.last_was_incremental);
+        // "banana" lacks "ap" but contained the previous query "a".
+        assert!(results.contains(&&"banana".to_owned()));
+    }
+
+    #[test]
+    fn query_dependent_key_bypasses_shortcut() {
+        let items = words();
+        let opts = MatchSorterOptions {
+            // Longer queries are appended to the value, so they match every
+            // item.
+            keys: vec![Key::from_fn_lazy(|s: &String, query: &str| {
+                if query.len() < 2 {
+                    vec![s.clone()]
+                } else {
+                    vec![format!("{s} {query}")]
+                }
+            })],
+            ..Default::default()
+        };
+        let mut ctx = IncrementalContext::new();
+        let _ = match_sorter_incremental(&items, "p", &opts, &mut ctx);
+        let results = match_sorter_incremental(&items, "pa", &opts, &mut ctx);
+        assert!(!ctx.last_was_incremental);
+        // "banana" lacks "p" but matches "pa" through the appended query.
+        assert!(results.contains(&&"banana".to_owned()));
+        assert_eq!(results, match_sorter(&items, "pa", &opts));
+    }
+
+    #[test]
+    fn changed_exclude_terms_bypass_shortcut() {
+        let items = words();
+        let excluding = MatchSorterOptions {
+            exclude_terms: vec!["pie".to_owned()],
+            ..Default::default()
+        };
+        let plain = MatchSorterOptions::default();
+        let mut ctx = IncrementalContext::new();
+        let _ = match_sorter_incremental(&items, "a", &excluding, &mut ctx);
+        let results = match_sorter_incremental(&items, "ap", &plain, &mut ctx);
+        assert!(!ctx.last_was_incremental);
+        assert!(results.contains(&&"Apple pie".to_owned()));
+    }
+
+    #[test]
+    fn other_items_or_reset_bypass_shortcut() {
+        let items = words();
+        let other = words();
+        let opts = MatchSorterOptions::default();
+        let mut ctx = IncrementalContext::new();
+        let _ = match_sorter_incremental(&items, "a", &opts, &mut ctx);
+        let _ = match_sorter_incremental(&other, "ap", &opts, &mut ctx);
+        assert!(!ctx.last_was_incremental);
+
+        ctx.reset();
+        let _ = match_sorter_incremental(&other, "app", &opts, &mut ctx);
+        assert!(!ctx.last_was_incremental);
+    }
+}
@@ -5,11 +5,30 @@
 //! optional per-key ranking attributes (`threshold`, `min_ranking`,
 //! `max_ranking`) that override global defaults during match evaluation.
 //!
+//! With the `async` feature, `AsyncKey` produces values from a future
+//! for `match_sorter_async`.
+//!
 //! [`RankingInfo`] captures the result of evaluating a single item against
 //! a query across all of its keys.
 
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Write};
+use std::ops::Range;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+use std::rc::Rc;
+#[cfg(feature = "counters")]
+use std::sync::Arc;
+#[cfg(feature = "counters")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use crate::options::MatchSorterOptions;
-use crate::ranking::{PreparedQuery, Ranking, get_match_ranking, get_match_ranking_prepared};
+use crate::ranking::{
+    DiacriticsMode, NormalizationForm, PreparedQuery, Ranking, RankingConfig,
+    get_match_ranking_prepared, lowercase_into, prepare_value_for_comparison,
+};
 
 /// Extract all string values from an item for a given key.
 ///
@@ -40,10 +59,58 @@ pub fn get_item_values<T>(item: &T, key: &Key<T>) -> Vec<String> {
     key.extract(item)
 }
 
+/// Extract every value of `item` under `keys`, paired with its `key_index`.
+///
+/// Values come in the order [`get_highest_ranking`] considers them, and
+/// each index is the [`RankingInfo::key_index`] a match on that value
+/// would report. Keys that [skip](Key::skip_if) the item contribute
+/// nothing. A [`Key::select`] key contributes the values of all its
+/// sub-keys under a single index, since ranking keeps only the best of
+/// them.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::key::{Key, extract_all_values};
+///
+/// struct User { name: String, tags: Vec<String> }
+///
+/// let user = User { name: "Alice".to_owned(), tags: vec!["admin".to_owned(), "ops".to_owned()] };
+/// let keys = vec![
+///     Key::<User>::from_fn(|u| u.name.as_str()),
+///     Key::new(|u: &User| u.tags.clone()),
+/// ];
+/// assert_eq!(
+///     extract_all_values(&user, &keys),
+///     vec![(0, "Alice".to_owned()), (1, "admin".to_owned()), (2, "ops".to_owned())]
+/// );
+/// ```
+pub fn extract_all_values<T>(item: &T, keys: &[Key<T>]) -> Vec<(usize, String)> {
+    let mut all = Vec::new();
+    let mut key_index = 0;
+    for key in keys {
+        if key.skips(item) {
+            continue;
+        }
+        let values = key.extract_unchecked(item, "");
+        if let KeySource::Select(_) = key.source {
+            all.extend(values.into_iter().map(|value| (key_index, value)));
+            key_index += 1;
+        } else {
+            for value in values {
+                all.push((key_index, value));
+                key_index += 1;
+            }
+        }
+    }
+    all
+}
+
 /// Evaluate all keys for a single item and return the best ranking.
 ///
 /// Flattens all keys' extracted values into a single indexed list preserving
-/// key order. Each value is scored via [`get_match_ranking`], then clamped by
+/// key order. Each value is scored via
+/// [`get_match_ranking`](crate::ranking::get_match_ranking), then clamped by
 /// the owning key's `min_ranking` / `max_ranking` attributes. The best-ranked
 /// value is returned. When two values produce equal rank, the one with the
 /// lower `key_index` (earlier in the flattened list) wins.
@@ -55,6 +122,11 @@ pub fn get_item_values<T>(item: &T, key: &Key<T>) -> Vec<String> {
 /// - If a value's rank is below the key's `min_ranking` **and** the rank is
 ///   not [`Ranking::NoMatch`], it is promoted **up** to `min_ranking`.
 /// - [`Ranking::NoMatch`] is **never** promoted by `min_ranking`.
+/// - After clamping, [`Key::prefix_boost`] and [`Key::word_prefix_boost`]
+///   promote a value whose text actually starts with the query (or has a
+///   word that does), even past `max_ranking`.
+/// - A [`Key::select`] key first picks its best sub-key value, then applies
+///   its own clamps and boosts to that single value.
 ///
 /// # Arguments
 ///
@@ -87,126 +159,317 @@ pub fn get_highest_ranking<T>(
     keys: &[Key<T>],
     query: &str,
     options: &MatchSorterOptions<T>,
+) -> RankingInfo {
+    // Thin wrapper: prepare the query once for all of this item's values.
+    let pq = PreparedQuery::with_case_folding(
+        query,
+        options.diacritics(),
+        options.normalization_form,
+        options.unicode_case_folding,
+    );
+    let finder = if pq.lower.is_empty() {
+        None
+    } else {
+        Some(memchr::memmem::Finder::new(pq.lower.as_bytes()))
+    };
+    let config = RankingConfig {
+        match_floor: Some(options.soft_floor(options.global_threshold(query))),
+        ..options.ranking_config()
+    };
+    let mut buf = String::new();
+    get_highest_ranking_prepared(
+        item,
+        keys,
+        &pq,
+        &config,
+        options.item_score_modifier.as_deref(),
+        &mut KeyScratch {
+            candidate_buf: &mut buf,
+            extracted: None,
+        },
+        finder.as_ref(),
+    )
+}
+
+/// Buffers borrowed by [`get_highest_ranking_prepared`].
+pub(crate) struct KeyScratch<'b> {
+    /// Reusable buffer for preparing candidate strings.
+    pub(crate) candidate_buf: &'b mut String,
+    /// Receives every value the keys extract, when set, so that callers
+    /// can inspect them without running the extractors again.
+    pub(crate) extracted: Option<&'b mut Vec<String>>,
+}
+
+/// Evaluate all keys for a single item using pre-prepared query data.
+///
+/// Same logic as [`get_highest_ranking`] but avoids redundant query preparation
+/// by accepting a [`PreparedQuery`], the ranking config derived from the
+/// options, reusable [`KeyScratch`] buffers, and an optional SIMD
+/// substring finder. `item_modifier` is the options'
+/// [`item_score_modifier`](MatchSorterOptions::item_score_modifier), used
+/// for keys without their own score modifier.
+pub(crate) fn get_highest_ranking_prepared<T>(
+    item: &T,
+    keys: &[Key<T>],
+    pq: &PreparedQuery,
+    config: &RankingConfig<'_>,
+    item_modifier: Option<ScoreModifierRef<'_, T>>,
+    scratch: &mut KeyScratch<'_>,
+    finder: Option<&memchr::memmem::Finder<'_>>,
 ) -> RankingInfo {
     let mut best = RankingInfo {
         rank: Ranking::NoMatch,
         ranked_value: String::new(),
         key_index: 0,
         key_threshold: None,
+        matched_key_alias: None,
+        tie_priority: None,
     };
+    // The key that produced `best`; its alias is resolved once at the end.
+    let mut best_key: Option<&Key<T>> = None;
 
     // Flatten all keys' values into a single indexed sequence. The
     // `key_index` counter runs across all values from all keys, preserving
     // the order in which keys (and their values) appear.
     let mut key_index: usize = 0;
+    // Set once a required key yields no values, or a require-match key
+    // matches below its threshold; the item then cannot match.
+    let mut required_missing = false;
 
-    for key in keys {
-        let values = key.extract(item);
-        let threshold = key.threshold;
-        let min = key.min_ranking_value();
-        let max = key.max_ranking_value();
-
-        for value in &values {
-            let mut rank = get_match_ranking(value, query, options.keep_diacritics);
-
-            // Clamp down: if the rank exceeds the key's max_ranking, cap it.
-            if rank > *max {
-                rank = *max;
+    for (i, key) in keys.iter().enumerate() {
+        if key.skips(item) {
+            if key.required || key.require_match {
+                required_missing = true;
+                break;
             }
+            continue;
+        }
+        let threshold = config
+            .key_thresholds
+            .and_then(|thresholds| thresholds.get(&i).copied())
+            .or(key.threshold);
+        // Whether a final rank satisfies a require-match key.
+        let passes = |rank: Ranking| {
+            rank != Ranking::NoMatch && threshold.or(config.match_floor).is_none_or(|f| rank >= f)
+        };
 
-            // Promote up: if the rank is below the key's min_ranking AND the
-            // rank is NOT NoMatch, boost it to min_ranking. NoMatch is never
-            // promoted -- an item that doesn't match stays unmatched.
-            if rank < *min && rank != Ranking::NoMatch {
-                rank = *min;
+        // A select key contributes only the best value among its sub-keys,
+        // which takes a single slot in the flattened order.
+        if let KeySource::Select(sub_keys) = &key.source {
+            // Sub-key indices do not refer to the options' keys.
+            let sub_config = RankingConfig {
+                key_thresholds: None,
+                ..*config
+            };
+            key.count_call();
+            // The select key's own modifier replaces the global one.
+            let sub_modifier = if key.score_modifier.is_some() {
+                None
+            } else {
+                item_modifier
+            };
+            let selected = get_highest_ranking_prepared(
+                item,
+                sub_keys,
+                pq,
+                &sub_config,
+                sub_modifier,
+                &mut KeyScratch {
+                    candidate_buf: &mut *scratch.candidate_buf,
+                    extracted: scratch.extracted.as_deref_mut(),
+                },
+                finder,
+            );
+            // Only an unmatched selection can come from sub-keys without
+            // values.
+            if key.required && selected.rank == Ranking::NoMatch && !key.has_values(item, &pq.lower)
+            {
+                required_missing = true;
+                break;
             }
-
-            // Update best: strictly better rank wins, or equal rank with a
-            // lower key_index wins (but since we iterate in order, the first
-            // occurrence at a given rank level is already the lowest index,
-            // so we only replace on strictly-greater).
-            if rank > best.rank {
+            let rank = key.adjust_rank(selected.rank, &selected.ranked_value, pq, config);
+            let rank = key.apply_weight(key.modify_score(item, rank, None));
+            if key.require_match && !passes(rank) {
+                required_missing = true;
+                break;
+            }
+            if outranks(rank, key, best.rank, best_key) {
                 best = RankingInfo {
                     rank,
-                    ranked_value: value.clone(),
+                    ranked_value: selected.ranked_value,
                     key_index,
                     key_threshold: threshold,
+                    matched_key_alias: selected.matched_key_alias,
+                    tie_priority: selected.tie_priority,
                 };
+                best_key = Some(key);
             }
-
             key_index += 1;
+            continue;
         }
-    }
-
-    best
-}
 
-/// Evaluate all keys for a single item using pre-prepared query data.
-///
-/// Same logic as [`get_highest_ranking`] but avoids redundant query preparation
-/// by accepting a [`PreparedQuery`], a reusable candidate buffer, and an
-/// optional SIMD substring finder.
-pub(crate) fn get_highest_ranking_prepared<T>(
-    item: &T,
-    keys: &[Key<T>],
-    pq: &PreparedQuery,
-    options: &MatchSorterOptions<T>,
-    candidate_buf: &mut String,
-    finder: Option<&memchr::memmem::Finder<'_>>,
-) -> RankingInfo {
-    let mut best = RankingInfo {
-        rank: Ranking::NoMatch,
-        ranked_value: String::new(),
-        key_index: 0,
-        key_threshold: None,
-    };
+        let values = key.extract_for_ranking(item, pq, config.strict_empty_values);
+        if let Some(extracted) = scratch.extracted.as_deref_mut() {
+            extracted.extend(values.iter().cloned());
+        }
+        if key.required && values.is_empty() {
+            required_missing = true;
+            break;
+        }
 
-    let mut key_index: usize = 0;
+        // A language key compares its normalized values with the query
+        // normalized the same way, both lowercased with the language's case
+        // rules. Cached ranks assume default lowercasing.
+        let tag = key.item_language(item);
+        let normalizer = tag.and_then(|tag| config.language_normalizer(tag));
+        let case_rules = tag.and_then(|tag| config.case_rules(tag));
+        let key_config = RankingConfig {
+            prefilter: config.prefilter || key.prefilter,
+            // A language normalizer rewrites the prepared values.
+            pre_normalized: normalizer.is_none()
+                && config.normalization == NormalizationForm::Nfd
+                && key.pre_normalized == Some(config.diacritics),
+            language: case_rules,
+            ranking_cache: config.ranking_cache.filter(|_| case_rules.is_none()),
+            ..*config
+        };
+        let language_pq = pq.for_language(normalizer, case_rules);
+        let language_finder = language_pq
+            .as_ref()
+            .filter(|lpq| !lpq.lower.is_empty())
+            .map(|lpq| memchr::memmem::Finder::new(lpq.lower.as_bytes()));
+        let (key_pq, key_finder) = match &language_pq {
+            Some(lpq) => (lpq, language_finder.as_ref()),
+            None => (pq, finder),
+        };
 
-    for key in keys {
-        let values = key.extract(item);
-        let threshold = key.threshold;
-        let min = key.min_ranking_value();
-        let max = key.max_ranking_value();
-
-        for value in &values {
-            let mut rank = get_match_ranking_prepared(
-                value,
-                pq,
-                options.keep_diacritics,
-                candidate_buf,
-                finder,
+        let mut key_passes = false;
+        for value in values {
+            let value = match normalizer {
+                Some(normalize) => normalize(&value),
+                None => value,
+            };
+            let rank = get_match_ranking_prepared(
+                &value,
+                key_pq,
+                &key_config,
+                scratch.candidate_buf,
+                key_finder,
             );
 
-            if rank > *max {
-                rank = *max;
-            }
-
-            if rank < *min && rank != Ranking::NoMatch {
-                rank = *min;
-            }
+            // Clamp into [min_ranking, max_ranking] and apply prefix boosts,
+            // then any score modifier and the key's weight.
+            let rank = key.adjust_rank(rank, &value, key_pq, &key_config);
+            let rank = key.apply_weight(key.modify_score(item, rank, item_modifier));
+            key_passes |= passes(rank);
 
-            if rank > best.rank {
+            // Update best: strictly better rank wins, or equal rank from a
+            // higher-priority key. Since we iterate in order, the first
+            // occurrence at a given rank and priority is already the lowest
+            // index.
+            if outranks(rank, key, best.rank, best_key) {
                 best = RankingInfo {
                     rank,
-                    ranked_value: value.clone(),
+                    ranked_value: value,
                     key_index,
                     key_threshold: threshold,
+                    matched_key_alias: None,
+                    tie_priority: None,
                 };
+                best_key = Some(key);
             }
 
             key_index += 1;
         }
+        if key.require_match && !key_passes {
+            required_missing = true;
+            break;
+        }
     }
 
+    if required_missing {
+        return RankingInfo {
+            rank: Ranking::NoMatch,
+            ranked_value: String::new(),
+            key_index: 0,
+            key_threshold: None,
+            matched_key_alias: None,
+            tie_priority: None,
+        };
+    }
+    if let Some(alias) = best_key.and_then(|key| key.alias_for(item)) {
+        best.matched_key_alias = Some(alias);
+    }
+    if let Some(priority) = best_key.and_then(|key| key.tie_priority.as_ref()) {
+        best.tie_priority = Some(priority(item));
+    }
     best
 }
 
+/// Whether a match of `rank` on `key` replaces the best match so far,
+/// which has `best_rank` and came from `best_key`.
+fn outranks<T>(rank: Ranking, key: &Key<T>, best_rank: Ranking, best_key: Option<&Key<T>>) -> bool {
+    rank > best_rank
+        || (rank == best_rank
+            && rank != Ranking::NoMatch
+            && best_key.is_some_and(|best| key.priority > best.priority))
+}
+
+/// The check behind [`Key::require_non_empty`]: panic in debug builds when
+/// `values` is empty or holds an empty string, and drop empty strings in
+/// release builds.
+fn drop_empty_values(mut values: Vec<String>) -> Vec<String> {
+    debug_assert!(
+        !values.is_empty() && values.iter().all(|value| !value.is_empty()),
+        "key extracted empty values: {values:?}"
+    );
+    values.retain(|value| !value.is_empty());
+    values
+}
+
 /// Type alias for the boxed extractor closure stored inside a [`Key`].
 ///
-/// Given a reference to an item of type `T`, the extractor returns a
-/// `Vec<String>` of values to rank against the query.
-type Extractor<T> = Box<dyn Fn(&T) -> Vec<String>>;
+/// Given a reference to an item of type `T` and the query (see
+/// [`Key::from_fn_lazy`]), the extractor returns a `Vec<String>` of values
+/// to rank against the query.
+type Extractor<T> = Box<dyn Fn(&T, &str) -> Vec<String>>;
+
+/// Boxed item predicate used by [`Key::skip_if`].
+type SkipPredicate<T> = Box<dyn Fn(&T) -> bool>;
+
+/// Boxed language-tag lookup used by [`Key::multi_language`].
+type LanguageFn<T> = Box<dyn Fn(&T) -> &str>;
+
+/// Boxed rank adjustment used by [`Key::with_score_modifier`].
+type ScoreModifier<T> = Box<dyn Fn(&T, Ranking) -> Ranking>;
+
+/// Boxed per-item sort priority used by [`Key::tie_priority`].
+type TiePriorityFn<T> = Box<dyn Fn(&T) -> i64>;
+
+/// Borrowed form of a score modifier, as passed down the ranking loop.
+type ScoreModifierRef<'m, T> = &'m dyn Fn(&T, Ranking) -> Ranking;
+
+/// A key's display name; see [`Key::alias`].
+enum KeyAlias<T> {
+    /// The same name for every item.
+    Fixed(String),
+    /// A name computed from each item.
+    PerItem(Box<dyn Fn(&T) -> String>),
+}
+
+/// Where a [`Key`] gets its values from.
+enum KeySource<T> {
+    /// A closure that extracts the values directly.
+    Extract(Extractor<T>),
+    /// Sub-keys whose single best-ranked value is chosen per query; see
+    /// [`Key::select`].
+    Select(Vec<Key<T>>),
+}
+
+/// A boxed closure that extracts an optional single string value from an item.
+///
+/// Used by [`Key::first_of`], which tries a list of these in order.
+pub type OptionalExtractor<T> = Box<dyn Fn(&T) -> Option<String>>;
 
 /// A single key specification for extracting matchable string values from an item.
 ///
@@ -238,9 +501,10 @@ type Extractor<T> = Box<dyn Fn(&T) -> Vec<String>>;
 /// let key = Key::<User>::from_fn(|u| u.name.as_str());
 /// ```
 pub struct Key<T> {
-    /// Closure that extracts one or more string values from an item.
-    /// Returns a `Vec<String>` to support multi-valued fields (e.g., tags).
-    extractor: Extractor<T>,
+    /// Closure that extracts one or more string values from an item, or the
+    /// sub-keys of a [`Key::select`] key. Extraction returns a `Vec<String>`
+    /// to support multi-valued fields (e.g., tags).
+    source: KeySource<T>,
 
     /// Per-key threshold override. When `Some`, this key's matches must meet
     /// this ranking to be considered. When `None`, the global threshold
@@ -258,6 +522,124 @@ pub struct Key<T> {
     ///
     /// Defaults to [`Ranking::NoMatch`] (no boosting).
     pub(crate) min_ranking: Ranking,
+
+    /// Factor applied to this key's ranks; see [`Key::weight`].
+    weight: f64,
+
+    /// Tiebreaker between keys with equal ranks; see [`Key::priority`].
+    priority: usize,
+
+    /// Tiebreaker between items matched with equal ranks on this key; see
+    /// [`Key::tie_priority`].
+    tie_priority: Option<TiePriorityFn<T>>,
+
+    /// Promote a match to `StartsWith` when the value starts with the query.
+    pub(crate) prefix_boost: bool,
+
+    /// Promote a match to `WordStartsWith` when a word in the value starts
+    /// with the query.
+    pub(crate) word_prefix_boost: bool,
+
+    /// Reject values lacking the query's first character before ranking
+    /// them; see [`Key::with_prefilter`].
+    prefilter: bool,
+
+    /// The diacritics mode the extracted values were already prepared with;
+    /// see [`Key::extract_normalized`].
+    pre_normalized: Option<DiacriticsMode>,
+
+    /// Exclude items for which this key yields no values; see
+    /// [`Key::required`].
+    required: bool,
+
+    /// Rank matching values `NoMatch` and the rest `Contains`; see
+    /// [`Key::invert`].
+    inverted: bool,
+
+    /// The extractor looks at the query; see [`Key::from_fn_lazy`].
+    query_dependent: bool,
+
+    /// Exclude items for which no value of this key reaches its threshold;
+    /// see [`Key::require_match`].
+    require_match: bool,
+
+    /// Items for which this key yields no values; see [`Key::skip_if`].
+    skip: Option<SkipPredicate<T>>,
+
+    /// Each item's BCP-47 language tag; see [`Key::multi_language`].
+    language: Option<LanguageFn<T>>,
+
+    /// The BCP-47 language tag of every value; see [`Key::language`].
+    language_tag: Option<String>,
+
+    /// Display name reported for matches on this key; see [`Key::alias`].
+    alias: Option<KeyAlias<T>>,
+
+    /// Adjusts each match's rank after ranking; see
+    /// [`Key::with_score_modifier`].
+    score_modifier: Option<ScoreModifier<T>>,
+
+    /// Counts evaluations of this key; see [`Key::with_call_counter`].
+    #[cfg(feature = "counters")]
+    call_counter: Option<CallCounter>,
+}
+
+impl<T> fmt::Debug for Key<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let placeholder =
+            |set: bool| -> &'static dyn fmt::Debug { if set { &"Some(<fn>)" } else { &"None" } };
+        let mut s = f.debug_struct("Key");
+        match &self.source {
+            KeySource::Extract(_) => s.field("source", &"<extractor>"),
+            KeySource::Select(keys) => s.field("select", keys),
+        };
+        s.field("threshold", &self.threshold)
+            .field("min_ranking", &self.min_ranking)
+            .field("max_ranking", &self.max_ranking)
+            .field("weight", &self.weight)
+            .field("priority", &self.priority)
+            .field("tie_priority", placeholder(self.tie_priority.is_some()))
+            .field("prefix_boost", &self.prefix_boost)
+            .field("word_prefix_boost", &self.word_prefix_boost)
+            .field("prefilter", &self.prefilter)
+            .field("pre_normalized", &self.pre_normalized)
+            .field("required", &self.required)
+            .field("inverted", &self.inverted)
+            .field("query_dependent", &self.query_dependent)
+            .field("require_match", &self.require_match)
+            .field("skip", placeholder(self.skip.is_some()))
+            .field("language", placeholder(self.language.is_some()))
+            .field("language_tag", &self.language_tag)
+            .field("alias", placeholder(self.alias.is_some()))
+            .field("score_modifier", placeholder(self.score_modifier.is_some()));
+        #[cfg(feature = "counters")]
+        if let Some(counter) = &self.call_counter {
+            s.field("calls", &counter.get());
+        }
+        s.finish()
+    }
+}
+
+/// Shared handle to the call count of a key built with
+/// [`Key::with_call_counter`].
+///
+/// A debugging aid, only available with the `counters` feature. Clones
+/// share the same count.
+#[cfg(feature = "counters")]
+#[derive(Debug, Clone, Default)]
+pub struct CallCounter(Arc<AtomicU64>);
+
+#[cfg(feature = "counters")]
+impl CallCounter {
+    /// The number of times the key has been evaluated so far.
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Set the count back to zero.
+    pub fn reset(&self) {
+        self.0.store(0, Ordering::Relaxed);
+    }
 }
 
 impl<T> Key<T> {
@@ -284,10 +666,28 @@ impl<T> Key<T> {
         F: Fn(&T) -> Vec<String> + 'static,
     {
         Self {
-            extractor: Box::new(extractor),
+            source: KeySource::Extract(Box::new(move |item, _| extractor(item))),
             threshold: None,
             min_ranking: Ranking::NoMatch,
             max_ranking: Ranking::CaseSensitiveEqual,
+            weight: 1.0,
+            priority: 0,
+            tie_priority: None,
+            prefix_boost: false,
+            word_prefix_boost: false,
+            prefilter: false,
+            pre_normalized: None,
+            required: false,
+            inverted: false,
+            query_dependent: false,
+            require_match: false,
+            skip: None,
+            language: None,
+            language_tag: None,
+            alias: None,
+            score_modifier: None,
+            #[cfg(feature = "counters")]
+            call_counter: None,
         }
     }
 
@@ -319,10 +719,28 @@ impl<T> Key<T> {
         F: Fn(&T) -> &str + 'static,
     {
         Self {
-            extractor: Box::new(move |item| vec![f(item).to_owned()]),
+            source: KeySource::Extract(Box::new(move |item, _| vec![f(item).to_owned()])),
             threshold: None,
             min_ranking: Ranking::NoMatch,
             max_ranking: Ranking::CaseSensitiveEqual,
+            weight: 1.0,
+            priority: 0,
+            tie_priority: None,
+            prefix_boost: false,
+            word_prefix_boost: false,
+            prefilter: false,
+            pre_normalized: None,
+            required: false,
+            inverted: false,
+            query_dependent: false,
+            require_match: false,
+            skip: None,
+            language: None,
+            language_tag: None,
+            alias: None,
+            score_modifier: None,
+            #[cfg(feature = "counters")]
+            call_counter: None,
         }
     }
 
@@ -353,138 +771,2761 @@ impl<T> Key<T> {
         F: Fn(&T) -> Vec<&str> + 'static,
     {
         Self {
-            extractor: Box::new(move |item| f(item).into_iter().map(|s| s.to_owned()).collect()),
+            source: KeySource::Extract(Box::new(move |item, _| {
+                f(item).into_iter().map(|s| s.to_owned()).collect()
+            })),
             threshold: None,
             min_ranking: Ranking::NoMatch,
             max_ranking: Ranking::CaseSensitiveEqual,
+            weight: 1.0,
+            priority: 0,
+            tie_priority: None,
+            prefix_boost: false,
+            word_prefix_boost: false,
+            prefilter: false,
+            pre_normalized: None,
+            required: false,
+            inverted: false,
+            query_dependent: false,
+            require_match: false,
+            skip: None,
+            language: None,
+            language_tag: None,
+            alias: None,
+            score_modifier: None,
+            #[cfg(feature = "counters")]
+            call_counter: None,
         }
     }
 
-    /// Set a per-key threshold override.
+    /// Create a key whose extractor also receives the query.
     ///
-    /// When set, matches produced by this key must meet or exceed the given
-    /// ranking to be considered. Matches below the threshold are treated as
-    /// `NoMatch` for this key.
+    /// During a search, `f` is called with the item and the query as it is
+    /// compared: prepared (diacritics handled as configured) and
+    /// lowercased, and one token at a time when the
+    /// [`query_mode`](MatchSorterOptions::query_mode) splits the query. This
+    /// lets a key pick what to rank based on the query, such as the most
+    /// relevant sentence of a long document. When values are extracted
+    /// without ranking them against the query, as in [`Key::extract`] or
+    /// when checking [`exclude_terms`](MatchSorterOptions::exclude_terms),
+    /// `f` receives an empty query.
+    ///
+    /// The result is an ordinary key: every other builder method applies,
+    /// and it can be mixed freely with other keys. Since a longer query may
+    /// extract different values, an
+    /// [`IncrementalContext`](crate::IncrementalContext) ranks every item
+    /// again rather than only the previous matches.
     ///
     /// # Arguments
     ///
-    /// * `ranking` - The minimum ranking a match must achieve on this key.
+    /// * `f` - A closure that, given a reference to an item and the query,
+    ///   returns the values to rank.
     ///
     /// # Examples
     ///
     /// ```
-    /// use matchsorter::key::Key;
-    /// use matchsorter::Ranking;
+    /// use matchsorter::{Key, MatchSorterOptions, match_sorter};
     ///
-    /// let key = Key::new(|s: &String| vec![s.clone()])
-    ///     .threshold(Ranking::StartsWith);
+    /// struct Doc { body: &'static str }
+    /// impl matchsorter::AsMatchStr for Doc {
+    ///     fn as_match_str(&self) -> &str { self.body }
+    /// }
+    ///
+    /// // Rank only the sentence that mentions the query.
+    /// let key = Key::from_fn_lazy(|d: &Doc, query: &str| {
+    ///     d.body
+    ///         .split(". ")
+    ///         .find(|sentence| sentence.to_lowercase().contains(query))
+    ///         .map(str::to_owned)
+    ///         .into_iter()
+    ///         .collect()
+    /// });
+    /// let docs = [Doc { body: "Intro text. Rust is fast. The end" }];
+    /// let opts = MatchSorterOptions { keys: vec![key], ..Default::default() };
+    /// let info = matchsorter::get_highest_ranking(&docs[0], &opts.keys, "rust", &opts);
+    /// assert_eq!(info.ranked_value, "Rust is fast");
+    /// assert_eq!(match_sorter(&docs, "rust", &opts).len(), 1);
     /// ```
-    #[must_use]
-    pub fn threshold(mut self, ranking: Ranking) -> Self {
-        self.threshold = Some(ranking);
-        self
+    pub fn from_fn_lazy<F>(f: F) -> Self
+    where
+        F: Fn(&T, &str) -> Vec<String> + 'static,
+    {
+        Self {
+            source: KeySource::Extract(Box::new(f)),
+            threshold: None,
+            min_ranking: Ranking::NoMatch,
+            max_ranking: Ranking::CaseSensitiveEqual,
+            weight: 1.0,
+            priority: 0,
+            tie_priority: None,
+            prefix_boost: false,
+            word_prefix_boost: false,
+            prefilter: false,
+            pre_normalized: None,
+            required: false,
+            inverted: false,
+            query_dependent: true,
+            require_match: false,
+            skip: None,
+            language: None,
+            language_tag: None,
+            alias: None,
+            score_modifier: None,
+            #[cfg(feature = "counters")]
+            call_counter: None,
+        }
     }
 
-    /// Set the maximum ranking this key can contribute.
+    /// Create a key from a closure that borrows a [`Display`] value.
     ///
-    /// The ranking produced by this key is clamped down to at most this
-    /// value. For example, setting `max_ranking` to [`Ranking::Contains`]
-    /// means this key can never produce `StartsWith`, `Equal`, or
-    /// `CaseSensitiveEqual`.
-    ///
-    /// Defaults to [`Ranking::CaseSensitiveEqual`] (no clamping).
+    /// The value is formatted with `{}` (its `Display` form, not `Debug`),
+    /// so numeric fields such as IDs, versions or prices can be matched
+    /// without storing a string copy on the item.
     ///
     /// # Arguments
     ///
-    /// * `ranking` - The ceiling for rankings produced by this key.
+    /// * `f` - A closure that, given a reference to an item, returns a
+    ///   borrowed value to format.
     ///
     /// # Examples
     ///
     /// ```
     /// use matchsorter::key::Key;
-    /// use matchsorter::Ranking;
     ///
-    /// let key = Key::new(|s: &String| vec![s.clone()])
-    ///     .max_ranking(Ranking::Contains);
+    /// struct Order { id: u64 }
+    ///
+    /// let key = Key::<Order>::from_display(|o| &o.id);
+    /// assert_eq!(key.extract(&Order { id: 1042 }), vec!["1042"]);
     /// ```
-    #[must_use]
-    pub fn max_ranking(mut self, ranking: Ranking) -> Self {
-        self.max_ranking = ranking;
-        self
+    pub fn from_display<V, F>(f: F) -> Self
+    where
+        V: Display + ?Sized,
+        F: Fn(&T) -> &V + 'static,
+    {
+        Self::new(move |item| vec![f(item).to_string()])
     }
 
-    /// Set the minimum ranking this key can contribute.
-    ///
-    /// Non-`NoMatch` results are promoted up to at least this ranking.
-    /// A `NoMatch` result is never promoted -- an item that does not match
-    /// at all stays `NoMatch` regardless of this setting.
-    ///
-    /// Defaults to [`Ranking::NoMatch`] (no boosting).
-    ///
-    /// # Arguments
+    /// Like [`Key::from_display`], but formats through a reused
+    /// thread-local buffer.
     ///
-    /// * `ranking` - The floor for non-`NoMatch` rankings produced by this key.
+    /// `format!` grows its output from an empty `String`, which can take
+    /// several reallocations for longer values. This variant formats into
+    /// a buffer kept per thread and copies the result out with a single
+    /// exact-size allocation. Prefer it for keys evaluated over large item
+    /// sets.
     ///
     /// # Examples
     ///
     /// ```
     /// use matchsorter::key::Key;
-    /// use matchsorter::Ranking;
     ///
-    /// let key = Key::new(|s: &String| vec![s.clone()])
-    ///     .min_ranking(Ranking::Contains);
+    /// struct Product { price: f64 }
+    ///
+    /// let key = Key::<Product>::from_display_cached(|p| &p.price);
+    /// assert_eq!(key.extract(&Product { price: 9.5 }), vec!["9.5"]);
     /// ```
-    #[must_use]
-    pub fn min_ranking(mut self, ranking: Ranking) -> Self {
-        self.min_ranking = ranking;
-        self
+    pub fn from_display_cached<V, F>(f: F) -> Self
+    where
+        V: Display + ?Sized,
+        F: Fn(&T) -> &V + 'static,
+    {
+        Self::new(move |item| vec![format_with_buffer(f(item))])
     }
 
-    /// Extract string values from an item using this key's extractor closure.
+    /// Create a key from a closure that borrows a [`ToString`] value.
     ///
-    /// # Arguments
+    /// Equivalent to [`Key::from_display`] for `Display` types (which get
+    /// `ToString` for free), but also accepts types with a hand-written
+    /// `ToString` implementation.
     ///
-    /// * `item` - A reference to the item to extract values from.
+    /// # Examples
     ///
-    /// # Returns
+    /// ```
+    /// use matchsorter::key::Key;
     ///
-    /// A `Vec<String>` of extracted values. An empty vector means the item
-    /// produces no match candidates for this key.
+    /// struct Release { major: u32 }
+    ///
+    /// let key = Key::<Release>::from_to_string(|r| &r.major);
+    /// assert_eq!(key.extract(&Release { major: 3 }), vec!["3"]);
+    /// ```
+    pub fn from_to_string<V, F>(f: F) -> Self
+    where
+        V: ToString + ?Sized,
+        F: Fn(&T) -> &V + 'static,
+    {
+        Self::new(move |item| vec![f(item).to_string()])
+    }
+
+    /// Create a key from a closure returning an optional value, which
+    /// yields one value for `Some` and none for `None`.
+    ///
+    /// The value is converted with [`ToString`], so numbers and other
+    /// `Display` types can be returned as they are.
     ///
     /// # Examples
     ///
     /// ```
     /// use matchsorter::key::Key;
     ///
-    /// let key = Key::new(|s: &String| vec![s.clone()]);
-    /// let values = key.extract(&"hello".to_owned());
-    /// assert_eq!(values, vec!["hello"]);
+    /// struct Ticket { assignee: Option<String>, sprint: Option<u32> }
+    ///
+    /// let assignee = Key::<Ticket>::extract_when_some(|t| t.assignee.clone());
+    /// let sprint = Key::<Ticket>::extract_when_some(|t| t.sprint);
+    /// let ticket = Ticket { assignee: None, sprint: Some(14) };
+    /// assert!(assignee.extract(&ticket).is_empty());
+    /// assert_eq!(sprint.extract(&ticket), vec!["14"]);
     /// ```
-    pub fn extract(&self, item: &T) -> Vec<String> {
-        (self.extractor)(item)
+    pub fn extract_when_some<V, F>(f: F) -> Self
+    where
+        V: ToString,
+        F: Fn(&T) -> Option<V> + 'static,
+    {
+        Self::new(move |item| f(item).map(|value| value.to_string()).into_iter().collect())
     }
 
-    /// Returns the per-key threshold override, if set.
+    /// Create a key that follows a dot-separated `path` through an item
+    /// implementing [`NestedExtractor`].
     ///
-    /// When `Some`, matches on this key must meet or exceed this ranking.
-    /// When `None`, the global threshold applies.
-    pub fn threshold_value(&self) -> Option<&Ranking> {
-        self.threshold.as_ref()
-    }
-
-    /// Returns the maximum ranking this key can contribute.
-    pub fn max_ranking_value(&self) -> &Ranking {
-        &self.max_ranking
+    /// Every segment but the last is resolved with
+    /// [`NestedExtractor::get_nested`], and the last with
+    /// [`NestedExtractor::get_field`]. A final segment of the form
+    /// `name[i]` selects element `i` of [`NestedExtractor::get_list`].
+    /// A missing intermediate value, an out-of-range index or a malformed
+    /// path yields no values. Index syntax is only supported on the last
+    /// segment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::{Key, NestedExtractor};
+    ///
+    /// struct Address { city: String }
+    /// struct User { address: Address }
+    ///
+    /// impl NestedExtractor for Address {
+    ///     fn get_field(&self, field: &str) -> Option<String> {
+    ///         (field == "city").then(|| self.city.clone())
+    ///     }
+    /// }
+    ///
+    /// impl NestedExtractor for User {
+    ///     fn get_field(&self, _field: &str) -> Option<String> {
+    ///         None
+    ///     }
+    ///
+    ///     fn get_nested(&self, field: &str) -> Option<&dyn NestedExtractor> {
+    ///         (field == "address").then_some(&self.address as &dyn NestedExtractor)
+    ///     }
+    /// }
+    ///
+    /// let key = Key::<User>::from_nested_extractor("address.city");
+    /// let user = User { address: Address { city: "Lisbon".to_owned() } };
+    /// assert_eq!(key.extract(&user), vec!["Lisbon"]);
+    /// ```
+    pub fn from_nested_extractor(path: &str) -> Self
+    where
+        T: NestedExtractor,
+    {
+        let path = DotPath::parse(path);
+        Self::new(move |item| {
+            path.as_ref()
+                .and_then(|path| path.resolve(item))
+                .into_iter()
+                .collect()
+        })
     }
 
-    /// Returns the minimum ranking this key can contribute.
-    pub fn min_ranking_value(&self) -> &Ranking {
-        &self.min_ranking
+    /// Create a key that splits a field on `delimiter` into separate values.
+    ///
+    /// Each non-empty part becomes its own match candidate, so a field like
+    /// `"admin,staff,moderator"` ranks each role independently and the best
+    /// one wins. Empty parts (from repeated or trailing delimiters) are
+    /// dropped. Parts are not trimmed; split on `' '` or use
+    /// [`Key::split_whitespace`] for space-separated fields. Combine with
+    /// [`Key::max_values`] to cap the number of parts for untrusted input.
+    ///
+    /// # Arguments
+    ///
+    /// * `delimiter` - The character separating values
+    /// * `field_fn` - Returns the borrowed field to split
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// struct User { roles: String }
+    ///
+    /// let key = Key::<User>::split_on(',', |u| u.roles.as_str());
+    /// let user = User { roles: "admin,,staff,".to_owned() };
+    /// assert_eq!(key.extract(&user), vec!["admin", "staff"]);
+    /// ```
+    pub fn split_on<F>(delimiter: char, field_fn: F) -> Self
+    where
+        F: Fn(&T) -> &str + 'static,
+    {
+        Self::new(move |item| {
+            field_fn(item)
+                .split(delimiter)
+                .filter(|part| !part.is_empty())
+                .map(str::to_owned)
+                .collect()
+        })
     }
-}
 
-/// The result of ranking a single item against a query across all keys.
-///
+    /// Create a key that splits a field on Unicode whitespace into separate
+    /// values.
+    ///
+    /// Like [`Key::split_on`], but any run of whitespace (per
+    /// [`char::is_whitespace`]) separates values, so tags stored as
+    /// `"admin  staff\tmoderator"` yield three candidates.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_fn` - Returns the borrowed field to split
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// struct User { tags: String }
+    ///
+    /// let key = Key::<User>::split_whitespace(|u| u.tags.as_str());
+    /// let user = User { tags: " admin  staff\u{3000}moderator ".to_owned() };
+    /// assert_eq!(key.extract(&user), vec!["admin", "staff", "moderator"]);
+    /// ```
+    pub fn split_whitespace<F>(field_fn: F) -> Self
+    where
+        F: Fn(&T) -> &str + 'static,
+    {
+        Self::new(move |item| {
+            field_fn(item)
+                .split_whitespace()
+                .map(str::to_owned)
+                .collect()
+        })
+    }
+
+    /// Create a key that joins a multi-value field into a single value.
+    ///
+    /// The inverse of [`Key::split_on`]: the values from `field_fn` are
+    /// ranked as one string with `separator` between them, so a query can
+    /// span several values. Joined with `" "`, tags `["fruit", "tropical"]`
+    /// match `"fruit trop"`, and `"tropic"` still ranks as a word start.
+    /// An empty list yields no value rather than an empty string.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_fn` - Returns the values to join
+    /// * `separator` - Inserted between consecutive values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// struct Product { tags: Vec<String> }
+    ///
+    /// let key = Key::<Product>::join(|p| p.tags.clone(), " ");
+    /// let product = Product { tags: vec!["fruit".to_owned(), "tropical".to_owned()] };
+    /// assert_eq!(key.extract(&product), vec!["fruit tropical"]);
+    /// ```
+    pub fn join<F>(field_fn: F, separator: &str) -> Self
+    where
+        F: Fn(&T) -> Vec<String> + 'static,
+    {
+        let separator = separator.to_owned();
+        Self::new(move |item| {
+            let values = field_fn(item);
+            if values.is_empty() {
+                Vec::new()
+            } else {
+                vec![values.join(&separator)]
+            }
+        })
+    }
+
+    /// Create a key from a prioritized list of optional extractors.
+    ///
+    /// The extractors are tried in order and the first one returning a
+    /// non-empty `Some` wins; later extractors are not called. This models
+    /// preferred-source-with-fallback fields such as display name, then
+    /// username, then email prefix. The key yields at most one value, so a
+    /// match always reports the key's own `key_index` regardless of which
+    /// extractor supplied it. If every extractor returns `None` (or an empty
+    /// string), the item produces no candidates for this key.
+    ///
+    /// # Arguments
+    ///
+    /// * `extractors` - Closures tried in declaration order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// struct User { display_name: Option<String>, username: String }
+    ///
+    /// let key = Key::<User>::first_of(vec![
+    ///     Box::new(|u: &User| u.display_name.clone()),
+    ///     Box::new(|u: &User| Some(u.username.clone())),
+    /// ]);
+    /// let user = User { display_name: None, username: "jdoe".to_owned() };
+    /// assert_eq!(key.extract(&user), vec!["jdoe"]);
+    /// ```
+    pub fn first_of(extractors: Vec<OptionalExtractor<T>>) -> Self
+    where
+        T: 'static,
+    {
+        Self::new(move |item| {
+            extractors
+                .iter()
+                .filter_map(|extractor| extractor(item))
+                .find(|value| !value.is_empty())
+                .into_iter()
+                .collect()
+        })
+    }
+
+    /// Create a key from a prioritized list of borrowing extractors.
+    ///
+    /// Like [`Key::first_of`], but each closure returns a borrowed `&str`
+    /// and an empty string means "not present", so the next closure is
+    /// tried. All closures must share one type; non-capturing closures can
+    /// be collected as `fn(&T) -> &str` pointers.
+    ///
+    /// # Arguments
+    ///
+    /// * `extractors` - Closures tried in declaration order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// struct User { nickname: String, name: String }
+    ///
+    /// let fns: Vec<fn(&User) -> &str> = vec![|u| u.nickname.as_str(), |u| u.name.as_str()];
+    /// let key = Key::first_of_fns(fns);
+    /// let user = User { nickname: String::new(), name: "Jane".to_owned() };
+    /// assert_eq!(key.extract(&user), vec!["Jane"]);
+    /// ```
+    pub fn first_of_fns<F>(extractors: Vec<F>) -> Self
+    where
+        F: Fn(&T) -> &str + 'static,
+    {
+        Self::new(move |item| {
+            extractors
+                .iter()
+                .map(|extractor| extractor(item))
+                .find(|value| !value.is_empty())
+                .map(str::to_owned)
+                .into_iter()
+                .collect()
+        })
+    }
+
+    /// Create a key that contributes only the best-ranked value among
+    /// several sub-keys.
+    ///
+    /// Unlike listing the sub-keys separately, which adds every value to the
+    /// flattened key list, a select key ranks each sub-key's values against
+    /// the current query and keeps the single best one (the earliest on
+    /// ties). Use it for fields with several representations, such as a
+    /// normalized and a display form, where only the better match matters.
+    ///
+    /// Each sub-key's own `min_ranking`, `max_ranking` and prefix boosts
+    /// apply while choosing; its `threshold` is ignored. The select key's
+    /// own ranking attributes then apply to the chosen value, and it always
+    /// occupies exactly one position in the `key_index` order, however many
+    /// values its sub-keys produce. [`Key::extract`] returns the values of
+    /// all sub-keys, and value builders such as [`Key::map`] chained on a
+    /// select key apply to each sub-key's values.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The sub-keys to choose a value from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::{Key, get_highest_ranking};
+    /// use matchsorter::{MatchSorterOptions, Ranking};
+    ///
+    /// struct Song { title: String, sort_title: String }
+    ///
+    /// let keys = vec![Key::<Song>::select(vec![
+    ///     Key::from_fn(|s: &Song| s.title.as_str()),
+    ///     Key::from_fn(|s: &Song| s.sort_title.as_str()),
+    /// ])];
+    /// let song = Song { title: "The Wall".to_owned(), sort_title: "Wall, The".to_owned() };
+    /// let opts = MatchSorterOptions::default();
+    /// let info = get_highest_ranking(&song, &keys, "wall", &opts);
+    /// assert_eq!(info.rank, Ranking::StartsWith);
+    /// assert_eq!(info.ranked_value, "Wall, The");
+    /// ```
+    pub fn select(keys: Vec<Key<T>>) -> Self {
+        Self {
+            source: KeySource::Select(keys),
+            threshold: None,
+            min_ranking: Ranking::NoMatch,
+            max_ranking: Ranking::CaseSensitiveEqual,
+            weight: 1.0,
+            priority: 0,
+            tie_priority: None,
+            prefix_boost: false,
+            word_prefix_boost: false,
+            prefilter: false,
+            pre_normalized: None,
+            required: false,
+            inverted: false,
+            query_dependent: false,
+            require_match: false,
+            skip: None,
+            language: None,
+            language_tag: None,
+            alias: None,
+            score_modifier: None,
+            #[cfg(feature = "counters")]
+            call_counter: None,
+        }
+    }
+
+    /// Combine this key with `other` into one key yielding both keys'
+    /// values: this key's first, then `other`'s.
+    ///
+    /// Use it when two fields are interchangeable for matching, such as a
+    /// preferred and a legal name, so that they share one set of ranking
+    /// attributes. The union keeps this key's `threshold`, `min_ranking`,
+    /// `max_ranking`, prefix boosts and alias; `other`'s are ignored. Each value
+    /// still takes its own position in the `key_index` order, exactly as if
+    /// the values came from one multi-valued key. Duplicate values are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The key whose values follow this key's values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    /// use matchsorter::Ranking;
+    ///
+    /// struct Person { preferred: String, legal: String }
+    ///
+    /// let key = Key::<Person>::from_fn(|p| p.preferred.as_str())
+    ///     .threshold(Ranking::Contains)
+    ///     .union(Key::from_fn(|p: &Person| p.legal.as_str()));
+    /// let person = Person { preferred: "Liz".to_owned(), legal: "Elizabeth".to_owned() };
+    /// assert_eq!(key.extract(&person), vec!["Liz", "Elizabeth"]);
+    /// assert_eq!(key.threshold_value(), Some(&Ranking::Contains));
+    /// ```
+    #[must_use]
+    pub fn union(self, other: Key<T>) -> Self
+    where
+        T: 'static,
+    {
+        Self::union_all(vec![self, other])
+    }
+
+    /// Combine several keys into one key yielding all their values in
+    /// order.
+    ///
+    /// The ranking attributes (including weight, priority and tie
+    /// priority) and alias come from the first key, as with
+    /// [`Key::union`]. An empty list yields a key that extracts nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys whose values are concatenated, in order.
+    pub fn union_all(mut keys: Vec<Key<T>>) -> Self
+    where
+        T: 'static,
+    {
+        if keys.is_empty() {
+            return Self::new(|_| Vec::new());
+        }
+        let query_dependent = keys.iter().any(Key::depends_on_query);
+        // The first key's extraction stays in the list; its ranking
+        // attributes and alias move to the union.
+        let first = keys.remove(0);
+        keys.insert(
+            0,
+            Self {
+                source: first.source,
+                skip: first.skip,
+                #[cfg(feature = "counters")]
+                call_counter: first.call_counter,
+                ..Self::new(|_| Vec::new())
+            },
+        );
+        Self {
+            source: KeySource::Extract(Box::new(move |item, query| {
+                keys.iter()
+                    .flat_map(|key| key.extract_with_query(item, query))
+                    .collect()
+            })),
+            query_dependent,
+            // The other keys' values need not be prepared.
+            pre_normalized: None,
+            skip: None,
+            #[cfg(feature = "counters")]
+            call_counter: None,
+            ..first
+        }
+    }
+
+    /// Create a key that matches a field by its spelling **or** its sound.
+    ///
+    /// The extractor returns two values: the original field value followed
+    /// by its [`soundex`](crate::phonetic::soundex) code. Because the
+    /// original comes first, exact and prefix matches on the spelling keep
+    /// their higher tiers and lower `key_index`.
+    ///
+    /// The query is **not** encoded. To find sound-alike names, the caller
+    /// must pass the query through [`soundex`](crate::phonetic::soundex)
+    /// (e.g. search for `soundex("smyth")`, not `"smyth"`); a plain query
+    /// only matches the original spelling.
+    ///
+    /// Requires the `phonetic` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_fn` - A closure that, given a reference to an item, returns
+    ///   the borrowed field to encode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    /// use matchsorter::phonetic::soundex;
+    /// use matchsorter::{AsMatchStr, MatchSorterOptions, match_sorter};
+    ///
+    /// struct Person { name: String }
+    ///
+    /// impl AsMatchStr for Person {
+    ///     fn as_match_str(&self) -> &str { &self.name }
+    /// }
+    ///
+    /// let key = Key::<Person>::with_soundex(|p| p.name.as_str());
+    /// let values = key.extract(&Person { name: "Smith".to_owned() });
+    /// assert_eq!(values, vec!["Smith".to_owned(), soundex("smyth")]);
+    ///
+    /// let people = [Person { name: "Smith".to_owned() }];
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![Key::with_soundex(|p: &Person| p.name.as_str())],
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(match_sorter(&people, &soundex("smyth"), &opts).len(), 1);
+    /// assert!(match_sorter(&people, "smyth", &opts).is_empty());
+    /// ```
+    #[cfg(feature = "phonetic")]
+    pub fn with_soundex<F>(field_fn: F) -> Self
+    where
+        F: Fn(&T) -> &str + 'static,
+    {
+        Self::new(move |item| {
+            let value = field_fn(item);
+            let code = crate::phonetic::soundex(value);
+            if code.is_empty() {
+                vec![value.to_owned()]
+            } else {
+                vec![value.to_owned(), code]
+            }
+        })
+    }
+
+    /// Set a per-key threshold override.
+    ///
+    /// When set, matches produced by this key must meet or exceed the given
+    /// ranking to be considered. Matches below the threshold are treated as
+    /// `NoMatch` for this key.
+    ///
+    /// # Arguments
+    ///
+    /// * `ranking` - The minimum ranking a match must achieve on this key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    /// use matchsorter::Ranking;
+    ///
+    /// let key = Key::new(|s: &String| vec![s.clone()])
+    ///     .threshold(Ranking::StartsWith);
+    /// ```
+    #[must_use]
+    pub fn threshold(mut self, ranking: Ranking) -> Self {
+        self.threshold = Some(ranking);
+        self
+    }
+
+    /// Set the maximum ranking this key can contribute.
+    ///
+    /// The ranking produced by this key is clamped down to at most this
+    /// value. For example, setting `max_ranking` to [`Ranking::Contains`]
+    /// means this key can never produce `StartsWith`, `Equal`, or
+    /// `CaseSensitiveEqual`.
+    ///
+    /// Defaults to [`Ranking::CaseSensitiveEqual`] (no clamping).
+    ///
+    /// # Arguments
+    ///
+    /// * `ranking` - The ceiling for rankings produced by this key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    /// use matchsorter::Ranking;
+    ///
+    /// let key = Key::new(|s: &String| vec![s.clone()])
+    ///     .max_ranking(Ranking::Contains);
+    /// ```
+    #[must_use]
+    pub fn max_ranking(mut self, ranking: Ranking) -> Self {
+        self.max_ranking = ranking;
+        self
+    }
+
+    /// Set the minimum ranking this key can contribute.
+    ///
+    /// Non-`NoMatch` results are promoted up to at least this ranking.
+    /// A `NoMatch` result is never promoted -- an item that does not match
+    /// at all stays `NoMatch` regardless of this setting.
+    ///
+    /// Defaults to [`Ranking::NoMatch`] (no boosting).
+    ///
+    /// # Arguments
+    ///
+    /// * `ranking` - The floor for non-`NoMatch` rankings produced by this key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    /// use matchsorter::Ranking;
+    ///
+    /// let key = Key::new(|s: &String| vec![s.clone()])
+    ///     .min_ranking(Ranking::Contains);
+    /// ```
+    #[must_use]
+    pub fn min_ranking(mut self, ranking: Ranking) -> Self {
+        self.min_ranking = ranking;
+        self
+    }
+
+    /// Scale the ranks this key contributes by `weight`.
+    ///
+    /// Each match is converted to a percentage with [`Ranking::as_pct`],
+    /// multiplied by `weight` and mapped back with [`Ranking::from_pct`],
+    /// after clamping, prefix boosts and any score modifier. A weight of
+    /// `0.5` turns an `Equal` match (85%) into an `Acronym` match (42.5%,
+    /// just below `Contains`), so a lighter key only wins when it matches
+    /// clearly better than a heavier one. The weighted rank is
+    /// what thresholds, `get_highest_ranking` and the final sort see.
+    ///
+    /// Defaults to `1.0`, which leaves ranks unchanged. Weights above `1.0`
+    /// promote matches, up to [`Ranking::CaseSensitiveEqual`]; a weight of
+    /// zero, a negative weight or NaN drops every match on this key.
+    ///
+    /// # Arguments
+    ///
+    /// * `weight` - The factor applied to this key's ranks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{MatchSorterOptions, Ranking, get_highest_ranking};
+    /// use matchsorter::key::Key;
+    ///
+    /// struct User { name: String, email: String }
+    ///
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![
+    ///         Key::<User>::from_fn(|u| u.name.as_str()),
+    ///         Key::from_fn(|u: &User| u.email.as_str()).weight(0.5),
+    ///     ],
+    ///     ..Default::default()
+    /// };
+    /// let user = User { name: "Ann Lee".to_owned(), email: "lee@example.com".to_owned() };
+    /// // "lee" starts the email, but the weighted match loses to the name.
+    /// let info = get_highest_ranking(&user, &opts.keys, "lee", &opts);
+    /// assert_eq!(info.rank, Ranking::WordStartsWith);
+    /// assert_eq!(info.ranked_value, "Ann Lee");
+    /// ```
+    #[must_use]
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Set this key's priority for breaking ties between keys.
+    ///
+    /// When two keys of an item produce the same rank, the match from the
+    /// key with the higher priority is reported; among keys of equal
+    /// priority the one earlier in the list still wins. Priorities only
+    /// decide which match [`get_highest_ranking`] reports (its value,
+    /// `key_index` and alias), never whether an item matches.
+    ///
+    /// Defaults to `0`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The priority level; higher wins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{MatchSorterOptions, get_highest_ranking};
+    /// use matchsorter::key::Key;
+    ///
+    /// struct Book { title: String, series: String }
+    ///
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![
+    ///         Key::<Book>::from_fn(|b| b.title.as_str()),
+    ///         Key::from_fn(|b: &Book| b.series.as_str()).priority(1),
+    ///     ],
+    ///     ..Default::default()
+    /// };
+    /// let book = Book { title: "Dune".to_owned(), series: "Dune".to_owned() };
+    /// let info = get_highest_ranking(&book, &opts.keys, "dune", &opts);
+    /// assert_eq!(info.key_index, 1);
+    /// ```
+    #[must_use]
+    pub fn priority(mut self, n: usize) -> Self {
+        self.priority = n;
+        self
+    }
+
+    /// Break ties between items matched on this key by a per-item
+    /// priority, such as verified accounts before unverified ones.
+    ///
+    /// When two items have the same rank and `key_index`, the one with the
+    /// higher priority sorts first, before
+    /// [`base_sort`](crate::MatchSorterOptions::base_sort) is consulted.
+    /// Negative values are allowed, and items whose winning key has no tie
+    /// priority sort after those whose key has one. `priority_fn` runs only
+    /// for items whose best value comes from this key, and its result is
+    /// reported in [`RankedItem::tie_priority`](crate::RankedItem::tie_priority).
+    /// Unlike [`priority`](Self::priority), which chooses between keys of
+    /// one item, this orders different items. A custom
+    /// [`sorter`](crate::MatchSorterOptions::sorter) ignores it.
+    ///
+    /// # Arguments
+    ///
+    /// * `priority_fn` - Returns the item's priority; higher sorts first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{AsMatchStr, MatchSorterOptions, match_sorter};
+    /// use matchsorter::key::Key;
+    ///
+    /// struct Account { name: &'static str, verified: bool }
+    ///
+    /// impl AsMatchStr for Account {
+    ///     fn as_match_str(&self) -> &str { self.name }
+    /// }
+    ///
+    /// let accounts = [
+    ///     Account { name: "alice", verified: false },
+    ///     Account { name: "alice", verified: true },
+    /// ];
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![Key::from_fn(|a: &Account| a.name).tie_priority(|a| i64::from(a.verified))],
+    ///     ..Default::default()
+    /// };
+    /// let results = match_sorter(&accounts, "alice", &opts);
+    /// assert!(results[0].verified);
+    /// ```
+    #[must_use]
+    pub fn tie_priority<F>(self, priority_fn: F) -> Self
+    where
+        F: Fn(&T) -> i64 + 'static,
+    {
+        Self {
+            tie_priority: Some(Box::new(priority_fn)),
+            ..self
+        }
+    }
+
+    /// Weight `keys` by their position: `1.0` for the first key, `decay`
+    /// for the second, `decay²` for the third and so on.
+    ///
+    /// A shorthand for a "name matters more than email matters more than
+    /// description" ordering without choosing each [`weight`](Key::weight)
+    /// by hand. Any weight already set on the keys is replaced. A `decay`
+    /// of `1.0` weights every key equally; `0.0` keeps only the first key.
+    /// Values between `0.5` and `1.0` are typical.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys, most important first.
+    /// * `decay` - The factor between the weights of consecutive keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let keys = Key::<String>::weight_by_position(
+    ///     vec![
+    ///         Key::new(|s: &String| vec![s.clone()]),
+    ///         Key::new(|s: &String| vec![s.to_lowercase()]),
+    ///         Key::new(|s: &String| vec![s.to_uppercase()]),
+    ///     ],
+    ///     0.5,
+    /// );
+    /// let weights: Vec<f64> = keys.iter().map(Key::weight_value).collect();
+    /// assert_eq!(weights, [1.0, 0.5, 0.25]);
+    /// ```
+    pub fn weight_by_position(keys: Vec<Key<T>>, decay: f64) -> Vec<Key<T>> {
+        let mut weight = 1.0;
+        keys.into_iter()
+            .map(|key| {
+                let key = key.weight(weight);
+                weight *= decay;
+                key
+            })
+            .collect()
+    }
+
+    /// Promote matches on this key to [`Ranking::StartsWith`] when the
+    /// extracted value, lowercased, starts with the lowercased query.
+    ///
+    /// Unlike [`min_ranking`](Key::min_ranking), the promotion is
+    /// conditional on actual prefix alignment: other matches keep their
+    /// rank. The boost is applied after `min_ranking` / `max_ranking`
+    /// clamping, so a key capped with `max_ranking` still surfaces values
+    /// that begin with the query. [`Ranking::NoMatch`] is never promoted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::{Key, get_highest_ranking};
+    /// use matchsorter::{MatchSorterOptions, Ranking};
+    ///
+    /// let keys = vec![
+    ///     Key::<String>::from_fn(|s| s.as_str())
+    ///         .max_ranking(Ranking::Contains)
+    ///         .prefix_boost(),
+    /// ];
+    /// let opts = MatchSorterOptions::default();
+    /// let rank = |s: &str| get_highest_ranking(&s.to_owned(), &keys, "app", &opts).rank;
+    /// assert_eq!(rank("apple"), Ranking::StartsWith);
+    /// assert_eq!(rank("pineapple"), Ranking::Contains);
+    /// ```
+    #[must_use]
+    pub fn prefix_boost(mut self) -> Self {
+        self.prefix_boost = true;
+        self
+    }
+
+    /// Promote matches on this key to [`Ranking::WordStartsWith`] when any
+    /// whitespace-separated word of the lowercased value starts with the
+    /// lowercased query.
+    ///
+    /// Like [`prefix_boost`](Key::prefix_boost), the boost runs after
+    /// clamping and never promotes [`Ranking::NoMatch`] or lowers a rank.
+    /// Both boosts may be combined; a value that starts with the query then
+    /// gets the higher `StartsWith`.
+    #[must_use]
+    pub fn word_prefix_boost(mut self) -> Self {
+        self.word_prefix_boost = true;
+        self
+    }
+
+    /// Reject this key's values that lack the query's first character
+    /// before ranking them.
+    ///
+    /// The check is a single `memchr` scan for that character in either
+    /// case, so it never changes a rank: no tier matches a value missing
+    /// it. It pays off when most values lack it, and costs one extra scan
+    /// of each value that has it. Values are checked only when both they
+    /// and the query's first character are ASCII once prepared; see
+    /// [`MatchSorterOptions::prefilter`] to enable it for every key. Has no
+    /// effect on a [`Key::select`] key; set it on the sub-keys instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{Key, MatchSorterOptions, match_sorter};
+    ///
+    /// let items = ["Zebra", "apple", "lizard"];
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![Key::from_fn(|s: &&str| *s).with_prefilter()],
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(match_sorter(&items, "z", &opts), vec![&"Zebra", &"lizard"]);
+    /// ```
+    #[must_use]
+    pub fn with_prefilter(mut self) -> Self {
+        self.prefilter = true;
+        self
+    }
+
+    /// Exclude items for which this key yields no values.
+    ///
+    /// An item whose required key extracts nothing -- or is skipped via
+    /// [`Key::skip_if`] -- ranks [`Ranking::NoMatch`], however well its
+    /// other keys match. A required [`Key::select`] key is satisfied when
+    /// any of its sub-keys yields a value. With several required keys, all
+    /// of them must yield values. Values that merely fail to match the
+    /// query still satisfy the requirement; see [`Key::require_match`] to
+    /// demand a match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{Key, MatchSorterOptions, match_sorter};
+    ///
+    /// struct Recipe { title: Option<String>, notes: String }
+    /// impl matchsorter::AsMatchStr for Recipe {
+    ///     fn as_match_str(&self) -> &str { &self.notes }
+    /// }
+    ///
+    /// let recipes = [
+    ///     Recipe { title: Some("Lemon tart".to_owned()), notes: "tart".to_owned() },
+    ///     Recipe { title: None, notes: "tart".to_owned() },
+    /// ];
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![
+    ///         Key::new(|r: &Recipe| r.title.iter().cloned().collect()).required(),
+    ///         Key::from_fn(|r: &Recipe| r.notes.as_str()),
+    ///     ],
+    ///     ..Default::default()
+    /// };
+    /// let results = match_sorter(&recipes, "tart", &opts);
+    /// assert_eq!(results.len(), 1);
+    /// assert!(results[0].title.is_some());
+    /// ```
+    #[must_use]
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Let items for which this key yields no values match through other
+    /// keys; the default, undoing [`Key::required`].
+    #[must_use]
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+
+    /// Exclude items for which no value of this key matches the query at or
+    /// above the key's threshold, however well other keys match.
+    ///
+    /// Stronger than [`Key::required`]: the key must not only yield values,
+    /// one of them must also match. The threshold is the key's own (set via
+    /// [`Key::threshold`] or
+    /// [`MatchSorterOptions::key_thresholds`](crate::MatchSorterOptions::key_thresholds)),
+    /// otherwise the global one, lowered by any
+    /// [`soft_threshold`](crate::MatchSorterOptions::soft_threshold). With
+    /// several require-match keys, an item must match all of them, turning
+    /// the usual "any key matches" into "every one of these keys matches".
+    ///
+    /// The check sees the rank after [`Key::min_ranking`],
+    /// [`Key::max_ranking`] and any score modifier have been applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{Key, MatchSorterOptions, Ranking, match_sorter};
+    ///
+    /// struct Book { title: &'static str, author: &'static str }
+    /// impl matchsorter::AsMatchStr for Book {
+    ///     fn as_match_str(&self) -> &str { self.title }
+    /// }
+    ///
+    /// let books = [
+    ///     Book { title: "Tolkien: a biography", author: "Carpenter" },
+    ///     Book { title: "The Hobbit", author: "Tolkien" },
+    /// ];
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![
+    ///         Key::from_fn(|b: &Book| b.title),
+    ///         Key::from_fn(|b: &Book| b.author).require_match(),
+    ///     ],
+    ///     threshold: Ranking::Contains,
+    ///     ..Default::default()
+    /// };
+    /// let results = match_sorter(&books, "tolkien", &opts);
+    /// assert_eq!(results.len(), 1);
+    /// assert_eq!(results[0].title, "The Hobbit");
+    /// ```
+    #[must_use]
+    pub fn require_match(mut self) -> Self {
+        self.require_match = true;
+        self
+    }
+
+    /// Flip this key's ranking: values that match the query rank
+    /// [`Ranking::NoMatch`], and values that don't rank
+    /// [`Ranking::Contains`].
+    ///
+    /// Use this to find items that do *not* mention the query in a field.
+    /// Since an item ranks by its best key, an inverted key only excludes
+    /// an item when no other key matches it; combined with a normal key,
+    /// the item is still found through that key. An item for which the key
+    /// yields no values gets nothing from it, and an empty query matches
+    /// every value, so it excludes everything.
+    ///
+    /// The inverted rank is then clamped by [`Key::min_ranking`] and
+    /// [`Key::max_ranking`] as usual, with `NoMatch` never promoted. Prefix
+    /// boosts have no effect. Inverting twice restores the original key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{Key, MatchSorterOptions, match_sorter};
+    ///
+    /// let subjects = ["Cheap pills, spam inside", "Meeting notes", "Lunch?"];
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![Key::from_fn(|s: &&str| *s).invert()],
+    ///     ..Default::default()
+    /// };
+    /// let results = match_sorter(&subjects, "spam", &opts);
+    /// // Equally ranked items keep alphabetical order.
+    /// assert_eq!(results, vec![&"Lunch?", &"Meeting notes"]);
+    /// ```
+    #[must_use]
+    pub fn invert(mut self) -> Self {
+        self.inverted = !self.inverted;
+        self
+    }
+
+    /// Yield no values for items matching `predicate`.
+    ///
+    /// The predicate sees the item before extraction and runs once per item
+    /// each time the key is evaluated; a skipped item contributes no values
+    /// (and no `key_index` positions) for this key but can still match
+    /// through other keys. Calling `skip_if` again skips items matching
+    /// either predicate. Combine with [`Key::only_if`] and [`Key::union`]
+    /// to route items to different fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - Returns `true` for items this key should skip.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// struct User { email: String, is_system: bool }
+    ///
+    /// let key = Key::<User>::from_fn(|u| u.email.as_str()).skip_if(|u| u.is_system);
+    /// let bot = User { email: "bot@internal".to_owned(), is_system: true };
+    /// assert!(key.extract(&bot).is_empty());
+    /// ```
+    #[must_use]
+    pub fn skip_if<P>(self, predicate: P) -> Self
+    where
+        T: 'static,
+        P: Fn(&T) -> bool + 'static,
+    {
+        let skip: SkipPredicate<T> = match self.skip {
+            Some(previous) => Box::new(move |item| previous(item) || predicate(item)),
+            None => Box::new(predicate),
+        };
+        Self {
+            skip: Some(skip),
+            ..self
+        }
+    }
+
+    /// Yield values only for items matching `predicate`; the negation of
+    /// [`Key::skip_if`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// struct User { name: String, legal_name: String, is_system: bool }
+    ///
+    /// // Search system users by name, everyone else by legal name.
+    /// let key = Key::<User>::from_fn(|u| u.name.as_str())
+    ///     .only_if(|u| u.is_system)
+    ///     .union(Key::from_fn(|u: &User| u.legal_name.as_str()).skip_if(|u| u.is_system));
+    /// let user = User { name: "Liz".to_owned(), legal_name: "Elizabeth".to_owned(), is_system: false };
+    /// assert_eq!(key.extract(&user), vec!["Elizabeth"]);
+    /// ```
+    #[must_use]
+    pub fn only_if<P>(self, predicate: P) -> Self
+    where
+        T: 'static,
+        P: Fn(&T) -> bool + 'static,
+    {
+        self.skip_if(move |item| !predicate(item))
+    }
+
+    /// Yield no values from the extractor for items not matching
+    /// `predicate`.
+    ///
+    /// Unlike [`Key::only_if`], which skips the whole key, this empties
+    /// the extractor's output, so it composes with the builder methods
+    /// that follow it: a later [`Key::or_default_str`] supplies its default
+    /// for rejected items, and for a [`Key::select`] key the predicate
+    /// applies to each sub-key. A rejected item
+    /// contributes no values, so the next key's values take the following
+    /// `key_index` positions; with [`Key::required`], the item is excluded.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - Returns `true` for items whose values are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// struct User { phone: String, phone_public: bool }
+    ///
+    /// let key = Key::<User>::from_fn(|u| u.phone.as_str())
+    ///     .extract_if(|u| u.phone_public)
+    ///     .or_default_str("hidden");
+    /// let user = User { phone: "555-0100".to_owned(), phone_public: false };
+    /// assert_eq!(key.extract(&user), vec!["hidden"]);
+    /// ```
+    #[must_use]
+    pub fn extract_if<P>(self, predicate: P) -> Self
+    where
+        T: 'static,
+        P: Fn(&T) -> bool + 'static,
+    {
+        // Kept values are unchanged, so they stay prepared.
+        let pre_normalized = self.pre_normalized;
+        let predicate = Rc::new(predicate);
+        let key = self.wrap_extractors(&|extractor| {
+            let predicate = Rc::clone(&predicate);
+            Box::new(move |item, query| {
+                if predicate(item) {
+                    extractor(item, query)
+                } else {
+                    Vec::new()
+                }
+            })
+        });
+        Self {
+            pre_normalized,
+            ..key
+        }
+    }
+
+    /// Normalize this key's values according to each item's language.
+    ///
+    /// `lang_fn` returns the item's BCP-47 language tag (`"de"`, `"tr-TR"`,
+    /// `"ja"`). While ranking, the tag selects a normalizer from
+    /// [`MatchSorterOptions::language_normalizers`] or, failing that, the
+    /// [built-in normalizers](crate::language::builtin_normalizer), and
+    /// both the key's values and the query are passed through it before
+    /// they are compared. Items in languages without a normalizer are
+    /// ranked as usual.
+    ///
+    /// Matches report the normalized value as their `ranked_value`.
+    /// Normalizing the query costs a fresh query preparation whenever the
+    /// normalizer changes it, for every item and key. [`Key::extract`] and
+    /// exclude terms see the values as extracted. Has no effect on a
+    /// [`Key::select`] key; set it on the sub-keys instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{Key, MatchSorterOptions, match_sorter};
+    ///
+    /// struct City { name: &'static str, lang: &'static str }
+    /// impl matchsorter::AsMatchStr for City {
+    ///     fn as_match_str(&self) -> &str { self.name }
+    /// }
+    ///
+    /// let cities = [
+    ///     City { name: "D\u{fc}sseldorf", lang: "de" },
+    ///     City { name: "\u{130}zmir", lang: "tr" },
+    /// ];
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![Key::from_fn(|c: &City| c.name).multi_language(|c| c.lang)],
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(match_sorter(&cities, "duesseldorf", &opts).len(), 1);
+    /// ```
+    #[must_use]
+    pub fn multi_language<F>(self, lang_fn: F) -> Self
+    where
+        F: Fn(&T) -> &str + 'static,
+    {
+        Self {
+            language: Some(Box::new(lang_fn)),
+            ..self
+        }
+    }
+
+    /// Treat every value of this key as text in the BCP-47 language `tag`
+    /// (`"tr"`, `"de-AT"`).
+    ///
+    /// The key's values and the query are lowercased with the language's
+    /// case rules (see
+    /// [`LanguageAwareOptions`](crate::language::LanguageAwareOptions)) or
+    /// with [`MatchSorterOptions::custom_case_fold`], and pass through the
+    /// language's normalizer as described for [`Key::multi_language`].
+    /// Turkish and Azerbaijani lowercase `I` to `ı` and `İ` to `i`; their
+    /// built-in normalizer then folds every i to a plain `i`, so the case
+    /// rules decide only once that normalizer is replaced in
+    /// [`MatchSorterOptions::language_normalizers`]. Diacritics are
+    /// stripped before lowercasing, so without
+    /// [`keep_diacritics`](MatchSorterOptions::keep_diacritics) an `İ`
+    /// loses its dot and lowercases like `I`.
+    ///
+    /// A [`Key::multi_language`] tag, where set, takes precedence over this
+    /// one. Keys whose case rules differ from the default lowercasing skip
+    /// the [ranking cache](MatchSorterOptions::ranking_cache). Has no
+    /// effect on a [`Key::select`] key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{Key, MatchSorterOptions, Ranking, match_sorter};
+    ///
+    /// let cities = ["\u{130}stanbul", "Isparta"];
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![Key::from_fn(|c: &&str| *c).language("tr")],
+    ///     threshold: Ranking::StartsWith,
+    ///     keep_diacritics: true,
+    ///     ..Default::default()
+    /// }
+    /// .language_normalizer("tr", str::to_owned);
+    /// // "İstanbul" lowercases to "istanbul"; "Isparta" to "ısparta".
+    /// assert_eq!(match_sorter(&cities, "is", &opts), vec![&"\u{130}stanbul"]);
+    /// ```
+    #[must_use]
+    pub fn language(self, tag: impl Into<String>) -> Self {
+        Self {
+            language_tag: Some(tag.into()),
+            ..self
+        }
+    }
+
+    /// Name this key, so that results matched through it report which
+    /// field matched.
+    ///
+    /// The alias is copied into [`RankingInfo::matched_key_alias`] and
+    /// [`RankedItem::key_alias`](crate::RankedItem::key_alias) when this key
+    /// produces the winning value. Aliases need not be unique; the
+    /// `key_index` still tells keys apart. On a [`Key::select`] key, the
+    /// select key's alias takes precedence over those of its sub-keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The display name, e.g. the field name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::{Key, get_highest_ranking};
+    /// use matchsorter::MatchSorterOptions;
+    ///
+    /// struct User { name: String, email: String }
+    ///
+    /// let keys = vec![
+    ///     Key::<User>::from_fn(|u| u.name.as_str()).alias("name"),
+    ///     Key::<User>::from_fn(|u| u.email.as_str()).alias("email"),
+    /// ];
+    /// let user = User { name: "Alice".to_owned(), email: "ally@example.com".to_owned() };
+    /// let info = get_highest_ranking(&user, &keys, "ally", &MatchSorterOptions::default());
+    /// assert_eq!(info.matched_key_alias.as_deref(), Some("email"));
+    /// ```
+    #[must_use]
+    pub fn alias(self, name: impl Into<String>) -> Self {
+        Self {
+            alias: Some(KeyAlias::Fixed(name.into())),
+            ..self
+        }
+    }
+
+    /// Like [`Key::alias`], but computes the name from the matched item.
+    ///
+    /// The closure runs only for items whose best value comes from this
+    /// key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::{Key, get_highest_ranking};
+    /// use matchsorter::MatchSorterOptions;
+    ///
+    /// struct Field { label: String, value: String }
+    ///
+    /// let keys = vec![Key::<Field>::from_fn(|f| f.value.as_str()).alias_from_fn(|f| f.label.clone())];
+    /// let field = Field { label: "city".to_owned(), value: "Paris".to_owned() };
+    /// let info = get_highest_ranking(&field, &keys, "par", &MatchSorterOptions::default());
+    /// assert_eq!(info.matched_key_alias.as_deref(), Some("city"));
+    /// ```
+    #[must_use]
+    pub fn alias_from_fn<F>(self, f: F) -> Self
+    where
+        F: Fn(&T) -> String + 'static,
+    {
+        Self {
+            alias: Some(KeyAlias::PerItem(Box::new(f))),
+            ..self
+        }
+    }
+
+    /// Adjust the rank of every match on this key with `f`.
+    ///
+    /// `f` receives the item and the rank of a matching value (after
+    /// clamping and prefix boosts) and returns the rank to use instead, for
+    /// example to lift featured items or recently used ones. Values that
+    /// do not match are not passed to `f`, and returning
+    /// [`Ranking::NoMatch`] drops the match. The result is used as is: it
+    /// is not clamped to [`min_ranking`](Self::min_ranking) or
+    /// [`max_ranking`](Self::max_ranking), and it is the caller's
+    /// responsibility to return a valid rank, such as a `Matches` score
+    /// within `1.0..2.0`, and to respect tier boundaries.
+    ///
+    /// A key's modifier replaces
+    /// [`MatchSorterOptions::item_score_modifier`] for that key's matches.
+    /// Calling this again replaces the previous modifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - Maps an item and its natural rank to the adjusted rank
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{MatchSorterOptions, Ranking, match_sorter};
+    /// use matchsorter::key::Key;
+    ///
+    /// let items = vec!["Nutmeg".to_owned(), "Coconut".to_owned()];
+    /// let featured = |s: &String| s == "Coconut";
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![Key::new(|s: &String| vec![s.clone()]).with_score_modifier(
+    ///         move |item, rank| {
+    ///             if featured(item) && rank == Ranking::Contains {
+    ///                 Ranking::StartsWith
+    ///             } else {
+    ///                 rank
+    ///             }
+    ///         },
+    ///     )],
+    ///     ..Default::default()
+    /// };
+    /// // "Coconut" only contains "nut", but now ties with "Nutmeg" and wins
+    /// // alphabetically.
+    /// let results = match_sorter(&items, "nut", &opts);
+    /// assert_eq!(results, vec![&"Coconut".to_owned(), &"Nutmeg".to_owned()]);
+    /// ```
+    #[must_use]
+    pub fn with_score_modifier<F>(self, f: F) -> Self
+    where
+        F: Fn(&T, Ranking) -> Ranking + 'static,
+    {
+        Self {
+            score_modifier: Some(Box::new(f)),
+            ..self
+        }
+    }
+
+    /// Count every evaluation of this key, returning the key and a handle
+    /// to the count.
+    ///
+    /// Each [`extract`](Self::extract) call, and each time ranking
+    /// evaluates the key, adds one; items skipped by
+    /// [`skip_if`](Self::skip_if) are not counted. The key's `Debug` output
+    /// includes the current count. Calling this again replaces the
+    /// previous counter.
+    ///
+    /// This is a debugging and testing aid for spotting redundant
+    /// extraction, available only with the `counters` feature; it adds an
+    /// atomic increment to every evaluation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let (key, calls) = Key::new(|s: &String| vec![s.clone()]).with_call_counter();
+    /// key.extract(&"apple".to_owned());
+    /// key.extract(&"grape".to_owned());
+    /// assert_eq!(calls.get(), 2);
+    /// assert!(format!("{key:?}").contains("calls: 2"));
+    ///
+    /// calls.reset();
+    /// assert_eq!(calls.get(), 0);
+    /// ```
+    #[cfg(feature = "counters")]
+    #[must_use]
+    pub fn with_call_counter(self) -> (Self, CallCounter) {
+        let counter = CallCounter::default();
+        let key = Self {
+            call_counter: Some(counter.clone()),
+            ..self
+        };
+        (key, counter)
+    }
+
+    /// Record one evaluation of this key in its call counter, if any.
+    #[inline]
+    fn count_call(&self) {
+        #[cfg(feature = "counters")]
+        if let Some(counter) = &self.call_counter {
+            counter.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Apply this key's score modifier, or `fallback` when it has none, to
+    /// a matching `rank`.
+    fn modify_score(
+        &self,
+        item: &T,
+        rank: Ranking,
+        fallback: Option<ScoreModifierRef<'_, T>>,
+    ) -> Ranking {
+        if rank == Ranking::NoMatch {
+            return rank;
+        }
+        match self.score_modifier.as_deref().or(fallback) {
+            Some(modify) => modify(item, rank),
+            None => rank,
+        }
+    }
+
+    /// Scale a final rank by this key's weight.
+    fn apply_weight(&self, rank: Ranking) -> Ranking {
+        if self.weight == 1.0 || rank == Ranking::NoMatch {
+            return rank;
+        }
+        Ranking::from_pct(rank.as_pct() * self.weight)
+    }
+
+    /// Returns whether this key or any of its sub-keys has a score
+    /// modifier.
+    pub(crate) fn has_score_modifier(&self) -> bool {
+        self.score_modifier.is_some()
+            || matches!(&self.source, KeySource::Select(keys) if keys.iter().any(Key::has_score_modifier))
+    }
+
+    /// Returns whether this key or any of its sub-keys extracts values that
+    /// depend on the query.
+    pub(crate) fn depends_on_query(&self) -> bool {
+        self.query_dependent
+            || matches!(&self.source, KeySource::Select(keys) if keys.iter().any(Key::depends_on_query))
+    }
+
+    /// Returns whether this key or any of its sub-keys is inverted.
+    pub(crate) fn has_inversion(&self) -> bool {
+        self.inverted
+            || matches!(&self.source, KeySource::Select(keys) if keys.iter().any(Key::has_inversion))
+    }
+
+    /// This key's alias for `item`, if it has one.
+    fn alias_for(&self, item: &T) -> Option<String> {
+        match self.alias.as_ref()? {
+            KeyAlias::Fixed(name) => Some(name.clone()),
+            KeyAlias::PerItem(f) => Some(f(item)),
+        }
+    }
+
+    /// The language of this key's values for `item`, if it has one.
+    fn item_language<'s>(&'s self, item: &'s T) -> Option<&'s str> {
+        match &self.language {
+            Some(lang) => Some(lang(item)),
+            None => self.language_tag.as_deref(),
+        }
+    }
+
+    /// Returns whether this key yields no values for `item`.
+    fn skips(&self, item: &T) -> bool {
+        self.skip.as_ref().is_some_and(|skip| skip(item))
+    }
+
+    /// Returns whether this key yields at least one value for `item`; for
+    /// a select key, whether any sub-key does.
+    fn has_values(&self, item: &T, query: &str) -> bool {
+        if self.skips(item) {
+            return false;
+        }
+        match &self.source {
+            KeySource::Extract(_) => !self.extract_unchecked(item, query).is_empty(),
+            KeySource::Select(sub_keys) => sub_keys.iter().any(|key| key.has_values(item, query)),
+        }
+    }
+
+    /// Invert the rank if this key is inverted, then clamp it into
+    /// `[min_ranking, max_ranking]` and apply the prefix boosts. `NoMatch`
+    /// is never promoted -- a value that doesn't match stays unmatched.
+    fn adjust_rank(
+        &self,
+        rank: Ranking,
+        value: &str,
+        pq: &PreparedQuery,
+        config: &RankingConfig<'_>,
+    ) -> Ranking {
+        if self.inverted {
+            if rank != Ranking::NoMatch {
+                return Ranking::NoMatch;
+            }
+            return Ranking::Contains.clamp(self.min_ranking, self.max_ranking);
+        }
+        if rank == Ranking::NoMatch {
+            return rank;
+        }
+        let rank = rank.clamp(self.min_ranking, self.max_ranking);
+        self.apply_prefix_boost(rank, value, pq, config)
+    }
+
+    /// Apply the configured prefix boosts to a clamped, non-`NoMatch` rank.
+    fn apply_prefix_boost(
+        &self,
+        rank: Ranking,
+        value: &str,
+        pq: &PreparedQuery,
+        config: &RankingConfig<'_>,
+    ) -> Ranking {
+        let wants_prefix = self.prefix_boost && rank < Ranking::StartsWith;
+        let wants_word = self.word_prefix_boost && rank < Ranking::WordStartsWith;
+        if !wants_prefix && !wants_word {
+            return rank;
+        }
+        let mut lower = String::new();
+        config.lowercase_into(&config.prepare(value), &mut lower);
+        if wants_prefix && lower.starts_with(&pq.lower) {
+            return Ranking::StartsWith;
+        }
+        let word_starts = |(pos, _): (usize, &str)| {
+            lower[..pos]
+                .chars()
+                .next_back()
+                .is_none_or(char::is_whitespace)
+        };
+        if wants_word && lower.match_indices(&pq.lower).any(word_starts) {
+            return Ranking::WordStartsWith;
+        }
+        rank
+    }
+
+    /// Apply a stemming function to every value this key extracts.
+    ///
+    /// Each extracted value is lowercased and split on whitespace; every word
+    /// is passed to `stem_fn` and replaced by its stem, keeping the original
+    /// separators. This lets an inflected query like `"running"` meet a
+    /// candidate `"run"` once both sides are reduced to the same stem (pair
+    /// it with [`MatchSorterOptions::query_stemmer`] for the query side).
+    ///
+    /// The stemming algorithm (Porter, Snowball, a lookup table, ...) is
+    /// supplied by the caller; `stem_fn` receives one lowercase word and
+    /// returns its stem. A panic inside `stem_fn` propagates out of
+    /// [`match_sorter`](crate::match_sorter).
+    ///
+    /// # Arguments
+    ///
+    /// * `stem_fn` - Maps a lowercase word to its stem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::new(|s: &String| vec![s.clone()])
+    ///     .with_stemmer(|w| w.trim_end_matches("ing").to_owned());
+    /// assert_eq!(key.extract(&"Jumping Jack".to_owned()), vec!["jump jack"]);
+    /// ```
+    #[must_use]
+    pub fn with_stemmer<F>(self, stem_fn: F) -> Self
+    where
+        T: 'static,
+        F: Fn(&str) -> String + 'static,
+    {
+        self.map_values(move |values| {
+            values
+                .iter()
+                .map(|value| stem_words(value, &stem_fn))
+                .collect()
+        })
+    }
+
+    /// Transform every extracted value with `f`.
+    ///
+    /// Use this for normalization the other wrappers do not cover, such as
+    /// trimming or stripping a prefix. Ranking attributes are preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - Function applied to each extracted value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::from_fn(|s: &String| s.as_str()).map(|v| v.trim().to_owned());
+    /// assert_eq!(key.extract(&"  padded  ".to_owned()), vec!["padded"]);
+    /// ```
+    #[must_use]
+    pub fn map<F>(self, f: F) -> Self
+    where
+        T: 'static,
+        F: Fn(String) -> String + 'static,
+    {
+        self.map_values(move |values| values.into_iter().map(&f).collect())
+    }
+
+    /// Trim leading and trailing whitespace from every extracted value.
+    ///
+    /// Padding from databases or user input otherwise keeps a value from
+    /// ranking [`Ranking::Equal`] or better: against `" apple "` the query
+    /// `"apple"` only reaches [`Ranking::WordStartsWith`]. Values without padding
+    /// are passed through without allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::from_fn(|s: &String| s.as_str()).trim();
+    /// assert_eq!(key.extract(&" apple\t".to_owned()), vec!["apple"]);
+    /// ```
+    #[must_use]
+    pub fn trim(self) -> Self
+    where
+        T: 'static,
+    {
+        self.map(|value| trim_owned(value, str::trim))
+    }
+
+    /// Like [`Key::trim`], but only trims leading whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::from_fn(|s: &String| s.as_str()).trim_start();
+    /// assert_eq!(key.extract(&" apple ".to_owned()), vec!["apple "]);
+    /// ```
+    #[must_use]
+    pub fn trim_start(self) -> Self
+    where
+        T: 'static,
+    {
+        self.map(|value| trim_owned(value, str::trim_start))
+    }
+
+    /// Like [`Key::trim`], but only trims trailing whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::from_fn(|s: &String| s.as_str()).trim_end();
+    /// assert_eq!(key.extract(&" apple ".to_owned()), vec![" apple"]);
+    /// ```
+    #[must_use]
+    pub fn trim_end(self) -> Self
+    where
+        T: 'static,
+    {
+        self.map(|value| trim_owned(value, str::trim_end))
+    }
+
+    /// Trim leading and trailing characters matching `pattern` from every
+    /// extracted value.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Returns `true` for characters to trim
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::from_fn(|s: &String| s.as_str()).trim_matches(|c| c == '"' || c == '\'');
+    /// assert_eq!(key.extract(&"\"quoted\"".to_owned()), vec!["quoted"]);
+    /// ```
+    #[must_use]
+    pub fn trim_matches<F>(self, pattern: F) -> Self
+    where
+        T: 'static,
+        F: Fn(char) -> bool + 'static,
+    {
+        self.map(move |value| trim_owned(value, |v| v.trim_matches(&pattern)))
+    }
+
+    /// Transform the whole list of extracted values with `f`.
+    ///
+    /// Unlike [`Key::map`], `f` sees every value at once, so it can
+    /// deduplicate, reorder, filter (e.g. drop stop words) or normalize in
+    /// bulk. It runs once per extraction, before any value is ranked. The
+    /// order of the returned values sets their `key_index`, which breaks
+    /// ties between equally ranked values. For a [`Key::select`] key, `f`
+    /// runs on each sub-key's values separately.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - Function applied to the extracted values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::split_whitespace(|s: &String| s.as_str())
+    ///     .preprocess_values(|values| values.into_iter().filter(|v| v != "the").collect());
+    /// assert_eq!(key.extract(&"the lord of the rings".to_owned()), vec!["lord", "of", "rings"]);
+    /// ```
+    #[must_use]
+    pub fn preprocess_values<F>(self, f: F) -> Self
+    where
+        T: 'static,
+        F: Fn(Vec<String>) -> Vec<String> + 'static,
+    {
+        self.map_values(f)
+    }
+
+    /// Drop extracted values equal to an earlier one.
+    ///
+    /// Keeps the first occurrence of each value, in its original position,
+    /// so the surviving values keep their relative order. Only exact
+    /// duplicates are removed; `"Tag"` and `"tag"` both stay.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::split_on(',', |s: &String| s.as_str()).deduplicate_values();
+    /// assert_eq!(key.extract(&"b,a,b,A,a".to_owned()), vec!["b", "a", "A"]);
+    /// ```
+    #[must_use]
+    pub fn deduplicate_values(self) -> Self
+    where
+        T: 'static,
+    {
+        self.preprocess_values(|mut values| {
+            let mut seen = HashSet::with_capacity(values.len());
+            let first: Vec<bool> = values
+                .iter()
+                .map(|value| seen.insert(value.as_str()))
+                .collect();
+            let mut first = first.into_iter();
+            values.retain(|_| first.next().unwrap_or(true));
+            values
+        })
+    }
+
+    /// Sort the extracted values in ascending byte order.
+    ///
+    /// Makes the `key_index` tiebreak between equally ranked values
+    /// independent of the order the extractor returns them in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::split_on(',', |s: &String| s.as_str()).sort_values();
+    /// assert_eq!(key.extract(&"pear,apple,fig".to_owned()), vec!["apple", "fig", "pear"]);
+    /// ```
+    #[must_use]
+    pub fn sort_values(self) -> Self
+    where
+        T: 'static,
+    {
+        self.preprocess_values(|mut values| {
+            values.sort_unstable();
+            values
+        })
+    }
+
+    /// Lowercase every extracted value.
+    ///
+    /// Normalizes inconsistently cased data (`"ALICE"`, `"Alice"`) so that a
+    /// lowercase query reaches [`Ranking::CaseSensitiveEqual`] on any of
+    /// them. The trade-off: the case-sensitive tier now compares against
+    /// the lowercased value, so a query with uppercase letters (`"Alice"`)
+    /// can reach at most [`Ranking::Equal`], even on a value that was
+    /// originally cased the same way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::from_fn(|s: &String| s.as_str()).lowercased();
+    /// assert_eq!(key.extract(&"ALICE".to_owned()), vec!["alice"]);
+    /// ```
+    #[must_use]
+    pub fn lowercased(self) -> Self
+    where
+        T: 'static,
+    {
+        self.map(|value| {
+            let mut lower = String::new();
+            lowercase_into(&value, &mut lower);
+            lower
+        })
+    }
+
+    /// Prepare every extracted value for comparison as it is extracted, so
+    /// that ranking can skip that step.
+    ///
+    /// Each value goes through [`prepare_value_for_comparison`] with
+    /// `keep_diacritics`. When the search uses the same setting (and
+    /// the default [`DiacriticsMode`] and [`NormalizationForm`]), ranking
+    /// takes the values as they are instead of preparing them again.
+    /// Ranks are unchanged either way. Useful when an extractor would
+    /// normalize its values anyway, since the work is then done once.
+    ///
+    /// Values prepared this way are what [`Key::extract`] returns and what
+    /// matches report as their `ranked_value`. Later value transforms such
+    /// as [`Key::map`] clear the mark, and ranking prepares their output as
+    /// usual; values normalized by a language normalizer are prepared
+    /// again too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{Key, MatchSorterOptions, Ranking, get_highest_ranking};
+    ///
+    /// let keys = vec![Key::from_fn(|s: &&str| *s).extract_normalized(false)];
+    /// assert_eq!(keys[0].extract(&"Cr\u{e8}me br\u{fb}l\u{e9}e"), vec!["Creme brulee"]);
+    ///
+    /// let opts = MatchSorterOptions::default();
+    /// let info = get_highest_ranking(&"Cr\u{e8}me br\u{fb}l\u{e9}e", &keys, "brulee", &opts);
+    /// assert_eq!(info.rank, Ranking::WordStartsWith);
+    /// ```
+    #[must_use]
+    pub fn extract_normalized(self, keep_diacritics: bool) -> Self
+    where
+        T: 'static,
+    {
+        let key =
+            self.map(
+                move |value| match prepare_value_for_comparison(&value, keep_diacritics) {
+                    Cow::Borrowed(_) => value,
+                    Cow::Owned(prepared) => prepared,
+                },
+            );
+        Self {
+            pre_normalized: Some(DiacriticsMode::from_keep_diacritics(keep_diacritics)),
+            ..key
+        }
+    }
+
+    /// Uppercase every extracted value.
+    ///
+    /// The mirror of [`Key::lowercased`], with the same trade-off: only an
+    /// uppercase query can reach [`Ranking::CaseSensitiveEqual`].
+    #[must_use]
+    pub fn uppercased(self) -> Self
+    where
+        T: 'static,
+    {
+        self.map(|value| value.to_uppercase())
+    }
+
+    /// Keep at most `n` extracted values, dropping the rest.
+    ///
+    /// Useful with [`Key::split_on`] and [`Key::split_whitespace`] to limit
+    /// how many values of a pathological field (e.g. a user-supplied tag
+    /// string with thousands of separators) get ranked. The extractor still
+    /// produces every value; those beyond the first `n` are dropped before
+    /// ranking.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - Maximum number of values this key contributes per item
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::split_on(',', |s: &String| s.as_str()).max_values(2);
+    /// assert_eq!(key.extract(&"a,b,c,d".to_owned()), vec!["a", "b"]);
+    /// ```
+    #[must_use]
+    pub fn max_values(self, n: usize) -> Self
+    where
+        T: 'static,
+    {
+        self.map_values(move |mut values| {
+            values.truncate(n);
+            values
+        })
+    }
+
+    /// Keep only the value at index `n` (0-based) of the extracted values.
+    ///
+    /// Yields no values when the extractor returns `n` or fewer values.
+    /// Like the other extractor wrappers, this leaves `threshold`,
+    /// `min_ranking` and `max_ranking` untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - Index of the value to keep
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::split_on(',', |s: &String| s.as_str()).extract_nth(1);
+    /// assert_eq!(key.extract(&"a,b,c".to_owned()), vec!["b"]);
+    /// assert!(key.extract(&"a".to_owned()).is_empty());
+    /// ```
+    #[must_use]
+    pub fn extract_nth(self, n: usize) -> Self
+    where
+        T: 'static,
+    {
+        self.map_values(move |values| values.into_iter().nth(n).into_iter().collect())
+    }
+
+    /// Keep only the first extracted value. Shorthand for
+    /// [`extract_nth(0)`](Key::extract_nth).
+    #[must_use]
+    pub fn extract_first(self) -> Self
+    where
+        T: 'static,
+    {
+        self.extract_nth(0)
+    }
+
+    /// Keep only the last extracted value, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::split_on('/', |s: &String| s.as_str()).extract_last();
+    /// assert_eq!(key.extract(&"src/key.rs".to_owned()), vec!["key.rs"]);
+    /// ```
+    #[must_use]
+    pub fn extract_last(self) -> Self
+    where
+        T: 'static,
+    {
+        self.map_values(|values| values.into_iter().last().into_iter().collect())
+    }
+
+    /// Keep only the extracted values whose indices fall in `range`.
+    ///
+    /// The range is clipped to the number of values, so out-of-bounds
+    /// ranges yield fewer (or no) values rather than panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - Half-open range of value indices to keep
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::split_on(',', |s: &String| s.as_str()).extract_slice(1..3);
+    /// assert_eq!(key.extract(&"a,b,c,d".to_owned()), vec!["b", "c"]);
+    /// ```
+    #[must_use]
+    pub fn extract_slice(self, range: Range<usize>) -> Self
+    where
+        T: 'static,
+    {
+        self.map_values(move |values| {
+            values
+                .into_iter()
+                .skip(range.start)
+                .take(range.end.saturating_sub(range.start))
+                .collect()
+        })
+    }
+
+    /// Yield `default` when the extractor returns no values.
+    ///
+    /// The item then ranks against the sentinel instead of having no value
+    /// for this key, so a query such as `"unknown"` can find it. Unlike
+    /// [`Key::first_of`], which tries other extractors, this substitutes a
+    /// value within the same key. Because the key always yields at least
+    /// one value, it also always satisfies [`Key::required`]; items skipped
+    /// by [`Key::skip_if`] still yield nothing. For a [`Key::select`] key,
+    /// each sub-key gets the default separately.
+    ///
+    /// # Arguments
+    ///
+    /// * `default` - The value used when the extractor returns nothing
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::new(|s: &Option<String>| s.iter().cloned().collect())
+    ///     .or_default_str("(no name)");
+    /// assert_eq!(key.extract(&None), vec!["(no name)"]);
+    /// assert_eq!(key.extract(&Some("Ada".to_owned())), vec!["Ada"]);
+    /// ```
+    #[must_use]
+    pub fn or_default_str(self, default: &'static str) -> Self
+    where
+        T: 'static,
+    {
+        self.or_default_fn(move |_| default.to_owned())
+    }
+
+    /// Yield `f(item)` when the extractor returns no values.
+    ///
+    /// The computed counterpart of [`Key::or_default_str`]; `f` is only
+    /// called for items whose extractor returned nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - Computes the value used when the extractor returns nothing
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// struct Contact { name: Option<String>, email: String }
+    ///
+    /// let key = Key::new(|c: &Contact| c.name.iter().cloned().collect())
+    ///     .or_default_fn(|c| c.email.clone());
+    /// let contact = Contact { name: None, email: "ada@example.com".to_owned() };
+    /// assert_eq!(key.extract(&contact), vec!["ada@example.com"]);
+    /// ```
+    #[must_use]
+    pub fn or_default_fn<F>(self, f: F) -> Self
+    where
+        T: 'static,
+        F: Fn(&T) -> String + 'static,
+    {
+        let f = Rc::new(f);
+        self.wrap_extractors(&|extractor| {
+            let f = Rc::clone(&f);
+            Box::new(move |item, query| {
+                let values = extractor(item, query);
+                if values.is_empty() {
+                    vec![f(item)]
+                } else {
+                    values
+                }
+            })
+        })
+    }
+
+    /// Yield `default` when the extractor returns no values.
+    ///
+    /// Like [`Key::or_default_str`], but the default is any owned string,
+    /// such as a label read from configuration or built at runtime. The
+    /// default is ranked like any extracted value, so with a default of
+    /// `"unknown"` the query `"unk"` finds the item as `StartsWith`.
+    /// Because the key always yields at least one value, it also always
+    /// satisfies [`Key::required`].
+    ///
+    /// # Arguments
+    ///
+    /// * `default` - The value used when the extractor returns nothing
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let label = format!("{} (unassigned)", "owner");
+    /// let key = Key::new(|s: &Option<String>| s.iter().cloned().collect())
+    ///     .with_default_value(label);
+    /// assert_eq!(key.extract(&None), vec!["owner (unassigned)"]);
+    /// assert_eq!(key.extract(&Some("Ada".to_owned())), vec!["Ada"]);
+    /// ```
+    #[must_use]
+    pub fn with_default_value(self, default: impl Into<String>) -> Self
+    where
+        T: 'static,
+    {
+        let default = default.into();
+        self.or_default_fn(move |_| default.clone())
+    }
+
+    /// Wrap the extractor so `f` post-processes every extracted value list,
+    /// keeping the key's ranking attributes. For a [`Key::select`] key, `f`
+    /// runs on each sub-key's values separately.
+    fn map_values<F>(self, f: F) -> Self
+    where
+        T: 'static,
+        F: Fn(Vec<String>) -> Vec<String> + 'static,
+    {
+        let f = Rc::new(f);
+        self.wrap_extractors(&|extractor| {
+            let f = Rc::clone(&f);
+            Box::new(move |item, query| f(extractor(item, query)))
+        })
+    }
+
+    /// Replace every extractor of this key (each sub-key's, for a
+    /// [`Key::select`] key) with `wrap(extractor)`.
+    ///
+    /// The new values may no longer be prepared, so this also clears
+    /// [`Key::extract_normalized`].
+    fn wrap_extractors(self, wrap: &dyn Fn(Extractor<T>) -> Extractor<T>) -> Self {
+        let source = match self.source {
+            KeySource::Extract(extractor) => KeySource::Extract(wrap(extractor)),
+            KeySource::Select(keys) => KeySource::Select(
+                keys.into_iter()
+                    .map(|key| key.wrap_extractors(wrap))
+                    .collect(),
+            ),
+        };
+        Self {
+            source,
+            pre_normalized: None,
+            ..self
+        }
+    }
+
+    /// Check every extracted value list with `validator`, panicking on error.
+    ///
+    /// Wraps the extractor so that each `Vec<String>` it produces is passed
+    /// to `validator` before ranking. If the validator returns `Err(msg)`,
+    /// extraction panics with `"key validation failed: {msg}"`. Use this to
+    /// catch extractor bugs (empty values, duplicates, stray NUL bytes)
+    /// close to their source rather than as puzzling rankings.
+    ///
+    /// The check runs on every extraction; see
+    /// [`Key::debug_only_validate`] for a variant that is compiled out of
+    /// release builds.
+    ///
+    /// # Arguments
+    ///
+    /// * `validator` - Inspects the extracted values and returns `Err` with a
+    ///   message describing the first violation.
+    ///
+    /// # Panics
+    ///
+    /// The returned key panics during extraction when `validator` rejects
+    /// the extracted values.
+    ///
+    /// # Examples
+    ///
+    /// ```should_panic
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::new(|s: &String| vec![s.clone()]).validate(|values| {
+    ///     if values.iter().all(|v| v.len() <= 5) {
+    ///         Ok(())
+    ///     } else {
+    ///         Err("value too long".to_owned())
+    ///     }
+    /// });
+    /// key.extract(&"much too long".to_owned()); // panics
+    /// ```
+    #[must_use]
+    pub fn validate<F>(self, validator: F) -> Self
+    where
+        T: 'static,
+        F: Fn(&[String]) -> Result<(), String> + 'static,
+    {
+        self.map_values(move |values| {
+            if let Err(msg) = validator(&values) {
+                panic!("key validation failed: {msg}");
+            }
+            values
+        })
+    }
+
+    /// Like [`Key::validate`], but only in builds with `debug_assertions`.
+    ///
+    /// In release builds the validator is dropped and the key is returned
+    /// unchanged, so the check costs nothing in production.
+    #[must_use]
+    pub fn debug_only_validate<F>(self, validator: F) -> Self
+    where
+        T: 'static,
+        F: Fn(&[String]) -> Result<(), String> + 'static,
+    {
+        #[cfg(debug_assertions)]
+        {
+            self.validate(validator)
+        }
+        #[cfg(not(debug_assertions))]
+        {
+            let _ = validator;
+            self
+        }
+    }
+
+    /// Validate that no extracted value is an empty string.
+    ///
+    /// See [`Key::validate`] for the panic behavior.
+    #[must_use]
+    pub fn ensure_non_empty_values(self) -> Self
+    where
+        T: 'static,
+    {
+        self.validate(|values| match values.iter().position(String::is_empty) {
+            Some(i) => Err(format!("value {i} is empty")),
+            None => Ok(()),
+        })
+    }
+
+    /// Treat empty extracted values as a bug in the extractor.
+    ///
+    /// In builds with `debug_assertions`, extraction panics when the
+    /// extractor returns no values or any empty string. In release builds
+    /// empty strings are dropped instead, as if the extractor had not
+    /// returned them. Set
+    /// [`MatchSorterOptions::strict_empty_values`](crate::MatchSorterOptions::strict_empty_values)
+    /// to apply this to every key.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, the returned key panics during extraction when the
+    /// extractor returns no values or an empty string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::new(|s: &String| vec![s.clone()]).require_non_empty();
+    /// assert_eq!(key.extract(&"Alice".to_owned()), vec!["Alice"]);
+    /// ```
+    #[must_use]
+    pub fn require_non_empty(self) -> Self
+    where
+        T: 'static,
+    {
+        self.map_values(drop_empty_values)
+    }
+
+    /// Replace empty extracted values with `default`.
+    ///
+    /// Each empty string becomes `default`, and an extractor returning no
+    /// values yields `default` alone. Unlike [`Key::require_non_empty`],
+    /// this never panics.
+    ///
+    /// # Arguments
+    ///
+    /// * `default` - The value used in place of an empty one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::new(|s: &String| vec![s.clone()]).require_non_empty_or_default("(unnamed)");
+    /// assert_eq!(key.extract(&String::new()), vec!["(unnamed)"]);
+    /// ```
+    #[must_use]
+    pub fn require_non_empty_or_default(self, default: &'static str) -> Self
+    where
+        T: 'static,
+    {
+        self.map_values(move |mut values| {
+            if values.is_empty() {
+                return vec![default.to_owned()];
+            }
+            for value in values.iter_mut().filter(|value| value.is_empty()) {
+                default.clone_into(value);
+            }
+            values
+        })
+    }
+
+    /// Validate that no extracted value contains a NUL (`'\0'`) character.
+    ///
+    /// See [`Key::validate`] for the panic behavior.
+    #[must_use]
+    pub fn ensure_no_nulls(self) -> Self
+    where
+        T: 'static,
+    {
+        self.validate(
+            |values| match values.iter().position(|v| v.contains('\0')) {
+                Some(i) => Err(format!("value {i} contains a NUL character")),
+                None => Ok(()),
+            },
+        )
+    }
+
+    /// Validate that the extracted values contain no duplicates.
+    ///
+    /// Duplicates are harmless for ranking but waste work and shift the
+    /// `key_index` of later values. See [`Key::validate`] for the panic
+    /// behavior.
+    #[must_use]
+    pub fn ensure_unique_values(self) -> Self
+    where
+        T: 'static,
+    {
+        self.validate(|values| {
+            for (i, value) in values.iter().enumerate() {
+                if values[..i].contains(value) {
+                    return Err(format!("value {i} duplicates {value:?}"));
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Extract string values from an item using this key's extractor closure.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - A reference to the item to extract values from.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<String>` of extracted values. An empty vector means the item
+    /// produces no match candidates for this key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::new(|s: &String| vec![s.clone()]);
+    /// let values = key.extract(&"hello".to_owned());
+    /// assert_eq!(values, vec!["hello"]);
+    /// ```
+    pub fn extract(&self, item: &T) -> Vec<String> {
+        self.extract_with_query(item, "")
+    }
+
+    /// [`extract`](Self::extract), passing `query` to a
+    /// [lazy](Key::from_fn_lazy) extractor.
+    fn extract_with_query(&self, item: &T, query: &str) -> Vec<String> {
+        if self.skips(item) {
+            return Vec::new();
+        }
+        self.extract_unchecked(item, query)
+    }
+
+    /// Iterate over the values this key extracts from `item`.
+    ///
+    /// Nothing is extracted until the first call to `next`, so an iterator
+    /// that is never advanced costs nothing. The extractor itself still
+    /// produces all of its values at once; the iterator then hands them out
+    /// one by one, in the same order as [`extract`](Self::extract).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::new(|s: &String| s.split(',').map(str::to_owned).collect());
+    /// let item = "red,green,blue".to_owned();
+    /// let first_two: Vec<String> = key.extract_iter(&item).take(2).collect();
+    /// assert_eq!(first_two, vec!["red", "green"]);
+    /// ```
+    pub fn extract_iter<'a>(&'a self, item: &'a T) -> impl Iterator<Item = String> + 'a {
+        let mut values: Option<std::vec::IntoIter<String>> = None;
+        std::iter::from_fn(move || {
+            values
+                .get_or_insert_with(|| self.extract(item).into_iter())
+                .next()
+        })
+    }
+
+    /// Extract values for ranking, without consulting the
+    /// [`Key::skip_if`] predicate. With `strict`, the values are checked as
+    /// [`Key::require_non_empty`] does.
+    fn extract_for_ranking(&self, item: &T, pq: &PreparedQuery, strict: bool) -> Vec<String> {
+        let values = self.extract_unchecked(item, &pq.lower);
+        if strict {
+            drop_empty_values(values)
+        } else {
+            values
+        }
+    }
+
+    /// Extract values without consulting the [`Key::skip_if`] predicate.
+    fn extract_unchecked(&self, item: &T, query: &str) -> Vec<String> {
+        self.count_call();
+        match &self.source {
+            KeySource::Extract(extractor) => extractor(item, query),
+            KeySource::Select(keys) => keys
+                .iter()
+                .flat_map(|key| key.extract_with_query(item, query))
+                .collect(),
+        }
+    }
+
+    /// Returns the per-key threshold override, if set.
+    ///
+    /// When `Some`, matches on this key must meet or exceed this ranking.
+    /// When `None`, the global threshold applies.
+    pub fn threshold_value(&self) -> Option<&Ranking> {
+        self.threshold.as_ref()
+    }
+
+    /// Returns the maximum ranking this key can contribute.
+    pub fn max_ranking_value(&self) -> &Ranking {
+        &self.max_ranking
+    }
+
+    /// Returns the minimum ranking this key can contribute.
+    pub fn min_ranking_value(&self) -> &Ranking {
+        &self.min_ranking
+    }
+
+    /// Returns the factor applied to this key's ranks.
+    pub fn weight_value(&self) -> f64 {
+        self.weight
+    }
+
+    /// Returns this key's tiebreaking priority.
+    pub fn priority_value(&self) -> usize {
+        self.priority
+    }
+}
+
+impl Key<HashMap<String, String>> {
+    /// Create a key that looks up a dot-separated `path` in a string map.
+    ///
+    /// Shorthand for [`Key::from_nested_extractor`] on
+    /// `HashMap<String, String>` items. Since the map is flat, only
+    /// single-segment paths find a value; use
+    /// [`Key::from_nested_extractor`] directly on a map of maps to follow
+    /// longer paths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use matchsorter::key::Key;
+    ///
+    /// let key = Key::from_dot_path_on_map("city");
+    /// let row = HashMap::from([("city".to_owned(), "Lisbon".to_owned())]);
+    /// assert_eq!(key.extract(&row), vec!["Lisbon"]);
+    /// ```
+    pub fn from_dot_path_on_map(path: &str) -> Self {
+        Self::from_nested_extractor(path)
+    }
+}
+
+thread_local! {
+    /// Formatting buffer reused by [`Key::from_display_cached`].
+    static DISPLAY_BUF: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Format `value` into the thread-local buffer and copy it out.
+///
+/// Falls back to a plain `to_string` if the buffer is already borrowed,
+/// which only happens if `value`'s `Display` impl itself runs a
+/// `from_display_cached` extractor.
+fn format_with_buffer<V: Display + ?Sized>(value: &V) -> String {
+    DISPLAY_BUF.with(|buf| match buf.try_borrow_mut() {
+        Ok(mut buf) => {
+            buf.clear();
+            write!(buf, "{value}").expect("writing to a String cannot fail");
+            buf.as_str().to_owned()
+        }
+        Err(_) => value.to_string(),
+    })
+}
+
+/// Apply `trim` to `value`, reusing `value` itself when nothing was
+/// trimmed.
+fn trim_owned(value: String, trim: impl Fn(&str) -> &str) -> String {
+    let trimmed = trim(&value);
+    if trimmed.len() == value.len() {
+        value
+    } else {
+        trimmed.to_owned()
+    }
+}
+
+/// Lowercase `value` and replace each whitespace-separated word with
+/// `stem_fn(word)`, keeping the whitespace between words unchanged.
+///
+/// Shared by [`Key::with_stemmer`] and the query-side
+/// [`MatchSorterOptions::query_stemmer`].
+pub(crate) fn stem_words<F>(value: &str, stem_fn: &F) -> String
+where
+    F: Fn(&str) -> String + ?Sized,
+{
+    let lower = value.to_lowercase();
+    let mut out = String::with_capacity(lower.len());
+    let mut word_start: Option<usize> = None;
+    for (i, c) in lower.char_indices() {
+        if c.is_whitespace() {
+            if let Some(start) = word_start.take() {
+                out.push_str(&stem_fn(&lower[start..i]));
+            }
+            out.push(c);
+        } else if word_start.is_none() {
+            word_start = Some(i);
+        }
+    }
+    if let Some(start) = word_start {
+        out.push_str(&stem_fn(&lower[start..]));
+    }
+    out
+}
+
+/// A type whose fields can be looked up by name, for
+/// [`Key::from_nested_extractor`] paths such as `"address.city"`.
+///
+/// Only [`get_field`](NestedExtractor::get_field) is required. Implement
+/// [`get_nested`](NestedExtractor::get_nested) for fields holding another
+/// `NestedExtractor`, and [`get_list`](NestedExtractor::get_list) for
+/// fields indexable with `field[i]`.
+///
+/// `HashMap<String, String>` implements this trait by key lookup, and
+/// `HashMap<String, V>` by returning the nested `V`, so maps of maps can be
+/// walked without a custom implementation.
+pub trait NestedExtractor {
+    /// Returns the string value of `field`, or `None` if it has none.
+    fn get_field(&self, field: &str) -> Option<String>;
+
+    /// Returns the elements of `field` for `field[i]` path segments.
+    ///
+    /// Defaults to the single value from
+    /// [`get_field`](NestedExtractor::get_field), if any.
+    fn get_list(&self, field: &str) -> Vec<String> {
+        self.get_field(field).into_iter().collect()
+    }
+
+    /// Returns the nested value stored in `field`, for paths that continue
+    /// past it. Defaults to `None`.
+    fn get_nested(&self, field: &str) -> Option<&dyn NestedExtractor> {
+        let _ = field;
+        None
+    }
+}
+
+impl NestedExtractor for HashMap<String, String> {
+    fn get_field(&self, field: &str) -> Option<String> {
+        self.get(field).cloned()
+    }
+}
+
+impl<V: NestedExtractor> NestedExtractor for HashMap<String, V> {
+    fn get_field(&self, _field: &str) -> Option<String> {
+        None
+    }
+
+    fn get_nested(&self, field: &str) -> Option<&dyn NestedExtractor> {
+        self.get(field).map(|value| value as &dyn NestedExtractor)
+    }
+}
+
+/// A parsed [`Key::from_nested_extractor`] path.
+struct DotPath {
+    /// Segments resolved with [`NestedExtractor::get_nested`].
+    parents: Vec<String>,
+    /// The last segment's field name.
+    field: String,
+    /// The index from a trailing `[i]` on the last segment.
+    index: Option<usize>,
+}
+
+impl DotPath {
+    /// Parse `path`, returning `None` if any segment is empty or an index
+    /// appears anywhere but the last segment or is not a number.
+    fn parse(path: &str) -> Option<Self> {
+        let mut segments: Vec<&str> = path.split('.').collect();
+        let last = segments.pop()?;
+        if segments.iter().any(|s| s.is_empty() || s.contains('[')) {
+            return None;
+        }
+        let (field, index) = match last.strip_suffix(']') {
+            Some(rest) => {
+                let (field, index) = rest.split_once('[')?;
+                (field, Some(index.parse().ok()?))
+            }
+            None => (last, None),
+        };
+        if field.is_empty() || field.contains('[') {
+            return None;
+        }
+        Some(Self {
+            parents: segments.into_iter().map(str::to_owned).collect(),
+            field: field.to_owned(),
+            index,
+        })
+    }
+
+    /// Follow the path through `item`, returning the value it names.
+    fn resolve(&self, item: &dyn NestedExtractor) -> Option<String> {
+        let mut current = item;
+        for parent in &self.parents {
+            current = current.get_nested(parent)?;
+        }
+        match self.index {
+            Some(index) => current.get_list(&self.field).into_iter().nth(index),
+            None => current.get_field(&self.field),
+        }
+    }
+}
+
+/// Boxed future returned by [`AsyncKey::extract_async`].
+#[cfg(feature = "async")]
+pub type ExtractFuture<'a> = Pin<Box<dyn Future<Output = Vec<String>> + 'a>>;
+
+/// A key whose values are produced asynchronously, such as by a database
+/// lookup or an HTTP request.
+///
+/// Used by [`match_sorter_async`](crate::match_sorter_async) through
+/// [`AsyncMatchSorterOptions::keys`](crate::AsyncMatchSorterOptions::keys).
+/// The method returns a boxed future rather than being an `async fn` so
+/// that the trait stays object safe and keys can be stored as
+/// `Box<dyn AsyncKey<T>>`.
+///
+/// Any `Fn(&T) -> impl Future<Output = Vec<String>>` closure whose future
+/// does not borrow the item implements the trait; implement it by hand
+/// when the future needs to borrow from the item or the key.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::key::{AsyncKey, ExtractFuture};
+///
+/// struct User { id: u32 }
+///
+/// struct DisplayName;
+///
+/// impl AsyncKey<User> for DisplayName {
+///     fn extract_async<'a>(&'a self, user: &'a User) -> ExtractFuture<'a> {
+///         Box::pin(async move { vec![format!("user-{}", user.id)] })
+///     }
+/// }
+///
+/// // Closures work too, as long as the future owns its data.
+/// let by_id = |user: &User| {
+///     let id = user.id;
+///     async move { vec![id.to_string()] }
+/// };
+/// let keys: Vec<Box<dyn AsyncKey<User>>> = vec![Box::new(DisplayName), Box::new(by_id)];
+/// assert_eq!(keys.len(), 2);
+/// ```
+#[cfg(feature = "async")]
+pub trait AsyncKey<T> {
+    /// Produce the values of `item` to rank against the query.
+    fn extract_async<'a>(&'a self, item: &'a T) -> ExtractFuture<'a>;
+}
+
+#[cfg(feature = "async")]
+impl<T, F, Fut> AsyncKey<T> for F
+where
+    F: Fn(&T) -> Fut,
+    Fut: Future<Output = Vec<String>> + 'static,
+{
+    fn extract_async<'a>(&'a self, item: &'a T) -> ExtractFuture<'a> {
+        Box::pin(self(item))
+    }
+}
+
+/// The result of ranking a single item against a query across all keys.
+///
 /// Captures which key and value produced the best match, along with the
 /// resulting ranking and any per-key threshold that applied.
 ///
@@ -499,570 +3540,2894 @@ impl<T> Key<T> {
 ///     ranked_value: "hello".to_owned(),
 ///     key_index: 0,
 ///     key_threshold: None,
+///     matched_key_alias: None,
+///     tie_priority: None,
 /// };
 /// assert_eq!(info.rank, Ranking::Contains);
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+///
+/// `RankingInfo` is [`Eq`] and [`Hash`] (see the hashing notes on
+/// [`Ranking`]), so it can serve as a cache key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RankingInfo {
     /// The ranking score for the best-matching key/value combination.
     pub rank: Ranking,
 
-    /// The string value that produced the best match.
-    pub ranked_value: String,
+    /// The string value that produced the best match.
+    pub ranked_value: String,
+
+    /// Index of the key (in the flattened key-values list) that produced
+    /// the best match.
+    pub key_index: usize,
+
+    /// Per-key threshold override from the winning key, or `None` if the
+    /// key uses the global threshold.
+    pub key_threshold: Option<Ranking>,
+
+    /// The winning key's [alias](Key::alias), or `None` if it has none.
+    pub matched_key_alias: Option<String>,
+
+    /// The winning key's [tie priority](Key::tie_priority) for the item,
+    /// or `None` if it has none.
+    pub tie_priority: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- Helper types for testing ---
+
+    #[derive(Debug)]
+    struct User {
+        name: String,
+        email: String,
+        tags: Vec<String>,
+    }
+
+    fn sample_user() -> User {
+        User {
+            name: "Alice".to_owned(),
+            email: "alice@example.com".to_owned(),
+            tags: vec!["admin".to_owned(), "staff".to_owned()],
+        }
+    }
+
+    // --- Key::new tests ---
+
+    #[test]
+    fn new_accepts_closure_returning_vec_string() {
+        let key = Key::new(|u: &User| vec![u.name.clone()]);
+        let values = key.extract(&sample_user());
+        assert_eq!(values, vec!["Alice"]);
+    }
+
+    #[test]
+    fn new_default_threshold_is_none() {
+        let key = Key::new(|_: &User| vec![]);
+        assert_eq!(key.threshold, None);
+    }
+
+    #[test]
+    fn new_default_min_ranking_is_no_match() {
+        let key = Key::new(|_: &User| vec![]);
+        assert_eq!(key.min_ranking, Ranking::NoMatch);
+    }
+
+    #[test]
+    fn new_default_max_ranking_is_case_sensitive_equal() {
+        let key = Key::new(|_: &User| vec![]);
+        assert_eq!(key.max_ranking, Ranking::CaseSensitiveEqual);
+    }
+
+    // --- Key::from_fn tests ---
+
+    #[test]
+    fn from_fn_single_value_extraction() {
+        let key = Key::<User>::from_fn(|u| u.name.as_str());
+        let values = key.extract(&sample_user());
+        assert_eq!(values, vec!["Alice"]);
+    }
+
+    #[test]
+    fn from_fn_equivalent_to_new_with_vec() {
+        let user = sample_user();
+        let key_new = Key::new(|u: &User| vec![u.name.clone()]);
+        let key_fn = Key::<User>::from_fn(|u| u.name.as_str());
+
+        let values_new = key_new.extract(&user);
+        let values_fn = key_fn.extract(&user);
+        assert_eq!(values_new, values_fn);
+    }
+
+    #[test]
+    fn from_fn_default_attributes() {
+        let key = Key::<User>::from_fn(|u| u.name.as_str());
+        assert_eq!(key.threshold, None);
+        assert_eq!(key.min_ranking, Ranking::NoMatch);
+        assert_eq!(key.max_ranking, Ranking::CaseSensitiveEqual);
+    }
+
+    // --- Key::from_fn_multi tests ---
+
+    #[test]
+    fn from_fn_multi_extracts_multiple_values() {
+        let key = Key::<User>::from_fn_multi(|u| u.tags.iter().map(|t| t.as_str()).collect());
+        let values = key.extract(&sample_user());
+        assert_eq!(values, vec!["admin", "staff"]);
+    }
+
+    #[test]
+    fn from_fn_multi_default_attributes() {
+        let key = Key::<User>::from_fn_multi(|u| u.tags.iter().map(|t| t.as_str()).collect());
+        assert_eq!(key.threshold, None);
+        assert_eq!(key.min_ranking, Ranking::NoMatch);
+        assert_eq!(key.max_ranking, Ranking::CaseSensitiveEqual);
+    }
+
+    #[test]
+    fn from_fn_multi_empty_vec() {
+        let key = Key::<User>::from_fn_multi(|_| vec![]);
+        let values = key.extract(&sample_user());
+        assert!(values.is_empty());
+    }
+
+    // --- Key::from_fn_lazy tests ---
+
+    /// Tags of the user that contain the query.
+    fn matching_tags(u: &User, query: &str) -> Vec<String> {
+        u.tags
+            .iter()
+            .filter(|tag| tag.contains(query))
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn from_fn_lazy_tailors_values_to_query() {
+        let opts = MatchSorterOptions {
+            keys: vec![Key::from_fn_lazy(matching_tags)],
+            ..Default::default()
+        };
+        let user = sample_user();
+        let info = get_highest_ranking(&user, &opts.keys, "sta", &opts);
+        assert_eq!(info.rank, Ranking::StartsWith);
+        assert_eq!(info.ranked_value, "staff");
+        let info = get_highest_ranking(&user, &opts.keys, "adm", &opts);
+        assert_eq!(info.ranked_value, "admin");
+        let info = get_highest_ranking(&user, &opts.keys, "xyz", &opts);
+        assert_eq!(info.rank, Ranking::NoMatch);
+    }
+
+    #[test]
+    fn from_fn_lazy_receives_prepared_lowercased_query() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&seen);
+        let key = Key::from_fn_lazy(move |u: &User, query: &str| {
+            log.borrow_mut().push(query.to_owned());
+            vec![u.name.clone()]
+        })
+        // Wrapping the extractor keeps passing the query through.
+        .deduplicate_values();
+        let opts = MatchSorterOptions {
+            keys: vec![key],
+            ..Default::default()
+        };
+        let user = sample_user();
+        get_highest_ranking(&user, &opts.keys, "AL\u{00cd}", &opts);
+        assert_eq!(*seen.borrow(), vec!["ali"]);
+
+        // Plain extraction has no query.
+        opts.keys[0].extract(&user);
+        assert_eq!(seen.borrow().last().map(String::as_str), Some(""));
+    }
+
+    #[test]
+    fn from_fn_lazy_mixes_with_other_keys() {
+        let opts = MatchSorterOptions {
+            keys: vec![
+                Key::<User>::from_fn(|u| u.name.as_str()),
+                Key::from_fn_lazy(matching_tags),
+                Key::from_fn(|u: &User| u.email.as_str()),
+            ],
+            ..Default::default()
+        };
+        let user = sample_user();
+        let info = get_highest_ranking(&user, &opts.keys, "alice", &opts);
+        assert_eq!(info.rank, Ranking::Equal);
+        assert_eq!(info.key_index, 0);
+        // Only the matching tag is extracted, so the email comes right
+        // after it in the flattened order.
+        let info = get_highest_ranking(&user, &opts.keys, "staff", &opts);
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.key_index, 1);
+        let info = get_highest_ranking(&user, &opts.keys, "alice@", &opts);
+        assert_eq!(info.rank, Ranking::StartsWith);
+        assert_eq!(info.key_index, 1);
+    }
+
+    // --- Key::with_soundex tests ---
+
+    #[cfg(feature = "phonetic")]
+    #[test]
+    fn with_soundex_extracts_original_then_code() {
+        let key = Key::<User>::with_soundex(|u| u.name.as_str());
+        let values = key.extract(&sample_user());
+        assert_eq!(values, vec!["Alice", "A420"]);
+    }
+
+    #[cfg(feature = "phonetic")]
+    #[test]
+    fn with_soundex_omits_code_for_letterless_value() {
+        let key = Key::<String>::with_soundex(|s| s.as_str());
+        let values = key.extract(&"42".to_owned());
+        assert_eq!(values, vec!["42"]);
+    }
+
+    // --- Key::split_on / split_whitespace tests ---
+
+    #[test]
+    fn split_on_drops_empty_parts() {
+        let key = Key::<String>::split_on(',', |s| s.as_str());
+        assert_eq!(key.extract(&",a,,b,".to_owned()), vec!["a", "b"]);
+        assert!(key.extract(&",,,".to_owned()).is_empty());
+        assert!(key.extract(&String::new()).is_empty());
+    }
+
+    #[test]
+    fn split_whitespace_handles_unicode_spaces() {
+        let key = Key::<String>::split_whitespace(|s| s.as_str());
+        assert_eq!(
+            key.extract(&"a\u{00a0}b\t\tc\n".to_owned()),
+            vec!["a", "b", "c"]
+        );
+    }
+
+    #[test]
+    fn split_values_ranked_independently() {
+        let keys = vec![Key::<String>::split_on(' ', |s| s.as_str())];
+        let info = get_highest_ranking(&"admin staff".to_owned(), &keys, "admin", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.ranked_value, "admin");
+        assert_eq!(info.key_index, 0);
+
+        // The second token wins and reports its own key_index.
+        let info = get_highest_ranking(&"admin staff".to_owned(), &keys, "staff", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.ranked_value, "staff");
+        assert_eq!(info.key_index, 1);
+    }
+
+    // --- Key::trim tests ---
+
+    #[test]
+    fn trim_variants_strip_expected_ends() {
+        let padded = " \tapple \n".to_owned();
+        let base = || Key::<String>::from_fn(String::as_str);
+        assert_eq!(base().trim().extract(&padded), vec!["apple"]);
+        assert_eq!(base().trim_start().extract(&padded), vec!["apple \n"]);
+        assert_eq!(base().trim_end().extract(&padded), vec![" \tapple"]);
+        assert_eq!(
+            base()
+                .trim_matches(|c| c == '*')
+                .extract(&"**apple*".to_owned()),
+            vec!["apple"]
+        );
+        assert_eq!(base().trim().extract(&"   ".to_owned()), vec![""]);
+    }
+
+    #[test]
+    fn trim_reaches_case_sensitive_equal() {
+        let item = " apple ".to_owned();
+        let untrimmed = [Key::<String>::from_fn(String::as_str)];
+        let trimmed = [Key::<String>::from_fn(String::as_str).trim()];
+        assert_eq!(
+            get_highest_ranking(&item, &untrimmed, "apple", &default_opts()).rank,
+            Ranking::WordStartsWith
+        );
+        let info = get_highest_ranking(&item, &trimmed, "apple", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.ranked_value, "apple");
+    }
+
+    #[test]
+    fn trim_owned_reuses_untrimmed_buffer() {
+        let value = "apple".to_owned();
+        let ptr = value.as_ptr();
+        let kept = trim_owned(value, str::trim);
+        assert_eq!(kept, "apple");
+        assert_eq!(kept.as_ptr(), ptr);
+
+        let trimmed = trim_owned(" apple".to_owned(), str::trim);
+        assert_eq!(trimmed, "apple");
+    }
+
+    // --- Key::join tests ---
+
+    fn joined_key(separator: &str) -> Key<Vec<String>> {
+        Key::join(Vec::clone, separator)
+    }
+
+    fn tag_list(values: &[&str]) -> Vec<String> {
+        values.iter().map(|&v| v.to_owned()).collect()
+    }
+
+    #[test]
+    fn join_ranks_values_as_one_string() {
+        let item = tag_list(&["fruit", "tropical"]);
+        let keys = [joined_key(" ")];
+        let rank = |query| get_highest_ranking(&item, &keys, query, &default_opts());
+        assert_eq!(rank("fruit").rank, Ranking::StartsWith);
+        assert_eq!(rank("fruit trop").rank, Ranking::StartsWith);
+        assert_eq!(rank("tropic").rank, Ranking::WordStartsWith);
+        assert_eq!(rank("uit trop").rank, Ranking::Contains);
+        assert_eq!(rank("fruit").ranked_value, "fruit tropical");
+    }
+
+    #[test]
+    fn join_separator_changes_word_boundaries() {
+        let item = tag_list(&["fruit", "tropical"]);
+        let rank = |separator, query| {
+            get_highest_ranking(&item, &[joined_key(separator)], query, &default_opts()).rank
+        };
+        // The space after the comma still starts a word...
+        assert_eq!(rank(", ", "tropic"), Ranking::WordStartsWith);
+        // ...but a query spanning the values must now include the comma.
+        assert_eq!(rank(", ", "fruit, trop"), Ranking::StartsWith);
+        assert!(rank(", ", "fruit trop") < Ranking::Contains);
+        // With no whitespace in the separator there is no second word.
+        assert_eq!(rank("/", "tropic"), Ranking::Contains);
+    }
+
+    #[test]
+    fn join_empty_list_yields_no_value() {
+        let key = joined_key(" ");
+        assert!(key.extract(&Vec::new()).is_empty());
+        assert_eq!(key.extract(&tag_list(&["solo"])), vec!["solo"]);
+        assert_eq!(key.extract(&tag_list(&["", ""])), vec![" "]);
+    }
+
+    #[test]
+    fn split_best_token_wins() {
+        let keys = vec![Key::<String>::split_whitespace(|s| s.as_str())];
+        let info = get_highest_ranking(
+            &"moderator admins staff".to_owned(),
+            &keys,
+            "adm",
+            &default_opts(),
+        );
+        assert_eq!(info.rank, Ranking::StartsWith);
+        assert_eq!(info.ranked_value, "admins");
+    }
+
+    #[test]
+    fn max_values_truncates() {
+        let key = Key::<String>::split_whitespace(|s| s.as_str()).max_values(2);
+        assert_eq!(key.extract(&"a b c".to_owned()), vec!["a", "b"]);
+        let key = Key::<String>::split_whitespace(|s| s.as_str()).max_values(0);
+        assert!(key.extract(&"a b c".to_owned()).is_empty());
+    }
+
+    // --- extract_nth / extract_slice tests ---
+
+    fn csv_key() -> Key<String> {
+        Key::split_on(',', |s: &String| s.as_str())
+    }
+
+    #[test]
+    fn extract_nth_in_and_out_of_bounds() {
+        let item = "a,b,c".to_owned();
+        assert_eq!(csv_key().extract_nth(2).extract(&item), vec!["c"]);
+        assert!(csv_key().extract_nth(3).extract(&item).is_empty());
+        assert!(csv_key().extract_nth(usize::MAX).extract(&item).is_empty());
+    }
+
+    #[test]
+    fn extract_first_matches_nth_zero() {
+        for item in ["a,b,c", "x", ""] {
+            let item = item.to_owned();
+            assert_eq!(
+                csv_key().extract_first().extract(&item),
+                csv_key().extract_nth(0).extract(&item)
+            );
+        }
+    }
+
+    #[test]
+    fn extract_last_values() {
+        assert_eq!(
+            csv_key().extract_last().extract(&"a,b,c".to_owned()),
+            vec!["c"]
+        );
+        assert!(csv_key().extract_last().extract(&String::new()).is_empty());
+    }
+
+    #[test]
+    fn extract_slice_ranges() {
+        let item = "a,b,c,d".to_owned();
+        assert_eq!(csv_key().extract_slice(1..3).extract(&item), vec!["b", "c"]);
+        assert_eq!(
+            csv_key().extract_slice(2..10).extract(&item),
+            vec!["c", "d"]
+        );
+        assert!(csv_key().extract_slice(5..9).extract(&item).is_empty());
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 3..1;
+        assert!(csv_key().extract_slice(reversed).extract(&item).is_empty());
+    }
+
+    #[test]
+    fn extract_wrappers_keep_ranking_attributes() {
+        let key = csv_key()
+            .threshold(Ranking::Contains)
+            .min_ranking(Ranking::Acronym)
+            .max_ranking(Ranking::StartsWith)
+            .extract_nth(1);
+        assert_eq!(key.threshold_value(), Some(&Ranking::Contains));
+        assert_eq!(key.min_ranking_value(), &Ranking::Acronym);
+        assert_eq!(key.max_ranking_value(), &Ranking::StartsWith);
+    }
+
+    // --- preprocess_values tests ---
+
+    #[test]
+    fn preprocess_values_runs_once_per_extract() {
+        let calls = Rc::new(std::cell::Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let key = csv_key().preprocess_values(move |values| {
+            counter.set(counter.get() + 1);
+            values
+        });
+        let item = "a,b,c,d".to_owned();
+        for expected in 1..=3 {
+            assert_eq!(key.extract(&item), vec!["a", "b", "c", "d"]);
+            assert_eq!(calls.get(), expected);
+        }
+    }
+
+    #[test]
+    fn deduplicate_values_removes_exact_duplicates() {
+        let key = csv_key().deduplicate_values();
+        assert_eq!(
+            key.extract(&"red,blue,red,Red,blue,red ".to_owned()),
+            vec!["red", "blue", "Red", "red "]
+        );
+        assert!(key.extract(&String::new()).is_empty());
+    }
+
+    #[test]
+    fn preprocessed_order_decides_key_index_ties() {
+        let item = "zeta apple,alpha apple".to_owned();
+        let rank = |key: Key<String>| {
+            let opts = MatchSorterOptions {
+                keys: vec![key],
+                ..Default::default()
+            };
+            get_highest_ranking(&item, &opts.keys, "apple", &opts)
+        };
+        // Both values rank `WordStartsWith`; the first one wins.
+        let info = rank(csv_key());
+        assert_eq!(info.rank, Ranking::WordStartsWith);
+        assert_eq!(info.ranked_value, "zeta apple");
+        let info = rank(csv_key().sort_values());
+        assert_eq!(info.ranked_value, "alpha apple");
+        assert_eq!(info.key_index, 0);
+        let info = rank(csv_key().preprocess_values(|mut values| {
+            values.reverse();
+            values
+        }));
+        assert_eq!(info.ranked_value, "alpha apple");
+    }
+
+    // --- prefix boost tests ---
+
+    fn key_rank(key: Key<String>, value: &str, query: &str) -> Ranking {
+        let opts = MatchSorterOptions::default();
+        get_highest_ranking(&value.to_owned(), &[key], query, &opts).rank
+    }
+
+    fn plain_key() -> Key<String> {
+        Key::from_fn(|s: &String| s.as_str())
+    }
+
+    #[test]
+    fn prefix_boost_leaves_starts_with_unchanged() {
+        assert_eq!(
+            key_rank(plain_key().prefix_boost(), "apple", "app"),
+            Ranking::StartsWith
+        );
+        assert_eq!(
+            key_rank(plain_key().prefix_boost(), "App", "app"),
+            Ranking::Equal
+        );
+    }
+
+    #[test]
+    fn prefix_boost_requires_prefix_alignment() {
+        // "pineapple" contains "app" but does not start with it.
+        assert_eq!(
+            key_rank(plain_key().prefix_boost(), "pineapple", "app"),
+            Ranking::Contains
+        );
+    }
+
+    #[test]
+    fn prefix_boost_promotes_past_max_ranking() {
+        let capped = || plain_key().max_ranking(Ranking::Contains);
+        assert_eq!(key_rank(capped(), "apple", "app"), Ranking::Contains);
+        assert_eq!(
+            key_rank(capped().prefix_boost(), "Apple", "app"),
+            Ranking::StartsWith
+        );
+        assert_eq!(
+            key_rank(capped().prefix_boost(), "pineapple", "app"),
+            Ranking::Contains
+        );
+    }
+
+    #[test]
+    fn prefix_boost_never_promotes_no_match() {
+        assert_eq!(
+            key_rank(
+                plain_key().prefix_boost().word_prefix_boost(),
+                "banana",
+                "app"
+            ),
+            Ranking::NoMatch
+        );
+    }
+
+    #[test]
+    fn word_prefix_boost_promotes_to_word_starts_with() {
+        let capped = || plain_key().max_ranking(Ranking::Acronym);
+        assert_eq!(
+            key_rank(capped().word_prefix_boost(), "green apple", "app"),
+            Ranking::WordStartsWith
+        );
+        assert_eq!(
+            key_rank(capped().word_prefix_boost(), "pineapple", "app"),
+            Ranking::Acronym
+        );
+        assert_eq!(
+            key_rank(capped().word_prefix_boost(), "apple", "app"),
+            Ranking::WordStartsWith
+        );
+        assert_eq!(
+            key_rank(capped().word_prefix_boost().prefix_boost(), "apple", "app"),
+            Ranking::StartsWith
+        );
+    }
+
+    // --- lowercased / uppercased / map tests ---
+
+    #[test]
+    fn lowercased_rewrites_extracted_values() {
+        let key = plain_key().lowercased();
+        assert_eq!(key.extract(&"ALICE".to_owned()), vec!["alice"]);
+        assert_eq!(key.extract(&"\u{00c9}LAN".to_owned()), vec!["\u{00e9}lan"]);
+    }
+
+    #[test]
+    fn lowercased_ranking_trade_off() {
+        assert_eq!(key_rank(plain_key(), "ALICE", "alice"), Ranking::Equal);
+        assert_eq!(
+            key_rank(plain_key().lowercased(), "ALICE", "alice"),
+            Ranking::CaseSensitiveEqual
+        );
+        // A cased query can no longer hit the case-sensitive tier.
+        assert_eq!(
+            key_rank(plain_key(), "Alice", "Alice"),
+            Ranking::CaseSensitiveEqual
+        );
+        assert_eq!(
+            key_rank(plain_key().lowercased(), "Alice", "Alice"),
+            Ranking::Equal
+        );
+    }
+
+    #[test]
+    fn uppercased_rewrites_extracted_values() {
+        let key = plain_key().uppercased();
+        assert_eq!(key.extract(&"alice".to_owned()), vec!["ALICE"]);
+        assert_eq!(key_rank(key, "alice", "ALICE"), Ranking::CaseSensitiveEqual);
+    }
+
+    #[test]
+    fn lowercased_chains_with_map() {
+        let key = plain_key()
+            .lowercased()
+            .map(|v| v.replace(' ', "_"))
+            .threshold(Ranking::Equal);
+        assert_eq!(key.extract(&"Alice Smith".to_owned()), vec!["alice_smith"]);
+        assert_eq!(key.threshold_value(), Some(&Ranking::Equal));
+    }
+
+    // --- from_display / from_to_string tests ---
+
+    struct Record {
+        id: u64,
+        score: f64,
+    }
+
+    // Required by `rank_and_sort`, but unused in keys mode.
+    impl crate::AsMatchStr for Record {
+        fn as_match_str(&self) -> &str {
+            ""
+        }
+    }
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record {
+                id: 1042,
+                score: 1.0,
+            },
+            Record {
+                id: 42,
+                score: 0.25,
+            },
+            Record { id: 7, score: 42.5 },
+            Record {
+                id: 4200,
+                score: 3.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn from_display_formats_integers_as_decimal() {
+        let key = Key::<Record>::from_display(|r| &r.id);
+        assert_eq!(key.extract(&records()[0]), vec!["1042"]);
+        assert_eq!(
+            key_rank_of(&key, &records()[1], "42"),
+            Ranking::CaseSensitiveEqual
+        );
+    }
+
+    #[test]
+    fn from_display_uses_display_not_debug() {
+        // `Debug` would render 1.0 as "1.0"; `Display` renders "1".
+        let key = Key::<Record>::from_display(|r| &r.score);
+        assert_eq!(key.extract(&records()[0]), vec!["1"]);
+        let cached = Key::<Record>::from_display_cached(|r| &r.score);
+        assert_eq!(cached.extract(&records()[0]), vec!["1"]);
+        assert_eq!(cached.extract(&records()[1]), vec!["0.25"]);
+    }
+
+    #[test]
+    fn from_display_cached_reuses_buffer_across_calls() {
+        let key = Key::<Record>::from_display_cached(|r| &r.id);
+        let values: Vec<Vec<String>> = records().iter().map(|r| key.extract(r)).collect();
+        assert_eq!(
+            values,
+            vec![vec!["1042"], vec!["42"], vec!["7"], vec!["4200"]]
+        );
+    }
+
+    #[test]
+    fn from_to_string_matches_from_display() {
+        let display = Key::<Record>::from_display(|r| &r.id);
+        let to_string = Key::<Record>::from_to_string(|r| &r.id);
+        for record in records() {
+            assert_eq!(display.extract(&record), to_string.extract(&record));
+        }
+    }
+
+    #[test]
+    fn numeric_keys_match_stringified_items() {
+        let items = records();
+        let numeric = {
+            let opts = MatchSorterOptions {
+                keys: vec![Key::<Record>::from_display(|r| &r.id)],
+                ..Default::default()
+            };
+            let ranked = crate::rank_and_sort(&items, "42", &opts);
+            ranked.iter().map(|ri| ri.index).collect::<Vec<_>>()
+        };
+        let strings: Vec<String> = items.iter().map(|r| r.id.to_string()).collect();
+        let expected: Vec<usize> =
+            crate::match_sorter_order(&strings, "42", &MatchSorterOptions::default());
+        assert_eq!(numeric, expected);
+    }
+
+    fn key_rank_of(key: &Key<Record>, record: &Record, query: &str) -> Ranking {
+        let opts = MatchSorterOptions::default();
+        get_highest_ranking(record, std::slice::from_ref(key), query, &opts).rank
+    }
+
+    // --- Key::first_of tests ---
+
+    #[test]
+    fn first_of_first_some_skips_later_extractors() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let calls = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let key = Key::<User>::first_of(vec![
+            Box::new(|u: &User| Some(u.name.clone())),
+            Box::new(move |u: &User| {
+                counter.set(counter.get() + 1);
+                Some(u.email.clone())
+            }),
+        ]);
+        assert_eq!(key.extract(&sample_user()), vec!["Alice"]);
+        assert_eq!(calls.get(), 0);
+    }
+
+    #[test]
+    fn first_of_all_none_is_empty() {
+        let key = Key::<User>::first_of(vec![Box::new(|_: &User| None), Box::new(|_: &User| None)]);
+        assert!(key.extract(&sample_user()).is_empty());
+    }
+
+    #[test]
+    fn first_of_resolves_to_first_some() {
+        let key = Key::<User>::first_of(vec![
+            Box::new(|_: &User| None),
+            Box::new(|_: &User| Some(String::new())),
+            Box::new(|u: &User| Some(u.email.clone())),
+            Box::new(|u: &User| Some(u.name.clone())),
+        ]);
+        assert_eq!(key.extract(&sample_user()), vec!["alice@example.com"]);
+    }
+
+    #[test]
+    fn first_of_key_index_is_zero_for_any_source() {
+        let keys = vec![Key::<User>::first_of(vec![
+            Box::new(|_: &User| None),
+            Box::new(|u: &User| Some(u.email.clone())),
+        ])];
+        let info = get_highest_ranking(&sample_user(), &keys, "alice", &default_opts());
+        assert_eq!(info.rank, Ranking::StartsWith);
+        assert_eq!(info.key_index, 0);
+    }
+
+    #[test]
+    fn first_of_fns_skips_empty_values() {
+        let fns: Vec<fn(&User) -> &str> = vec![|_| "", |u| u.email.as_str()];
+        let key = Key::first_of_fns(fns);
+        assert_eq!(key.extract(&sample_user()), vec!["alice@example.com"]);
+
+        let fns: Vec<fn(&User) -> &str> = vec![|_| ""];
+        let key = Key::first_of_fns(fns);
+        assert!(key.extract(&sample_user()).is_empty());
+    }
+
+    // --- RankingInfo hashing ---
+
+    #[test]
+    fn ranking_info_usable_as_set_key() {
+        use std::collections::HashSet;
+
+        let info = |rank, value: &str| RankingInfo {
+            rank,
+            ranked_value: value.to_owned(),
+            key_index: 0,
+            key_threshold: None,
+            matched_key_alias: None,
+            tie_priority: None,
+        };
+        let mut set = HashSet::new();
+        assert!(set.insert(info(Ranking::Matches(1.5), "abc")));
+        assert!(!set.insert(info(Ranking::Matches(1.5), "abc")));
+        assert!(set.insert(info(Ranking::Matches(1.25), "abc")));
+        assert!(set.insert(info(Ranking::Contains, "abc")));
+        assert_eq!(set.len(), 3);
+    }
+
+    // --- Validation tests ---
+
+    #[test]
+    fn validate_receives_extracted_values() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&seen);
+        let key = Key::<User>::from_fn_multi(|u| u.tags.iter().map(String::as_str).collect())
+            .validate(move |values| {
+                sink.borrow_mut().extend_from_slice(values);
+                Ok(())
+            });
+        assert_eq!(key.extract(&sample_user()), vec!["admin", "staff"]);
+        assert_eq!(*seen.borrow(), vec!["admin", "staff"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "key validation failed: nope")]
+    fn validate_panics_with_message() {
+        let key = Key::<User>::from_fn(|u| u.name.as_str()).validate(|_| Err("nope".to_owned()));
+        let _ = key.extract(&sample_user());
+    }
+
+    #[test]
+    #[should_panic(expected = "key validation failed: value 1 is empty")]
+    fn ensure_non_empty_values_rejects_empty() {
+        let key =
+            Key::<User>::new(|u| vec![u.name.clone(), String::new()]).ensure_non_empty_values();
+        let _ = key.extract(&sample_user());
+    }
+
+    #[test]
+    #[should_panic(expected = "key validation failed: value 0 contains a NUL character")]
+    fn ensure_no_nulls_rejects_nul() {
+        let key = Key::<User>::new(|_| vec!["a\0b".to_owned()]).ensure_no_nulls();
+        let _ = key.extract(&sample_user());
+    }
+
+    #[test]
+    #[should_panic(expected = "key validation failed: value 2 duplicates \"admin\"")]
+    fn ensure_unique_values_rejects_duplicates() {
+        let key = Key::<User>::new(|u| {
+            let mut tags = u.tags.clone();
+            tags.push("admin".to_owned());
+            tags
+        })
+        .ensure_unique_values();
+        let _ = key.extract(&sample_user());
+    }
+
+    #[test]
+    fn builtin_validators_accept_valid_values() {
+        let key = Key::<User>::from_fn_multi(|u| u.tags.iter().map(String::as_str).collect())
+            .ensure_non_empty_values()
+            .ensure_no_nulls()
+            .ensure_unique_values();
+        assert_eq!(key.extract(&sample_user()), vec!["admin", "staff"]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "key validation failed: debug")]
+    fn debug_only_validate_runs_in_debug() {
+        let key = Key::<User>::from_fn(|u| u.name.as_str())
+            .debug_only_validate(|_| Err("debug".to_owned()));
+        let _ = key.extract(&sample_user());
+    }
+
+    #[cfg(not(debug_assertions))]
+    #[test]
+    fn debug_only_validate_skipped_in_release() {
+        let key = Key::<User>::from_fn(|u| u.name.as_str())
+            .debug_only_validate(|_| Err("debug".to_owned()));
+        assert_eq!(key.extract(&sample_user()), vec!["Alice"]);
+    }
+
+    // --- Key::require_non_empty tests ---
+
+    #[test]
+    fn require_non_empty_keeps_non_empty_values() {
+        let key = Key::new(|u: &User| u.tags.clone()).require_non_empty();
+        assert_eq!(key.extract(&sample_user()), vec!["admin", "staff"]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "key extracted empty values")]
+    fn require_non_empty_asserts_on_empty_string_in_debug() {
+        let key = Key::<User>::from_fn(|u| u.email.as_str()).require_non_empty();
+        let user = User {
+            email: String::new(),
+            ..sample_user()
+        };
+        let _ = key.extract(&user);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "key extracted empty values")]
+    fn require_non_empty_asserts_on_no_values_in_debug() {
+        let key = Key::new(|u: &User| u.tags.clone()).require_non_empty();
+        let user = User {
+            tags: Vec::new(),
+            ..sample_user()
+        };
+        let _ = key.extract(&user);
+    }
+
+    #[cfg(not(debug_assertions))]
+    #[test]
+    fn require_non_empty_drops_empty_strings_in_release() {
+        let key = Key::new(|u: &User| u.tags.clone()).require_non_empty();
+        let user = User {
+            tags: vec![String::new(), "staff".to_owned(), String::new()],
+            ..sample_user()
+        };
+        assert_eq!(key.extract(&user), vec!["staff"]);
+        let user = User {
+            tags: vec![String::new()],
+            ..sample_user()
+        };
+        assert!(key.extract(&user).is_empty());
+    }
+
+    #[test]
+    fn require_non_empty_or_default_substitutes_default() {
+        let key = Key::new(|u: &User| u.tags.clone()).require_non_empty_or_default("none");
+        let user = |tags: &[&str]| User {
+            tags: tags.iter().map(|&t| t.to_owned()).collect(),
+            ..sample_user()
+        };
+        assert_eq!(key.extract(&user(&["admin", ""])), vec!["admin", "none"]);
+        assert_eq!(key.extract(&user(&[])), vec!["none"]);
+        assert_eq!(key.extract(&user(&["staff"])), vec!["staff"]);
+    }
+
+    // --- Stemming tests ---
+
+    /// Toy stemmer: strips a trailing "ning" or "ing".
+    fn toy_stem(word: &str) -> String {
+        word.strip_suffix("ning")
+            .or_else(|| word.strip_suffix("ing"))
+            .unwrap_or(word)
+            .to_owned()
+    }
+
+    #[test]
+    fn stem_words_lowercases_and_keeps_separators() {
+        assert_eq!(
+            stem_words("Running  Jumping\tJack", &toy_stem),
+            "run  jump\tjack"
+        );
+        assert_eq!(stem_words("", &toy_stem), "");
+        assert_eq!(stem_words("  ", &toy_stem), "  ");
+    }
+
+    #[test]
+    fn with_stemmer_stems_every_value() {
+        let key = Key::<User>::from_fn_multi(|u| u.tags.iter().map(String::as_str).collect())
+            .with_stemmer(toy_stem);
+        let user = User {
+            tags: vec!["Running".to_owned(), "coding".to_owned()],
+            ..sample_user()
+        };
+        assert_eq!(key.extract(&user), vec!["run", "cod"]);
+    }
+
+    #[test]
+    fn with_stemmer_preserves_ranking_attributes() {
+        let key = Key::<User>::from_fn(|u| u.name.as_str())
+            .threshold(Ranking::Contains)
+            .max_ranking(Ranking::StartsWith)
+            .with_stemmer(toy_stem);
+        assert_eq!(key.threshold_value(), Some(&Ranking::Contains));
+        assert_eq!(key.max_ranking_value(), &Ranking::StartsWith);
+    }
+
+    #[test]
+    fn with_stemmer_stemmed_exact_match_is_case_sensitive_equal() {
+        let keys = vec![Key::<User>::from_fn(|u| u.name.as_str()).with_stemmer(toy_stem)];
+        let user = User {
+            name: "Running".to_owned(),
+            ..sample_user()
+        };
+        let info = get_highest_ranking(&user, &keys, "run", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.ranked_value, "run");
+    }
+
+    // --- Builder method tests ---
+
+    #[test]
+    fn threshold_sets_value() {
+        let key = Key::new(|_: &User| vec![]).threshold(Ranking::StartsWith);
+        assert_eq!(key.threshold, Some(Ranking::StartsWith));
+    }
+
+    #[test]
+    fn max_ranking_sets_value() {
+        let key = Key::new(|_: &User| vec![]).max_ranking(Ranking::Contains);
+        assert_eq!(key.max_ranking, Ranking::Contains);
+    }
+
+    #[test]
+    fn min_ranking_sets_value() {
+        let key = Key::new(|_: &User| vec![]).min_ranking(Ranking::Contains);
+        assert_eq!(key.min_ranking, Ranking::Contains);
+    }
+
+    #[test]
+    fn builder_chain_all_three() {
+        let key = Key::new(|u: &User| vec![u.email.clone()])
+            .threshold(Ranking::Acronym)
+            .max_ranking(Ranking::Equal)
+            .min_ranking(Ranking::Contains);
+
+        assert_eq!(key.threshold, Some(Ranking::Acronym));
+        assert_eq!(key.max_ranking, Ranking::Equal);
+        assert_eq!(key.min_ranking, Ranking::Contains);
+    }
+
+    #[test]
+    fn builder_chain_preserves_extractor() {
+        let key = Key::new(|u: &User| vec![u.name.clone()])
+            .threshold(Ranking::StartsWith)
+            .max_ranking(Ranking::Contains)
+            .min_ranking(Ranking::Acronym);
+
+        let values = key.extract(&sample_user());
+        assert_eq!(values, vec!["Alice"]);
+    }
+
+    #[test]
+    fn builder_from_fn_with_chain() {
+        let key = Key::<User>::from_fn(|u| u.email.as_str())
+            .threshold(Ranking::WordStartsWith)
+            .max_ranking(Ranking::StartsWith);
+
+        assert_eq!(key.threshold, Some(Ranking::WordStartsWith));
+        assert_eq!(key.max_ranking, Ranking::StartsWith);
+        // min_ranking left at default
+        assert_eq!(key.min_ranking, Ranking::NoMatch);
+
+        let values = key.extract(&sample_user());
+        assert_eq!(values, vec!["alice@example.com"]);
+    }
+
+    #[test]
+    fn builder_from_fn_multi_with_chain() {
+        let key = Key::<User>::from_fn_multi(|u| u.tags.iter().map(|t| t.as_str()).collect())
+            .min_ranking(Ranking::Contains);
+
+        assert_eq!(key.min_ranking, Ranking::Contains);
+        assert_eq!(key.threshold, None);
+        assert_eq!(key.max_ranking, Ranking::CaseSensitiveEqual);
+
+        let values = key.extract(&sample_user());
+        assert_eq!(values, vec!["admin", "staff"]);
+    }
+
+    // --- Builder override tests ---
+
+    #[test]
+    fn builder_last_call_wins_for_same_method() {
+        let key = Key::new(|_: &User| vec![])
+            .threshold(Ranking::Contains)
+            .threshold(Ranking::StartsWith);
+        assert_eq!(key.threshold, Some(Ranking::StartsWith));
+    }
+
+    #[test]
+    fn builder_matches_variant_in_threshold() {
+        // Ensure Matches(f64) variant works in builder methods.
+        let key = Key::new(|_: &User| vec![])
+            .threshold(Ranking::Matches(1.5))
+            .min_ranking(Ranking::Matches(1.2))
+            .max_ranking(Ranking::Matches(1.8));
+
+        assert_eq!(key.threshold, Some(Ranking::Matches(1.5)));
+        assert_eq!(key.min_ranking, Ranking::Matches(1.2));
+        assert_eq!(key.max_ranking, Ranking::Matches(1.8));
+    }
+
+    // --- RankingInfo tests ---
+
+    #[test]
+    fn ranking_info_construction() {
+        let info = RankingInfo {
+            rank: Ranking::Contains,
+            ranked_value: "hello".to_owned(),
+            key_index: 2,
+            key_threshold: Some(Ranking::StartsWith),
+            matched_key_alias: None,
+            tie_priority: None,
+        };
+
+        assert_eq!(info.rank, Ranking::Contains);
+        assert_eq!(info.ranked_value, "hello");
+        assert_eq!(info.key_index, 2);
+        assert_eq!(info.key_threshold, Some(Ranking::StartsWith));
+    }
+
+    #[test]
+    fn ranking_info_with_no_threshold() {
+        let info = RankingInfo {
+            rank: Ranking::Equal,
+            ranked_value: "world".to_owned(),
+            key_index: 0,
+            key_threshold: None,
+            matched_key_alias: None,
+            tie_priority: None,
+        };
+
+        assert_eq!(info.key_threshold, None);
+    }
+
+    #[test]
+    fn ranking_info_debug_formatting() {
+        let info = RankingInfo {
+            rank: Ranking::Acronym,
+            ranked_value: "test".to_owned(),
+            key_index: 1,
+            key_threshold: None,
+            matched_key_alias: None,
+            tie_priority: None,
+        };
+        let debug_str = format!("{info:?}");
+        assert!(debug_str.contains("Acronym"));
+        assert!(debug_str.contains("test"));
+    }
+
+    #[test]
+    fn ranking_info_clone() {
+        let info = RankingInfo {
+            rank: Ranking::StartsWith,
+            ranked_value: "cloned".to_owned(),
+            key_index: 3,
+            key_threshold: Some(Ranking::Contains),
+            matched_key_alias: None,
+            tie_priority: None,
+        };
+        let cloned = info.clone();
+        assert_eq!(info, cloned);
+    }
+
+    #[test]
+    fn ranking_info_partial_eq() {
+        let a = RankingInfo {
+            rank: Ranking::Contains,
+            ranked_value: "val".to_owned(),
+            key_index: 0,
+            key_threshold: None,
+            matched_key_alias: None,
+            tie_priority: None,
+        };
+        let b = RankingInfo {
+            rank: Ranking::Contains,
+            ranked_value: "val".to_owned(),
+            key_index: 0,
+            key_threshold: None,
+            matched_key_alias: None,
+            tie_priority: None,
+        };
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn ranking_info_partial_eq_different_rank() {
+        let a = RankingInfo {
+            rank: Ranking::Contains,
+            ranked_value: "val".to_owned(),
+            key_index: 0,
+            key_threshold: None,
+            matched_key_alias: None,
+            tie_priority: None,
+        };
+        let b = RankingInfo {
+            rank: Ranking::Equal,
+            ranked_value: "val".to_owned(),
+            key_index: 0,
+            key_threshold: None,
+            matched_key_alias: None,
+            tie_priority: None,
+        };
+        assert_ne!(a, b);
+    }
+
+    // --- Key with primitive types ---
+
+    #[test]
+    fn key_with_string_type() {
+        let key = Key::new(|s: &String| vec![s.clone()]);
+        let values = key.extract(&"hello world".to_owned());
+        assert_eq!(values, vec!["hello world"]);
+    }
+
+    #[test]
+    fn from_fn_with_string_type() {
+        let key = Key::<String>::from_fn(|s| s.as_str());
+        let values = key.extract(&"test".to_owned());
+        assert_eq!(values, vec!["test"]);
+    }
+
+    // --- get_item_values tests ---
+
+    #[test]
+    fn get_item_values_single_value() {
+        let key = Key::<User>::from_fn(|u| u.name.as_str());
+        let values = get_item_values(&sample_user(), &key);
+        assert_eq!(values, vec!["Alice"]);
+    }
+
+    #[test]
+    fn get_item_values_multi_value() {
+        let key = Key::<User>::from_fn_multi(|u| u.tags.iter().map(|t| t.as_str()).collect());
+        let values = get_item_values(&sample_user(), &key);
+        assert_eq!(values, vec!["admin", "staff"]);
+    }
+
+    #[test]
+    fn get_item_values_empty() {
+        let key = Key::new(|_: &User| vec![]);
+        let values = get_item_values(&sample_user(), &key);
+        assert!(values.is_empty());
+    }
+
+    // --- get_highest_ranking tests ---
+
+    fn default_opts<T>() -> MatchSorterOptions<T> {
+        MatchSorterOptions::default()
+    }
+
+    #[test]
+    fn highest_ranking_single_key_exact_match() {
+        // "Alice" queried with "Alice" -> CaseSensitiveEqual
+        let keys = vec![Key::new(|u: &User| vec![u.name.clone()])];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.ranked_value, "Alice");
+        assert_eq!(info.key_index, 0);
+        assert_eq!(info.key_threshold, None);
+    }
+
+    #[test]
+    fn highest_ranking_picks_best_across_multiple_keys() {
+        // Key 0 extracts email ("alice@example.com"), query "Alice" -> Contains
+        // Key 1 extracts name ("Alice"), query "Alice" -> CaseSensitiveEqual
+        let keys: Vec<Key<User>> = vec![
+            Key::new(|u: &User| vec![u.email.clone()]),
+            Key::new(|u: &User| vec![u.name.clone()]),
+        ];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.ranked_value, "Alice");
+        // Key 0 produces 1 value (index 0), key 1 produces 1 value (index 1)
+        assert_eq!(info.key_index, 1);
+    }
+
+    #[test]
+    fn highest_ranking_max_ranking_clamps_down() {
+        // "Alice" queried with "Alice" would normally be CaseSensitiveEqual,
+        // but max_ranking = Contains clamps it down to Contains.
+        let keys = vec![Key::new(|u: &User| vec![u.name.clone()]).max_ranking(Ranking::Contains)];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::Contains);
+    }
+
+    #[test]
+    fn highest_ranking_max_ranking_clamps_starts_with_to_contains() {
+        // "Alice" queried with "ali" -> StartsWith normally, clamped to Contains
+        let keys = vec![Key::new(|u: &User| vec![u.name.clone()]).max_ranking(Ranking::Contains)];
+        let info = get_highest_ranking(&sample_user(), &keys, "ali", &default_opts());
+        assert_eq!(info.rank, Ranking::Contains);
+    }
+
+    #[test]
+    fn highest_ranking_min_ranking_promotes_matches_to_contains() {
+        // "playground" queried with "plgnd" -> Matches(~1.11) normally.
+        // min_ranking = Contains promotes it up to Contains.
+        let item = "playground".to_owned();
+        let keys = vec![Key::new(|s: &String| vec![s.clone()]).min_ranking(Ranking::Contains)];
+        let info = get_highest_ranking(&item, &keys, "plgnd", &default_opts());
+        assert_eq!(info.rank, Ranking::Contains);
+    }
+
+    #[test]
+    fn highest_ranking_min_ranking_does_not_promote_no_match() {
+        // "abc" queried with "xyz" -> NoMatch. min_ranking = Contains should
+        // NOT promote it; NoMatch stays NoMatch.
+        let item = "abc".to_owned();
+        let keys = vec![Key::new(|s: &String| vec![s.clone()]).min_ranking(Ranking::Contains)];
+        let info = get_highest_ranking(&item, &keys, "xyz", &default_opts());
+        assert_eq!(info.rank, Ranking::NoMatch);
+    }
+
+    #[test]
+    fn highest_ranking_tie_break_lower_key_index_wins() {
+        // Both keys produce the same value "Alice" with the same ranking.
+        // The first key's value (key_index 0) should win.
+        let keys: Vec<Key<User>> = vec![
+            Key::new(|u: &User| vec![u.name.clone()]),
+            Key::new(|u: &User| vec![u.name.clone()]),
+        ];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.key_index, 0);
+    }
+
+    #[test]
+    fn highest_ranking_tie_break_with_clamping() {
+        // Key 0: name "Alice", max_ranking = Contains -> clamped to Contains
+        // Key 1: email "alice@example.com", query "alice" -> StartsWith,
+        //        max_ranking = Contains -> clamped to Contains
+        // Both produce Contains, but key_index 0 wins.
+        let keys: Vec<Key<User>> = vec![
+            Key::new(|u: &User| vec![u.name.clone()]).max_ranking(Ranking::Contains),
+            Key::new(|u: &User| vec![u.email.clone()]).max_ranking(Ranking::Contains),
+        ];
+        let info = get_highest_ranking(&sample_user(), &keys, "alice", &default_opts());
+        assert_eq!(info.rank, Ranking::Contains);
+        assert_eq!(info.key_index, 0);
+        assert_eq!(info.ranked_value, "Alice");
+    }
+
+    #[test]
+    fn highest_ranking_key_threshold_reflected() {
+        // Key with a threshold set -- the returned RankingInfo should have
+        // the key's threshold in key_threshold.
+        let keys = vec![Key::new(|u: &User| vec![u.name.clone()]).threshold(Ranking::StartsWith)];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.key_threshold, Some(Ranking::StartsWith));
+    }
+
+    #[test]
+    fn highest_ranking_key_threshold_none_when_not_set() {
+        let keys = vec![Key::new(|u: &User| vec![u.name.clone()])];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.key_threshold, None);
+    }
+
+    #[test]
+    fn highest_ranking_multi_value_key_best_value_wins() {
+        // Key extracts tags: ["admin", "staff"]. Query "admin" matches
+        // "admin" as CaseSensitiveEqual but "staff" as NoMatch.
+        // Best should be CaseSensitiveEqual for "admin".
+        let keys = vec![Key::new(|u: &User| u.tags.clone())];
+        let info = get_highest_ranking(&sample_user(), &keys, "admin", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.ranked_value, "admin");
+        assert_eq!(info.key_index, 0);
+    }
+
+    #[test]
+    fn highest_ranking_flattened_index_across_keys() {
+        // Key 0 extracts tags: ["admin", "staff"] (indices 0, 1)
+        // Key 1 extracts name: ["Alice"] (index 2)
+        // Query "Alice" matches name at CaseSensitiveEqual (best).
+        let keys: Vec<Key<User>> = vec![
+            Key::new(|u: &User| u.tags.clone()),
+            Key::new(|u: &User| vec![u.name.clone()]),
+        ];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.key_index, 2);
+    }
+
+    #[test]
+    fn highest_ranking_no_keys_returns_no_match() {
+        let keys: Vec<Key<User>> = vec![];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::NoMatch);
+    }
+
+    #[test]
+    fn highest_ranking_empty_extractor_returns_no_match() {
+        let keys = vec![Key::new(|_: &User| vec![])];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::NoMatch);
+    }
+
+    // --- Key::select tests ---
+
+    fn name_or_email() -> Key<User> {
+        Key::select(vec![
+            Key::new(|u: &User| vec![u.email.clone()]),
+            Key::new(|u: &User| vec![u.name.clone()]),
+        ])
+    }
+
+    #[test]
+    fn select_picks_best_sub_value() {
+        let keys = vec![name_or_email()];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.ranked_value, "Alice");
+
+        let info = get_highest_ranking(&sample_user(), &keys, "example", &default_opts());
+        assert_eq!(info.rank, Ranking::Contains);
+        assert_eq!(info.ranked_value, "alice@example.com");
+    }
+
+    #[test]
+    fn select_occupies_a_single_key_index() {
+        // Separately, tags (0, 1), email (2) and name (3) precede the last
+        // key; as a select key they collapse into index 0.
+        let keys: Vec<Key<User>> = vec![
+            Key::select(vec![
+                Key::new(|u: &User| u.tags.clone()),
+                Key::new(|u: &User| vec![u.email.clone()]),
+            ]),
+            Key::new(|u: &User| vec![u.name.clone()]),
+        ];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.key_index, 1);
+
+        let info = get_highest_ranking(&sample_user(), &keys, "staff", &default_opts());
+        assert_eq!(info.ranked_value, "staff");
+        assert_eq!(info.key_index, 0);
+
+        // A select key whose sub-keys yield nothing still takes its slot.
+        let keys: Vec<Key<User>> = vec![
+            Key::select(vec![Key::new(|_: &User| vec![])]),
+            Key::new(|u: &User| vec![u.name.clone()]),
+        ];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.key_index, 1);
+    }
+
+    #[test]
+    fn select_outer_ranking_bounds_apply_after_selection() {
+        let capped = vec![name_or_email().max_ranking(Ranking::Contains)];
+        let info = get_highest_ranking(&sample_user(), &capped, "Alice", &default_opts());
+        // The name still wins the selection; the cap applies afterwards.
+        assert_eq!(info.rank, Ranking::Contains);
+        assert_eq!(info.ranked_value, "Alice");
+
+        let floored = vec![name_or_email().min_ranking(Ranking::Contains)];
+        let info = get_highest_ranking(&sample_user(), &floored, "aex", &default_opts());
+        assert_eq!(info.rank, Ranking::Contains);
+        let info = get_highest_ranking(&sample_user(), &floored, "zzz", &default_opts());
+        assert_eq!(info.rank, Ranking::NoMatch);
+    }
+
+    #[test]
+    fn select_honors_sub_key_ranking_bounds() {
+        let keys = vec![Key::select(vec![
+            Key::new(|u: &User| vec![u.name.clone()]).max_ranking(Ranking::Acronym),
+            Key::new(|u: &User| vec![u.email.clone()]),
+        ])];
+        let info = get_highest_ranking(&sample_user(), &keys, "alice", &default_opts());
+        assert_eq!(info.rank, Ranking::StartsWith);
+        assert_eq!(info.ranked_value, "alice@example.com");
+    }
+
+    #[test]
+    fn select_extract_and_value_builders_cover_sub_keys() {
+        let key = name_or_email().uppercased().extract_first();
+        assert_eq!(
+            key.extract(&sample_user()),
+            vec!["ALICE@EXAMPLE.COM", "ALICE"]
+        );
+    }
+
+    // --- Key::union tests ---
+
+    fn name_key() -> Key<User> {
+        Key::new(|u: &User| vec![u.name.clone()])
+    }
+
+    fn tags_key() -> Key<User> {
+        Key::new(|u: &User| u.tags.clone())
+    }
+
+    #[test]
+    fn union_values_all_participate() {
+        let key = name_key().union(tags_key());
+        assert_eq!(key.extract(&sample_user()), vec!["Alice", "admin", "staff"]);
+
+        let keys = vec![key];
+        for (query, value) in [("Alice", "Alice"), ("admin", "admin"), ("sta", "staff")] {
+            let info = get_highest_ranking(&sample_user(), &keys, query, &default_opts());
+            assert_eq!(info.ranked_value, value);
+        }
+    }
+
+    #[test]
+    fn union_best_value_wins_from_either_side() {
+        let keys = vec![tags_key().union(name_key())];
+        let info = get_highest_ranking(&sample_user(), &keys, "alice", &default_opts());
+        assert_eq!(info.rank, Ranking::Equal);
+        assert_eq!(info.ranked_value, "Alice");
+        assert_eq!(info.key_index, 2);
+    }
+
+    #[test]
+    fn union_uses_first_key_attributes() {
+        let key = name_key()
+            .max_ranking(Ranking::Contains)
+            .threshold(Ranking::Acronym)
+            .union(
+                tags_key()
+                    .max_ranking(Ranking::Acronym)
+                    .threshold(Ranking::Equal),
+            );
+        assert_eq!(key.threshold_value(), Some(&Ranking::Acronym));
+        assert_eq!(key.max_ranking_value(), &Ranking::Contains);
+        let info = get_highest_ranking(&sample_user(), &[key], "admin", &default_opts());
+        assert_eq!(info.rank, Ranking::Contains);
+    }
+
+    #[test]
+    fn union_with_itself_keeps_first_duplicate() {
+        let keys = vec![name_key().union(name_key())];
+        assert_eq!(keys[0].extract(&sample_user()), vec!["Alice", "Alice"]);
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.key_index, 0);
+    }
+
+    #[test]
+    fn union_key_index_matches_separate_keys() {
+        let fused = vec![
+            Key::union_all(vec![tags_key(), Key::new(|u: &User| vec![u.email.clone()])]),
+            name_key(),
+        ];
+        let separate = vec![
+            tags_key(),
+            Key::new(|u: &User| vec![u.email.clone()]),
+            name_key(),
+        ];
+        for query in ["staff", "example", "Alice"] {
+            let a = get_highest_ranking(&sample_user(), &fused, query, &default_opts());
+            let b = get_highest_ranking(&sample_user(), &separate, query, &default_opts());
+            assert_eq!(a, b, "query {query:?}");
+        }
+        let info = get_highest_ranking(&sample_user(), &fused, "Alice", &default_opts());
+        assert_eq!(info.key_index, 3);
+    }
+
+    #[test]
+    fn union_all_empty_extracts_nothing() {
+        let key = Key::<User>::union_all(Vec::new());
+        assert!(key.extract(&sample_user()).is_empty());
+    }
+
+    // --- Key::skip_if / only_if tests ---
+
+    fn is_admin(u: &User) -> bool {
+        u.tags.iter().any(|t| t == "admin")
+    }
+
+    #[test]
+    fn skip_if_skipped_item_yields_no_values_or_key_index() {
+        let user = sample_user();
+        let key = tags_key().skip_if(is_admin);
+        assert!(key.extract(&user).is_empty());
+
+        // The skipped key takes no key_index slots, so the name key sits at 0.
+        let keys = vec![key, name_key()];
+        let info = get_highest_ranking(&user, &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.key_index, 0);
+        let info = get_highest_ranking(&user, &keys, "staff", &default_opts());
+        assert_eq!(info.rank, Ranking::NoMatch);
+    }
+
+    #[test]
+    fn skip_if_keeps_items_not_matching_predicate() {
+        let key = tags_key().skip_if(|u| u.name == "Bob");
+        assert_eq!(key.extract(&sample_user()), vec!["admin", "staff"]);
+    }
+
+    #[test]
+    fn only_if_is_symmetric_with_skip_if() {
+        let user = sample_user();
+        let bob = User {
+            name: "Bob".to_owned(),
+            email: "bob@example.com".to_owned(),
+            tags: vec!["staff".to_owned()],
+        };
+        let skip = tags_key().skip_if(is_admin);
+        let only = tags_key().only_if(is_admin);
+        for u in [&user, &bob] {
+            assert_ne!(skip.extract(u).is_empty(), only.extract(u).is_empty());
+        }
+        assert_eq!(only.extract(&user), vec!["admin", "staff"]);
+        assert_eq!(skip.extract(&bob), vec!["staff"]);
+    }
+
+    #[test]
+    fn skip_if_chained_skips_either() {
+        let key = tags_key().skip_if(|u| u.name == "Bob").skip_if(is_admin);
+        assert!(key.extract(&sample_user()).is_empty());
+    }
+
+    #[test]
+    fn skip_if_routes_items_through_union() {
+        let key = tags_key()
+            .only_if(is_admin)
+            .union(Key::new(|u: &User| vec![u.email.clone()]).skip_if(is_admin));
+        assert_eq!(key.extract(&sample_user()), vec!["admin", "staff"]);
+    }
+
+    // --- Key::extract_if / extract_when_some tests ---
+
+    #[test]
+    fn extract_if_false_yields_no_values_and_next_key_follows() {
+        let user = sample_user();
+        let key = tags_key().extract_if(|u| !is_admin(u));
+        assert!(key.extract(&user).is_empty());
+
+        // The rejected key contributes no positions, so the name key is 0.
+        let keys = vec![key, name_key()];
+        let info = get_highest_ranking(&user, &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.key_index, 0);
+        let info = get_highest_ranking(&user, &keys, "staff", &default_opts());
+        assert_eq!(info.rank, Ranking::NoMatch);
+    }
+
+    #[test]
+    fn extract_if_true_extracts_normally() {
+        let user = sample_user();
+        let key = tags_key().extract_if(is_admin);
+        assert_eq!(key.extract(&user), vec!["admin", "staff"]);
+        let keys = vec![key, name_key()];
+        let info = get_highest_ranking(&user, &keys, "staff", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.key_index, 1);
+        let info = get_highest_ranking(&user, &keys, "Alice", &default_opts());
+        assert_eq!(info.key_index, 2);
+    }
+
+    #[test]
+    fn extract_if_false_on_required_key_excludes_item() {
+        let user = sample_user();
+        let keys = vec![tags_key().extract_if(|_| false).required(), name_key()];
+        let info = get_highest_ranking(&user, &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::NoMatch);
+
+        let keys = vec![tags_key().extract_if(|_| true).required(), name_key()];
+        let info = get_highest_ranking(&user, &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+    }
+
+    #[test]
+    fn extract_if_feeds_later_defaults() {
+        let calls = Rc::new(std::cell::Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let key = Key::new(move |u: &User| {
+            counter.set(counter.get() + 1);
+            u.tags.clone()
+        })
+        .extract_if(|_| false)
+        .or_default_str("none");
+        assert_eq!(key.extract(&sample_user()), vec!["none"]);
+        // A rejected item's values are not extracted at all.
+        assert_eq!(calls.get(), 0);
+        // only_if skips the key, default included.
+        let skipped = tags_key().only_if(|_| false).or_default_str("none");
+        assert!(skipped.extract(&sample_user()).is_empty());
+    }
+
+    #[test]
+    fn extract_when_some_handles_some_and_none() {
+        let key = Key::<User>::extract_when_some(|u| u.tags.first().cloned());
+        assert_eq!(key.extract(&sample_user()), vec!["admin"]);
+        let untagged = User {
+            tags: Vec::new(),
+            ..sample_user()
+        };
+        assert!(key.extract(&untagged).is_empty());
+
+        let len_key = Key::<User>::extract_when_some(|u| Some(u.name.len()));
+        assert_eq!(len_key.extract(&sample_user()), vec!["5"]);
+    }
+
+    // --- Key::alias tests ---
+
+    #[test]
+    fn alias_reported_for_winning_key() {
+        let keys = vec![
+            name_key().alias("name"),
+            Key::new(|u: &User| vec![u.email.clone()]).alias("email"),
+            tags_key(),
+        ];
+        let info = get_highest_ranking(&sample_user(), &keys, "example", &default_opts());
+        assert_eq!(info.matched_key_alias.as_deref(), Some("email"));
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.matched_key_alias.as_deref(), Some("name"));
+        let info = get_highest_ranking(&sample_user(), &keys, "staff", &default_opts());
+        assert_eq!(info.matched_key_alias, None);
+    }
+
+    #[test]
+    fn alias_shared_by_keys_distinguished_by_key_index() {
+        let keys = vec![
+            name_key().alias("field"),
+            Key::new(|u: &User| vec![u.email.clone()]).alias("field"),
+        ];
+        let by_name = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        let by_email = get_highest_ranking(&sample_user(), &keys, "example", &default_opts());
+        assert_eq!(by_name.matched_key_alias, by_email.matched_key_alias);
+        assert_eq!(by_name.key_index, 0);
+        assert_eq!(by_email.key_index, 1);
+    }
+
+    #[test]
+    fn alias_from_fn_computed_per_item() {
+        let keys = vec![tags_key().alias_from_fn(|u| format!("{}'s tags", u.name))];
+        let info = get_highest_ranking(&sample_user(), &keys, "admin", &default_opts());
+        assert_eq!(info.matched_key_alias.as_deref(), Some("Alice's tags"));
+    }
+
+    #[test]
+    fn alias_on_select_overrides_sub_keys() {
+        let sub_keys = || vec![name_key().alias("name"), tags_key()];
+        let keys = vec![Key::select(sub_keys())];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.matched_key_alias.as_deref(), Some("name"));
+
+        let keys = vec![Key::select(sub_keys()).alias("who")];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.matched_key_alias.as_deref(), Some("who"));
+    }
+
+    #[test]
+    fn alias_union_keeps_first_alias() {
+        let keys = vec![name_key().alias("name").union(tags_key().alias("tags"))];
+        let info = get_highest_ranking(&sample_user(), &keys, "staff", &default_opts());
+        assert_eq!(info.matched_key_alias.as_deref(), Some("name"));
+    }
+
+    #[test]
+    fn highest_ranking_max_ranking_does_not_affect_lower_ranks() {
+        // max_ranking = StartsWith. Query "admin" vs "admin" -> CaseSensitiveEqual,
+        // clamped to StartsWith. But if the natural rank is Contains (lower
+        // than max_ranking), no clamping occurs.
+        let item = "xxadminxx".to_owned();
+        let keys = vec![Key::new(|s: &String| vec![s.clone()]).max_ranking(Ranking::StartsWith)];
+        // "xxadminxx" contains "admin" -> Contains, which is below StartsWith
+        let info = get_highest_ranking(&item, &keys, "admin", &default_opts());
+        assert_eq!(info.rank, Ranking::Contains);
+    }
+
+    #[test]
+    fn highest_ranking_min_ranking_does_not_affect_higher_ranks() {
+        // min_ranking = Contains. If the rank is already above Contains
+        // (e.g., StartsWith), it should not be affected.
+        let keys = vec![Key::new(|u: &User| vec![u.name.clone()]).min_ranking(Ranking::Contains)];
+        // "Alice" queried with "ali" -> StartsWith, which is above Contains
+        let info = get_highest_ranking(&sample_user(), &keys, "ali", &default_opts());
+        assert_eq!(info.rank, Ranking::StartsWith);
+    }
+
+    #[test]
+    fn highest_ranking_both_clamps_applied() {
+        // max_ranking = Contains and min_ranking = Contains effectively
+        // forces all non-NoMatch results to exactly Contains.
+        let keys = vec![
+            Key::new(|u: &User| vec![u.name.clone()])
+                .min_ranking(Ranking::Contains)
+                .max_ranking(Ranking::Contains),
+        ];
+        // "Alice" queried with "Alice" -> CaseSensitiveEqual, clamped to Contains
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::Contains);
+    }
+
+    #[test]
+    fn highest_ranking_winning_key_threshold_from_correct_key() {
+        // Key 0 has threshold StartsWith, extracts email -> Contains for "alice"
+        // Key 1 has threshold Acronym, extracts name -> CaseSensitiveEqual for "Alice"
+        // Key 1 wins, so key_threshold should be Acronym (key 1's threshold).
+        let keys: Vec<Key<User>> = vec![
+            Key::new(|u: &User| vec![u.email.clone()]).threshold(Ranking::StartsWith),
+            Key::new(|u: &User| vec![u.name.clone()]).threshold(Ranking::Acronym),
+        ];
+        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.key_threshold, Some(Ranking::Acronym));
+    }
+
+    #[test]
+    fn highest_ranking_keep_diacritics_option_passed() {
+        // "cafe" + combining acute = "caf\u{e9}" equivalent. Query "cafe" with
+        // keep_diacritics=true means they won't match (different chars).
+        let item = "caf\u{e9}".to_owned();
+        let keys = vec![Key::new(|s: &String| vec![s.clone()])];
+
+        let opts_strip = MatchSorterOptions {
+            keep_diacritics: false,
+            ..Default::default()
+        };
+        let info_strip = get_highest_ranking(&item, &keys, "cafe", &opts_strip);
+        assert_eq!(info_strip.rank, Ranking::CaseSensitiveEqual);
+
+        let opts_keep = MatchSorterOptions {
+            keep_diacritics: true,
+            ..Default::default()
+        };
+        let info_keep = get_highest_ranking(&item, &keys, "cafe", &opts_keep);
+        assert_eq!(info_keep.rank, Ranking::NoMatch);
+    }
+
+    #[test]
+    fn extract_all_values_matches_ranking_key_index() {
+        let user = sample_user();
+        let keys = vec![
+            Key::new(|u: &User| vec![u.name.clone()]),
+            Key::new(|u: &User| vec![u.email.clone()]).skip_if(|_| true),
+            Key::new(|u: &User| u.tags.clone()),
+            Key::select(vec![
+                Key::new(|_: &User| vec!["first".to_owned()]),
+                Key::new(|_: &User| vec!["second".to_owned()]),
+            ]),
+            Key::new(|_: &User| vec!["last".to_owned()]),
+        ];
+        let all = extract_all_values(&user, &keys);
+        let values: Vec<&str> = all.iter().map(|(_, v)| v.as_str()).collect();
+        assert_eq!(
+            values,
+            ["Alice", "admin", "staff", "first", "second", "last"]
+        );
+        for (index, value) in &all {
+            let info = get_highest_ranking(&user, &keys, value, &default_opts());
+            assert_eq!(info.key_index, *index, "value {value:?}");
+            assert_eq!(&info.ranked_value, value);
+        }
+        let indices: Vec<usize> = all.iter().map(|(i, _)| *i).collect();
+        assert_eq!(indices, [0, 1, 2, 3, 3, 4]);
+    }
+
+    #[test]
+    fn extract_iter_yields_extract_values_then_ends() {
+        let user = sample_user();
+        let key = Key::new(|u: &User| u.tags.clone());
+        let mut iter = key.extract_iter(&user);
+        let mut expected = key.extract(&user);
+        expected.reverse();
+        for value in iter.by_ref() {
+            assert_eq!(Some(value), expected.pop());
+        }
+        assert!(expected.is_empty());
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn extract_iter_empty_extractor_is_empty() {
+        let key = Key::new(|_: &User| Vec::new());
+        assert_eq!(key.extract_iter(&sample_user()).count(), 0);
+        assert!(extract_all_values(&sample_user(), &[key]).is_empty());
+
+        let skipped = Key::new(|u: &User| vec![u.name.clone()]).skip_if(|_| true);
+        assert_eq!(skipped.extract_iter(&sample_user()).count(), 0);
+    }
+
+    #[cfg(feature = "counters")]
+    #[test]
+    fn call_counter_counts_each_extract() {
+        let (key, calls) = Key::new(|s: &String| vec![s.clone()]).with_call_counter();
+        for expected in 1..=3 {
+            key.extract(&"apple".to_owned());
+            assert_eq!(calls.get(), expected);
+        }
+        assert!(format!("{key:?}").contains("calls: 3"));
+        calls.reset();
+        assert_eq!(calls.get(), 0);
+    }
+
+    #[cfg(feature = "counters")]
+    #[test]
+    fn call_counter_shared_between_clones() {
+        let (key, calls) = Key::new(|s: &String| vec![s.clone()]).with_call_counter();
+        let other = calls.clone();
+        key.extract(&"apple".to_owned());
+        assert_eq!(other.get(), 1);
+        other.reset();
+        assert_eq!(calls.get(), 0);
+    }
+
+    #[cfg(feature = "counters")]
+    #[test]
+    fn call_counter_counts_past_u32_max() {
+        let (key, calls) = Key::new(|s: &String| vec![s.clone()]).with_call_counter();
+        calls.0.store(u64::from(u32::MAX), Ordering::Relaxed);
+        key.extract(&"apple".to_owned());
+        key.extract(&"apple".to_owned());
+        assert_eq!(calls.get(), u64::from(u32::MAX) + 2);
+    }
+
+    #[cfg(feature = "counters")]
+    #[test]
+    fn call_counter_counts_ranking_but_not_skipped_items() {
+        let (name, calls) = Key::new(|s: &String| vec![s.clone()])
+            .skip_if(|s: &String| s.starts_with('x'))
+            .with_call_counter();
+        let (select, select_calls) =
+            Key::select(vec![Key::new(|s: &String| vec![s.clone()])]).with_call_counter();
+        let keys = vec![name, select];
+        let items = ["apple", "xylophone", "grape"].map(str::to_owned);
+        for item in &items {
+            let _ = get_highest_ranking(item, &keys, "ap", &default_opts());
+        }
+        assert_eq!(calls.get(), 2);
+        assert_eq!(select_calls.get(), 3);
+    }
 
-    /// Index of the key (in the flattened key-values list) that produced
-    /// the best match.
-    pub key_index: usize,
+    // --- Key::required tests ---
 
-    /// Per-key threshold override from the winning key, or `None` if the
-    /// key uses the global threshold.
-    pub key_threshold: Option<Ranking>,
-}
+    struct Recipe {
+        title: Option<&'static str>,
+        author: Option<&'static str>,
+        notes: &'static str,
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn recipe_keys(title: Key<Recipe>, author: Key<Recipe>) -> Vec<Key<Recipe>> {
+        vec![title, author, Key::from_fn(|r: &Recipe| r.notes)]
+    }
 
-    // --- Helper types for testing ---
+    fn title_key() -> Key<Recipe> {
+        Key::new(|r: &Recipe| r.title.iter().map(|t| (*t).to_owned()).collect())
+    }
 
-    #[derive(Debug)]
-    struct User {
-        name: String,
-        email: String,
-        tags: Vec<String>,
+    fn author_key() -> Key<Recipe> {
+        Key::new(|r: &Recipe| r.author.iter().map(|a| (*a).to_owned()).collect())
     }
 
-    fn sample_user() -> User {
-        User {
-            name: "Alice".to_owned(),
-            email: "alice@example.com".to_owned(),
-            tags: vec!["admin".to_owned(), "staff".to_owned()],
-        }
+    fn recipe_rank(recipe: &Recipe, keys: &[Key<Recipe>], query: &str) -> Ranking {
+        let opts = MatchSorterOptions::default();
+        get_highest_ranking(recipe, keys, query, &opts).rank
     }
 
-    // --- Key::new tests ---
+    #[test]
+    fn required_key_without_values_excludes_item() {
+        let recipe = Recipe {
+            title: None,
+            author: Some("Ann"),
+            notes: "tart",
+        };
+        let keys = recipe_keys(title_key().required(), author_key());
+        assert_eq!(recipe_rank(&recipe, &keys, "tart"), Ranking::NoMatch);
+        // A skipped required key counts as yielding no values.
+        let keys = recipe_keys(title_key().skip_if(|_| true).required(), author_key());
+        let titled = Recipe {
+            title: Some("Lemon"),
+            ..recipe
+        };
+        assert_eq!(recipe_rank(&titled, &keys, "tart"), Ranking::NoMatch);
+    }
 
     #[test]
-    fn new_accepts_closure_returning_vec_string() {
-        let key = Key::new(|u: &User| vec![u.name.clone()]);
-        let values = key.extract(&sample_user());
-        assert_eq!(values, vec!["Alice"]);
+    fn required_key_with_values_ranks_normally() {
+        let recipe = Recipe {
+            title: Some("Lemon"),
+            author: None,
+            notes: "tart",
+        };
+        let keys = recipe_keys(title_key().required(), author_key());
+        let info = get_highest_ranking(&recipe, &keys, "tart", &MatchSorterOptions::default());
+        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(info.ranked_value, "tart");
+        // The required key need not match the query itself.
+        assert_eq!(info.key_index, 1);
     }
 
     #[test]
-    fn new_default_threshold_is_none() {
-        let key = Key::new(|_: &User| vec![]);
-        assert_eq!(key.threshold, None);
+    fn every_required_key_must_yield_values() {
+        let keys = recipe_keys(title_key().required(), author_key().required());
+        let both = Recipe {
+            title: Some("Lemon"),
+            author: Some("Ann"),
+            notes: "tart",
+        };
+        assert_eq!(
+            recipe_rank(&both, &keys, "tart"),
+            Ranking::CaseSensitiveEqual
+        );
+        let no_author = Recipe {
+            author: None,
+            ..both
+        };
+        assert_eq!(recipe_rank(&no_author, &keys, "tart"), Ranking::NoMatch);
+        let no_title = Recipe {
+            title: None,
+            ..both
+        };
+        assert_eq!(recipe_rank(&no_title, &keys, "tart"), Ranking::NoMatch);
     }
 
     #[test]
-    fn new_default_min_ranking_is_no_match() {
-        let key = Key::new(|_: &User| vec![]);
-        assert_eq!(key.min_ranking, Ranking::NoMatch);
+    fn optional_keys_ignore_missing_values() {
+        let recipe = Recipe {
+            title: None,
+            author: None,
+            notes: "tart",
+        };
+        let keys = recipe_keys(title_key().required().optional(), author_key());
+        assert_eq!(
+            recipe_rank(&recipe, &keys, "tart"),
+            Ranking::CaseSensitiveEqual
+        );
+        assert!(!author_key().required);
     }
 
     #[test]
-    fn new_default_max_ranking_is_case_sensitive_equal() {
-        let key = Key::new(|_: &User| vec![]);
-        assert_eq!(key.max_ranking, Ranking::CaseSensitiveEqual);
+    fn required_select_key_needs_any_sub_key_value() {
+        let keys = vec![
+            Key::select(vec![title_key(), author_key()]).required(),
+            Key::from_fn(|r: &Recipe| r.notes),
+        ];
+        let author_only = Recipe {
+            title: None,
+            author: Some("Ann"),
+            notes: "tart",
+        };
+        assert_eq!(
+            recipe_rank(&author_only, &keys, "tart"),
+            Ranking::CaseSensitiveEqual
+        );
+        let neither = Recipe {
+            author: None,
+            ..author_only
+        };
+        assert_eq!(recipe_rank(&neither, &keys, "tart"), Ranking::NoMatch);
     }
 
-    // --- Key::from_fn tests ---
+    // --- Key::multi_language tests ---
 
-    #[test]
-    fn from_fn_single_value_extraction() {
-        let key = Key::<User>::from_fn(|u| u.name.as_str());
-        let values = key.extract(&sample_user());
-        assert_eq!(values, vec!["Alice"]);
+    struct Place {
+        name: &'static str,
+        lang: &'static str,
     }
 
-    #[test]
-    fn from_fn_equivalent_to_new_with_vec() {
-        let user = sample_user();
-        let key_new = Key::new(|u: &User| vec![u.name.clone()]);
-        let key_fn = Key::<User>::from_fn(|u| u.name.as_str());
+    fn place_rank(place: &Place, query: &str, opts: &MatchSorterOptions<Place>) -> Ranking {
+        get_highest_ranking(place, &opts.keys, query, opts).rank
+    }
 
-        let values_new = key_new.extract(&user);
-        let values_fn = key_fn.extract(&user);
-        assert_eq!(values_new, values_fn);
+    fn place_options() -> MatchSorterOptions<Place> {
+        MatchSorterOptions {
+            keys: vec![Key::from_fn(|p: &Place| p.name).multi_language(|p| p.lang)],
+            keep_diacritics: true,
+            ..Default::default()
+        }
     }
 
     #[test]
-    fn from_fn_default_attributes() {
-        let key = Key::<User>::from_fn(|u| u.name.as_str());
-        assert_eq!(key.threshold, None);
-        assert_eq!(key.min_ranking, Ranking::NoMatch);
-        assert_eq!(key.max_ranking, Ranking::CaseSensitiveEqual);
+    fn multi_language_folds_turkish_i() {
+        let opts = place_options();
+        let istanbul = Place {
+            name: "\u{130}stanbul",
+            lang: "tr",
+        };
+        assert_eq!(
+            place_rank(&istanbul, "istanbul", &opts),
+            Ranking::CaseSensitiveEqual
+        );
+        assert_eq!(place_rank(&istanbul, "ISTANBUL", &opts), Ranking::Equal);
+        assert_eq!(
+            place_rank(&istanbul, "\u{131}stanbul", &opts),
+            Ranking::CaseSensitiveEqual
+        );
+        // Without a language, the dotted capital lowercases to "i\u{307}".
+        let untagged = Place {
+            lang: "en",
+            ..istanbul
+        };
+        assert!(place_rank(&untagged, "istanbul", &opts) < Ranking::Equal);
     }
 
-    // --- Key::from_fn_multi tests ---
-
     #[test]
-    fn from_fn_multi_extracts_multiple_values() {
-        let key = Key::<User>::from_fn_multi(|u| u.tags.iter().map(|t| t.as_str()).collect());
-        let values = key.extract(&sample_user());
-        assert_eq!(values, vec!["admin", "staff"]);
+    fn multi_language_matches_german_umlauts() {
+        let opts = place_options();
+        let muenchen = Place {
+            name: "M\u{fc}nchen",
+            lang: "de-DE",
+        };
+        assert_eq!(place_rank(&muenchen, "muenchen", &opts), Ranking::Equal);
+        assert_eq!(place_rank(&muenchen, "m\u{fc}nchen", &opts), Ranking::Equal);
+        let info = get_highest_ranking(&muenchen, &opts.keys, "Mue", &opts);
+        assert_eq!(info.rank, Ranking::StartsWith);
+        assert_eq!(info.ranked_value, "Muenchen");
     }
 
     #[test]
-    fn from_fn_multi_default_attributes() {
-        let key = Key::<User>::from_fn_multi(|u| u.tags.iter().map(|t| t.as_str()).collect());
-        assert_eq!(key.threshold, None);
-        assert_eq!(key.min_ranking, Ranking::NoMatch);
-        assert_eq!(key.max_ranking, Ranking::CaseSensitiveEqual);
+    fn multi_language_prefers_custom_normalizers() {
+        let opts = place_options().language_normalizer("de", str::to_owned);
+        let muenchen = Place {
+            name: "M\u{fc}nchen",
+            lang: "de-AT",
+        };
+        assert!(place_rank(&muenchen, "muenchen", &opts) < Ranking::Contains);
+        assert_eq!(place_rank(&muenchen, "m\u{fc}nchen", &opts), Ranking::Equal);
+
+        let opts = place_options().language_normalizer("ja", |s| s.replace('\u{30fc}', ""));
+        let ramen = Place {
+            name: "\u{30e9}\u{30fc}\u{30e1}\u{30f3}",
+            lang: "ja",
+        };
+        assert_eq!(
+            place_rank(&ramen, "\u{30e9}\u{30e1}\u{30f3}", &opts),
+            Ranking::CaseSensitiveEqual
+        );
     }
 
     #[test]
-    fn from_fn_multi_empty_vec() {
-        let key = Key::<User>::from_fn_multi(|_| vec![]);
-        let values = key.extract(&sample_user());
-        assert!(values.is_empty());
+    fn multi_language_unifies_kana() {
+        let opts = place_options();
+        let ramen = Place {
+            name: "\u{30e9}\u{30fc}\u{30e1}\u{30f3}",
+            lang: "ja",
+        };
+        assert_eq!(
+            place_rank(&ramen, "\u{3089}\u{30fc}\u{3081}\u{3093}", &opts),
+            Ranking::CaseSensitiveEqual
+        );
     }
 
-    // --- Builder method tests ---
+    // --- Key::language tests ---
 
-    #[test]
-    fn threshold_sets_value() {
-        let key = Key::new(|_: &User| vec![]).threshold(Ranking::StartsWith);
-        assert_eq!(key.threshold, Some(Ranking::StartsWith));
+    fn city_rank(key: Key<&'static str>, city: &'static str, query: &str) -> Ranking {
+        // Replace the built-in Turkish normalizer, which would fold every i
+        // to a plain "i" before the case rules see it.
+        let opts = MatchSorterOptions {
+            keys: vec![key],
+            keep_diacritics: true,
+            ..Default::default()
+        }
+        .language_normalizer("tr", str::to_owned);
+        get_highest_ranking(&city, &opts.keys, query, &opts).rank
+    }
+
+    fn city_key() -> Key<&'static str> {
+        Key::from_fn(|c: &&str| *c)
     }
 
     #[test]
-    fn max_ranking_sets_value() {
-        let key = Key::new(|_: &User| vec![]).max_ranking(Ranking::Contains);
-        assert_eq!(key.max_ranking, Ranking::Contains);
+    fn language_lowercases_turkish_dotted_capital() {
+        let turkish = || city_key().language("tr");
+        assert_eq!(
+            city_rank(turkish(), "\u{130}stanbul", "istanbul"),
+            Ranking::Equal
+        );
+        assert_eq!(
+            city_rank(turkish(), "\u{130}STANBUL", "\u{130}st"),
+            Ranking::StartsWith
+        );
+        // A dotless capital lowercases to a dotless "ı".
+        assert_eq!(
+            city_rank(turkish(), "ISPARTA", "\u{131}sparta"),
+            Ranking::Equal
+        );
+        assert!(city_rank(turkish(), "ISPARTA", "isparta") < Ranking::Equal);
     }
 
     #[test]
-    fn min_ranking_sets_value() {
-        let key = Key::new(|_: &User| vec![]).min_ranking(Ranking::Contains);
-        assert_eq!(key.min_ranking, Ranking::Contains);
+    fn language_leaves_other_languages_alone() {
+        let english = || city_key().language("en");
+        assert!(city_rank(english(), "\u{130}stanbul", "istanbul") < Ranking::Equal);
+        assert_eq!(city_rank(english(), "ISPARTA", "isparta"), Ranking::Equal);
+        assert_eq!(city_rank(city_key(), "ISPARTA", "isparta"), Ranking::Equal);
     }
 
     #[test]
-    fn builder_chain_all_three() {
-        let key = Key::new(|u: &User| vec![u.email.clone()])
-            .threshold(Ranking::Acronym)
-            .max_ranking(Ranking::Equal)
-            .min_ranking(Ranking::Contains);
+    fn language_uses_builtin_normalizer_and_item_tags() {
+        let opts = MatchSorterOptions {
+            keys: vec![city_key().language("tr-TR")],
+            keep_diacritics: true,
+            ranking_cache: Some(std::sync::Arc::new(std::sync::Mutex::new(
+                crate::RankingCache::new(16),
+            ))),
+            ..Default::default()
+        };
+        let rank = |city: &'static str, query: &str| {
+            get_highest_ranking(&city, &opts.keys, query, &opts).rank
+        };
+        assert_eq!(rank("ISPARTA", "isparta"), Ranking::Equal);
+        assert_eq!(rank("\u{130}zmir", "izmir"), Ranking::CaseSensitiveEqual);
 
-        assert_eq!(key.threshold, Some(Ranking::Acronym));
-        assert_eq!(key.max_ranking, Ranking::Equal);
-        assert_eq!(key.min_ranking, Ranking::Contains);
+        // A per-item tag replaces the fixed one.
+        let untagged = Place {
+            name: "\u{130}stanbul",
+            lang: "en",
+        };
+        let opts = MatchSorterOptions {
+            keys: vec![
+                Key::from_fn(|p: &Place| p.name)
+                    .multi_language(|p| p.lang)
+                    .language("tr"),
+            ],
+            keep_diacritics: true,
+            ..Default::default()
+        };
+        assert!(place_rank(&untagged, "istanbul", &opts) < Ranking::Equal);
     }
 
     #[test]
-    fn builder_chain_preserves_extractor() {
-        let key = Key::new(|u: &User| vec![u.name.clone()])
-            .threshold(Ranking::StartsWith)
-            .max_ranking(Ranking::Contains)
-            .min_ranking(Ranking::Acronym);
-
-        let values = key.extract(&sample_user());
-        assert_eq!(values, vec!["Alice"]);
+    fn custom_case_fold_replaces_builtin_rules() {
+        let opts = MatchSorterOptions {
+            keys: vec![city_key().language("tr-TR"), city_key()],
+            custom_case_fold: Some(Box::new(|c, tag| match c {
+                'I' if tag == "tr-TR" => "i".to_owned(),
+                'S' => "z".to_owned(),
+                _ => c.to_lowercase().collect(),
+            })),
+            ..Default::default()
+        }
+        .language_normalizer("tr", str::to_owned);
+        let info = get_highest_ranking(&"ISPARTA", &opts.keys, "izparta", &opts);
+        assert_eq!(info.rank, Ranking::Equal);
+        assert_eq!(info.key_index, 0);
+        // Keys without a language lowercase as usual.
+        let info = get_highest_ranking(&"ISPARTA", &opts.keys, "isparta", &opts);
+        assert_eq!(info.rank, Ranking::Equal);
+        assert_eq!(info.key_index, 1);
     }
 
+    // --- Key::with_prefilter tests ---
+
     #[test]
-    fn builder_from_fn_with_chain() {
-        let key = Key::<User>::from_fn(|u| u.email.as_str())
-            .threshold(Ranking::WordStartsWith)
-            .max_ranking(Ranking::StartsWith);
+    fn prefiltered_key_ranks_like_plain_key() {
+        let plain = [subject_key()];
+        let prefiltered = [subject_key().with_prefilter()];
+        for (subject, query) in [
+            ("Zebra crossing", "zc"),
+            ("lizard", "z"),
+            ("apple", "z"),
+            ("apple", "APL"),
+            ("\u{212a}iln", "kiln"),
+        ] {
+            assert_eq!(
+                subject_rank(&prefiltered, subject, query),
+                subject_rank(&plain, subject, query),
+                "{subject:?} vs {query:?}"
+            );
+        }
+        assert!(Key::union_all(vec![subject_key().with_prefilter(), subject_key()]).prefilter);
+    }
 
-        assert_eq!(key.threshold, Some(Ranking::WordStartsWith));
-        assert_eq!(key.max_ranking, Ranking::StartsWith);
-        // min_ranking left at default
-        assert_eq!(key.min_ranking, Ranking::NoMatch);
+    // --- Key::extract_normalized tests ---
 
-        let values = key.extract(&sample_user());
-        assert_eq!(values, vec!["alice@example.com"]);
+    const DESSERTS: [&str; 4] = [
+        "Cr\u{e8}me br\u{fb}l\u{e9}e",
+        "cre\u{300}me",
+        "Pi\u{f1}a colada",
+        "plain pudding",
+    ];
+
+    #[test]
+    fn extract_normalized_ranks_like_plain_key() {
+        for keep_diacritics in [false, true] {
+            let opts = MatchSorterOptions {
+                keep_diacritics,
+                ..Default::default()
+            };
+            let plain = [subject_key()];
+            let normalized = [subject_key().extract_normalized(keep_diacritics)];
+            for dessert in DESSERTS {
+                for query in ["creme", "cr\u{e8}me", "pina", "BRUL", "pd", "zzz"] {
+                    assert_eq!(
+                        get_highest_ranking(&dessert, &normalized, query, &opts).rank,
+                        get_highest_ranking(&dessert, &plain, query, &opts).rank,
+                        "{dessert:?} vs {query:?}, keep_diacritics {keep_diacritics}"
+                    );
+                }
+            }
+        }
     }
 
     #[test]
-    fn builder_from_fn_multi_with_chain() {
-        let key = Key::<User>::from_fn_multi(|u| u.tags.iter().map(|t| t.as_str()).collect())
-            .min_ranking(Ranking::Contains);
+    fn extract_normalized_skips_second_preparation() {
+        use crate::ranking::COMPARISON_PREPARES;
 
-        assert_eq!(key.min_ranking, Ranking::Contains);
-        assert_eq!(key.threshold, None);
-        assert_eq!(key.max_ranking, Ranking::CaseSensitiveEqual);
+        let calls = |keys: &[Key<&'static str>]| {
+            let opts = MatchSorterOptions::default();
+            let before = COMPARISON_PREPARES.get();
+            for dessert in DESSERTS {
+                get_highest_ranking(&dessert, keys, "creme", &opts);
+            }
+            COMPARISON_PREPARES.get() - before
+        };
+        // An extractor that prepares its values itself, unmarked.
+        let by_hand =
+            Key::new(|s: &&str| vec![prepare_value_for_comparison(s, false).into_owned()]);
+        let marked = subject_key().extract_normalized(false);
+        assert_eq!(calls(&[by_hand]) - calls(&[marked]), DESSERTS.len());
 
-        let values = key.extract(&sample_user());
-        assert_eq!(values, vec!["admin", "staff"]);
+        // A later transform clears the mark.
+        let remapped = subject_key()
+            .extract_normalized(false)
+            .map(|v| v + "\u{e9}");
+        assert_eq!(remapped.pre_normalized, None);
+        assert_eq!(
+            Key::union_all(vec![subject_key().extract_normalized(true)]).pre_normalized,
+            None
+        );
     }
 
-    // --- Builder override tests ---
-
     #[test]
-    fn builder_last_call_wins_for_same_method() {
-        let key = Key::new(|_: &User| vec![])
-            .threshold(Ranking::Contains)
-            .threshold(Ranking::StartsWith);
-        assert_eq!(key.threshold, Some(Ranking::StartsWith));
+    fn extract_normalized_needs_matching_diacritics_mode() {
+        // Values kept with their diacritics are prepared again when the
+        // search strips them.
+        let keys = [subject_key().extract_normalized(true)];
+        let opts = MatchSorterOptions::default();
+        let info = get_highest_ranking(&DESSERTS[0], &keys, "creme brulee", &opts);
+        assert_eq!(info.rank, Ranking::Equal);
+        assert_eq!(info.ranked_value, DESSERTS[0]);
     }
 
-    #[test]
-    fn builder_matches_variant_in_threshold() {
-        // Ensure Matches(f64) variant works in builder methods.
-        let key = Key::new(|_: &User| vec![])
-            .threshold(Ranking::Matches(1.5))
-            .min_ranking(Ranking::Matches(1.2))
-            .max_ranking(Ranking::Matches(1.8));
+    // --- Key::invert tests ---
 
-        assert_eq!(key.threshold, Some(Ranking::Matches(1.5)));
-        assert_eq!(key.min_ranking, Ranking::Matches(1.2));
-        assert_eq!(key.max_ranking, Ranking::Matches(1.8));
+    fn subject_rank(keys: &[Key<&'static str>], subject: &'static str, query: &str) -> Ranking {
+        let opts = MatchSorterOptions::default();
+        get_highest_ranking(&subject, keys, query, &opts).rank
     }
 
-    // --- RankingInfo tests ---
+    fn subject_key() -> Key<&'static str> {
+        Key::from_fn(|s: &&str| *s)
+    }
 
     #[test]
-    fn ranking_info_construction() {
-        let info = RankingInfo {
-            rank: Ranking::Contains,
-            ranked_value: "hello".to_owned(),
-            key_index: 2,
-            key_threshold: Some(Ranking::StartsWith),
+    fn inverted_key_excludes_matching_values() {
+        let keys = [subject_key().invert()];
+        assert_eq!(
+            subject_rank(&keys, "buy spam now", "spam"),
+            Ranking::NoMatch
+        );
+        // Fuzzy matches count as matches too.
+        assert_eq!(subject_rank(&keys, "s-p-a-m", "spam"), Ranking::NoMatch);
+        assert_eq!(subject_rank(&keys, "meeting", "spam"), Ranking::Contains);
+        assert_eq!(subject_rank(&keys, "meeting", ""), Ranking::NoMatch);
+
+        let items = ["spam offer", "minutes", "Spam!", "agenda"];
+        let opts = MatchSorterOptions {
+            keys: vec![subject_key().invert()],
+            ..Default::default()
         };
+        assert_eq!(
+            crate::match_sorter(&items, "spam", &opts),
+            vec![&"agenda", &"minutes"]
+        );
+    }
 
+    #[test]
+    fn normal_key_still_includes_item_with_inverted_match() {
+        let body = Key::from_fn(|s: &&str| &s[s.find(':').map_or(0, |i| i + 1)..]);
+        let keys = [
+            Key::from_fn(|s: &&str| s.split(':').next().unwrap_or_default()).invert(),
+            body,
+        ];
+        // The inverted subject excludes, but the body matches exactly.
+        assert_eq!(
+            subject_rank(&keys, "spam:spam", "spam"),
+            Ranking::CaseSensitiveEqual
+        );
+        // Neither key includes an item whose subject matches and body doesn't.
+        assert_eq!(subject_rank(&keys, "spam:hello", "spam"), Ranking::NoMatch);
+        let info = get_highest_ranking(
+            &"hello:world",
+            &keys,
+            "spam",
+            &MatchSorterOptions::default(),
+        );
         assert_eq!(info.rank, Ranking::Contains);
-        assert_eq!(info.ranked_value, "hello");
-        assert_eq!(info.key_index, 2);
-        assert_eq!(info.key_threshold, Some(Ranking::StartsWith));
+        assert_eq!(info.key_index, 0);
     }
 
     #[test]
-    fn ranking_info_with_no_threshold() {
-        let info = RankingInfo {
-            rank: Ranking::Equal,
-            ranked_value: "world".to_owned(),
-            key_index: 0,
-            key_threshold: None,
-        };
+    fn inverted_rank_is_clamped() {
+        let capped = [subject_key().invert().max_ranking(Ranking::Acronym)];
+        assert_eq!(subject_rank(&capped, "minutes", "spam"), Ranking::Acronym);
+        assert_eq!(subject_rank(&capped, "spam", "spam"), Ranking::NoMatch);
 
-        assert_eq!(info.key_threshold, None);
-    }
+        let boosted = [subject_key().invert().min_ranking(Ranking::StartsWith)];
+        assert_eq!(
+            subject_rank(&boosted, "minutes", "spam"),
+            Ranking::StartsWith
+        );
+        // `NoMatch` is never promoted.
+        assert_eq!(subject_rank(&boosted, "spam", "spam"), Ranking::NoMatch);
 
-    #[test]
-    fn ranking_info_debug_formatting() {
-        let info = RankingInfo {
-            rank: Ranking::Acronym,
-            ranked_value: "test".to_owned(),
-            key_index: 1,
-            key_threshold: None,
-        };
-        let debug_str = format!("{info:?}");
-        assert!(debug_str.contains("Acronym"));
-        assert!(debug_str.contains("test"));
+        let twice = [subject_key().invert().invert()];
+        assert_eq!(
+            subject_rank(&twice, "spam", "spam"),
+            Ranking::CaseSensitiveEqual
+        );
     }
 
     #[test]
-    fn ranking_info_clone() {
-        let info = RankingInfo {
-            rank: Ranking::StartsWith,
-            ranked_value: "cloned".to_owned(),
-            key_index: 3,
-            key_threshold: Some(Ranking::Contains),
-        };
-        let cloned = info.clone();
-        assert_eq!(info, cloned);
+    fn inverted_select_key_inverts_best_sub_key() {
+        let keys =
+            [Key::select(vec![subject_key(), subject_key().map(|s| s.to_uppercase())]).invert()];
+        assert_eq!(subject_rank(&keys, "spam", "spam"), Ranking::NoMatch);
+        assert_eq!(subject_rank(&keys, "ham", "spam"), Ranking::Contains);
+        assert!(keys[0].has_inversion());
+        assert!(Key::select(vec![subject_key().invert()]).has_inversion());
     }
 
-    #[test]
-    fn ranking_info_partial_eq() {
-        let a = RankingInfo {
-            rank: Ranking::Contains,
-            ranked_value: "val".to_owned(),
-            key_index: 0,
-            key_threshold: None,
-        };
-        let b = RankingInfo {
-            rank: Ranking::Contains,
-            ranked_value: "val".to_owned(),
-            key_index: 0,
-            key_threshold: None,
-        };
-        assert_eq!(a, b);
+    // --- Key::require_match tests ---
+
+    struct Book {
+        title: &'static str,
+        author: &'static str,
+        tags: Vec<&'static str>,
     }
 
-    #[test]
-    fn ranking_info_partial_eq_different_rank() {
-        let a = RankingInfo {
-            rank: Ranking::Contains,
-            ranked_value: "val".to_owned(),
-            key_index: 0,
-            key_threshold: None,
-        };
-        let b = RankingInfo {
-            rank: Ranking::Equal,
-            ranked_value: "val".to_owned(),
-            key_index: 0,
-            key_threshold: None,
-        };
-        assert_ne!(a, b);
+    fn book(title: &'static str, author: &'static str) -> Book {
+        Book {
+            title,
+            author,
+            tags: Vec::new(),
+        }
     }
 
-    // --- Key with primitive types ---
+    fn book_title() -> Key<Book> {
+        Key::from_fn(|b: &Book| b.title)
+    }
 
-    #[test]
-    fn key_with_string_type() {
-        let key = Key::new(|s: &String| vec![s.clone()]);
-        let values = key.extract(&"hello world".to_owned());
-        assert_eq!(values, vec!["hello world"]);
+    fn book_author() -> Key<Book> {
+        Key::from_fn(|b: &Book| b.author)
+    }
+
+    fn book_rank(book: &Book, keys: Vec<Key<Book>>, query: &str, threshold: Ranking) -> Ranking {
+        let opts = MatchSorterOptions {
+            threshold,
+            ..Default::default()
+        };
+        get_highest_ranking(book, &keys, query, &opts).rank
     }
 
     #[test]
-    fn from_fn_with_string_type() {
-        let key = Key::<String>::from_fn(|s| s.as_str());
-        let values = key.extract(&"test".to_owned());
-        assert_eq!(values, vec!["test"]);
+    fn every_require_match_key_must_match() {
+        let keys = || vec![book_title().require_match(), book_author().require_match()];
+        let both = book("Dune", "Dune Estate");
+        let title_only = book("Dune", "Herbert");
+        let author_only = book("Emma", "Dune Estate");
+        assert_eq!(
+            book_rank(&both, keys(), "dune", Ranking::Contains),
+            Ranking::Equal
+        );
+        assert_eq!(
+            book_rank(&title_only, keys(), "dune", Ranking::Contains),
+            Ranking::NoMatch
+        );
+        assert_eq!(
+            book_rank(&author_only, keys(), "dune", Ranking::Contains),
+            Ranking::NoMatch
+        );
     }
 
-    // --- get_item_values tests ---
+    #[test]
+    fn require_match_uses_global_or_key_threshold() {
+        // "dn" fuzzily matches "Dune" but is below the global threshold.
+        let fuzzy_title = book("Dune", "Dn");
+        let keys = || vec![book_title().require_match(), book_author()];
+        assert_eq!(
+            book_rank(&fuzzy_title, keys(), "dn", Ranking::Contains),
+            Ranking::NoMatch
+        );
+        assert!(book_rank(&fuzzy_title, keys(), "dn", Ranking::MATCH_THRESHOLD) > Ranking::NoMatch);
+
+        let lenient = || {
+            vec![
+                book_title()
+                    .require_match()
+                    .threshold(Ranking::MATCH_THRESHOLD),
+                book_author(),
+            ]
+        };
+        assert_eq!(
+            book_rank(&fuzzy_title, lenient(), "dn", Ranking::Contains),
+            Ranking::Equal
+        );
+    }
 
     #[test]
-    fn get_item_values_single_value() {
-        let key = Key::<User>::from_fn(|u| u.name.as_str());
-        let values = get_item_values(&sample_user(), &key);
-        assert_eq!(values, vec!["Alice"]);
+    fn items_without_require_match_keys_are_unaffected() {
+        let emma = book("Emma", "Austen");
+        assert_eq!(
+            book_rank(
+                &emma,
+                vec![book_title(), book_author()],
+                "austen",
+                Ranking::Contains
+            ),
+            Ranking::Equal
+        );
+        // A require-match key without values excludes the item too.
+        let tag_key = Key::new(|b: &Book| b.tags.iter().map(|t| (*t).to_owned()).collect());
+        assert_eq!(
+            book_rank(
+                &emma,
+                vec![book_author(), tag_key.require_match()],
+                "austen",
+                Ranking::Contains
+            ),
+            Ranking::NoMatch
+        );
     }
 
     #[test]
-    fn get_item_values_multi_value() {
-        let key = Key::<User>::from_fn_multi(|u| u.tags.iter().map(|t| t.as_str()).collect());
-        let values = get_item_values(&sample_user(), &key);
-        assert_eq!(values, vec!["admin", "staff"]);
+    fn require_match_checks_rank_after_min_ranking() {
+        let dune = Book {
+            tags: vec!["sci-fi", "desert"],
+            ..book("Dune", "Herbert")
+        };
+        let tags = || Key::new(|b: &Book| b.tags.iter().map(|t| (*t).to_owned()).collect());
+        // "dst" only fuzzily matches "desert", below `Contains`...
+        assert_eq!(
+            book_rank(
+                &dune,
+                vec![book_title(), tags().require_match()],
+                "dst",
+                Ranking::Contains
+            ),
+            Ranking::NoMatch
+        );
+        // ...unless `min_ranking` promotes it first.
+        assert_eq!(
+            book_rank(
+                &dune,
+                vec![
+                    book_title(),
+                    tags().min_ranking(Ranking::Contains).require_match()
+                ],
+                "dst",
+                Ranking::Contains
+            ),
+            Ranking::Contains
+        );
+        // `min_ranking` never promotes `NoMatch`.
+        assert_eq!(
+            book_rank(
+                &dune,
+                vec![
+                    book_author(),
+                    tags().min_ranking(Ranking::Contains).require_match()
+                ],
+                "herbert",
+                Ranking::Contains
+            ),
+            Ranking::NoMatch
+        );
     }
 
     #[test]
-    fn get_item_values_empty() {
-        let key = Key::new(|_: &User| vec![]);
-        let values = get_item_values(&sample_user(), &key);
-        assert!(values.is_empty());
+    fn require_match_select_key_needs_any_sub_key_match() {
+        let keys = || {
+            vec![
+                Key::select(vec![book_title(), book_author()]).require_match(),
+                Key::new(|b: &Book| b.tags.iter().map(|t| (*t).to_owned()).collect()),
+            ]
+        };
+        let tagged = |title, author| Book {
+            tags: vec!["classic"],
+            ..book(title, author)
+        };
+        assert_eq!(
+            book_rank(
+                &tagged("Emma", "Austen"),
+                keys(),
+                "classic",
+                Ranking::Contains
+            ),
+            Ranking::NoMatch
+        );
+        assert_eq!(
+            book_rank(
+                &tagged("Emma", "Classic Press"),
+                keys(),
+                "classic",
+                Ranking::Contains
+            ),
+            Ranking::CaseSensitiveEqual
+        );
     }
 
-    // --- get_highest_ranking tests ---
+    // --- Key::weight / priority tests ---
 
-    fn default_opts<T>() -> MatchSorterOptions<T> {
-        MatchSorterOptions::default()
+    fn book_info(book: &Book, keys: &[Key<Book>], query: &str) -> RankingInfo {
+        get_highest_ranking(book, keys, query, &MatchSorterOptions::default())
     }
 
     #[test]
-    fn highest_ranking_single_key_exact_match() {
-        // "Alice" queried with "Alice" -> CaseSensitiveEqual
-        let keys = vec![Key::new(|u: &User| vec![u.name.clone()])];
-        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
-        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
-        assert_eq!(info.ranked_value, "Alice");
-        assert_eq!(info.key_index, 0);
-        assert_eq!(info.key_threshold, None);
+    fn weight_by_position_decays_monotonically() {
+        let keys = Key::weight_by_position((0..6).map(|_| book_title()).collect(), 0.8);
+        let weights: Vec<f64> = keys.iter().map(Key::weight_value).collect();
+        assert_eq!(weights[0], 1.0);
+        assert!(weights.windows(2).all(|w| w[1] < w[0]), "{weights:?}");
+        assert!((weights[5] - 0.8f64.powi(5)).abs() < 1e-12);
     }
 
     #[test]
-    fn highest_ranking_picks_best_across_multiple_keys() {
-        // Key 0 extracts email ("alice@example.com"), query "Alice" -> Contains
-        // Key 1 extracts name ("Alice"), query "Alice" -> CaseSensitiveEqual
-        let keys: Vec<Key<User>> = vec![
-            Key::new(|u: &User| vec![u.email.clone()]),
-            Key::new(|u: &User| vec![u.name.clone()]),
-        ];
-        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
-        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
-        assert_eq!(info.ranked_value, "Alice");
-        // Key 0 produces 1 value (index 0), key 1 produces 1 value (index 1)
-        assert_eq!(info.key_index, 1);
+    fn weight_by_position_boundaries() {
+        let emma = book("Persuasion", "Emma Tennant");
+        let collapsed = Key::weight_by_position(vec![book_title(), book_author()], 0.0);
+        assert_eq!(
+            collapsed.iter().map(Key::weight_value).collect::<Vec<_>>(),
+            [1.0, 0.0]
+        );
+        assert_eq!(book_info(&emma, &collapsed, "emma").rank, Ranking::NoMatch);
+        assert_eq!(
+            book_info(&emma, &collapsed, "persuasion").rank,
+            Ranking::Equal
+        );
+
+        let uniform = Key::weight_by_position(vec![book_title(), book_author()], 1.0);
+        assert!(uniform.iter().all(|key| key.weight_value() == 1.0));
+        let plain = [book_title(), book_author()];
+        for query in ["emma", "tennant", "pers", "et", "zzz"] {
+            assert_eq!(
+                book_info(&emma, &uniform, query),
+                book_info(&emma, &plain, query),
+                "{query}"
+            );
+        }
     }
 
     #[test]
-    fn highest_ranking_max_ranking_clamps_down() {
-        // "Alice" queried with "Alice" would normally be CaseSensitiveEqual,
-        // but max_ranking = Contains clamps it down to Contains.
-        let keys = vec![Key::new(|u: &User| vec![u.name.clone()]).max_ranking(Ranking::Contains)];
-        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
-        assert_eq!(info.rank, Ranking::Contains);
+    fn weight_scales_ranks_in_get_highest_ranking() {
+        let emma = book("Emma", "Emma Tennant");
+        let info = book_info(&emma, &[book_title(), book_author()], "emma");
+        assert_eq!((info.rank, info.key_index), (Ranking::Equal, 0));
+
+        // Halved, the title's Equal match drops below the author's
+        // StartsWith match.
+        let keys = [book_title().weight(0.5), book_author()];
+        let info = book_info(&emma, &keys, "emma");
+        assert_eq!(info.rank, Ranking::StartsWith);
+        assert_eq!(info.ranked_value, "Emma Tennant");
+        assert_eq!(info.key_index, 1);
+
+        let keys = [book_title(), book_author().weight(0.5)];
+        let info = book_info(&emma, &keys, "tennant");
+        // WordStartsWith is 57%; half of it is just above Acronym's 28%.
+        assert_eq!(info.rank, Ranking::Acronym);
+        assert_eq!(
+            book_info(&emma, &[book_title().weight(2.0)], "em").rank,
+            Ranking::CaseSensitiveEqual
+        );
     }
 
     #[test]
-    fn highest_ranking_max_ranking_clamps_starts_with_to_contains() {
-        // "Alice" queried with "ali" -> StartsWith normally, clamped to Contains
-        let keys = vec![Key::new(|u: &User| vec![u.name.clone()]).max_ranking(Ranking::Contains)];
-        let info = get_highest_ranking(&sample_user(), &keys, "ali", &default_opts());
-        assert_eq!(info.rank, Ranking::Contains);
+    fn priority_breaks_ties_between_equal_ranks() {
+        let dune = book("Dune", "Dune");
+        assert_eq!(
+            book_info(&dune, &[book_title(), book_author()], "dune").key_index,
+            0
+        );
+        let keys = [book_title(), book_author().priority(1)];
+        assert_eq!(book_info(&dune, &keys, "dune").key_index, 1);
+        let keys = [book_title().priority(1), book_author().priority(1)];
+        assert_eq!(book_info(&dune, &keys, "dune").key_index, 0);
+
+        // Priority never outweighs a better rank.
+        let keys = [book_title(), book_author().priority(5)];
+        let info = book_info(&book("Dune", "Dune Messiah"), &keys, "dune");
+        assert_eq!((info.rank, info.key_index), (Ranking::Equal, 0));
     }
 
-    #[test]
-    fn highest_ranking_min_ranking_promotes_matches_to_contains() {
-        // "playground" queried with "plgnd" -> Matches(~1.11) normally.
-        // min_ranking = Contains promotes it up to Contains.
-        let item = "playground".to_owned();
-        let keys = vec![Key::new(|s: &String| vec![s.clone()]).min_ranking(Ranking::Contains)];
-        let info = get_highest_ranking(&item, &keys, "plgnd", &default_opts());
-        assert_eq!(info.rank, Ranking::Contains);
+    // --- Key::or_default tests ---
+
+    struct Contact {
+        nickname: Option<&'static str>,
+        email: &'static str,
+    }
+
+    fn nickname() -> Key<Contact> {
+        Key::from_fn_multi(|c: &Contact| c.nickname.into_iter().collect())
+    }
+
+    fn email() -> Key<Contact> {
+        Key::from_fn(|c: &Contact| c.email)
     }
 
     #[test]
-    fn highest_ranking_min_ranking_does_not_promote_no_match() {
-        // "abc" queried with "xyz" -> NoMatch. min_ranking = Contains should
-        // NOT promote it; NoMatch stays NoMatch.
-        let item = "abc".to_owned();
-        let keys = vec![Key::new(|s: &String| vec![s.clone()]).min_ranking(Ranking::Contains)];
-        let info = get_highest_ranking(&item, &keys, "xyz", &default_opts());
-        assert_eq!(info.rank, Ranking::NoMatch);
+    fn or_default_used_for_empty_values() {
+        let anonymous = Contact {
+            nickname: None,
+            email: "x@example.com",
+        };
+        assert_eq!(
+            nickname().or_default_str("unknown").extract(&anonymous),
+            ["unknown"]
+        );
+        assert_eq!(
+            nickname()
+                .or_default_fn(|c| c.email.to_uppercase())
+                .extract(&anonymous),
+            ["X@EXAMPLE.COM"]
+        );
+        // A skipped item still yields nothing.
+        let key = nickname().or_default_str("unknown").skip_if(|_| true);
+        let opts = MatchSorterOptions::default();
+        assert_eq!(
+            get_highest_ranking(&anonymous, &[key], "unknown", &opts).rank,
+            Ranking::NoMatch
+        );
     }
 
     #[test]
-    fn highest_ranking_tie_break_lower_key_index_wins() {
-        // Both keys produce the same value "Alice" with the same ranking.
-        // The first key's value (key_index 0) should win.
-        let keys: Vec<Key<User>> = vec![
-            Key::new(|u: &User| vec![u.name.clone()]),
-            Key::new(|u: &User| vec![u.name.clone()]),
-        ];
-        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
-        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
-        assert_eq!(info.key_index, 0);
+    fn or_default_not_called_for_present_values() {
+        let calls = Rc::new(std::cell::Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let key = nickname().or_default_fn(move |_| {
+            counter.set(counter.get() + 1);
+            "unknown".to_owned()
+        });
+        let named = Contact {
+            nickname: Some("Ace"),
+            email: "a@example.com",
+        };
+        assert_eq!(key.extract(&named), ["Ace"]);
+        assert_eq!(calls.get(), 0);
     }
 
     #[test]
-    fn highest_ranking_tie_break_with_clamping() {
-        // Key 0: name "Alice", max_ranking = Contains -> clamped to Contains
-        // Key 1: email "alice@example.com", query "alice" -> StartsWith,
-        //        max_ranking = Contains -> clamped to Contains
-        // Both produce Contains, but key_index 0 wins.
-        let keys: Vec<Key<User>> = vec![
-            Key::new(|u: &User| vec![u.name.clone()]).max_ranking(Ranking::Contains),
-            Key::new(|u: &User| vec![u.email.clone()]).max_ranking(Ranking::Contains),
-        ];
-        let info = get_highest_ranking(&sample_user(), &keys, "alice", &default_opts());
+    fn or_default_value_participates_in_ranking() {
+        let keys = [nickname().or_default_str("(no nickname)"), email()];
+        let opts = MatchSorterOptions::default();
+        let anonymous = Contact {
+            nickname: None,
+            email: "x@example.com",
+        };
+        let info = get_highest_ranking(&anonymous, &keys, "no nick", &opts);
         assert_eq!(info.rank, Ranking::Contains);
+        assert_eq!(info.ranked_value, "(no nickname)");
         assert_eq!(info.key_index, 0);
-        assert_eq!(info.ranked_value, "Alice");
     }
 
     #[test]
-    fn highest_ranking_key_threshold_reflected() {
-        // Key with a threshold set -- the returned RankingInfo should have
-        // the key's threshold in key_threshold.
-        let keys = vec![Key::new(|u: &User| vec![u.name.clone()]).threshold(Ranking::StartsWith)];
-        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
-        assert_eq!(info.key_threshold, Some(Ranking::StartsWith));
+    fn or_default_keeps_key_index_stable() {
+        let opts = MatchSorterOptions::default();
+        let named = Contact {
+            nickname: Some("Ace"),
+            email: "x@example.com",
+        };
+        let anonymous = Contact {
+            nickname: None,
+            ..named
+        };
+        let keys = [nickname().or_default_str("unknown"), email()];
+        for contact in [&named, &anonymous] {
+            let info = get_highest_ranking(contact, &keys, "x@example", &opts);
+            assert_eq!(info.key_index, 1);
+        }
+        // Without the default, the missing nickname shifts the email's index.
+        let keys = [nickname(), email()];
+        let info = get_highest_ranking(&anonymous, &keys, "x@example", &opts);
+        assert_eq!(info.key_index, 0);
     }
 
     #[test]
-    fn highest_ranking_key_threshold_none_when_not_set() {
-        let keys = vec![Key::new(|u: &User| vec![u.name.clone()])];
-        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
-        assert_eq!(info.key_threshold, None);
+    fn with_default_value_substituted_for_empty_values() {
+        let anonymous = Contact {
+            nickname: None,
+            email: "x@example.com",
+        };
+        let named = Contact {
+            nickname: Some("Ace"),
+            ..anonymous
+        };
+        let key = nickname().with_default_value(String::from("unknown"));
+        assert_eq!(key.extract(&anonymous), ["unknown"]);
+        assert_eq!(key.extract(&named), ["Ace"]);
     }
 
     #[test]
-    fn highest_ranking_multi_value_key_best_value_wins() {
-        // Key extracts tags: ["admin", "staff"]. Query "admin" matches
-        // "admin" as CaseSensitiveEqual but "staff" as NoMatch.
-        // Best should be CaseSensitiveEqual for "admin".
-        let keys = vec![Key::new(|u: &User| u.tags.clone())];
-        let info = get_highest_ranking(&sample_user(), &keys, "admin", &default_opts());
-        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
-        assert_eq!(info.ranked_value, "admin");
+    fn with_default_value_matches_query() {
+        let opts = MatchSorterOptions::default();
+        let anonymous = Contact {
+            nickname: None,
+            email: "x@example.com",
+        };
+        let keys = [nickname().with_default_value("unknown"), email()];
+        let info = get_highest_ranking(&anonymous, &keys, "unk", &opts);
+        assert_eq!(info.rank, Ranking::StartsWith);
+        assert_eq!(info.ranked_value, "unknown");
         assert_eq!(info.key_index, 0);
     }
 
     #[test]
-    fn highest_ranking_flattened_index_across_keys() {
-        // Key 0 extracts tags: ["admin", "staff"] (indices 0, 1)
-        // Key 1 extracts name: ["Alice"] (index 2)
-        // Query "Alice" matches name at CaseSensitiveEqual (best).
-        let keys: Vec<Key<User>> = vec![
-            Key::new(|u: &User| u.tags.clone()),
-            Key::new(|u: &User| vec![u.name.clone()]),
-        ];
-        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
-        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
-        assert_eq!(info.key_index, 2);
+    fn with_default_value_satisfies_required() {
+        let opts = MatchSorterOptions::default();
+        let anonymous = Contact {
+            nickname: None,
+            email: "x@example.com",
+        };
+        let keys = [nickname().required(), email()];
+        let info = get_highest_ranking(&anonymous, &keys, "x@example", &opts);
+        assert_eq!(info.rank, Ranking::NoMatch);
+
+        // The default counts as a value, so the item is no longer excluded.
+        for key in [
+            nickname().with_default_value("unknown").required(),
+            nickname().required().with_default_value("unknown"),
+        ] {
+            let info = get_highest_ranking(&anonymous, &[key, email()], "x@example", &opts);
+            assert_eq!(info.rank, Ranking::StartsWith);
+            assert_eq!(info.key_index, 1);
+        }
     }
 
-    #[test]
-    fn highest_ranking_no_keys_returns_no_match() {
-        let keys: Vec<Key<User>> = vec![];
-        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
-        assert_eq!(info.rank, Ranking::NoMatch);
+    // -----------------------------------------------------------------------
+    // Dot-path keys
+    // -----------------------------------------------------------------------
+
+    struct Post {
+        title: String,
+        tags: Vec<String>,
     }
 
-    #[test]
-    fn highest_ranking_empty_extractor_returns_no_match() {
-        let keys = vec![Key::new(|_: &User| vec![])];
-        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
-        assert_eq!(info.rank, Ranking::NoMatch);
+    impl NestedExtractor for Post {
+        fn get_field(&self, field: &str) -> Option<String> {
+            (field == "title").then(|| self.title.clone())
+        }
+
+        fn get_list(&self, field: &str) -> Vec<String> {
+            if field == "tags" {
+                self.tags.clone()
+            } else {
+                self.get_field(field).into_iter().collect()
+            }
+        }
     }
 
-    #[test]
-    fn highest_ranking_max_ranking_does_not_affect_lower_ranks() {
-        // max_ranking = StartsWith. Query "admin" vs "admin" -> CaseSensitiveEqual,
-        // clamped to StartsWith. But if the natural rank is Contains (lower
-        // than max_ranking), no clamping occurs.
-        let item = "xxadminxx".to_owned();
-        let keys = vec![Key::new(|s: &String| vec![s.clone()]).max_ranking(Ranking::StartsWith)];
-        // "xxadminxx" contains "admin" -> Contains, which is below StartsWith
-        let info = get_highest_ranking(&item, &keys, "admin", &default_opts());
-        assert_eq!(info.rank, Ranking::Contains);
+    fn row(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+            .collect()
     }
 
     #[test]
-    fn highest_ranking_min_ranking_does_not_affect_higher_ranks() {
-        // min_ranking = Contains. If the rank is already above Contains
-        // (e.g., StartsWith), it should not be affected.
-        let keys = vec![Key::new(|u: &User| vec![u.name.clone()]).min_ranking(Ranking::Contains)];
-        // "Alice" queried with "ali" -> StartsWith, which is above Contains
-        let info = get_highest_ranking(&sample_user(), &keys, "ali", &default_opts());
-        assert_eq!(info.rank, Ranking::StartsWith);
+    fn dot_path_single_segment_matches_direct_access() {
+        let post = Post {
+            title: "Rust tips".to_owned(),
+            tags: vec![],
+        };
+        let key = Key::<Post>::from_nested_extractor("title");
+        assert_eq!(key.extract(&post), vec![post.title.clone()]);
+
+        let map = row(&[("city", "Lisbon")]);
+        assert_eq!(Key::from_dot_path_on_map("city").extract(&map), ["Lisbon"]);
+        assert!(
+            Key::from_dot_path_on_map("country")
+                .extract(&map)
+                .is_empty()
+        );
     }
 
     #[test]
-    fn highest_ranking_both_clamps_applied() {
-        // max_ranking = Contains and min_ranking = Contains effectively
-        // forces all non-NoMatch results to exactly Contains.
-        let keys = vec![
-            Key::new(|u: &User| vec![u.name.clone()])
-                .min_ranking(Ranking::Contains)
-                .max_ranking(Ranking::Contains),
-        ];
-        // "Alice" queried with "Alice" -> CaseSensitiveEqual, clamped to Contains
-        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
-        assert_eq!(info.rank, Ranking::Contains);
+    fn dot_path_two_segments_walk_map_of_maps() {
+        let user = HashMap::from([
+            ("address".to_owned(), row(&[("city", "Lisbon")])),
+            ("company".to_owned(), row(&[("city", "Porto")])),
+        ]);
+        let key =
+            Key::<HashMap<String, HashMap<String, String>>>::from_nested_extractor("address.city");
+        assert_eq!(key.extract(&user), ["Lisbon"]);
     }
 
     #[test]
-    fn highest_ranking_winning_key_threshold_from_correct_key() {
-        // Key 0 has threshold StartsWith, extracts email -> Contains for "alice"
-        // Key 1 has threshold Acronym, extracts name -> CaseSensitiveEqual for "Alice"
-        // Key 1 wins, so key_threshold should be Acronym (key 1's threshold).
-        let keys: Vec<Key<User>> = vec![
-            Key::new(|u: &User| vec![u.email.clone()]).threshold(Ranking::StartsWith),
-            Key::new(|u: &User| vec![u.name.clone()]).threshold(Ranking::Acronym),
-        ];
-        let info = get_highest_ranking(&sample_user(), &keys, "Alice", &default_opts());
-        assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
-        assert_eq!(info.key_threshold, Some(Ranking::Acronym));
+    fn dot_path_missing_intermediate_is_empty() {
+        let user = HashMap::from([("address".to_owned(), row(&[("city", "Lisbon")]))]);
+        for path in ["billing.city", "address.zip", "address"] {
+            let key = Key::<HashMap<String, HashMap<String, String>>>::from_nested_extractor(path);
+            assert!(key.extract(&user).is_empty(), "{path}");
+        }
+        // A flat map has nothing to descend into.
+        let flat = row(&[("address", "Lisbon")]);
+        assert!(
+            Key::from_dot_path_on_map("address.city")
+                .extract(&flat)
+                .is_empty()
+        );
     }
 
     #[test]
-    fn highest_ranking_keep_diacritics_option_passed() {
-        // "cafe" + combining acute = "caf\u{e9}" equivalent. Query "cafe" with
-        // keep_diacritics=true means they won't match (different chars).
-        let item = "caf\u{e9}".to_owned();
-        let keys = vec![Key::new(|s: &String| vec![s.clone()])];
-
-        let opts_strip = MatchSorterOptions {
-            keep_diacritics: false,
-            ..Default::default()
+    fn dot_path_index_selects_list_element() {
+        let post = Post {
+            title: "Rust tips".to_owned(),
+            tags: vec!["rust".to_owned(), "tips".to_owned()],
         };
-        let info_strip = get_highest_ranking(&item, &keys, "cafe", &opts_strip);
-        assert_eq!(info_strip.rank, Ranking::CaseSensitiveEqual);
+        let key = |path| Key::<Post>::from_nested_extractor(path);
+        assert_eq!(key("tags[0]").extract(&post), ["rust"]);
+        assert_eq!(key("tags[1]").extract(&post), ["tips"]);
+        assert!(key("tags[2]").extract(&post).is_empty());
+        // Fields without a list fall back to their single value.
+        assert_eq!(key("title[0]").extract(&post), ["Rust tips"]);
+    }
 
-        let opts_keep = MatchSorterOptions {
-            keep_diacritics: true,
-            ..Default::default()
+    #[test]
+    fn dot_path_malformed_paths_are_empty() {
+        let post = Post {
+            title: "Rust tips".to_owned(),
+            tags: vec!["rust".to_owned()],
         };
-        let info_keep = get_highest_ranking(&item, &keys, "cafe", &opts_keep);
-        assert_eq!(info_keep.rank, Ranking::NoMatch);
+        for path in [
+            "",
+            ".title",
+            "title.",
+            "tags[x]",
+            "tags[0",
+            "tags[0].name",
+            "[0]",
+        ] {
+            let key = Key::<Post>::from_nested_extractor(path);
+            assert!(key.extract(&post).is_empty(), "{path:?}");
+        }
     }
 }
@@ -0,0 +1,346 @@
+//! Per-language text normalization for multilingual search.
+//!
+//! A key marked with [`Key::multi_language`](crate::key::Key::multi_language)
+//! reports each item's language as a BCP-47 tag. Before ranking, the key's
+//! values and the query are both passed through that language's normalizer,
+//! looked up first in
+//! [`MatchSorterOptions::language_normalizers`](crate::MatchSorterOptions::language_normalizers)
+//! and then among the built-in normalizers here.
+//!
+//! A key with a fixed language, set with
+//! [`Key::language`](crate::key::Key::language), is normalized the same way
+//! and is also lowercased with that language's case rules; see
+//! [`LanguageAwareOptions`](crate::language::LanguageAwareOptions).
+
+use std::fmt;
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Fold German umlauts and the sharp s into their standard two-letter
+/// spellings: `ä` -> `ae`, `ö` -> `oe`, `ü` -> `ue`, `ß` -> `ss`.
+///
+/// Diacritics stripping would turn `"Müller"` into `"Muller"`; this keeps
+/// the umlaut as an `e`, so `"Müller"` and `"Mueller"` match each other
+/// but not `"Muller"`. Uppercase umlauts keep their case (`Ä` -> `Ae`),
+/// as does the capital sharp s (`ẞ` -> `SS`). Decomposed umlauts are
+/// composed first.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::language::normalize_german;
+///
+/// assert_eq!(normalize_german("Müller"), "Mueller");
+/// assert_eq!(normalize_german("Straße"), "Strasse");
+/// assert_eq!(normalize_german("\u{00c4}rger"), "Aerger");
+/// ```
+pub fn normalize_german(s: &str) -> String {
+    if s.is_ascii() {
+        return s.to_owned();
+    }
+    let mut out = String::with_capacity(s.len() + 4);
+    for c in s.nfc() {
+        match c {
+            'ä' => out.push_str("ae"),
+            'ö' => out.push_str("oe"),
+            'ü' => out.push_str("ue"),
+            'Ä' => out.push_str("Ae"),
+            'Ö' => out.push_str("Oe"),
+            'Ü' => out.push_str("Ue"),
+            'ß' => out.push_str("ss"),
+            'ẞ' => out.push_str("SS"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Fold the four Turkish i's -- `i`, dotless `ı`, dotted capital `İ` and
+/// capital `I` -- into a plain `i`.
+///
+/// Default lowercasing maps `İ` to `i` plus a combining dot and leaves `ı`
+/// alone, so `"İstanbul"`, `"ISTANBUL"` and `"ıstanbul"` would not equal
+/// `"istanbul"`. Folding all four to `i` makes them equal, whichever
+/// keyboard the query was typed on. A combining dot above an `i` is
+/// dropped too.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::language::normalize_turkish;
+///
+/// assert_eq!(normalize_turkish("\u{0130}stanbul"), "istanbul");
+/// assert_eq!(normalize_turkish("D\u{0130}YARBAKIR"), "DiYARBAKiR");
+/// assert_eq!(normalize_turkish("\u{0131}s\u{0131}k"), "isik");
+/// ```
+pub fn normalize_turkish(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut after_i = false;
+    for c in s.chars() {
+        match c {
+            'i' | 'ı' | 'İ' | 'I' => {
+                out.push('i');
+                after_i = true;
+            }
+            // A decomposed `İ` or an explicit dot on a lowercase `i`.
+            '\u{0307}' if after_i => after_i = false,
+            _ => {
+                out.push(c);
+                after_i = false;
+            }
+        }
+    }
+    out
+}
+
+/// Map katakana to the corresponding hiragana, so that both scripts of the
+/// same word match each other.
+///
+/// Covers the full-width katakana letters `ァ`-`ヶ` and the iteration marks
+/// `ヽ` and `ヾ`. The prolonged sound mark `ー`, half-width katakana and
+/// everything else are left alone.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::language::normalize_japanese;
+///
+/// assert_eq!(normalize_japanese("カタカナ"), "かたかな");
+/// assert_eq!(normalize_japanese("ラーメン"), "らーめん");
+/// ```
+pub fn normalize_japanese(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '\u{30a1}'..='\u{30f6}' | '\u{30fd}' | '\u{30fe}' => {
+                char::from_u32(c as u32 - 0x60).unwrap_or(c)
+            }
+            _ => c,
+        })
+        .collect()
+}
+
+/// The primary language subtag of a BCP-47 tag, e.g. `"de"` for
+/// `"de-AT"`.
+pub(crate) fn primary_subtag(tag: &str) -> &str {
+    tag.split(['-', '_']).next().unwrap_or(tag)
+}
+
+/// Whether `tag` names Turkish or Azerbaijani, whose dotted and dotless i's
+/// lowercase differently from the Unicode default.
+fn is_turkic(tag: &str) -> bool {
+    let primary = primary_subtag(tag);
+    primary.eq_ignore_ascii_case("tr") || primary.eq_ignore_ascii_case("az")
+}
+
+/// A custom case fold: the lowercase form of a character in the language
+/// named by a BCP-47 tag. See
+/// [`MatchSorterOptions::custom_case_fold`](crate::MatchSorterOptions::custom_case_fold).
+pub type CaseFold = dyn Fn(char, &str) -> String;
+
+/// A language tag together with the case folding used for it.
+///
+/// Default lowercasing follows the language-neutral Unicode mappings, which
+/// are wrong for Turkish and Azerbaijani: there `I` lowercases to the
+/// dotless `ı` and `İ` to a plain `i`, not to `i` plus a combining dot.
+/// Keys marked with [`Key::language`](crate::key::Key::language) lowercase
+/// their values and the query through these options, so that `"İstanbul"`
+/// equals `"istanbul"` in a Turkish key. Other languages lowercase as
+/// usual unless a custom case fold is given.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::language::LanguageAwareOptions;
+///
+/// let turkish = LanguageAwareOptions::new("tr");
+/// assert_eq!(turkish.lowercase("\u{130}stanbul"), "istanbul");
+/// assert_eq!(turkish.lowercase("ISPARTA"), "\u{131}sparta");
+///
+/// let english = LanguageAwareOptions::new("en");
+/// assert_eq!(english.lowercase("\u{130}stanbul"), "i\u{307}stanbul");
+/// ```
+#[derive(Clone, Copy)]
+pub struct LanguageAwareOptions<'a> {
+    /// The BCP-47 language tag.
+    pub tag: &'a str,
+    /// Replaces the built-in case rules when `Some`; called with each
+    /// character and [`tag`](Self::tag).
+    pub case_fold: Option<&'a CaseFold>,
+}
+
+impl<'a> LanguageAwareOptions<'a> {
+    /// Options for `tag` with the built-in case rules.
+    #[must_use]
+    pub fn new(tag: &'a str) -> Self {
+        Self {
+            tag,
+            case_fold: None,
+        }
+    }
+
+    /// Lowercase `s` with this language's case rules.
+    #[must_use]
+    pub fn lowercase(&self, s: &str) -> String {
+        let mut buf = String::with_capacity(s.len());
+        self.lowercase_into(s, &mut buf);
+        buf
+    }
+
+    /// Whether these rules can lowercase differently from
+    /// [`lowercase_into`](crate::ranking::lowercase_into).
+    pub(crate) fn folds_specially(&self) -> bool {
+        self.case_fold.is_some() || is_turkic(self.tag)
+    }
+
+    /// [`lowercase`](Self::lowercase) into `buf`, replacing its contents.
+    pub(crate) fn lowercase_into(&self, s: &str, buf: &mut String) {
+        buf.clear();
+        let turkic = is_turkic(self.tag);
+        for c in s.chars() {
+            self.push_lowercase(c, turkic, buf);
+        }
+    }
+
+    /// The UTF-8 length of `c` lowercased with these rules.
+    pub(crate) fn lowercase_len(&self, c: char) -> usize {
+        let mut buf = String::new();
+        self.push_lowercase(c, is_turkic(self.tag), &mut buf);
+        buf.len()
+    }
+
+    fn push_lowercase(&self, c: char, turkic: bool, buf: &mut String) {
+        match (self.case_fold, c) {
+            (Some(fold), _) => buf.push_str(&fold(c, self.tag)),
+            (None, 'I') if turkic => buf.push('ı'),
+            (None, 'İ') if turkic => buf.push('i'),
+            (None, _) => buf.extend(c.to_lowercase()),
+        }
+    }
+}
+
+impl fmt::Debug for LanguageAwareOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LanguageAwareOptions")
+            .field("tag", &self.tag)
+            .field(
+                "case_fold",
+                if self.case_fold.is_some() {
+                    &"Some(<fn>)"
+                } else {
+                    &"None"
+                },
+            )
+            .finish()
+    }
+}
+
+/// A built-in normalizer.
+type Normalizer = fn(&str) -> String;
+
+/// The built-in normalizers by primary language subtag.
+static BUILTINS: [(&str, Normalizer); 4] = [
+    ("de", normalize_german),
+    ("tr", normalize_turkish),
+    ("az", normalize_turkish),
+    ("ja", normalize_japanese),
+];
+
+/// [`builtin_normalizer`], borrowed from the static table.
+pub(crate) fn builtin_entry(tag: &str) -> Option<&'static Normalizer> {
+    let primary = primary_subtag(tag);
+    BUILTINS
+        .iter()
+        .find(|(lang, _)| primary.eq_ignore_ascii_case(lang))
+        .map(|(_, normalize)| normalize)
+}
+
+/// The built-in normalizer for a BCP-47 tag, matched on its primary
+/// language subtag, or `None` for languages without one.
+///
+/// German (`de`), Turkish (`tr`, and Azerbaijani `az`, which shares its
+/// i's) and Japanese (`ja`) have built-in normalizers.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::language::builtin_normalizer;
+///
+/// let german = builtin_normalizer("de-CH").unwrap();
+/// assert_eq!(german("Grüße"), "Gruesse");
+/// assert!(builtin_normalizer("en").is_none());
+/// ```
+pub fn builtin_normalizer(tag: &str) -> Option<fn(&str) -> String> {
+    builtin_entry(tag).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn german_folds_decomposed_umlauts() {
+        assert_eq!(normalize_german("Mu\u{0308}ller"), "Mueller");
+        assert_eq!(normalize_german("GROẞ"), "GROSS");
+        assert_eq!(normalize_german("plain"), "plain");
+    }
+
+    #[test]
+    fn turkish_drops_dot_only_after_i() {
+        assert_eq!(normalize_turkish("I\u{0307}zmir"), "izmir");
+        assert_eq!(normalize_turkish("i\u{0307}"), "i");
+        assert_eq!(normalize_turkish("a\u{0307}"), "a\u{0307}");
+    }
+
+    #[test]
+    fn japanese_leaves_other_scripts_alone() {
+        assert_eq!(normalize_japanese("ヽヾ"), "ゝゞ");
+        assert_eq!(normalize_japanese("漢字abc"), "漢字abc");
+        // Half-width katakana is out of range.
+        assert_eq!(normalize_japanese("\u{ff76}"), "\u{ff76}");
+    }
+
+    #[test]
+    fn builtin_lookup_uses_primary_subtag() {
+        assert!(builtin_normalizer("TR").is_some());
+        assert!(builtin_normalizer("ja_JP").is_some());
+        assert!(builtin_normalizer("az-Latn-AZ").is_some());
+        assert!(builtin_normalizer("fr-CA").is_none());
+        assert!(builtin_normalizer("").is_none());
+        assert_eq!(primary_subtag("de-AT"), "de");
+    }
+
+    #[test]
+    fn turkish_case_rules_cover_both_capitals() {
+        let turkish = LanguageAwareOptions::new("tr-TR");
+        assert_eq!(turkish.lowercase("D\u{130}YARBAKIR"), "diyarbak\u{131}r");
+        assert_eq!(LanguageAwareOptions::new("az").lowercase("I"), "\u{131}");
+        assert!(turkish.folds_specially());
+        assert_eq!(turkish.lowercase_len('\u{130}'), 1);
+    }
+
+    #[test]
+    fn other_languages_lowercase_as_usual() {
+        let german = LanguageAwareOptions::new("de");
+        assert!(!german.folds_specially());
+        assert_eq!(german.lowercase("ISTANBUL \u{130}"), "istanbul i\u{307}");
+        assert_eq!(german.lowercase_len('\u{130}'), 3);
+    }
+
+    #[test]
+    fn custom_case_fold_receives_tag() {
+        let fold = |c: char, tag: &str| match (c, tag) {
+            ('Q', "xx") => "k".to_owned(),
+            _ => c.to_lowercase().collect(),
+        };
+        let options = LanguageAwareOptions {
+            tag: "xx",
+            case_fold: Some(&fold),
+        };
+        assert!(options.folds_specially());
+        assert_eq!(options.lowercase("QI"), "ki");
+        assert_eq!(
+            format!("{options:?}"),
+            r#"LanguageAwareOptions { tag: "xx", case_fold: "Some(<fn>)" }"#
+        );
+    }
+}
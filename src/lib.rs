@@ -33,7 +33,7 @@
 //! use matchsorter::{match_sorter, MatchSorterOptions};
 //!
 //! let items = ["apple", "banana", "grape", "pineapple"];
-//! let results = match_sorter(&items, "ap", MatchSorterOptions::default());
+//! let results = match_sorter(&items, "ap", &MatchSorterOptions::default());
 //! // "apple" (StartsWith), "grape" (Contains), "pineapple" (Contains); "banana" is dropped
 //! assert_eq!(results, vec![&"apple", &"grape", &"pineapple"]);
 //! ```
@@ -66,7 +66,7 @@
 //!     ..Default::default()
 //! };
 //!
-//! let results = match_sorter(&users, "ali", opts);
+//! let results = match_sorter(&users, "ali", &opts);
 //! // "Alice" (StartsWith on name), "Malika" (Contains "ali" in name); "Bob" is dropped
 //! assert_eq!(results.len(), 2);
 //! assert_eq!(results[0].name, "Alice");
@@ -85,7 +85,7 @@
 //!     threshold: Ranking::Contains,
 //!     ..Default::default()
 //! };
-//! let results = match_sorter(&items, "pl", opts);
+//! let results = match_sorter(&items, "pl", &opts);
 //! assert_eq!(results.len(), 2);
 //! ```
 //!
@@ -98,7 +98,7 @@
 //! use matchsorter::{match_sorter, MatchSorterOptions};
 //!
 //! let items = ["cafe", "caf\u{00e9}", "coffee"];
-//! let results = match_sorter(&items, "cafe", MatchSorterOptions::default());
+//! let results = match_sorter(&items, "cafe", &MatchSorterOptions::default());
 //! // Both "cafe" and "cafe" (accented) match; "coffee" is dropped
 //! assert_eq!(results.len(), 2);
 //! assert_eq!(results[0], &"cafe");
@@ -115,7 +115,7 @@
 //!     keep_diacritics: true,
 //!     ..Default::default()
 //! };
-//! let results = match_sorter(&items, "cafe", opts);
+//! let results = match_sorter(&items, "cafe", &opts);
 //! // Only the unaccented "cafe" matches when diacritics are preserved
 //! assert_eq!(results, vec![&"cafe"]);
 //! ```
@@ -128,6 +128,8 @@
 //!   [`Key::from_fn`](key::Key::from_fn), [`Key::from_fn_multi`](key::Key::from_fn_multi)
 //! - **Per-key controls** -- `threshold`, `min_ranking`, `max_ranking`
 //! - **Custom sorting** -- replace the tiebreaker or the entire sort
+//! - **Ranking metadata** -- [`match_sorter_ranked`] returns each match as a
+//!   [`RankedItem`] with its rank, matched value and key
 //! - **Zero-copy no-keys mode** -- `&str`, `String`, `Cow<str>` via [`AsMatchStr`]
 //! - **SIMD-accelerated** substring search via `memchr`
 
@@ -143,19 +145,57 @@ pub mod no_keys;
 /// Configuration options for the match-sorting algorithm.
 pub mod options;
 
+/// Paginated access to sorted match results, with an optional result cache.
+pub mod pagination;
+
+/// Incremental re-ranking for type-ahead queries that extend the previous one.
+pub mod incremental;
+
+/// Least-recently-used cache of candidate rankings shared between searches.
+pub mod cache;
+
 /// Sorting logic for ordering matched candidates by rank and tie-breaking criteria.
 pub mod sort;
 
+/// Per-language normalizers (German, Turkish, Japanese) for multilingual keys.
+pub mod language;
+
+/// Phonetic encodings (Soundex) for sound-alike name matching.
+#[cfg(feature = "phonetic")]
+pub mod phonetic;
+
 use std::borrow::Cow;
+#[cfg(feature = "tls_cache")]
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::time::Instant;
 
 // Re-export primary public API types and functions at the crate root.
-pub use key::{Key, RankingInfo, get_highest_ranking, get_item_values};
-pub use no_keys::{AsMatchStr, rank_item};
-pub use options::{MatchSorterOptions, RankedItem};
-pub use ranking::{Ranking, get_match_ranking};
-pub use sort::{default_base_sort, sort_ranked_values};
+pub use cache::RankingCache;
+pub use incremental::IncrementalContext;
+pub use key::{Key, RankingInfo, extract_all_values, get_highest_ranking, get_item_values};
+pub use no_keys::{AsMatchStr, MatchableOsString, MatchablePath, rank_item};
+#[cfg(feature = "async")]
+pub use options::AsyncMatchSorterOptions;
+pub use options::{
+    EarlyStopCriteria, EmptyQueryBehavior, KeysExtendMode, MatchAnnotation, MatchSorterOptions,
+    MatchSorterResult, MatchSorterSnapshot, OwnedRankedItem, PartialMatchSorterOptions, QueryMode,
+    RankedItem, RankingStats, ScoredItem,
+};
+pub use pagination::{PageResult, PaginationCache};
+pub use ranking::{
+    DiacriticsLanguage, DiacriticsMode, FuzzyMode, InvalidRanking, InvalidRankingTier,
+    NormalizationForm, ParseRankingError, PctScale, Ranking, UnicodeCaseFolding,
+    camel_case_splitter, get_match_ranking, ranking_tier_label,
+};
+pub use sort::{
+    default_base_sort, sort_ranked_values, sort_ranked_values_by_tier, unicode_base_sort,
+};
 
-use key::get_highest_ranking_prepared as get_highest_ranking_prepared_impl;
+use key::{KeyScratch, get_highest_ranking_prepared as get_highest_ranking_prepared_impl};
 use no_keys::AsMatchStr as AsMatchStrTrait;
 use ranking::{PreparedQuery, get_match_ranking_prepared as get_match_ranking_prepared_impl};
 use sort::{
@@ -178,6 +218,9 @@ use sort::{
 /// [`AsMatchStr::as_match_str()`]. When keys are provided, each key's extractor
 /// is called and the best ranking across all keys is used.
 ///
+/// The options are borrowed, so one [`MatchSorterOptions`] can serve any
+/// number of searches; [`match_sorter_owned`] takes them by value.
+///
 /// # Arguments
 ///
 /// * `items` - Slice of items to search through
@@ -195,7 +238,7 @@ use sort::{
 /// use matchsorter::{match_sorter, MatchSorterOptions};
 ///
 /// let items = ["apple", "banana", "grape", "pineapple"];
-/// let results = match_sorter(&items, "ap", MatchSorterOptions::default());
+/// let results = match_sorter(&items, "ap", &MatchSorterOptions::default());
 /// // "apple" (StartsWith), "grape" (Contains), "pineapple" (Contains); "banana" is dropped
 /// assert_eq!(results, vec![&"apple", &"grape", &"pineapple"]);
 /// ```
@@ -210,12 +253,105 @@ use sort::{
 ///     threshold: Ranking::Contains,
 ///     ..Default::default()
 /// };
-/// let results = match_sorter(&items, "pl", opts);
+/// let results = match_sorter(&items, "pl", &opts);
 /// // "apple" contains "pl" -> Contains (passes). "playground" starts with
 /// // "pl" -> StartsWith (passes). "banana" has no match above Contains.
 /// assert_eq!(results.len(), 2);
 /// ```
+///
+/// With the `tracing` feature, each call runs in a `match_sorter` span
+/// recording the item count, query and threshold. Every match emits a
+/// `TRACE` event with its ranked value, rank and key index, and a final
+/// `DEBUG` event reports the items processed, the items matched and the
+/// sort duration.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(items = items.len(), value = value, threshold = ?options.threshold)
+    )
+)]
 pub fn match_sorter<'a, T>(
+    items: &'a [T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> Vec<&'a T>
+where
+    T: AsMatchStrTrait,
+{
+    if let Some(unranked) = unranked_items(items, value, options) {
+        return unranked;
+    }
+    match_sorter_ranked(items, value, options)
+        .into_iter()
+        .map(|ri| ri.item)
+        .collect()
+}
+
+/// Like [`match_sorter`], but returns the [`RankedItem`] of each match.
+///
+/// The matches and their order are exactly those of [`match_sorter`],
+/// which is implemented on top of this function, except that with
+/// [`EmptyQueryBehavior::ReturnAllUnsorted`] the empty query still ranks
+/// every item (to fill in its [`RankedItem`]), keeping the matches in
+/// input order. Each [`RankedItem`] also
+/// carries the ranking computed along the way: the rank, the matched value
+/// and key, and the matched portion. `ranked_value` borrows from `items`
+/// in no-keys mode and is owned in keys mode; either way it stays valid as
+/// long as `items` does.
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, sorting, etc.)
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, Ranking, match_sorter_ranked};
+///
+/// let items = ["grape", "apple", "banana"];
+/// let ranked = match_sorter_ranked(&items, "ap", &MatchSorterOptions::default());
+/// assert_eq!(ranked.len(), 2);
+/// assert_eq!(*ranked[0].item, "apple");
+/// assert_eq!(ranked[0].rank, Ranking::StartsWith);
+/// assert_eq!(ranked[1].index, 0);
+/// assert_eq!(ranked[1].matched_portion.as_deref(), Some("ap"));
+/// ```
+pub fn match_sorter_ranked<'a, T>(
+    items: &'a [T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> Vec<RankedItem<'a, T>>
+where
+    T: AsMatchStrTrait,
+{
+    search_ranked(items, value, options, None, None).0
+}
+
+/// Like [`match_sorter`], but takes the options by value.
+///
+/// For callers that build the options for a single search and have no use
+/// for them afterwards.
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, sorting, etc.)
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, match_sorter_owned};
+///
+/// let items = ["apple", "banana", "grape"];
+/// let results = match_sorter_owned(&items, "ap", MatchSorterOptions::default());
+/// assert_eq!(results, vec![&"apple", &"grape"]);
+/// ```
+pub fn match_sorter_owned<'a, T>(
     items: &'a [T],
     value: &str,
     options: MatchSorterOptions<T>,
@@ -223,187 +359,2252 @@ pub fn match_sorter<'a, T>(
 where
     T: AsMatchStrTrait,
 {
-    // Step 1: Rank each item and filter by the effective threshold.
-    // Pre-compute query data once to avoid redundant work per item.
-    let pq = PreparedQuery::new(value, options.keep_diacritics);
-    let finder = if pq.lower.is_empty() {
-        None
-    } else {
-        Some(memchr::memmem::Finder::new(pq.lower.as_bytes()))
-    };
-    // Reusable buffer for lowercasing each candidate (avoids per-item allocation).
-    // Pre-allocate with `value.len().max(32)` so the first `lowercase_into` call
-    // does not trigger a grow-from-zero reallocation; 32 bytes covers most short
-    // candidates while `value.len()` scales for longer queries.
-    let mut candidate_buf = String::with_capacity(value.len().max(32));
+    match_sorter(items, value, &options)
+}
+
+/// Like [`match_sorter`], but returns clones of the matching items.
+///
+/// The result does not borrow `items`, so a function that owns its items
+/// can return the matches. Works in both no-keys and keys mode, since keys
+/// are part of `options`.
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, sorting, etc.)
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, match_sorter_into_owned};
+///
+/// fn search(query: &str) -> Vec<String> {
+///     let items = vec!["apple".to_owned(), "banana".to_owned(), "grape".to_owned()];
+///     match_sorter_into_owned(&items, query, &MatchSorterOptions::default())
+/// }
+///
+/// assert_eq!(search("ap"), vec!["apple", "grape"]);
+/// ```
+pub fn match_sorter_into_owned<T>(
+    items: &[T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> Vec<T>
+where
+    T: AsMatchStrTrait + Clone,
+{
+    match_sorter(items, value, options)
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+/// Number of items ranked between checks of
+/// [`MatchSorterOptions::cancel_token`].
+const CANCEL_CHECK_INTERVAL: usize = 1000;
+
+/// Like [`match_sorter`], but reports whether the search was cancelled.
+///
+/// Before every 1000th item the
+/// [`cancel_token`](MatchSorterOptions::cancel_token) is checked; once it
+/// is `true`, ranking stops and [`MatchSorterResult::Cancelled`] holds the
+/// matches found so far, unsorted, in input order. Without a token, or if
+/// it is never set, the result is [`MatchSorterResult::Complete`] with the
+/// same items [`match_sorter`] returns. With the `tracing` feature it emits
+/// the same events as [`match_sorter`], outside any span of its own.
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, sorting, etc.)
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use matchsorter::{MatchSorterOptions, MatchSorterResult, match_sorter_cancellable};
+///
+/// let cancel = Arc::new(AtomicBool::new(false));
+/// let items = ["apple", "banana", "grape", "pineapple"];
+/// let opts = MatchSorterOptions {
+///     cancel_token: Some(Arc::clone(&cancel)),
+///     ..Default::default()
+/// };
+/// // Another thread may call `cancel.store(true, Ordering::Relaxed)`.
+/// let result = match_sorter_cancellable(&items, "ap", &opts);
+/// assert_eq!(
+///     result,
+///     MatchSorterResult::Complete(vec![&"apple", &"grape", &"pineapple"])
+/// );
+/// ```
+pub fn match_sorter_cancellable<'a, T>(
+    items: &'a [T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> MatchSorterResult<'a, T>
+where
+    T: AsMatchStrTrait,
+{
+    search_cancellable(items, value, options)
+}
 
-    let mut ranked_items: Vec<RankedItem<'a, T>> = Vec::with_capacity(items.len());
+/// The body of [`match_sorter_cancellable`], borrowing the options.
+fn search_cancellable<'a, T>(
+    items: &'a [T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> MatchSorterResult<'a, T>
+where
+    T: AsMatchStrTrait,
+{
+    if let Some(unranked) = unranked_items(items, value, options) {
+        return MatchSorterResult::Complete(unranked);
+    }
+    let cancel = options.cancel_token.as_deref();
+    let (ranked_items, cancelled) = search_ranked(items, value, options, None, cancel);
+    let matches = ranked_items.into_iter().map(|ri| ri.item).collect();
+    if cancelled {
+        MatchSorterResult::Cancelled(matches)
+    } else {
+        MatchSorterResult::Complete(matches)
+    }
+}
 
+/// Rank and sort `items`, checking `cancel` as ranking goes.
+///
+/// Returns the sorted matches and `false`, or, once `cancel` is set, the
+/// matches found so far in input order and `true`. An empty query is
+/// answered per
+/// [`empty_query_behavior`](MatchSorterOptions::empty_query_behavior).
+/// `tokens` are `value`'s prepared tokens, if the caller has them.
+fn search_ranked<'a, T>(
+    items: &'a [T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+    tokens: Option<&QueryTokens>,
+    cancel: Option<&AtomicBool>,
+) -> (Vec<RankedItem<'a, T>>, bool)
+where
+    T: AsMatchStrTrait,
+{
+    if empty_query(value, options) == Some(EmptyQueryBehavior::ReturnEmpty) {
+        return (Vec::new(), false);
+    }
+    let mut ranker = match tokens {
+        Some(tokens) => Ranker::with_tokens(value, options, tokens.clone()),
+        None => Ranker::new(value, options),
+    };
+    let mut ranked_items = ranker.output_vec(items.len());
     for (index, item) in items.iter().enumerate() {
-        let (rank, ranked_value, key_index, key_threshold) = if options.keys.is_empty() {
-            // No-keys mode: rank the item directly via AsMatchStr.
-            let s = item.as_match_str();
-            let rank = get_match_ranking_prepared_impl(
-                s,
-                &pq,
-                options.keep_diacritics,
-                &mut candidate_buf,
-                finder.as_ref(),
-            );
-            // Zero-copy: borrow the string directly from the input item.
-            (rank, Cow::Borrowed(s), 0_usize, None)
-        } else {
-            // Keys mode: evaluate all keys and pick the best ranking.
-            let info = get_highest_ranking_prepared_impl(
-                item,
-                &options.keys,
-                &pq,
-                &options,
-                &mut candidate_buf,
-                finder.as_ref(),
+        if ranker.should_stop() {
+            break;
+        }
+        if index % CANCEL_CHECK_INTERVAL == 0
+            && cancel.is_some_and(|token| token.load(AtomicOrdering::Relaxed))
+        {
+            return (ranked_items, true);
+        }
+        if let Some(ranked) = ranker.rank(index, item) {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(
+                index,
+                ranked_value = %ranked.ranked_value,
+                rank = ?ranked.rank,
+                key_index = ranked.key_index,
+                "item matched"
             );
-            (
-                info.rank,
-                Cow::Owned(info.ranked_value),
-                info.key_index,
-                info.key_threshold,
-            )
-        };
+            ranked_items.push(ranked);
+        }
+    }
+    if options.shrink_to_fit {
+        ranked_items.shrink_to_fit();
+    }
 
-        // Use per-key threshold when set, otherwise fall back to global threshold.
-        let effective_threshold = key_threshold.as_ref().unwrap_or(&options.threshold);
-        if rank >= *effective_threshold {
-            ranked_items.push(RankedItem {
-                item,
-                index,
-                rank,
-                ranked_value,
-                key_index,
-                key_threshold,
-            });
+    #[cfg(feature = "tracing")]
+    let (matched, sort_start) = (ranked_items.len(), std::time::Instant::now());
+    let sorted = sort_for_query(ranked_items, value, options);
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        processed = items.len(),
+        matched,
+        sort_duration = ?sort_start.elapsed(),
+        "match_sorter finished"
+    );
+    (sorted, false)
+}
+
+/// The [`EmptyQueryBehavior`] that answers `value`, or `None` when `value`
+/// is not the empty query.
+fn empty_query<T>(value: &str, options: &MatchSorterOptions<T>) -> Option<EmptyQueryBehavior> {
+    value.is_empty().then_some(options.empty_query_behavior)
+}
+
+/// The items the empty query returns without ranking them: none for
+/// [`EmptyQueryBehavior::ReturnEmpty`], and every item passing the
+/// [`item_filter`](MatchSorterOptions::item_filter), in input order, for
+/// [`EmptyQueryBehavior::ReturnAllUnsorted`]. `None` when `value` must be
+/// searched.
+fn unranked_items<'a, T>(
+    items: &'a [T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> Option<Vec<&'a T>> {
+    unranked_indices(items, value, options)
+        .map(|indices| indices.into_iter().map(|index| &items[index]).collect())
+}
+
+/// Like [`unranked_items`], but returns the positions of the items in
+/// `items`.
+fn unranked_indices<'a, T: 'a>(
+    items: impl IntoIterator<Item = &'a T>,
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> Option<Vec<usize>> {
+    match empty_query(value, options)? {
+        EmptyQueryBehavior::ReturnAll => None,
+        EmptyQueryBehavior::ReturnEmpty => Some(Vec::new()),
+        EmptyQueryBehavior::ReturnAllUnsorted => Some(
+            items
+                .into_iter()
+                .enumerate()
+                .filter(|(_, item)| {
+                    options
+                        .item_filter
+                        .as_ref()
+                        .is_none_or(|filter| filter(item))
+                })
+                .map(|(index, _)| index)
+                .collect(),
+        ),
+    }
+}
+
+/// A search for one query, reused across batches of items.
+///
+/// [`match_sorter`] prepares the query (normalizing and lowercasing it and
+/// building its substring finder) on every call. A session prepares it
+/// once, so ranking page after page of items for the same query, as with
+/// paginated data or a stream of batches, skips that work. Each
+/// [`rank_page`](Self::rank_page) returns exactly what [`match_sorter`]
+/// returns for the same items, query and options.
+///
+/// For interactive search, [`update_query`](Self::update_query) switches
+/// to a new query, preparing it only if it differs from the current one.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, MatchSorterSession};
+///
+/// let mut session = MatchSorterSession::new("ap", MatchSorterOptions::default());
+/// assert_eq!(session.rank_page(&["grape", "apple"]), vec![&"apple", &"grape"]);
+/// assert_eq!(session.rank_page(&["banana", "apricot"]), vec![&"apricot"]);
+///
+/// session.update_query("ban");
+/// assert_eq!(session.rank_page(&["banana", "apricot"]), vec![&"banana"]);
+/// ```
+pub struct MatchSorterSession<T> {
+    query: String,
+    tokens: QueryTokens,
+    options: MatchSorterOptions<T>,
+}
+
+impl<T> MatchSorterSession<T>
+where
+    T: AsMatchStrTrait,
+{
+    /// Create a session searching for `query` with `options`, preparing
+    /// the query once.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The search query string
+    /// * `options` - Configuration options (threshold, keys, sorting, etc.)
+    pub fn new(query: &str, options: MatchSorterOptions<T>) -> Self {
+        Self {
+            query: query.to_owned(),
+            tokens: QueryTokens::new(query, &options),
+            options,
         }
     }
 
-    // Step 2: Sort the filtered items.
-    if let Some(ref sorter) = options.sorter {
-        ranked_items = sorter(ranked_items);
-    } else {
-        ranked_items.sort_by(|a, b| {
-            if let Some(ref base_sort) = options.base_sort {
-                sort_ranked_values_impl(a, b, base_sort.as_ref())
-            } else {
-                sort_ranked_values_impl(a, b, &default_base_sort_impl)
-            }
-        });
+    /// Filter and sort `items` by how well they match the session's query.
+    ///
+    /// Returns the same result as [`match_sorter`] for the same items,
+    /// query and options.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The batch of items to search through
+    pub fn rank_page<'a>(&self, items: &'a [T]) -> Vec<&'a T> {
+        if let Some(unranked) = unranked_items(items, &self.query, &self.options) {
+            return unranked;
+        }
+        search_ranked(items, &self.query, &self.options, Some(&self.tokens), None)
+            .0
+            .into_iter()
+            .map(|ri| ri.item)
+            .collect()
+    }
+
+    /// Search for `new_query` from now on.
+    ///
+    /// The query is prepared again only if it differs from the current one.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_query` - The search query string
+    pub fn update_query(&mut self, new_query: &str) {
+        if new_query != self.query {
+            self.tokens = QueryTokens::new(new_query, &self.options);
+            new_query.clone_into(&mut self.query);
+        }
     }
 
-    // Step 3: Extract references to the original items.
-    ranked_items.iter().map(|ri| ri.item).collect()
+    /// The query the session searches for.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// The options the session searches with.
+    pub fn options(&self) -> &MatchSorterOptions<T> {
+        &self.options
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Like [`match_sorter`], but with keys whose values are produced by a
+/// future.
+///
+/// Every [`AsyncKey`](key::AsyncKey) in `options.keys` is awaited for every
+/// item, all concurrently, before ranking starts; the slowest extraction
+/// therefore bounds the wait rather than the sum of all of them. The
+/// awaited values are then ranked exactly as a synchronous key returning
+/// them would be, followed by any synchronous keys in `options.options`.
+///
+/// The [`cancel_token`](MatchSorterOptions::cancel_token) is checked each
+/// time an extraction finishes. Once it is `true`, the remaining
+/// extractions are dropped and an empty list is returned, since no item has
+/// been ranked yet. Ranking itself, like [`match_sorter`], runs to
+/// completion.
+///
+/// The returned future is not `Send`, since the options hold non-`Send`
+/// closures; await it directly instead of spawning it onto another thread.
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `options` - The async keys and all other configuration options
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{AsyncMatchSorterOptions, AsMatchStr, match_sorter_async};
+/// use matchsorter::key::AsyncKey;
+///
+/// struct User { id: u32 }
+///
+/// impl AsMatchStr for User {
+///     fn as_match_str(&self) -> &str { "" }
+/// }
+///
+/// let users = [User { id: 1 }, User { id: 2 }];
+/// let name: Box<dyn AsyncKey<User>> = Box::new(|user: &User| {
+///     let id = user.id;
+///     // In practice this would await a database or HTTP lookup.
+///     async move { vec![if id == 1 { "Alice" } else { "Bob" }.to_owned()] }
+/// });
+/// let opts = AsyncMatchSorterOptions {
+///     keys: vec![name],
+///     ..Default::default()
+/// };
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// let results = runtime.block_on(match_sorter_async(&users, "bo", opts));
+/// assert_eq!(results.len(), 1);
+/// assert_eq!(results[0].id, 2);
+/// ```
+#[cfg(feature = "async")]
+pub async fn match_sorter_async<'a, T>(
+    items: &'a [T],
+    value: &str,
+    options: AsyncMatchSorterOptions<T>,
+) -> Vec<&'a T>
+where
+    T: AsMatchStrTrait + 'static,
+{
+    use futures::stream::{FuturesUnordered, StreamExt};
+    use std::cell::Cell;
+    use std::rc::Rc;
 
-    // --- Basic no-keys mode tests ---
+    let AsyncMatchSorterOptions {
+        keys: async_keys,
+        mut options,
+    } = options;
+    if async_keys.is_empty() {
+        return match_sorter(items, value, &options);
+    }
+    if let Some(unranked) = unranked_items(items, value, &options) {
+        return unranked;
+    }
+    let cancelled = |options: &MatchSorterOptions<T>| {
+        options
+            .cancel_token
+            .as_ref()
+            .is_some_and(|token| token.load(AtomicOrdering::Relaxed))
+    };
+    if cancelled(&options) {
+        return Vec::new();
+    }
 
-    #[test]
-    fn no_keys_basic_str_slice() {
-        let items = ["apple", "banana", "grape"];
-        let results = match_sorter(&items, "ap", MatchSorterOptions::default());
-        // "apple" matches via StartsWith (best), "grape" matches via fuzzy
-        assert_eq!(results[0], &"apple");
-        assert!(!results.is_empty());
+    // values[item][key] holds the awaited values of one key for one item.
+    let mut values = vec![vec![Vec::new(); async_keys.len()]; items.len()];
+    let mut pending: FuturesUnordered<_> = items
+        .iter()
+        .enumerate()
+        .flat_map(|(index, item)| {
+            async_keys
+                .iter()
+                .enumerate()
+                .map(move |(k, key)| async move { (index, k, key.extract_async(item).await) })
+        })
+        .collect();
+    while let Some((index, k, extracted)) = pending.next().await {
+        if cancelled(&options) {
+            return Vec::new();
+        }
+        values[index][k] = extracted;
+    }
+    drop(pending);
+
+    // Serve the awaited values through ordinary keys, pointed at the item
+    // being ranked, so that ranking reuses the synchronous pipeline.
+    let values = Rc::new(values);
+    let current = Rc::new(Cell::new(0_usize));
+    let sync_keys = std::mem::take(&mut options.keys);
+    options.keys = (0..async_keys.len())
+        .map(|k| {
+            let values = Rc::clone(&values);
+            let current = Rc::clone(&current);
+            Key::new(move |_: &T| values[current.get()][k].clone())
+        })
+        .chain(sync_keys)
+        .collect();
+
+    let mut ranker = Ranker::new(value, &options);
+    let mut ranked_items = ranker.output_vec(items.len());
+    for (index, item) in items.iter().enumerate() {
+        if ranker.should_stop() {
+            break;
+        }
+        current.set(index);
+        if let Some(ranked) = ranker.rank(index, item) {
+            ranked_items.push(ranked);
+        }
     }
+    if options.shrink_to_fit {
+        ranked_items.shrink_to_fit();
+    }
+    sort_for_query(ranked_items, value, &options)
+        .iter()
+        .map(|ri| ri.item)
+        .collect()
+}
+
+/// Sort an owned `Vec<T>` in place by match quality, dropping non-matches.
+///
+/// Produces the same order as [`match_sorter`] but rearranges `items`
+/// itself instead of returning references, so callers that own their data
+/// avoid a second vector of `&T`. Items below the threshold are removed.
+///
+/// Only a `Vec<usize>` of the surviving indices is allocated besides the
+/// ranking pass; the permutation is applied with `Vec::swap` by following
+/// each index through the positions already filled, which needs no
+/// scratch copy of `T`.
+///
+/// # Arguments
+///
+/// * `items` - The items to reorder and filter in place
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, sorting, etc.)
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{match_sorter_inplace, MatchSorterOptions};
+///
+/// let mut items = vec!["pineapple", "banana", "grape", "apple"];
+/// match_sorter_inplace(&mut items, "ap", &MatchSorterOptions::default());
+/// assert_eq!(items, vec!["apple", "grape", "pineapple"]);
+/// ```
+pub fn match_sorter_inplace<T>(items: &mut Vec<T>, value: &str, options: &MatchSorterOptions<T>)
+where
+    T: AsMatchStrTrait,
+{
+    let order = match_sorter_order(items, value, options);
+    apply_order(items, &order);
+}
+
+/// Filter and sort items by match quality, returning their positions.
+///
+/// Produces the same order as [`match_sorter`], but yields each matching
+/// item's index into `items` instead of a reference. Useful when the
+/// caller keeps items in an external array or arena and needs stable
+/// positions to map results back.
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, sorting, etc.)
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{match_sorter_order, MatchSorterOptions};
+///
+/// let items = ["pineapple", "banana", "grape", "apple"];
+/// let order = match_sorter_order(&items, "ap", &MatchSorterOptions::default());
+/// assert_eq!(order, vec![3, 2, 0]);
+/// ```
+pub fn match_sorter_order<T>(
+    items: &[T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> Vec<usize>
+where
+    T: AsMatchStrTrait,
+{
+    if let Some(unranked) = unranked_indices(items, value, options) {
+        return unranked;
+    }
+    rank_and_sort(items, value, options)
+        .iter()
+        .map(|ri| ri.index)
+        .collect()
+}
+
+/// Filter and sort items, describing what matched in each one.
+///
+/// Returns the same matches in the same order as [`match_sorter`], each
+/// as a [`MatchAnnotation`] holding the item, its rank, the alias of the
+/// key that matched (see [`Key::alias`]), the matched value, and the byte
+/// ranges of that value to highlight:
+///
+/// - `CaseSensitiveEqual` and `Equal`: the whole value.
+/// - `StartsWith`, `WordStartsWith` and `Contains`: the occurrence of the
+///   query.
+/// - `Acronym`: each acronym letter that spells the query.
+/// - `Matches`: each matched character, with adjacent characters merged.
+///
+/// The empty query is answered as by [`match_sorter_ranked`].
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, sorting, etc.)
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, Ranking, match_sorter_with_annotations};
+///
+/// let items = ["grape", "apple", "banana"];
+/// let annotated = match_sorter_with_annotations(&items, "ap", &MatchSorterOptions::default());
+/// assert_eq!(annotated.len(), 2);
+/// assert_eq!(*annotated[0].item, "apple");
+/// assert_eq!(annotated[0].rank, Ranking::StartsWith);
+/// assert_eq!(annotated[0].highlight_ranges, vec![0..2]);
+/// assert_eq!(annotated[1].matched_value, "grape");
+/// assert_eq!(annotated[1].highlight_ranges, vec![2..4]);
+/// ```
+pub fn match_sorter_with_annotations<'a, T>(
+    items: &'a [T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> Vec<MatchAnnotation<'a, T>>
+where
+    T: AsMatchStrTrait,
+{
+    if empty_query(value, options) == Some(EmptyQueryBehavior::ReturnEmpty) {
+        return Vec::new();
+    }
+    let mut ranker = Ranker::new(value, options);
+    let ranked = rank_unsorted(&mut ranker, items, options);
+    sort_for_query(ranked, value, options)
+        .into_iter()
+        .map(|ri| {
+            let highlight_ranges = ranker.highlight_ranges(&ri.ranked_value);
+            MatchAnnotation {
+                item: ri.item,
+                rank: ri.rank,
+                matched_field: ri.matched_key_alias,
+                matched_value: ri.ranked_value.into_owned(),
+                highlight_ranges,
+            }
+        })
+        .collect()
+}
+
+/// Filter and sort items, pairing each with a normalized score.
+///
+/// Returns the same items in the same order as [`match_sorter`], each as a
+/// [`ScoredItem`] whose score is its rank's [`Ranking::to_f64`] divided by
+/// 7. Unless [`MatchSorterOptions::sorter`] replaces the sort, scores are
+/// therefore in descending order, with equal scores ordered by key index
+/// and then [`MatchSorterOptions::base_sort`]. The empty query is answered
+/// as by [`match_sorter_ranked`].
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, sorting, etc.)
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, match_sorter_by_score};
+///
+/// let items = ["apple", "ap", "grape"];
+/// let scored = match_sorter_by_score(&items, "ap", &MatchSorterOptions::default());
+/// assert_eq!(*scored[0].item, "ap");
+/// assert_eq!(scored[0].score, 1.0);
+/// let strong: Vec<&&str> = scored
+///     .into_iter()
+///     .filter(|s| s.above(0.5))
+///     .map(|s| s.into_item())
+///     .collect();
+/// assert_eq!(strong, vec![&"ap", &"apple"]);
+/// ```
+pub fn match_sorter_by_score<'a, T>(
+    items: &'a [T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> Vec<ScoredItem<'a, T>>
+where
+    T: AsMatchStrTrait,
+{
+    match_sorter_ranked(items, value, options)
+        .into_iter()
+        .map(|ri| ScoredItem {
+            item: ri.item,
+            score: ri.rank.to_f64() / 7.0,
+        })
+        .collect()
+}
+
+/// Filter and sort items, also returning the ranking that produced the
+/// order.
+///
+/// The returned [`MatchSorterSnapshot`] holds the same matches as
+/// [`match_sorter`] in `sorted`, the [`RankedItem`] of each match in input
+/// order (before sorting) in `ranked`, and the number of matches per tier
+/// in `stats`. Use it to see which tier and key each item was ranked with
+/// when the order is unexpected. The empty query is answered as by
+/// [`match_sorter_ranked`].
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, sorting, etc.)
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, Ranking, match_sorter_with_snapshot};
+///
+/// let items = ["grape", "apple", "ap"];
+/// let snapshot = match_sorter_with_snapshot(&items, "ap", &MatchSorterOptions::default());
+/// assert_eq!(snapshot.sorted, vec![&"ap", &"apple", &"grape"]);
+/// // Input order, with the tier each item was ranked in.
+/// let ranks: Vec<Ranking> = snapshot.ranked.iter().map(|ri| ri.rank).collect();
+/// assert_eq!(
+///     ranks,
+///     vec![Ranking::Contains, Ranking::StartsWith, Ranking::CaseSensitiveEqual]
+/// );
+/// assert_eq!(snapshot.stats.contains, 1);
+/// ```
+pub fn match_sorter_with_snapshot<'a, T>(
+    items: &'a [T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> MatchSorterSnapshot<'a, T>
+where
+    T: AsMatchStrTrait,
+{
+    let ranked = if empty_query(value, options) == Some(EmptyQueryBehavior::ReturnEmpty) {
+        Vec::new()
+    } else {
+        rank_unsorted(&mut Ranker::new(value, options), items, options)
+    };
+    let stats = ranked.iter().map(|ri| ri.rank).collect();
+    let sorted = sort_for_query(ranked.clone(), value, options)
+        .into_iter()
+        .map(|ri| ri.item)
+        .collect();
+    MatchSorterSnapshot {
+        sorted,
+        ranked,
+        stats,
+    }
+}
+
+/// Filter and sort items by match quality, returning an identifier for each.
+///
+/// Like [`match_sorter_order`], but maps every matching item through
+/// `key_fn` instead of returning its position. `key_fn` is called once per
+/// matching item, in sorted order.
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, sorting, etc.)
+/// * `key_fn` - Extracts the identifier of an item (e.g. a database ID)
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{match_sorter_by, AsMatchStr, MatchSorterOptions};
+///
+/// struct Product { id: usize, name: &'static str }
+///
+/// impl AsMatchStr for Product {
+///     fn as_match_str(&self) -> &str {
+///         self.name
+///     }
+/// }
+///
+/// let items = [
+///     Product { id: 10, name: "pineapple" },
+///     Product { id: 20, name: "apple" },
+/// ];
+/// let ids = match_sorter_by(&items, "ap", &MatchSorterOptions::default(), |p| p.id);
+/// assert_eq!(ids, vec![20, 10]);
+/// ```
+pub fn match_sorter_by<T, F>(
+    items: &[T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+    key_fn: F,
+) -> Vec<usize>
+where
+    T: AsMatchStrTrait,
+    F: Fn(&T) -> usize,
+{
+    match_sorter_order(items, value, options)
+        .into_iter()
+        .map(|index| key_fn(&items[index]))
+        .collect()
+}
+
+/// Merge two result sets into one, re-ranking every item against the
+/// query.
+///
+/// For results of separate searches, such as one over local items and one
+/// over remote items. Every item of `a` and `b` is ranked against `value`
+/// and the union is sorted exactly as [`match_sorter`] sorts, dropping
+/// items below the threshold. Duplicates are identified by
+/// [`dedup_key`](MatchSorterOptions::dedup_key), or by address when it is
+/// `None`; only the first occurrence, in `a` then `b`, is kept.
+/// [`RankedItem::index`] is the item's position in `a` followed by `b`, so
+/// a tiebreaker based on it keeps `a`'s items ahead of `b`'s.
+///
+/// # Arguments
+///
+/// * `a` - The first result set
+/// * `b` - The second result set
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, sorting, etc.)
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, match_sorter, match_sorter_merge};
+///
+/// let local = ["apple", "grape"];
+/// let remote = ["apricot", "pineapple"];
+/// let opts = MatchSorterOptions::default();
+/// let merged = match_sorter_merge(
+///     match_sorter(&local, "ap", &opts),
+///     match_sorter(&remote, "ap", &opts),
+///     "ap",
+///     &opts,
+/// );
+/// assert_eq!(merged, vec![&"apple", &"apricot", &"grape", &"pineapple"]);
+/// ```
+pub fn match_sorter_merge<'a, T>(
+    a: Vec<&'a T>,
+    b: Vec<&'a T>,
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> Vec<&'a T>
+where
+    T: AsMatchStrTrait,
+{
+    let union = dedup_items(a.into_iter().chain(b), options);
+    if let Some(unranked) = unranked_indices(union.iter().copied(), value, options) {
+        return unranked.into_iter().map(|index| union[index]).collect();
+    }
+    let mut ranker = Ranker::new(value, options);
+    let mut ranked_items = ranker.output_vec(union.len());
+    for (index, item) in union.into_iter().enumerate() {
+        if ranker.should_stop() {
+            break;
+        }
+        if let Some(ranked) = ranker.rank(index, item) {
+            ranked_items.push(ranked);
+        }
+    }
+    sort_for_query(ranked_items, value, options)
+        .into_iter()
+        .map(|ri| ri.item)
+        .collect()
+}
+
+/// Merge two sorted lists of ranked items without re-ranking them.
+///
+/// Both lists must be in the order of the default comparator (rank, then
+/// key index, then [`base_sort`](MatchSorterOptions::base_sort)), as
+/// produced by searches with the same `options`. They are merged in one
+/// pass with that comparator, taking from `a` on ties, and duplicates are
+/// dropped as in [`match_sorter_merge`]. A custom
+/// [`sorter`](MatchSorterOptions::sorter) is not consulted, since merging
+/// needs a comparison of two items.
+///
+/// # Arguments
+///
+/// * `a` - The first sorted list
+/// * `b` - The second sorted list
+/// * `options` - The options both lists were searched with
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{
+///     MatchSorterOptions, default_base_sort, match_sorter_merge_presorted,
+///     match_sorter_with_snapshot, sort_ranked_values,
+/// };
+///
+/// let local = ["grape", "apple"];
+/// let remote = ["apricot"];
+/// let ranked = |items| {
+///     let mut ranked = match_sorter_with_snapshot(items, "ap", &MatchSorterOptions::default()).ranked;
+///     ranked.sort_by(|a, b| sort_ranked_values(a, b, &default_base_sort));
+///     ranked
+/// };
+/// let merged = match_sorter_merge_presorted(
+///     ranked(&local[..]),
+///     ranked(&remote[..]),
+///     &MatchSorterOptions::default(),
+/// );
+/// assert_eq!(merged, vec![&"apple", &"apricot", &"grape"]);
+/// ```
+pub fn match_sorter_merge_presorted<'a, T>(
+    a: Vec<RankedItem<'a, T>>,
+    b: Vec<RankedItem<'a, T>>,
+    options: &MatchSorterOptions<T>,
+) -> Vec<&'a T> {
+    let compare = |x: &RankedItem<'a, T>, y: &RankedItem<'a, T>| match &options.base_sort {
+        Some(base_sort) => sort_ranked_values_impl(x, y, base_sort.as_ref()),
+        None => sort_ranked_values_impl(x, y, &default_base_sort_impl),
+    };
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    let mut a = a.into_iter().peekable();
+    let mut b = b.into_iter().peekable();
+    loop {
+        let next = match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) if compare(y, x).is_lt() => b.next(),
+            (Some(_), _) => a.next(),
+            (None, _) => b.next(),
+        };
+        match next {
+            Some(ri) => merged.push(ri),
+            None => break,
+        }
+    }
+    dedup_items(merged.into_iter().map(|ri| ri.item), options)
+}
+
+/// Keep the first of each group of duplicate `items`, as identified by
+/// [`MatchSorterOptions::dedup_key`] or else by address.
+fn dedup_items<'a, T>(
+    items: impl Iterator<Item = &'a T>,
+    options: &MatchSorterOptions<T>,
+) -> Vec<&'a T> {
+    match &options.dedup_key {
+        Some(dedup_key) => {
+            let mut seen = HashSet::new();
+            items.filter(|item| seen.insert(dedup_key(item))).collect()
+        }
+        None => {
+            let mut seen = HashSet::new();
+            items
+                .filter(|item| seen.insert(std::ptr::from_ref(*item)))
+                .collect()
+        }
+    }
+}
+
+/// Rearrange `items` so that `items[i]` becomes the item originally at
+/// `order[i]`, then truncate to `order.len()`.
+///
+/// `order` must hold distinct indices into `items`; it may be shorter than
+/// `items`, in which case the unlisted items are dropped.
+fn apply_order<T>(items: &mut Vec<T>, order: &[usize]) {
+    // Swaps at earlier positions may already have moved the item wanted at
+    // position `i`: whenever an original index `j < i` is hit, the item
+    // that lived there was swapped out to `order[j]`, so follow the chain
+    // until it points at a position that has not been filled yet.
+    for i in 0..order.len() {
+        let mut src = order[i];
+        while src < i {
+            src = order[src];
+        }
+        items.swap(i, src);
+    }
+    items.truncate(order.len());
+}
+
+/// Return one page of the sorted match results.
+///
+/// Ranks and sorts all items exactly like [`match_sorter`], then slices out
+/// the zero-based `page` of `page_size` items. The returned [`PageResult`]
+/// carries the total match count and whether another page follows. A page
+/// past the end, or a `page_size` of zero, yields an empty page.
+///
+/// Each call re-ranks every item; use a [`PaginationCache`] when fetching
+/// several pages of the same query.
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `page` - Zero-based page index
+/// * `page_size` - Maximum number of items per page
+/// * `options` - Configuration options (threshold, keys, sorting, etc.)
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{match_sorter_paginate, MatchSorterOptions};
+///
+/// let items = ["apple", "apricot", "grape", "pineapple"];
+/// let page = match_sorter_paginate(&items, "ap", 1, 3, &MatchSorterOptions::default());
+/// assert_eq!(page.items, vec![&"pineapple"]);
+/// assert_eq!(page.total, 4);
+/// assert!(!page.has_next);
+/// ```
+pub fn match_sorter_paginate<'a, T>(
+    items: &'a [T],
+    value: &str,
+    page: usize,
+    page_size: usize,
+    options: &MatchSorterOptions<T>,
+) -> PageResult<'a, T>
+where
+    T: AsMatchStrTrait,
+{
+    PageResult::from_sorted(&match_sorter(items, value, options), page, page_size)
+}
+
+/// Filter and sort items, re-ranking only the previous matches when the
+/// query refines the previous one.
+///
+/// Returns the same result as [`match_sorter`]. `ctx` remembers the
+/// previous query and its matches; when the new query extends it (as when
+/// the user keeps typing) and the options allow it, only those matches are
+/// re-ranked. See [`IncrementalContext`] for the exact conditions; in every
+/// other case all items are ranked.
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, sorting, etc.)
+/// * `ctx` - State carried from the previous call
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{IncrementalContext, MatchSorterOptions, match_sorter_incremental};
+///
+/// let items = ["apple", "apricot", "grape", "banana"];
+/// let opts = MatchSorterOptions::default();
+/// let mut ctx = IncrementalContext::new();
+/// for query in ["a", "ap", "apr"] {
+///     let results = match_sorter_incremental(&items, query, &opts, &mut ctx);
+///     assert!(!results.is_empty());
+/// }
+/// ```
+pub fn match_sorter_incremental<'a, T>(
+    items: &'a [T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+    ctx: &mut IncrementalContext<'a, T>,
+) -> Vec<&'a T>
+where
+    T: AsMatchStrTrait,
+{
+    if let Some(unranked) = unranked_items(items, value, options) {
+        ctx.reset();
+        return unranked;
+    }
+    ctx.search(items, value, options)
+        .iter()
+        .map(|ri| ri.item)
+        .collect()
+}
+
+/// Returns whether at least one item matches the query.
+///
+/// Equivalent to `!match_sorter(items, value, options).is_empty()`, but
+/// stops at the first item that meets its effective threshold and never
+/// collects or sorts results. `sorter` and `base_sort` are not called.
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, etc.)
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{match_sorter_any, MatchSorterOptions};
+///
+/// let items = ["apple", "banana", "grape"];
+/// assert!(match_sorter_any(&items, "ban", &MatchSorterOptions::default()));
+/// assert!(!match_sorter_any(&items, "xyz", &MatchSorterOptions::default()));
+/// ```
+pub fn match_sorter_any<T>(items: &[T], value: &str, options: &MatchSorterOptions<T>) -> bool
+where
+    T: AsMatchStrTrait,
+{
+    if let Some(unranked) = unranked_indices(items, value, options) {
+        return !unranked.is_empty();
+    }
+    let mut ranker = Ranker::new(value, options);
+    items
+        .iter()
+        .enumerate()
+        .any(|(index, item)| ranker.rank(index, item).is_some())
+}
+
+/// Returns whether every item selected by `predicate` matches the query.
+///
+/// Items for which `predicate` returns `false` are skipped without being
+/// ranked. Stops at the first selected item that falls below its effective
+/// threshold. Pass `|_| true` to require that all items match; like
+/// [`Iterator::all`], an empty selection yields `true`.
+///
+/// # Arguments
+///
+/// * `items` - Slice of items to search through
+/// * `value` - The search query string
+/// * `options` - Configuration options (threshold, keys, etc.)
+/// * `predicate` - Selects the items that must match
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{match_sorter_all, MatchSorterOptions};
+///
+/// let items = ["apple", "pineapple", "banana"];
+/// // Every item containing "pine" or starting with "a" matches "apple"...
+/// let selected = |s: &&str| s.starts_with('a') || s.contains("pine");
+/// assert!(match_sorter_all(&items, "apple", &MatchSorterOptions::default(), selected));
+/// // ...but not every item does.
+/// assert!(!match_sorter_all(&items, "apple", &MatchSorterOptions::default(), |_| true));
+/// ```
+pub fn match_sorter_all<T, P>(
+    items: &[T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+    predicate: P,
+) -> bool
+where
+    T: AsMatchStrTrait,
+    P: Fn(&T) -> bool,
+{
+    if let Some(unranked) = unranked_indices(items, value, options) {
+        let mut included = vec![false; items.len()];
+        for index in unranked {
+            included[index] = true;
+        }
+        return items
+            .iter()
+            .zip(included)
+            .all(|(item, included)| included || !predicate(item));
+    }
+    let mut ranker = Ranker::new(value, options);
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| predicate(item))
+        .all(|(index, item)| ranker.rank(index, item).is_some())
+}
+
+/// Rank every item, drop those below the effective threshold, and sort the
+/// rest, answering the empty query like [`match_sorter_ranked`]. Shared by
+/// the public entry points, which differ only in how they hand the sorted
+/// [`RankedItem`]s back to the caller.
+pub(crate) fn rank_and_sort<'a, T>(
+    items: &'a [T],
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> Vec<RankedItem<'a, T>>
+where
+    T: AsMatchStrTrait,
+{
+    search_ranked(items, value, options, None, None).0
+}
+
+/// Rank each item and keep those passing the effective threshold, in input
+/// order.
+fn rank_unsorted<'a, T>(
+    ranker: &mut Ranker<'_, T>,
+    items: &'a [T],
+    options: &MatchSorterOptions<T>,
+) -> Vec<RankedItem<'a, T>>
+where
+    T: AsMatchStrTrait,
+{
+    let mut ranked_items = ranker.output_vec(items.len());
+    for (index, item) in items.iter().enumerate() {
+        if ranker.should_stop() {
+            break;
+        }
+        if let Some(ranked) = ranker.rank(index, item) {
+            ranked_items.push(ranked);
+        }
+    }
+    if options.shrink_to_fit {
+        ranked_items.shrink_to_fit();
+    }
+    ranked_items
+}
+
+/// Sort ranked items with [`sort_ranked`], except for the empty query under
+/// [`EmptyQueryBehavior::ReturnAllUnsorted`], which keeps them in input
+/// order.
+fn sort_for_query<'a, T>(
+    ranked_items: Vec<RankedItem<'a, T>>,
+    value: &str,
+    options: &MatchSorterOptions<T>,
+) -> Vec<RankedItem<'a, T>> {
+    if empty_query(value, options) == Some(EmptyQueryBehavior::ReturnAllUnsorted) {
+        ranked_items
+    } else {
+        sort_ranked(ranked_items, options)
+    }
+}
+
+/// Sort ranked items with the custom `sorter` when set, otherwise with the
+/// default three-level comparator (using `base_sort` as the tiebreaker).
+///
+/// The default path uses the stable [`slice::sort_by`], so items that
+/// compare equal keep the order they were ranked in, which is input order;
+/// see `SORT_STABILITY.md`.
+pub(crate) fn sort_ranked<'a, T>(
+    mut ranked_items: Vec<RankedItem<'a, T>>,
+    options: &MatchSorterOptions<T>,
+) -> Vec<RankedItem<'a, T>> {
+    if let Some(ref sorter) = options.sorter {
+        ranked_items = sorter(ranked_items);
+    } else {
+        ranked_items.sort_by(|a, b| {
+            if let Some(ref base_sort) = options.base_sort {
+                sort_ranked_values_impl(a, b, base_sort.as_ref())
+            } else {
+                sort_ranked_values_impl(a, b, &default_base_sort_impl)
+            }
+        });
+    }
+
+    ranked_items
+}
+
+/// Per-call ranking state: the prepared query tokens, the ranking config,
+/// the resolved global threshold, and a reusable candidate buffer. Built
+/// once per search and then fed items one at a time.
+pub(crate) struct Ranker<'o, T> {
+    options: &'o MatchSorterOptions<T>,
+    /// One entry per query token; exactly one in [`QueryMode::Single`].
+    queries: Vec<Arc<QueryToken>>,
+    /// One entry per non-empty [`MatchSorterOptions::exclude_terms`] entry.
+    excludes: Vec<Arc<QueryToken>>,
+    config: ranking::RankingConfig<'o>,
+    candidate_buf: String,
+    /// The values the keys extracted from the item being ranked, kept for
+    /// the exclude terms.
+    extracted: Vec<String>,
+    global_threshold: Ranking,
+    /// Matches so far that count towards
+    /// [`MatchSorterOptions::early_stop_criteria`].
+    early_stop_hits: usize,
+}
+
+/// A prepared query token and its SIMD substring finder.
+///
+/// With the `tls_cache` feature (on by default), each thread keeps its most
+/// recently prepared tokens, so repeated searches for the same query skip
+/// preparing it again.
+struct QueryToken {
+    pq: PreparedQuery,
+    /// `None` when the token is empty, since `memmem` panics on empty needles.
+    finder: Option<memchr::memmem::Finder<'static>>,
+}
+
+/// The prepared tokens of a query and of the exclude terms.
+#[derive(Clone)]
+struct QueryTokens {
+    /// One entry per query token; exactly one in [`QueryMode::Single`].
+    queries: Vec<Arc<QueryToken>>,
+    /// One entry per non-empty [`MatchSorterOptions::exclude_terms`] entry.
+    excludes: Vec<Arc<QueryToken>>,
+}
+
+impl QueryTokens {
+    /// Stem `value` and split it into tokens as the options ask, and
+    /// prepare each token and exclude term.
+    fn new<T>(value: &str, options: &MatchSorterOptions<T>) -> Self {
+        let stemmed_value;
+        let value = match &options.query_stemmer {
+            Some(stem_fn) => {
+                stemmed_value = key::stem_words(value, stem_fn.as_ref());
+                stemmed_value.as_str()
+            }
+            None => value,
+        };
+        let queries = match options.query_mode {
+            QueryMode::Single => vec![QueryToken::new(value, options)],
+            QueryMode::AnyToken => {
+                let mut tokens: Vec<Arc<QueryToken>> = value
+                    .split_whitespace()
+                    .map(|token| QueryToken::new(token, options))
+                    .collect();
+                if tokens.is_empty() {
+                    tokens.push(QueryToken::new("", options));
+                }
+                tokens
+            }
+        };
+        let excludes = options
+            .exclude_terms
+            .iter()
+            .filter(|term| !term.trim().is_empty())
+            .map(|term| QueryToken::new(term, options))
+            .collect();
+        Self { queries, excludes }
+    }
+}
+
+/// How many recently prepared tokens each thread keeps.
+#[cfg(feature = "tls_cache")]
+const QUERY_CACHE_CAPACITY: usize = 8;
+
+/// The settings a token was prepared with: its text, diacritics mode,
+/// normalization form and case folding.
+#[cfg(feature = "tls_cache")]
+type QueryCacheKey = (
+    String,
+    ranking::DiacriticsMode,
+    ranking::NormalizationForm,
+    UnicodeCaseFolding,
+);
+
+#[cfg(feature = "tls_cache")]
+thread_local! {
+    /// This thread's recently prepared tokens, least recently used first.
+    static QUERY_CACHE: RefCell<Vec<(QueryCacheKey, Arc<QueryToken>)>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+#[cfg(test)]
+thread_local! {
+    /// How many tokens [`QueryToken::prepare`] has built on this thread.
+    static PREPARE_CALLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+impl QueryToken {
+    /// The token for `value`, reused from this thread's recent calls when
+    /// it was prepared with the same settings.
+    #[cfg(feature = "tls_cache")]
+    fn new<T>(value: &str, options: &MatchSorterOptions<T>) -> Arc<Self> {
+        let diacritics = options.diacritics();
+        let normalization = options.normalization_form;
+        let folding = options.unicode_case_folding;
+        QUERY_CACHE.with_borrow_mut(|cache| {
+            let hit = cache.iter().position(|((query, d, n, f), _)| {
+                query == value && *d == diacritics && *n == normalization && *f == folding
+            });
+            let entry = match hit {
+                Some(pos) => cache.remove(pos),
+                None => {
+                    if cache.len() == QUERY_CACHE_CAPACITY {
+                        cache.remove(0);
+                    }
+                    let token = Self::prepare(value, diacritics, normalization, folding);
+                    (
+                        (value.to_owned(), diacritics, normalization, folding),
+                        Arc::new(token),
+                    )
+                }
+            };
+            let token = Arc::clone(&entry.1);
+            cache.push(entry);
+            token
+        })
+    }
+
+    /// The token for `value`, prepared afresh.
+    #[cfg(not(feature = "tls_cache"))]
+    fn new<T>(value: &str, options: &MatchSorterOptions<T>) -> Arc<Self> {
+        Arc::new(Self::prepare(
+            value,
+            options.diacritics(),
+            options.normalization_form,
+            options.unicode_case_folding,
+        ))
+    }
+
+    fn prepare(
+        value: &str,
+        diacritics: ranking::DiacriticsMode,
+        normalization: ranking::NormalizationForm,
+        folding: UnicodeCaseFolding,
+    ) -> Self {
+        #[cfg(test)]
+        PREPARE_CALLS.with(|calls| calls.set(calls.get() + 1));
+        let pq = PreparedQuery::with_case_folding(value, diacritics, normalization, folding);
+        let finder = if pq.lower.is_empty() {
+            None
+        } else {
+            Some(memchr::memmem::Finder::new(pq.lower.as_bytes()).into_owned())
+        };
+        Self { pq, finder }
+    }
+}
+
+impl<'o, T> Ranker<'o, T>
+where
+    T: AsMatchStrTrait,
+{
+    /// Pre-compute query data once to avoid redundant work per item.
+    pub(crate) fn new(value: &str, options: &'o MatchSorterOptions<T>) -> Self {
+        Self::with_tokens(value, options, QueryTokens::new(value, options))
+    }
+
+    /// Like [`new`](Self::new), with `value`'s tokens already prepared.
+    fn with_tokens(value: &str, options: &'o MatchSorterOptions<T>, tokens: QueryTokens) -> Self {
+        let global_threshold = options.global_threshold(value);
+        let QueryTokens { queries, excludes } = tokens;
+        Self {
+            options,
+            queries,
+            excludes,
+            config: ranking::RankingConfig {
+                match_floor: Some(options.soft_floor(global_threshold)),
+                ..options.ranking_config()
+            },
+            // Reusable buffer for lowercasing each candidate (avoids per-item
+            // allocation). Pre-allocate with `value.len().max(32)` so the first
+            // `lowercase_into` call does not trigger a grow-from-zero
+            // reallocation; 32 bytes covers most short candidates while
+            // `value.len()` scales for longer queries.
+            candidate_buf: String::with_capacity(value.len().max(32)),
+            extracted: Vec::new(),
+            global_threshold,
+            early_stop_hits: 0,
+        }
+    }
+
+    /// Whether [`MatchSorterOptions::early_stop_criteria`] is met, so that
+    /// no further items should be ranked.
+    pub(crate) fn should_stop(&self) -> bool {
+        self.options
+            .early_stop_criteria
+            .is_some_and(|criteria| self.early_stop_hits >= criteria.min_count)
+    }
+
+    /// The prepared (lowercased, normalized) query, or `None` when the
+    /// query was split into several tokens.
+    pub(crate) fn single_query_lower(&self) -> Option<&str> {
+        match self.queries.as_slice() {
+            [query] => Some(&query.pq.lower),
+            _ => None,
+        }
+    }
+
+    /// The lowest rank an item needs to be included when no per-key
+    /// threshold applies: the soft threshold if it is below the global
+    /// one, otherwise the global threshold.
+    pub(crate) fn global_floor(&self) -> Ranking {
+        self.options.soft_floor(self.global_threshold)
+    }
+
+    /// Initial capacity of the match list for `item_count` items:
+    /// [`MatchSorterOptions::expected_match_count`] if set, otherwise a
+    /// guess from the global floor. Never more than `item_count`.
+    pub(crate) fn output_capacity(&self, item_count: usize) -> usize {
+        let capacity = match (self.options.expected_match_count, self.global_floor()) {
+            (Some(expected), _) => expected,
+            // Only exact matches, of which there are usually few.
+            (None, Ranking::CaseSensitiveEqual | Ranking::Equal) => 1,
+            (None, Ranking::Matches(_) | Ranking::NoMatch) => item_count,
+            (None, _) => item_count / 10,
+        };
+        capacity.min(item_count)
+    }
+
+    /// Create the match list for `item_count` items; see
+    /// [`output_capacity`](Self::output_capacity).
+    pub(crate) fn output_vec<'a>(&self, item_count: usize) -> Vec<RankedItem<'a, T>> {
+        Vec::with_capacity(self.output_capacity(item_count))
+    }
+
+    /// Rank one item, returning `None` if it falls below its effective
+    /// threshold, the [`item_filter`](MatchSorterOptions::item_filter)
+    /// rejects it, or it took longer to rank than
+    /// [`discard_items_slower_than`](MatchSorterOptions::discard_items_slower_than)
+    /// allows.
+    pub(crate) fn rank<'a>(&mut self, index: usize, item: &'a T) -> Option<RankedItem<'a, T>> {
+        let ranked = match self.options.discard_items_slower_than {
+            Some(limit) => {
+                let start = Instant::now();
+                let ranked = self.rank_item(index, item);
+                if start.elapsed() > limit {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(index, ?limit, "item ranked too slowly; discarded");
+                    if let Some(on_slow_item) = &self.options.on_slow_item {
+                        on_slow_item(item);
+                    }
+                    return None;
+                }
+                ranked
+            }
+            None => self.rank_item(index, item),
+        }?;
+        if self
+            .options
+            .early_stop_criteria
+            .is_some_and(|criteria| ranked.rank >= criteria.min_tier)
+        {
+            self.early_stop_hits += 1;
+        }
+        Some(ranked)
+    }
+
+    /// [`rank`](Self::rank) without timing the item.
+    fn rank_item<'a>(&mut self, index: usize, item: &'a T) -> Option<RankedItem<'a, T>> {
+        if let Some(filter) = &self.options.item_filter
+            && !filter(item)
+        {
+            return None;
+        }
+        let (
+            rank,
+            ranked_value,
+            key_index,
+            key_threshold,
+            matched_key_alias,
+            tie_priority,
+            best_query,
+        ) = if self.options.keys.is_empty() {
+            // No-keys mode: rank the item directly via AsMatchStr.
+            let s = item.as_match_str();
+            let mut rank = Ranking::NoMatch;
+            let mut best_query = 0;
+            for (i, query) in self.queries.iter().enumerate() {
+                let token_rank = get_match_ranking_prepared_impl(
+                    s,
+                    &query.pq,
+                    &self.config,
+                    &mut self.candidate_buf,
+                    query.finder.as_ref(),
+                );
+                if token_rank > rank {
+                    rank = token_rank;
+                    best_query = i;
+                }
+            }
+            if rank != Ranking::NoMatch
+                && let Some(modify) = &self.options.item_score_modifier
+            {
+                rank = modify(item, rank);
+            }
+            // Zero-copy: borrow the string directly from the input item.
+            (
+                rank,
+                Cow::Borrowed(s),
+                0_usize,
+                None,
+                None,
+                None,
+                best_query,
+            )
+        } else {
+            // Keys mode: evaluate all keys per token and keep the best
+            // ranking; on ties the earlier token wins. The first token's
+            // pass records the extracted values for the exclude terms.
+            let mut best: Option<(RankingInfo, usize)> = None;
+            self.extracted.clear();
+            for (i, query) in self.queries.iter().enumerate() {
+                let record = i == 0 && !self.excludes.is_empty();
+                let info = get_highest_ranking_prepared_impl(
+                    item,
+                    &self.options.keys,
+                    &query.pq,
+                    &self.config,
+                    self.options.item_score_modifier.as_deref(),
+                    &mut KeyScratch {
+                        candidate_buf: &mut self.candidate_buf,
+                        extracted: record.then_some(&mut self.extracted),
+                    },
+                    query.finder.as_ref(),
+                );
+                if best.as_ref().is_none_or(|(b, _)| info.rank > b.rank) {
+                    best = Some((info, i));
+                }
+            }
+            let (info, best_query) = best.expect("a ranker always holds at least one query token");
+            (
+                info.rank,
+                Cow::Owned(info.ranked_value),
+                info.key_index,
+                info.key_threshold,
+                info.matched_key_alias,
+                info.tie_priority,
+                best_query,
+            )
+        };
+
+        // Use per-key threshold when set, otherwise fall back to global threshold.
+        // A lower soft threshold admits weaker items, flagged as soft matches.
+        let hard_threshold = key_threshold.unwrap_or(self.global_threshold);
+        let floor = self.options.soft_floor(hard_threshold);
+        if rank < floor || self.is_excluded(item) {
+            return None;
+        }
+        // In no-keys mode `rank` is the value's own tier unless a score
+        // modifier changed it; in keys mode it may have been adjusted by the
+        // winning key, so re-rank the value.
+        let known_rank = Some(rank).filter(|_| self.options.item_score_modifier.is_none());
+        let matched_portion = match ranked_value {
+            Cow::Borrowed(value) => self.matched_portion(best_query, value, known_rank),
+            Cow::Owned(ref value) => self
+                .matched_portion(best_query, value, None)
+                .map(|p| Cow::Owned(p.into_owned())),
+        };
+        Some(RankedItem {
+            item,
+            index,
+            rank,
+            ranked_value,
+            key_index,
+            key_threshold,
+            is_soft_match: rank < hard_threshold,
+            matched_portion,
+            matched_key_alias,
+            tie_priority,
+        })
+    }
+
+    /// The part of `value` matched by the query token at `query`, ranking
+    /// `value` first unless its `rank` is already known.
+    fn matched_portion<'v>(
+        &mut self,
+        query: usize,
+        value: &'v str,
+        rank: Option<Ranking>,
+    ) -> Option<Cow<'v, str>> {
+        let query = &self.queries[query];
+        let rank = rank.unwrap_or_else(|| {
+            get_match_ranking_prepared_impl(
+                value,
+                &query.pq,
+                &self.config,
+                &mut self.candidate_buf,
+                query.finder.as_ref(),
+            )
+        });
+        ranking::get_matched_portion(
+            value,
+            rank,
+            &query.pq,
+            &self.config,
+            &mut self.candidate_buf,
+            query.finder.as_ref(),
+        )
+    }
+
+    /// Byte ranges of `value` matched by the query: those of the token
+    /// that ranks `value` highest, the earlier token winning ties.
+    fn highlight_ranges(&mut self, value: &str) -> Vec<Range<usize>> {
+        let mut best: Option<(Ranking, &QueryToken)> = None;
+        for query in &self.queries {
+            let rank = get_match_ranking_prepared_impl(
+                value,
+                &query.pq,
+                &self.config,
+                &mut self.candidate_buf,
+                query.finder.as_ref(),
+            );
+            if best.is_none_or(|(best_rank, _)| rank > best_rank) {
+                best = Some((rank, query));
+            }
+        }
+        let Some((rank, query)) = best else {
+            return Vec::new();
+        };
+        ranking::get_matched_ranges(
+            value,
+            rank,
+            &query.pq,
+            &self.config,
+            &mut self.candidate_buf,
+            query.finder.as_ref(),
+        )
+    }
+
+    /// Whether any exclude term matches one of `item`'s values at or above
+    /// the exclude threshold. In keys mode, the values are those
+    /// [`rank_item`](Self::rank_item) just extracted.
+    fn is_excluded(&mut self, item: &T) -> bool {
+        if self.excludes.is_empty() {
+            return false;
+        }
+        let threshold = self.options.exclude_threshold;
+        let excludes = &self.excludes;
+        let config = &self.config;
+        let matches = |value: &str, buf: &mut String| {
+            excludes.iter().any(|term| {
+                get_match_ranking_prepared_impl(value, &term.pq, config, buf, term.finder.as_ref())
+                    >= threshold
+            })
+        };
+        if self.options.keys.is_empty() {
+            matches(item.as_match_str(), &mut self.candidate_buf)
+        } else {
+            self.extracted
+                .iter()
+                .any(|value| matches(value, &mut self.candidate_buf))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- Basic no-keys mode tests ---
+
+    #[test]
+    fn no_keys_basic_str_slice() {
+        let items = ["apple", "banana", "grape"];
+        let results = match_sorter(&items, "ap", &MatchSorterOptions::default());
+        // "apple" matches via StartsWith (best), "grape" matches via fuzzy
+        assert_eq!(results[0], &"apple");
+        assert!(!results.is_empty());
+    }
+
+    #[test]
+    fn no_keys_exact_match_first() {
+        let items = ["banana", "apple", "pineapple"];
+        let results = match_sorter(&items, "apple", &MatchSorterOptions::default());
+        // "apple" is CaseSensitiveEqual (best rank), should be first
+        assert_eq!(results[0], &"apple");
+    }
+
+    #[test]
+    fn no_keys_empty_query_returns_all_sorted() {
+        let items = ["banana", "apple", "cherry"];
+        let results = match_sorter(&items, "", &MatchSorterOptions::default());
+        // Empty query matches everything via StartsWith; sorted alphabetically
+        // by base_sort since all have same rank and key_index
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], &"apple");
+        assert_eq!(results[1], &"banana");
+        assert_eq!(results[2], &"cherry");
+    }
+
+    #[test]
+    fn no_keys_no_match_returns_empty() {
+        let items = ["apple", "banana", "grape"];
+        let results = match_sorter(&items, "xyz", &MatchSorterOptions::default());
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn no_keys_string_items() {
+        let items = vec!["hello".to_owned(), "help".to_owned(), "world".to_owned()];
+        let results = match_sorter(&items, "hel", &MatchSorterOptions::default());
+        // Both "hello" and "help" start with "hel"
+        assert_eq!(results.len(), 2);
+        // Alphabetical tiebreaker: "hello" < "help"
+        assert_eq!(results[0].as_str(), "hello");
+        assert_eq!(results[1].as_str(), "help");
+    }
+
+    #[test]
+    fn no_keys_empty_items() {
+        let items: [&str; 0] = [];
+        let results = match_sorter(&items, "test", &MatchSorterOptions::default());
+        assert!(results.is_empty());
+    }
+
+    // --- Threshold filtering tests ---
+
+    #[test]
+    fn threshold_filters_below() {
+        let items = ["apple", "banana", "grape"];
+        let opts = MatchSorterOptions {
+            threshold: Ranking::Contains,
+            ..Default::default()
+        };
+        let results = match_sorter(&items, "ap", &opts);
+        // "apple" has "ap" at position 0 -> StartsWith (>= Contains)
+        // "grape" contains "ap" at position 2 -> Contains (>= Contains)
+        // "banana" has no "ap" substring or fuzzy above Contains
+        assert_eq!(results, vec![&"apple", &"grape"]);
+    }
+
+    #[test]
+    fn threshold_case_sensitive_equal_excludes_case_insensitive() {
+        let items = ["Apple", "apple", "APPLE"];
+        let opts = MatchSorterOptions {
+            threshold: Ranking::CaseSensitiveEqual,
+            ..Default::default()
+        };
+        let results = match_sorter(&items, "apple", &opts);
+        // Only exact case-sensitive match passes
+        assert_eq!(results, vec![&"apple"]);
+    }
+
+    #[test]
+    fn key_threshold_overrides_global() {
+        // Use keys mode: key with per-key threshold that is stricter
+        let items = vec!["apple".to_owned(), "apricot".to_owned()];
+        let opts = MatchSorterOptions {
+            keys: vec![
+                Key::new(|s: &String| vec![s.clone()]).threshold(Ranking::CaseSensitiveEqual),
+            ],
+            threshold: Ranking::Matches(1.0), // global is permissive
+            ..Default::default()
+        };
+        let results = match_sorter(&items, "apple", &opts);
+        // Per-key threshold is CaseSensitiveEqual, so only exact match passes
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].as_str(), "apple");
+    }
+
+    #[test]
+    fn key_thresholds_map_overrides_key_threshold() {
+        let items = vec!["apple".to_owned(), "pineapple".to_owned()];
+        let key = || Key::new(|s: &String| vec![s.clone()]).threshold(Ranking::CaseSensitiveEqual);
+
+        // Looser than the key's own threshold.
+        let opts = MatchSorterOptions {
+            keys: vec![key()],
+            ..Default::default()
+        }
+        .key_threshold(0, Ranking::Contains);
+        assert_eq!(match_sorter(&items, "apple", &opts).len(), 2);
+
+        // Stricter than the global threshold for a key without its own.
+        let opts = MatchSorterOptions {
+            keys: vec![Key::new(|s: &String| vec![s.clone()])],
+            threshold: Ranking::Matches(1.0),
+            ..Default::default()
+        }
+        .key_threshold(0, Ranking::Equal);
+        assert_eq!(match_sorter(&items, "apple", &opts), vec![&items[0]]);
+    }
+
+    #[test]
+    fn key_thresholds_priority_map_then_key_then_global() {
+        let items = vec![
+            "apple".to_owned(),
+            "pineapple".to_owned(),
+            "grape".to_owned(),
+        ];
+        let run = |map: Option<Ranking>, key: Option<Ranking>| {
+            let mut k = Key::new(|s: &String| vec![s.clone()]);
+            if let Some(t) = key {
+                k = k.threshold(t);
+            }
+            let mut opts = MatchSorterOptions {
+                keys: vec![k],
+                threshold: Ranking::StartsWith,
+                ..Default::default()
+            };
+            if let Some(t) = map {
+                opts = opts.key_threshold(0, t);
+            }
+            match_sorter(&items, "ap", &opts).len()
+        };
+        // apple: StartsWith, pineapple and grape: Contains.
+        assert_eq!(run(None, None), 1);
+        assert_eq!(run(None, Some(Ranking::Contains)), 3);
+        assert_eq!(run(Some(Ranking::StartsWith), Some(Ranking::Contains)), 1);
+        assert_eq!(run(Some(Ranking::Contains), Some(Ranking::Equal)), 3);
+    }
+
+    #[test]
+    fn key_thresholds_out_of_range_index_is_ignored() {
+        let items = vec!["apple".to_owned(), "pineapple".to_owned()];
+        let opts = MatchSorterOptions {
+            keys: vec![Key::new(|s: &String| vec![s.clone()])],
+            ..Default::default()
+        }
+        .key_threshold(5, Ranking::CaseSensitiveEqual);
+        assert_eq!(match_sorter(&items, "apple", &opts).len(), 2);
+
+        // No keys at all: the map has nothing to apply to.
+        let opts = MatchSorterOptions::default().key_threshold(0, Ranking::CaseSensitiveEqual);
+        assert_eq!(match_sorter(&items, "apple", &opts).len(), 2);
+    }
+
+    #[test]
+    fn soft_threshold_flags_weak_matches() {
+        let items = ["apple", "grape", "a-p", "banana"];
+        let opts = MatchSorterOptions {
+            threshold: Ranking::Contains,
+            soft_threshold: Some(Ranking::Matches(1.0)),
+            ..Default::default()
+        };
+        let ranked = rank_and_sort(&items, "ap", &opts);
+        let flags: Vec<(&str, bool)> = ranked
+            .iter()
+            .map(|ri| (*ri.item, ri.is_soft_match))
+            .collect();
+        // "banana" has no "a...p" subsequence and stays excluded.
+        assert_eq!(
+            flags,
+            vec![("apple", false), ("grape", false), ("a-p", true)]
+        );
+    }
+
+    #[test]
+    fn soft_threshold_excludes_below_soft() {
+        let items = ["apple", "grape", "a-p"];
+        let opts = MatchSorterOptions {
+            threshold: Ranking::StartsWith,
+            soft_threshold: Some(Ranking::Contains),
+            ..Default::default()
+        };
+        let ranked = rank_and_sort(&items, "ap", &opts);
+        let flags: Vec<(&str, bool)> = ranked
+            .iter()
+            .map(|ri| (*ri.item, ri.is_soft_match))
+            .collect();
+        assert_eq!(flags, vec![("apple", false), ("grape", true)]);
+    }
+
+    #[test]
+    fn threshold_alone_acts_as_hard_threshold() {
+        let items = ["apple", "grape", "a-p"];
+        let opts = MatchSorterOptions {
+            threshold: Ranking::Contains,
+            ..Default::default()
+        };
+        let ranked = rank_and_sort(&items, "ap", &opts);
+        assert_eq!(ranked.len(), 2);
+        assert!(ranked.iter().all(|ri| !ri.is_soft_match));
+    }
+
+    #[test]
+    fn soft_threshold_above_hard_is_ignored() {
+        let items = ["apple", "grape", "a-p"];
+        let opts = MatchSorterOptions {
+            threshold: Ranking::Contains,
+            soft_threshold: Some(Ranking::Equal),
+            ..Default::default()
+        };
+        let ranked = rank_and_sort(&items, "ap", &opts);
+        assert_eq!(ranked.len(), 2);
+        assert!(ranked.iter().all(|ri| !ri.is_soft_match));
+    }
+
+    #[test]
+    fn soft_threshold_uses_key_threshold_as_hard() {
+        let items = vec!["apple".to_owned(), "grape".to_owned()];
+        let opts = MatchSorterOptions {
+            keys: vec![Key::from_fn(|s: &String| s.as_str()).threshold(Ranking::StartsWith)],
+            soft_threshold: Some(Ranking::Contains),
+            ..Default::default()
+        };
+        let ranked = rank_and_sort(&items, "ap", &opts);
+        let flags: Vec<(&str, bool)> = ranked
+            .iter()
+            .map(|ri| (ri.item.as_str(), ri.is_soft_match))
+            .collect();
+        assert_eq!(flags, vec![("apple", false), ("grape", true)]);
+    }
+
+    // --- QueryMode::AnyToken tests ---
+
+    fn any_token() -> MatchSorterOptions<&'static str> {
+        MatchSorterOptions {
+            query_mode: QueryMode::AnyToken,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn any_token_matches_either_word() {
+        let items = ["apple pie", "banana split", "cherry tart"];
+        let results = match_sorter(&items, "apple banana", &any_token());
+        assert_eq!(results, vec![&"apple pie", &"banana split"]);
+    }
+
+    #[test]
+    fn any_token_orders_by_best_token_rank() {
+        let items = ["pineapple", "cherry", "apple pie", "banana"];
+        let ranked = rank_and_sort(&items, "apple banana", &any_token());
+        let got: Vec<(&str, Ranking)> = ranked.iter().map(|ri| (*ri.item, ri.rank)).collect();
+        assert_eq!(
+            got,
+            vec![
+                ("banana", Ranking::CaseSensitiveEqual),
+                ("apple pie", Ranking::StartsWith),
+                ("pineapple", Ranking::Contains),
+            ]
+        );
+    }
+
+    #[test]
+    fn any_token_keeps_winning_ranked_value() {
+        let items = vec!["red fruit|banana".to_owned()];
+        let opts = MatchSorterOptions {
+            keys: vec![Key::split_on('|', |s: &String| s.as_str())],
+            query_mode: QueryMode::AnyToken,
+            ..Default::default()
+        };
+        let ranked = rank_and_sort(&items, "fruit banana", &opts);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].rank, Ranking::CaseSensitiveEqual);
+        assert_eq!(ranked[0].ranked_value, "banana");
+        assert_eq!(ranked[0].key_index, 1);
+    }
+
+    #[test]
+    fn any_token_single_token_matches_single_mode() {
+        let items = ["apple", "grape", "pineapple", "banana"];
+        assert_eq!(
+            match_sorter(&items, " ap ", &any_token()),
+            match_sorter(&items, "ap", &MatchSorterOptions::default())
+        );
+    }
+
+    #[test]
+    fn any_token_whitespace_query_acts_as_empty_query() {
+        let items = ["banana", "apple", ""];
+        assert_eq!(
+            rank_and_sort(&items, " \t ", &any_token()),
+            rank_and_sort(&items, "", &MatchSorterOptions::default())
+        );
+    }
+
+    // --- exclude_terms tests ---
+
+    fn excluding(terms: &[&str]) -> MatchSorterOptions<&'static str> {
+        MatchSorterOptions {
+            exclude_terms: terms.iter().map(|t| (*t).to_owned()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn exclude_terms_remove_matching_items() {
+        let items = ["apple", "pineapple-banana", "cherry"];
+        assert_eq!(
+            match_sorter(&items, "apple", &MatchSorterOptions::default()),
+            vec![&"apple", &"pineapple-banana"]
+        );
+        assert_eq!(
+            match_sorter(&items, "apple", &excluding(&["banana"])),
+            vec![&"apple"]
+        );
+        assert_eq!(
+            match_sorter(&items, "", &excluding(&["BANANA", "cherry"])),
+            vec![&"apple"]
+        );
+    }
+
+    #[test]
+    fn empty_exclude_terms_are_a_no_op() {
+        let items = ["apple", "pineapple-banana", "cherry", ""];
+        let expected = rank_and_sort(&items, "ap", &MatchSorterOptions::default());
+        assert_eq!(rank_and_sort(&items, "ap", &excluding(&[])), expected);
+        assert_eq!(
+            rank_and_sort(&items, "ap", &excluding(&["", " \t"])),
+            expected
+        );
+    }
+
+    #[test]
+    fn exclude_threshold_controls_looseness() {
+        let items = ["banana bread", "apple bread"];
+        // "bnn" is only a fuzzy match for "banana bread".
+        assert_eq!(match_sorter(&items, "bread", &excluding(&["bnn"])).len(), 2);
+        let opts = MatchSorterOptions {
+            exclude_threshold: Ranking::MATCH_THRESHOLD,
+            ..excluding(&["bnn"])
+        };
+        assert_eq!(match_sorter(&items, "bread", &opts), vec![&"apple bread"]);
+    }
+
+    #[test]
+    fn exclude_terms_respect_keep_diacritics() {
+        let items = ["cr\u{00e8}me br\u{00fb}l\u{00e9}e", "creme caramel"];
+        assert_eq!(
+            match_sorter(&items, "cr", &excluding(&["brulee"])),
+            vec![&"creme caramel"]
+        );
+        let opts = MatchSorterOptions {
+            keep_diacritics: true,
+            ..excluding(&["brulee"])
+        };
+        assert_eq!(match_sorter(&items, "cr", &opts).len(), 2);
+    }
+
+    #[test]
+    fn exclude_terms_check_every_key_value() {
+        let items = vec![
+            "Granny Smith|green|sour".to_owned(),
+            "Gala|red|sweet".to_owned(),
+            "Golden Delicious|yellow|sweet".to_owned(),
+        ];
+        let opts = MatchSorterOptions {
+            keys: vec![
+                Key::split_on('|', |s: &String| s.as_str()).extract_first(),
+                Key::split_on('|', |s: &String| s.as_str()).extract_slice(1..3),
+            ],
+            exclude_terms: vec!["sour".to_owned()],
+            ..Default::default()
+        };
+        // The query matches the name key; the exclusion hits the tag key.
+        let results: Vec<&str> = match_sorter(&items, "g", &opts)
+            .into_iter()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            results,
+            vec!["Gala|red|sweet", "Golden Delicious|yellow|sweet"]
+        );
+    }
+
+    #[test]
+    fn exclude_terms_reuse_extracted_values() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let extracted = Rc::new(Cell::new(0));
+        let skip_checked = Rc::new(Cell::new(0));
+        let (extract_count, skip_count) = (Rc::clone(&extracted), Rc::clone(&skip_checked));
+        let items = vec!["green apple".to_owned(), "red apple".to_owned()];
+        let opts = MatchSorterOptions {
+            keys: vec![
+                Key::new(move |s: &String| {
+                    extract_count.set(extract_count.get() + 1);
+                    vec![s.clone()]
+                })
+                .skip_if(move |_: &String| {
+                    skip_count.set(skip_count.get() + 1);
+                    false
+                }),
+            ],
+            exclude_terms: vec!["green".to_owned()],
+            ..Default::default()
+        };
+        let results = match_sorter(&items, "apple", &opts);
+        assert_eq!(results, vec![&"red apple".to_owned()]);
+        assert_eq!(extracted.get(), 2);
+        assert_eq!(skip_checked.get(), 2);
+    }
+
+    // --- match_sorter_into_owned tests ---
 
     #[test]
-    fn no_keys_exact_match_first() {
-        let items = ["banana", "apple", "pineapple"];
-        let results = match_sorter(&items, "apple", MatchSorterOptions::default());
-        // "apple" is CaseSensitiveEqual (best rank), should be first
-        assert_eq!(results[0], &"apple");
+    fn into_owned_matches_reference_order() {
+        let items = vec![
+            "pineapple".to_owned(),
+            "banana".to_owned(),
+            "grape".to_owned(),
+            "apple".to_owned(),
+        ];
+        let refs = match_sorter(&items, "ap", &MatchSorterOptions::default());
+        let owned = match_sorter_into_owned(&items, "ap", &MatchSorterOptions::default());
+        assert!(owned.iter().eq(refs));
     }
 
     #[test]
-    fn no_keys_empty_query_returns_all_sorted() {
-        let items = ["banana", "apple", "cherry"];
-        let results = match_sorter(&items, "", MatchSorterOptions::default());
-        // Empty query matches everything via StartsWith; sorted alphabetically
-        // by base_sort since all have same rank and key_index
-        assert_eq!(results.len(), 3);
-        assert_eq!(results[0], &"apple");
-        assert_eq!(results[1], &"banana");
-        assert_eq!(results[2], &"cherry");
+    fn into_owned_is_independent_of_input() {
+        let mut items = vec!["apple".to_owned(), "apricot".to_owned()];
+        let owned = match_sorter_into_owned(&items, "ap", &MatchSorterOptions::default());
+        items[0].push_str(" pie");
+        items.clear();
+        assert_eq!(owned, vec!["apple".to_owned(), "apricot".to_owned()]);
     }
 
     #[test]
-    fn no_keys_no_match_returns_empty() {
-        let items = ["apple", "banana", "grape"];
-        let results = match_sorter(&items, "xyz", MatchSorterOptions::default());
-        assert!(results.is_empty());
+    fn into_owned_keys_mode() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct City {
+            name: &'static str,
+        }
+        impl AsMatchStr for City {
+            fn as_match_str(&self) -> &str {
+                ""
+            }
+        }
+
+        let items = [
+            City { name: "Oslo" },
+            City { name: "Osaka" },
+            City { name: "Lima" },
+        ];
+        let opts = MatchSorterOptions {
+            keys: vec![Key::from_fn(|c: &City| c.name)],
+            ..Default::default()
+        };
+        assert_eq!(
+            match_sorter_into_owned(&items, "os", &opts),
+            vec![City { name: "Osaka" }, City { name: "Oslo" }]
+        );
     }
 
-    #[test]
-    fn no_keys_string_items() {
-        let items = vec!["hello".to_owned(), "help".to_owned(), "world".to_owned()];
-        let results = match_sorter(&items, "hel", MatchSorterOptions::default());
-        // Both "hello" and "help" start with "hel"
-        assert_eq!(results.len(), 2);
-        // Alphabetical tiebreaker: "hello" < "help"
-        assert_eq!(results[0].as_str(), "hello");
-        assert_eq!(results[1].as_str(), "help");
+    // --- Cancellation tests ---
+
+    fn numbered(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("item {i}")).collect()
     }
 
     #[test]
-    fn no_keys_empty_items() {
-        let items: [&str; 0] = [];
-        let results = match_sorter(&items, "test", MatchSorterOptions::default());
-        assert!(results.is_empty());
-    }
+    fn cancel_mid_loop_returns_ranked_so_far() {
+        use std::sync::Arc;
+        use std::sync::atomic::AtomicBool;
 
-    // --- Threshold filtering tests ---
+        let items = numbered(3500);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let trigger = Arc::clone(&cancel);
+        // The extractor sets the token while ranking item 1500; the next
+        // check happens before item 2000.
+        let opts = MatchSorterOptions {
+            keys: vec![Key::new(move |s: &String| {
+                if s == "item 1500" {
+                    trigger.store(true, AtomicOrdering::Relaxed);
+                }
+                vec![s.clone()]
+            })],
+            cancel_token: Some(cancel),
+            ..Default::default()
+        };
+        let result = match_sorter_cancellable(&items, "item", &opts);
+        assert!(result.is_cancelled());
+        let ranked = result.into_vec();
+        assert_eq!(ranked.len(), 2000);
+        assert!(ranked.iter().copied().eq(items[..2000].iter()));
+    }
 
     #[test]
-    fn threshold_filters_below() {
-        let items = ["apple", "banana", "grape"];
+    fn cancel_before_start_returns_nothing() {
+        use std::sync::Arc;
+        use std::sync::atomic::AtomicBool;
+
+        let items = numbered(10);
         let opts = MatchSorterOptions {
-            threshold: Ranking::Contains,
+            cancel_token: Some(Arc::new(AtomicBool::new(true))),
             ..Default::default()
         };
-        let results = match_sorter(&items, "ap", opts);
-        // "apple" has "ap" at position 0 -> StartsWith (>= Contains)
-        // "grape" contains "ap" at position 2 -> Contains (>= Contains)
-        // "banana" has no "ap" substring or fuzzy above Contains
-        assert_eq!(results, vec![&"apple", &"grape"]);
+        assert_eq!(
+            match_sorter_cancellable(&items, "item", &opts),
+            MatchSorterResult::Cancelled(vec![])
+        );
     }
 
     #[test]
-    fn threshold_case_sensitive_equal_excludes_case_insensitive() {
-        let items = ["Apple", "apple", "APPLE"];
+    fn uncancelled_result_matches_match_sorter() {
+        use std::sync::Arc;
+        use std::sync::atomic::AtomicBool;
+
+        let items = numbered(2500);
+        let expected = match_sorter(&items, "item 12", &MatchSorterOptions::default());
+        let result = match_sorter_cancellable(&items, "item 12", &MatchSorterOptions::default());
+        assert_eq!(result, MatchSorterResult::Complete(expected.clone()));
+
         let opts = MatchSorterOptions {
-            threshold: Ranking::CaseSensitiveEqual,
+            cancel_token: Some(Arc::new(AtomicBool::new(false))),
             ..Default::default()
         };
-        let results = match_sorter(&items, "apple", opts);
-        // Only exact case-sensitive match passes
-        assert_eq!(results, vec![&"apple"]);
+        let result = match_sorter_cancellable(&items, "item 12", &opts);
+        assert!(!result.is_cancelled());
+        assert_eq!(result.into_vec(), expected);
     }
 
     #[test]
-    fn key_threshold_overrides_global() {
-        // Use keys mode: key with per-key threshold that is stricter
-        let items = vec!["apple".to_owned(), "apricot".to_owned()];
+    fn match_sorter_ignores_cancel_token() {
+        use std::sync::Arc;
+
+        let items = numbered(2500);
+        let expected = match_sorter(&items, "item 12", &MatchSorterOptions::default());
         let opts = MatchSorterOptions {
-            keys: vec![
-                Key::new(|s: &String| vec![s.clone()]).threshold(Ranking::CaseSensitiveEqual),
-            ],
-            threshold: Ranking::Matches(1.0), // global is permissive
+            cancel_token: Some(Arc::new(AtomicBool::new(true))),
             ..Default::default()
         };
-        let results = match_sorter(&items, "apple", opts);
-        // Per-key threshold is CaseSensitiveEqual, so only exact match passes
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].as_str(), "apple");
+        assert_eq!(match_sorter(&items, "item 12", &opts), expected);
+        let ranked: Vec<&String> = match_sorter_ranked(&items, "item 12", &opts)
+            .into_iter()
+            .map(|ri| ri.item)
+            .collect();
+        assert_eq!(ranked, expected);
+    }
+
+    // --- Ranking cache tests ---
+
+    #[test]
+    fn ranking_cache_shared_between_calls() {
+        use std::sync::{Arc, Mutex};
+
+        let items = ["apple", "pineapple", "grape", "banana", "Apple pie"];
+        let cache = Arc::new(Mutex::new(RankingCache::new(16)));
+        let opts = || MatchSorterOptions {
+            ranking_cache: Some(Arc::clone(&cache)),
+            ..Default::default()
+        };
+        for query in ["ap", "app", "ap"] {
+            let cached = match_sorter(&items, query, &opts());
+            let uncached = match_sorter(&items, query, &MatchSorterOptions::default());
+            assert_eq!(cached, uncached, "query {query:?}");
+        }
+        // Two distinct queries, one entry per item and query.
+        assert_eq!(cache.lock().unwrap().len(), 2 * items.len());
+    }
+
+    #[test]
+    fn ranking_cache_ignored_with_non_default_ranking_options() {
+        use std::sync::{Arc, Mutex};
+
+        let items = ["North-West Airlines", "New World Order"];
+        let cache = Arc::new(Mutex::new(RankingCache::new(16)));
+        let opts = |cache: Option<Arc<Mutex<RankingCache>>>| MatchSorterOptions {
+            acronym_exact_match: true,
+            ranking_cache: cache,
+            ..Default::default()
+        };
+        assert_eq!(
+            match_sorter(&items, "nwa", &opts(Some(Arc::clone(&cache)))),
+            match_sorter(&items, "nwa", &opts(None))
+        );
+        assert!(cache.lock().unwrap().is_empty());
     }
 
     // --- Custom sorter tests ---
@@ -422,9 +2623,9 @@ mod tests {
         let default_results = match_sorter(
             &["apple", "banana", "grape"],
             "a",
-            MatchSorterOptions::default(),
+            &MatchSorterOptions::default(),
         );
-        let custom_results = match_sorter(&items, "a", opts);
+        let custom_results = match_sorter(&items, "a", &opts);
 
         // Custom sorter reverses the order
         assert_eq!(custom_results.len(), default_results.len());
@@ -445,7 +2646,70 @@ mod tests {
             })),
             ..Default::default()
         };
-        let _ = match_sorter(&items, "ap", opts);
+        let _ = match_sorter(&items, "ap", &opts);
+    }
+
+    #[test]
+    fn sorter_sees_matched_portions() {
+        let items = ["Green", "evergreen", "Greenland", "grey-eyed nomad"];
+        let opts = MatchSorterOptions {
+            threshold: Ranking::Matches(1.0),
+            sorter: Some(Box::new(|items: Vec<RankedItem<&str>>| {
+                let portions: Vec<_> = items
+                    .iter()
+                    .map(|ri| ri.matched_portion.as_deref())
+                    .collect();
+                assert_eq!(
+                    portions,
+                    vec![
+                        Some("Green"),
+                        Some("green"),
+                        Some("Green"),
+                        Some("grey-eyed n")
+                    ]
+                );
+                items
+            })),
+            ..Default::default()
+        };
+        assert_eq!(match_sorter(&items, "green", &opts).len(), 4);
+    }
+
+    #[test]
+    fn matched_portion_ignores_key_rank_adjustment_and_follows_best_token() {
+        let item = "Green".to_owned();
+        let opts = MatchSorterOptions {
+            keys: vec![Key::from_fn(|s: &String| s.as_str()).max_ranking(Ranking::Contains)],
+            query_mode: QueryMode::AnyToken,
+            ..Default::default()
+        };
+        let mut ranker = Ranker::new("zzz reen", &opts);
+        let ranked = ranker.rank(0, &item).unwrap();
+        assert_eq!(ranked.rank, Ranking::Contains);
+        assert_eq!(ranked.matched_portion.as_deref(), Some("reen"));
+
+        let mut ranker = Ranker::new("green", &opts);
+        let ranked = ranker.rank(0, &item).unwrap();
+        assert_eq!(ranked.rank, Ranking::Contains);
+        assert_eq!(ranked.matched_portion.as_deref(), Some("Green"));
+    }
+
+    #[test]
+    fn match_sorter_propagates_key_alias() {
+        let items = vec!["ally@example.com".to_owned()];
+        let opts = |expected: &'static str| MatchSorterOptions {
+            keys: vec![
+                Key::from_fn(|s: &String| s.as_str()).alias("address"),
+                Key::from_fn(|s: &String| s.split('@').next().unwrap_or_default()).alias("user"),
+            ],
+            sorter: Some(Box::new(move |items: Vec<RankedItem<String>>| {
+                assert_eq!(items[0].key_alias(), Some(expected));
+                items
+            })),
+            ..Default::default()
+        };
+        assert_eq!(match_sorter(&items, "example", &opts("address")).len(), 1);
+        assert_eq!(match_sorter(&items, "ally", &opts("user")).len(), 1);
     }
 
     // --- Custom base_sort tests ---
@@ -460,7 +2724,7 @@ mod tests {
             ..Default::default()
         };
         // All items match empty-ish query via StartsWith with the same rank
-        let results = match_sorter(&items, "", opts);
+        let results = match_sorter(&items, "", &opts);
         // Reverse alphabetical: gamma, beta, alpha
         assert_eq!(results[0], &"gamma");
         assert_eq!(results[1], &"beta");
@@ -498,7 +2762,7 @@ mod tests {
             keys: vec![Key::new(|i: &Item| vec![i.name.clone()])],
             ..Default::default()
         };
-        let results = match_sorter(&items, "ali", opts);
+        let results = match_sorter(&items, "ali", &opts);
         // "Alice" matches via StartsWith, "Charlie" matches via fuzzy
         // ('a','l','i' found in "charlie"). "Bob" does not match.
         assert!(!results.is_empty());
@@ -537,7 +2801,7 @@ mod tests {
         };
         // "alice" matches name as Equal and email as StartsWith;
         // Equal > StartsWith so name key wins
-        let results = match_sorter(&items, "alice", opts);
+        let results = match_sorter(&items, "alice", &opts);
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "Alice");
     }
@@ -549,7 +2813,7 @@ mod tests {
         // "apple": query "app" -> StartsWith
         // "pineapple": query "app" -> Contains (substring at pos 4)
         let items = ["pineapple", "apple"];
-        let results = match_sorter(&items, "app", MatchSorterOptions::default());
+        let results = match_sorter(&items, "app", &MatchSorterOptions::default());
         assert_eq!(results[0], &"apple"); // StartsWith > Contains
         assert_eq!(results[1], &"pineapple");
     }
@@ -557,7 +2821,7 @@ mod tests {
     #[test]
     fn diacritics_handling() {
         let items = ["cafe", "caf\u{00e9}"];
-        let results = match_sorter(&items, "cafe", MatchSorterOptions::default());
+        let results = match_sorter(&items, "cafe", &MatchSorterOptions::default());
         // Both should match (diacritics stripped by default)
         assert_eq!(results.len(), 2);
     }
@@ -569,8 +2833,401 @@ mod tests {
             keep_diacritics: true,
             ..Default::default()
         };
-        let results = match_sorter(&items, "cafe", opts);
+        let results = match_sorter(&items, "cafe", &opts);
         // Only "cafe" matches when diacritics are kept
         assert_eq!(results, vec![&"cafe"]);
     }
+
+    #[test]
+    fn diacritics_mode_option() {
+        let items = ["a\u{00f1}o", "ano", "an\u{0303}ejo"];
+        let spanish = || MatchSorterOptions {
+            diacritics_mode: DiacriticsMode::StripNonDistinctive(DiacriticsLanguage::Spanish),
+            threshold: Ranking::Contains,
+            ..Default::default()
+        };
+        let strip_all = MatchSorterOptions {
+            threshold: Ranking::Contains,
+            ..Default::default()
+        };
+        assert_eq!(
+            match_sorter(&items, "ano", &strip_all),
+            vec![&"ano", &"a\u{00f1}o"]
+        );
+        assert_eq!(match_sorter(&items, "ano", &spanish()), vec![&"ano"]);
+        assert_eq!(
+            match_sorter(&items, "a\u{00f1}", &spanish()),
+            vec![&"an\u{0303}ejo", &"a\u{00f1}o"]
+        );
+
+        // keep_diacritics takes precedence over the mode.
+        let opts = MatchSorterOptions {
+            keep_diacritics: true,
+            ..spanish()
+        };
+        assert_eq!(
+            match_sorter(&items, "a\u{00f1}", &opts),
+            vec![&"a\u{00f1}o"]
+        );
+    }
+
+    // --- match_sorter_any / match_sorter_all tests ---
+
+    #[test]
+    fn any_stops_at_first_match() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let calls = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let items = vec!["miss", "apple", "apricot", "grape"];
+        let opts = MatchSorterOptions {
+            keys: vec![Key::new(move |s: &&str| {
+                counter.set(counter.get() + 1);
+                vec![(*s).to_owned()]
+            })],
+            threshold: Ranking::StartsWith,
+            ..Default::default()
+        };
+        assert!(match_sorter_any(&items, "ap", &opts));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn any_false_when_nothing_matches() {
+        let items = ["apple", "banana"];
+        assert!(!match_sorter_any(
+            &items,
+            "xyz",
+            &MatchSorterOptions::default()
+        ));
+        let empty: [&str; 0] = [];
+        assert!(!match_sorter_any(
+            &empty,
+            "",
+            &MatchSorterOptions::default()
+        ));
+    }
+
+    #[test]
+    fn any_respects_per_key_threshold() {
+        let items = ["grape"];
+        // "ae" fuzzy-matches "grape", but the key demands StartsWith.
+        let opts = MatchSorterOptions {
+            keys: vec![Key::from_fn(|s: &&str| *s).threshold(Ranking::StartsWith)],
+            ..Default::default()
+        };
+        assert!(!match_sorter_any(&items, "ae", &opts));
+        // A permissive per-key threshold overrides a strict global one.
+        let opts = MatchSorterOptions {
+            keys: vec![Key::from_fn(|s: &&str| *s).threshold(Ranking::Matches(1.0))],
+            threshold: Ranking::Equal,
+            ..Default::default()
+        };
+        assert!(match_sorter_any(&items, "ae", &opts));
+    }
+
+    #[test]
+    fn any_agrees_with_match_sorter() {
+        let items = ["apple", "banana", "grape", "San Francisco", "caf\u{00e9}"];
+        let queries = ["", "a", "ap", "xyz", "sf", "cafe", "NANA", "zz"];
+        let thresholds = [
+            Ranking::Matches(1.0),
+            Ranking::Acronym,
+            Ranking::Contains,
+            Ranking::Equal,
+        ];
+        for query in queries {
+            for threshold in thresholds {
+                let opts = || MatchSorterOptions {
+                    threshold,
+                    ..Default::default()
+                };
+                assert_eq!(
+                    match_sorter_any(&items, query, &opts()),
+                    !match_sorter(&items, query, &opts()).is_empty(),
+                    "query={query:?} threshold={threshold:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn all_with_predicate() {
+        let items = ["apple", "apricot", "banana"];
+        let opts = || MatchSorterOptions {
+            threshold: Ranking::StartsWith,
+            ..Default::default()
+        };
+        assert!(match_sorter_all(&items, "ap", &opts(), |s| s.starts_with('a')));
+        assert!(!match_sorter_all(&items, "ap", &opts(), |_| true));
+        assert!(match_sorter_all(&items, "ap", &opts(), |_| false));
+    }
+
+    // --- match_sorter_order / match_sorter_by tests ---
+
+    #[test]
+    fn order_matches_match_sorter() {
+        let items = ["pineapple", "banana", "grape", "apple", "snap"];
+        let order = match_sorter_order(&items, "ap", &MatchSorterOptions::default());
+        let by_order: Vec<&&str> = order.iter().map(|&i| &items[i]).collect();
+        assert_eq!(
+            by_order,
+            match_sorter(&items, "ap", &MatchSorterOptions::default())
+        );
+    }
+
+    #[test]
+    fn order_positions_are_valid_and_distinct() {
+        let items = ["ab", "abc", "b", "xab", "zzz"];
+        let mut order = match_sorter_order(&items, "ab", &MatchSorterOptions::default());
+        assert!(order.iter().all(|&i| i < items.len()));
+        order.sort_unstable();
+        order.dedup();
+        assert_eq!(order.len(), 3);
+    }
+
+    #[test]
+    fn by_propagates_identifiers() {
+        let items = vec![
+            "pineapple".to_owned(),
+            "banana".to_owned(),
+            "apple".to_owned(),
+        ];
+        let ids = match_sorter_by(&items, "ap", &MatchSorterOptions::default(), |s| {
+            100 + s.len()
+        });
+        assert_eq!(ids, vec![105, 109]);
+    }
+
+    // --- match_sorter_inplace tests ---
+
+    /// Every ordered selection of `k` distinct indices out of `0..n`.
+    fn selections(n: usize, k: usize) -> Vec<Vec<usize>> {
+        if k == 0 {
+            return vec![Vec::new()];
+        }
+        let mut out = Vec::new();
+        for prefix in selections(n, k - 1) {
+            for next in (0..n).filter(|i| !prefix.contains(i)) {
+                let mut sel = prefix.clone();
+                sel.push(next);
+                out.push(sel);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn apply_order_exhaustive_small() {
+        for n in 0..=5 {
+            for k in 0..=n {
+                for order in selections(n, k) {
+                    let mut items: Vec<usize> = (0..n).collect();
+                    apply_order(&mut items, &order);
+                    assert_eq!(items, order, "n={n} order={order:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn inplace_matches_match_sorter_order() {
+        let items = vec![
+            "pineapple".to_owned(),
+            "Apple".to_owned(),
+            "banana".to_owned(),
+            "grape".to_owned(),
+            "apple".to_owned(),
+            "snap pea".to_owned(),
+        ];
+        let expected: Vec<String> = match_sorter(&items, "ap", &MatchSorterOptions::default())
+            .into_iter()
+            .cloned()
+            .collect();
+
+        let mut owned = items.clone();
+        match_sorter_inplace(&mut owned, "ap", &MatchSorterOptions::default());
+        assert_eq!(owned, expected);
+    }
+
+    #[test]
+    fn inplace_removes_filtered_items() {
+        let mut items = vec!["apple", "banana", "playground"];
+        let opts = MatchSorterOptions {
+            threshold: Ranking::Contains,
+            ..Default::default()
+        };
+        match_sorter_inplace(&mut items, "pl", &opts);
+        assert_eq!(items, vec!["playground", "apple"]);
+    }
+
+    #[test]
+    fn inplace_empty_items() {
+        let mut items: Vec<String> = Vec::new();
+        match_sorter_inplace(&mut items, "a", &MatchSorterOptions::default());
+        assert!(items.is_empty());
+    }
+
+    #[test]
+    fn inplace_no_matches_clears() {
+        let mut items = vec!["apple", "banana"];
+        match_sorter_inplace(&mut items, "xyz", &MatchSorterOptions::default());
+        assert!(items.is_empty());
+    }
+
+    // --- Output capacity tests ---
+
+    fn capacity_for(opts: &MatchSorterOptions<&str>, item_count: usize) -> usize {
+        Ranker::new("ap", opts).output_capacity(item_count)
+    }
+
+    #[test]
+    fn output_capacity_follows_threshold() {
+        let with_threshold = |threshold| MatchSorterOptions {
+            threshold,
+            ..Default::default()
+        };
+        assert_eq!(
+            capacity_for(&with_threshold(Ranking::CaseSensitiveEqual), 1000),
+            1
+        );
+        assert_eq!(capacity_for(&with_threshold(Ranking::Equal), 1000), 1);
+        assert_eq!(capacity_for(&with_threshold(Ranking::Contains), 1000), 100);
+        assert_eq!(
+            capacity_for(&with_threshold(Ranking::StartsWith), 1000),
+            100
+        );
+        assert_eq!(capacity_for(&MatchSorterOptions::default(), 1000), 1000);
+        assert_eq!(
+            capacity_for(&with_threshold(Ranking::CaseSensitiveEqual), 0),
+            0
+        );
+        // A soft threshold lowers the floor the guess is based on.
+        let soft = MatchSorterOptions {
+            soft_threshold: Some(Ranking::Acronym),
+            ..with_threshold(Ranking::Equal)
+        };
+        assert_eq!(capacity_for(&soft, 1000), 100);
+    }
+
+    #[test]
+    fn output_capacity_hint_overrides_guess_up_to_item_count() {
+        let opts = MatchSorterOptions::default().capacity_hint(7);
+        assert_eq!(capacity_for(&opts, 1000), 7);
+        assert_eq!(capacity_for(&opts, 3), 3);
+    }
+
+    #[test]
+    fn results_ignore_initial_capacity() {
+        let items = ["apple", "grape", "pineapple", "apricot", "banana", "snap"];
+        let expected = match_sorter(&items, "ap", &MatchSorterOptions::default());
+        for hint in [0, 1, 2, 100] {
+            let opts = MatchSorterOptions::default().capacity_hint(hint);
+            assert_eq!(match_sorter(&items, "ap", &opts), expected, "hint {hint}");
+        }
+        let exact = MatchSorterOptions {
+            threshold: Ranking::CaseSensitiveEqual,
+            ..Default::default()
+        };
+        assert_eq!(match_sorter(&items, "apple", &exact), vec![&"apple"]);
+    }
+
+    #[test]
+    fn shrink_to_fit_releases_unused_capacity() {
+        let items: Vec<String> = (0..50).map(|i| format!("item {i}")).collect();
+        let ranked = rank_and_sort(&items, "item 4", &MatchSorterOptions::default());
+        assert!(ranked.capacity() >= 50);
+        let opts = MatchSorterOptions {
+            shrink_to_fit: true,
+            ..Default::default()
+        };
+        let ranked = rank_and_sort(&items, "item 4", &opts);
+        assert!(ranked.len() < 50);
+        assert_eq!(ranked.capacity(), ranked.len());
+    }
+
+    /// Tokens prepared on this thread so far.
+    fn prepare_calls() -> usize {
+        PREPARE_CALLS.with(std::cell::Cell::get)
+    }
+
+    #[cfg(feature = "tls_cache")]
+    #[test]
+    fn query_cache_reuses_tokens_for_same_query() {
+        let opts = MatchSorterOptions::<&str>::default();
+        let first = Ranker::new("reuse me", &opts);
+        let before = prepare_calls();
+        let second = Ranker::new("reuse me", &opts);
+        assert_eq!(prepare_calls(), before);
+        assert!(Arc::ptr_eq(&first.queries[0], &second.queries[0]));
+
+        let items = ["reuse me", "other"];
+        match_sorter(&items, "reuse me", &MatchSorterOptions::default());
+        assert_eq!(prepare_calls(), before);
+    }
+
+    #[cfg(feature = "tls_cache")]
+    #[test]
+    fn query_cache_invalidates_on_change() {
+        let opts = MatchSorterOptions::<&str>::default();
+        let first = Ranker::new("cached query", &opts);
+        let before = prepare_calls();
+        Ranker::new("another query", &opts);
+        assert_eq!(prepare_calls(), before + 1);
+
+        let kept = MatchSorterOptions::<&str> {
+            keep_diacritics: true,
+            ..Default::default()
+        };
+        let third = Ranker::new("cached query", &kept);
+        assert_eq!(prepare_calls(), before + 2);
+        assert!(!Arc::ptr_eq(&first.queries[0], &third.queries[0]));
+
+        // Filling the cache evicts the least recently used entry.
+        for i in 0..QUERY_CACHE_CAPACITY {
+            Ranker::new(&format!("filler {i}"), &opts);
+        }
+        let before = prepare_calls();
+        Ranker::new("cached query", &opts);
+        assert_eq!(prepare_calls(), before + 1);
+    }
+
+    #[cfg(not(feature = "tls_cache"))]
+    #[test]
+    fn query_cache_disabled_prepares_every_call() {
+        let opts = MatchSorterOptions::<&str>::default();
+        let first = Ranker::new("uncached", &opts);
+        let before = prepare_calls();
+        let second = Ranker::new("uncached", &opts);
+        assert_eq!(prepare_calls(), before + 1);
+        assert!(!Arc::ptr_eq(&first.queries[0], &second.queries[0]));
+    }
+    #[test]
+    fn session_prepares_query_once() {
+        let session = MatchSorterSession::new("session query", MatchSorterOptions::default());
+        let before = prepare_calls();
+        session.rank_page(&["session query", "other"]);
+        session.rank_page(&["query session"]);
+        assert_eq!(prepare_calls(), before);
+    }
+
+    #[test]
+    fn session_update_query_prepares_only_changes() {
+        let mut session = MatchSorterSession::new("first session", MatchSorterOptions::default());
+        let first = Arc::clone(&session.tokens.queries[0]);
+        let before = prepare_calls();
+        session.update_query("first session");
+        assert_eq!(prepare_calls(), before);
+        assert!(Arc::ptr_eq(&first, &session.tokens.queries[0]));
+
+        session.update_query("second session");
+        assert_eq!(prepare_calls(), before + 1);
+        assert_eq!(session.query(), "second session");
+        assert_eq!(session.tokens.queries[0].pq.lower, "second session");
+        assert_eq!(
+            session.rank_page(&["first session", "second session"]),
+            vec![&"second session"]
+        );
+    }
 }
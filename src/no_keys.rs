@@ -5,7 +5,9 @@
 //! provides a uniform way to obtain a `&str` from any string-like type, and
 //! [`rank_item`] uses it to score items directly against a query.
 
-use std::borrow::Cow;
+use std::ffi::{CStr, OsStr, OsString};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
 
 use crate::ranking::{Ranking, get_match_ranking};
 
@@ -16,10 +18,29 @@ use crate::ranking::{Ranking, get_match_ranking};
 ///
 /// # Built-in Implementations
 ///
-/// - [`String`] -- delegates to [`String::as_str`]
 /// - [`str`] -- returns `self`
-/// - [`&str`] -- dereferences and returns `self`
-/// - [`Cow<'_, str>`] -- delegates to [`AsRef::as_ref`]
+/// - Any `T: Deref<Target = str>` -- dereferences to `str`. This covers
+///   [`String`], `&str`, [`Cow<'_, str>`](std::borrow::Cow), `Box<str>`,
+///   `Rc<str>`, `Arc<str>`, and third-party string types such as
+///   `CompactString` or `SmartString`, as well as your own newtypes that
+///   implement `Deref`.
+/// - [`Path`], [`OsStr`] and [`CStr`] -- their UTF-8 content (see below)
+/// - [`MatchablePath`] and [`MatchableOsString`] -- an owned path or OS
+///   string, matched by its lossy UTF-8 form
+///
+/// # Paths and OS strings
+///
+/// `as_match_str` must borrow from `self`, so a `Path`, `OsStr` or `CStr`
+/// that is not valid UTF-8 cannot be converted lossily: it matches as the
+/// empty string, which only the empty query matches. `PathBuf`, `OsString`
+/// and `CString` cannot implement the trait alongside the
+/// `Deref<Target = str>` blanket implementation; wrap owned paths in
+/// [`MatchablePath`] and owned OS strings in [`MatchableOsString`], which
+/// store a lossy copy when needed, or search them in keys mode, e.g.
+/// `Key::new(|p: &PathBuf| vec![p.to_string_lossy().into_owned()])`.
+///
+/// Because of the blanket implementation, implement `AsMatchStr` manually
+/// only for types that do **not** deref to `str`.
 ///
 /// # Examples
 ///
@@ -31,38 +52,192 @@ use crate::ranking::{Ranking, get_match_ranking};
 ///
 /// let borrowed: &str = "world";
 /// assert_eq!(borrowed.as_match_str(), "world");
+///
+/// let boxed: Box<str> = "boxed".into();
+/// assert_eq!(boxed.as_match_str(), "boxed");
 /// ```
 pub trait AsMatchStr {
     /// Returns the string representation of this item for matching.
     fn as_match_str(&self) -> &str;
 }
 
-impl AsMatchStr for String {
+impl AsMatchStr for str {
     fn as_match_str(&self) -> &str {
-        self.as_str()
+        self
     }
 }
 
-impl AsMatchStr for str {
+// The implicit `T: Sized` bound keeps this blanket impl from overlapping the
+// `str` impl above. It replaces the former per-type impls for `String`,
+// `&str` and `Cow<'_, str>`, all of which deref to `str`.
+impl<T> AsMatchStr for T
+where
+    T: Deref<Target = str>,
+{
     fn as_match_str(&self) -> &str {
         self
     }
 }
 
-// Blanket impl for `&&str`, `&&&str`, etc. is not needed; this covers
-// the `&str` case because Rust auto-derefs to `str` through `AsMatchStr`.
-// However, an explicit impl for `&str` is necessary so that `T = &str`
-// satisfies the `AsMatchStr` bound without requiring the caller to
-// double-reference.
-impl AsMatchStr for &str {
+impl AsMatchStr for OsStr {
+    /// The UTF-8 content, or `""` if this is not valid UTF-8.
     fn as_match_str(&self) -> &str {
-        self
+        self.to_str().unwrap_or_default()
+    }
+}
+
+impl AsMatchStr for Path {
+    /// The UTF-8 content, or `""` if this path is not valid UTF-8.
+    fn as_match_str(&self) -> &str {
+        self.as_os_str().as_match_str()
+    }
+}
+
+impl AsMatchStr for CStr {
+    /// The UTF-8 content, or `""` if this is not valid UTF-8.
+    fn as_match_str(&self) -> &str {
+        self.to_str().unwrap_or_default()
+    }
+}
+
+/// An owned path that can be searched in no-keys mode.
+///
+/// Matches by the path's UTF-8 form. A path that is not valid UTF-8 matches
+/// by its lossy conversion, with each invalid sequence replaced by
+/// `U+FFFD`; that copy is the only extra allocation, and valid paths store
+/// none.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, MatchablePath, match_sorter};
+///
+/// let items: Vec<MatchablePath> = ["src/lib.rs", "README.md", "src/key.rs"]
+///     .into_iter()
+///     .map(MatchablePath::new)
+///     .collect();
+/// let results = match_sorter(&items, "src/", &MatchSorterOptions::default());
+/// assert_eq!(results[0].as_path().to_str(), Some("src/key.rs"));
+/// assert_eq!(results.len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MatchablePath {
+    path: PathBuf,
+    /// Lossy UTF-8 form, stored only when `path` is not valid UTF-8.
+    lossy: Option<Box<str>>,
+}
+
+impl MatchablePath {
+    /// Wrap a path for matching.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let lossy = match path.to_str() {
+            Some(_) => None,
+            None => Some(path.to_string_lossy().into()),
+        };
+        Self { path, lossy }
+    }
+
+    /// The wrapped path.
+    pub fn as_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Unwrap the path.
+    pub fn into_path_buf(self) -> PathBuf {
+        self.path
+    }
+}
+
+impl From<PathBuf> for MatchablePath {
+    fn from(path: PathBuf) -> Self {
+        Self::new(path)
     }
 }
 
-impl AsMatchStr for Cow<'_, str> {
+impl AsRef<Path> for MatchablePath {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsMatchStr for MatchablePath {
     fn as_match_str(&self) -> &str {
-        self.as_ref()
+        match &self.lossy {
+            Some(lossy) => lossy,
+            None => self.path.as_match_str(),
+        }
+    }
+}
+
+/// An owned OS string that can be searched in no-keys mode.
+///
+/// The [`OsString`] counterpart of [`MatchablePath`]: a string that is not
+/// valid UTF-8 matches by its lossy conversion, and valid strings store no
+/// copy.
+///
+/// # Examples
+///
+/// ```
+/// use std::ffi::OsString;
+///
+/// use matchsorter::{MatchSorterOptions, MatchableOsString, match_sorter};
+///
+/// let items: Vec<MatchableOsString> = ["HOME", "PATH", "HOSTNAME"]
+///     .into_iter()
+///     .map(MatchableOsString::new)
+///     .collect();
+/// let results = match_sorter(&items, "ho", &MatchSorterOptions::default());
+/// assert_eq!(results[0].as_os_str(), "HOME");
+/// assert_eq!(results.len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MatchableOsString {
+    string: OsString,
+    /// Lossy UTF-8 form, stored only when `string` is not valid UTF-8.
+    lossy: Option<Box<str>>,
+}
+
+impl MatchableOsString {
+    /// Wrap an OS string for matching.
+    pub fn new(string: impl Into<OsString>) -> Self {
+        let string = string.into();
+        let lossy = match string.to_str() {
+            Some(_) => None,
+            None => Some(string.to_string_lossy().into()),
+        };
+        Self { string, lossy }
+    }
+
+    /// The wrapped OS string.
+    pub fn as_os_str(&self) -> &OsStr {
+        &self.string
+    }
+
+    /// Unwrap the OS string.
+    pub fn into_os_string(self) -> OsString {
+        self.string
+    }
+}
+
+impl From<OsString> for MatchableOsString {
+    fn from(string: OsString) -> Self {
+        Self::new(string)
+    }
+}
+
+impl AsRef<OsStr> for MatchableOsString {
+    fn as_ref(&self) -> &OsStr {
+        &self.string
+    }
+}
+
+impl AsMatchStr for MatchableOsString {
+    fn as_match_str(&self) -> &str {
+        match &self.lossy {
+            Some(lossy) => lossy,
+            None => self.string.as_match_str(),
+        }
     }
 }
 
@@ -97,12 +272,16 @@ impl AsMatchStr for Cow<'_, str> {
 /// let item = "Greenland";
 /// assert_eq!(rank_item(&item, "green", false), Ranking::StartsWith);
 /// ```
-pub fn rank_item<T: AsMatchStr>(item: &T, query: &str, keep_diacritics: bool) -> Ranking {
+pub fn rank_item<T: AsMatchStr + ?Sized>(item: &T, query: &str, keep_diacritics: bool) -> Ranking {
     get_match_ranking(item.as_match_str(), query, keep_diacritics)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
     use super::*;
 
     // --- AsMatchStr trait implementation tests ---
@@ -143,6 +322,136 @@ mod tests {
         assert_eq!(s.as_match_str(), "");
     }
 
+    #[test]
+    fn as_match_str_smart_pointers() {
+        let boxed: Box<str> = "boxed".into();
+        let rc: Rc<str> = "rc".into();
+        let arc: Arc<str> = "arc".into();
+        assert_eq!(boxed.as_match_str(), "boxed");
+        assert_eq!(rc.as_match_str(), "rc");
+        assert_eq!(arc.as_match_str(), "arc");
+    }
+
+    /// A user-defined newtype that derefs to `str`.
+    struct Name(String);
+
+    impl Deref for Name {
+        type Target = str;
+
+        fn deref(&self) -> &str {
+            &self.0
+        }
+    }
+
+    // --- Path and OS string tests ---
+
+    #[test]
+    fn as_match_str_paths_and_os_strings() {
+        let path = MatchablePath::new("src/ranking/mod.rs");
+        assert_eq!(path.as_match_str(), "src/ranking/mod.rs");
+        assert_eq!(path.as_path().as_match_str(), "src/ranking/mod.rs");
+        assert_eq!(OsStr::new("Cargo.toml").as_match_str(), "Cargo.toml");
+        assert_eq!(
+            MatchablePath::from(PathBuf::from("a/b")).into_path_buf(),
+            PathBuf::from("a/b")
+        );
+    }
+
+    #[test]
+    fn path_with_parent_components_matches() {
+        let path = MatchablePath::new("../shared/../config/settings.toml");
+        assert_eq!(rank_item(&path, "settings", false), Ranking::Contains);
+        assert_eq!(rank_item(&path, "../shared", false), Ranking::StartsWith);
+    }
+
+    #[test]
+    fn path_with_non_ascii_matches() {
+        let path = MatchablePath::new("docs/r\u{00e9}sum\u{00e9}.pdf");
+        assert_eq!(rank_item(&path, "resume", false), Ranking::Contains);
+        assert_eq!(
+            rank_item(&path, "r\u{00e9}sum\u{00e9}", true),
+            Ranking::Contains
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_paths() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let raw = Path::new(OsStr::from_bytes(b"bad\xffname.txt"));
+        assert_eq!(raw.as_match_str(), "");
+        let path = MatchablePath::new(raw);
+        assert_eq!(path.as_match_str(), "bad\u{fffd}name.txt");
+        assert_eq!(rank_item(&path, "name", false), Ranking::Contains);
+    }
+
+    #[test]
+    fn matchable_os_string_utf8() {
+        let string = MatchableOsString::from(OsString::from("LANG=en_US"));
+        assert_eq!(string.as_match_str(), "LANG=en_US");
+        assert_eq!(rank_item(&string, "lang", false), Ranking::StartsWith);
+        assert_eq!(string.into_os_string(), OsString::from("LANG=en_US"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn matchable_os_string_lossy() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let raw = OsString::from_vec(b"caf\xe9 menu".to_vec());
+        assert_eq!(raw.as_os_str().as_match_str(), "");
+        let string = MatchableOsString::new(raw.clone());
+        assert_eq!(string.as_match_str(), "caf\u{fffd} menu");
+        assert_eq!(rank_item(&string, "menu", false), Ranking::WordStartsWith);
+        assert_eq!(string.as_os_str(), raw.as_os_str());
+    }
+
+    #[test]
+    fn as_match_str_c_str() {
+        assert_eq!(c"libmatch.so".as_match_str(), "libmatch.so");
+        assert_eq!(rank_item(c"libmatch.so", "match", false), Ranking::Contains);
+        assert_eq!(c"bad\xff".as_match_str(), "");
+    }
+
+    #[test]
+    fn matchable_paths_in_match_sorter() {
+        let items: Vec<MatchablePath> = [
+            "src/lib.rs",
+            "src/key.rs",
+            "README.md",
+            "benches/benchmarks.rs",
+        ]
+        .into_iter()
+        .map(MatchablePath::new)
+        .collect();
+        let results = crate::match_sorter(&items, "src/", &crate::MatchSorterOptions::default());
+        let paths: Vec<&Path> = results.iter().map(|p| p.as_path()).collect();
+        assert_eq!(
+            paths,
+            vec![Path::new("src/key.rs"), Path::new("src/lib.rs")]
+        );
+    }
+
+    #[test]
+    fn as_match_str_deref_newtype() {
+        let name = Name("Ada".to_owned());
+        assert_eq!(name.as_match_str(), "Ada");
+        assert_eq!(rank_item(&name, "ada", false), Ranking::Equal);
+    }
+
+    #[test]
+    fn deref_newtype_in_match_sorter() {
+        let items = vec![
+            Name("banana".to_owned()),
+            Name("apple".to_owned()),
+            Name("grape".to_owned()),
+        ];
+        let results = crate::match_sorter(&items, "ap", &crate::MatchSorterOptions::default());
+        let names: Vec<&str> = results.iter().map(|n| &***n).collect();
+        assert_eq!(names, vec!["apple", "grape"]);
+    }
+
     // --- rank_item with String items ---
 
     #[test]
@@ -8,10 +8,21 @@
 
 use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt;
+use std::ops::Range;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use crate::cache::RankingCache;
+#[cfg(feature = "async")]
+use crate::key::AsyncKey;
 use crate::key::Key;
-use crate::ranking::Ranking;
+use crate::ranking::{
+    DiacriticsMode, FuzzyMode, NormalizationForm, Ranking, RankingConfig, UnicodeCaseFolding,
+    ranking_tier_label,
+};
 
 /// Type alias for a custom tiebreaker sort closure used in [`MatchSorterOptions`].
 ///
@@ -25,6 +36,63 @@ type BaseSortFn<T> = Box<dyn Fn(&RankedItem<T>, &RankedItem<T>) -> Ordering>;
 /// completely replacing the default three-level sort.
 type SorterFn<T> = Box<dyn Fn(Vec<RankedItem<T>>) -> Vec<RankedItem<T>>>;
 
+/// Type alias for a query-dependent threshold closure used in
+/// [`MatchSorterOptions`].
+///
+/// Receives the raw query and returns the global threshold to apply.
+type ThresholdFn = Box<dyn Fn(&str) -> Ranking>;
+
+/// Type alias for a custom `Matches` sub-score closure used in
+/// [`MatchSorterOptions`].
+///
+/// Receives `(first_pos, last_pos, candidate_len, query_len)` in chars and
+/// returns a sub-score, which is clamped into `(1.0, 2.0]`.
+type ClosenessScorerFn = Box<dyn Fn(usize, usize, usize, usize) -> f64>;
+
+/// Type alias for a word-splitting closure used in [`MatchSorterOptions`].
+///
+/// Receives a candidate and returns the byte range of each of its words.
+type WordSplitterFn = Box<dyn Fn(&str) -> Vec<Range<usize>>>;
+
+/// Type alias for a word-stemming closure used in [`MatchSorterOptions`].
+///
+/// Receives a single lowercase word and returns its stem.
+type StemmerFn = Box<dyn Fn(&str) -> String>;
+
+/// Type alias for a per-language normalizer used in [`MatchSorterOptions`].
+///
+/// Receives a value or query before preparation and returns its normalized
+/// form.
+type LanguageNormalizerFn = Box<dyn Fn(&str) -> String>;
+
+/// Type alias for a custom case fold used in [`MatchSorterOptions`].
+///
+/// Receives a character and a BCP-47 language tag and returns the
+/// character's lowercase form in that language.
+type CaseFoldFn = Box<dyn Fn(char, &str) -> String>;
+
+/// Type alias for a rank-adjusting closure used in [`MatchSorterOptions`].
+///
+/// Receives an item and the rank of one of its matches and returns the
+/// rank to use instead.
+type ScoreModifierFn<T> = Box<dyn Fn(&T, Ranking) -> Ranking>;
+
+/// Type alias for an item predicate used in [`MatchSorterOptions`].
+///
+/// Returns `true` for items that may be ranked.
+type ItemFilterFn<T> = Box<dyn Fn(&T) -> bool>;
+
+/// Type alias for a deduplication key used in [`MatchSorterOptions`].
+///
+/// Returns the value by which two items count as duplicates.
+type DedupKeyFn<T> = Box<dyn Fn(&T) -> String>;
+
+/// Type alias for an item callback used in [`MatchSorterOptions`].
+///
+/// Receives an item discarded for ranking slower than
+/// [`MatchSorterOptions::discard_items_slower_than`].
+type SlowItemFn<T> = Box<dyn Fn(&T)>;
+
 /// An item annotated with its ranking information.
 ///
 /// Produced during the ranking phase of the match-sorting pipeline and
@@ -50,11 +118,16 @@ type SorterFn<T> = Box<dyn Fn(Vec<RankedItem<T>>) -> Vec<RankedItem<T>>>;
 ///     ranked_value: Cow::Borrowed("hello"),
 ///     key_index: 0,
 ///     key_threshold: None,
+///     is_soft_match: false,
+///     matched_portion: None,
+///     matched_key_alias: None,
+///     tie_priority: None,
 /// };
 /// assert_eq!(ranked.rank, Ranking::CaseSensitiveEqual);
 /// assert_eq!(*ranked.item, "hello");
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RankedItem<'a, T> {
     /// Reference to the original item in the input slice.
     pub item: &'a T,
@@ -78,6 +151,386 @@ pub struct RankedItem<'a, T> {
     /// Per-key threshold override from the winning key, or `None` if the
     /// key uses the global threshold.
     pub key_threshold: Option<Ranking>,
+
+    /// `true` when the item passed only the
+    /// [`soft_threshold`](MatchSorterOptions::soft_threshold), not the hard
+    /// threshold. UIs typically dim such weak matches.
+    pub is_soft_match: bool,
+
+    /// The part of `ranked_value` that matched the query, for highlighting.
+    ///
+    /// The whole value for `Equal` and `CaseSensitiveEqual`, the matched
+    /// occurrence for `StartsWith`, `WordStartsWith` and `Contains`, the
+    /// value's acronym for `Acronym`, and the span from the first to the
+    /// last matched character for `Matches`. The tier is that of the value
+    /// itself, before any per-key `max_ranking` / `min_ranking` adjustment;
+    /// `None` when the value does not match the query on its own.
+    pub matched_portion: Option<Cow<'a, str>>,
+
+    /// The [alias](crate::Key::alias) of the key that produced
+    /// `ranked_value`, or `None` in no-keys mode and for keys without one.
+    pub matched_key_alias: Option<String>,
+
+    /// The [tie priority](crate::Key::tie_priority) of the key that
+    /// produced `ranked_value`, or `None` in no-keys mode and for keys
+    /// without one. Among items with equal rank and key index, a higher
+    /// priority sorts first, and items without one sort last.
+    pub tie_priority: Option<i64>,
+}
+
+// Implemented by hand so that cloning only copies the item reference and
+// does not require `T: Clone`.
+impl<T> Clone for RankedItem<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            item: self.item,
+            index: self.index,
+            rank: self.rank,
+            ranked_value: self.ranked_value.clone(),
+            key_index: self.key_index,
+            key_threshold: self.key_threshold,
+            is_soft_match: self.is_soft_match,
+            matched_portion: self.matched_portion.clone(),
+            matched_key_alias: self.matched_key_alias.clone(),
+            tie_priority: self.tie_priority,
+        }
+    }
+}
+
+impl<T> RankedItem<'_, T> {
+    /// Returns the lowercase label of this item's ranking tier, e.g.
+    /// `"starts_with"` or `"fuzzy"`. See [`ranking_tier_label`].
+    pub fn rank_label(&self) -> &'static str {
+        ranking_tier_label(self.rank)
+    }
+
+    /// Returns the integer tier of this item's ranking, from 7
+    /// (`CaseSensitiveEqual`) down to 0 (`NoMatch`). Every `Matches`
+    /// sub-score maps to tier 1.
+    pub fn rank_tier(&self) -> u8 {
+        self.rank.tier_value()
+    }
+
+    /// Returns the [alias](crate::Key::alias) of the key that matched, or
+    /// `None` if that key has none.
+    pub fn key_alias(&self) -> Option<&str> {
+        self.matched_key_alias.as_deref()
+    }
+
+    /// Returns `true` if the item matched the query exactly, including case
+    /// ([`Ranking::CaseSensitiveEqual`]).
+    pub fn is_exact_match(&self) -> bool {
+        self.rank == Ranking::CaseSensitiveEqual
+    }
+
+    /// Clone the item and ranked value into an [`OwnedRankedItem`] that no
+    /// longer borrows the input slice.
+    pub fn into_owned(self) -> OwnedRankedItem<T>
+    where
+        T: Clone,
+    {
+        OwnedRankedItem {
+            item: self.item.clone(),
+            index: self.index,
+            rank: self.rank,
+            ranked_value: self.ranked_value.into_owned(),
+            key_index: self.key_index,
+            key_threshold: self.key_threshold,
+            is_soft_match: self.is_soft_match,
+            matched_portion: self.matched_portion.map(Cow::into_owned),
+            matched_key_alias: self.matched_key_alias,
+            tie_priority: self.tie_priority,
+        }
+    }
+}
+
+/// A [`RankedItem`] that owns its item and ranked value.
+///
+/// `RankedItem` borrows from the searched slice, which ties it to that
+/// slice's lifetime. `OwnedRankedItem` can be stored, sent across threads,
+/// or (with the `serde` feature) serialized and deserialized to cache match
+/// results. Borrow it back as a `RankedItem` with `RankedItem::from(&owned)`.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+/// use matchsorter::{OwnedRankedItem, RankedItem, Ranking};
+///
+/// let cached: Vec<OwnedRankedItem<String>> = {
+///     let item = "apple".to_owned();
+///     let ranked = RankedItem {
+///         item: &item,
+///         index: 0,
+///         rank: Ranking::StartsWith,
+///         ranked_value: Cow::Borrowed(item.as_str()),
+///         key_index: 0,
+///         key_threshold: None,
+///         is_soft_match: false,
+///         matched_portion: None,
+///         matched_key_alias: None,
+///         tie_priority: None,
+///     };
+///     vec![ranked.into_owned()]
+/// };
+/// // `item` is gone, but the owned result lives on.
+/// assert_eq!(cached[0].item, "apple");
+/// assert_eq!(RankedItem::from(&cached[0]).rank, Ranking::StartsWith);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedRankedItem<T> {
+    /// The original item.
+    pub item: T,
+
+    /// Original index of the item in the searched slice.
+    pub index: usize,
+
+    /// The ranking score representing how well the item matched the query.
+    pub rank: Ranking,
+
+    /// The string value that produced the best match against the query.
+    pub ranked_value: String,
+
+    /// Index of the winning key-value pair in the flattened key-values list.
+    pub key_index: usize,
+
+    /// Per-key threshold override from the winning key, or `None`.
+    pub key_threshold: Option<Ranking>,
+
+    /// `true` when the item passed only the soft threshold.
+    pub is_soft_match: bool,
+
+    /// The part of `ranked_value` that matched the query, or `None`.
+    pub matched_portion: Option<String>,
+
+    /// The alias of the winning key, or `None`.
+    pub matched_key_alias: Option<String>,
+
+    /// The tie priority of the winning key, or `None`.
+    pub tie_priority: Option<i64>,
+}
+
+/// A match together with what matched, as returned by
+/// [`match_sorter_with_annotations`](crate::match_sorter_with_annotations).
+///
+/// `highlight_ranges` are byte ranges into `matched_value` covering the
+/// characters that matched the query, suitable for rendering highlights.
+/// They are sorted, non-empty and never touch. They describe the value's
+/// own match, so they do not reflect per-key adjustments such as
+/// [`Key::max_ranking`](crate::Key::max_ranking) or a weight.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchAnnotation<'a, T> {
+    /// Reference to the matching item.
+    pub item: &'a T,
+
+    /// The ranking of the item, as used for sorting.
+    pub rank: Ranking,
+
+    /// The alias of the key that produced the match, or `None` in no-keys
+    /// mode or when the key has no alias.
+    pub matched_field: Option<String>,
+
+    /// The string value that produced the best match against the query.
+    pub matched_value: String,
+
+    /// Byte ranges of `matched_value` that matched the query.
+    pub highlight_ranges: Vec<Range<usize>>,
+}
+
+/// A match paired with a normalized score, as returned by
+/// [`match_sorter_by_score`](crate::match_sorter_by_score).
+///
+/// `score` is [`Ranking::to_f64`] divided by 7, so it lies in
+/// `[0.0, 1.0]`: 1.0 for [`Ranking::CaseSensitiveEqual`] down to about
+/// 0.29 for [`Ranking::Acronym`], with fuzzy matches below that.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoredItem<'a, T> {
+    /// Reference to the matching item.
+    pub item: &'a T,
+
+    /// The item's normalized score in `[0.0, 1.0]`.
+    pub score: f64,
+}
+
+impl<'a, T> ScoredItem<'a, T> {
+    /// Returns the matching item.
+    pub fn into_item(self) -> &'a T {
+        self.item
+    }
+
+    /// Returns the normalized score.
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+
+    /// Returns whether the score is strictly greater than `threshold`.
+    ///
+    /// For filtering results on a score cut-off after the search.
+    pub fn above(&self, threshold: f64) -> bool {
+        self.score > threshold
+    }
+}
+
+/// The sorted matches of a search together with the ranking that produced
+/// them, as returned by
+/// [`match_sorter_with_snapshot`](crate::match_sorter_with_snapshot).
+///
+/// `ranked` holds the matches in input order, before sorting, so that an
+/// unexpected order can be traced back to the tier and key each item was
+/// ranked with.
+#[derive(Debug, PartialEq)]
+pub struct MatchSorterSnapshot<'a, T> {
+    /// The matching items in sorted order, as returned by
+    /// [`match_sorter`](crate::match_sorter).
+    pub sorted: Vec<&'a T>,
+
+    /// The ranked matches in input order, before sorting.
+    pub ranked: Vec<RankedItem<'a, T>>,
+
+    /// How many of the matches fell into each tier.
+    pub stats: RankingStats,
+}
+
+impl<'a, T> MatchSorterSnapshot<'a, T> {
+    /// Discard the ranking and return the sorted matches.
+    pub fn into_sorted(self) -> Vec<&'a T> {
+        self.sorted
+    }
+}
+
+/// The number of matches per ranking tier, as reported in a
+/// [`MatchSorterSnapshot`]. Field names follow [`ranking_tier_label`].
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{Ranking, RankingStats};
+///
+/// let stats: RankingStats = [Ranking::Equal, Ranking::Contains, Ranking::Contains]
+///     .into_iter()
+///     .collect();
+/// assert_eq!(stats.contains, 2);
+/// assert_eq!(stats.total(), 3);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RankingStats {
+    /// Matches ranked [`Ranking::CaseSensitiveEqual`].
+    pub exact: usize,
+    /// Matches ranked [`Ranking::Equal`].
+    pub equal: usize,
+    /// Matches ranked [`Ranking::StartsWith`].
+    pub starts_with: usize,
+    /// Matches ranked [`Ranking::WordStartsWith`].
+    pub word_starts_with: usize,
+    /// Matches ranked [`Ranking::Contains`].
+    pub contains: usize,
+    /// Matches ranked [`Ranking::Acronym`].
+    pub acronym: usize,
+    /// Matches ranked [`Ranking::Matches`], whatever the sub-score.
+    pub fuzzy: usize,
+    /// Matches ranked [`Ranking::NoMatch`], which only pass a `NoMatch`
+    /// threshold.
+    pub no_match: usize,
+}
+
+impl RankingStats {
+    /// Count one match ranked `rank`.
+    pub fn record(&mut self, rank: Ranking) {
+        let count = match rank {
+            Ranking::CaseSensitiveEqual => &mut self.exact,
+            Ranking::Equal => &mut self.equal,
+            Ranking::StartsWith => &mut self.starts_with,
+            Ranking::WordStartsWith => &mut self.word_starts_with,
+            Ranking::Contains => &mut self.contains,
+            Ranking::Acronym => &mut self.acronym,
+            Ranking::Matches(_) => &mut self.fuzzy,
+            Ranking::NoMatch => &mut self.no_match,
+        };
+        *count += 1;
+    }
+
+    /// The number of matches counted across all tiers.
+    pub fn total(&self) -> usize {
+        self.exact
+            + self.equal
+            + self.starts_with
+            + self.word_starts_with
+            + self.contains
+            + self.acronym
+            + self.fuzzy
+            + self.no_match
+    }
+}
+
+impl FromIterator<Ranking> for RankingStats {
+    fn from_iter<I: IntoIterator<Item = Ranking>>(iter: I) -> Self {
+        let mut stats = Self::default();
+        for rank in iter {
+            stats.record(rank);
+        }
+        stats
+    }
+}
+
+/// Outcome of [`match_sorter_cancellable`](crate::match_sorter_cancellable).
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::AtomicBool;
+/// use matchsorter::{MatchSorterOptions, MatchSorterResult, match_sorter_cancellable};
+///
+/// let items = ["apple", "grape"];
+/// let opts = MatchSorterOptions {
+///     cancel_token: Some(Arc::new(AtomicBool::new(true))),
+///     ..Default::default()
+/// };
+/// let result = match_sorter_cancellable(&items, "ap", &opts);
+/// assert!(result.is_cancelled());
+/// assert_eq!(result, MatchSorterResult::Cancelled(vec![]));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchSorterResult<'a, T> {
+    /// Every item was ranked; matches are sorted best first.
+    Complete(Vec<&'a T>),
+    /// The cancel token was set. Holds the matches found before
+    /// cancellation, unsorted, in input order.
+    Cancelled(Vec<&'a T>),
+}
+
+impl<'a, T> MatchSorterResult<'a, T> {
+    /// Returns `true` if the search was cancelled before ranking every item.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, MatchSorterResult::Cancelled(_))
+    }
+
+    /// The matched items, whether or not the search completed.
+    pub fn into_vec(self) -> Vec<&'a T> {
+        match self {
+            MatchSorterResult::Complete(items) | MatchSorterResult::Cancelled(items) => items,
+        }
+    }
+}
+
+impl<'a, T> From<&'a OwnedRankedItem<T>> for RankedItem<'a, T> {
+    /// Borrow an owned result as a `RankedItem`; the owned value acts as
+    /// the backing storage for the item and ranked value.
+    fn from(owned: &'a OwnedRankedItem<T>) -> Self {
+        RankedItem {
+            item: &owned.item,
+            index: owned.index,
+            rank: owned.rank,
+            ranked_value: Cow::Borrowed(&owned.ranked_value),
+            key_index: owned.key_index,
+            key_threshold: owned.key_threshold,
+            is_soft_match: owned.is_soft_match,
+            matched_portion: owned.matched_portion.as_deref().map(Cow::Borrowed),
+            matched_key_alias: owned.matched_key_alias.clone(),
+            tie_priority: owned.tie_priority,
+        }
+    }
 }
 
 /// Global options that control match-sorting behavior.
@@ -89,9 +542,47 @@ pub struct RankedItem<'a, T> {
 /// All fields default to their most common usage:
 /// - `keys`: empty (no-keys mode; items must be string-like)
 /// - `threshold`: `Ranking::Matches(1.0)` (include fuzzy matches and above)
+/// - `threshold_fn`: `None` (always use `threshold`)
+/// - `key_thresholds`: empty (keys use their own threshold, if any)
+/// - `soft_threshold`: `None` (no weak matches; `threshold` is the cutoff)
+/// - `query_mode`: [`QueryMode::Single`] (the query is one string)
+/// - `empty_query_behavior`: [`EmptyQueryBehavior::ReturnAll`] (the empty
+///   query matches every item)
+/// - `exclude_terms`: empty (no item is excluded)
+/// - `exclude_threshold`: `Ranking::Contains`
 /// - `keep_diacritics`: `false` (diacritics are stripped before comparison)
+/// - `diacritics_mode`: [`DiacriticsMode::StripAll`] (every mark is stripped)
+/// - `normalization_form`: [`NormalizationForm::Nfd`] (no compatibility
+///   folding)
+/// - `unicode_case_folding`: [`UnicodeCaseFolding::Simple`] (plain
+///   lowercasing)
+/// - `acronym_prefix_match`: `false` (acronym may contain the query anywhere)
+/// - `acronym_exact_match`: `false`
+/// - `acronym_max_length`: `None` (acronyms use every word)
+/// - `hyphen_is_word_boundary`: `false` (only spaces start words)
+/// - `unicode_word_boundaries`: `false` (only the ASCII space byte)
+/// - `word_splitter`: `None` (uses the word-boundary settings above)
+/// - `fuzzy_mode`: [`FuzzyMode::Greedy`] (first occurrence of each char)
+/// - `grapheme_matching`: `false` (spreads are counted in chars)
+/// - `ensemble_windows`: `None` (one greedy scan per fuzzy match)
+/// - `prefilter`: `false` (every candidate is fully ranked)
+/// - `strict_empty_values`: `false` (empty values are ranked like any other)
+/// - `query_stemmer`: `None` (the query is matched as typed)
+/// - `language_normalizers`: empty (only the built-in normalizers apply)
+/// - `custom_case_fold`: `None` (uses the built-in case rules)
+/// - `closeness_scorer`: `None` (uses `1.0 + 1.0 / spread`)
+/// - `item_score_modifier`: `None` (ranks are used as computed)
+/// - `item_filter`: `None` (every item is ranked)
+/// - `discard_items_slower_than`: `None` (items are not timed)
+/// - `on_slow_item`: `None`
 /// - `base_sort`: `None` (uses default alphabetical tiebreaker)
 /// - `sorter`: `None` (uses default three-level sort)
+/// - `dedup_key`: `None` (merged duplicates are the same item in memory)
+/// - `cancel_token`: `None` (searches always run to completion)
+/// - `ranking_cache`: `None` (every candidate is ranked afresh)
+/// - `expected_match_count`: `None` (capacity guessed from the threshold)
+/// - `shrink_to_fit`: `false`
+/// - `early_stop_criteria`: `None` (every item is ranked)
 ///
 /// Because `base_sort` and `sorter` hold trait objects (`Box<dyn Fn>`),
 /// `MatchSorterOptions<T>` cannot derive `Clone`, `PartialEq`, or `Default`.
@@ -124,16 +615,350 @@ pub struct MatchSorterOptions<T> {
     /// score, meaning all matching items (including fuzzy) are included.
     pub threshold: Ranking,
 
+    /// Computes the global threshold from the query.
+    ///
+    /// When `Some`, the closure is called once per search with the raw query
+    /// (before any [`query_stemmer`](Self::query_stemmer)) and its result
+    /// replaces [`threshold`](Self::threshold). Per-key thresholds still take
+    /// precedence over it. Typical use is a stricter threshold for very short
+    /// queries, which otherwise fuzzy-match almost everything; see
+    /// [`with_length_adaptive_threshold`](Self::with_length_adaptive_threshold).
+    pub threshold_fn: Option<ThresholdFn>,
+
+    /// Per-key thresholds by index into [`keys`](Self::keys).
+    ///
+    /// An entry for key `i` takes precedence over that key's own
+    /// [`Key::threshold`], which in turn takes precedence over the global
+    /// threshold. Useful when thresholds come from configuration rather
+    /// than being set while building the keys. Entries for indices past the
+    /// end of `keys` are ignored. See also
+    /// [`key_threshold`](Self::key_threshold).
+    pub key_thresholds: HashMap<usize, Ranking>,
+
+    /// Lower cutoff for "weak" matches, below the hard threshold.
+    ///
+    /// [`threshold`](Self::threshold) (or [`threshold_fn`](Self::threshold_fn),
+    /// or a per-key threshold) acts as the hard threshold: items at or above
+    /// it are full matches. When `Some` and lower than the hard threshold,
+    /// items ranked between the two are still included but carry
+    /// [`RankedItem::is_soft_match`] set to `true`; items below it are
+    /// excluded. A soft threshold at or above the hard one has no effect.
+    pub soft_threshold: Option<Ranking>,
+
+    /// How the query is interpreted.
+    ///
+    /// [`QueryMode::Single`] matches the whole query as typed.
+    /// [`QueryMode::AnyToken`] treats whitespace-separated tokens as OR
+    /// alternatives, so `"apple banana"` finds items matching either word.
+    /// Each token is prepared once per search; an item's
+    /// [`ranked_value`](RankedItem::ranked_value) comes from the winning
+    /// token. Thresholds apply to the best token's rank, and
+    /// [`threshold_fn`](Self::threshold_fn) still receives the full query.
+    pub query_mode: QueryMode,
+
+    /// What [`match_sorter`](crate::match_sorter) returns for the empty
+    /// query.
+    ///
+    /// The empty string is a prefix of every value, so by default
+    /// ([`EmptyQueryBehavior::ReturnAll`]) it matches every item and the
+    /// items are sorted as usual. [`EmptyQueryBehavior::ReturnEmpty`]
+    /// returns nothing instead, for search boxes that show no results until
+    /// something is typed. [`EmptyQueryBehavior::ReturnAllUnsorted`]
+    /// returns every item in input order without ranking any of them, which
+    /// is much faster when all items are shown anyway. Only the empty
+    /// string counts as empty; a query of spaces is searched as usual.
+    ///
+    /// Every entry point answers the empty query this way. Those that
+    /// report how each item ranked, such as
+    /// [`match_sorter_ranked`](crate::match_sorter_ranked), still rank the
+    /// items under [`EmptyQueryBehavior::ReturnAllUnsorted`] to fill in
+    /// their rankings, keeping the matches in input order.
+    pub empty_query_behavior: EmptyQueryBehavior,
+
+    /// Terms that remove an item from the results, like `-banana` in a
+    /// search box.
+    ///
+    /// After an item passes the threshold, each of its values (its
+    /// [`AsMatchStr`](crate::AsMatchStr) string, or every value its keys
+    /// extract) is ranked against each term; a rank at or above
+    /// [`exclude_threshold`](Self::exclude_threshold) excludes the item.
+    /// Terms honor [`keep_diacritics`](Self::keep_diacritics) but are not
+    /// stemmed, and keys' `min_ranking`/`max_ranking` and prefix boosts do
+    /// not apply to them. Empty or whitespace-only terms are ignored.
+    pub exclude_terms: Vec<String>,
+
+    /// The lowest rank at which an [`exclude_terms`](Self::exclude_terms)
+    /// entry excludes an item. The default, `Contains`, excludes items
+    /// containing the term anywhere; lower it to `Acronym` or a `Matches`
+    /// rank to also exclude abbreviated or fuzzy occurrences.
+    pub exclude_threshold: Ranking,
+
     /// When `true`, diacritics (accents, combining marks) are preserved during
     /// comparison. When `false` (default), diacritics are stripped so that
     /// e.g. "cafe" matches "caf\u{00e9}".
     pub keep_diacritics: bool,
 
+    /// Which diacritics are stripped when
+    /// [`keep_diacritics`](Self::keep_diacritics) is `false`.
+    ///
+    /// The default, [`DiacriticsMode::StripAll`], strips every combining
+    /// mark. [`DiacriticsMode::StripNonDistinctive`] keeps the marks that
+    /// make a different letter in the given language, such as the Spanish
+    /// tilde in `"a\u{00f1}o"`. `keep_diacritics: true` overrides this
+    /// setting and keeps every mark.
+    pub diacritics_mode: DiacriticsMode,
+
+    /// The Unicode normalization applied to the query and every candidate
+    /// before comparison.
+    ///
+    /// The default, [`NormalizationForm::Nfd`], only decomposes characters
+    /// to strip diacritics. [`NormalizationForm::Nfkc`] also folds
+    /// compatibility characters, so the ligature `"\u{fb01}"` matches `"fi"`
+    /// and full-width `"\u{ff41}"` matches `"a"`; useful for text from PDFs,
+    /// LaTeX output or CJK sources. [`NormalizationForm::None`] compares
+    /// strings as written, diacritics included.
+    pub normalization_form: NormalizationForm,
+
+    /// How the query and every candidate are case-folded before comparison.
+    ///
+    /// The default, [`UnicodeCaseFolding::Simple`], lowercases each
+    /// character. [`UnicodeCaseFolding::Full`] applies Unicode full case
+    /// folding, so the German `"Ma\u{df}e"` equals `"masse"`, and
+    /// [`UnicodeCaseFolding::Turkic`] follows the Turkish rules for the
+    /// letter i. Keys with a [`Key::language`] whose case rules differ
+    /// (Turkish, Azerbaijani, or any language with
+    /// [`custom_case_fold`](Self::custom_case_fold)) use those rules
+    /// instead. Folding follows diacritics stripping, so without
+    /// [`keep_diacritics`](Self::keep_diacritics) an `"\u{130}"` loses its
+    /// dot before the Turkic rules see it.
+    pub unicode_case_folding: UnicodeCaseFolding,
+
+    /// When `true`, the `Acronym` tier only applies if the query is a
+    /// **prefix** of the candidate's acronym.
+    ///
+    /// By default (`false`), the query may appear anywhere in the acronym, so
+    /// `"wa"` matches `"North-West Airlines"` (acronym `"nwa"`) as `Acronym`.
+    /// Prefix mode rejects such mid-acronym hits while still accepting
+    /// abbreviations typed from the start, like `"nw"` or `"nwa"`. Rejected
+    /// candidates fall through to fuzzy matching.
+    pub acronym_prefix_match: bool,
+
+    /// When `true`, the `Acronym` tier only applies if the query **equals**
+    /// the candidate's acronym. Takes precedence over `acronym_prefix_match`.
+    ///
+    /// This is the strictest mode: `"nwa"` matches `"North-West Airlines"`
+    /// but `"nw"` does not. Use it when partial abbreviations produce too
+    /// many spurious `Acronym` hits, at the cost of ranking partially typed
+    /// acronyms as fuzzy matches only.
+    pub acronym_exact_match: bool,
+
+    /// Maximum number of characters in a candidate's acronym, or `None` for
+    /// no limit.
+    ///
+    /// With `Some(4)`, only the first four word-initial characters form the
+    /// acronym, so a query must match within the first four words to reach
+    /// the `Acronym` tier. Users rarely type abbreviations longer than a few
+    /// letters, and long candidates otherwise produce acronyms that
+    /// spuriously contain short queries.
+    pub acronym_max_length: Option<usize>,
+
+    /// When `true`, a hyphen (`'-'`) preceding a substring match counts as a
+    /// word boundary for the `WordStartsWith` tier, in addition to a space.
+    ///
+    /// Hyphens are always acronym delimiters; this makes word-start
+    /// detection agree with them. Useful for hyphenated names, compound
+    /// words, and URL slugs: with this enabled, `"west"` ranks
+    /// `"North-West"` as `WordStartsWith` instead of `Contains`.
+    pub hyphen_is_word_boundary: bool,
+
+    /// When `true`, any Unicode whitespace character (per
+    /// [`char::is_whitespace`]) preceding a substring match counts as a word
+    /// boundary for the `WordStartsWith` tier, not just the ASCII space.
+    ///
+    /// This covers tabs, newlines, no-break space (U+00A0), thin space
+    /// (U+2009), ideographic space (U+3000) and the like. When the byte
+    /// before a match is ASCII the check stays a single byte comparison;
+    /// only a multi-byte predecessor is decoded, so ASCII-heavy data pays
+    /// almost nothing for enabling it.
+    pub unicode_word_boundaries: bool,
+
+    /// Custom word splitter for the `WordStartsWith` tier.
+    ///
+    /// Called with each candidate after diacritics handling but before
+    /// lowercasing, so that case transitions are visible; it returns the
+    /// byte range of each word. A substring match then ranks
+    /// `WordStartsWith` only when it begins at the start of a returned
+    /// word other than the first character. The splitter replaces
+    /// [`hyphen_is_word_boundary`](Self::hyphen_is_word_boundary) and
+    /// [`unicode_word_boundaries`](Self::unicode_word_boundaries) for this
+    /// tier; a splitter returning no words disables it. Acronyms are still
+    /// built from spaces and hyphens.
+    ///
+    /// [`camel_case_splitter`](crate::camel_case_splitter) splits
+    /// identifiers such as `"parseHtmlResponse"` into their words. When
+    /// `None`, the word-boundary settings above apply.
+    pub word_splitter: Option<WordSplitterFn>,
+
+    /// How query characters are assigned to candidate positions when
+    /// computing the `Matches` tier sub-score.
+    ///
+    /// [`FuzzyMode::Greedy`] takes the first occurrence of each character;
+    /// [`FuzzyMode::Optimal`] finds the assignment with the smallest spread,
+    /// so `"aab"` queried with `"ab"` scores `Matches(2.0)` instead of
+    /// `Matches(1.5)`. The mode never moves a candidate between tiers.
+    pub fuzzy_mode: FuzzyMode,
+
+    /// When `true`, the `Matches` tier measures spread in grapheme clusters
+    /// instead of chars, as in
+    /// [`get_closeness_ranking_grapheme`](crate::ranking::get_closeness_ranking_grapheme).
+    ///
+    /// A letter followed by combining marks then counts as one position, so
+    /// NFC and NFD spellings of the same text score alike. This only
+    /// matters when marks survive preparation, i.e. with
+    /// [`keep_diacritics`](Self::keep_diacritics) or a
+    /// [`diacritics_mode`](Self::diacritics_mode) that keeps some marks.
+    /// Which candidates match is unchanged. A custom
+    /// [`closeness_scorer`](Self::closeness_scorer) receives grapheme
+    /// positions and lengths.
+    pub grapheme_matching: bool,
+
+    /// When `Some(n)`, a fuzzy match in [`FuzzyMode::Greedy`] runs up to `n`
+    /// greedy scans, each starting at a later occurrence of the query's
+    /// first character, and keeps the best sub-score, as in
+    /// [`get_closeness_ranking_ensemble`](crate::ranking::get_closeness_ranking_ensemble).
+    ///
+    /// Makes the `Matches` sub-score less sensitive to where the first scan
+    /// happens to start, at the cost of up to `n` scans per fuzzy match.
+    /// Only affects candidates that reach the `Matches` tier, and has no
+    /// effect in [`FuzzyMode::Optimal`], which already finds the tightest
+    /// span. `Some(1)` (or `Some(0)`) ranks like `None`.
+    pub ensemble_windows: Option<usize>,
+
+    /// When `true`, candidates lacking the query's first character are
+    /// rejected with a `memchr` scan before ranking, for every key and in
+    /// no-keys mode.
+    ///
+    /// Results are unchanged; see [`Key::with_prefilter`] for when the
+    /// check applies and what it costs.
+    pub prefilter: bool,
+
+    /// When `true`, every key behaves as if wrapped in
+    /// [`Key::require_non_empty`]: in builds with `debug_assertions`,
+    /// ranking panics when a key extracts no values or an empty string, and
+    /// in release builds empty strings are dropped before ranking.
+    ///
+    /// Only applies while ranking; [`Key::extract`] and
+    /// [`get_item_values`](crate::get_item_values) still return every
+    /// value. Has no effect in no-keys mode.
+    pub strict_empty_values: bool,
+
+    /// Stemming function applied to the query before ranking.
+    ///
+    /// When `Some`, the query is lowercased and each whitespace-separated
+    /// word is replaced by its stem, exactly as
+    /// [`Key::with_stemmer`] does for candidate values. Use the same
+    /// function on both sides so that `"running"` and `"run"` meet at
+    /// `"run"`. When `None`, the query is matched as typed.
+    pub query_stemmer: Option<StemmerFn>,
+
+    /// Normalizers for keys marked with [`Key::multi_language`] or
+    /// [`Key::language`], by BCP-47 language tag.
+    ///
+    /// For each value of such a key, the item's tag is looked up here,
+    /// first as given and then by its primary language subtag (`"de"` for
+    /// `"de-AT"`), and then among the
+    /// [built-in normalizers](crate::language::builtin_normalizer). The
+    /// normalizer found is applied to the value and to the query before
+    /// either is prepared for ranking. Entries here take precedence over
+    /// the built-ins; map a tag to a function returning its input unchanged
+    /// to switch a built-in off. Languages without a normalizer are ranked
+    /// as usual.
+    pub language_normalizers: HashMap<String, LanguageNormalizerFn>,
+
+    /// Lowercases text for keys with a language, replacing the built-in
+    /// case rules.
+    ///
+    /// Called as `fold(c, tag)` for every character of the values and the
+    /// query of a key marked with [`Key::language`] or
+    /// [`Key::multi_language`], where `tag` is the key's or item's BCP-47
+    /// tag; the results are concatenated. Keys without a language always
+    /// use the default Unicode lowercasing. When `None`, Turkish and
+    /// Azerbaijani lowercase their dotted and dotless i's by their own rules
+    /// and every other language uses the default; see
+    /// [`LanguageAwareOptions`](crate::language::LanguageAwareOptions).
+    pub custom_case_fold: Option<CaseFoldFn>,
+
+    /// Custom formula for the `Matches` tier sub-score.
+    ///
+    /// Called as `scorer(first_pos, last_pos, candidate_len, query_len)`
+    /// whenever a candidate falls through to fuzzy matching, with char
+    /// positions of the first and last matched query characters and char
+    /// lengths of the (lowercased) candidate and query. The result is
+    /// clamped into `(1.0, 2.0]`, so any formula stays below `Acronym`.
+    ///
+    /// When `None`, the default `1.0 + 1.0 / spread` (with `spread =
+    /// last_pos - first_pos`, and `2.0` for a zero spread) is used. That
+    /// curve drops off steeply; linear decay such as
+    /// `1.0 + (1.0 - spread / candidate_len)` or exponential decay such as
+    /// `1.0 + 2.0^(-spread)` are common alternatives.
+    pub closeness_scorer: Option<ClosenessScorerFn>,
+
+    /// Adjusts the rank of every match, for keys without their own
+    /// [`Key::with_score_modifier`].
+    ///
+    /// Called with the item and the rank of each matching value (after
+    /// per-key clamping and boosts); the returned rank is used instead,
+    /// and [`Ranking::NoMatch`] drops the match. In no-keys mode it is
+    /// applied to the item's own rank. As with the per-key modifier, the
+    /// result is used as is. When `None`, ranks are used as computed.
+    pub item_score_modifier: Option<ScoreModifierFn<T>>,
+
+    /// Restricts the search to items for which the predicate returns
+    /// `true`, such as those visible in a viewport or passing a secondary
+    /// filter.
+    ///
+    /// Called once per item before ranking; rejected items are skipped
+    /// without extracting or ranking their values. The results are those
+    /// of searching the filtered items, except that
+    /// [`RankedItem::index`] still refers to the full slice. This saves
+    /// collecting the subset into a new `Vec`, which pays off when the
+    /// filter keeps most items; when it rejects most of them, filtering
+    /// the slice first and searching the smaller one is as fast. When
+    /// `None`, every item is ranked.
+    ///
+    /// Not to be confused with [`prefilter`](Self::prefilter), which only
+    /// skips values lacking the query's first character.
+    pub item_filter: Option<ItemFilterFn<T>>,
+
+    /// Discards items that took longer than this to rank, after ranking
+    /// them.
+    ///
+    /// This is a filter on the results, not a timeout. Each item is ranked
+    /// to completion on the calling thread and only then timed; if it took
+    /// longer, it is treated as a non-match and reported to
+    /// [`on_slow_item`](Self::on_slow_item). A slow extractor is never
+    /// interrupted, so the search takes as long as it would without this
+    /// option. It only keeps items whose keys were unusually slow, such as
+    /// ones calling another service, out of the results.
+    ///
+    /// Since it depends on wall-clock time, the filter is not deterministic:
+    /// the same search may keep an item once and drop it the next time.
+    /// [`PaginationCache`](crate::PaginationCache) does not cache, and
+    /// [`match_sorter_incremental`](crate::match_sorter_incremental) does
+    /// not refine, searches that set it. When `None`, items are not timed.
+    pub discard_items_slower_than: Option<Duration>,
+
+    /// Called with each item discarded by
+    /// [`discard_items_slower_than`](Self::discard_items_slower_than), for
+    /// logging.
+    pub on_slow_item: Option<SlowItemFn<T>>,
+
     /// Custom tiebreaker sort function.
     ///
     /// Called when two items have identical rank and key index during the
     /// default three-level sort. When `None`, the default alphabetical
-    /// comparison of `ranked_value` is used.
+    /// comparison of `ranked_value` is used. The sort is stable, so items
+    /// for which this returns [`Ordering::Equal`] keep their input order.
     pub base_sort: Option<BaseSortFn<T>>,
 
     /// Complete sort override.
@@ -142,8 +967,90 @@ pub struct MatchSorterOptions<T> {
     /// closure receives the filtered `Vec<RankedItem<T>>` and must return
     /// the items in the desired final order. When `None`, the default
     /// three-level sort (rank descending, key_index ascending, base_sort
-    /// tiebreaker) is used.
+    /// tiebreaker) is used. The items arrive in input order; whether equal
+    /// items keep that order is up to the closure.
     pub sorter: Option<SorterFn<T>>,
+
+    /// Identifies duplicates when merging result sets with
+    /// [`match_sorter_merge`](crate::match_sorter_merge) or
+    /// [`match_sorter_merge_presorted`](crate::match_sorter_merge_presorted).
+    ///
+    /// Two items whose keys are equal are duplicates, and only the first
+    /// one is kept. When `None`, only references to the same item in
+    /// memory are duplicates, so equal items from two different
+    /// collections are both kept. Other entry points ignore it.
+    pub dedup_key: Option<DedupKeyFn<T>>,
+
+    /// Flag that cancels a long-running search when set to `true`.
+    ///
+    /// [`match_sorter_cancellable`](crate::match_sorter_cancellable) checks
+    /// the flag before every 1000th item. Once it is set, ranking stops and
+    /// the matches found so far are returned unsorted, as
+    /// [`MatchSorterResult::Cancelled`], so a partial result is never
+    /// mistaken for a complete one.
+    /// `match_sorter_async`, with the `async` feature, checks it while
+    /// awaiting extractions. Every other entry point, including
+    /// [`match_sorter`](crate::match_sorter), ignores the flag and runs to
+    /// completion.
+    pub cancel_token: Option<Arc<AtomicBool>>,
+
+    /// Cache of candidate rankings shared between searches.
+    ///
+    /// Each candidate value is looked up by its value, the query and the
+    /// diacritics setting before it is ranked. Cached rankings are those of
+    /// [`get_match_ranking`](crate::get_match_ranking), so the cache is
+    /// only consulted while the acronym, word-boundary, fuzzy-mode,
+    /// grapheme and closeness-scorer options all have their defaults; otherwise it is
+    /// ignored. Each lookup locks the cache and hashes the value, so the
+    /// cache pays off mainly for long values.
+    pub ranking_cache: Option<Arc<Mutex<RankingCache>>>,
+
+    /// Initial capacity of the list of matches collected while ranking.
+    ///
+    /// Capped at the number of items. When `None`, the capacity is guessed
+    /// from the effective global threshold: one slot for `Equal` and
+    /// above, a tenth of the items for `StartsWith` down to `Acronym`, and
+    /// every item for `Matches` and below. Set it when the typical number
+    /// of matches is known, to avoid both over-allocating for a handful of
+    /// matches and re-allocating for many; see
+    /// [`capacity_hint`](Self::capacity_hint).
+    pub expected_match_count: Option<usize>,
+
+    /// Release the unused capacity of the match list once every item has
+    /// been ranked, before sorting.
+    ///
+    /// Lowers the memory held during sorting and by results kept around,
+    /// such as a [`PaginationCache`](crate::PaginationCache)'s, at the cost
+    /// of one reallocation.
+    pub shrink_to_fit: bool,
+
+    /// Stop ranking once enough good matches have been found.
+    ///
+    /// Items are ranked in input order. Before each item, the search checks
+    /// whether [`min_count`](EarlyStopCriteria::min_count) matches at or
+    /// above [`min_tier`](EarlyStopCriteria::min_tier) have been found; if
+    /// so, the remaining items are never ranked and the matches found so
+    /// far are sorted and returned as usual.
+    ///
+    /// This trades completeness for speed: a better match later in the
+    /// slice is missed, and so is every weaker match after the stopping
+    /// point. The result is only what a full search returns when the slice
+    /// holds no further matches. Honored by every entry point that collects
+    /// matches; [`match_sorter_incremental`](crate::match_sorter_incremental)
+    /// never refines a stopped search. See
+    /// [`stop_at_first_exact`](Self::stop_at_first_exact).
+    pub early_stop_criteria: Option<EarlyStopCriteria>,
+}
+
+/// When a search may stop early; see
+/// [`MatchSorterOptions::early_stop_criteria`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EarlyStopCriteria {
+    /// The lowest rank that counts towards [`min_count`](Self::min_count).
+    pub min_tier: Ranking,
+    /// How many matches at or above [`min_tier`](Self::min_tier) end the
+    /// search. Zero stops before the first item.
+    pub min_count: usize,
 }
 
 impl<T> Default for MatchSorterOptions<T> {
@@ -151,18 +1058,501 @@ impl<T> Default for MatchSorterOptions<T> {
     ///
     /// - `keys`: empty (no-keys mode)
     /// - `threshold`: `Ranking::Matches(1.0)` (include all fuzzy matches)
+    /// - `threshold_fn`: `None`
+    /// - `key_thresholds`: empty
+    /// - `soft_threshold`: `None`
+    /// - `query_mode`: [`QueryMode::Single`]
+    /// - `empty_query_behavior`: [`EmptyQueryBehavior::ReturnAll`]
+    /// - `exclude_terms`: empty
+    /// - `exclude_threshold`: `Ranking::Contains`
     /// - `keep_diacritics`: `false`
+    /// - `diacritics_mode`: `DiacriticsMode::StripAll`
+    /// - `normalization_form`: `NormalizationForm::Nfd`
+    /// - `unicode_case_folding`: `UnicodeCaseFolding::Simple`
+    /// - `acronym_prefix_match`: `false`
+    /// - `acronym_exact_match`: `false`
+    /// - `acronym_max_length`: `None`
+    /// - `hyphen_is_word_boundary`: `false`
+    /// - `unicode_word_boundaries`: `false`
+    /// - `word_splitter`: `None`
+    /// - `fuzzy_mode`: [`FuzzyMode::Greedy`]
+    /// - `grapheme_matching`: `false`
+    /// - `ensemble_windows`: `None`
+    /// - `prefilter`: `false`
+    /// - `strict_empty_values`: `false`
+    /// - `query_stemmer`: `None`
+    /// - `language_normalizers`: empty
+    /// - `custom_case_fold`: `None`
+    /// - `closeness_scorer`: `None`
+    /// - `item_score_modifier`: `None`
+    /// - `item_filter`: `None`
+    /// - `discard_items_slower_than`: `None`
+    /// - `on_slow_item`: `None`
     /// - `base_sort`: `None`
     /// - `sorter`: `None`
+    /// - `dedup_key`: `None`
+    /// - `cancel_token`: `None`
+    /// - `ranking_cache`: `None`
+    /// - `expected_match_count`: `None`
+    /// - `shrink_to_fit`: `false`
+    /// - `early_stop_criteria`: `None`
     fn default() -> Self {
         Self {
             keys: Vec::new(),
-            threshold: Ranking::Matches(1.0),
+            threshold: Ranking::MATCH_THRESHOLD,
+            threshold_fn: None,
+            key_thresholds: HashMap::new(),
+            soft_threshold: None,
+            query_mode: QueryMode::Single,
+            empty_query_behavior: EmptyQueryBehavior::ReturnAll,
+            exclude_terms: Vec::new(),
+            exclude_threshold: Ranking::Contains,
             keep_diacritics: false,
+            diacritics_mode: DiacriticsMode::StripAll,
+            normalization_form: NormalizationForm::Nfd,
+            unicode_case_folding: UnicodeCaseFolding::Simple,
+            acronym_prefix_match: false,
+            acronym_exact_match: false,
+            acronym_max_length: None,
+            hyphen_is_word_boundary: false,
+            unicode_word_boundaries: false,
+            word_splitter: None,
+            fuzzy_mode: FuzzyMode::Greedy,
+            grapheme_matching: false,
+            ensemble_windows: None,
+            prefilter: false,
+            strict_empty_values: false,
+            query_stemmer: None,
+            language_normalizers: HashMap::new(),
+            custom_case_fold: None,
+            closeness_scorer: None,
+            item_score_modifier: None,
+            item_filter: None,
+            discard_items_slower_than: None,
+            on_slow_item: None,
             base_sort: None,
             sorter: None,
+            dedup_key: None,
+            cancel_token: None,
+            ranking_cache: None,
+            expected_match_count: None,
+            shrink_to_fit: false,
+            early_stop_criteria: None,
+        }
+    }
+}
+
+impl<T> MatchSorterOptions<T> {
+    /// Create options that stop at the first exact match.
+    ///
+    /// Sets [`early_stop_criteria`](Self::early_stop_criteria) to stop once
+    /// one item ranks [`Ranking::Equal`] or better; all other fields take
+    /// their defaults. Matches found before it are still returned, sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{MatchSorterOptions, match_sorter};
+    ///
+    /// let items = ["applesauce", "Apple", "apple", "pineapple"];
+    /// let found = match_sorter(&items, "apple", &MatchSorterOptions::stop_at_first_exact());
+    /// // "apple" and "pineapple" are never ranked.
+    /// assert_eq!(found, vec![&"Apple", &"applesauce"]);
+    /// ```
+    pub fn stop_at_first_exact() -> Self {
+        Self {
+            early_stop_criteria: Some(EarlyStopCriteria {
+                min_tier: Ranking::Equal,
+                min_count: 1,
+            }),
+            ..Self::default()
+        }
+    }
+
+    /// Create options whose threshold depends on the query length.
+    ///
+    /// Queries shorter than `boundary` characters use `short_threshold`;
+    /// longer ones use `long_threshold`. All other fields take their
+    /// defaults. This sets [`threshold_fn`](Self::threshold_fn).
+    ///
+    /// # Arguments
+    ///
+    /// * `short_threshold` - Threshold for queries of fewer than `boundary` chars
+    /// * `long_threshold` - Threshold for all other queries
+    /// * `boundary` - Query length (in chars) at which `long_threshold` starts
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{MatchSorterOptions, Ranking, match_sorter};
+    ///
+    /// let items = ["apple", "banana", "grape"];
+    /// let opts = || {
+    ///     MatchSorterOptions::with_length_adaptive_threshold(
+    ///         Ranking::StartsWith,
+    ///         Ranking::Matches(1.0),
+    ///         3,
+    ///     )
+    /// };
+    /// // "ae" fuzzy-matches "apple" and "grape", but 2-char queries need StartsWith.
+    /// assert!(match_sorter(&items, "ae", &opts()).is_empty());
+    /// // "ape" is long enough for fuzzy matches.
+    /// assert_eq!(match_sorter(&items, "ape", &opts()), vec![&"grape", &"apple"]);
+    /// ```
+    pub fn with_length_adaptive_threshold(
+        short_threshold: Ranking,
+        long_threshold: Ranking,
+        boundary: usize,
+    ) -> Self {
+        Self {
+            threshold_fn: Some(Box::new(move |query| {
+                if query.chars().count() < boundary {
+                    short_threshold
+                } else {
+                    long_threshold
+                }
+            })),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the global threshold for `query`: the result of
+    /// [`threshold_fn`](Self::threshold_fn) when set, otherwise
+    /// [`threshold`](Self::threshold).
+    pub(crate) fn global_threshold(&self, query: &str) -> Ranking {
+        match &self.threshold_fn {
+            Some(threshold_fn) => threshold_fn(query),
+            None => self.threshold,
         }
     }
+
+    /// Set the [`query_stemmer`](Self::query_stemmer) applied to the query
+    /// before ranking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{MatchSorterOptions, match_sorter};
+    /// use matchsorter::key::Key;
+    ///
+    /// let stem = |w: &str| w.trim_end_matches("ning").to_owned();
+    /// let items = vec!["run".to_owned(), "walk".to_owned()];
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![Key::new(|s: &String| vec![s.clone()]).with_stemmer(stem)],
+    ///     ..Default::default()
+    /// }
+    /// .with_query_stemmer(stem);
+    /// assert_eq!(match_sorter(&items, "running", &opts), vec![&"run".to_owned()]);
+    /// ```
+    #[must_use]
+    pub fn with_query_stemmer<F>(mut self, stem_fn: F) -> Self
+    where
+        F: Fn(&str) -> String + 'static,
+    {
+        self.query_stemmer = Some(Box::new(stem_fn));
+        self
+    }
+
+    /// Register `normalize` for the BCP-47 language `tag` in
+    /// [`language_normalizers`](Self::language_normalizers).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{Key, MatchSorterOptions, match_sorter};
+    ///
+    /// struct Word { text: String, lang: String }
+    /// impl matchsorter::AsMatchStr for Word {
+    ///     fn as_match_str(&self) -> &str { &self.text }
+    /// }
+    ///
+    /// let words = [Word { text: "Æble".to_owned(), lang: "da".to_owned() }];
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![Key::from_fn(|w: &Word| w.text.as_str()).multi_language(|w| &w.lang)],
+    ///     ..Default::default()
+    /// }
+    /// .language_normalizer("da", |s| s.replace('Æ', "Ae").replace('æ', "ae"));
+    /// assert_eq!(match_sorter(&words, "aeble", &opts).len(), 1);
+    /// ```
+    #[must_use]
+    pub fn language_normalizer<F>(mut self, tag: impl Into<String>, normalize: F) -> Self
+    where
+        F: Fn(&str) -> String + 'static,
+    {
+        self.language_normalizers
+            .insert(tag.into(), Box::new(normalize));
+        self
+    }
+
+    /// Break ties with [`unicode_base_sort`](crate::unicode_base_sort),
+    /// which compares `ranked_value`s in Unicode NFC, replacing any
+    /// [`base_sort`](Self::base_sort).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{MatchSorterOptions, match_sorter};
+    ///
+    /// // NFC and NFD spellings of the same name.
+    /// let items = ["r\u{e9}sum\u{e9}", "re\u{301}sume\u{301}"];
+    /// // By bytes, the NFD spelling sorts first...
+    /// let bytes = match_sorter(&items, "resume", &MatchSorterOptions::default());
+    /// assert_eq!(bytes, vec![&items[1], &items[0]]);
+    /// // ...but canonically the two tie and keep their input order.
+    /// let opts = MatchSorterOptions::default().unicode_base_sort();
+    /// assert_eq!(match_sorter(&items, "resume", &opts), vec![&items[0], &items[1]]);
+    /// ```
+    #[must_use]
+    pub fn unicode_base_sort(mut self) -> Self
+    where
+        T: 'static,
+    {
+        self.base_sort = Some(Box::new(crate::sort::unicode_base_sort));
+        self
+    }
+
+    /// Set [`expected_match_count`](Self::expected_match_count), the
+    /// initial capacity of the match list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{MatchSorterOptions, match_sorter};
+    ///
+    /// let items = ["apple", "banana", "cherry"];
+    /// let opts = MatchSorterOptions::default().capacity_hint(1);
+    /// assert_eq!(match_sorter(&items, "cherry", &opts), vec![&"cherry"]);
+    /// ```
+    #[must_use]
+    pub fn capacity_hint(mut self, expected_matches: usize) -> Self {
+        self.expected_match_count = Some(expected_matches);
+        self
+    }
+
+    /// Set the threshold of the key at `key_index` in
+    /// [`key_thresholds`](Self::key_thresholds), overriding the key's own
+    /// threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_index` - Position of the key in [`keys`](Self::keys)
+    /// * `threshold` - Minimum rank a match on that key needs
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{MatchSorterOptions, Ranking, match_sorter};
+    /// use matchsorter::key::Key;
+    ///
+    /// let items = vec!["apple".to_owned(), "pineapple".to_owned()];
+    /// let opts = MatchSorterOptions {
+    ///     keys: vec![Key::new(|s: &String| vec![s.clone()])],
+    ///     ..Default::default()
+    /// }
+    /// .key_threshold(0, Ranking::StartsWith);
+    /// assert_eq!(match_sorter(&items, "apple", &opts), vec![&"apple".to_owned()]);
+    /// ```
+    #[must_use]
+    pub fn key_threshold(mut self, key_index: usize, threshold: Ranking) -> Self {
+        self.key_thresholds.insert(key_index, threshold);
+        self
+    }
+
+    /// Merge `overrides` into these options, returning the combined set.
+    ///
+    /// Every `Some` field of `overrides` replaces the corresponding field of
+    /// `self`; every `None` field keeps the value from `self`. Keys follow
+    /// [`PartialMatchSorterOptions::keys_mode`]: they either replace the base
+    /// keys or are appended after them.
+    ///
+    /// `MatchSorterOptions` cannot be cloned (it holds boxed closures), so
+    /// this is the way to derive per-call variants from shared defaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `overrides` - Fields to override; `None` inherits from `self`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{MatchSorterOptions, PartialMatchSorterOptions, Ranking};
+    ///
+    /// let base = MatchSorterOptions::<String> {
+    ///     keep_diacritics: true,
+    ///     ..Default::default()
+    /// };
+    /// let opts = base.extend(PartialMatchSorterOptions {
+    ///     threshold: Some(Ranking::Contains),
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(opts.threshold, Ranking::Contains);
+    /// assert!(opts.keep_diacritics);
+    /// ```
+    #[must_use]
+    pub fn extend(mut self, overrides: PartialMatchSorterOptions<T>) -> Self {
+        if let Some(keys) = overrides.keys {
+            match overrides.keys_mode {
+                KeysExtendMode::Replace => self.keys = keys,
+                KeysExtendMode::Append => self.keys.extend(keys),
+            }
+        }
+        Self {
+            keys: self.keys,
+            threshold: overrides.threshold.unwrap_or(self.threshold),
+            threshold_fn: overrides.threshold_fn.unwrap_or(self.threshold_fn),
+            key_thresholds: overrides.key_thresholds.unwrap_or(self.key_thresholds),
+            soft_threshold: overrides.soft_threshold.unwrap_or(self.soft_threshold),
+            query_mode: overrides.query_mode.unwrap_or(self.query_mode),
+            empty_query_behavior: overrides
+                .empty_query_behavior
+                .unwrap_or(self.empty_query_behavior),
+            exclude_terms: overrides.exclude_terms.unwrap_or(self.exclude_terms),
+            exclude_threshold: overrides
+                .exclude_threshold
+                .unwrap_or(self.exclude_threshold),
+            keep_diacritics: overrides.keep_diacritics.unwrap_or(self.keep_diacritics),
+            diacritics_mode: overrides.diacritics_mode.unwrap_or(self.diacritics_mode),
+            normalization_form: overrides
+                .normalization_form
+                .unwrap_or(self.normalization_form),
+            unicode_case_folding: overrides
+                .unicode_case_folding
+                .unwrap_or(self.unicode_case_folding),
+            acronym_prefix_match: overrides
+                .acronym_prefix_match
+                .unwrap_or(self.acronym_prefix_match),
+            acronym_exact_match: overrides
+                .acronym_exact_match
+                .unwrap_or(self.acronym_exact_match),
+            acronym_max_length: overrides
+                .acronym_max_length
+                .unwrap_or(self.acronym_max_length),
+            hyphen_is_word_boundary: overrides
+                .hyphen_is_word_boundary
+                .unwrap_or(self.hyphen_is_word_boundary),
+            unicode_word_boundaries: overrides
+                .unicode_word_boundaries
+                .unwrap_or(self.unicode_word_boundaries),
+            word_splitter: overrides.word_splitter.unwrap_or(self.word_splitter),
+            fuzzy_mode: overrides.fuzzy_mode.unwrap_or(self.fuzzy_mode),
+            grapheme_matching: overrides
+                .grapheme_matching
+                .unwrap_or(self.grapheme_matching),
+            ensemble_windows: overrides.ensemble_windows.unwrap_or(self.ensemble_windows),
+            prefilter: overrides.prefilter.unwrap_or(self.prefilter),
+            strict_empty_values: overrides
+                .strict_empty_values
+                .unwrap_or(self.strict_empty_values),
+            query_stemmer: overrides.query_stemmer.unwrap_or(self.query_stemmer),
+            language_normalizers: overrides
+                .language_normalizers
+                .unwrap_or(self.language_normalizers),
+            custom_case_fold: overrides.custom_case_fold.unwrap_or(self.custom_case_fold),
+            closeness_scorer: overrides.closeness_scorer.unwrap_or(self.closeness_scorer),
+            item_score_modifier: overrides
+                .item_score_modifier
+                .unwrap_or(self.item_score_modifier),
+            item_filter: overrides.item_filter.unwrap_or(self.item_filter),
+            discard_items_slower_than: overrides
+                .discard_items_slower_than
+                .unwrap_or(self.discard_items_slower_than),
+            on_slow_item: overrides.on_slow_item.unwrap_or(self.on_slow_item),
+            base_sort: overrides.base_sort.unwrap_or(self.base_sort),
+            sorter: overrides.sorter.unwrap_or(self.sorter),
+            dedup_key: overrides.dedup_key.unwrap_or(self.dedup_key),
+            cancel_token: overrides.cancel_token.unwrap_or(self.cancel_token),
+            ranking_cache: overrides.ranking_cache.unwrap_or(self.ranking_cache),
+            expected_match_count: overrides
+                .expected_match_count
+                .unwrap_or(self.expected_match_count),
+            shrink_to_fit: overrides.shrink_to_fit.unwrap_or(self.shrink_to_fit),
+            early_stop_criteria: overrides
+                .early_stop_criteria
+                .unwrap_or(self.early_stop_criteria),
+        }
+    }
+
+    /// The lowest rank an item needs when `threshold` is the hard cutoff:
+    /// the soft threshold if it is lower, otherwise `threshold`.
+    pub(crate) fn soft_floor(&self, threshold: Ranking) -> Ranking {
+        match self.soft_threshold {
+            Some(soft) if soft < threshold => soft,
+            _ => threshold,
+        }
+    }
+
+    /// The diacritics treatment in effect: `keep_diacritics` overrides
+    /// `diacritics_mode`.
+    pub(crate) fn diacritics(&self) -> DiacriticsMode {
+        if self.keep_diacritics {
+            DiacriticsMode::Preserve
+        } else {
+            self.diacritics_mode
+        }
+    }
+
+    /// Collect the per-candidate ranking settings into a [`RankingConfig`].
+    pub(crate) fn ranking_config(&self) -> RankingConfig<'_> {
+        RankingConfig {
+            diacritics: self.diacritics(),
+            normalization: self.normalization_form,
+            case_folding: self.unicode_case_folding,
+            key_thresholds: Some(&self.key_thresholds).filter(|map| !map.is_empty()),
+            acronym_prefix_match: self.acronym_prefix_match,
+            acronym_exact_match: self.acronym_exact_match,
+            acronym_max_length: self.acronym_max_length,
+            hyphen_is_word_boundary: self.hyphen_is_word_boundary,
+            unicode_word_boundaries: self.unicode_word_boundaries,
+            word_splitter: self.word_splitter.as_deref(),
+            fuzzy_mode: self.fuzzy_mode,
+            grapheme_matching: self.grapheme_matching,
+            ensemble_windows: self.ensemble_windows,
+            prefilter: self.prefilter,
+            strict_empty_values: self.strict_empty_values,
+            pre_normalized: false,
+            match_floor: None,
+            language_normalizers: Some(&self.language_normalizers).filter(|map| !map.is_empty()),
+            case_fold: self.custom_case_fold.as_deref(),
+            language: None,
+            closeness_scorer: self.closeness_scorer.as_deref(),
+            ranking_cache: self.ranking_cache.as_deref().filter(|_| {
+                self.normalization_form == NormalizationForm::Nfd
+                    && self.unicode_case_folding == UnicodeCaseFolding::Simple
+                    && !self.acronym_prefix_match
+                    && !self.acronym_exact_match
+                    && self.acronym_max_length.is_none()
+                    && !self.hyphen_is_word_boundary
+                    && !self.unicode_word_boundaries
+                    && self.fuzzy_mode == FuzzyMode::Greedy
+                    && !self.grapheme_matching
+                    && self.ensemble_windows.is_none()
+                    && self.closeness_scorer.is_none()
+                    && self.word_splitter.is_none()
+            }),
+        }
+    }
+}
+
+/// Debug stand-in for a normalizer table: its tags, sorted.
+fn normalizer_tags(table: &HashMap<String, LanguageNormalizerFn>) -> Vec<&str> {
+    let mut tags: Vec<&str> = table.keys().map(String::as_str).collect();
+    tags.sort_unstable();
+    tags
+}
+
+/// Debug stand-in for an optional boxed closure: `"Some(<fn>)"` or `"None"`.
+fn fn_placeholder<F: ?Sized>(f: &Option<Box<F>>) -> &'static dyn fmt::Debug {
+    if f.is_some() { &"Some(<fn>)" } else { &"None" }
+}
+
+/// Debug placeholder for an overriding closure field of
+/// [`PartialMatchSorterOptions`].
+fn partial_fn_placeholder<F: ?Sized>(f: &Option<Option<Box<F>>>) -> &'static dyn fmt::Debug {
+    match f {
+        Some(Some(_)) => &"Some(Some(<fn>))",
+        Some(None) => &"Some(None)",
+        None => &"None",
+    }
 }
 
 // Manual `Debug` implementation because `Box<dyn Fn>` does not implement
@@ -172,23 +1562,416 @@ impl<T> fmt::Debug for MatchSorterOptions<T> {
         f.debug_struct("MatchSorterOptions")
             .field("keys", &format_args!("[{} key(s)]", self.keys.len()))
             .field("threshold", &self.threshold)
+            .field("threshold_fn", fn_placeholder(&self.threshold_fn))
+            .field("key_thresholds", &self.key_thresholds)
+            .field("soft_threshold", &self.soft_threshold)
+            .field("query_mode", &self.query_mode)
+            .field("empty_query_behavior", &self.empty_query_behavior)
+            .field("exclude_terms", &self.exclude_terms)
+            .field("exclude_threshold", &self.exclude_threshold)
             .field("keep_diacritics", &self.keep_diacritics)
+            .field("diacritics_mode", &self.diacritics_mode)
+            .field("normalization_form", &self.normalization_form)
+            .field("unicode_case_folding", &self.unicode_case_folding)
+            .field("acronym_prefix_match", &self.acronym_prefix_match)
+            .field("acronym_exact_match", &self.acronym_exact_match)
+            .field("acronym_max_length", &self.acronym_max_length)
+            .field("hyphen_is_word_boundary", &self.hyphen_is_word_boundary)
+            .field("unicode_word_boundaries", &self.unicode_word_boundaries)
+            .field("word_splitter", fn_placeholder(&self.word_splitter))
+            .field("fuzzy_mode", &self.fuzzy_mode)
+            .field("grapheme_matching", &self.grapheme_matching)
+            .field("ensemble_windows", &self.ensemble_windows)
+            .field("prefilter", &self.prefilter)
+            .field("strict_empty_values", &self.strict_empty_values)
+            .field("query_stemmer", fn_placeholder(&self.query_stemmer))
             .field(
-                "base_sort",
-                if self.base_sort.is_some() {
-                    &"Some(<fn>)" as &dyn fmt::Debug
-                } else {
-                    &"None" as &dyn fmt::Debug
-                },
+                "language_normalizers",
+                &normalizer_tags(&self.language_normalizers),
             )
+            .field("custom_case_fold", fn_placeholder(&self.custom_case_fold))
+            .field("closeness_scorer", fn_placeholder(&self.closeness_scorer))
             .field(
-                "sorter",
-                if self.sorter.is_some() {
-                    &"Some(<fn>)" as &dyn fmt::Debug
-                } else {
-                    &"None" as &dyn fmt::Debug
-                },
+                "item_score_modifier",
+                fn_placeholder(&self.item_score_modifier),
             )
+            .field("item_filter", fn_placeholder(&self.item_filter))
+            .field("discard_items_slower_than", &self.discard_items_slower_than)
+            .field("on_slow_item", fn_placeholder(&self.on_slow_item))
+            .field("base_sort", fn_placeholder(&self.base_sort))
+            .field("sorter", fn_placeholder(&self.sorter))
+            .field("dedup_key", fn_placeholder(&self.dedup_key))
+            .field("cancel_token", &self.cancel_token)
+            .field("ranking_cache", &self.ranking_cache)
+            .field("expected_match_count", &self.expected_match_count)
+            .field("shrink_to_fit", &self.shrink_to_fit)
+            .field("early_stop_criteria", &self.early_stop_criteria)
+            .finish()
+    }
+}
+
+/// How the search query is interpreted. See
+/// [`MatchSorterOptions::query_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueryMode {
+    /// The whole query is matched as a single string.
+    #[default]
+    Single,
+    /// The query is split on whitespace and each token is an alternative:
+    /// an item matches if any token matches, and its rank is the best rank
+    /// across all tokens. A query without tokens (empty or all whitespace)
+    /// behaves like the empty query.
+    AnyToken,
+}
+
+/// What an empty query returns. See
+/// [`MatchSorterOptions::empty_query_behavior`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyQueryBehavior {
+    /// Every item matches and is sorted as for any other query.
+    #[default]
+    ReturnAll,
+    /// No item matches.
+    ReturnEmpty,
+    /// Every item passing the [`item_filter`](MatchSorterOptions::item_filter)
+    /// is returned in input order, without being ranked.
+    ReturnAllUnsorted,
+}
+
+/// How [`MatchSorterOptions::extend`] combines base keys with override keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeysExtendMode {
+    /// Override keys replace the base keys entirely.
+    #[default]
+    Replace,
+    /// Override keys are appended after the base keys, so base keys keep
+    /// their lower `key_index` values.
+    Append,
+}
+
+/// A set of optional overrides for [`MatchSorterOptions`].
+///
+/// Mirrors `MatchSorterOptions` with every field optional: `Some` overrides
+/// the base value in [`MatchSorterOptions::extend`], `None` inherits it.
+/// Fields that are already an `Option` there, closures included, become
+/// `Option<Option<_>>`, so `Some(None)` clears a value the base has set.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{KeysExtendMode, PartialMatchSorterOptions, Ranking};
+/// use matchsorter::key::Key;
+///
+/// let overrides = PartialMatchSorterOptions::<String> {
+///     threshold: Some(Ranking::StartsWith),
+///     keys: Some(vec![Key::new(|s: &String| vec![s.clone()])]),
+///     keys_mode: KeysExtendMode::Append,
+///     ..Default::default()
+/// };
+/// assert!(overrides.keep_diacritics.is_none());
+/// ```
+pub struct PartialMatchSorterOptions<T> {
+    /// Keys to replace or append to the base keys, per `keys_mode`.
+    pub keys: Option<Vec<Key<T>>>,
+    /// How `keys` combines with the base keys. Defaults to
+    /// [`KeysExtendMode::Replace`].
+    pub keys_mode: KeysExtendMode,
+    /// Overrides [`MatchSorterOptions::threshold`].
+    pub threshold: Option<Ranking>,
+    /// Overrides [`MatchSorterOptions::threshold_fn`].
+    pub threshold_fn: Option<Option<ThresholdFn>>,
+    /// Overrides [`MatchSorterOptions::key_thresholds`].
+    pub key_thresholds: Option<HashMap<usize, Ranking>>,
+    /// Overrides [`MatchSorterOptions::soft_threshold`].
+    pub soft_threshold: Option<Option<Ranking>>,
+    /// Overrides [`MatchSorterOptions::query_mode`].
+    pub query_mode: Option<QueryMode>,
+    /// Overrides [`MatchSorterOptions::empty_query_behavior`].
+    pub empty_query_behavior: Option<EmptyQueryBehavior>,
+    /// Overrides [`MatchSorterOptions::exclude_terms`] (replacing the list).
+    pub exclude_terms: Option<Vec<String>>,
+    /// Overrides [`MatchSorterOptions::exclude_threshold`].
+    pub exclude_threshold: Option<Ranking>,
+    /// Overrides [`MatchSorterOptions::keep_diacritics`].
+    pub keep_diacritics: Option<bool>,
+    /// Overrides [`MatchSorterOptions::diacritics_mode`].
+    pub diacritics_mode: Option<DiacriticsMode>,
+    /// Overrides [`MatchSorterOptions::normalization_form`].
+    pub normalization_form: Option<NormalizationForm>,
+    /// Overrides [`MatchSorterOptions::unicode_case_folding`].
+    pub unicode_case_folding: Option<UnicodeCaseFolding>,
+    /// Overrides [`MatchSorterOptions::acronym_prefix_match`].
+    pub acronym_prefix_match: Option<bool>,
+    /// Overrides [`MatchSorterOptions::acronym_exact_match`].
+    pub acronym_exact_match: Option<bool>,
+    /// Overrides [`MatchSorterOptions::acronym_max_length`];
+    /// `Some(None)` lifts the base limit.
+    pub acronym_max_length: Option<Option<usize>>,
+    /// Overrides [`MatchSorterOptions::hyphen_is_word_boundary`].
+    pub hyphen_is_word_boundary: Option<bool>,
+    /// Overrides [`MatchSorterOptions::unicode_word_boundaries`].
+    pub unicode_word_boundaries: Option<bool>,
+    /// Overrides [`MatchSorterOptions::word_splitter`].
+    pub word_splitter: Option<Option<WordSplitterFn>>,
+    /// Overrides [`MatchSorterOptions::fuzzy_mode`].
+    pub fuzzy_mode: Option<FuzzyMode>,
+    /// Overrides [`MatchSorterOptions::grapheme_matching`].
+    pub grapheme_matching: Option<bool>,
+    /// Overrides [`MatchSorterOptions::ensemble_windows`].
+    pub ensemble_windows: Option<Option<usize>>,
+    /// Overrides [`MatchSorterOptions::prefilter`].
+    pub prefilter: Option<bool>,
+    /// Overrides [`MatchSorterOptions::strict_empty_values`].
+    pub strict_empty_values: Option<bool>,
+    /// Overrides [`MatchSorterOptions::query_stemmer`].
+    pub query_stemmer: Option<Option<StemmerFn>>,
+    /// Overrides [`MatchSorterOptions::language_normalizers`].
+    pub language_normalizers: Option<HashMap<String, LanguageNormalizerFn>>,
+    /// Overrides [`MatchSorterOptions::custom_case_fold`].
+    pub custom_case_fold: Option<Option<CaseFoldFn>>,
+    /// Overrides [`MatchSorterOptions::closeness_scorer`].
+    pub closeness_scorer: Option<Option<ClosenessScorerFn>>,
+    /// Overrides [`MatchSorterOptions::item_score_modifier`].
+    pub item_score_modifier: Option<Option<ScoreModifierFn<T>>>,
+    /// Overrides [`MatchSorterOptions::item_filter`].
+    pub item_filter: Option<Option<ItemFilterFn<T>>>,
+    /// Overrides [`MatchSorterOptions::discard_items_slower_than`].
+    pub discard_items_slower_than: Option<Option<Duration>>,
+    /// Overrides [`MatchSorterOptions::on_slow_item`].
+    pub on_slow_item: Option<Option<SlowItemFn<T>>>,
+    /// Overrides [`MatchSorterOptions::base_sort`].
+    pub base_sort: Option<Option<BaseSortFn<T>>>,
+    /// Overrides [`MatchSorterOptions::sorter`].
+    pub sorter: Option<Option<SorterFn<T>>>,
+    /// Overrides [`MatchSorterOptions::dedup_key`].
+    pub dedup_key: Option<Option<DedupKeyFn<T>>>,
+    /// Overrides [`MatchSorterOptions::cancel_token`].
+    pub cancel_token: Option<Option<Arc<AtomicBool>>>,
+    /// Overrides [`MatchSorterOptions::ranking_cache`].
+    pub ranking_cache: Option<Option<Arc<Mutex<RankingCache>>>>,
+    /// Overrides [`MatchSorterOptions::expected_match_count`].
+    pub expected_match_count: Option<Option<usize>>,
+    /// Overrides [`MatchSorterOptions::shrink_to_fit`].
+    pub shrink_to_fit: Option<bool>,
+    /// Overrides [`MatchSorterOptions::early_stop_criteria`].
+    pub early_stop_criteria: Option<Option<EarlyStopCriteria>>,
+}
+
+impl<T> Default for PartialMatchSorterOptions<T> {
+    /// Returns overrides that change nothing: every field `None`, keys in
+    /// [`KeysExtendMode::Replace`] mode.
+    fn default() -> Self {
+        Self {
+            keys: None,
+            keys_mode: KeysExtendMode::Replace,
+            threshold: None,
+            threshold_fn: None,
+            key_thresholds: None,
+            soft_threshold: None,
+            query_mode: None,
+            empty_query_behavior: None,
+            exclude_terms: None,
+            exclude_threshold: None,
+            keep_diacritics: None,
+            diacritics_mode: None,
+            normalization_form: None,
+            unicode_case_folding: None,
+            acronym_prefix_match: None,
+            acronym_exact_match: None,
+            acronym_max_length: None,
+            hyphen_is_word_boundary: None,
+            unicode_word_boundaries: None,
+            word_splitter: None,
+            fuzzy_mode: None,
+            grapheme_matching: None,
+            ensemble_windows: None,
+            prefilter: None,
+            strict_empty_values: None,
+            query_stemmer: None,
+            language_normalizers: None,
+            custom_case_fold: None,
+            closeness_scorer: None,
+            item_score_modifier: None,
+            item_filter: None,
+            discard_items_slower_than: None,
+            on_slow_item: None,
+            base_sort: None,
+            sorter: None,
+            dedup_key: None,
+            cancel_token: None,
+            ranking_cache: None,
+            expected_match_count: None,
+            shrink_to_fit: None,
+            early_stop_criteria: None,
+        }
+    }
+}
+
+impl<T> From<MatchSorterOptions<T>> for PartialMatchSorterOptions<T> {
+    /// Wrap every field of `options` in `Some`, so that extending any base
+    /// with the result yields `options`.
+    fn from(options: MatchSorterOptions<T>) -> Self {
+        Self {
+            keys: Some(options.keys),
+            keys_mode: KeysExtendMode::Replace,
+            threshold: Some(options.threshold),
+            threshold_fn: Some(options.threshold_fn),
+            key_thresholds: Some(options.key_thresholds),
+            soft_threshold: Some(options.soft_threshold),
+            query_mode: Some(options.query_mode),
+            empty_query_behavior: Some(options.empty_query_behavior),
+            exclude_terms: Some(options.exclude_terms),
+            exclude_threshold: Some(options.exclude_threshold),
+            keep_diacritics: Some(options.keep_diacritics),
+            diacritics_mode: Some(options.diacritics_mode),
+            normalization_form: Some(options.normalization_form),
+            unicode_case_folding: Some(options.unicode_case_folding),
+            acronym_prefix_match: Some(options.acronym_prefix_match),
+            acronym_exact_match: Some(options.acronym_exact_match),
+            acronym_max_length: Some(options.acronym_max_length),
+            hyphen_is_word_boundary: Some(options.hyphen_is_word_boundary),
+            unicode_word_boundaries: Some(options.unicode_word_boundaries),
+            word_splitter: Some(options.word_splitter),
+            fuzzy_mode: Some(options.fuzzy_mode),
+            grapheme_matching: Some(options.grapheme_matching),
+            ensemble_windows: Some(options.ensemble_windows),
+            prefilter: Some(options.prefilter),
+            strict_empty_values: Some(options.strict_empty_values),
+            query_stemmer: Some(options.query_stemmer),
+            language_normalizers: Some(options.language_normalizers),
+            custom_case_fold: Some(options.custom_case_fold),
+            closeness_scorer: Some(options.closeness_scorer),
+            item_score_modifier: Some(options.item_score_modifier),
+            item_filter: Some(options.item_filter),
+            discard_items_slower_than: Some(options.discard_items_slower_than),
+            on_slow_item: Some(options.on_slow_item),
+            base_sort: Some(options.base_sort),
+            sorter: Some(options.sorter),
+            dedup_key: Some(options.dedup_key),
+            cancel_token: Some(options.cancel_token),
+            ranking_cache: Some(options.ranking_cache),
+            expected_match_count: Some(options.expected_match_count),
+            shrink_to_fit: Some(options.shrink_to_fit),
+            early_stop_criteria: Some(options.early_stop_criteria),
+        }
+    }
+}
+
+impl<T> fmt::Debug for PartialMatchSorterOptions<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartialMatchSorterOptions")
+            .field(
+                "keys",
+                &self
+                    .keys
+                    .as_ref()
+                    .map(|keys| format!("[{} key(s)]", keys.len())),
+            )
+            .field("keys_mode", &self.keys_mode)
+            .field("threshold", &self.threshold)
+            .field("threshold_fn", partial_fn_placeholder(&self.threshold_fn))
+            .field("key_thresholds", &self.key_thresholds)
+            .field("soft_threshold", &self.soft_threshold)
+            .field("query_mode", &self.query_mode)
+            .field("empty_query_behavior", &self.empty_query_behavior)
+            .field("exclude_terms", &self.exclude_terms)
+            .field("exclude_threshold", &self.exclude_threshold)
+            .field("keep_diacritics", &self.keep_diacritics)
+            .field("diacritics_mode", &self.diacritics_mode)
+            .field("normalization_form", &self.normalization_form)
+            .field("unicode_case_folding", &self.unicode_case_folding)
+            .field("acronym_prefix_match", &self.acronym_prefix_match)
+            .field("acronym_exact_match", &self.acronym_exact_match)
+            .field("acronym_max_length", &self.acronym_max_length)
+            .field("hyphen_is_word_boundary", &self.hyphen_is_word_boundary)
+            .field("unicode_word_boundaries", &self.unicode_word_boundaries)
+            .field("word_splitter", partial_fn_placeholder(&self.word_splitter))
+            .field("fuzzy_mode", &self.fuzzy_mode)
+            .field("grapheme_matching", &self.grapheme_matching)
+            .field("ensemble_windows", &self.ensemble_windows)
+            .field("prefilter", &self.prefilter)
+            .field("strict_empty_values", &self.strict_empty_values)
+            .field("query_stemmer", partial_fn_placeholder(&self.query_stemmer))
+            .field(
+                "language_normalizers",
+                &self.language_normalizers.as_ref().map(normalizer_tags),
+            )
+            .field(
+                "custom_case_fold",
+                partial_fn_placeholder(&self.custom_case_fold),
+            )
+            .field(
+                "closeness_scorer",
+                partial_fn_placeholder(&self.closeness_scorer),
+            )
+            .field(
+                "item_score_modifier",
+                partial_fn_placeholder(&self.item_score_modifier),
+            )
+            .field("item_filter", partial_fn_placeholder(&self.item_filter))
+            .field("discard_items_slower_than", &self.discard_items_slower_than)
+            .field("on_slow_item", partial_fn_placeholder(&self.on_slow_item))
+            .field("base_sort", partial_fn_placeholder(&self.base_sort))
+            .field("sorter", partial_fn_placeholder(&self.sorter))
+            .field("dedup_key", partial_fn_placeholder(&self.dedup_key))
+            .field("cancel_token", &self.cancel_token)
+            .field("ranking_cache", &self.ranking_cache)
+            .field("expected_match_count", &self.expected_match_count)
+            .field("shrink_to_fit", &self.shrink_to_fit)
+            .field("early_stop_criteria", &self.early_stop_criteria)
+            .finish()
+    }
+}
+
+/// Options for [`match_sorter_async`](crate::match_sorter_async).
+///
+/// Holds the [`AsyncKey`]s whose values are awaited before ranking, and a
+/// [`MatchSorterOptions`] with every other setting (threshold, sorting,
+/// exclude terms, cancel token, ...). Any synchronous keys in
+/// `options.keys` are ranked after the async keys, so their values come
+/// later in the flattened key order. With no keys at all, items are ranked
+/// through [`AsMatchStr`](crate::AsMatchStr) as in [`match_sorter`](crate::match_sorter).
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{AsyncMatchSorterOptions, MatchSorterOptions, Ranking};
+///
+/// let opts = AsyncMatchSorterOptions::<String> {
+///     options: MatchSorterOptions {
+///         threshold: Ranking::Contains,
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// assert!(opts.keys.is_empty());
+/// ```
+#[cfg(feature = "async")]
+pub struct AsyncMatchSorterOptions<T> {
+    /// Keys whose values are produced by a future. Every item's keys are
+    /// extracted concurrently before ranking starts.
+    pub keys: Vec<Box<dyn AsyncKey<T>>>,
+
+    /// All other settings, including any synchronous keys.
+    pub options: MatchSorterOptions<T>,
+}
+
+#[cfg(feature = "async")]
+impl<T> Default for AsyncMatchSorterOptions<T> {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            options: MatchSorterOptions::default(),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> fmt::Debug for AsyncMatchSorterOptions<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncMatchSorterOptions")
+            .field("keys", &format_args!("[{} key(s)]", self.keys.len()))
+            .field("options", &self.options)
             .finish()
     }
 }
@@ -196,6 +1979,7 @@ impl<T> fmt::Debug for MatchSorterOptions<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ranking::DiacriticsLanguage;
 
     #[test]
     fn default_keep_diacritics_is_false() {
@@ -209,12 +1993,71 @@ mod tests {
         assert_eq!(opts.threshold, Ranking::Matches(1.0));
     }
 
+    #[test]
+    fn default_threshold_fn_is_none() {
+        let opts = MatchSorterOptions::<String>::default();
+        assert!(opts.threshold_fn.is_none());
+        assert_eq!(opts.global_threshold("a"), Ranking::Matches(1.0));
+    }
+
+    #[test]
+    fn threshold_fn_overrides_threshold() {
+        let opts = MatchSorterOptions::<String> {
+            threshold: Ranking::Acronym,
+            threshold_fn: Some(Box::new(|q| {
+                if q.is_empty() {
+                    Ranking::Equal
+                } else {
+                    Ranking::Contains
+                }
+            })),
+            ..Default::default()
+        };
+        assert_eq!(opts.global_threshold(""), Ranking::Equal);
+        assert_eq!(opts.global_threshold("abc"), Ranking::Contains);
+    }
+
+    #[test]
+    fn length_adaptive_threshold_switches_at_boundary() {
+        let opts = MatchSorterOptions::<String>::with_length_adaptive_threshold(
+            Ranking::StartsWith,
+            Ranking::Acronym,
+            3,
+        );
+        assert_eq!(opts.global_threshold("ab"), Ranking::StartsWith);
+        assert_eq!(opts.global_threshold("abc"), Ranking::Acronym);
+        // Length is measured in chars, not bytes.
+        assert_eq!(
+            opts.global_threshold("\u{00e9}\u{00e9}"),
+            Ranking::StartsWith
+        );
+    }
+
     #[test]
     fn default_keys_is_empty() {
         let opts = MatchSorterOptions::<String>::default();
         assert!(opts.keys.is_empty());
     }
 
+    #[test]
+    fn default_acronym_modes_are_off() {
+        let opts = MatchSorterOptions::<String>::default();
+        assert!(!opts.acronym_prefix_match);
+        assert!(!opts.acronym_exact_match);
+    }
+
+    #[test]
+    fn default_hyphen_is_not_word_boundary() {
+        let opts = MatchSorterOptions::<String>::default();
+        assert!(!opts.hyphen_is_word_boundary);
+    }
+
+    #[test]
+    fn default_unicode_word_boundaries_is_off() {
+        let opts = MatchSorterOptions::<String>::default();
+        assert!(!opts.unicode_word_boundaries);
+    }
+
     #[test]
     fn default_base_sort_is_none() {
         let opts = MatchSorterOptions::<String>::default();
@@ -256,6 +2099,10 @@ mod tests {
             ranked_value: Cow::Borrowed("hello"),
             key_index: 0,
             key_threshold: None,
+            is_soft_match: false,
+            matched_portion: None,
+            matched_key_alias: None,
+            tie_priority: None,
         };
         assert_eq!(ranked.rank, Ranking::CaseSensitiveEqual);
         assert_eq!(ranked.ranked_value, "hello");
@@ -265,6 +2112,59 @@ mod tests {
         assert_eq!(*ranked.item, "hello");
     }
 
+    fn ranked_with(rank: Ranking) -> RankedItem<'static, &'static str> {
+        RankedItem {
+            item: &"item",
+            index: 0,
+            rank,
+            ranked_value: Cow::Borrowed("item"),
+            key_index: 0,
+            key_threshold: None,
+            is_soft_match: false,
+            matched_portion: None,
+            matched_key_alias: None,
+            tie_priority: None,
+        }
+    }
+
+    #[test]
+    fn ranked_item_rank_label_and_tier() {
+        let cases = [
+            (Ranking::CaseSensitiveEqual, "exact", 7),
+            (Ranking::Equal, "equal", 6),
+            (Ranking::StartsWith, "starts_with", 5),
+            (Ranking::WordStartsWith, "word_starts_with", 4),
+            (Ranking::Contains, "contains", 3),
+            (Ranking::Acronym, "acronym", 2),
+            (Ranking::Matches(1.5), "fuzzy", 1),
+            (Ranking::NoMatch, "no_match", 0),
+        ];
+        for (rank, label, tier) in cases {
+            let ranked = ranked_with(rank);
+            assert_eq!(ranked.rank_label(), label);
+            assert_eq!(ranked.rank_label(), ranking_tier_label(rank));
+            assert_eq!(ranked.rank_tier(), tier);
+        }
+    }
+
+    #[test]
+    fn ranked_item_is_exact_match() {
+        assert!(ranked_with(Ranking::CaseSensitiveEqual).is_exact_match());
+        assert!(!ranked_with(Ranking::Equal).is_exact_match());
+        assert!(!ranked_with(Ranking::Matches(2.0)).is_exact_match());
+    }
+
+    #[test]
+    fn ranked_item_key_alias() {
+        assert_eq!(ranked_with(Ranking::Equal).key_alias(), None);
+        let ranked = RankedItem {
+            matched_key_alias: Some("email".to_owned()),
+            ..ranked_with(Ranking::Equal)
+        };
+        assert_eq!(ranked.key_alias(), Some("email"));
+        assert!(format!("{ranked:?}").contains(r#"matched_key_alias: Some("email")"#));
+    }
+
     #[test]
     fn ranked_item_with_threshold() {
         let item = 42u32;
@@ -275,11 +2175,96 @@ mod tests {
             ranked_value: Cow::Borrowed("forty-two"),
             key_index: 1,
             key_threshold: Some(Ranking::StartsWith),
+            is_soft_match: false,
+            matched_portion: None,
+            matched_key_alias: None,
+            tie_priority: None,
         };
         assert_eq!(ranked.key_threshold, Some(Ranking::StartsWith));
         assert_eq!(*ranked.item, 42);
     }
 
+    fn ranked_pair(item: &String) -> [RankedItem<'_, String>; 2] {
+        [
+            RankedItem {
+                item,
+                index: 2,
+                rank: Ranking::Matches(1.375),
+                ranked_value: Cow::Borrowed(item.as_str()),
+                key_index: 0,
+                key_threshold: None,
+                is_soft_match: true,
+                matched_portion: None,
+                matched_key_alias: None,
+                tie_priority: None,
+            },
+            RankedItem {
+                item,
+                index: 5,
+                rank: Ranking::WordStartsWith,
+                ranked_value: Cow::Owned("Jane Doe".to_owned()),
+                key_index: 3,
+                key_threshold: Some(Ranking::Contains),
+                is_soft_match: false,
+                matched_portion: None,
+                matched_key_alias: None,
+                tie_priority: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn ranked_item_into_owned_and_back() {
+        let item = "jane@example.com".to_owned();
+        for ranked in ranked_pair(&item) {
+            let owned = ranked.clone().into_owned();
+            assert_eq!(owned.item, item);
+            assert_eq!(owned.ranked_value, ranked.ranked_value);
+            assert_eq!(RankedItem::from(&owned), ranked);
+        }
+    }
+
+    #[test]
+    fn owned_ranked_items_outlive_input() {
+        fn search() -> Vec<OwnedRankedItem<String>> {
+            let item = "jane@example.com".to_owned();
+            ranked_pair(&item)
+                .into_iter()
+                .map(RankedItem::into_owned)
+                .collect()
+        }
+        let cached = search();
+        assert_eq!(cached.len(), 2);
+        assert_eq!(cached[1].ranked_value, "Jane Doe");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn owned_ranked_item_json_roundtrip() {
+        let item = "jane@example.com".to_owned();
+        for ranked in ranked_pair(&item) {
+            let owned = ranked.clone().into_owned();
+            let json = serde_json::to_string(&owned).unwrap();
+            let back: OwnedRankedItem<String> = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, owned);
+            assert_eq!(RankedItem::from(&back), ranked);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn ranked_item_serializes_like_owned() {
+        let item = "jane@example.com".to_owned();
+        let [fuzzy, _] = ranked_pair(&item);
+        let json = serde_json::to_string(&fuzzy).unwrap();
+        assert_eq!(
+            json,
+            serde_json::to_string(&fuzzy.clone().into_owned()).unwrap()
+        );
+        let back: OwnedRankedItem<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.rank, Ranking::Matches(1.375));
+    }
+
     #[test]
     fn ranked_item_debug() {
         let item = "test".to_owned();
@@ -290,6 +2275,10 @@ mod tests {
             ranked_value: Cow::Borrowed("test"),
             key_index: 0,
             key_threshold: None,
+            is_soft_match: false,
+            matched_portion: None,
+            matched_key_alias: None,
+            tie_priority: None,
         };
         let debug_str = format!("{ranked:?}");
         assert!(debug_str.contains("Acronym"));
@@ -306,6 +2295,10 @@ mod tests {
             ranked_value: Cow::Borrowed("world"),
             key_index: 2,
             key_threshold: Some(Ranking::Contains),
+            is_soft_match: false,
+            matched_portion: None,
+            matched_key_alias: None,
+            tie_priority: None,
         };
         let cloned = ranked.clone();
         assert_eq!(ranked, cloned);
@@ -321,6 +2314,10 @@ mod tests {
             ranked_value: Cow::Borrowed("a"),
             key_index: 0,
             key_threshold: None,
+            is_soft_match: false,
+            matched_portion: None,
+            matched_key_alias: None,
+            tie_priority: None,
         };
         let b = RankedItem {
             item: &item,
@@ -329,6 +2326,10 @@ mod tests {
             ranked_value: Cow::Borrowed("a"),
             key_index: 0,
             key_threshold: None,
+            is_soft_match: false,
+            matched_portion: None,
+            matched_key_alias: None,
+            tie_priority: None,
         };
         assert_eq!(a, b);
     }
@@ -343,6 +2344,10 @@ mod tests {
             ranked_value: Cow::Borrowed("a"),
             key_index: 0,
             key_threshold: None,
+            is_soft_match: false,
+            matched_portion: None,
+            matched_key_alias: None,
+            tie_priority: None,
         };
         let b = RankedItem {
             item: &item,
@@ -351,7 +2356,238 @@ mod tests {
             ranked_value: Cow::Borrowed("a"),
             key_index: 0,
             key_threshold: None,
+            is_soft_match: false,
+            matched_portion: None,
+            matched_key_alias: None,
+            tie_priority: None,
         };
         assert_ne!(a, b);
     }
+
+    // --- extend tests ---
+
+    fn key_values(opts: &MatchSorterOptions<String>) -> Vec<String> {
+        let item = "x".to_owned();
+        opts.keys.iter().flat_map(|k| k.extract(&item)).collect()
+    }
+
+    fn tagged_key(tag: &'static str) -> Key<String> {
+        Key::new(move |_: &String| vec![tag.to_owned()])
+    }
+
+    #[test]
+    fn extend_all_none_keeps_base() {
+        let base = MatchSorterOptions {
+            keys: vec![tagged_key("a")],
+            threshold: Ranking::Contains,
+            keep_diacritics: true,
+            hyphen_is_word_boundary: true,
+            sorter: Some(Box::new(|items| items)),
+            ..Default::default()
+        };
+        let opts = base.extend(PartialMatchSorterOptions::default());
+        assert_eq!(key_values(&opts), vec!["a"]);
+        assert_eq!(opts.threshold, Ranking::Contains);
+        assert!(opts.keep_diacritics);
+        assert!(!opts.acronym_prefix_match);
+        assert!(opts.hyphen_is_word_boundary);
+        assert!(opts.sorter.is_some());
+        assert!(opts.base_sort.is_none());
+    }
+
+    #[test]
+    fn extend_all_some_takes_overrides() {
+        let base = MatchSorterOptions {
+            keys: vec![tagged_key("a")],
+            ..Default::default()
+        };
+        let overrides = PartialMatchSorterOptions::from(MatchSorterOptions {
+            keys: vec![tagged_key("b")],
+            threshold: Ranking::StartsWith,
+            threshold_fn: Some(Box::new(|_| Ranking::Contains)),
+            key_thresholds: HashMap::from([(0, Ranking::Equal)]),
+            soft_threshold: Some(Ranking::Acronym),
+            query_mode: QueryMode::AnyToken,
+            empty_query_behavior: EmptyQueryBehavior::ReturnEmpty,
+            exclude_terms: vec!["banana".to_owned()],
+            exclude_threshold: Ranking::Acronym,
+            keep_diacritics: true,
+            diacritics_mode: DiacriticsMode::StripNonDistinctive(DiacriticsLanguage::German),
+            normalization_form: NormalizationForm::Nfkc,
+            unicode_case_folding: UnicodeCaseFolding::Full,
+            acronym_prefix_match: true,
+            acronym_exact_match: true,
+            acronym_max_length: Some(4),
+            hyphen_is_word_boundary: true,
+            unicode_word_boundaries: true,
+            word_splitter: Some(Box::new(|_| Vec::new())),
+            fuzzy_mode: FuzzyMode::Optimal,
+            grapheme_matching: true,
+            ensemble_windows: Some(3),
+            prefilter: true,
+            strict_empty_values: true,
+            query_stemmer: Some(Box::new(str::to_owned)),
+            language_normalizers: HashMap::from([(
+                "de".to_owned(),
+                Box::new(str::to_owned) as LanguageNormalizerFn,
+            )]),
+            custom_case_fold: Some(Box::new(|c, _| c.to_lowercase().collect())),
+            closeness_scorer: Some(Box::new(|_, _, _, _| 1.5)),
+            item_score_modifier: Some(Box::new(|_, rank| rank)),
+            item_filter: Some(Box::new(|_| true)),
+            discard_items_slower_than: Some(Duration::from_millis(5)),
+            on_slow_item: Some(Box::new(|_| {})),
+            base_sort: Some(Box::new(|a, b| a.index.cmp(&b.index))),
+            sorter: Some(Box::new(|items| items)),
+            dedup_key: Some(Box::new(String::clone)),
+            cancel_token: Some(Arc::new(AtomicBool::new(true))),
+            ranking_cache: Some(Arc::new(Mutex::new(RankingCache::new(8)))),
+            expected_match_count: Some(5),
+            shrink_to_fit: true,
+            early_stop_criteria: Some(EarlyStopCriteria {
+                min_tier: Ranking::Contains,
+                min_count: 3,
+            }),
+        });
+        let opts = base.extend(overrides);
+        assert_eq!(key_values(&opts), vec!["b"]);
+        assert_eq!(opts.threshold, Ranking::StartsWith);
+        assert!(opts.threshold_fn.is_some());
+        assert_eq!(opts.key_thresholds, HashMap::from([(0, Ranking::Equal)]));
+        assert_eq!(opts.soft_threshold, Some(Ranking::Acronym));
+        assert_eq!(opts.query_mode, QueryMode::AnyToken);
+        assert_eq!(opts.empty_query_behavior, EmptyQueryBehavior::ReturnEmpty);
+        assert_eq!(opts.exclude_terms, vec!["banana".to_owned()]);
+        assert_eq!(opts.exclude_threshold, Ranking::Acronym);
+        assert!(opts.keep_diacritics);
+        assert_eq!(
+            opts.diacritics_mode,
+            DiacriticsMode::StripNonDistinctive(DiacriticsLanguage::German)
+        );
+        assert_eq!(opts.normalization_form, NormalizationForm::Nfkc);
+        assert_eq!(opts.unicode_case_folding, UnicodeCaseFolding::Full);
+        assert!(opts.acronym_prefix_match);
+        assert!(opts.acronym_exact_match);
+        assert_eq!(opts.acronym_max_length, Some(4));
+        assert!(opts.hyphen_is_word_boundary);
+        assert!(opts.unicode_word_boundaries);
+        assert!(opts.word_splitter.is_some());
+        assert_eq!(opts.fuzzy_mode, FuzzyMode::Optimal);
+        assert!(opts.grapheme_matching);
+        assert_eq!(opts.ensemble_windows, Some(3));
+        assert!(opts.prefilter);
+        assert!(opts.strict_empty_values);
+        assert!(opts.query_stemmer.is_some());
+        assert!(opts.language_normalizers.contains_key("de"));
+        assert!(opts.custom_case_fold.is_some());
+        assert!(opts.closeness_scorer.is_some());
+        assert!(opts.item_score_modifier.is_some());
+        assert!(opts.item_filter.is_some());
+        assert_eq!(
+            opts.discard_items_slower_than,
+            Some(Duration::from_millis(5))
+        );
+        assert!(opts.on_slow_item.is_some());
+        assert!(opts.dedup_key.is_some());
+        assert!(opts.cancel_token.is_some());
+        assert!(opts.ranking_cache.is_some());
+        assert_eq!(opts.expected_match_count, Some(5));
+        assert!(opts.shrink_to_fit);
+        assert_eq!(
+            opts.early_stop_criteria,
+            Some(EarlyStopCriteria {
+                min_tier: Ranking::Contains,
+                min_count: 3,
+            })
+        );
+        assert!(opts.base_sort.is_some());
+        assert!(opts.sorter.is_some());
+    }
+
+    #[test]
+    fn extend_keys_append_vs_replace() {
+        let base = || MatchSorterOptions {
+            keys: vec![tagged_key("a")],
+            ..Default::default()
+        };
+        let replaced = base().extend(PartialMatchSorterOptions {
+            keys: Some(vec![tagged_key("b")]),
+            ..Default::default()
+        });
+        assert_eq!(key_values(&replaced), vec!["b"]);
+
+        let appended = base().extend(PartialMatchSorterOptions {
+            keys: Some(vec![tagged_key("b"), tagged_key("c")]),
+            keys_mode: KeysExtendMode::Append,
+            ..Default::default()
+        });
+        assert_eq!(key_values(&appended), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn extend_can_lift_acronym_max_length() {
+        let base = || MatchSorterOptions::<String> {
+            acronym_max_length: Some(3),
+            ..Default::default()
+        };
+        let inherited = base().extend(PartialMatchSorterOptions::default());
+        assert_eq!(inherited.acronym_max_length, Some(3));
+        let lifted = base().extend(PartialMatchSorterOptions {
+            acronym_max_length: Some(None),
+            ..Default::default()
+        });
+        assert_eq!(lifted.acronym_max_length, None);
+    }
+
+    #[test]
+    fn extend_some_none_clears_base_options() {
+        let base = || MatchSorterOptions::<String> {
+            soft_threshold: Some(Ranking::Contains),
+            ensemble_windows: Some(3),
+            discard_items_slower_than: Some(Duration::from_millis(5)),
+            expected_match_count: Some(5),
+            early_stop_criteria: Some(EarlyStopCriteria {
+                min_tier: Ranking::Contains,
+                min_count: 3,
+            }),
+            item_filter: Some(Box::new(|_| true)),
+            sorter: Some(Box::new(|items| items)),
+            ..Default::default()
+        };
+        let inherited = base().extend(PartialMatchSorterOptions::default());
+        assert_eq!(inherited.soft_threshold, Some(Ranking::Contains));
+        assert!(inherited.item_filter.is_some());
+
+        let cleared = base().extend(PartialMatchSorterOptions {
+            soft_threshold: Some(None),
+            ensemble_windows: Some(None),
+            discard_items_slower_than: Some(None),
+            expected_match_count: Some(None),
+            early_stop_criteria: Some(None),
+            item_filter: Some(None),
+            sorter: Some(None),
+            ..Default::default()
+        });
+        assert_eq!(cleared.soft_threshold, None);
+        assert_eq!(cleared.ensemble_windows, None);
+        assert_eq!(cleared.discard_items_slower_than, None);
+        assert_eq!(cleared.expected_match_count, None);
+        assert_eq!(cleared.early_stop_criteria, None);
+        assert!(cleared.item_filter.is_none());
+        assert!(cleared.sorter.is_none());
+
+        // `From` carries the `None`s over, so the result matches its source.
+        let defaults = base().extend(MatchSorterOptions::default().into());
+        assert_eq!(defaults.soft_threshold, None);
+        assert!(defaults.sorter.is_none());
+    }
+
+    #[test]
+    fn partial_default_is_replace_mode() {
+        let partial = PartialMatchSorterOptions::<String>::default();
+        assert_eq!(partial.keys_mode, KeysExtendMode::Replace);
+        assert!(partial.keys.is_none());
+        let debug_str = format!("{partial:?}");
+        assert!(debug_str.contains("PartialMatchSorterOptions"));
+    }
 }
@@ -0,0 +1,428 @@
+//! Paginated access to match results.
+//!
+//! [`PageResult`] is one window of the sorted matches plus the metadata an
+//! infinite-scroll UI needs. [`PaginationCache`] keeps the full sorted result
+//! for a query so that fetching further pages does not re-rank every item.
+
+use std::collections::HashMap;
+
+use crate::no_keys::AsMatchStr;
+use crate::options::{EarlyStopCriteria, EmptyQueryBehavior, MatchSorterOptions, QueryMode};
+use crate::ranking::{DiacriticsMode, FuzzyMode, NormalizationForm, Ranking, UnicodeCaseFolding};
+
+/// One page of match results.
+///
+/// Produced by [`match_sorter_paginate`](crate::match_sorter_paginate) and
+/// [`PaginationCache::page`]. Pages are zero-based.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, match_sorter_paginate};
+///
+/// let items = ["apple", "apricot", "grape", "pineapple"];
+/// let page = match_sorter_paginate(&items, "ap", 0, 2, &MatchSorterOptions::default());
+/// assert_eq!(page.items, vec![&"apple", &"apricot"]);
+/// assert_eq!(page.total, 4);
+/// assert!(page.has_next);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageResult<'a, T> {
+    /// The matching items on this page, best matches first.
+    pub items: Vec<&'a T>,
+
+    /// Total number of matching items across all pages.
+    pub total: usize,
+
+    /// The zero-based page index that was requested.
+    pub page: usize,
+
+    /// The requested page size.
+    pub page_size: usize,
+
+    /// Whether at least one more matching item follows this page.
+    pub has_next: bool,
+}
+
+impl<'a, T> PageResult<'a, T> {
+    /// Slice one page out of the full sorted result.
+    ///
+    /// A page past the end, or a `page_size` of zero, yields an empty page
+    /// with `has_next == false`.
+    pub(crate) fn from_sorted(sorted: &[&'a T], page: usize, page_size: usize) -> Self {
+        let total = sorted.len();
+        let start = page.saturating_mul(page_size).min(total);
+        let end = start.saturating_add(page_size).min(total);
+        Self {
+            items: sorted[start..end].to_vec(),
+            total,
+            page,
+            page_size,
+            has_next: page_size > 0 && end < total,
+        }
+    }
+}
+
+/// Identifies the inputs a cached result was computed from: the query, the
+/// item slice, and every option that can change the result and can be
+/// compared.
+#[derive(Debug, Clone, PartialEq)]
+struct CacheKey {
+    query: String,
+    threshold: Ranking,
+    key_thresholds: HashMap<usize, Ranking>,
+    soft_threshold: Option<Ranking>,
+    query_mode: QueryMode,
+    empty_query_behavior: EmptyQueryBehavior,
+    exclude_terms: Vec<String>,
+    exclude_threshold: Ranking,
+    keep_diacritics: bool,
+    diacritics_mode: DiacriticsMode,
+    normalization_form: NormalizationForm,
+    unicode_case_folding: UnicodeCaseFolding,
+    acronym_prefix_match: bool,
+    acronym_exact_match: bool,
+    acronym_max_length: Option<usize>,
+    hyphen_is_word_boundary: bool,
+    unicode_word_boundaries: bool,
+    fuzzy_mode: FuzzyMode,
+    grapheme_matching: bool,
+    ensemble_windows: Option<usize>,
+    prefilter: bool,
+    strict_empty_values: bool,
+    early_stop_criteria: Option<EarlyStopCriteria>,
+    key_count: usize,
+    items_addr: usize,
+    items_len: usize,
+}
+
+impl CacheKey {
+    fn new<T>(items: &[T], value: &str, options: &MatchSorterOptions<T>) -> Self {
+        Self {
+            query: value.to_owned(),
+            threshold: options.threshold,
+            key_thresholds: options.key_thresholds.clone(),
+            soft_threshold: options.soft_threshold,
+            query_mode: options.query_mode,
+            empty_query_behavior: options.empty_query_behavior,
+            exclude_terms: options.exclude_terms.clone(),
+            exclude_threshold: options.exclude_threshold,
+            keep_diacritics: options.keep_diacritics,
+            diacritics_mode: options.diacritics_mode,
+            normalization_form: options.normalization_form,
+            unicode_case_folding: options.unicode_case_folding,
+            acronym_prefix_match: options.acronym_prefix_match,
+            acronym_exact_match: options.acronym_exact_match,
+            acronym_max_length: options.acronym_max_length,
+            hyphen_is_word_boundary: options.hyphen_is_word_boundary,
+            unicode_word_boundaries: options.unicode_word_boundaries,
+            fuzzy_mode: options.fuzzy_mode,
+            grapheme_matching: options.grapheme_matching,
+            ensemble_windows: options.ensemble_windows,
+            prefilter: options.prefilter,
+            strict_empty_values: options.strict_empty_values,
+            early_stop_criteria: options.early_stop_criteria,
+            key_count: options.keys.len(),
+            items_addr: items.as_ptr().addr(),
+            items_len: items.len(),
+        }
+    }
+}
+
+/// Returns whether a search with `options` must bypass the cache.
+///
+/// Closures that can change the result cannot be compared, and
+/// [`discard_items_slower_than`](MatchSorterOptions::discard_items_slower_than)
+/// makes the result depend on how long each item took to rank.
+fn bypasses_cache<T>(options: &MatchSorterOptions<T>) -> bool {
+    options.discard_items_slower_than.is_some()
+        || options.threshold_fn.is_some()
+        || options.word_splitter.is_some()
+        || options.query_stemmer.is_some()
+        || !options.language_normalizers.is_empty()
+        || options.custom_case_fold.is_some()
+        || options.closeness_scorer.is_some()
+        || options.item_score_modifier.is_some()
+        || options.item_filter.is_some()
+        || options.base_sort.is_some()
+        || options.sorter.is_some()
+}
+
+/// Caches the full sorted result of a query for repeated page requests.
+///
+/// The first [`page`](Self::page) call for a query ranks and sorts every
+/// item; later calls with the same query, item slice and options only
+/// slice the cached result. Changing the query, the slice, or any option
+/// that holds plain data (thresholds, query mode, diacritics, case folding,
+/// word boundary and acronym settings, and so on) re-ranks automatically.
+///
+/// Options holding closures cannot be compared. A search with a
+/// `threshold_fn`, `word_splitter`, `query_stemmer`, language normalizer,
+/// `custom_case_fold`, `closeness_scorer`, `item_score_modifier`,
+/// `item_filter`, `base_sort` or `sorter` is never cached and always
+/// re-ranks, as is one with `discard_items_slower_than` set, whose result
+/// is not deterministic. Keys are compared only by their number, so call
+/// [`invalidate`](Self::invalidate) after replacing one.
+///
+/// The cache borrows the items it ranked, so the compiler rejects any
+/// attempt to modify them while the cache is alive:
+///
+/// ```compile_fail
+/// use matchsorter::{MatchSorterOptions, PaginationCache};
+///
+/// let mut items = vec!["apple".to_owned()];
+/// let opts = MatchSorterOptions::default();
+/// let mut cache = PaginationCache::new();
+/// let _ = cache.page(&items, "ap", 0, 10, &opts);
+/// items.push("apricot".to_owned()); // error: `items` is borrowed by `cache`
+/// let _ = cache.page(&items, "ap", 0, 10, &opts);
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, PaginationCache};
+///
+/// let items = ["apple", "apricot", "grape", "pineapple"];
+/// let opts = MatchSorterOptions::default();
+/// let mut cache = PaginationCache::new();
+///
+/// let first = cache.page(&items, "ap", 0, 3, &opts);
+/// let second = cache.page(&items, "ap", 1, 3, &opts);
+/// assert_eq!(first.items.len(), 3);
+/// assert_eq!(second.items, vec![&"pineapple"]);
+/// assert!(!second.has_next);
+/// ```
+pub struct PaginationCache<'a, T> {
+    /// Inputs the cached result was computed from, or `None` when empty.
+    key: Option<CacheKey>,
+
+    /// The full sorted result for `key`.
+    sorted: Vec<&'a T>,
+}
+
+impl<'a, T> PaginationCache<'a, T> {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self {
+            key: None,
+            sorted: Vec::new(),
+        }
+    }
+
+    /// Create an empty cache with room for `n` matches.
+    ///
+    /// Use the expected number of matches to avoid reallocating on the
+    /// first query.
+    pub fn with_capacity(n: usize) -> Self {
+        Self {
+            key: None,
+            sorted: Vec::with_capacity(n),
+        }
+    }
+
+    /// Discard the cached result, forcing the next [`page`](Self::page)
+    /// call to re-rank.
+    pub fn invalidate(&mut self) {
+        self.key = None;
+        self.sorted.clear();
+    }
+
+    /// Return one page of matches for `value`, ranking only on a cache miss.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - Slice of items to search through
+    /// * `value` - The search query string
+    /// * `page` - Zero-based page index
+    /// * `page_size` - Maximum number of items per page
+    /// * `options` - Configuration options (threshold, keys, sorting, etc.)
+    pub fn page(
+        &mut self,
+        items: &'a [T],
+        value: &str,
+        page: usize,
+        page_size: usize,
+        options: &MatchSorterOptions<T>,
+    ) -> PageResult<'a, T>
+    where
+        T: AsMatchStr,
+    {
+        if bypasses_cache(options) {
+            return crate::match_sorter_paginate(items, value, page, page_size, options);
+        }
+        let key = CacheKey::new(items, value, options);
+        if self.key.as_ref() != Some(&key) {
+            self.sorted = crate::match_sorter(items, value, options);
+            self.key = Some(key);
+        }
+        PageResult::from_sorted(&self.sorted, page, page_size)
+    }
+}
+
+impl<T> Default for PaginationCache<'_, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{match_sorter, match_sorter_paginate};
+
+    fn fruits() -> Vec<String> {
+        [
+            "apple",
+            "apricot",
+            "grape",
+            "pineapple",
+            "snap",
+            "banana",
+            "papaya",
+        ]
+        .iter()
+        .map(|s| (*s).to_owned())
+        .collect()
+    }
+
+    #[test]
+    fn pages_concatenate_to_full_result() {
+        let items = fruits();
+        let full = match_sorter(&items, "ap", &MatchSorterOptions::default());
+        let mut collected = Vec::new();
+        let mut page = 0;
+        loop {
+            let result =
+                match_sorter_paginate(&items, "ap", page, 2, &MatchSorterOptions::default());
+            assert_eq!(result.total, full.len());
+            assert!(result.items.len() <= 2);
+            collected.extend(result.items);
+            if !result.has_next {
+                break;
+            }
+            page += 1;
+        }
+        assert_eq!(collected, full);
+    }
+
+    #[test]
+    fn last_page_has_no_next() {
+        let items = fruits();
+        let total = match_sorter(&items, "ap", &MatchSorterOptions::default()).len();
+        let last = (total - 1) / 2;
+        let result = match_sorter_paginate(&items, "ap", last, 2, &MatchSorterOptions::default());
+        assert!(!result.has_next);
+        assert!(!result.items.is_empty());
+        assert_eq!(result.page, last);
+        assert_eq!(result.page_size, 2);
+    }
+
+    #[test]
+    fn exact_multiple_last_page_has_no_next() {
+        let items = ["ab", "abc", "abcd", "abcde"];
+        let result = match_sorter_paginate(&items, "ab", 1, 2, &MatchSorterOptions::default());
+        assert_eq!(result.items.len(), 2);
+        assert!(!result.has_next);
+    }
+
+    #[test]
+    fn page_past_end_is_empty() {
+        let items = fruits();
+        let result = match_sorter_paginate(&items, "ap", 100, 2, &MatchSorterOptions::default());
+        assert!(result.items.is_empty());
+        assert!(!result.has_next);
+        let result = match_sorter_paginate(
+            &items,
+            "ap",
+            usize::MAX,
+            usize::MAX,
+            &MatchSorterOptions::default(),
+        );
+        assert!(result.items.is_empty());
+    }
+
+    #[test]
+    fn zero_page_size_is_empty() {
+        let items = fruits();
+        let result = match_sorter_paginate(&items, "ap", 0, 0, &MatchSorterOptions::default());
+        assert!(result.items.is_empty());
+        assert!(result.total > 0);
+        assert!(!result.has_next);
+    }
+
+    #[test]
+    fn cache_reuses_and_reranks_on_change() {
+        let items = fruits();
+        let opts = MatchSorterOptions::default();
+        let mut cache = PaginationCache::with_capacity(8);
+
+        let first = cache.page(&items, "ap", 0, 3, &opts);
+        let again = cache.page(&items, "ap", 0, 3, &opts);
+        assert_eq!(first, again);
+
+        let other = cache.page(&items, "gr", 0, 3, &opts);
+        assert_eq!(other.items, vec![&"grape".to_owned()]);
+
+        cache.invalidate();
+        let after = cache.page(&items, "ap", 0, 3, &opts);
+        assert_eq!(first, after);
+    }
+
+    #[test]
+    fn cache_reranks_when_compared_option_changes() {
+        let items = ["North-West".to_owned()];
+        let with_hyphens = |hyphen_is_word_boundary| MatchSorterOptions {
+            threshold: Ranking::WordStartsWith,
+            hyphen_is_word_boundary,
+            ..Default::default()
+        };
+        let mut cache = PaginationCache::new();
+        assert_eq!(
+            cache
+                .page(&items, "west", 0, 10, &with_hyphens(false))
+                .total,
+            0
+        );
+        assert_eq!(
+            cache.page(&items, "west", 0, 10, &with_hyphens(true)).total,
+            1
+        );
+    }
+
+    #[test]
+    fn cache_is_bypassed_with_closure_options() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let items = fruits();
+        let excluded = Rc::new(Cell::new("apple"));
+        let current = Rc::clone(&excluded);
+        let opts = MatchSorterOptions {
+            item_filter: Some(Box::new(move |s: &String| s != current.get())),
+            ..Default::default()
+        };
+        let mut cache = PaginationCache::new();
+        let first = cache.page(&items, "ap", 0, 100, &opts);
+        assert!(!first.items.contains(&&"apple".to_owned()));
+        excluded.set("grape");
+        let second = cache.page(&items, "ap", 0, 100, &opts);
+        assert!(second.items.contains(&&"apple".to_owned()));
+        assert!(!second.items.contains(&&"grape".to_owned()));
+    }
+
+    #[test]
+    fn cache_sees_modified_items_after_release() {
+        let mut items = fruits();
+        let opts = MatchSorterOptions::default();
+        let before = {
+            let mut cache = PaginationCache::new();
+            cache.page(&items, "ap", 0, 100, &opts).total
+        };
+        items.push("apex".to_owned());
+        let mut cache = PaginationCache::new();
+        let after = cache.page(&items, "ap", 0, 100, &opts);
+        assert_eq!(after.total, before + 1);
+        assert!(after.items.contains(&&"apex".to_owned()));
+    }
+}
@@ -0,0 +1,152 @@
+//! Phonetic encodings for sound-alike name matching.
+//!
+//! Enabled by the `phonetic` Cargo feature. The encodings here are used by
+//! [`Key::with_soundex`](crate::key::Key::with_soundex) to let items match
+//! queries that are spelled differently but pronounced alike, such as
+//! `"Smyth"` and `"Smith"`.
+
+/// Returns the Soundex digit for an ASCII letter, or `None` for letters that
+/// are not coded (vowels, `h`, `w`, `y`).
+fn soundex_digit(c: char) -> Option<char> {
+    match c {
+        'b' | 'f' | 'p' | 'v' => Some('1'),
+        'c' | 'g' | 'j' | 'k' | 'q' | 's' | 'x' | 'z' => Some('2'),
+        'd' | 't' => Some('3'),
+        'l' => Some('4'),
+        'm' | 'n' => Some('5'),
+        'r' => Some('6'),
+        _ => None,
+    }
+}
+
+/// Compute the American Soundex code of a string.
+///
+/// Follows the standard US Soundex rules:
+///
+/// 1. Keep the first letter (uppercased).
+/// 2. Map the remaining consonants to digits (`bfpv` -> 1, `cgjkqsxz` -> 2,
+///    `dt` -> 3, `l` -> 4, `mn` -> 5, `r` -> 6).
+/// 3. Collapse adjacent letters with the same digit, including across `h`
+///    and `w`. Vowels (and `y`) separate letters, so a repeated digit after
+///    a vowel is coded again.
+/// 4. Pad with `'0'` or truncate to exactly 4 characters.
+///
+/// Non-ASCII-alphabetic characters are ignored. Returns an empty string if
+/// the input contains no ASCII letters.
+///
+/// # Arguments
+///
+/// * `s` - The word to encode
+///
+/// # Returns
+///
+/// A 4-character code such as `"R163"`, or `""` for input without letters.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::phonetic::soundex;
+///
+/// assert_eq!(soundex("Robert"), "R163");
+/// assert_eq!(soundex("Rupert"), "R163");
+/// assert_eq!(soundex("Tymczak"), "T522");
+/// assert_eq!(soundex(""), "");
+/// ```
+pub fn soundex(s: &str) -> String {
+    let mut letters = s
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_lowercase());
+
+    let first = match letters.next() {
+        Some(c) => c,
+        None => return String::new(),
+    };
+
+    let mut code = String::with_capacity(4);
+    code.push(first.to_ascii_uppercase());
+
+    // The first letter's digit counts as "previous" so that e.g. "Pfister"
+    // does not code the 'f' (same digit as 'p').
+    let mut prev = soundex_digit(first);
+    for c in letters {
+        if code.len() == 4 {
+            break;
+        }
+        match soundex_digit(c) {
+            Some(d) => {
+                if prev != Some(d) {
+                    code.push(d);
+                }
+                prev = Some(d);
+            }
+            // 'h' and 'w' are transparent: they do not separate duplicates.
+            None if c == 'h' || c == 'w' => {}
+            // Vowels and 'y' separate duplicate digits.
+            None => prev = None,
+        }
+    }
+
+    while code.len() < 4 {
+        code.push('0');
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn robert_and_rupert_share_code() {
+        assert_eq!(soundex("Robert"), "R163");
+        assert_eq!(soundex("Rupert"), soundex("Robert"));
+    }
+
+    #[test]
+    fn smith_and_smyth_share_code() {
+        assert_eq!(soundex("Smith"), "S530");
+        assert_eq!(soundex("smyth"), "S530");
+        assert_eq!(soundex("Smythe"), "S530");
+    }
+
+    #[test]
+    fn pads_short_codes() {
+        assert_eq!(soundex("Lee"), "L000");
+        assert_eq!(soundex("A"), "A000");
+    }
+
+    #[test]
+    fn truncates_long_codes() {
+        assert_eq!(soundex("Washington"), "W252");
+    }
+
+    #[test]
+    fn collapses_adjacent_duplicates() {
+        // 'c' and 'k' share digit 2; 'z' after a vowel is coded again.
+        assert_eq!(soundex("Jackson"), "J250");
+        assert_eq!(soundex("Tymczak"), "T522");
+    }
+
+    #[test]
+    fn first_letter_digit_suppresses_duplicate() {
+        assert_eq!(soundex("Pfister"), "P236");
+    }
+
+    #[test]
+    fn h_and_w_do_not_separate_duplicates() {
+        assert_eq!(soundex("Ashcraft"), "A261");
+    }
+
+    #[test]
+    fn ignores_non_letters() {
+        assert_eq!(soundex("O'Hara"), "O600");
+        assert_eq!(soundex("123"), "");
+        assert_eq!(soundex(""), "");
+    }
+
+    #[test]
+    fn case_insensitive() {
+        assert_eq!(soundex("ROBERT"), soundex("robert"));
+    }
+}
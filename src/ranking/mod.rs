@@ -5,9 +5,19 @@
 //! equality down to fuzzy character-by-character matching.
 
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::{Mutex, PoisonError};
 
 use unicode_normalization::UnicodeNormalization;
 use unicode_normalization::char::is_combining_mark;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::cache::RankingCache;
+use crate::language::{self, CaseFold, LanguageAwareOptions};
 
 /// Represents the quality of a match between a candidate string and a query.
 ///
@@ -36,6 +46,29 @@ use unicode_normalization::char::is_combining_mark;
 /// `Ranking` implements [`PartialOrd`] such that higher-quality matches compare
 /// as greater. For two `Matches` variants, the one with the higher sub-score
 /// is greater.
+///
+/// # Hashing and equality
+///
+/// `Ranking` implements [`Eq`] and [`Hash`], so it can be used as a
+/// `HashMap` or `HashSet` key (e.g. to group results by tier). `Matches`
+/// hashes its sub-score via [`f64::to_bits`], with `-0.0` normalized to
+/// `0.0` so that equal rankings always hash equally.
+///
+/// The `Eq` contract assumes sub-scores are never NaN: `Matches(NaN)` is not
+/// equal to itself, and `Matches(NaN)` and `Matches(-NaN)` hash differently
+/// because their bit patterns differ. Sub-scores produced by this crate are
+/// always finite; avoid constructing NaN sub-scores yourself.
+///
+/// `Ranking` is deliberately not [`Ord`]: ordering stays on
+/// [`PartialOrd`], which sorting helpers such as
+/// [`sort_ranked_values`](crate::sort_ranked_values) already use.
+///
+/// # Parsing
+///
+/// `Ranking` parses from its variant name, e.g. `"StartsWith"`, so that a
+/// threshold can be set from a config file; see the [`FromStr`] impl.
+/// [`Display`](fmt::Display) writes the same names, and with the `serde`
+/// feature a ranking is serialized as that string.
 #[derive(Debug, Clone, Copy)]
 pub enum Ranking {
     /// Exact byte-for-byte match (tier 7).
@@ -62,12 +95,549 @@ pub enum Ranking {
 }
 
 impl Ranking {
+    /// The lowest ranking that still counts as a match: `Matches(1.0)`.
+    ///
+    /// This is the default global threshold in
+    /// [`MatchSorterOptions`](crate::MatchSorterOptions).
+    pub const MATCH_THRESHOLD: Ranking = Ranking::Matches(1.0);
+
+    /// The number of fixed tiers: every variant except `Matches`.
+    pub const TIER_COUNT: usize = 7;
+
+    /// The fixed tiers, best first, leaving out `Matches`, whose sub-score
+    /// makes it a range of rankings rather than a single tier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// let tiers = Ranking::fixed_tiers();
+    /// assert_eq!(tiers.len(), Ranking::TIER_COUNT);
+    /// assert_eq!(tiers[0], Ranking::CaseSensitiveEqual);
+    /// assert_eq!(tiers[Ranking::TIER_COUNT - 1], Ranking::NoMatch);
+    /// ```
+    pub fn fixed_tiers() -> &'static [Ranking] {
+        const TIERS: [Ranking; Ranking::TIER_COUNT] = [
+            Ranking::CaseSensitiveEqual,
+            Ranking::Equal,
+            Ranking::StartsWith,
+            Ranking::WordStartsWith,
+            Ranking::Contains,
+            Ranking::Acronym,
+            Ranking::NoMatch,
+        ];
+        &TIERS
+    }
+
+    /// Iterate over the fixed tiers in descending order.
+    ///
+    /// See [`Ranking::fixed_tiers`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{MatchSorterOptions, Ranking, match_sorter};
+    ///
+    /// let items = ["apple", "pineapple", "grape"];
+    /// for tier in Ranking::tiers().take_while(|&tier| tier >= Ranking::Contains) {
+    ///     let opts = MatchSorterOptions { threshold: tier, ..Default::default() };
+    ///     println!("{tier:?}: {:?}", match_sorter(&items, "apple", &opts));
+    /// }
+    /// ```
+    pub fn tiers() -> impl Iterator<Item = Ranking> {
+        Self::fixed_tiers().iter().copied()
+    }
+
+    /// Returns the highest ranking produced by `rankings`, or `None` if it
+    /// is empty.
+    ///
+    /// Ties keep the earliest ranking. Uses the [`PartialOrd`] ordering, so
+    /// a (contract-violating) NaN sub-score never replaces the current best.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// let best = Ranking::max_of([Ranking::Contains, Ranking::StartsWith, Ranking::Acronym]);
+    /// assert_eq!(best, Some(Ranking::StartsWith));
+    /// assert_eq!(Ranking::max_of([]), None);
+    /// ```
+    pub fn max_of<I>(rankings: I) -> Option<Ranking>
+    where
+        I: IntoIterator<Item = Ranking>,
+    {
+        rankings
+            .into_iter()
+            .reduce(|best, r| if r > best { r } else { best })
+    }
+
+    /// Returns the lowest ranking produced by `rankings`, or `None` if it
+    /// is empty.
+    ///
+    /// Ties keep the earliest ranking. See [`Ranking::max_of`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// let worst = Ranking::min_of([Ranking::Contains, Ranking::Matches(1.5), Ranking::Equal]);
+    /// assert_eq!(worst, Some(Ranking::Matches(1.5)));
+    /// ```
+    pub fn min_of<I>(rankings: I) -> Option<Ranking>
+    where
+        I: IntoIterator<Item = Ranking>,
+    {
+        rankings
+            .into_iter()
+            .reduce(|worst, r| if r < worst { r } else { worst })
+    }
+
+    /// Restrict this ranking to the range `[lo, hi]`.
+    ///
+    /// The ceiling is applied first, then the floor, so if `lo > hi` the
+    /// result is `lo`. Unlike [`Ord::clamp`] this never panics.
+    ///
+    /// # Arguments
+    ///
+    /// * `lo` - The minimum ranking to return
+    /// * `hi` - The maximum ranking to return
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert_eq!(
+    ///     Ranking::Equal.clamp(Ranking::Acronym, Ranking::Contains),
+    ///     Ranking::Contains
+    /// );
+    /// assert_eq!(
+    ///     Ranking::NoMatch.clamp(Ranking::Contains, Ranking::Contains),
+    ///     Ranking::Contains
+    /// );
+    /// ```
+    #[must_use]
+    pub fn clamp(self, lo: Ranking, hi: Ranking) -> Ranking {
+        let capped = if self > hi { hi } else { self };
+        if capped < lo { lo } else { capped }
+    }
+
+    /// Turn this ranking upside down, so that close matches rank low and
+    /// non-matches rank high.
+    ///
+    /// Meant for a [`Key::with_score_modifier`](crate::Key::with_score_modifier)
+    /// that pushes items matching an unwanted term down the list instead of
+    /// dropping them, as [`Key::invert`](crate::Key::invert) would. The
+    /// mapping is:
+    ///
+    /// | Ranking | Inverted |
+    /// |---|---|
+    /// | `CaseSensitiveEqual`, `Equal` | `NoMatch` |
+    /// | `StartsWith`, `WordStartsWith` | `Acronym` |
+    /// | `Contains` | `WordStartsWith` |
+    /// | `Acronym` | `Contains` |
+    /// | `Matches(s)` | `Matches(3.0 - s)` |
+    /// | `NoMatch` | `CaseSensitiveEqual` |
+    ///
+    /// `Matches(s)` is mirrored within its tier, so `Matches(2.0)` becomes
+    /// `Matches(1.0)`, just above `NoMatch`.
+    ///
+    /// Inverting twice is not the identity: the mapping merges tiers
+    /// (`Equal` and `CaseSensitiveEqual` both become `NoMatch`), so
+    /// `StartsWith.invert().invert()` is `Contains` and
+    /// `Equal.invert().invert()` is `CaseSensitiveEqual`. Only
+    /// `CaseSensitiveEqual`, `NoMatch` and `Matches` come back unchanged,
+    /// the latter up to rounding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert_eq!(Ranking::Equal.invert(), Ranking::NoMatch);
+    /// assert_eq!(Ranking::Contains.invert(), Ranking::WordStartsWith);
+    /// assert_eq!(Ranking::Matches(1.25).invert(), Ranking::Matches(1.75));
+    /// assert_eq!(Ranking::NoMatch.invert(), Ranking::CaseSensitiveEqual);
+    /// ```
+    #[must_use]
+    pub fn invert(self) -> Ranking {
+        match self {
+            Ranking::CaseSensitiveEqual | Ranking::Equal => Ranking::NoMatch,
+            Ranking::StartsWith | Ranking::WordStartsWith => Ranking::Acronym,
+            Ranking::Contains => Ranking::WordStartsWith,
+            Ranking::Acronym => Ranking::Contains,
+            Ranking::Matches(score) => Ranking::Matches(2.0 - (score - 1.0)),
+            Ranking::NoMatch => Ranking::CaseSensitiveEqual,
+        }
+    }
+
+    /// Invert this ranking to a yes/no answer: any match becomes
+    /// `NoMatch` and `NoMatch` becomes `CaseSensitiveEqual`.
+    ///
+    /// The binary counterpart of [`invert`](Self::invert), for when how
+    /// well a value matched does not matter. Unlike `invert`, applying it
+    /// twice turns every match into `CaseSensitiveEqual`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert_eq!(Ranking::Matches(1.1).invert_to_no_match(), Ranking::NoMatch);
+    /// assert_eq!(Ranking::NoMatch.invert_to_no_match(), Ranking::CaseSensitiveEqual);
+    /// ```
+    #[must_use]
+    pub fn invert_to_no_match(self) -> Ranking {
+        match self {
+            Ranking::NoMatch => Ranking::CaseSensitiveEqual,
+            _ => Ranking::NoMatch,
+        }
+    }
+
+    /// Move this ranking `steps` tiers up, towards `CaseSensitiveEqual`.
+    ///
+    /// Tiers are counted as in [`u8::from`], with `Matches` one tier above
+    /// `NoMatch`, so `Matches(_).promote(1)` is `Acronym`. The result
+    /// saturates at `CaseSensitiveEqual`. Landing on the `Matches` tier
+    /// from `NoMatch` yields `Matches(2.0)`, as [`Ranking::try_from`] does;
+    /// `promote(0)` returns the ranking unchanged, sub-score included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert_eq!(Ranking::Contains.promote(2), Ranking::StartsWith);
+    /// assert_eq!(Ranking::Matches(1.5).promote(1), Ranking::Acronym);
+    /// assert_eq!(Ranking::Equal.promote(5), Ranking::CaseSensitiveEqual);
+    /// ```
+    #[must_use]
+    pub fn promote(self, steps: u8) -> Ranking {
+        self.shift_tier(i32::from(steps))
+    }
+
+    /// Move this ranking `steps` tiers down, towards `NoMatch`.
+    ///
+    /// The counterpart of [`promote`](Self::promote): the result saturates
+    /// at `NoMatch`, and landing on the `Matches` tier from `Acronym`
+    /// yields `Matches(2.0)`, the best fuzzy sub-score.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert_eq!(Ranking::StartsWith.demote(2), Ranking::Contains);
+    /// assert_eq!(Ranking::Acronym.demote(1), Ranking::Matches(2.0));
+    /// assert_eq!(Ranking::Matches(1.5).demote(1), Ranking::NoMatch);
+    /// assert_eq!(Ranking::Contains.demote(9), Ranking::NoMatch);
+    /// ```
+    #[must_use]
+    pub fn demote(self, steps: u8) -> Ranking {
+        self.shift_tier(-i32::from(steps))
+    }
+
+    /// The signed number of tiers from `other` up to `self`: positive when
+    /// `self` is the better tier, negative when it is the worse one.
+    ///
+    /// Tiers are counted as in [`promote`](Self::promote), so two `Matches`
+    /// rankings are 0 tiers apart whatever their sub-scores, and
+    /// `rank.promote(n).tier_distance(rank)` is `n` unless the promotion
+    /// saturated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert_eq!(Ranking::StartsWith.tier_distance(Ranking::Contains), 2);
+    /// assert_eq!(Ranking::Contains.tier_distance(Ranking::StartsWith), -2);
+    /// assert_eq!(Ranking::Matches(1.2).tier_distance(Ranking::Matches(1.9)), 0);
+    /// ```
+    pub fn tier_distance(self, other: Ranking) -> i32 {
+        i32::from(self.tier_value()) - i32::from(other.tier_value())
+    }
+
+    /// Same as [`tier_distance`](Self::tier_distance), under the name used
+    /// when reporting how far a rank sits above a threshold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert_eq!(Ranking::WordStartsWith.tier_gap(Ranking::Acronym), 2);
+    /// ```
+    pub fn tier_gap(self, other: Ranking) -> i32 {
+        self.tier_distance(other)
+    }
+
+    /// Returns whether `self` sits more tiers above `threshold` than
+    /// `other` does.
+    ///
+    /// As with [`tier_gap`](Self::tier_gap), `Matches` sub-scores are
+    /// ignored, so two `Matches` rankings are never closer than each other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// let threshold = Ranking::Contains;
+    /// assert!(Ranking::StartsWith.is_closer_than(Ranking::Contains, threshold));
+    /// assert!(!Ranking::Matches(1.9).is_closer_than(Ranking::Matches(1.1), threshold));
+    /// ```
+    pub fn is_closer_than(self, other: Ranking, threshold: Ranking) -> bool {
+        self.tier_gap(threshold) > other.tier_gap(threshold)
+    }
+
+    /// The ranking `delta` tiers away, clamped to the valid tiers.
+    fn shift_tier(self, delta: i32) -> Ranking {
+        if delta == 0 {
+            return self;
+        }
+        let tier = (i32::from(self.tier_value()) + delta).clamp(0, 7);
+        Ranking::try_from(tier as u8).expect("tier is clamped to 0-7")
+    }
+
+    /// Returns whether two rankings are equal, allowing `Matches`
+    /// sub-scores to differ by up to `tolerance`.
+    ///
+    /// Useful when sub-scores come from different computations that may
+    /// round differently. Fixed tiers compare exactly, as with `==`, and a
+    /// `Matches` ranking never equals a fixed tier. A NaN sub-score or
+    /// tolerance never compares equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert!(Ranking::Matches(1.5).approx_eq(Ranking::Matches(1.4999), 0.001));
+    /// assert!(!Ranking::Matches(1.5).approx_eq(Ranking::Matches(1.4), 0.001));
+    /// assert!(Ranking::Contains.approx_eq(Ranking::Contains, 0.0));
+    /// ```
+    pub fn approx_eq(self, other: Ranking, tolerance: f64) -> bool {
+        match (self, other) {
+            (Ranking::Matches(a), Ranking::Matches(b)) => (a - b).abs() <= tolerance,
+            _ => self.tier_value() == other.tier_value(),
+        }
+    }
+
+    /// Returns whether two rankings fall in the same tier, treating every
+    /// `Matches` sub-score as equal.
+    ///
+    /// See [`sort_ranked_values_by_tier`](crate::sort_ranked_values_by_tier)
+    /// for a comparator built on it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert!(Ranking::Matches(1.0).eq_tier(Ranking::Matches(2.0)));
+    /// assert!(!Ranking::Matches(2.0).eq_tier(Ranking::Acronym));
+    /// ```
+    pub fn eq_tier(self, other: Ranking) -> bool {
+        self.tier_value() == other.tier_value()
+    }
+
+    /// Returns whether this ranking is strictly better than `lo` and
+    /// strictly worse than `hi`, as compared by `PartialOrd`.
+    ///
+    /// `Matches` sub-scores count, so `Matches(1.5)` lies between
+    /// `Matches(1.2)` and `Matches(1.8)`. Always `false` when `lo` is not
+    /// below `hi`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert!(Ranking::WordStartsWith.between(Ranking::Contains, Ranking::Equal));
+    /// assert!(Ranking::Matches(1.5).between(Ranking::NoMatch, Ranking::Acronym));
+    /// assert!(!Ranking::Contains.between(Ranking::Contains, Ranking::Equal));
+    /// ```
+    pub fn between(self, lo: Ranking, hi: Ranking) -> bool {
+        self > lo && self < hi
+    }
+
+    /// Returns whether this ranking is at least `lo` and at most `hi`; the
+    /// inclusive form of [`between`](Self::between).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert!(Ranking::Contains.at_or_between(Ranking::Contains, Ranking::Equal));
+    /// assert!(!Ranking::Acronym.at_or_between(Ranking::Contains, Ranking::Equal));
+    /// ```
+    pub fn at_or_between(self, lo: Ranking, hi: Ranking) -> bool {
+        self >= lo && self <= hi
+    }
+
+    /// Returns whether this ranking meets `threshold`: the same as
+    /// `self >= threshold`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert!(Ranking::StartsWith.at_least(Ranking::Contains));
+    /// assert!(!Ranking::NoMatch.at_least(Ranking::Contains));
+    /// ```
+    pub fn at_least(self, threshold: Ranking) -> bool {
+        self >= threshold
+    }
+
+    /// The ranking's value on the JS `match-sorter` numeric scale:
+    /// `CaseSensitiveEqual` 7 down to `Acronym` 2, the sub-score for
+    /// `Matches` and 0 for `NoMatch`.
+    ///
+    /// A `Matches` sub-score outside `[1.0, 2.0]` is clamped into it, so
+    /// the result is always in `[0.0, 7.0]` and orders rankings the same
+    /// way as comparing them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert_eq!(Ranking::CaseSensitiveEqual.to_f64(), 7.0);
+    /// assert_eq!(Ranking::Matches(1.5).to_f64(), 1.5);
+    /// assert_eq!(Ranking::NoMatch.to_f64(), 0.0);
+    /// ```
+    pub fn to_f64(self) -> f64 {
+        match self {
+            Ranking::Matches(s) => s.clamp(1.0, 2.0),
+            fixed => f64::from(fixed.tier_value()),
+        }
+    }
+
+    /// Express this ranking as a percentage in `[0.0, 100.0]` using the
+    /// default [`PctScale`].
+    ///
+    /// Intended for display in logs and dashboards. Fixed tiers map to
+    /// `CaseSensitiveEqual` 100, `Equal` 85, `StartsWith` 71,
+    /// `WordStartsWith` 57, `Contains` 43, `Acronym` 28 and `NoMatch` 0;
+    /// `Matches(s)` maps linearly from `(1.0, 2.0]` to `(0.0, 14.0]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert_eq!(Ranking::StartsWith.as_pct(), 71.0);
+    /// assert_eq!(Ranking::Matches(1.5).as_pct(), 7.0);
+    /// ```
+    pub fn as_pct(self) -> f64 {
+        self.as_pct_with(&PctScale::default())
+    }
+
+    /// Express this ranking as a percentage using a custom `scale`.
+    ///
+    /// A `Matches` sub-score outside `[1.0, 2.0]` is clamped into it first,
+    /// so the result never leaves the range the scale describes.
+    pub fn as_pct_with(self, scale: &PctScale) -> f64 {
+        match self {
+            Ranking::CaseSensitiveEqual => scale.case_sensitive_equal,
+            Ranking::Equal => scale.equal,
+            Ranking::StartsWith => scale.starts_with,
+            Ranking::WordStartsWith => scale.word_starts_with,
+            Ranking::Contains => scale.contains,
+            Ranking::Acronym => scale.acronym,
+            Ranking::Matches(s) => (s.clamp(1.0, 2.0) - 1.0) * scale.matches_max,
+            Ranking::NoMatch => 0.0,
+        }
+    }
+
+    /// Approximate inverse of [`Ranking::as_pct`] using the default
+    /// [`PctScale`].
+    ///
+    /// See [`Ranking::from_pct_with`] for how values between tiers map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert_eq!(Ranking::from_pct(71.0), Ranking::StartsWith);
+    /// assert_eq!(Ranking::from_pct(80.0), Ranking::StartsWith);
+    /// assert_eq!(Ranking::from_pct(7.0), Ranking::Matches(1.5));
+    /// ```
+    pub fn from_pct(pct: f64) -> Ranking {
+        Self::from_pct_with(pct, &PctScale::default())
+    }
+
+    /// Approximate inverse of [`Ranking::as_pct_with`].
+    ///
+    /// Returns the highest ranking whose percentage does not exceed `pct`,
+    /// so each fixed tier round-trips exactly and the result can be used as
+    /// a threshold: every ranking at least `pct` percent compares `>=` it.
+    /// Percentages within the `Matches` band map back to the corresponding
+    /// sub-score; zero, negative and NaN percentages yield `NoMatch`.
+    pub fn from_pct_with(pct: f64, scale: &PctScale) -> Ranking {
+        let tiers = [
+            (scale.case_sensitive_equal, Ranking::CaseSensitiveEqual),
+            (scale.equal, Ranking::Equal),
+            (scale.starts_with, Ranking::StartsWith),
+            (scale.word_starts_with, Ranking::WordStartsWith),
+            (scale.contains, Ranking::Contains),
+            (scale.acronym, Ranking::Acronym),
+        ];
+        if let Some(&(_, rank)) = tiers.iter().find(|(tier_pct, _)| pct >= *tier_pct) {
+            return rank;
+        }
+        if pct.is_nan() || pct <= 0.0 {
+            return Ranking::NoMatch;
+        }
+        if scale.matches_max <= 0.0 || pct >= scale.matches_max {
+            return Ranking::Matches(2.0);
+        }
+        Ranking::Matches(1.0 + pct / scale.matches_max)
+    }
+
+    /// Rebuild a ranking from its integer tier (as returned by
+    /// `u8::from(ranking)`) and its `Matches` sub-score.
+    ///
+    /// `score` is only used for tier 1 (`Matches`), where it must fall in
+    /// `(1.0, 2.0]`; it is ignored for every other tier.
+    ///
+    /// # Errors
+    ///
+    /// [`InvalidRanking::Tier`] if `tier` is above 7, and
+    /// [`InvalidRanking::Score`] if `tier` is 1 and `score` is out of range
+    /// or NaN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{InvalidRanking, Ranking};
+    ///
+    /// assert_eq!(Ranking::try_from_tier_and_score(1, 1.5), Ok(Ranking::Matches(1.5)));
+    /// assert_eq!(Ranking::try_from_tier_and_score(5, 0.0), Ok(Ranking::StartsWith));
+    /// assert_eq!(Ranking::try_from_tier_and_score(8, 0.0), Err(InvalidRanking::Tier(8)));
+    /// assert!(Ranking::try_from_tier_and_score(1, f64::NAN).is_err());
+    /// ```
+    pub fn try_from_tier_and_score(tier: u8, score: f64) -> Result<Self, InvalidRanking> {
+        match Ranking::try_from(tier)? {
+            Ranking::Matches(_) if score > 1.0 && score <= 2.0 => Ok(Ranking::Matches(score)),
+            Ranking::Matches(_) => Err(InvalidRanking::Score(score)),
+            rank => Ok(rank),
+        }
+    }
+
     /// Returns the integer tier value for this ranking.
     ///
     /// Fixed tiers return their integer value (0-7). The `Matches` variant
     /// returns 1, since its effective value is the continuous sub-score
     /// stored in the variant (which falls in `(1.0, 2.0]`).
-    fn tier_value(&self) -> u8 {
+    pub(crate) fn tier_value(&self) -> u8 {
         match self {
             Ranking::CaseSensitiveEqual => 7,
             Ranking::Equal => 6,
@@ -94,6 +664,23 @@ impl PartialEq for Ranking {
     }
 }
 
+// Sub-scores are expected to be finite (see the type docs), which makes the
+// `PartialEq` above an equivalence relation.
+impl Eq for Ranking {}
+
+// Manual `Hash` consistent with `PartialEq`: the tier value identifies the
+// variant, and `Matches` additionally hashes its sub-score bits. `-0.0` and
+// `0.0` compare equal but have different bits, so normalize before hashing.
+impl Hash for Ranking {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.tier_value().hash(state);
+        if let Ranking::Matches(score) = self {
+            let score = if *score == 0.0 { 0.0 } else { *score };
+            score.to_bits().hash(state);
+        }
+    }
+}
+
 // Manual `PartialOrd` to enable ranking comparisons. Higher-quality matches
 // compare as greater. Fixed tiers are compared by their integer tier value.
 // Two `Matches` variants are compared by their sub-scores. A `Matches`
@@ -111,1149 +698,4968 @@ impl PartialOrd for Ranking {
     }
 }
 
-/// Compute a fuzzy closeness ranking via greedy forward character matching.
-///
-/// For each character in `query`, scans forward through `candidate` to find it.
-/// If all query characters are found in order, the ranking is based on how
-/// closely together they appear (the "spread").
-///
-/// # Arguments
-///
-/// * `candidate` - The string being evaluated
-/// * `query` - The search query whose characters must appear in order
-///
-/// # Returns
-///
-/// - [`Ranking::NoMatch`] if any query character cannot be found in order
-/// - `Ranking::Matches(2.0)` when `spread == 0` (single-character query)
-/// - `Ranking::Matches(1.0 + 1.0 / spread)` otherwise, where `spread` is
-///   the distance (in char positions) between the first and last matched
-///   characters. The result is always in the range `(1.0, 2.0]`.
-///
-/// # Examples
-///
-/// ```
-/// use matchsorter::ranking::{get_closeness_ranking, Ranking};
-///
-/// // Fuzzy match: chars spread across the candidate
-/// let rank = get_closeness_ranking("playground", "plgnd");
-/// assert!(matches!(rank, Ranking::Matches(s) if s > 1.0 && s < 2.0));
-///
-/// // No match: query chars not present
-/// assert_eq!(get_closeness_ranking("abc", "xyz"), Ranking::NoMatch);
-///
-/// // Single char: spread is 0, returns upper-bound score
-/// assert_eq!(get_closeness_ranking("ab", "a"), Ranking::Matches(2.0));
-/// ```
-pub fn get_closeness_ranking(candidate: &str, query: &str) -> Ranking {
-    // Tracks our position as we scan forward through the candidate.
-    // `.chars()` gives us an iterator over Unicode scalar values, which is
-    // critical for correct character-by-character matching.
-    let mut candidate_chars = candidate.chars().enumerate();
+impl From<Ranking> for u8 {
+    /// The integer tier, from 7 (`CaseSensitiveEqual`) down to 0
+    /// (`NoMatch`). Every `Matches` sub-score maps to 1.
+    fn from(rank: Ranking) -> Self {
+        rank.tier_value()
+    }
+}
 
-    let mut first_match_index: Option<usize> = None;
-    let mut last_match_index: usize = 0;
+impl TryFrom<u8> for Ranking {
+    type Error = InvalidRankingTier;
 
-    for query_char in query.chars() {
-        // Scan forward through the remaining candidate characters to find
-        // the next occurrence of `query_char`. This greedy approach mirrors
-        // the JS `findMatchingCharacter` function.
-        let found = candidate_chars.find(|&(_, c)| c == query_char);
+    /// The ranking for an integer tier. Tier 1 yields `Matches(2.0)`, the
+    /// best fuzzy sub-score; use [`Ranking::try_from_tier_and_score`] to
+    /// restore a specific one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::{InvalidRankingTier, Ranking};
+    ///
+    /// assert_eq!(Ranking::try_from(5), Ok(Ranking::StartsWith));
+    /// assert_eq!(Ranking::try_from(1), Ok(Ranking::Matches(2.0)));
+    /// assert_eq!(Ranking::try_from(9), Err(InvalidRankingTier(9)));
+    /// assert_eq!(u8::from(Ranking::Contains), 3);
+    /// ```
+    fn try_from(tier: u8) -> Result<Self, Self::Error> {
+        Ok(match tier {
+            7 => Ranking::CaseSensitiveEqual,
+            6 => Ranking::Equal,
+            5 => Ranking::StartsWith,
+            4 => Ranking::WordStartsWith,
+            3 => Ranking::Contains,
+            2 => Ranking::Acronym,
+            1 => Ranking::Matches(2.0),
+            0 => Ranking::NoMatch,
+            _ => return Err(InvalidRankingTier(tier)),
+        })
+    }
+}
 
-        match found {
-            Some((pos, _)) => {
-                if first_match_index.is_none() {
-                    first_match_index = Some(pos);
-                }
-                last_match_index = pos;
-            }
-            None => return Ranking::NoMatch,
-        }
+/// Error returned by `Ranking::try_from(u8)` for a tier above 7.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidRankingTier(pub u8);
+
+impl fmt::Display for InvalidRankingTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid ranking tier {}, expected 0-7", self.0)
     }
+}
 
-    // `first_match_index` is `None` only if `query` was empty.
-    // An empty query trivially matches with spread 0.
-    let first = first_match_index.unwrap_or(0);
-    let spread = last_match_index - first;
+impl std::error::Error for InvalidRankingTier {}
 
-    if spread == 0 {
-        // Single-character query or empty query: return the upper bound.
-        // In the JS version this case produces `Infinity` from `1/0`,
-        // which is clamped. We use 2.0 as a safe maximum.
-        Ranking::Matches(2.0)
-    } else {
-        Ranking::Matches(1.0 + 1.0 / spread as f64)
+/// Error returned by [`Ranking::try_from_tier_and_score`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InvalidRanking {
+    /// The tier was above 7.
+    Tier(u8),
+    /// The tier was 1 (`Matches`) but the sub-score was outside
+    /// `(1.0, 2.0]`.
+    Score(f64),
+}
+
+impl From<InvalidRankingTier> for InvalidRanking {
+    fn from(err: InvalidRankingTier) -> Self {
+        InvalidRanking::Tier(err.0)
     }
 }
 
-/// Returns whether `c` is an acronym word-boundary delimiter.
-///
-/// Only space (`' '`) and hyphen (`'-'`) are recognized as delimiters.
-fn is_acronym_delimiter(c: char) -> bool {
-    c == ' ' || c == '-'
+impl fmt::Display for InvalidRanking {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidRanking::Tier(tier) => InvalidRankingTier(*tier).fmt(f),
+            InvalidRanking::Score(score) => {
+                write!(f, "invalid Matches sub-score {score}, expected (1.0, 2.0]")
+            }
+        }
+    }
 }
 
-/// Extract the acronym from a string by collecting word-initial characters.
-///
-/// Word boundaries are space (`' '`) and hyphen (`'-'`) only. The first
-/// character is always included; subsequent characters are included when
-/// the previous character was a delimiter and the current character is not
-/// itself a delimiter. The caller is responsible for lowercasing the input
-/// before calling.
-///
-/// # Arguments
-///
-/// * `s` - The input string to extract an acronym from
-///
-/// # Returns
-///
-/// A `String` containing the first character of each word
-///
-/// # Examples
-///
-/// ```
-/// use matchsorter::ranking::get_acronym;
-///
-/// assert_eq!(get_acronym("north-west airlines"), "nwa");
-/// assert_eq!(get_acronym("san francisco"), "sf");
-/// assert_eq!(get_acronym("single"), "s");
-/// assert_eq!(get_acronym(""), "");
-/// ```
-pub fn get_acronym(s: &str) -> String {
-    let mut chars = s.chars();
+impl std::error::Error for InvalidRanking {}
+
+impl fmt::Display for Ranking {
+    /// The variant name, with the sub-score in parentheses for `Matches`:
+    /// `"StartsWith"`, `"Matches(1.5)"`. [`Ranking::from_str`] parses it
+    /// back.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Ranking::CaseSensitiveEqual => "CaseSensitiveEqual",
+            Ranking::Equal => "Equal",
+            Ranking::StartsWith => "StartsWith",
+            Ranking::WordStartsWith => "WordStartsWith",
+            Ranking::Contains => "Contains",
+            Ranking::Acronym => "Acronym",
+            Ranking::Matches(score) => return write!(f, "Matches({score:?})"),
+            Ranking::NoMatch => "NoMatch",
+        };
+        f.write_str(name)
+    }
+}
 
-    // Empty string produces an empty acronym.
-    let first = match chars.next() {
-        Some(c) => c,
-        None => return String::new(),
-    };
+impl FromStr for Ranking {
+    type Err = ParseRankingError;
 
-    // Estimate capacity: one char per word. Use memchr for a fast count of
-    // delimiter bytes (space and hyphen are single-byte ASCII).
-    let word_count_estimate = 1 + memchr::memchr2_iter(b' ', b'-', s.as_bytes()).count();
-    let mut acronym = String::with_capacity(word_count_estimate);
+    /// Parse a variant name, ignoring surrounding whitespace.
+    ///
+    /// `"Matches"` alone yields `Matches(2.0)`, the best fuzzy sub-score,
+    /// as `Ranking::try_from(1)` does; `"Matches(1.5)"` sets the sub-score,
+    /// which must be finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    ///
+    /// assert_eq!(" StartsWith ".parse(), Ok(Ranking::StartsWith));
+    /// assert_eq!("Matches".parse(), Ok(Ranking::Matches(2.0)));
+    /// assert_eq!("Matches( 1.5 )".parse(), Ok(Ranking::Matches(1.5)));
+    /// assert!("starts_with".parse::<Ranking>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Ok(match s {
+            "CaseSensitiveEqual" => Ranking::CaseSensitiveEqual,
+            "Equal" => Ranking::Equal,
+            "StartsWith" => Ranking::StartsWith,
+            "WordStartsWith" => Ranking::WordStartsWith,
+            "Contains" => Ranking::Contains,
+            "Acronym" => Ranking::Acronym,
+            "Matches" => Ranking::Matches(2.0),
+            "NoMatch" => Ranking::NoMatch,
+            _ => {
+                let score = s
+                    .strip_prefix("Matches")
+                    .and_then(|rest| rest.trim_start().strip_prefix('('))
+                    .and_then(|rest| rest.strip_suffix(')'))
+                    .ok_or_else(|| ParseRankingError::UnknownName(s.to_owned()))?;
+                match score.trim().parse::<f64>() {
+                    Ok(score) if score.is_finite() => Ranking::Matches(score),
+                    _ => return Err(ParseRankingError::InvalidScore(score.trim().to_owned())),
+                }
+            }
+        })
+    }
+}
 
-    // First character is always included (virtual leading delimiter).
-    acronym.push(first);
+impl TryFrom<&str> for Ranking {
+    type Error = ParseRankingError;
 
-    // Track the previous character to detect word boundaries.
-    let mut prev = first;
-    for c in chars {
-        if is_acronym_delimiter(prev) && !is_acronym_delimiter(c) {
-            acronym.push(c);
-        }
-        prev = c;
+    /// Same as [`Ranking::from_str`].
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
     }
-
-    acronym
 }
 
-/// Lookup table mapping Latin-1 Supplement bytes (0x00..0x3F offset from U+00C0)
-/// to their base ASCII character after stripping diacritics. A value of 0
-/// means the character has no simple ASCII base (e.g. U+00D0 ETH, U+00D7
-/// MULTIPLICATION SIGN) and must be kept as-is.
-///
-/// Covers U+00C0 through U+00FF (the accented Latin characters that appear
-/// as 2-byte UTF-8 with lead byte 0xC3).
-const LATIN1_STRIP: [u8; 64] = [
-    // U+00C0..U+00CF
-    b'A', b'A', b'A', b'A', b'A', b'A', 0, b'C', // A-grave..AE(0)..C-cedilla
-    b'E', b'E', b'E', b'E', b'I', b'I', b'I', b'I', // E-grave..I-diaeresis
-    // U+00D0..U+00DF
-    0, b'N', b'O', b'O', b'O', b'O', b'O', 0, // ETH(0)..N-tilde..O-diaeresis..times(0)
-    0, b'U', b'U', b'U', b'U', b'Y', 0,
-    0, // O-stroke(0)..U-grave..Y-acute..thorn(0)..sharp-s(0)
-    // U+00E0..U+00EF
-    b'a', b'a', b'a', b'a', b'a', b'a', 0, b'c', // a-grave..ae(0)..c-cedilla
-    b'e', b'e', b'e', b'e', b'i', b'i', b'i', b'i', // e-grave..i-diaeresis
-    // U+00F0..U+00FF
-    0, b'n', b'o', b'o', b'o', b'o', b'o', 0, // eth(0)..n-tilde..o-diaeresis..divide(0)
-    0, b'u', b'u', b'u', b'u', b'y', 0,
-    b'y', // o-stroke(0)..u-grave..y-acute..thorn(0)..y-diaeresis
-];
-
-/// Fast diacritics stripping for strings whose non-ASCII characters are all
-/// in the Latin-1 Supplement range (U+00C0..U+00FF). Returns `None` if any
-/// character falls outside this range, signaling the caller to use the
-/// general NFD path.
-///
-/// When all non-ASCII chars are Latin-1 and at least one has a strippable
-/// accent, returns `Some(Cow::Owned(...))`. When none need stripping (e.g.
-/// all non-ASCII chars are U+00C6 AE, U+00D0 ETH, etc.), returns
-/// `Some(Cow::Borrowed(s))`.
-fn strip_latin1_diacritics(s: &str) -> Option<Cow<'_, str>> {
-    let bytes = s.as_bytes();
-    let mut needs_strip = false;
-    let mut i = 0;
+/// Error returned when parsing a [`Ranking`] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseRankingError {
+    /// The string was not a variant name. Holds the trimmed input.
+    UnknownName(String),
+    /// The sub-score of `"Matches(...)"` was not a finite number. Holds the
+    /// text between the parentheses.
+    InvalidScore(String),
+}
 
-    // First pass: verify all non-ASCII chars are 2-byte Latin-1 Supplement
-    // and check whether any stripping is needed.
-    while i < bytes.len() {
-        let b = bytes[i];
-        if b < 0x80 {
-            // ASCII byte: skip.
-            i += 1;
-        } else if b == 0xC3 && i + 1 < bytes.len() {
-            // 2-byte UTF-8 for U+00C0..U+00FF (lead byte 0xC3).
-            let trail = bytes[i + 1];
-            // Trail byte for U+00C0..U+00FF is 0x80..0xBF.
-            let offset = trail.wrapping_sub(0x80);
-            if offset < 64 && LATIN1_STRIP[offset as usize] != 0 {
-                needs_strip = true;
+impl fmt::Display for ParseRankingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseRankingError::UnknownName(name) => write!(
+                f,
+                "unknown ranking {name:?}, expected one of CaseSensitiveEqual, Equal, \
+                 StartsWith, WordStartsWith, Contains, Acronym, Matches, Matches(<score>) \
+                 or NoMatch"
+            ),
+            ParseRankingError::InvalidScore(score) => {
+                write!(
+                    f,
+                    "invalid Matches sub-score {score:?}, expected a finite number"
+                )
             }
-            i += 2;
-        } else if b == 0xC2 && i + 1 < bytes.len() {
-            // 2-byte UTF-8 for U+0080..U+00BF (non-break space, soft
-            // hyphen, etc.) -- no diacritics, but still Latin-1. Skip.
-            i += 2;
-        } else {
-            // b >= 0x80 (all remaining non-ASCII bytes): multi-byte char
-            // outside Latin-1 Supplement. Bail out to the general NFD path.
-            return None;
         }
     }
+}
 
-    if !needs_strip {
-        return Some(Cow::Borrowed(s));
+impl std::error::Error for ParseRankingError {}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Ranking {
+    /// Serializes as the [`Display`](fmt::Display) string.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
     }
+}
 
-    // Second pass: build the stripped string.
-    let mut result = String::with_capacity(s.len());
-    let mut j = 0;
-    while j < bytes.len() {
-        let b = bytes[j];
-        if b < 0x80 {
-            result.push(b as char);
-            j += 1;
-        } else if b == 0xC3 && j + 1 < bytes.len() {
-            let trail = bytes[j + 1];
-            let offset = trail.wrapping_sub(0x80);
-            if offset < 64 {
-                let base = LATIN1_STRIP[offset as usize];
-                if base != 0 {
-                    result.push(base as char);
-                } else {
-                    // Non-strippable Latin-1 char (AE, ETH, etc.): keep
-                    // original 2-byte sequence.
-                    // SAFETY: we verified this is valid 2-byte UTF-8 above.
-                    result.push_str(
-                        std::str::from_utf8(&bytes[j..j + 2]).expect("valid 2-byte UTF-8 sequence"),
-                    );
-                }
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Ranking {
+    /// Deserializes from a string accepted by [`Ranking::from_str`].
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RankingVisitor;
+
+        impl serde::de::Visitor<'_> for RankingVisitor {
+            type Value = Ranking;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a ranking name such as \"StartsWith\" or \"Matches(1.5)\"")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Ranking, E> {
+                s.parse().map_err(E::custom)
             }
-            j += 2;
-        } else {
-            // 0xC2 lead byte or other: keep the 2-byte sequence.
-            result.push_str(
-                std::str::from_utf8(&bytes[j..j + 2]).expect("valid 2-byte UTF-8 sequence"),
-            );
-            j += 2;
         }
+
+        deserializer.deserialize_str(RankingVisitor)
     }
+}
 
-    Some(Cow::Owned(result))
+/// Percentages assigned to each tier by [`Ranking::as_pct_with`] and
+/// [`Ranking::from_pct_with`].
+///
+/// Values should increase with tier quality, and `matches_max` should not
+/// exceed `acronym`, or the mapping stops being monotonic. `NoMatch` is
+/// always `0.0`.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{PctScale, Ranking};
+///
+/// let scale = PctScale {
+///     equal: 95.0,
+///     ..PctScale::default()
+/// };
+/// assert_eq!(Ranking::Equal.as_pct_with(&scale), 95.0);
+/// assert_eq!(Ranking::from_pct_with(95.0, &scale), Ranking::Equal);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PctScale {
+    /// Percentage for [`Ranking::CaseSensitiveEqual`]. Default `100.0`.
+    pub case_sensitive_equal: f64,
+    /// Percentage for [`Ranking::Equal`]. Default `85.0`.
+    pub equal: f64,
+    /// Percentage for [`Ranking::StartsWith`]. Default `71.0`.
+    pub starts_with: f64,
+    /// Percentage for [`Ranking::WordStartsWith`]. Default `57.0`.
+    pub word_starts_with: f64,
+    /// Percentage for [`Ranking::Contains`]. Default `43.0`.
+    pub contains: f64,
+    /// Percentage for [`Ranking::Acronym`]. Default `28.0`.
+    pub acronym: f64,
+    /// Percentage for `Matches(2.0)`; `Matches(s)` maps linearly to
+    /// `(s - 1.0) * matches_max`. Default `14.0`.
+    pub matches_max: f64,
 }
 
-/// Prepare a string for comparison by optionally stripping diacritics.
+impl Default for PctScale {
+    fn default() -> Self {
+        Self {
+            case_sensitive_equal: 100.0,
+            equal: 85.0,
+            starts_with: 71.0,
+            word_starts_with: 57.0,
+            contains: 43.0,
+            acronym: 28.0,
+            matches_max: 14.0,
+        }
+    }
+}
+
+/// Returns a lowercase, underscore-separated label for `rank`'s tier.
 ///
-/// When `keep_diacritics` is `false`, applies Unicode NFD decomposition and
-/// removes combining marks (`General_Category = Mark`), effectively stripping
-/// accents and diacritical marks from characters. When `keep_diacritics` is
-/// `true`, the original string is returned unchanged.
+/// Intended for logs and analytics, where the `Debug` output (which includes
+/// the `Matches` sub-score) is too noisy. Labels are `"exact"`, `"equal"`,
+/// `"starts_with"`, `"word_starts_with"`, `"contains"`, `"acronym"`,
+/// `"fuzzy"` (any `Matches` sub-score) and `"no_match"`.
 ///
-/// Returns [`Cow::Borrowed`] when no modification is needed (either because
-/// `keep_diacritics` is `true`, or because the string contains no diacritics
-/// to strip). Only allocates a new `String` ([`Cow::Owned`]) when characters
-/// are actually removed.
+/// # Examples
+///
+/// ```
+/// use matchsorter::{Ranking, ranking_tier_label};
+///
+/// assert_eq!(ranking_tier_label(Ranking::WordStartsWith), "word_starts_with");
+/// assert_eq!(ranking_tier_label(Ranking::Matches(1.25)), "fuzzy");
+/// ```
+pub fn ranking_tier_label(rank: Ranking) -> &'static str {
+    match rank {
+        Ranking::CaseSensitiveEqual => "exact",
+        Ranking::Equal => "equal",
+        Ranking::StartsWith => "starts_with",
+        Ranking::WordStartsWith => "word_starts_with",
+        Ranking::Contains => "contains",
+        Ranking::Acronym => "acronym",
+        Ranking::Matches(_) => "fuzzy",
+        Ranking::NoMatch => "no_match",
+    }
+}
+
+/// Compute a fuzzy closeness ranking via greedy forward character matching.
+///
+/// For each character in `query`, scans forward through `candidate` to find it.
+/// If all query characters are found in order, the ranking is based on how
+/// closely together they appear (the "spread").
 ///
 /// # Arguments
 ///
-/// * `s` - The input string to prepare
-/// * `keep_diacritics` - If `true`, skip diacritics stripping entirely
+/// * `candidate` - The string being evaluated
+/// * `query` - The search query whose characters must appear in order
 ///
 /// # Returns
 ///
-/// A [`Cow<'_, str>`] that is either borrowed from the input or an owned
-/// string with combining marks removed.
+/// - [`Ranking::NoMatch`] if any query character cannot be found in order
+/// - `Ranking::Matches(2.0)` when `spread == 0` (single-character query)
+/// - `Ranking::Matches(1.0 + 1.0 / spread)` otherwise, where `spread` is
+///   the distance (in char positions) between the first and last matched
+///   characters. The result is always in the range `(1.0, 2.0]`.
 ///
 /// # Examples
 ///
 /// ```
-/// use matchsorter::ranking::prepare_value_for_comparison;
+/// use matchsorter::ranking::{get_closeness_ranking, Ranking};
 ///
-/// // Stripping an accent produces a new string
-/// let result = prepare_value_for_comparison("cafe\u{0301}", false);
-/// assert_eq!(result, "cafe");
-/// assert!(matches!(result, std::borrow::Cow::Owned(_)));
+/// // Fuzzy match: chars spread across the candidate
+/// let rank = get_closeness_ranking("playground", "plgnd");
+/// assert!(matches!(rank, Ranking::Matches(s) if s > 1.0 && s < 2.0));
 ///
-/// // ASCII strings are returned borrowed (no allocation)
-/// let result = prepare_value_for_comparison("cafe", false);
-/// assert_eq!(result, "cafe");
-/// assert!(matches!(result, std::borrow::Cow::Borrowed(_)));
+/// // No match: query chars not present
+/// assert_eq!(get_closeness_ranking("abc", "xyz"), Ranking::NoMatch);
 ///
-/// // With keep_diacritics=true, the original is always returned
-/// let result = prepare_value_for_comparison("cafe\u{0301}", true);
-/// assert_eq!(result, "cafe\u{0301}");
-/// assert!(matches!(result, std::borrow::Cow::Borrowed(_)));
+/// // Single char: spread is 0, returns upper-bound score
+/// assert_eq!(get_closeness_ranking("ab", "a"), Ranking::Matches(2.0));
 /// ```
-pub fn prepare_value_for_comparison(s: &str, keep_diacritics: bool) -> Cow<'_, str> {
-    if keep_diacritics {
-        return Cow::Borrowed(s);
-    }
+pub fn get_closeness_ranking(candidate: &str, query: &str) -> Ranking {
+    get_closeness_ranking_with(candidate, query, FuzzyMode::Greedy, false, None)
+}
 
-    // Fast path: ASCII strings never contain diacritics or combining marks.
-    if s.is_ascii() {
-        return Cow::Borrowed(s);
-    }
+/// [`get_closeness_ranking`] with the spread counted in grapheme clusters
+/// rather than chars.
+///
+/// Query characters are matched exactly as in [`get_closeness_ranking`],
+/// so a candidate matches in both or in neither. Only the distance between
+/// the first and last matched characters is measured in extended grapheme
+/// clusters, so a base letter followed by combining marks counts once:
+/// the NFD `"e\u{301}"` and the NFC `"\u{e9}"` are both one grapheme,
+/// and visually identical strings get the same score whichever
+/// normalization form they use.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::ranking::{get_closeness_ranking, get_closeness_ranking_grapheme};
+///
+/// let nfc = "caf\u{e9}s";
+/// let nfd = "cafe\u{301}s";
+/// assert_eq!(
+///     get_closeness_ranking_grapheme(nfc, "cs"),
+///     get_closeness_ranking_grapheme(nfd, "cs"),
+/// );
+/// // Counting chars, the combining mark widens the NFD spread.
+/// assert_ne!(get_closeness_ranking(nfc, "cs"), get_closeness_ranking(nfd, "cs"));
+/// ```
+pub fn get_closeness_ranking_grapheme(candidate: &str, query: &str) -> Ranking {
+    get_closeness_ranking_with(candidate, query, FuzzyMode::Greedy, true, None)
+}
 
-    // Try the Latin-supplement fast path first: if every non-ASCII char is
-    // in U+00C0..U+00FF (2-byte UTF-8: 0xC3 lead byte), we can strip
-    // diacritics via a cheap lookup table instead of running full NFD.
-    // This covers the vast majority of accented Latin text (French, German,
-    // Spanish, Portuguese, etc.).
-    if let Some(result) = strip_latin1_diacritics(s) {
-        return result;
+/// [`get_closeness_ranking`] that also returns the char positions of the
+/// matched characters.
+///
+/// Positions index into `candidate.chars()`, not bytes, and increase
+/// strictly. `NoMatch` (and an empty query) come with an empty list. Like
+/// [`get_closeness_ranking`], the comparison is exact; lowercase both
+/// strings first for case-insensitive highlighting.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::ranking::{Ranking, get_closeness_ranking_with_positions};
+///
+/// let (rank, positions) = get_closeness_ranking_with_positions("playground", "plgnd");
+/// assert!(matches!(rank, Ranking::Matches(_)));
+/// assert_eq!(positions, vec![0, 1, 4, 8, 9]);
+///
+/// let (rank, positions) = get_closeness_ranking_with_positions("abc", "xyz");
+/// assert_eq!(rank, Ranking::NoMatch);
+/// assert!(positions.is_empty());
+/// ```
+pub fn get_closeness_ranking_with_positions(candidate: &str, query: &str) -> (Ranking, Vec<usize>) {
+    let mut positions = Vec::new();
+    if !greedy_match(candidate, query, |pos| positions.push(pos)) {
+        return (Ranking::NoMatch, Vec::new());
     }
+    (get_closeness_ranking(candidate, query), positions)
+}
 
-    // General path for non-Latin-1 non-ASCII text (CJK, extended Latin,
-    // combining marks, etc.): single-pass NFD with lazy allocation.
-    // Iterates the NFD output once. As long as no combining mark is found,
-    // no allocation occurs. When the first mark appears, allocates a buffer,
-    // backfills the non-mark prefix, then continues filtering.
-    let mut nfd = s.nfd();
-    let mut prefix_len: usize = 0;
+/// [`get_closeness_ranking_with_positions`] with the positions converted to
+/// UTF-8 byte ranges of `candidate`.
+///
+/// Each range covers one matched character, so `&candidate[range]` is
+/// always valid and yields that character.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::ranking::get_closeness_ranking_byte_ranges;
+///
+/// let candidate = "cr\u{00e8}me";
+/// let (_, ranges) = get_closeness_ranking_byte_ranges(candidate, "c\u{00e8}e");
+/// assert_eq!(ranges, vec![0..1, 2..4, 5..6]);
+/// assert_eq!(&candidate[ranges[1].clone()], "\u{00e8}");
+/// ```
+pub fn get_closeness_ranking_byte_ranges(
+    candidate: &str,
+    query: &str,
+) -> (Ranking, Vec<Range<usize>>) {
+    let (rank, positions) = get_closeness_ranking_with_positions(candidate, query);
+    let mut wanted = positions.into_iter().peekable();
+    let mut ranges = Vec::with_capacity(wanted.len());
+    for (pos, (byte, c)) in candidate.char_indices().enumerate() {
+        if wanted.peek() == Some(&pos) {
+            wanted.next();
+            ranges.push(byte..byte + c.len_utf8());
+        }
+    }
+    (rank, ranges)
+}
 
-    loop {
-        match nfd.next() {
-            Some(c) if is_combining_mark(c) => break,
-            Some(_) => {
-                prefix_len += 1;
+/// How the `Matches` tier assigns query characters to candidate positions.
+///
+/// The mode only decides which in-order occurrence of each query character
+/// is used, and therefore the spread fed into the sub-score. Whether a
+/// candidate reaches the `Matches` tier at all is the same in both modes.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{FuzzyMode, MatchSorterOptions, match_sorter};
+///
+/// let items = ["axxab", "axb"];
+/// let opts = MatchSorterOptions {
+///     fuzzy_mode: FuzzyMode::Optimal,
+///     ..Default::default()
+/// };
+/// // Optimal mode sees the adjacent "ab" at the end of "axxab".
+/// assert_eq!(match_sorter(&items, "ab", &opts), vec![&"axxab", &"axb"]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FuzzyMode {
+    /// Match each query character at its first occurrence after the
+    /// previous one. Fast, but may report a wider spread than necessary
+    /// (`"aab"` queried with `"ab"` has spread 2).
+    #[default]
+    Greedy,
+    /// Choose the assignment with the smallest spread between the first
+    /// and last matched characters (`"aab"` queried with `"ab"` has
+    /// spread 1). Costs `O(query_len * candidate_len)` per candidate.
+    Optimal,
+}
+
+/// Signature of a custom `Matches` sub-score formula.
+///
+/// Called as `scorer(first_pos, last_pos, candidate_len, query_len)` with
+/// char positions of the first and last matched query characters and the
+/// char lengths of the candidate and query (grapheme positions and lengths
+/// in grapheme matching mode).
+pub(crate) type ClosenessScorer = dyn Fn(usize, usize, usize, usize) -> f64;
+
+/// Signature of a per-language normalizer; see
+/// [`MatchSorterOptions::language_normalizers`](crate::MatchSorterOptions::language_normalizers).
+pub(crate) type LanguageNormalizer = dyn Fn(&str) -> String;
+
+/// Signature of a custom word splitter for the `WordStartsWith` tier.
+///
+/// Returns the byte range of each word in the given candidate.
+pub(crate) type WordSplitter = dyn Fn(&str) -> Vec<Range<usize>>;
+
+/// Split `s` into words at separators and camelCase humps.
+///
+/// Words are runs of alphanumeric characters; everything else (spaces,
+/// punctuation, underscores, emoji) separates them. Within a run a new word
+/// starts at an uppercase letter following a lowercase letter or digit
+/// (`"fooBar"`), and at the last capital of an acronym followed by a
+/// lowercase letter (`"HTMLParser"` splits before `"Parser"`). Returns the
+/// byte range of each word.
+///
+/// Intended for [`MatchSorterOptions::word_splitter`], which passes the
+/// candidate in its original case.
+///
+/// [`MatchSorterOptions::word_splitter`]: crate::MatchSorterOptions::word_splitter
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::ranking::camel_case_splitter;
+///
+/// let s = "parseHTMLResponse_v2";
+/// let words: Vec<&str> = camel_case_splitter(s).into_iter().map(|r| &s[r]).collect();
+/// assert_eq!(words, ["parse", "HTML", "Response", "v2"]);
+/// ```
+pub fn camel_case_splitter(s: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut chars = s.char_indices().peekable();
+    let mut prev: Option<char> = None;
+    while let Some((byte, c)) = chars.next() {
+        if !c.is_alphanumeric() {
+            if let Some(begin) = start.take() {
+                words.push(begin..byte);
             }
-            None => {
-                // No combining marks found: input is unchanged after NFD.
-                return Cow::Borrowed(s);
+            prev = None;
+            continue;
+        }
+        let next_is_lower = chars.peek().is_some_and(|&(_, n)| n.is_lowercase());
+        let hump = prev.is_some_and(|p| {
+            c.is_uppercase()
+                && (p.is_lowercase() || p.is_numeric() || (p.is_uppercase() && next_is_lower))
+        });
+        match start {
+            Some(begin) if hump => {
+                words.push(begin..byte);
+                start = Some(byte);
             }
+            Some(_) => {}
+            None => start = Some(byte),
         }
+        prev = Some(c);
     }
+    if let Some(begin) = start {
+        words.push(begin..s.len());
+    }
+    words
+}
 
-    // A combining mark was found. Allocate and collect.
-    let mut result = String::with_capacity(s.len());
-
-    // Backfill non-mark chars emitted before the first mark.
-    for (i, c) in s.nfd().enumerate() {
-        if i >= prefix_len {
-            break;
-        }
-        result.push(c);
+/// Whether the prepared `candidate` provably cannot match `pq`: the query
+/// starts with an ASCII character that `candidate`, also ASCII, contains in
+/// neither case.
+///
+/// Every tier needs each query character somewhere in the lowercased
+/// candidate, so such a candidate ranks `NoMatch`. Non-ASCII candidates are
+/// never rejected, since characters like the Kelvin sign lowercase to ASCII
+/// letters; nor are they under a key's own case rules.
+fn prefilter_rejects(candidate: &str, pq: &PreparedQuery, config: &RankingConfig<'_>) -> bool {
+    let Some(&first) = pq.lower.as_bytes().first() else {
+        return false;
+    };
+    if !first.is_ascii() || config.language.is_some() {
+        return false;
     }
+    let bytes = candidate.as_bytes();
+    let found = if first.is_ascii_lowercase() {
+        memchr::memchr2(first, first.to_ascii_uppercase(), bytes)
+    } else {
+        memchr::memchr(first, bytes)
+    };
+    found.is_none() && candidate.is_ascii()
+}
 
-    // Continue filtering the rest of the NFD iterator.
-    for c in nfd {
-        if !is_combining_mark(c) {
-            result.push(c);
+/// Map sorted byte offsets in `s` to the matching offsets in its lowercased
+/// form, as built by [`RankingConfig::lowercase_into`]. Offsets that do not
+/// fall on a character boundary of `s` are dropped.
+fn lowercase_offsets(s: &str, offsets: &[usize], config: &RankingConfig<'_>) -> Vec<usize> {
+    // ASCII lowercasing never changes byte lengths, except under a
+    // language's own case rules or Turkic folding.
+    if s.is_ascii() && config.folds_ascii_bytewise() {
+        return offsets.to_vec();
+    }
+    let mut mapped = Vec::with_capacity(offsets.len());
+    let mut wanted = offsets.iter().copied().peekable();
+    let mut lower = 0;
+    for (byte, c) in s.char_indices() {
+        while let Some(offset) = wanted.next_if(|&offset| offset <= byte) {
+            if offset == byte {
+                mapped.push(lower);
+            }
         }
+        lower += config.lowercase_len(c);
     }
-
-    Cow::Owned(result)
+    mapped
 }
 
-/// Pre-computed query data for amortizing repeated per-item ranking calls.
-///
-/// Caches the prepared (diacritics-stripped) query, its lowercased form,
-/// character count, and an ASCII flag so that `match_sorter` can avoid
-/// redundant work when ranking thousands of candidates against the same query.
+/// [`get_closeness_ranking`] with a selectable [`FuzzyMode`], a choice of
+/// units and an optional custom sub-score formula.
 ///
-/// Constructed once before the ranking loop via [`PreparedQuery::new`] and
-/// passed by reference to [`get_match_ranking_prepared`].
-pub(crate) struct PreparedQuery {
-    /// The query after optional diacritics stripping.
-    prepared: String,
-    /// Lowercased version of the prepared query.
-    pub(crate) lower: String,
-    /// Character count of the lowercased query (cached to avoid repeated
-    /// `.chars().count()` calls).
-    char_count: usize,
+/// With `graphemes`, positions and lengths are counted in grapheme
+/// clusters instead of chars; the matched characters are the same either
+/// way. When `scorer` is `None`, the default `1.0 + 1.0 / spread` formula
+/// is used. Otherwise the scorer's result is clamped into `(1.0, 2.0]` (NaN
+/// maps to the lower end) so a custom formula can never escape the
+/// `Matches` tier.
+pub(crate) fn get_closeness_ranking_with(
+    candidate: &str,
+    query: &str,
+    mode: FuzzyMode,
+    graphemes: bool,
+    scorer: Option<&ClosenessScorer>,
+) -> Ranking {
+    let span = match mode {
+        FuzzyMode::Greedy => greedy_match_span(candidate, query),
+        FuzzyMode::Optimal => optimal_match_span(candidate, query),
+    };
+    match span {
+        Some(span) => score_span(candidate, query, span, graphemes, scorer),
+        None => Ranking::NoMatch,
+    }
 }
 
-impl PreparedQuery {
-    /// Create a new `PreparedQuery` by preparing and lowercasing the query once.
-    ///
-    /// # Arguments
-    ///
-    /// * `query` - The raw search query string
-    /// * `keep_diacritics` - If `true`, skip diacritics stripping
-    pub(crate) fn new(query: &str, keep_diacritics: bool) -> Self {
-        let prepared = prepare_value_for_comparison(query, keep_diacritics).into_owned();
-        let lower = prepared.to_lowercase();
-        // ASCII fast path: byte length equals character count for ASCII strings.
-        let char_count = if lower.is_ascii() {
-            lower.len()
+/// The `Matches` ranking of a match spanning the char positions `span` of
+/// `candidate`.
+fn score_span(
+    candidate: &str,
+    query: &str,
+    span: (usize, usize),
+    graphemes: bool,
+    scorer: Option<&ClosenessScorer>,
+) -> Ranking {
+    let (first, last_match_index) = if graphemes {
+        grapheme_span(candidate, span)
+    } else {
+        span
+    };
+    let spread = last_match_index - first;
+
+    if let Some(scorer) = scorer {
+        let (candidate_len, query_len) = if graphemes {
+            (
+                candidate.graphemes(true).count(),
+                query.graphemes(true).count(),
+            )
         } else {
-            lower.chars().count()
+            (candidate.chars().count(), query.chars().count())
         };
-        Self {
-            prepared,
-            lower,
-            char_count,
-        }
+        let score = scorer(first, last_match_index, candidate_len, query_len);
+        return Ranking::Matches(clamp_closeness_score(score));
     }
-}
 
-/// Lowercase `s` into `buf`, reusing the buffer's allocation.
-///
-/// When `s` is ASCII, uses a byte-level fast path that avoids Unicode
-/// case-mapping tables entirely. For non-ASCII input, falls back to
-/// `char::to_lowercase()`.
-///
-/// Both branches include an already-lowercase early exit: if the string
-/// contains no uppercase characters, it is bulk-copied into `buf` via
-/// `push_str` instead of iterating per-character.
-fn lowercase_into(s: &str, buf: &mut String) {
-    buf.clear();
-    if s.is_ascii() {
-        buf.reserve(s.len());
-        if s.as_bytes().iter().all(|b| !b.is_ascii_uppercase()) {
-            // Already lowercase: bulk-copy avoids per-byte case mapping.
-            buf.push_str(s);
-        } else {
-            // ASCII bytes are single-byte UTF-8, so lowercasing byte-by-byte
-            // and casting to char is safe and avoids Unicode lookup tables.
-            buf.extend(s.as_bytes().iter().map(|&b| b.to_ascii_lowercase() as char));
-        }
+    if spread == 0 {
+        // Single-character query or empty query: return the upper bound.
+        // In the JS version this case produces `Infinity` from `1/0`,
+        // which is clamped. We use 2.0 as a safe maximum.
+        Ranking::Matches(2.0)
     } else {
-        buf.reserve(s.len());
-        if s.chars().all(|c| !c.is_uppercase()) {
-            // Already lowercase: bulk-copy avoids per-char case mapping.
-            buf.push_str(s);
-        } else {
-            for c in s.chars() {
-                for lc in c.to_lowercase() {
-                    buf.push(lc);
-                }
-            }
-        }
+        Ranking::Matches(1.0 + 1.0 / spread as f64)
     }
 }
 
-/// Inner hot-path ranking function using pre-prepared query data and a
-/// reusable candidate buffer.
+/// Fuzzy-match `candidate` with several greedy scans and report how much
+/// they agree.
 ///
-/// This avoids redundant query preparation, lowercasing, and allocation
-/// when called repeatedly in a loop (e.g., inside `match_sorter`).
+/// Greedy matching takes the first occurrence of each query character, so
+/// its sub-score depends on where the scan happens to start. This runs up
+/// to `n_windows` scans, the `k`-th starting at the `k`-th occurrence of the
+/// query's first character, and returns the best ranking among them
+/// together with a confidence in `[0.0, 1.0]`: one minus the coefficient
+/// of variation of the windows' sub-scores (each taken above the `1.0`
+/// floor), so `1.0` when every window scores the same and lower the more
+/// they disagree. Only windows from which the whole query can be matched
+/// count.
+///
+/// The first window is the plain greedy scan, so with `n_windows` of `0`
+/// or `1` the ranking equals [`get_closeness_ranking`]'s. More windows are
+/// mostly useful for candidates much longer than the query. A candidate
+/// that does not match, or a query without a first character, yields a
+/// confidence of `1.0`, as there are no alignments to disagree.
 ///
 /// # Arguments
 ///
-/// * `test_string` - The candidate string being evaluated
-/// * `pq` - Pre-computed query data
-/// * `keep_diacritics` - If `true`, skip diacritics stripping on the candidate
-/// * `candidate_buf` - Reusable buffer for lowercasing the candidate
-/// * `finder` - SIMD-accelerated substring searcher for the lowercased query,
-///   or `None` when the query is empty (since `memmem` panics on empty needles)
-pub(crate) fn get_match_ranking_prepared(
-    test_string: &str,
-    pq: &PreparedQuery,
-    keep_diacritics: bool,
-    candidate_buf: &mut String,
-    finder: Option<&memchr::memmem::Finder<'_>>,
-) -> Ranking {
-    // Prepare candidate (strip diacritics if requested).
-    let candidate = prepare_value_for_comparison(test_string, keep_diacritics);
+/// * `candidate` - The string being evaluated
+/// * `query` - The search query whose characters must appear in order
+/// * `n_windows` - The maximum number of greedy scans to run
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::ranking::{Ranking, get_closeness_ranking, get_closeness_ranking_ensemble};
+///
+/// // The first "a" starts a wide match; the second one a tight match.
+/// let candidate = "a-------b-ab";
+/// assert_eq!(get_closeness_ranking(candidate, "ab"), Ranking::Matches(1.0 + 1.0 / 8.0));
+/// let (rank, confidence) = get_closeness_ranking_ensemble(candidate, "ab", 4);
+/// assert_eq!(rank, Ranking::Matches(2.0));
+/// assert!(confidence < 0.5);
+///
+/// // A single alignment leaves nothing to disagree about.
+/// let (rank, confidence) = get_closeness_ranking_ensemble("playground", "plgnd", 4);
+/// assert_eq!(rank, get_closeness_ranking("playground", "plgnd"));
+/// assert_eq!(confidence, 1.0);
+/// ```
+pub fn get_closeness_ranking_ensemble(
+    candidate: &str,
+    query: &str,
+    n_windows: usize,
+) -> (Ranking, f64) {
+    let (rank, confidence, _) = ensemble_ranking(candidate, query, n_windows, false, None);
+    (rank, confidence)
+}
 
-    // Step 1: If query has more characters than candidate, no match is possible.
-    // ASCII fast path: byte length equals character count for ASCII strings.
-    let candidate_char_count = if candidate.is_ascii() {
-        candidate.len()
+/// [`get_closeness_ranking_ensemble`] with grapheme spreads and a custom
+/// scorer, also returning the char span of the best window.
+pub(crate) fn ensemble_ranking(
+    candidate: &str,
+    query: &str,
+    n_windows: usize,
+    graphemes: bool,
+    scorer: Option<&ClosenessScorer>,
+) -> (Ranking, f64, Option<(usize, usize)>) {
+    let mut best: Option<(Ranking, (usize, usize))> = None;
+    let mut scores = Vec::new();
+    for span in window_spans(candidate, query, n_windows) {
+        let rank = score_span(candidate, query, span, graphemes, scorer);
+        if let Ranking::Matches(score) = rank {
+            scores.push(score - 1.0);
+        }
+        if best.is_none_or(|(best_rank, _)| rank > best_rank) {
+            best = Some((rank, span));
+        }
+    }
+    let Some((rank, span)) = best else {
+        return (Ranking::NoMatch, 1.0, None);
+    };
+    let n = scores.len() as f64;
+    let mean = scores.iter().sum::<f64>() / n;
+    let variance = scores.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n;
+    let confidence = if variance == 0.0 {
+        1.0
     } else {
-        candidate.chars().count()
+        (1.0 - variance.sqrt() / mean).clamp(0.0, 1.0)
     };
-    if pq.char_count > candidate_char_count {
-        return Ranking::NoMatch;
+    (rank, confidence, Some(span))
+}
+
+/// Char spans of greedy matches of `query` starting at each of the first
+/// `n_windows` occurrences of its first character in `candidate` (at least
+/// one scan), stopping at the first start from which `query` cannot be
+/// matched.
+fn window_spans(candidate: &str, query: &str, n_windows: usize) -> Vec<(usize, usize)> {
+    let Some(first_char) = query.chars().next() else {
+        return greedy_match_span(candidate, query).into_iter().collect();
+    };
+    let mut spans = Vec::new();
+    let starts = candidate
+        .char_indices()
+        .enumerate()
+        .filter(|&(_, (_, c))| c == first_char);
+    for (pos, (byte, _)) in starts.take(n_windows.max(1)) {
+        let Some((first, last)) = greedy_match_span(&candidate[byte..], query) else {
+            break;
+        };
+        spans.push((pos + first, pos + last));
     }
+    spans
+}
 
-    // Step 2: Case-sensitive exact equality on the prepared strings.
-    if *candidate == *pq.prepared {
-        return Ranking::CaseSensitiveEqual;
+/// Char positions of the first and last query characters when each is
+/// matched at its first occurrence after the previous one, or `None` if
+/// `query` is not a subsequence of `candidate`.
+///
+/// An empty query trivially matches at `(0, 0)`.
+fn greedy_match_span(candidate: &str, query: &str) -> Option<(usize, usize)> {
+    let mut first_match_index: Option<usize> = None;
+    let mut last_match_index: usize = 0;
+    let found = greedy_match(candidate, query, |pos| {
+        if first_match_index.is_none() {
+            first_match_index = Some(pos);
+        }
+        last_match_index = pos;
+    });
+    found.then(|| (first_match_index.unwrap_or(0), last_match_index))
+}
+
+/// Convert a span of char positions in `s` to the indices of the grapheme
+/// clusters containing those chars.
+fn grapheme_span(s: &str, (first, last): (usize, usize)) -> (usize, usize) {
+    // Every ASCII char is a grapheme of its own, except "\r\n".
+    if s.is_ascii() && !s.contains('\r') {
+        return (first, last);
+    }
+    let mut span = (0, 0);
+    let mut chars_before = 0;
+    for (index, grapheme) in s.graphemes(true).enumerate() {
+        let chars_after = chars_before + grapheme.chars().count();
+        if (chars_before..chars_after).contains(&first) {
+            span.0 = index;
+        }
+        if (chars_before..chars_after).contains(&last) {
+            span.1 = index;
+            break;
+        }
+        chars_before = chars_after;
     }
+    span
+}
 
-    // Step 3: Lowercase candidate into reusable buffer (avoids allocation).
-    lowercase_into(&candidate, candidate_buf);
+/// Match each query character at its first occurrence in `candidate`
+/// after the previous one, calling `on_match` with each matched char
+/// position. Returns `false` if `query` is not a subsequence of
+/// `candidate` (after reporting the positions matched so far).
+fn greedy_match(candidate: &str, query: &str, mut on_match: impl FnMut(usize)) -> bool {
+    // Tracks our position as we scan forward through the candidate.
+    // `.chars()` gives us an iterator over Unicode scalar values, which is
+    // critical for correct character-by-character matching.
+    let mut candidate_chars = candidate.chars().enumerate();
 
-    // Steps 4-8: Substring search.
-    if let Some(finder) = finder {
-        // Use SIMD-accelerated memmem for substring search.
-        let candidate_bytes = candidate_buf.as_bytes();
-        let mut iter = finder.find_iter(candidate_bytes);
+    for query_char in query.chars() {
+        // Scan forward through the remaining candidate characters to find
+        // the next occurrence of `query_char`. This greedy approach mirrors
+        // the JS `findMatchingCharacter` function.
+        let Some((pos, _)) = candidate_chars.find(|&(_, c)| c == query_char) else {
+            return false;
+        };
+        on_match(pos);
+    }
 
-        if let Some(first) = iter.next() {
-            if first == 0 {
-                // Step 5: Substring at byte position 0 with equal byte
-                // lengths means the lowercased strings are identical -> Equal.
-                if candidate_buf.len() == pq.lower.len() {
-                    return Ranking::Equal;
-                }
-                // Step 6: Starts with query but is longer -> StartsWith.
-                return Ranking::StartsWith;
-            }
+    true
+}
 
-            // Step 7: Check if any match position sits at a word boundary.
-            // A word boundary means the byte immediately before the match
-            // is a space (0x20). We already know first > 0 here.
-            if candidate_bytes[first - 1] == b' ' {
-                return Ranking::WordStartsWith;
+/// Char positions of the first and last query characters for the in-order
+/// assignment with the smallest spread, or `None` if `query` is not a
+/// subsequence of `candidate`. Ties keep the leftmost window.
+///
+/// Dynamic programming over candidate positions: `starts[i]` holds the
+/// latest start position from which `query[..=i]` can be matched ending at
+/// or before the current position. The latest start is always the best
+/// one, so a single row of `query_len` entries suffices and the scan costs
+/// `O(query_len * candidate_len)`.
+fn optimal_match_span(candidate: &str, query: &str) -> Option<(usize, usize)> {
+    let query_chars: Vec<char> = query.chars().collect();
+    let Some(&last_query_char) = query_chars.last() else {
+        return Some((0, 0));
+    };
+    let mut starts: Vec<Option<usize>> = vec![None; query_chars.len()];
+    let mut best: Option<(usize, usize)> = None;
+
+    for (pos, c) in candidate.chars().enumerate() {
+        // Walk the query backwards so `starts[i - 1]` still refers to a
+        // strictly earlier candidate position.
+        for i in (0..query_chars.len()).rev() {
+            if query_chars[i] != c {
+                continue;
             }
-            // Check remaining match positions lazily.
-            for pos in iter {
-                if pos > 0 && candidate_bytes[pos - 1] == b' ' {
-                    return Ranking::WordStartsWith;
-                }
+            let start = if i == 0 { Some(pos) } else { starts[i - 1] };
+            if start.is_some() {
+                starts[i] = start;
             }
-
-            // Step 8: A substring match exists but not at a word boundary.
-            return Ranking::Contains;
         }
-    } else {
-        // Empty query: always found at position 0.
-        if candidate_buf.is_empty() {
-            // Both are empty after lowercasing -> Equal.
-            return Ranking::Equal;
+        if c == last_query_char
+            && let Some(start) = starts[query_chars.len() - 1]
+            && best.is_none_or(|(first, last)| pos - start < last - first)
+        {
+            best = Some((start, pos));
         }
-        return Ranking::StartsWith;
     }
 
-    // No substring match found. Continue to acronym and fuzzy matching.
-
-    // Step 9: Single-character query that was not found as a substring cannot
-    // match via acronym or fuzzy.
-    if pq.char_count == 1 {
-        return Ranking::NoMatch;
-    }
+    best
+}
 
-    // Step 10: Compute acronym of the lowercased candidate. If the acronym
-    // contains the lowercased query as a substring, it is an acronym match.
-    let acronym = get_acronym(candidate_buf);
-    if acronym.contains(&pq.lower) {
-        return Ranking::Acronym;
+/// Clamp a custom closeness score into the `Matches` sub-score range
+/// `(1.0, 2.0]`. Values at or below `1.0`, and NaN, become the smallest
+/// `f64` above `1.0`.
+fn clamp_closeness_score(score: f64) -> f64 {
+    if score > 2.0 {
+        2.0
+    } else if score > 1.0 {
+        score
+    } else {
+        1.0_f64.next_up()
     }
-
-    // Step 11: Attempt fuzzy closeness ranking on the lowercased strings.
-    get_closeness_ranking(candidate_buf, &pq.lower)
 }
 
-/// Determine how well a candidate string matches a search query.
+/// Returns whether `c` is an acronym word-boundary delimiter.
 ///
-/// Implements an 11-step algorithm that classifies the match into one of the
-/// 8 ranking tiers, checked in descending order of specificity. The first
-/// matching tier is returned.
+/// Only space (`' '`) and hyphen (`'-'`) are recognized as delimiters.
+fn is_acronym_delimiter(c: char) -> bool {
+    c == ' ' || c == '-'
+}
+
+/// Extract the acronym from a string by collecting word-initial characters.
 ///
-/// Both inputs are first prepared via [`prepare_value_for_comparison`] to
-/// optionally strip diacritics. Steps 1-2 operate on the prepared strings;
-/// steps 3-11 operate on lowercased versions of those strings.
+/// Word boundaries are space (`' '`) and hyphen (`'-'`) only. The first
+/// character is always included; subsequent characters are included when
+/// the previous character was a delimiter and the current character is not
+/// itself a delimiter. The caller is responsible for lowercasing the input
+/// before calling.
 ///
 /// # Arguments
 ///
-/// * `test_string` - The candidate string being evaluated
-/// * `string_to_rank` - The search query
-/// * `keep_diacritics` - If `true`, skip diacritics stripping
+/// * `s` - The input string to extract an acronym from
 ///
 /// # Returns
 ///
-/// The [`Ranking`] tier that best describes how the query matches the candidate.
+/// A `String` containing the first character of each word
 ///
 /// # Examples
 ///
 /// ```
-/// use matchsorter::{get_match_ranking, Ranking};
+/// use matchsorter::ranking::get_acronym;
 ///
-/// assert_eq!(get_match_ranking("Green", "Green", false), Ranking::CaseSensitiveEqual);
-/// assert_eq!(get_match_ranking("Green", "green", false), Ranking::Equal);
-/// assert_eq!(get_match_ranking("Greenland", "green", false), Ranking::StartsWith);
-/// assert_eq!(get_match_ranking("abc", "xyz", false), Ranking::NoMatch);
+/// assert_eq!(get_acronym("north-west airlines"), "nwa");
+/// assert_eq!(get_acronym("san francisco"), "sf");
+/// assert_eq!(get_acronym("single"), "s");
+/// assert_eq!(get_acronym(""), "");
 /// ```
-pub fn get_match_ranking(
-    test_string: &str,
-    string_to_rank: &str,
-    keep_diacritics: bool,
-) -> Ranking {
-    // Thin wrapper: construct a PreparedQuery for one-off calls.
-    let pq = PreparedQuery::new(string_to_rank, keep_diacritics);
-    let finder = if pq.lower.is_empty() {
-        None
-    } else {
-        Some(memchr::memmem::Finder::new(pq.lower.as_bytes()))
+pub fn get_acronym(s: &str) -> String {
+    get_acronym_bounded(s, usize::MAX)
+}
+
+/// Like [`get_acronym`], but stops after collecting `max_len` characters.
+///
+/// Long hyphenated or multi-word candidates produce acronyms far longer
+/// than anything a user types as an abbreviation; bounding the acronym to
+/// the first few words keeps the `Acronym` tier focused on them. A
+/// `max_len` of zero yields an empty acronym.
+///
+/// # Arguments
+///
+/// * `s` - The input string to extract an acronym from
+/// * `max_len` - Maximum number of characters in the result
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::ranking::get_acronym_bounded;
+///
+/// assert_eq!(get_acronym_bounded("one two three four five", 3), "ott");
+/// assert_eq!(get_acronym_bounded("north-west airlines", 10), "nwa");
+/// ```
+pub fn get_acronym_bounded(s: &str, max_len: usize) -> String {
+    let mut chars = s.chars();
+
+    // Empty string (or an empty bound) produces an empty acronym.
+    let first = match chars.next() {
+        Some(c) if max_len > 0 => c,
+        _ => return String::new(),
     };
-    let mut buf = String::new();
-    get_match_ranking_prepared(test_string, &pq, keep_diacritics, &mut buf, finder.as_ref())
+
+    // Estimate capacity: one char per word. Use memchr for a fast count of
+    // delimiter bytes (space and hyphen are single-byte ASCII).
+    let word_count_estimate = 1 + memchr::memchr2_iter(b' ', b'-', s.as_bytes()).count();
+    let mut acronym = String::with_capacity(word_count_estimate.min(max_len));
+
+    // First character is always included (virtual leading delimiter).
+    acronym.push(first);
+    let mut len = 1;
+
+    // Track the previous character to detect word boundaries.
+    let mut prev = first;
+    for c in chars {
+        if len == max_len {
+            break;
+        }
+        if is_acronym_delimiter(prev) && !is_acronym_delimiter(c) {
+            acronym.push(c);
+            len += 1;
+        }
+        prev = c;
+    }
+
+    acronym
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Lookup table mapping Latin-1 Supplement bytes (0x00..0x3F offset from U+00C0)
+/// to their base ASCII character after stripping diacritics. A value of 0
+/// means the character has no simple ASCII base (e.g. U+00D0 ETH, U+00D7
+/// MULTIPLICATION SIGN) and must be kept as-is.
+///
+/// Covers U+00C0 through U+00FF (the accented Latin characters that appear
+/// as 2-byte UTF-8 with lead byte 0xC3).
+const LATIN1_STRIP: [u8; 64] = [
+    // U+00C0..U+00CF
+    b'A', b'A', b'A', b'A', b'A', b'A', 0, b'C', // A-grave..AE(0)..C-cedilla
+    b'E', b'E', b'E', b'E', b'I', b'I', b'I', b'I', // E-grave..I-diaeresis
+    // U+00D0..U+00DF
+    0, b'N', b'O', b'O', b'O', b'O', b'O', 0, // ETH(0)..N-tilde..O-diaeresis..times(0)
+    0, b'U', b'U', b'U', b'U', b'Y', 0,
+    0, // O-stroke(0)..U-grave..Y-acute..thorn(0)..sharp-s(0)
+    // U+00E0..U+00EF
+    b'a', b'a', b'a', b'a', b'a', b'a', 0, b'c', // a-grave..ae(0)..c-cedilla
+    b'e', b'e', b'e', b'e', b'i', b'i', b'i', b'i', // e-grave..i-diaeresis
+    // U+00F0..U+00FF
+    0, b'n', b'o', b'o', b'o', b'o', b'o', 0, // eth(0)..n-tilde..o-diaeresis..divide(0)
+    0, b'u', b'u', b'u', b'u', b'y', 0,
+    b'y', // o-stroke(0)..u-grave..y-acute..thorn(0)..y-diaeresis
+];
 
-    #[test]
-    fn full_tier_ordering_descending() {
-        // Verify the complete ordering chain from best to worst.
-        assert!(Ranking::CaseSensitiveEqual > Ranking::Equal);
-        assert!(Ranking::Equal > Ranking::StartsWith);
-        assert!(Ranking::StartsWith > Ranking::WordStartsWith);
-        assert!(Ranking::WordStartsWith > Ranking::Contains);
-        assert!(Ranking::Contains > Ranking::Acronym);
-        assert!(Ranking::Acronym > Ranking::Matches(1.5));
-        assert!(Ranking::Matches(1.5) > Ranking::NoMatch);
+/// Fast diacritics stripping for strings whose non-ASCII characters are all
+/// in the Latin-1 Supplement range (U+00C0..U+00FF). Returns `None` if any
+/// character falls outside this range, signaling the caller to use the
+/// general NFD path.
+///
+/// When all non-ASCII chars are Latin-1 and at least one has a strippable
+/// accent, returns `Some(Cow::Owned(...))`. When none need stripping (e.g.
+/// all non-ASCII chars are U+00C6 AE, U+00D0 ETH, etc.), returns
+/// `Some(Cow::Borrowed(s))`.
+fn strip_latin1_diacritics(s: &str) -> Option<Cow<'_, str>> {
+    let bytes = s.as_bytes();
+    let mut needs_strip = false;
+    let mut i = 0;
+
+    // First pass: verify all non-ASCII chars are 2-byte Latin-1 Supplement
+    // and check whether any stripping is needed.
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b < 0x80 {
+            // ASCII byte: skip.
+            i += 1;
+        } else if b == 0xC3 && i + 1 < bytes.len() {
+            // 2-byte UTF-8 for U+00C0..U+00FF (lead byte 0xC3).
+            let trail = bytes[i + 1];
+            // Trail byte for U+00C0..U+00FF is 0x80..0xBF.
+            let offset = trail.wrapping_sub(0x80);
+            if offset < 64 && LATIN1_STRIP[offset as usize] != 0 {
+                needs_strip = true;
+            }
+            i += 2;
+        } else if b == 0xC2 && i + 1 < bytes.len() {
+            // 2-byte UTF-8 for U+0080..U+00BF (non-break space, soft
+            // hyphen, etc.) -- no diacritics, but still Latin-1. Skip.
+            i += 2;
+        } else {
+            // b >= 0x80 (all remaining non-ASCII bytes): multi-byte char
+            // outside Latin-1 Supplement. Bail out to the general NFD path.
+            return None;
+        }
+    }
+
+    if !needs_strip {
+        return Some(Cow::Borrowed(s));
+    }
+
+    // Second pass: build the stripped string.
+    let mut result = String::with_capacity(s.len());
+    let mut j = 0;
+    while j < bytes.len() {
+        let b = bytes[j];
+        if b < 0x80 {
+            result.push(b as char);
+            j += 1;
+        } else if b == 0xC3 && j + 1 < bytes.len() {
+            let trail = bytes[j + 1];
+            let offset = trail.wrapping_sub(0x80);
+            if offset < 64 {
+                let base = LATIN1_STRIP[offset as usize];
+                if base != 0 {
+                    result.push(base as char);
+                } else {
+                    // Non-strippable Latin-1 char (AE, ETH, etc.): keep
+                    // original 2-byte sequence.
+                    // SAFETY: we verified this is valid 2-byte UTF-8 above.
+                    result.push_str(
+                        std::str::from_utf8(&bytes[j..j + 2]).expect("valid 2-byte UTF-8 sequence"),
+                    );
+                }
+            }
+            j += 2;
+        } else {
+            // 0xC2 lead byte or other: keep the 2-byte sequence.
+            result.push_str(
+                std::str::from_utf8(&bytes[j..j + 2]).expect("valid 2-byte UTF-8 sequence"),
+            );
+            j += 2;
+        }
+    }
+
+    Some(Cow::Owned(result))
+}
+
+/// How diacritics (accents and other combining marks) are treated before
+/// two strings are compared.
+///
+/// Setting [`MatchSorterOptions::keep_diacritics`](crate::MatchSorterOptions::keep_diacritics)
+/// to `true` takes precedence over the mode and behaves like
+/// [`DiacriticsMode::Preserve`].
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{DiacriticsLanguage, DiacriticsMode, MatchSorterOptions, Ranking, match_sorter};
+///
+/// let items = ["a\u{00f1}o", "ano"];
+/// let opts = MatchSorterOptions {
+///     diacritics_mode: DiacriticsMode::StripNonDistinctive(DiacriticsLanguage::Spanish),
+///     threshold: Ranking::Contains,
+///     ..Default::default()
+/// };
+/// // In Spanish "\u{00f1}" is its own letter, so "ano" does not equal "a\u{00f1}o".
+/// assert_eq!(match_sorter(&items, "ano", &opts), vec![&"ano"]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DiacriticsMode {
+    /// Strip every combining mark, so `"a\u{00f1}o"` compares as `"ano"`.
+    #[default]
+    StripAll,
+    /// Strip every combining mark except those that distinguish letters in
+    /// the given language; see [`DiacriticsLanguage::distinctive_marks`].
+    StripNonDistinctive(DiacriticsLanguage),
+    /// Keep the string as written.
+    Preserve,
+}
+
+impl DiacriticsMode {
+    /// The mode selected by a plain `keep_diacritics` flag.
+    pub(crate) const fn from_keep_diacritics(keep_diacritics: bool) -> Self {
+        if keep_diacritics {
+            Self::Preserve
+        } else {
+            Self::StripAll
+        }
+    }
+}
+
+/// A language whose distinctive diacritics
+/// [`DiacriticsMode::StripNonDistinctive`] keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiacriticsLanguage {
+    /// Keeps the tilde (U+0303), so `"\u{00f1}"` stays distinct from `"n"`.
+    Spanish,
+    /// Keeps the diaeresis (U+0308) of the umlauts `"\u{00e4}"`,
+    /// `"\u{00f6}"` and `"\u{00fc}"`.
+    German,
+    /// Keeps no marks, so this behaves like [`DiacriticsMode::StripAll`].
+    French,
+}
+
+/// Combining marks kept for each language by
+/// [`DiacriticsMode::StripNonDistinctive`].
+const DISTINCTIVE_MARKS: [(DiacriticsLanguage, &[char]); 3] = [
+    (DiacriticsLanguage::Spanish, &['\u{0303}']),
+    (DiacriticsLanguage::German, &['\u{0308}']),
+    (DiacriticsLanguage::French, &[]),
+];
+
+impl DiacriticsLanguage {
+    /// The combining marks that distinguish letters in this language and are
+    /// therefore not stripped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::DiacriticsLanguage;
+    ///
+    /// assert_eq!(DiacriticsLanguage::Spanish.distinctive_marks(), &['\u{0303}']);
+    /// assert!(DiacriticsLanguage::French.distinctive_marks().is_empty());
+    /// ```
+    pub fn distinctive_marks(self) -> &'static [char] {
+        DISTINCTIVE_MARKS
+            .iter()
+            .find(|(language, _)| *language == self)
+            .map_or(&[], |(_, marks)| marks)
+    }
+}
+
+/// The Unicode normalization applied to the query and candidates before
+/// they are compared.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, NormalizationForm, Ranking, match_sorter};
+///
+/// let items = ["\u{fb01}le", "\u{ff41}\u{ff50}\u{ff50}"];
+/// let opts = MatchSorterOptions {
+///     normalization_form: NormalizationForm::Nfkc,
+///     threshold: Ranking::Equal,
+///     ..Default::default()
+/// };
+/// // The "fi" ligature and full-width letters fold to plain ASCII.
+/// assert_eq!(match_sorter(&items, "file", &opts), vec![&"\u{fb01}le"]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum NormalizationForm {
+    /// Compare strings exactly as written. Diacritics are kept whatever the
+    /// [`DiacriticsMode`].
+    None,
+    /// Canonical decomposition (NFD), used to strip diacritics as the
+    /// [`DiacriticsMode`] says. Compatibility characters such as `"\u{fb01}"`
+    /// stay distinct from their plain spelling.
+    #[default]
+    Nfd,
+    /// Compatibility composition (NFKC) before the diacritics are handled,
+    /// folding typographic variants: the ligature `"\u{fb01}"` becomes `"fi"`,
+    /// `"\u{210c}"` becomes `"H"` and full-width `"\u{ff41}"` becomes `"a"`.
+    Nfkc,
+}
+
+/// How the query and candidates are case-folded before they are compared.
+///
+/// Every tier except `CaseSensitiveEqual` compares the folded strings, so
+/// the folding decides which spellings count as `Equal`.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, Ranking, UnicodeCaseFolding, match_sorter};
+///
+/// let items = ["Ma\u{df}e", "Masche"];
+/// let opts = MatchSorterOptions {
+///     unicode_case_folding: UnicodeCaseFolding::Full,
+///     threshold: Ranking::Equal,
+///     ..Default::default()
+/// };
+/// // Full folding turns "\u{df}" into "ss".
+/// assert_eq!(match_sorter(&items, "masse", &opts), vec![&"Ma\u{df}e"]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum UnicodeCaseFolding {
+    /// Lowercase each character with [`char::to_lowercase`]. `"\u{df}"`
+    /// stays `"\u{df}"`, so it never equals `"ss"`.
+    #[default]
+    Simple,
+    /// Full case folding as defined by the Unicode standard: characters
+    /// whose folding differs from their lowercase form are mapped through
+    /// the special cases of `CaseFolding.txt`, so `"\u{df}"` and `"\u{1e9e}"`
+    /// fold to `"ss"`, the `"\u{fb01}"` ligature to `"fi"` and the final
+    /// sigma to `"\u{3c3}"`. All other characters are lowercased.
+    Full,
+    /// Lowercasing with the Turkish and Azerbaijani rules for the letter i:
+    /// `"I"` folds to the dotless `"\u{131}"` and `"\u{130}"` to a plain
+    /// `"i"` rather than `"i\u{307}"`. All other characters are lowercased.
+    Turkic,
+}
+
+impl UnicodeCaseFolding {
+    /// Fold `s` into `buf`, replacing its contents.
+    pub(crate) fn fold_into(self, s: &str, buf: &mut String) {
+        if self == UnicodeCaseFolding::Simple {
+            lowercase_into(s, buf);
+            return;
+        }
+        buf.clear();
+        buf.reserve(s.len());
+        for c in s.chars() {
+            self.push_folded(c, buf);
+        }
+    }
+
+    /// The query form of `s`: lowercased with [`str::to_lowercase`] in
+    /// `Simple` mode, folded like candidates otherwise.
+    fn fold_query(self, s: &str) -> String {
+        if self == UnicodeCaseFolding::Simple {
+            return s.to_lowercase();
+        }
+        let mut buf = String::new();
+        self.fold_into(s, &mut buf);
+        buf
+    }
+
+    /// The UTF-8 length of `c` after folding.
+    fn folded_len(self, c: char) -> usize {
+        if self == UnicodeCaseFolding::Simple {
+            return c.to_lowercase().map(char::len_utf8).sum();
+        }
+        let mut buf = String::new();
+        self.push_folded(c, &mut buf);
+        buf.len()
+    }
+
+    fn push_folded(self, c: char, buf: &mut String) {
+        match (self, c) {
+            (UnicodeCaseFolding::Turkic, 'I') => buf.push('\u{131}'),
+            (UnicodeCaseFolding::Turkic, '\u{130}') => buf.push('i'),
+            (UnicodeCaseFolding::Full, _) if push_special_full_fold(c, buf) => {}
+            _ => buf.extend(c.to_lowercase()),
+        }
+    }
+}
+
+/// Push the full case folding of `c` when it differs from the lowercase
+/// mapping, returning whether it did.
+///
+/// Covers the full (`F`) mappings of Unicode's `CaseFolding.txt` and the
+/// common (`C`) mappings of characters that are already lowercase but fold
+/// to another letter. Cherokee, which folds to uppercase, is left
+/// lowercased.
+fn push_special_full_fold(c: char, buf: &mut String) -> bool {
+    // Greek letters with a combining iota subscript (ypogegrammeni) fold
+    // to the letter followed by a separate iota.
+    if let '\u{1f80}'..='\u{1faf}' = c {
+        let offset = c as u32 - 0x1f80;
+        let base = [0x1f00, 0x1f20, 0x1f60][(offset / 16) as usize] + offset % 8;
+        buf.extend(char::from_u32(base));
+        buf.push('\u{3b9}');
+        return true;
+    }
+    let folded = match c {
+        '\u{df}' | '\u{1e9e}' => "ss",
+        '\u{149}' => "\u{2bc}n",
+        '\u{1f0}' => "j\u{30c}",
+        '\u{390}' => "\u{3b9}\u{308}\u{301}",
+        '\u{3b0}' => "\u{3c5}\u{308}\u{301}",
+        '\u{587}' => "\u{565}\u{582}",
+        '\u{1e96}' => "h\u{331}",
+        '\u{1e97}' => "t\u{308}",
+        '\u{1e98}' => "w\u{30a}",
+        '\u{1e99}' => "y\u{30a}",
+        '\u{1e9a}' => "a\u{2be}",
+        '\u{1f50}' => "\u{3c5}\u{313}",
+        '\u{1f52}' => "\u{3c5}\u{313}\u{300}",
+        '\u{1f54}' => "\u{3c5}\u{313}\u{301}",
+        '\u{1f56}' => "\u{3c5}\u{313}\u{342}",
+        '\u{1fb2}' => "\u{1f70}\u{3b9}",
+        '\u{1fb3}' | '\u{1fbc}' => "\u{3b1}\u{3b9}",
+        '\u{1fb4}' => "\u{3ac}\u{3b9}",
+        '\u{1fb6}' => "\u{3b1}\u{342}",
+        '\u{1fb7}' => "\u{3b1}\u{342}\u{3b9}",
+        '\u{1fc2}' => "\u{1f74}\u{3b9}",
+        '\u{1fc3}' | '\u{1fcc}' => "\u{3b7}\u{3b9}",
+        '\u{1fc4}' => "\u{3ae}\u{3b9}",
+        '\u{1fc6}' => "\u{3b7}\u{342}",
+        '\u{1fc7}' => "\u{3b7}\u{342}\u{3b9}",
+        '\u{1fd2}' => "\u{3b9}\u{308}\u{300}",
+        '\u{1fd3}' => "\u{3b9}\u{308}\u{301}",
+        '\u{1fd6}' => "\u{3b9}\u{342}",
+        '\u{1fd7}' => "\u{3b9}\u{308}\u{342}",
+        '\u{1fe2}' => "\u{3c5}\u{308}\u{300}",
+        '\u{1fe3}' => "\u{3c5}\u{308}\u{301}",
+        '\u{1fe4}' => "\u{3c1}\u{313}",
+        '\u{1fe6}' => "\u{3c5}\u{342}",
+        '\u{1fe7}' => "\u{3c5}\u{308}\u{342}",
+        '\u{1ff2}' => "\u{1f7c}\u{3b9}",
+        '\u{1ff3}' | '\u{1ffc}' => "\u{3c9}\u{3b9}",
+        '\u{1ff4}' => "\u{3ce}\u{3b9}",
+        '\u{1ff6}' => "\u{3c9}\u{342}",
+        '\u{1ff7}' => "\u{3c9}\u{342}\u{3b9}",
+        '\u{fb00}' => "ff",
+        '\u{fb01}' => "fi",
+        '\u{fb02}' => "fl",
+        '\u{fb03}' => "ffi",
+        '\u{fb04}' => "ffl",
+        '\u{fb05}' | '\u{fb06}' => "st",
+        '\u{fb13}' => "\u{574}\u{576}",
+        '\u{fb14}' => "\u{574}\u{565}",
+        '\u{fb15}' => "\u{574}\u{56b}",
+        '\u{fb16}' => "\u{57e}\u{576}",
+        '\u{fb17}' => "\u{574}\u{56d}",
+        // Lowercase letters with a common folding to another letter.
+        '\u{b5}' => "\u{3bc}",
+        '\u{17f}' => "s",
+        '\u{345}' | '\u{1fbe}' => "\u{3b9}",
+        '\u{3c2}' => "\u{3c3}",
+        '\u{3d0}' => "\u{3b2}",
+        '\u{3d1}' => "\u{3b8}",
+        '\u{3d5}' => "\u{3c6}",
+        '\u{3d6}' => "\u{3c0}",
+        '\u{3f0}' => "\u{3ba}",
+        '\u{3f1}' => "\u{3c1}",
+        '\u{3f5}' => "\u{3b5}",
+        '\u{1e9b}' => "\u{1e61}",
+        _ => return false,
+    };
+    buf.push_str(folded);
+    true
+}
+
+/// Prepare a string for comparison according to a [`NormalizationForm`] and
+/// a [`DiacriticsMode`].
+///
+/// [`NormalizationForm::Nfd`] is the same as [`prepare_value_with_mode`],
+/// and [`NormalizationForm::None`] returns the string unchanged.
+/// [`NormalizationForm::Nfkc`] folds compatibility characters first, then
+/// treats diacritics like [`prepare_value_with_mode`].
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{DiacriticsMode, NormalizationForm};
+/// use matchsorter::ranking::prepare_value_with_form;
+///
+/// let strip = DiacriticsMode::StripAll;
+/// assert_eq!(prepare_value_with_form("\u{fb01}le", strip, NormalizationForm::Nfkc), "file");
+/// assert_eq!(prepare_value_with_form("\u{fb01}le", strip, NormalizationForm::Nfd), "\u{fb01}le");
+/// assert_eq!(prepare_value_with_form("caf\u{e9}", strip, NormalizationForm::Nfkc), "cafe");
+/// assert_eq!(prepare_value_with_form("caf\u{e9}", strip, NormalizationForm::None), "caf\u{e9}");
+/// ```
+pub fn prepare_value_with_form(
+    s: &str,
+    mode: DiacriticsMode,
+    form: NormalizationForm,
+) -> Cow<'_, str> {
+    match form {
+        NormalizationForm::None => Cow::Borrowed(s),
+        NormalizationForm::Nfd => prepare_value_with_mode(s, mode),
+        NormalizationForm::Nfkc => {
+            if s.is_ascii() || unicode_normalization::is_nfkc(s) {
+                return prepare_value_with_mode(s, mode);
+            }
+            let folded: String = s.nfkc().collect();
+            Cow::Owned(prepare_value_with_mode(&folded, mode).into_owned())
+        }
+    }
+}
+
+/// Prepare a string for comparison according to a [`DiacriticsMode`].
+///
+/// [`DiacriticsMode::StripAll`] and [`DiacriticsMode::Preserve`] behave like
+/// [`prepare_value_for_comparison`] with `keep_diacritics` set to `false`
+/// and `true` respectively. [`DiacriticsMode::StripNonDistinctive`] returns
+/// the string in decomposed form (NFD) with every combining mark removed
+/// except the language's distinctive ones, so that composed and decomposed
+/// spellings of a kept letter compare equal.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{DiacriticsLanguage, DiacriticsMode};
+/// use matchsorter::ranking::prepare_value_with_mode;
+///
+/// let spanish = DiacriticsMode::StripNonDistinctive(DiacriticsLanguage::Spanish);
+/// assert_eq!(prepare_value_with_mode("a\u{00f1}o", DiacriticsMode::StripAll), "ano");
+/// assert_eq!(prepare_value_with_mode("a\u{00f1}o", spanish), "an\u{0303}o");
+/// assert_eq!(prepare_value_with_mode("canci\u{00f3}n", spanish), "cancion");
+/// ```
+pub fn prepare_value_with_mode(s: &str, mode: DiacriticsMode) -> Cow<'_, str> {
+    match mode {
+        DiacriticsMode::StripAll => prepare_value_for_comparison(s, false),
+        DiacriticsMode::Preserve => Cow::Borrowed(s),
+        DiacriticsMode::StripNonDistinctive(language) => {
+            let keep = language.distinctive_marks();
+            if keep.is_empty() {
+                return prepare_value_for_comparison(s, false);
+            }
+            if s.is_ascii() {
+                return Cow::Borrowed(s);
+            }
+            let stripped = |c: char| is_combining_mark(c) && !keep.contains(&c);
+            if s.nfd().eq(s.chars()) && !s.chars().any(stripped) {
+                return Cow::Borrowed(s);
+            }
+            Cow::Owned(s.nfd().filter(|&c| !stripped(c)).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    /// How many times [`prepare_value_for_comparison`] ran on this thread.
+    pub(crate) static COMPARISON_PREPARES: std::cell::Cell<usize> =
+        const { std::cell::Cell::new(0) };
+}
+
+/// Prepare a string for comparison by optionally stripping diacritics.
+///
+/// When `keep_diacritics` is `false`, applies Unicode NFD decomposition and
+/// removes combining marks (`General_Category = Mark`), effectively stripping
+/// accents and diacritical marks from characters. When `keep_diacritics` is
+/// `true`, the original string is returned unchanged.
+///
+/// Returns [`Cow::Borrowed`] when no modification is needed (either because
+/// `keep_diacritics` is `true`, or because the string contains no diacritics
+/// to strip). Only allocates a new `String` ([`Cow::Owned`]) when characters
+/// are actually removed.
+///
+/// # Arguments
+///
+/// * `s` - The input string to prepare
+/// * `keep_diacritics` - If `true`, skip diacritics stripping entirely
+///
+/// # Returns
+///
+/// A [`Cow<'_, str>`] that is either borrowed from the input or an owned
+/// string with combining marks removed.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::ranking::prepare_value_for_comparison;
+///
+/// // Stripping an accent produces a new string
+/// let result = prepare_value_for_comparison("cafe\u{0301}", false);
+/// assert_eq!(result, "cafe");
+/// assert!(matches!(result, std::borrow::Cow::Owned(_)));
+///
+/// // ASCII strings are returned borrowed (no allocation)
+/// let result = prepare_value_for_comparison("cafe", false);
+/// assert_eq!(result, "cafe");
+/// assert!(matches!(result, std::borrow::Cow::Borrowed(_)));
+///
+/// // With keep_diacritics=true, the original is always returned
+/// let result = prepare_value_for_comparison("cafe\u{0301}", true);
+/// assert_eq!(result, "cafe\u{0301}");
+/// assert!(matches!(result, std::borrow::Cow::Borrowed(_)));
+/// ```
+pub fn prepare_value_for_comparison(s: &str, keep_diacritics: bool) -> Cow<'_, str> {
+    #[cfg(test)]
+    COMPARISON_PREPARES.with(|calls| calls.set(calls.get() + 1));
+    if keep_diacritics {
+        return Cow::Borrowed(s);
+    }
+
+    // Fast path: ASCII strings never contain diacritics or combining marks.
+    if s.is_ascii() {
+        return Cow::Borrowed(s);
+    }
+
+    // Try the Latin-supplement fast path first: if every non-ASCII char is
+    // in U+00C0..U+00FF (2-byte UTF-8: 0xC3 lead byte), we can strip
+    // diacritics via a cheap lookup table instead of running full NFD.
+    // This covers the vast majority of accented Latin text (French, German,
+    // Spanish, Portuguese, etc.).
+    if let Some(result) = strip_latin1_diacritics(s) {
+        return result;
+    }
+
+    // General path for non-Latin-1 non-ASCII text (CJK, extended Latin,
+    // combining marks, etc.): single-pass NFD with lazy allocation.
+    // Iterates the NFD output once. As long as no combining mark is found,
+    // no allocation occurs. When the first mark appears, allocates a buffer,
+    // backfills the non-mark prefix, then continues filtering.
+    let mut nfd = s.nfd();
+    let mut prefix_len: usize = 0;
+
+    loop {
+        match nfd.next() {
+            Some(c) if is_combining_mark(c) => break,
+            Some(_) => {
+                prefix_len += 1;
+            }
+            None => {
+                // No combining marks found: input is unchanged after NFD.
+                return Cow::Borrowed(s);
+            }
+        }
+    }
+
+    // A combining mark was found. Allocate and collect.
+    let mut result = String::with_capacity(s.len());
+
+    // Backfill non-mark chars emitted before the first mark.
+    for (i, c) in s.nfd().enumerate() {
+        if i >= prefix_len {
+            break;
+        }
+        result.push(c);
+    }
+
+    // Continue filtering the rest of the NFD iterator.
+    for c in nfd {
+        if !is_combining_mark(c) {
+            result.push(c);
+        }
+    }
+
+    Cow::Owned(result)
+}
+
+/// Pre-computed query data for amortizing repeated per-item ranking calls.
+///
+/// Caches the prepared (diacritics-stripped) query, its lowercased form,
+/// character count, and a character histogram so that `match_sorter` can
+/// avoid redundant work when ranking thousands of candidates against the
+/// same query.
+///
+/// Constructed once before the ranking loop via [`PreparedQuery::new`].
+/// [`PreparedQuery::quick_match`] is also available on its own for callers
+/// that want to pre-filter their own datasets.
+#[derive(Debug, Clone)]
+pub struct PreparedQuery {
+    /// The query as given, kept for per-language normalization.
+    original: String,
+    /// The query after optional diacritics stripping.
+    prepared: String,
+    /// Lowercased version of the prepared query.
+    pub(crate) lower: String,
+    /// Character count of the lowercased query (cached to avoid repeated
+    /// `.chars().count()` calls).
+    char_count: usize,
+    /// How diacritics were treated when preparing the query.
+    diacritics: DiacriticsMode,
+    /// The Unicode normalization applied when preparing the query.
+    normalization: NormalizationForm,
+    /// How `lower` was case-folded when no language rules apply.
+    case_folding: UnicodeCaseFolding,
+    /// Multiset of the characters in `lower`.
+    histogram: CharHistogram,
+}
+
+impl PreparedQuery {
+    /// Create a new `PreparedQuery` by preparing and lowercasing the query once.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The raw search query string
+    /// * `keep_diacritics` - If `true`, skip diacritics stripping
+    pub fn new(query: &str, keep_diacritics: bool) -> Self {
+        Self::with_diacritics_mode(query, DiacriticsMode::from_keep_diacritics(keep_diacritics))
+    }
+
+    /// Like [`new`](Self::new), but with any [`DiacriticsMode`].
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The raw search query string
+    /// * `diacritics` - How to treat diacritics in the query and candidates
+    pub fn with_diacritics_mode(query: &str, diacritics: DiacriticsMode) -> Self {
+        Self::with_normalization(query, diacritics, NormalizationForm::Nfd)
+    }
+
+    /// Like [`with_diacritics_mode`](Self::with_diacritics_mode), but with
+    /// any [`NormalizationForm`].
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The raw search query string
+    /// * `diacritics` - How to treat diacritics in the query and candidates
+    /// * `normalization` - The Unicode normalization applied first
+    pub fn with_normalization(
+        query: &str,
+        diacritics: DiacriticsMode,
+        normalization: NormalizationForm,
+    ) -> Self {
+        Self::with_case_folding(query, diacritics, normalization, UnicodeCaseFolding::Simple)
+    }
+
+    /// Like [`with_normalization`](Self::with_normalization), but with any
+    /// [`UnicodeCaseFolding`].
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The raw search query string
+    /// * `diacritics` - How to treat diacritics in the query and candidates
+    /// * `normalization` - The Unicode normalization applied first
+    /// * `case_folding` - How the query and candidates are case-folded
+    pub fn with_case_folding(
+        query: &str,
+        diacritics: DiacriticsMode,
+        normalization: NormalizationForm,
+        case_folding: UnicodeCaseFolding,
+    ) -> Self {
+        Self::build(query, diacritics, normalization, case_folding, None)
+    }
+
+    /// [`with_case_folding`](Self::with_case_folding), lowercasing with
+    /// `case` rules instead when given.
+    fn build(
+        query: &str,
+        diacritics: DiacriticsMode,
+        normalization: NormalizationForm,
+        case_folding: UnicodeCaseFolding,
+        case: Option<LanguageAwareOptions<'_>>,
+    ) -> Self {
+        let prepared = prepare_value_with_form(query, diacritics, normalization).into_owned();
+        let lower = match case {
+            Some(case) => case.lowercase(&prepared),
+            None => case_folding.fold_query(&prepared),
+        };
+        // ASCII fast path: byte length equals character count for ASCII strings.
+        let char_count = if lower.is_ascii() {
+            lower.len()
+        } else {
+            lower.chars().count()
+        };
+        let histogram = CharHistogram::new(&lower);
+        Self {
+            original: query.to_owned(),
+            prepared,
+            lower,
+            char_count,
+            diacritics,
+            normalization,
+            case_folding,
+            histogram,
+        }
+    }
+
+    /// This query prepared again for a key with a language: passed through
+    /// `normalize` and lowercased with `case` rules. Returns `None` when
+    /// there are no case rules and the normalizer, if any, leaves the query
+    /// unchanged.
+    pub(crate) fn for_language(
+        &self,
+        normalize: Option<&LanguageNormalizer>,
+        case: Option<LanguageAwareOptions<'_>>,
+    ) -> Option<Self> {
+        let normalized = normalize
+            .map(|normalize| normalize(&self.original))
+            .filter(|query| *query != self.original);
+        if normalized.is_none() && case.is_none() {
+            return None;
+        }
+        let query = normalized.as_deref().unwrap_or(&self.original);
+        Some(Self::build(
+            query,
+            self.diacritics,
+            self.normalization,
+            self.case_folding,
+            case,
+        ))
+    }
+
+    /// Fast check whether `candidate` could match this query at all.
+    ///
+    /// Returns `false` when the candidate, prepared and lowercased the same
+    /// way as during ranking, lacks some character of the query (counting
+    /// repeats). Every ranking tier needs all query characters, so a `false`
+    /// result guarantees [`Ranking::NoMatch`]. A `true` result is necessary
+    /// but not sufficient: the characters may still be out of order.
+    ///
+    /// Runs in `O(candidate_len)` regardless of the query length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::ranking::PreparedQuery;
+    ///
+    /// let pq = PreparedQuery::new("ogd", false);
+    /// assert!(pq.quick_match("Dog"));
+    /// assert!(!pq.quick_match("Cat"));
+    /// assert!(PreparedQuery::new("cafe", false).quick_match("CAF\u{00c9}"));
+    /// ```
+    pub fn quick_match(&self, candidate: &str) -> bool {
+        let candidate = prepare_value_with_form(candidate, self.diacritics, self.normalization);
+        let mut lower = String::new();
+        self.case_folding.fold_into(&candidate, &mut lower);
+        self.histogram.is_subset_of(&lower)
+    }
+
+    /// Rank every candidate against this query, returning one [`Ranking`]
+    /// per candidate in input order.
+    ///
+    /// Gives the same results as calling [`get_match_ranking`] on each
+    /// candidate with this query and diacritics setting, but builds
+    /// the substring finder and the lowercasing buffer once for the whole
+    /// batch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use matchsorter::Ranking;
+    /// use matchsorter::ranking::PreparedQuery;
+    ///
+    /// let pq = PreparedQuery::new("green", false);
+    /// assert_eq!(
+    ///     pq.apply_batch(&["Greenland", "evergreen", "blue"]),
+    ///     vec![Ranking::StartsWith, Ranking::Contains, Ranking::NoMatch]
+    /// );
+    /// ```
+    pub fn apply_batch(&self, candidates: &[&str]) -> Vec<Ranking> {
+        let mut output = Vec::with_capacity(candidates.len());
+        self.apply_batch_into(candidates, &mut output);
+        output
+    }
+
+    /// Like [`apply_batch`](Self::apply_batch), but writes the rankings into
+    /// `output`, replacing its contents, so one buffer can be reused across
+    /// batches.
+    pub fn apply_batch_into(&self, candidates: &[&str], output: &mut Vec<Ranking>) {
+        output.clear();
+        output.reserve(candidates.len());
+        let finder = if self.lower.is_empty() {
+            None
+        } else {
+            Some(memchr::memmem::Finder::new(self.lower.as_bytes()))
+        };
+        let config = RankingConfig {
+            diacritics: self.diacritics,
+            normalization: self.normalization,
+            case_folding: self.case_folding,
+            ..RankingConfig::default()
+        };
+        let mut buf = String::new();
+        output.extend(candidates.iter().map(|candidate| {
+            get_match_ranking_prepared(candidate, self, &config, &mut buf, finder.as_ref())
+        }));
+    }
+}
+
+/// Multiset of the characters in a lowercased query, for
+/// [`PreparedQuery::quick_match`].
+#[derive(Debug, Clone)]
+struct CharHistogram {
+    /// Occurrences of each ASCII character.
+    ascii: [u16; 128],
+    /// Occurrences of each non-ASCII character.
+    other: Vec<(char, u16)>,
+    /// Total number of characters counted.
+    total: usize,
+}
+
+impl CharHistogram {
+    fn new(s: &str) -> Self {
+        let mut ascii = [0u16; 128];
+        let mut other: Vec<(char, u16)> = Vec::new();
+        let mut total = 0;
+        for c in s.chars() {
+            let slot = if c.is_ascii() {
+                &mut ascii[c as usize]
+            } else {
+                let i = match other.iter().position(|&(oc, _)| oc == c) {
+                    Some(i) => i,
+                    None => {
+                        other.push((c, 0));
+                        other.len() - 1
+                    }
+                };
+                &mut other[i].1
+            };
+            // Saturated counts stop contributing, keeping `total` in sync
+            // with the stored counts (the check only gets more lenient).
+            if *slot < u16::MAX {
+                *slot += 1;
+                total += 1;
+            }
+        }
+        Self {
+            ascii,
+            other,
+            total,
+        }
+    }
+
+    /// Returns whether `candidate` contains every counted character at
+    /// least as many times as it was counted.
+    fn is_subset_of(&self, candidate: &str) -> bool {
+        if self.total == 0 {
+            return true;
+        }
+        let mut ascii = self.ascii;
+        let mut remaining = self.total;
+
+        // ASCII fast path: scan bytes, and fail early if the query needs
+        // non-ASCII characters the candidate cannot contain.
+        if candidate.is_ascii() {
+            if !self.other.is_empty() {
+                return false;
+            }
+            for &b in candidate.as_bytes() {
+                let slot = &mut ascii[usize::from(b)];
+                if *slot > 0 {
+                    *slot -= 1;
+                    remaining -= 1;
+                    if remaining == 0 {
+                        return true;
+                    }
+                }
+            }
+            return false;
+        }
+
+        let mut other = self.other.clone();
+        for c in candidate.chars() {
+            let slot = if c.is_ascii() {
+                &mut ascii[c as usize]
+            } else if let Some((_, n)) = other.iter_mut().find(|(oc, _)| *oc == c) {
+                n
+            } else {
+                continue;
+            };
+            if *slot > 0 {
+                *slot -= 1;
+                remaining -= 1;
+                if remaining == 0 {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Lowercase `s` into `buf`, reusing the buffer's allocation.
+///
+/// When `s` is ASCII, uses a byte-level fast path that avoids Unicode
+/// case-mapping tables entirely. For non-ASCII input, falls back to
+/// `char::to_lowercase()`.
+///
+/// Both branches include an already-lowercase early exit: if the string
+/// contains no uppercase characters, it is bulk-copied into `buf` via
+/// `push_str` instead of iterating per-character.
+pub(crate) fn lowercase_into(s: &str, buf: &mut String) {
+    buf.clear();
+    if s.is_ascii() {
+        buf.reserve(s.len());
+        if s.as_bytes().iter().all(|b| !b.is_ascii_uppercase()) {
+            // Already lowercase: bulk-copy avoids per-byte case mapping.
+            buf.push_str(s);
+        } else {
+            // ASCII bytes are single-byte UTF-8, so lowercasing byte-by-byte
+            // and casting to char is safe and avoids Unicode lookup tables.
+            buf.extend(s.as_bytes().iter().map(|&b| b.to_ascii_lowercase() as char));
+        }
+    } else {
+        buf.reserve(s.len());
+        if s.chars().all(|c| !c.is_uppercase()) {
+            // Already lowercase: bulk-copy avoids per-char case mapping.
+            buf.push_str(s);
+        } else {
+            for c in s.chars() {
+                for lc in c.to_lowercase() {
+                    buf.push(lc);
+                }
+            }
+        }
+    }
+}
+
+/// Per-call ranking behavior derived from
+/// [`MatchSorterOptions`](crate::MatchSorterOptions).
+///
+/// Bundles the non-generic settings that influence how a single candidate is
+/// ranked, so the hot path receives them as one reference instead of a
+/// growing list of arguments. Built once per `match_sorter` call via
+/// `MatchSorterOptions::ranking_config`.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct RankingConfig<'a> {
+    /// How diacritics are treated in the candidate.
+    pub(crate) diacritics: DiacriticsMode,
+    /// The Unicode normalization applied to the candidate.
+    pub(crate) normalization: NormalizationForm,
+    /// How the candidate is case-folded when no language rules apply.
+    pub(crate) case_folding: UnicodeCaseFolding,
+    /// Threshold overrides by top-level key index, or `None` when empty.
+    pub(crate) key_thresholds: Option<&'a HashMap<usize, Ranking>>,
+    /// If `true`, the `Acronym` tier requires the query to be a prefix of
+    /// the candidate's acronym rather than any substring of it.
+    pub(crate) acronym_prefix_match: bool,
+    /// If `true`, the `Acronym` tier requires the query to equal the
+    /// candidate's acronym. Takes precedence over `acronym_prefix_match`.
+    pub(crate) acronym_exact_match: bool,
+    /// Maximum number of characters in a candidate's acronym, or `None` for
+    /// no limit.
+    pub(crate) acronym_max_length: Option<usize>,
+    /// If `true`, a hyphen before a match also counts as a word boundary
+    /// for the `WordStartsWith` tier.
+    pub(crate) hyphen_is_word_boundary: bool,
+    /// If `true`, any Unicode whitespace before a match counts as a word
+    /// boundary, not just the ASCII space.
+    pub(crate) unicode_word_boundaries: bool,
+    /// Strategy for assigning query characters in the `Matches` tier.
+    pub(crate) fuzzy_mode: FuzzyMode,
+    /// If `true`, `Matches` spreads are counted in grapheme clusters.
+    pub(crate) grapheme_matching: bool,
+    /// Number of greedy scans whose best score a `Matches` rank takes, or
+    /// `None` for a single scan; see [`ensemble_ranking`].
+    pub(crate) ensemble_windows: Option<usize>,
+    /// If `true`, candidates lacking the query's first character are
+    /// rejected before ranking; see [`prefilter_rejects`].
+    pub(crate) prefilter: bool,
+    /// If `true`, keys extracting no values or empty strings are treated as
+    /// [`Key::require_non_empty`](crate::Key::require_non_empty) keys.
+    pub(crate) strict_empty_values: bool,
+    /// If `true`, candidates were already prepared with these settings and
+    /// are ranked as they are; see [`prepare`](Self::prepare).
+    pub(crate) pre_normalized: bool,
+    /// The rank a require-match key needs when it has no threshold of its
+    /// own, or `None` to accept any match.
+    pub(crate) match_floor: Option<Ranking>,
+    /// Per-language normalizers by BCP-47 tag, or `None` when empty.
+    pub(crate) language_normalizers: Option<&'a HashMap<String, Box<LanguageNormalizer>>>,
+    /// Custom case fold for keys with a language, or `None` for the
+    /// built-in case rules.
+    pub(crate) case_fold: Option<&'a CaseFold>,
+    /// Case rules of the key being ranked, or `None` for default
+    /// lowercasing. Only set by the key loop, and only when the rules fold
+    /// differently.
+    pub(crate) language: Option<LanguageAwareOptions<'a>>,
+    /// Custom `Matches` sub-score formula, or `None` for the default.
+    pub(crate) closeness_scorer: Option<&'a ClosenessScorer>,
+    /// Custom word splitter replacing the built-in word boundaries, or
+    /// `None` to use them.
+    pub(crate) word_splitter: Option<&'a WordSplitter>,
+    /// Cache consulted before ranking each candidate. Only set when every
+    /// other switch has its default value, since cached entries are keyed
+    /// on the candidate, query and diacritics setting alone.
+    pub(crate) ranking_cache: Option<&'a Mutex<RankingCache>>,
+}
+
+impl<'a> RankingConfig<'a> {
+    /// The number of ensemble windows, when they replace the single greedy
+    /// scan. Optimal mode already finds the tightest span.
+    fn greedy_ensemble_windows(&self) -> Option<usize> {
+        self.ensemble_windows
+            .filter(|_| self.fuzzy_mode == FuzzyMode::Greedy)
+    }
+
+    /// Create a config with default behavior and the given diacritics setting.
+    pub(crate) fn with_diacritics(keep_diacritics: bool) -> Self {
+        Self {
+            diacritics: DiacriticsMode::from_keep_diacritics(keep_diacritics),
+            ..Self::default()
+        }
+    }
+
+    /// Returns whether the match starting at byte offset `pos` (> 0) of
+    /// `candidate` begins at a word boundary.
+    ///
+    /// Match positions from `memmem` always fall on a UTF-8 character
+    /// boundary, so the preceding byte is either ASCII or the final
+    /// continuation byte (`0x80..=0xBF`) of a multi-byte character. Neither
+    /// space nor hyphen can collide with a continuation byte, so an ASCII
+    /// predecessor is settled by a single byte comparison. Only in
+    /// `unicode_word_boundaries` mode is a multi-byte predecessor decoded
+    /// and tested with [`char::is_whitespace`].
+    ///
+    /// With a word splitter, `split_starts` holds the word starts it found
+    /// (see [`split_word_starts`](Self::split_word_starts)) and replaces
+    /// the built-in rules.
+    fn is_word_boundary_at(
+        &self,
+        candidate: &str,
+        pos: usize,
+        split_starts: Option<&[usize]>,
+    ) -> bool {
+        if let Some(starts) = split_starts {
+            return starts.binary_search(&pos).is_ok();
+        }
+        let b = candidate.as_bytes()[pos - 1];
+        if b.is_ascii() {
+            return b == b' '
+                || (self.hyphen_is_word_boundary && b == b'-')
+                || (self.unicode_word_boundaries && char::from(b).is_whitespace());
+        }
+        self.unicode_word_boundaries
+            && candidate[..pos]
+                .chars()
+                .next_back()
+                .is_some_and(char::is_whitespace)
+    }
+
+    /// The normalizer for the BCP-47 language `tag`: from the options'
+    /// table, by full tag and then by primary subtag, falling back to the
+    /// built-in normalizers.
+    pub(crate) fn language_normalizer(&self, tag: &str) -> Option<&'a LanguageNormalizer> {
+        let custom = self.language_normalizers.and_then(|table| {
+            table
+                .get(tag)
+                .or_else(|| table.get(language::primary_subtag(tag)))
+        });
+        match custom {
+            Some(normalize) => Some(normalize.as_ref()),
+            None => language::builtin_entry(tag).map(|normalize| normalize as &LanguageNormalizer),
+        }
+    }
+
+    /// `value` prepared for comparison with these settings, or borrowed as
+    /// is when the key already prepared it.
+    pub(crate) fn prepare<'v>(&self, value: &'v str) -> Cow<'v, str> {
+        if self.pre_normalized {
+            Cow::Borrowed(value)
+        } else {
+            prepare_value_with_form(value, self.diacritics, self.normalization)
+        }
+    }
+
+    /// The case rules for a key in language `tag`, or `None` when they
+    /// lowercase like [`lowercase_into`].
+    pub(crate) fn case_rules<'t>(&self, tag: &'t str) -> Option<LanguageAwareOptions<'t>>
+    where
+        'a: 't,
+    {
+        Some(LanguageAwareOptions {
+            tag,
+            case_fold: self.case_fold,
+        })
+        .filter(LanguageAwareOptions::folds_specially)
+    }
+
+    /// Lowercase `s` into `buf` with the case rules of the key being
+    /// ranked.
+    pub(crate) fn lowercase_into(&self, s: &str, buf: &mut String) {
+        match self.language {
+            Some(language) => language.lowercase_into(s, buf),
+            None => self.case_folding.fold_into(s, buf),
+        }
+    }
+
+    /// Whether [`lowercase_into`](Self::lowercase_into) maps every ASCII
+    /// byte to one ASCII byte. Language rules and Turkic folding (`"I"` to
+    /// `"\u{131}"`) do not.
+    fn folds_ascii_bytewise(&self) -> bool {
+        self.language.is_none() && self.case_folding != UnicodeCaseFolding::Turkic
+    }
+
+    /// The UTF-8 length of `c` after [`lowercase_into`](Self::lowercase_into).
+    fn lowercase_len(&self, c: char) -> usize {
+        match self.language {
+            Some(language) => language.lowercase_len(c),
+            None => self.case_folding.folded_len(c),
+        }
+    }
+
+    /// The word starts the word splitter finds in `candidate` (prepared but
+    /// not lowercased), as sorted byte offsets into its lowercased form, or
+    /// `None` without a splitter.
+    fn split_word_starts(&self, candidate: &str) -> Option<Vec<usize>> {
+        let splitter = self.word_splitter?;
+        let mut starts: Vec<usize> = splitter(candidate)
+            .into_iter()
+            .map(|word| word.start)
+            .collect();
+        starts.sort_unstable();
+        starts.dedup();
+        Some(lowercase_offsets(candidate, &starts, self))
+    }
+
+    /// Returns whether `acronym` satisfies the configured acronym match mode
+    /// for the lowercased query `query`.
+    fn acronym_matches(&self, acronym: &str, query: &str) -> bool {
+        if self.acronym_exact_match {
+            acronym == query
+        } else if self.acronym_prefix_match {
+            acronym.starts_with(query)
+        } else {
+            acronym.contains(query)
+        }
+    }
+}
+
+/// Inner hot-path ranking function using pre-prepared query data and a
+/// reusable candidate buffer.
+///
+/// This avoids redundant query preparation, lowercasing, and allocation
+/// when called repeatedly in a loop (e.g., inside `match_sorter`).
+///
+/// # Arguments
+///
+/// * `test_string` - The candidate string being evaluated
+/// * `pq` - Pre-computed query data
+/// * `config` - Ranking behavior switches (diacritics, acronym mode, ...)
+/// * `candidate_buf` - Reusable buffer for lowercasing the candidate
+/// * `finder` - SIMD-accelerated substring searcher for the lowercased query,
+///   or `None` when the query is empty (since `memmem` panics on empty needles)
+pub(crate) fn get_match_ranking_prepared(
+    test_string: &str,
+    pq: &PreparedQuery,
+    config: &RankingConfig<'_>,
+    candidate_buf: &mut String,
+    finder: Option<&memchr::memmem::Finder<'_>>,
+) -> Ranking {
+    if let Some(cache) = config.ranking_cache {
+        let uncached = RankingConfig {
+            ranking_cache: None,
+            ..*config
+        };
+        // A panic while holding the lock cannot leave an entry half-written
+        // in a way that matters, so a poisoned cache is still usable.
+        return cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert_with(test_string, &pq.prepared, pq.diacritics, || {
+                get_match_ranking_prepared(test_string, pq, &uncached, candidate_buf, finder)
+            });
+    }
+
+    // Prepare candidate (normalize and strip diacritics if requested).
+    let candidate = config.prepare(test_string);
+
+    // Step 0: Optionally reject candidates missing the query's first char.
+    if config.prefilter && prefilter_rejects(&candidate, pq, config) {
+        return Ranking::NoMatch;
+    }
+
+    // Step 1: If query has more characters than candidate, no match is possible.
+    // ASCII fast path: byte length equals character count for ASCII strings.
+    // Full case folding can lengthen the candidate ("\u{df}" folds to "ss"),
+    // so then the check waits for the folded form in step 3.
+    let expands = config.language.is_none() && config.case_folding == UnicodeCaseFolding::Full;
+    let candidate_char_count = if candidate.is_ascii() {
+        candidate.len()
+    } else {
+        candidate.chars().count()
+    };
+    if pq.char_count > candidate_char_count && !expands {
+        return Ranking::NoMatch;
+    }
+
+    // Step 2: Case-sensitive exact equality on the prepared strings.
+    if *candidate == *pq.prepared {
+        return Ranking::CaseSensitiveEqual;
+    }
+
+    // Step 3: Lowercase candidate into reusable buffer (avoids allocation).
+    config.lowercase_into(&candidate, candidate_buf);
+    if expands && pq.char_count > candidate_buf.chars().count() {
+        return Ranking::NoMatch;
+    }
+
+    // Steps 4-8: Substring search.
+    if let Some(finder) = finder {
+        // Use SIMD-accelerated memmem for substring search.
+        let candidate_bytes = candidate_buf.as_bytes();
+        let mut iter = finder.find_iter(candidate_bytes);
+
+        if let Some(first) = iter.next() {
+            if first == 0 {
+                // Step 5: Substring at byte position 0 with equal byte
+                // lengths means the lowercased strings are identical -> Equal.
+                if candidate_buf.len() == pq.lower.len() {
+                    return Ranking::Equal;
+                }
+                // Step 6: Starts with query but is longer -> StartsWith.
+                return Ranking::StartsWith;
+            }
+
+            // Step 7: Check if any match position sits at a word boundary.
+            // A word boundary means the character immediately before the
+            // match is a space (0x20), a hyphen or other Unicode whitespace
+            // when configured, or a word start found by the word splitter.
+            // We already know first > 0 here.
+            let split_starts = config.split_word_starts(&candidate);
+            let split_starts = split_starts.as_deref();
+            if config.is_word_boundary_at(candidate_buf, first, split_starts) {
+                return Ranking::WordStartsWith;
+            }
+            // Check remaining match positions lazily.
+            for pos in iter {
+                if pos > 0 && config.is_word_boundary_at(candidate_buf, pos, split_starts) {
+                    return Ranking::WordStartsWith;
+                }
+            }
+
+            // Step 8: A substring match exists but not at a word boundary.
+            return Ranking::Contains;
+        }
+    } else {
+        // Empty query: always found at position 0.
+        if candidate_buf.is_empty() {
+            // Both are empty after lowercasing -> Equal.
+            return Ranking::Equal;
+        }
+        return Ranking::StartsWith;
+    }
+
+    // No substring match found. Continue to acronym and fuzzy matching.
+
+    // Step 9: Single-character query that was not found as a substring cannot
+    // match via acronym or fuzzy.
+    if pq.char_count == 1 {
+        return Ranking::NoMatch;
+    }
+
+    // Step 9b: Acronym and fuzzy matches both draw every query character
+    // from the candidate, so a candidate missing any of them cannot match.
+    if !pq.histogram.is_subset_of(candidate_buf) {
+        return Ranking::NoMatch;
+    }
+
+    // Step 10: Compute acronym of the lowercased candidate. By default, an
+    // acronym containing the lowercased query as a substring is an acronym
+    // match; the prefix and exact modes tighten this check.
+    let acronym = get_acronym_bounded(
+        candidate_buf,
+        config.acronym_max_length.unwrap_or(usize::MAX),
+    );
+    if config.acronym_matches(&acronym, &pq.lower) {
+        return Ranking::Acronym;
+    }
+
+    // Step 11: Attempt fuzzy closeness ranking on the lowercased strings.
+    if let Some(n_windows) = config.greedy_ensemble_windows() {
+        return ensemble_ranking(
+            candidate_buf,
+            &pq.lower,
+            n_windows,
+            config.grapheme_matching,
+            config.closeness_scorer,
+        )
+        .0;
+    }
+    get_closeness_ranking_with(
+        candidate_buf,
+        &pq.lower,
+        config.fuzzy_mode,
+        config.grapheme_matching,
+        config.closeness_scorer,
+    )
+}
+
+/// The part of `value` that matched the prepared query, given the `rank`
+/// that [`get_match_ranking_prepared`] assigned to `value`.
+///
+/// Locates the match in the lowercased, normalized candidate and maps it
+/// back onto `value`:
+///
+/// - `CaseSensitiveEqual` and `Equal`: all of `value`.
+/// - `StartsWith`, `WordStartsWith` and `Contains`: the occurrence of the
+///   query that earned the tier (the first one at a word boundary for
+///   `WordStartsWith`).
+/// - `Acronym`: the acronym of `value`, in its original case.
+/// - `Matches`: from the first to the last matched character.
+///
+/// `NoMatch` yields `None`. Only the acronym is owned; every other portion
+/// borrows from `value`.
+pub(crate) fn get_matched_portion<'v>(
+    value: &'v str,
+    rank: Ranking,
+    pq: &PreparedQuery,
+    config: &RankingConfig<'_>,
+    candidate_buf: &mut String,
+    finder: Option<&memchr::memmem::Finder<'_>>,
+) -> Option<Cow<'v, str>> {
+    match rank {
+        Ranking::NoMatch => return None,
+        Ranking::CaseSensitiveEqual | Ranking::Equal => return Some(Cow::Borrowed(value)),
+        Ranking::Acronym => {
+            let max_len = config.acronym_max_length.unwrap_or(usize::MAX);
+            return Some(Cow::Owned(get_acronym_bounded(value, max_len)));
+        }
+        // ASCII folds byte for byte, so the prefix needs no search.
+        Ranking::StartsWith if value.is_ascii() && config.folds_ascii_bytewise() => {
+            return value.get(..pq.lower.len()).map(Cow::Borrowed);
+        }
+        _ => {}
+    }
+    let folded = folded_match_span(value, rank, pq, config, candidate_buf, finder)?;
+    let range = unfold_range(value, folded, candidate_buf.len(), config)?;
+    Some(Cow::Borrowed(&value[range]))
+}
+
+/// Byte ranges of `value` to highlight for a match of `rank`, as
+/// described by [`get_matched_portion`], but with one range per matched
+/// character for `Matches` and one per acronym letter that spells the
+/// query for `Acronym`.
+///
+/// Ranges are sorted, non-empty and do not touch: adjacent matched
+/// characters share one range. `NoMatch`, and a match that cannot be
+/// mapped back onto `value`, yield no ranges.
+pub(crate) fn get_matched_ranges(
+    value: &str,
+    rank: Ranking,
+    pq: &PreparedQuery,
+    config: &RankingConfig<'_>,
+    candidate_buf: &mut String,
+    finder: Option<&memchr::memmem::Finder<'_>>,
+) -> Vec<Range<usize>> {
+    let ranges = match rank {
+        Ranking::NoMatch => return Vec::new(),
+        Ranking::CaseSensitiveEqual | Ranking::Equal => {
+            let whole = 0..value.len();
+            vec![whole]
+        }
+        _ => {
+            let folded = match rank {
+                Ranking::Acronym => {
+                    let candidate =
+                        prepare_value_with_form(value, config.diacritics, config.normalization);
+                    config.lowercase_into(&candidate, candidate_buf);
+                    acronym_query_ranges(candidate_buf, &pq.lower, config)
+                }
+                _ => {
+                    let Some(span) =
+                        folded_match_span(value, rank, pq, config, candidate_buf, finder)
+                    else {
+                        return Vec::new();
+                    };
+                    matched_char_ranges(candidate_buf, span, rank, pq)
+                }
+            };
+            let folded_len = candidate_buf.len();
+            let Some(ranges) = folded
+                .into_iter()
+                .map(|range| unfold_range(value, range, folded_len, config))
+                .collect::<Option<Vec<_>>>()
+            else {
+                return Vec::new();
+            };
+            ranges
+        }
+    };
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges.into_iter().filter(|range| !range.is_empty()) {
+        match merged.last_mut() {
+            Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Byte ranges, within the folded candidate, of the characters in `span`
+/// that matched: each query character for `Matches`, otherwise the whole
+/// span.
+fn matched_char_ranges(
+    candidate: &str,
+    span: Range<usize>,
+    rank: Ranking,
+    pq: &PreparedQuery,
+) -> Vec<Range<usize>> {
+    match rank {
+        Ranking::Matches(_) => {
+            // Greedy matching from the span's first character ends
+            // at its last, whichever fuzzy mode found the span.
+            let mut query = pq.lower.chars().peekable();
+            let mut ranges = Vec::with_capacity(pq.char_count);
+            for (byte, c) in candidate[span.clone()].char_indices() {
+                if query.next_if_eq(&c).is_some() {
+                    let start = span.start + byte;
+                    ranges.push(start..start + c.len_utf8());
+                }
+            }
+            ranges
+        }
+        _ => vec![span],
+    }
+}
+
+/// Byte ranges, within the folded `candidate`, of the acronym letters that
+/// spell `query`: the letters [`get_acronym_bounded`] takes from
+/// `candidate`, narrowed to the first occurrence of `query` among them.
+fn acronym_query_ranges(
+    candidate: &str,
+    query: &str,
+    config: &RankingConfig<'_>,
+) -> Vec<Range<usize>> {
+    let max_len = config.acronym_max_length.unwrap_or(usize::MAX);
+    let mut letters = Vec::new();
+    let mut prev: Option<char> = None;
+    for (byte, c) in candidate.char_indices() {
+        if letters.len() == max_len {
+            break;
+        }
+        if prev.is_none_or(|p| is_acronym_delimiter(p) && !is_acronym_delimiter(c)) {
+            letters.push((c, byte..byte + c.len_utf8()));
+        }
+        prev = Some(c);
+    }
+    let acronym: String = letters.iter().map(|(c, _)| c).collect();
+    let Some(start) = acronym.find(query) else {
+        return Vec::new();
+    };
+    let first = acronym[..start].chars().count();
+    letters
+        .into_iter()
+        .skip(first)
+        .take(query.chars().count())
+        .map(|(_, range)| range)
+        .collect()
+}
+
+/// The byte range, in the lowercased, normalized form of `value` that it
+/// leaves in `candidate_buf`, of the match that earned `rank`: the query
+/// occurrence for `StartsWith`, `WordStartsWith` and `Contains`, and the
+/// first to last matched character for `Matches`. `None` for other tiers.
+fn folded_match_span(
+    value: &str,
+    rank: Ranking,
+    pq: &PreparedQuery,
+    config: &RankingConfig<'_>,
+    candidate_buf: &mut String,
+    finder: Option<&memchr::memmem::Finder<'_>>,
+) -> Option<Range<usize>> {
+    let candidate = prepare_value_with_form(value, config.diacritics, config.normalization);
+    config.lowercase_into(&candidate, candidate_buf);
+    let folded = match rank {
+        Ranking::StartsWith => 0..pq.lower.len(),
+        Ranking::WordStartsWith | Ranking::Contains => {
+            let split_starts = match rank {
+                Ranking::WordStartsWith => config.split_word_starts(&candidate),
+                _ => None,
+            };
+            let start = finder?.find_iter(candidate_buf.as_bytes()).find(|&pos| {
+                rank == Ranking::Contains
+                    || (pos > 0
+                        && config.is_word_boundary_at(candidate_buf, pos, split_starts.as_deref()))
+            })?;
+            start..start + pq.lower.len()
+        }
+        Ranking::Matches(_) => {
+            let (first, last) = match (config.fuzzy_mode, config.greedy_ensemble_windows()) {
+                (FuzzyMode::Greedy, Some(n_windows)) => {
+                    ensemble_ranking(
+                        candidate_buf,
+                        &pq.lower,
+                        n_windows,
+                        config.grapheme_matching,
+                        config.closeness_scorer,
+                    )
+                    .2
+                }
+                (FuzzyMode::Greedy, None) => greedy_match_span(candidate_buf, &pq.lower),
+                (FuzzyMode::Optimal, _) => optimal_match_span(candidate_buf, &pq.lower),
+            }?;
+            let mut chars = candidate_buf.char_indices().skip(first);
+            let (start, _) = chars.clone().next()?;
+            let (end, c) = chars.nth(last - first)?;
+            start..end + c.len_utf8()
+        }
+        _ => return None,
+    };
+    Some(folded)
+}
+
+/// Map a byte range of the lowercased, normalized form of `value` (whose
+/// length is `folded_len`) back to a byte range of `value`.
+///
+/// Lowercasing, diacritics stripping and compatibility folding all work one
+/// character at a time, so each character of `value` owns a contiguous run
+/// of the folded bytes. The result covers every character owning part of
+/// `folded`, plus any stripped combining marks that follow it. Returns
+/// `None` if the folded lengths do not add up to `folded_len`, as happens
+/// when NFKC composes a letter with a kept combining mark.
+fn unfold_range(
+    value: &str,
+    folded: Range<usize>,
+    folded_len: usize,
+    config: &RankingConfig<'_>,
+) -> Option<Range<usize>> {
+    // ASCII is neither stripped nor resized by default lowercasing.
+    if value.is_ascii() && config.folds_ascii_bytewise() {
+        return (value.len() == folded_len).then_some(folded);
+    }
+    let mut start = None;
+    let mut end = None;
+    let mut offset = 0;
+    let mut utf8 = [0; 4];
+    for (byte, c) in value.char_indices() {
+        let stripped = prepare_value_with_form(
+            c.encode_utf8(&mut utf8),
+            config.diacritics,
+            config.normalization,
+        );
+        let len: usize = stripped.chars().map(|c| config.lowercase_len(c)).sum();
+        if folded.is_empty() {
+            if start.is_none() && offset >= folded.start {
+                start = Some(byte);
+            }
+        } else if len > 0 {
+            if offset < folded.end && offset + len > folded.start {
+                start.get_or_insert(byte);
+                end = Some(byte + c.len_utf8());
+            }
+        } else if end == Some(byte) {
+            // Keep stripped combining marks with the character they follow.
+            end = Some(byte + c.len_utf8());
+        }
+        offset += len;
+    }
+    if offset != folded_len || (start.is_none() && !folded.is_empty()) {
+        return None;
+    }
+    let start = start.unwrap_or(value.len());
+    Some(start..end.unwrap_or(start))
+}
+
+/// Determine how well a candidate string matches a search query.
+///
+/// Implements an 11-step algorithm that classifies the match into one of the
+/// 8 ranking tiers, checked in descending order of specificity. The first
+/// matching tier is returned.
+///
+/// Both inputs are first prepared via [`prepare_value_for_comparison`] to
+/// optionally strip diacritics. Steps 1-2 operate on the prepared strings;
+/// steps 3-11 operate on lowercased versions of those strings.
+///
+/// # Arguments
+///
+/// * `test_string` - The candidate string being evaluated
+/// * `string_to_rank` - The search query
+/// * `keep_diacritics` - If `true`, skip diacritics stripping
+///
+/// # Returns
+///
+/// The [`Ranking`] tier that best describes how the query matches the candidate.
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{get_match_ranking, Ranking};
+///
+/// assert_eq!(get_match_ranking("Green", "Green", false), Ranking::CaseSensitiveEqual);
+/// assert_eq!(get_match_ranking("Green", "green", false), Ranking::Equal);
+/// assert_eq!(get_match_ranking("Greenland", "green", false), Ranking::StartsWith);
+/// assert_eq!(get_match_ranking("abc", "xyz", false), Ranking::NoMatch);
+/// ```
+pub fn get_match_ranking(
+    test_string: &str,
+    string_to_rank: &str,
+    keep_diacritics: bool,
+) -> Ranking {
+    // Thin wrapper: construct a PreparedQuery for one-off calls.
+    let pq = PreparedQuery::new(string_to_rank, keep_diacritics);
+    let finder = if pq.lower.is_empty() {
+        None
+    } else {
+        Some(memchr::memmem::Finder::new(pq.lower.as_bytes()))
+    };
+    let mut buf = String::new();
+    let config = RankingConfig::with_diacritics(keep_diacritics);
+    get_match_ranking_prepared(test_string, &pq, &config, &mut buf, finder.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_tier_ordering_descending() {
+        // Verify the complete ordering chain from best to worst.
+        assert!(Ranking::CaseSensitiveEqual > Ranking::Equal);
+        assert!(Ranking::Equal > Ranking::StartsWith);
+        assert!(Ranking::StartsWith > Ranking::WordStartsWith);
+        assert!(Ranking::WordStartsWith > Ranking::Contains);
+        assert!(Ranking::Contains > Ranking::Acronym);
+        assert!(Ranking::Acronym > Ranking::Matches(1.5));
+        assert!(Ranking::Matches(1.5) > Ranking::NoMatch);
+    }
+
+    #[test]
+    fn matches_sub_score_ordering() {
+        // Higher sub-score means a better (greater) ranking.
+        assert!(Ranking::Matches(1.9) > Ranking::Matches(1.1));
+        assert!(Ranking::Matches(2.0) > Ranking::Matches(1.5));
+        assert!(Ranking::Matches(1.5) > Ranking::Matches(1.01));
+    }
+
+    #[test]
+    fn matches_below_acronym_above_no_match() {
+        // Any valid Matches sub-score (1.0, 2.0] is below Acronym (2)
+        // and above NoMatch (0).
+        assert!(Ranking::Acronym > Ranking::Matches(2.0));
+        assert!(Ranking::Matches(1.01) > Ranking::NoMatch);
+    }
+
+    #[test]
+    fn equality_same_fixed_tiers() {
+        assert_eq!(Ranking::CaseSensitiveEqual, Ranking::CaseSensitiveEqual);
+        assert_eq!(Ranking::Equal, Ranking::Equal);
+        assert_eq!(Ranking::NoMatch, Ranking::NoMatch);
+    }
+
+    #[test]
+    fn equality_same_matches_sub_score() {
+        assert_eq!(Ranking::Matches(1.5), Ranking::Matches(1.5));
+    }
+
+    #[test]
+    fn inequality_different_tiers() {
+        assert_ne!(Ranking::CaseSensitiveEqual, Ranking::Equal);
+        assert_ne!(Ranking::Matches(1.5), Ranking::NoMatch);
+    }
+
+    #[test]
+    fn inequality_different_sub_scores() {
+        assert_ne!(Ranking::Matches(1.2), Ranking::Matches(1.8));
+    }
+
+    #[test]
+    fn debug_formatting() {
+        // Verify Debug is derived and produces reasonable output.
+        let debug_str = format!("{:?}", Ranking::Matches(1.5));
+        assert!(debug_str.contains("Matches"));
+        assert!(debug_str.contains("1.5"));
+
+        let debug_str = format!("{:?}", Ranking::CaseSensitiveEqual);
+        assert!(debug_str.contains("CaseSensitiveEqual"));
+    }
+
+    #[test]
+    fn copy_produces_equal_value() {
+        let original = Ranking::Matches(1.75);
+        let copied = original;
+        assert_eq!(original, copied);
+
+        let original = Ranking::Contains;
+        let copied = original;
+        assert_eq!(original, copied);
+    }
+
+    #[test]
+    fn matches_at_boundary_values() {
+        // Sub-score at the upper boundary (2.0) is still below Acronym.
+        assert!(Ranking::Acronym > Ranking::Matches(2.0));
+        // Sub-score just above the lower boundary is above NoMatch.
+        assert!(Ranking::Matches(1.001) > Ranking::NoMatch);
+    }
+
+    // --- get_acronym tests ---
+
+    #[test]
+    fn acronym_hyphen_and_space() {
+        assert_eq!(get_acronym("north-west airlines"), "nwa");
+    }
+
+    #[test]
+    fn acronym_space_only() {
+        assert_eq!(get_acronym("san francisco"), "sf");
+    }
+
+    #[test]
+    fn acronym_single_word() {
+        assert_eq!(get_acronym("single"), "s");
+    }
+
+    #[test]
+    fn acronym_empty_string() {
+        assert_eq!(get_acronym(""), "");
+    }
+
+    #[test]
+    fn acronym_underscores_not_delimiters() {
+        // Underscores do NOT act as word boundaries.
+        assert_eq!(get_acronym("snake_case_word"), "s");
+    }
+
+    #[test]
+    fn acronym_consecutive_spaces() {
+        // Consecutive delimiters: only the non-delimiter char after the
+        // last delimiter in a run is included.
+        assert_eq!(get_acronym("hello  world"), "hw");
+    }
+
+    #[test]
+    fn acronym_consecutive_hyphens() {
+        assert_eq!(get_acronym("a--b"), "ab");
+    }
+
+    #[test]
+    fn acronym_mixed_delimiters() {
+        assert_eq!(get_acronym("one two-three four"), "ottf");
+    }
+
+    #[test]
+    fn acronym_single_char() {
+        assert_eq!(get_acronym("x"), "x");
+    }
+
+    #[test]
+    fn acronym_trailing_delimiter() {
+        // Trailing delimiter produces no extra character.
+        assert_eq!(get_acronym("hello "), "h");
+    }
+
+    #[test]
+    fn acronym_bounded_truncates() {
+        assert_eq!(get_acronym_bounded("one two three four five", 3), "ott");
+        assert_eq!(get_acronym_bounded("one two three four five", 5), "ottff");
+        assert_eq!(get_acronym_bounded("one two", 10), "ot");
+        assert_eq!(get_acronym_bounded("one two", 0), "");
+        assert_eq!(get_acronym_bounded("", 3), "");
+    }
+
+    #[test]
+    fn acronym_bounded_counts_chars_not_bytes() {
+        assert_eq!(
+            get_acronym_bounded("\u{00e9}t\u{00e9} \u{00e0} \u{00f4}ter", 2),
+            "\u{00e9}\u{00e0}"
+        );
+    }
+
+    #[test]
+    fn acronym_bounded_with_mixed_delimiters() {
+        let s = "north-northeast by-east turning-southeast";
+        assert_eq!(get_acronym(s), "nnbets");
+        assert_eq!(get_acronym_bounded(s, 4), "nnbe");
+        assert_eq!(get_acronym_bounded("a--b  c", 2), "ab");
+    }
+
+    #[test]
+    fn acronym_unbounded_matches_get_acronym() {
+        for s in ["north-west airlines", "san francisco", "x", "hello ", ""] {
+            assert_eq!(get_acronym_bounded(s, usize::MAX), get_acronym(s));
+        }
+    }
+
+    // --- prepare_value_for_comparison tests ---
+
+    #[test]
+    fn strips_combining_acute_accent() {
+        // "cafe" followed by U+0301 COMBINING ACUTE ACCENT -> "cafe"
+        let result = prepare_value_for_comparison("cafe\u{0301}", false);
+        assert_eq!(result, "cafe");
+        assert!(matches!(result, Cow::Owned(_)));
+    }
+
+    #[test]
+    fn returns_borrowed_for_plain_ascii() {
+        // Pure ASCII with no diacritics should not allocate.
+        let result = prepare_value_for_comparison("cafe", false);
+        assert_eq!(result, "cafe");
+        assert!(matches!(result, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn returns_borrowed_when_keep_diacritics_is_true() {
+        // When keep_diacritics is true, the input is returned as-is.
+        let input = "cafe\u{0301}";
+        let result = prepare_value_for_comparison(input, true);
+        assert_eq!(result, input);
+        assert!(matches!(result, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn strips_precomposed_accent() {
+        // U+00E9 (LATIN SMALL LETTER E WITH ACUTE) is a single precomposed
+        // codepoint that NFD decomposes into 'e' + U+0301.
+        let result = prepare_value_for_comparison("caf\u{00E9}", false);
+        assert_eq!(result, "cafe");
+        assert!(matches!(result, Cow::Owned(_)));
+    }
+
+    #[test]
+    fn strips_multiple_diacritics() {
+        // U+00FC = u with diaeresis, U+00F1 = n with tilde
+        let result = prepare_value_for_comparison("\u{00FC}ber-ma\u{00F1}ana", false);
+        assert_eq!(result, "uber-manana");
+        assert!(matches!(result, Cow::Owned(_)));
+    }
+
+    #[test]
+    fn returns_borrowed_for_empty_string() {
+        let result = prepare_value_for_comparison("", false);
+        assert_eq!(result, "");
+        assert!(matches!(result, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn returns_borrowed_for_non_ascii_without_diacritics() {
+        // Early-exit path: CJK characters have no combining marks, so the
+        // pre-scan returns Cow::Borrowed immediately without NFD decomposition
+        // or heap allocation.
+        let result = prepare_value_for_comparison("\u{4e16}\u{754c}", false);
+        assert_eq!(result, "\u{4e16}\u{754c}");
+        assert!(matches!(result, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn keep_diacritics_true_with_plain_ascii() {
+        let result = prepare_value_for_comparison("hello", true);
+        assert_eq!(result, "hello");
+        assert!(matches!(result, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn spanish_mode_keeps_tilde_but_strips_accents() {
+        let spanish = DiacriticsMode::StripNonDistinctive(DiacriticsLanguage::Spanish);
+        assert_eq!(
+            prepare_value_with_mode("a\u{00f1}o", DiacriticsMode::StripAll),
+            "ano"
+        );
+        assert_eq!(
+            prepare_value_with_mode("a\u{00f1}o", spanish),
+            "an\u{0303}o"
+        );
+        // Composed and decomposed spellings prepare identically.
+        assert_eq!(
+            prepare_value_with_mode("an\u{0303}o", spanish),
+            "an\u{0303}o"
+        );
+        assert_eq!(
+            prepare_value_with_mode("Canci\u{00f3}n", spanish),
+            "Cancion"
+        );
+        assert!(matches!(
+            prepare_value_with_mode("ano", spanish),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            prepare_value_with_mode("an\u{0303}o", spanish),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn language_modes_keep_only_their_marks() {
+        let german = DiacriticsMode::StripNonDistinctive(DiacriticsLanguage::German);
+        let french = DiacriticsMode::StripNonDistinctive(DiacriticsLanguage::French);
+        assert_eq!(
+            prepare_value_with_mode("M\u{00fc}ller", german),
+            "Mu\u{0308}ller"
+        );
+        assert_eq!(prepare_value_with_mode("Caf\u{00e9}", german), "Cafe");
+        assert_eq!(prepare_value_with_mode("a\u{00f1}o", german), "ano");
+        for s in [
+            "No\u{00eb}l",
+            "cr\u{00e8}me br\u{00fb}l\u{00e9}e",
+            "a\u{00f1}o",
+        ] {
+            assert_eq!(
+                prepare_value_with_mode(s, french),
+                prepare_value_for_comparison(s, false)
+            );
+        }
+        assert_eq!(
+            prepare_value_with_mode("a\u{00f1}o", DiacriticsMode::Preserve),
+            "a\u{00f1}o"
+        );
+    }
+
+    #[test]
+    fn ano_ranks_differently_under_strip_all_and_spanish() {
+        let rank = |candidate: &str, query: &str, mode: DiacriticsMode| {
+            PreparedQuery::with_diacritics_mode(query, mode).apply_batch(&[candidate])[0]
+        };
+        let spanish = DiacriticsMode::StripNonDistinctive(DiacriticsLanguage::Spanish);
+        assert_eq!(
+            rank("a\u{00f1}o", "ano", DiacriticsMode::StripAll),
+            Ranking::CaseSensitiveEqual
+        );
+        assert!(rank("a\u{00f1}o", "ano", spanish) < Ranking::Acronym);
+        assert_eq!(
+            rank("a\u{00f1}o", "an\u{0303}o", spanish),
+            Ranking::CaseSensitiveEqual
+        );
+        assert_eq!(rank("A\u{00d1}O", "a\u{00f1}o", spanish), Ranking::Equal);
+        assert_eq!(rank("Canci\u{00f3}n", "cancion", spanish), Ranking::Equal);
+    }
+
+    #[test]
+    fn strips_combining_tilde() {
+        // 'n' + U+0303 COMBINING TILDE -> "n"
+        let result = prepare_value_for_comparison("n\u{0303}", false);
+        assert_eq!(result, "n");
+        assert!(matches!(result, Cow::Owned(_)));
+    }
+
+    #[test]
+    fn strips_multiple_combining_marks_on_single_base() {
+        // 'a' + U+0300 (grave) + U+0301 (acute) -> "a"
+        // Multiple stacked combining marks should all be removed.
+        let result = prepare_value_for_comparison("a\u{0300}\u{0301}", false);
+        assert_eq!(result, "a");
+        assert!(matches!(result, Cow::Owned(_)));
+    }
+
+    #[test]
+    fn preserves_o_stroke_unchanged() {
+        // U+00F8 (LATIN SMALL LETTER O WITH STROKE) does NOT decompose via
+        // NFD -- its NFD form is itself, with no combining marks. The
+        // LATIN1_STRIP table must map it to 0 (preserve) rather than b'o'.
+        let result = prepare_value_for_comparison("\u{00F8}slo", false);
+        assert_eq!(result, "\u{00F8}slo");
+        assert!(
+            matches!(result, Cow::Borrowed(_)),
+            "o-stroke string should be returned borrowed (no allocation)"
+        );
+    }
+
+    // --- ranking_tier_label tests ---
+
+    #[test]
+    fn tier_label_fuzzy_for_any_sub_score() {
+        for score in [1.0, 1.0_f64.next_up(), 1.5, 2.0] {
+            assert_eq!(ranking_tier_label(Ranking::Matches(score)), "fuzzy");
+        }
+    }
+
+    #[test]
+    fn tier_labels_are_distinct() {
+        let labels: std::collections::HashSet<_> = [
+            Ranking::CaseSensitiveEqual,
+            Ranking::Equal,
+            Ranking::StartsWith,
+            Ranking::WordStartsWith,
+            Ranking::Contains,
+            Ranking::Acronym,
+            Ranking::Matches(1.5),
+            Ranking::NoMatch,
+        ]
+        .into_iter()
+        .map(ranking_tier_label)
+        .collect();
+        assert_eq!(labels.len(), 8);
+    }
+
+    // --- get_closeness_ranking tests ---
+
+    #[test]
+    fn closeness_fuzzy_match_playground() {
+        // "plgnd" chars found at positions 0, 1, 4, 8, 9 in "playground".
+        // spread = 9 - 0 = 9, score = 1.0 + 1.0/9 ~= 1.111
+        let rank = get_closeness_ranking("playground", "plgnd");
+        match rank {
+            Ranking::Matches(s) => {
+                assert!(s > 1.0, "sub-score {s} should be > 1.0");
+                assert!(s < 2.0, "sub-score {s} should be < 2.0");
+                // Verify the exact expected value.
+                let expected = 1.0 + 1.0 / 9.0;
+                assert!(
+                    (s - expected).abs() < f64::EPSILON,
+                    "expected {expected}, got {s}"
+                );
+            }
+            other => panic!("expected Matches, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn closeness_no_match() {
+        assert_eq!(get_closeness_ranking("abc", "xyz"), Ranking::NoMatch);
+    }
+
+    #[test]
+    fn closeness_single_char_match() {
+        // Single char "a" found at position 0 in "ab". spread = 0.
+        assert_eq!(get_closeness_ranking("ab", "a"), Ranking::Matches(2.0));
+    }
+
+    #[test]
+    fn closeness_single_char_not_found() {
+        assert_eq!(get_closeness_ranking("ab", "z"), Ranking::NoMatch);
+    }
+
+    #[test]
+    fn closeness_adjacent_chars() {
+        // "abc" in "abcdef": positions 0, 1, 2. spread = 2.
+        // score = 1.0 + 1.0/2 = 1.5
+        let rank = get_closeness_ranking("abcdef", "abc");
+        assert_eq!(rank, Ranking::Matches(1.5));
+    }
+
+    #[test]
+    fn closeness_two_char_query() {
+        // "ad" in "abcdef": positions 0, 3. spread = 3.
+        // score = 1.0 + 1.0/3
+        let rank = get_closeness_ranking("abcdef", "ad");
+        match rank {
+            Ranking::Matches(s) => {
+                let expected = 1.0 + 1.0 / 3.0;
+                assert!(
+                    (s - expected).abs() < f64::EPSILON,
+                    "expected {expected}, got {s}"
+                );
+            }
+            other => panic!("expected Matches, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn closeness_partial_mismatch() {
+        // "a" is found but "z" is not.
+        assert_eq!(get_closeness_ranking("abcdef", "az"), Ranking::NoMatch);
+    }
+
+    #[test]
+    fn closeness_query_longer_than_candidate() {
+        // More query chars than candidate chars: guaranteed NoMatch.
+        assert_eq!(get_closeness_ranking("ab", "abcdef"), Ranking::NoMatch);
+    }
+
+    #[test]
+    fn closeness_result_always_in_range() {
+        // Test several cases to verify the invariant (1.0, 2.0].
+        let cases = [
+            ("abcdefghijklmnop", "ap"),   // spread = 15
+            ("abcdefghijklmnop", "abop"), // spread = 15
+            ("abcdef", "af"),             // spread = 5
+            ("ab", "ab"),                 // spread = 1
+        ];
+        for (candidate, query) in cases {
+            let rank = get_closeness_ranking(candidate, query);
+            match rank {
+                Ranking::Matches(s) => {
+                    assert!(
+                        s > 1.0 && s <= 2.0,
+                        "score {s} out of range for ({candidate}, {query})"
+                    );
+                }
+                other => panic!("expected Matches for ({candidate}, {query}), got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn closeness_case_sensitive() {
+        // The function does case-sensitive matching; caller is responsible
+        // for lowercasing. 'A' != 'a'.
+        assert_eq!(get_closeness_ranking("abc", "A"), Ranking::NoMatch);
+    }
+
+    #[test]
+    fn closeness_empty_query() {
+        // Empty query: no characters to find, spread = 0.
+        assert_eq!(get_closeness_ranking("anything", ""), Ranking::Matches(2.0));
+    }
+
+    #[test]
+    fn closeness_unicode_chars() {
+        // Unicode characters are matched correctly via .chars().
+        // candidate: "a b c" (3 chars), query: "ac" -> positions 0, 2, spread = 2
+        let rank = get_closeness_ranking("a\u{00E9}c", "ac");
+        assert_eq!(rank, Ranking::Matches(1.5));
+    }
+
+    // --- closeness positions tests ---
+
+    #[test]
+    fn closeness_positions_increase_and_match_rank() {
+        for (candidate, query) in [
+            ("playground", "plgnd"),
+            ("aab", "ab"),
+            ("abc", "c"),
+            ("mississippi", "sip"),
+        ] {
+            let (rank, positions) = get_closeness_ranking_with_positions(candidate, query);
+            assert_eq!(rank, get_closeness_ranking(candidate, query));
+            assert_eq!(positions.len(), query.chars().count());
+            assert!(positions.windows(2).all(|w| w[0] < w[1]), "{positions:?}");
+            let chars: Vec<char> = candidate.chars().collect();
+            let matched: String = positions.iter().map(|&p| chars[p]).collect();
+            assert_eq!(matched, query);
+        }
+    }
+
+    #[test]
+    fn closeness_positions_empty_for_no_match_and_empty_query() {
+        assert_eq!(
+            get_closeness_ranking_with_positions("abc", "acx"),
+            (Ranking::NoMatch, Vec::new())
+        );
+        assert_eq!(
+            get_closeness_ranking_with_positions("abc", ""),
+            (Ranking::Matches(2.0), Vec::new())
+        );
+        assert_eq!(
+            get_closeness_ranking_byte_ranges("abc", "cb"),
+            (Ranking::NoMatch, Vec::new())
+        );
+    }
+
+    #[test]
+    fn closeness_byte_ranges_agree_with_char_positions() {
+        for (candidate, query) in [
+            ("playground", "plgnd"),
+            (
+                "cr\u{00e8}me br\u{00fb}l\u{00e9}e",
+                "\u{00e8}\u{00fb}\u{00e9}",
+            ),
+            ("\u{6771}\u{4eac}\u{90fd} tokyo", "\u{4eac}k"),
+            ("a\u{1f600}b\u{1f600}c", "\u{1f600}c"),
+        ] {
+            let (rank, positions) = get_closeness_ranking_with_positions(candidate, query);
+            let (byte_rank, ranges) = get_closeness_ranking_byte_ranges(candidate, query);
+            assert_eq!(rank, byte_rank);
+            assert_eq!(ranges.len(), positions.len());
+            let chars: Vec<char> = candidate.chars().collect();
+            for (range, &pos) in ranges.iter().zip(&positions) {
+                let slice = candidate.get(range.clone()).expect("valid UTF-8 range");
+                assert_eq!(slice, chars[pos].to_string());
+            }
+        }
+    }
+
+    // --- min_of / max_of / clamp tests ---
+
+    #[test]
+    fn max_of_and_min_of_empty() {
+        assert_eq!(Ranking::max_of(std::iter::empty()), None);
+        assert_eq!(Ranking::min_of(Vec::new()), None);
+    }
+
+    #[test]
+    fn max_of_and_min_of_mixed() {
+        let rankings = [
+            Ranking::Matches(1.5),
+            Ranking::Acronym,
+            Ranking::Matches(1.75),
+            Ranking::NoMatch,
+        ];
+        assert_eq!(Ranking::max_of(rankings), Some(Ranking::Acronym));
+        assert_eq!(Ranking::min_of(rankings), Some(Ranking::NoMatch));
+        assert_eq!(
+            Ranking::max_of(
+                rankings
+                    .into_iter()
+                    .filter(|r| matches!(r, Ranking::Matches(_)))
+            ),
+            Some(Ranking::Matches(1.75))
+        );
+    }
+
+    #[test]
+    fn clamp_floor_and_ceiling() {
+        assert_eq!(
+            Ranking::NoMatch.clamp(Ranking::Contains, Ranking::Contains),
+            Ranking::Contains
+        );
+        assert_eq!(
+            Ranking::CaseSensitiveEqual.clamp(Ranking::NoMatch, Ranking::StartsWith),
+            Ranking::StartsWith
+        );
+        assert_eq!(
+            Ranking::WordStartsWith.clamp(Ranking::Acronym, Ranking::StartsWith),
+            Ranking::WordStartsWith
+        );
+    }
+
+    #[test]
+    fn approx_eq_tolerates_sub_score_drift() {
+        assert!(Ranking::Matches(1.5).approx_eq(Ranking::Matches(1.4999), 0.001));
+        assert!(Ranking::Matches(1.4999).approx_eq(Ranking::Matches(1.5), 0.001));
+        assert!(!Ranking::Matches(1.5).approx_eq(Ranking::Matches(1.49), 0.001));
+        assert!(Ranking::Matches(1.5).approx_eq(Ranking::Matches(1.5), 0.0));
+        assert!(!Ranking::Matches(1.5).approx_eq(Ranking::Matches(1.5), f64::NAN));
+        assert!(!Ranking::Matches(f64::NAN).approx_eq(Ranking::Matches(1.5), 1.0));
+    }
+
+    #[test]
+    fn approx_eq_fixed_tiers_compare_exactly() {
+        assert!(Ranking::Contains.approx_eq(Ranking::Contains, 0.0));
+        assert!(!Ranking::Contains.approx_eq(Ranking::Acronym, 10.0));
+        assert!(!Ranking::Acronym.approx_eq(Ranking::Matches(2.0), 10.0));
+        assert!(!Ranking::NoMatch.approx_eq(Ranking::Matches(1.0), 10.0));
+    }
+
+    #[test]
+    fn eq_tier_ignores_sub_score() {
+        assert!(Ranking::Matches(1.0).eq_tier(Ranking::Matches(2.0)));
+        assert!(Ranking::Equal.eq_tier(Ranking::Equal));
+        assert!(!Ranking::Equal.eq_tier(Ranking::CaseSensitiveEqual));
+        assert!(!Ranking::Matches(2.0).eq_tier(Ranking::Acronym));
+        assert!(!Ranking::Matches(1.0).eq_tier(Ranking::NoMatch));
+    }
+
+    #[test]
+    fn tiers_are_distinct_and_descending() {
+        let tiers: Vec<Ranking> = Ranking::tiers().collect();
+        assert_eq!(tiers.len(), Ranking::TIER_COUNT);
+        assert_eq!(tiers, Ranking::fixed_tiers());
+        assert!(tiers.windows(2).all(|pair| pair[0] > pair[1]));
+        for (i, a) in tiers.iter().enumerate() {
+            assert!(tiers[i + 1..].iter().all(|b| !a.eq_tier(*b)));
+        }
+        assert!(
+            !Ranking::fixed_tiers()
+                .iter()
+                .any(|tier| matches!(tier, Ranking::Matches(_)))
+        );
+    }
+
+    #[test]
+    fn clamp_inverted_bounds_prefers_floor() {
+        assert_eq!(
+            Ranking::Equal.clamp(Ranking::StartsWith, Ranking::Acronym),
+            Ranking::StartsWith
+        );
+    }
+
+    #[test]
+    fn match_threshold_is_lowest_match() {
+        assert_eq!(Ranking::MATCH_THRESHOLD, Ranking::Matches(1.0));
+        assert!(Ranking::NoMatch < Ranking::MATCH_THRESHOLD);
+        assert!(Ranking::Matches(1.01) > Ranking::MATCH_THRESHOLD);
+    }
+
+    // --- quick_match tests ---
+
+    const QUICK_MATCH_CANDIDATES: [&str; 16] = [
+        "apple",
+        "Apple Pie",
+        "pineapple",
+        "banana split",
+        "North-West Airlines",
+        "San Francisco",
+        "caf\u{00e9} au lait",
+        "Cr\u{00e8}me Br\u{00fb}l\u{00e9}e",
+        "playground",
+        "grapefruit",
+        "mississippi",
+        "\u{6771}\u{4eac}\u{90fd}",
+        "snake_case_name",
+        "HTTPServerError",
+        "",
+        "a",
+    ];
+
+    const QUICK_MATCH_QUERIES: [&str; 16] = [
+        "", "a", "ap", "pie", "nwa", "sf", "cafe", "creme", "plgnd", "ssss", "sssss", "\u{4eac}",
+        "scn", "http", "xyz", "elppa",
+    ];
+
+    #[test]
+    fn quick_match_has_no_false_negatives() {
+        for keep_diacritics in [false, true] {
+            for query in QUICK_MATCH_QUERIES {
+                let pq = PreparedQuery::new(query, keep_diacritics);
+                for candidate in QUICK_MATCH_CANDIDATES {
+                    if get_match_ranking(candidate, query, keep_diacritics) != Ranking::NoMatch {
+                        assert!(pq.quick_match(candidate), "{candidate:?} / {query:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn quick_match_rejects_most_non_matches() {
+        let mut non_matches = 0;
+        let mut false_positives = 0;
+        for query in QUICK_MATCH_QUERIES {
+            let pq = PreparedQuery::new(query, false);
+            for candidate in QUICK_MATCH_CANDIDATES {
+                if get_match_ranking(candidate, query, false) == Ranking::NoMatch {
+                    non_matches += 1;
+                    if pq.quick_match(candidate) {
+                        false_positives += 1;
+                    }
+                }
+            }
+        }
+        assert!(
+            false_positives * 10 <= non_matches,
+            "{false_positives}/{non_matches}"
+        );
+    }
+
+    #[test]
+    fn quick_match_counts_repeats_and_handles_non_ascii() {
+        let pq = PreparedQuery::new("ssss", false);
+        assert!(pq.quick_match("Mississippi"));
+        assert!(!PreparedQuery::new("sssss", false).quick_match("Mississippi"));
+        // Necessary, not sufficient: characters present but out of order.
+        assert!(PreparedQuery::new("elppa", false).quick_match("apple"));
+        assert_eq!(get_match_ranking("apple", "elppa", false), Ranking::NoMatch);
+
+        assert!(PreparedQuery::new("\u{4eac}", false).quick_match("\u{6771}\u{4eac}"));
+        assert!(!PreparedQuery::new("\u{00e9}", true).quick_match("cafe"));
+        assert!(PreparedQuery::new("\u{00e9}", false).quick_match("cafe"));
+        assert!(PreparedQuery::new("", false).quick_match(""));
+    }
+
+    #[test]
+    fn apply_batch_matches_get_match_ranking_per_candidate() {
+        for keep_diacritics in [false, true] {
+            for query in QUICK_MATCH_QUERIES {
+                let pq = PreparedQuery::new(query, keep_diacritics);
+                let rankings = pq.apply_batch(&QUICK_MATCH_CANDIDATES);
+                assert_eq!(rankings.len(), QUICK_MATCH_CANDIDATES.len());
+                for (candidate, rank) in QUICK_MATCH_CANDIDATES.iter().zip(rankings) {
+                    assert_eq!(
+                        rank,
+                        get_match_ranking(candidate, query, keep_diacritics),
+                        "{candidate:?} / {query:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn apply_batch_into_replaces_output() {
+        let pq = PreparedQuery::new("ap", false);
+        let mut output = vec![Ranking::Equal; 5];
+        pq.apply_batch_into(&["apple", "grape"], &mut output);
+        assert_eq!(output, vec![Ranking::StartsWith, Ranking::Contains]);
+
+        pq.apply_batch_into(&[], &mut output);
+        assert!(output.is_empty());
+        assert!(pq.apply_batch(&[]).is_empty());
+    }
+
+    // --- as_pct / from_pct tests ---
+
+    const FIXED_TIERS: [Ranking; 7] = [
+        Ranking::NoMatch,
+        Ranking::Acronym,
+        Ranking::Contains,
+        Ranking::WordStartsWith,
+        Ranking::StartsWith,
+        Ranking::Equal,
+        Ranking::CaseSensitiveEqual,
+    ];
+
+    #[test]
+    fn as_pct_boundary_values() {
+        assert_eq!(Ranking::CaseSensitiveEqual.as_pct(), 100.0);
+        assert_eq!(Ranking::Equal.as_pct(), 85.0);
+        assert_eq!(Ranking::StartsWith.as_pct(), 71.0);
+        assert_eq!(Ranking::WordStartsWith.as_pct(), 57.0);
+        assert_eq!(Ranking::Contains.as_pct(), 43.0);
+        assert_eq!(Ranking::Acronym.as_pct(), 28.0);
+        assert_eq!(Ranking::Matches(2.0).as_pct(), 14.0);
+        assert_eq!(Ranking::Matches(1.0).as_pct(), 0.0);
+        assert_eq!(Ranking::NoMatch.as_pct(), 0.0);
+    }
+
+    #[test]
+    fn as_pct_clamps_out_of_range_sub_scores() {
+        assert_eq!(Ranking::Matches(3.0).as_pct(), 14.0);
+        assert_eq!(Ranking::Matches(0.5).as_pct(), 0.0);
+    }
+
+    #[test]
+    fn as_pct_is_monotonic() {
+        let mut rankings = vec![Ranking::NoMatch];
+        rankings.extend((1..=10).map(|i| Ranking::Matches(1.0 + f64::from(i) / 10.0)));
+        rankings.extend(FIXED_TIERS[1..].iter().copied());
+        for pair in rankings.windows(2) {
+            assert!(pair[0] < pair[1]);
+            assert!(pair[0].as_pct() < pair[1].as_pct(), "{pair:?}");
+        }
+    }
+
+    #[test]
+    fn to_f64_follows_js_scale_and_ordering() {
+        for (rank, value) in FIXED_TIERS.iter().zip([0, 2, 3, 4, 5, 6, 7]) {
+            assert_eq!(rank.to_f64(), f64::from(value), "{rank:?}");
+        }
+        assert_eq!(Ranking::Matches(1.25).to_f64(), 1.25);
+        assert_eq!(Ranking::Matches(3.0).to_f64(), 2.0);
+        let mut rankings = vec![Ranking::NoMatch];
+        rankings.extend((1..=10).map(|i| Ranking::Matches(1.0 + f64::from(i) / 10.0)));
+        rankings.extend(FIXED_TIERS[1..].iter().copied());
+        for pair in rankings.windows(2) {
+            assert!(pair[0].to_f64() <= pair[1].to_f64(), "{pair:?}");
+        }
+    }
+
+    #[test]
+    fn from_pct_round_trips_fixed_tiers() {
+        for rank in FIXED_TIERS {
+            assert_eq!(Ranking::from_pct(rank.as_pct()), rank);
+        }
+    }
+
+    #[test]
+    fn from_pct_round_trips_matches_approximately() {
+        for s in [1.1, 1.25, 1.5, 1.9, 2.0] {
+            let Ranking::Matches(back) = Ranking::from_pct(Ranking::Matches(s).as_pct()) else {
+                panic!("expected Matches for sub-score {s}");
+            };
+            assert!((back - s).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn from_pct_between_tiers_rounds_down() {
+        assert_eq!(Ranking::from_pct(99.9), Ranking::Equal);
+        assert_eq!(Ranking::from_pct(150.0), Ranking::CaseSensitiveEqual);
+        assert_eq!(Ranking::from_pct(20.0), Ranking::Matches(2.0));
+        assert_eq!(Ranking::from_pct(0.0), Ranking::NoMatch);
+        assert_eq!(Ranking::from_pct(-5.0), Ranking::NoMatch);
+        assert_eq!(Ranking::from_pct(f64::NAN), Ranking::NoMatch);
+    }
+
+    #[test]
+    fn from_pct_is_threshold_for_as_pct() {
+        for pct in [5.0, 28.0, 50.0, 85.0] {
+            let threshold = Ranking::from_pct(pct);
+            for rank in FIXED_TIERS {
+                if rank.as_pct() >= pct {
+                    assert!(rank >= threshold, "{rank:?} vs {threshold:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn custom_pct_scale() {
+        let scale = PctScale {
+            acronym: 40.0,
+            contains: 50.0,
+            matches_max: 20.0,
+            ..PctScale::default()
+        };
+        assert_eq!(Ranking::Acronym.as_pct_with(&scale), 40.0);
+        assert_eq!(Ranking::Matches(1.5).as_pct_with(&scale), 10.0);
+        assert_eq!(Ranking::from_pct_with(45.0, &scale), Ranking::Acronym);
+        assert_eq!(Ranking::from_pct_with(10.0, &scale), Ranking::Matches(1.5));
+    }
+
+    // --- tier conversion tests ---
+
+    #[test]
+    fn try_from_u8_covers_every_tier() {
+        for tier in 0..=7u8 {
+            let rank = Ranking::try_from(tier).unwrap();
+            assert_eq!(u8::from(rank), tier);
+        }
+        assert_eq!(Ranking::try_from(1), Ok(Ranking::Matches(2.0)));
+        assert_eq!(Ranking::try_from(7), Ok(Ranking::CaseSensitiveEqual));
+        assert_eq!(Ranking::try_from(0), Ok(Ranking::NoMatch));
+    }
+
+    #[test]
+    fn try_from_u8_rejects_tiers_above_seven() {
+        for tier in [8, 9, 100, u8::MAX] {
+            assert_eq!(Ranking::try_from(tier), Err(InvalidRankingTier(tier)));
+        }
+        assert_eq!(
+            InvalidRankingTier(8).to_string(),
+            "invalid ranking tier 8, expected 0-7"
+        );
+    }
+
+    #[test]
+    fn fixed_tiers_round_trip_through_u8() {
+        for rank in FIXED_TIERS {
+            assert_eq!(Ranking::try_from(u8::from(rank)), Ok(rank));
+        }
+        assert_eq!(u8::from(Ranking::Matches(1.3)), 1);
+    }
+
+    #[test]
+    fn tier_and_score_round_trip() {
+        for rank in FIXED_TIERS {
+            assert_eq!(
+                Ranking::try_from_tier_and_score(u8::from(rank), 0.0),
+                Ok(rank)
+            );
+        }
+        for score in [1.01, 1.5, 2.0] {
+            let rank = Ranking::Matches(score);
+            assert_eq!(
+                Ranking::try_from_tier_and_score(u8::from(rank), score),
+                Ok(rank)
+            );
+        }
+    }
+
+    #[test]
+    fn tier_and_score_rejects_invalid_input() {
+        assert_eq!(
+            Ranking::try_from_tier_and_score(8, 1.5),
+            Err(InvalidRanking::Tier(8))
+        );
+        for score in [1.0, 0.5, 2.5, f64::INFINITY] {
+            assert_eq!(
+                Ranking::try_from_tier_and_score(1, score),
+                Err(InvalidRanking::Score(score))
+            );
+        }
+        assert!(matches!(
+            Ranking::try_from_tier_and_score(1, f64::NAN),
+            Err(InvalidRanking::Score(s)) if s.is_nan()
+        ));
+    }
+
+    // --- inversion tests ---
+
+    #[test]
+    fn invert_follows_mapping_table() {
+        let table = [
+            (Ranking::CaseSensitiveEqual, Ranking::NoMatch),
+            (Ranking::Equal, Ranking::NoMatch),
+            (Ranking::StartsWith, Ranking::Acronym),
+            (Ranking::WordStartsWith, Ranking::Acronym),
+            (Ranking::Contains, Ranking::WordStartsWith),
+            (Ranking::Acronym, Ranking::Contains),
+            (Ranking::Matches(2.0), Ranking::Matches(1.0)),
+            (Ranking::Matches(1.5), Ranking::Matches(1.5)),
+            (Ranking::Matches(1.1), Ranking::Matches(1.9)),
+            (Ranking::NoMatch, Ranking::CaseSensitiveEqual),
+        ];
+        for (rank, inverted) in table {
+            assert!(rank.invert().approx_eq(inverted, 1e-12), "{rank:?}");
+        }
+        // Closer fuzzy matches invert to lower scores.
+        assert!(Ranking::Matches(1.9).invert() < Ranking::Matches(1.2).invert());
+    }
+
+    #[test]
+    fn double_invert_is_not_identity() {
+        assert_eq!(Ranking::StartsWith.invert().invert(), Ranking::Contains);
+        assert_eq!(Ranking::Contains.invert().invert(), Ranking::Acronym);
+        assert_eq!(
+            Ranking::Equal.invert().invert(),
+            Ranking::CaseSensitiveEqual
+        );
+        for rank in [Ranking::CaseSensitiveEqual, Ranking::NoMatch] {
+            assert_eq!(rank.invert().invert(), rank);
+        }
+        assert!(
+            Ranking::Matches(1.3)
+                .invert()
+                .invert()
+                .approx_eq(Ranking::Matches(1.3), 1e-12)
+        );
+    }
+
+    #[test]
+    fn invert_to_no_match_is_binary() {
+        for rank in Ranking::tiers().chain([Ranking::Matches(1.01), Ranking::NoMatch]) {
+            let inverted = rank.invert_to_no_match();
+            assert!(
+                inverted == Ranking::NoMatch || inverted == Ranking::CaseSensitiveEqual,
+                "{rank:?}"
+            );
+            assert_eq!(inverted == Ranking::NoMatch, rank != Ranking::NoMatch);
+        }
+    }
+
+    // --- between / at_least tests ---
+
+    #[test]
+    fn between_is_exclusive() {
+        assert!(Ranking::WordStartsWith.between(Ranking::Contains, Ranking::Equal));
+        assert!(Ranking::StartsWith.between(Ranking::Contains, Ranking::Equal));
+        // Contains ranks below WordStartsWith, so it is not between it and
+        // Equal.
+        assert!(!Ranking::Contains.between(Ranking::WordStartsWith, Ranking::Equal));
+        assert!(!Ranking::Contains.between(Ranking::Contains, Ranking::Equal));
+        assert!(!Ranking::Equal.between(Ranking::Contains, Ranking::Equal));
+        assert!(Ranking::Matches(1.5).between(Ranking::NoMatch, Ranking::Acronym));
+        assert!(Ranking::Matches(1.5).between(Ranking::Matches(1.2), Ranking::Matches(1.8)));
+        // An inverted range contains nothing.
+        assert!(!Ranking::StartsWith.between(Ranking::Equal, Ranking::Contains));
+    }
+
+    #[test]
+    fn at_or_between_is_inclusive() {
+        assert!(Ranking::Contains.at_or_between(Ranking::Contains, Ranking::Equal));
+        assert!(Ranking::Equal.at_or_between(Ranking::Contains, Ranking::Equal));
+        assert!(Ranking::StartsWith.at_or_between(Ranking::Contains, Ranking::Equal));
+        assert!(!Ranking::Acronym.at_or_between(Ranking::Contains, Ranking::Equal));
+        assert!(!Ranking::CaseSensitiveEqual.at_or_between(Ranking::Contains, Ranking::Equal));
+    }
+
+    #[test]
+    fn at_least_matches_ge() {
+        assert!(!Ranking::NoMatch.at_least(Ranking::Contains));
+        assert!(Ranking::Contains.at_least(Ranking::Contains));
+        assert!(Ranking::Equal.at_least(Ranking::Contains));
+        assert!(Ranking::Matches(1.8).at_least(Ranking::Matches(1.5)));
+        assert!(!Ranking::Matches(1.2).at_least(Ranking::Matches(1.5)));
+    }
+
+    // --- promote / demote tests ---
+
+    #[test]
+    fn promote_and_demote_saturate() {
+        assert_eq!(
+            Ranking::CaseSensitiveEqual.promote(1),
+            Ranking::CaseSensitiveEqual
+        );
+        assert_eq!(
+            Ranking::Acronym.promote(u8::MAX),
+            Ranking::CaseSensitiveEqual
+        );
+        assert_eq!(Ranking::NoMatch.demote(1), Ranking::NoMatch);
+        assert_eq!(Ranking::Equal.demote(u8::MAX), Ranking::NoMatch);
+    }
+
+    #[test]
+    fn promote_and_demote_through_matches_tier() {
+        assert_eq!(Ranking::Matches(1.1).promote(1), Ranking::Acronym);
+        assert_eq!(Ranking::Matches(1.1).promote(2), Ranking::Contains);
+        assert_eq!(Ranking::Matches(1.1).demote(1), Ranking::NoMatch);
+        assert_eq!(Ranking::Matches(1.1).promote(0), Ranking::Matches(1.1));
+        assert_eq!(Ranking::NoMatch.promote(1), Ranking::Matches(2.0));
+        assert_eq!(Ranking::Acronym.demote(1), Ranking::Matches(2.0));
+        assert_eq!(Ranking::Contains.demote(2), Ranking::Matches(2.0));
+    }
+
+    #[test]
+    fn promote_then_demote_round_trips_fixed_tiers() {
+        for &rank in Ranking::fixed_tiers() {
+            if rank != Ranking::CaseSensitiveEqual {
+                assert_eq!(rank.promote(1).demote(1), rank, "{rank:?}");
+            }
+            if rank != Ranking::NoMatch {
+                assert_eq!(rank.demote(1).promote(1), rank, "{rank:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn tier_distance_is_positive_when_self_is_better() {
+        assert_eq!(
+            Ranking::CaseSensitiveEqual.tier_distance(Ranking::NoMatch),
+            7
+        );
+        assert_eq!(
+            Ranking::NoMatch.tier_distance(Ranking::CaseSensitiveEqual),
+            -7
+        );
+        assert_eq!(Ranking::Acronym.tier_distance(Ranking::Matches(2.0)), 1);
+        assert_eq!(Ranking::Matches(1.01).tier_distance(Ranking::Acronym), -1);
+        assert_eq!(Ranking::Equal.tier_distance(Ranking::Equal), 0);
+        for &rank in Ranking::fixed_tiers() {
+            for steps in 0..=7_u8 {
+                let promoted = rank.promote(steps);
+                assert_eq!(
+                    promoted.tier_distance(rank),
+                    i32::from(steps).min(7 - i32::from(u8::from(rank)))
+                );
+                assert_eq!(rank.tier_distance(promoted), -promoted.tier_distance(rank));
+            }
+        }
+    }
+
+    #[test]
+    fn tier_gap_counts_tiers() {
+        assert_eq!(Ranking::CaseSensitiveEqual.tier_gap(Ranking::NoMatch), 7);
+        assert_eq!(Ranking::Contains.tier_gap(Ranking::Equal), -3);
+        assert_eq!(Ranking::Matches(1.5).tier_gap(Ranking::Acronym), -1);
+        assert_eq!(Ranking::Matches(1.5).tier_gap(Ranking::NoMatch), 1);
+        let ranks = Ranking::fixed_tiers()
+            .iter()
+            .copied()
+            .chain([Ranking::Matches(1.5)]);
+        for a in ranks.clone() {
+            for b in ranks.clone() {
+                assert_eq!(a.tier_gap(b), -b.tier_gap(a), "{a:?} {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn is_closer_than_compares_gaps_to_threshold() {
+        let threshold = Ranking::Acronym;
+        assert!(Ranking::Equal.is_closer_than(Ranking::Contains, threshold));
+        assert!(!Ranking::Contains.is_closer_than(Ranking::Equal, threshold));
+        assert!(!Ranking::Contains.is_closer_than(Ranking::Contains, threshold));
+        assert!(Ranking::Acronym.is_closer_than(Ranking::NoMatch, threshold));
+        assert!(!Ranking::Matches(1.9).is_closer_than(Ranking::Matches(1.1), threshold));
+    }
+
+    // --- string parsing tests ---
+
+    #[test]
+    fn canonical_names_parse() {
+        let names = [
+            ("CaseSensitiveEqual", Ranking::CaseSensitiveEqual),
+            ("Equal", Ranking::Equal),
+            ("StartsWith", Ranking::StartsWith),
+            ("WordStartsWith", Ranking::WordStartsWith),
+            ("Contains", Ranking::Contains),
+            ("Acronym", Ranking::Acronym),
+            ("Matches(1.5)", Ranking::Matches(1.5)),
+            ("NoMatch", Ranking::NoMatch),
+        ];
+        for (name, rank) in names {
+            assert_eq!(name.parse(), Ok(rank));
+            assert_eq!(Ranking::try_from(name), Ok(rank));
+            assert_eq!(rank.to_string(), name);
+        }
+        for rank in Ranking::tiers().chain([Ranking::MATCH_THRESHOLD, Ranking::Matches(1.375)]) {
+            assert_eq!(rank.to_string().parse(), Ok(rank));
+        }
+    }
+
+    #[test]
+    fn bare_matches_parses_as_best_sub_score() {
+        assert_eq!("Matches".parse(), Ok(Ranking::Matches(2.0)));
+        assert_eq!(Ranking::Matches(2.0).to_string(), "Matches(2.0)");
+    }
+
+    #[test]
+    fn parsing_tolerates_surrounding_whitespace() {
+        assert_eq!("  Contains\n".parse(), Ok(Ranking::Contains));
+        assert_eq!("\tMatches ( 1.25 ) ".parse(), Ok(Ranking::Matches(1.25)));
+    }
+
+    #[test]
+    fn unknown_strings_are_rejected() {
+        for name in [
+            "",
+            "contains",
+            "Starts With",
+            "Matches(",
+            "Matches)1.5(",
+            "Exact",
+        ] {
+            assert_eq!(
+                name.parse::<Ranking>(),
+                Err(ParseRankingError::UnknownName(name.trim().to_owned()))
+            );
+        }
+        let err = "startsWith".parse::<Ranking>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown ranking \"startsWith\", expected one of CaseSensitiveEqual, Equal, \
+             StartsWith, WordStartsWith, Contains, Acronym, Matches, Matches(<score>) or NoMatch"
+        );
+        for score in ["", "abc", "NaN", "inf"] {
+            assert_eq!(
+                format!("Matches({score})").parse::<Ranking>(),
+                Err(ParseRankingError::InvalidScore(score.to_owned()))
+            );
+        }
+        assert_eq!(
+            ParseRankingError::InvalidScore("abc".to_owned()).to_string(),
+            "invalid Matches sub-score \"abc\", expected a finite number"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_uses_display_strings() {
+        let json = serde_json::to_string(&[Ranking::StartsWith, Ranking::Matches(1.5)]).unwrap();
+        assert_eq!(json, r#"["StartsWith","Matches(1.5)"]"#);
+        let back: Vec<Ranking> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, vec![Ranking::StartsWith, Ranking::Matches(1.5)]);
+        assert_eq!(
+            serde_json::from_str::<Ranking>(r#"" Matches ""#).unwrap(),
+            Ranking::Matches(2.0)
+        );
+        let err = serde_json::from_str::<Ranking>(r#""Fuzzy""#).unwrap_err();
+        assert!(err.to_string().starts_with("unknown ranking \"Fuzzy\""));
+        assert!(serde_json::from_str::<Ranking>("3").is_err());
+    }
+
+    // --- Hash tests ---
+
+    fn hash_of(r: &Ranking) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        let mut hasher = DefaultHasher::new();
+        r.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn hash_consistent_with_eq() {
+        let rankings = [
+            Ranking::CaseSensitiveEqual,
+            Ranking::Equal,
+            Ranking::StartsWith,
+            Ranking::WordStartsWith,
+            Ranking::Contains,
+            Ranking::Acronym,
+            Ranking::Matches(1.5),
+            Ranking::Matches(1.25),
+            Ranking::Matches(0.0),
+            Ranking::Matches(-0.0),
+            Ranking::NoMatch,
+        ];
+        for a in &rankings {
+            for b in &rankings {
+                if a == b {
+                    assert_eq!(hash_of(a), hash_of(b), "{a:?} == {b:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hash_distinguishes_tiers_and_sub_scores() {
+        assert_ne!(hash_of(&Ranking::Contains), hash_of(&Ranking::Acronym));
+        assert_ne!(
+            hash_of(&Ranking::Matches(1.5)),
+            hash_of(&Ranking::Matches(1.25))
+        );
+    }
+
+    #[test]
+    fn hash_nan_sign_differs() {
+        // Documented caveat: NaN sub-scores hash by bit pattern.
+        assert_ne!(
+            hash_of(&Ranking::Matches(f64::NAN)),
+            hash_of(&Ranking::Matches(-f64::NAN))
+        );
+    }
+
+    #[test]
+    fn ranking_groups_in_hash_map() {
+        use std::collections::HashMap;
+
+        let candidates = ["apple", "Apple", "grape", "apricot", "pineapple"];
+        let mut groups: HashMap<Ranking, Vec<String>> = HashMap::new();
+        for c in candidates {
+            groups
+                .entry(get_match_ranking(c, "apple", false))
+                .or_default()
+                .push(c.to_owned());
+        }
+        assert_eq!(groups[&Ranking::CaseSensitiveEqual], vec!["apple"]);
+        assert_eq!(groups[&Ranking::Equal], vec!["Apple"]);
+        assert_eq!(groups[&Ranking::Contains], vec!["pineapple"]);
+        assert_eq!(groups[&Ranking::NoMatch], vec!["grape", "apricot"]);
+    }
+
+    // --- grapheme matching tests ---
+
+    #[test]
+    fn grapheme_spread_ignores_normalization_form() {
+        let nfc = "caf\u{e9}s";
+        let nfd = "cafe\u{301}s";
+        // c..s spans four graphemes in both forms.
+        let expected = Ranking::Matches(1.25);
+        assert_eq!(get_closeness_ranking_grapheme(nfc, "cs"), expected);
+        assert_eq!(get_closeness_ranking_grapheme(nfd, "cs"), expected);
+        // In chars the NFD combining mark adds one to the spread.
+        assert_eq!(get_closeness_ranking(nfc, "cs"), expected);
+        assert_eq!(get_closeness_ranking(nfd, "cs"), Ranking::Matches(1.2));
+    }
+
+    #[test]
+    fn grapheme_spread_counts_clusters_once() {
+        // A ZWJ family emoji is five chars but one grapheme, and "\r\n" is
+        // two chars but one grapheme.
+        let family = "a\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}b";
+        assert_eq!(
+            get_closeness_ranking(family, "ab"),
+            Ranking::Matches(1.0 + 1.0 / 6.0)
+        );
+        assert_eq!(
+            get_closeness_ranking_grapheme(family, "ab"),
+            Ranking::Matches(1.5)
+        );
+        assert_eq!(
+            get_closeness_ranking_grapheme("a\r\nb", "ab"),
+            Ranking::Matches(1.5)
+        );
+        assert_eq!(
+            get_closeness_ranking_grapheme("axb", "ab"),
+            Ranking::Matches(1.5)
+        );
+        assert_eq!(
+            get_closeness_ranking_grapheme("abc", "xyz"),
+            Ranking::NoMatch
+        );
+    }
+
+    #[test]
+    fn grapheme_matching_mode_in_full_ranking() {
+        let chars = RankingConfig {
+            diacritics: DiacriticsMode::Preserve,
+            ..RankingConfig::default()
+        };
+        let graphemes = RankingConfig {
+            grapheme_matching: true,
+            ..chars
+        };
+        let nfd = "cafe\u{301}s";
+        assert_eq!(rank_with(nfd, "cs", &chars), Ranking::Matches(1.2));
+        assert_eq!(rank_with(nfd, "cs", &graphemes), Ranking::Matches(1.25));
+        assert_eq!(
+            rank_with("caf\u{e9}s", "cs", &graphemes),
+            rank_with(nfd, "cs", &graphemes)
+        );
+    }
+
+    #[test]
+    fn grapheme_matching_scorer_receives_grapheme_units() {
+        let scorer = |first: usize, last: usize, cand: usize, query: usize| {
+            assert_eq!((first, last, cand, query), (0, 4, 5, 2));
+            1.5
+        };
+        assert_eq!(
+            get_closeness_ranking_with(
+                "cafe\u{301}s",
+                "cs",
+                FuzzyMode::Optimal,
+                true,
+                Some(&scorer)
+            ),
+            Ranking::Matches(1.5)
+        );
+    }
+
+    // --- custom closeness scorer tests ---
+
+    fn exponential(first: usize, last: usize, _: usize, _: usize) -> f64 {
+        1.0 + 2.0_f64.powi(-((last - first) as i32))
+    }
+
+    #[test]
+    fn closeness_scorer_receives_positions_and_lengths() {
+        let scorer = |first: usize, last: usize, cand: usize, query: usize| {
+            assert_eq!((first, last, cand, query), (1, 4, 6, 2));
+            1.5
+        };
+        assert_eq!(
+            get_closeness_ranking_with(
+                "\u{00e9}abcde",
+                "ad",
+                FuzzyMode::Greedy,
+                false,
+                Some(&scorer)
+            ),
+            Ranking::Matches(1.5)
+        );
+    }
+
+    #[test]
+    fn closeness_scorer_exponential_differs_from_default() {
+        // spread = 3
+        assert_eq!(
+            get_closeness_ranking("abcdef", "ad"),
+            Ranking::Matches(1.0 + 1.0 / 3.0)
+        );
+        assert_eq!(
+            get_closeness_ranking_with(
+                "abcdef",
+                "ad",
+                FuzzyMode::Greedy,
+                false,
+                Some(&exponential)
+            ),
+            Ranking::Matches(1.125)
+        );
+    }
+
+    #[test]
+    fn closeness_scorer_no_match_skips_scorer() {
+        let scorer = |_: usize, _: usize, _: usize, _: usize| -> f64 { panic!("called") };
+        assert_eq!(
+            get_closeness_ranking_with("abc", "xyz", FuzzyMode::Greedy, false, Some(&scorer)),
+            Ranking::NoMatch
+        );
+    }
+
+    #[test]
+    fn closeness_scorer_clamps_out_of_range() {
+        let high = |_: usize, _: usize, _: usize, _: usize| 5.0;
+        let low = |_: usize, _: usize, _: usize, _: usize| 0.25;
+        let nan = |_: usize, _: usize, _: usize, _: usize| f64::NAN;
+        assert_eq!(
+            get_closeness_ranking_with("abcdef", "ad", FuzzyMode::Greedy, false, Some(&high)),
+            Ranking::Matches(2.0)
+        );
+        for scorer in [&low as &ClosenessScorer, &nan] {
+            match get_closeness_ranking_with("abcdef", "ad", FuzzyMode::Greedy, false, Some(scorer))
+            {
+                Ranking::Matches(score) => assert!(score > 1.0 && score < 1.0 + 1e-9),
+                other => panic!("expected Matches, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn closeness_scorer_threaded_through_config() {
+        let config = RankingConfig {
+            closeness_scorer: Some(&exponential),
+            ..RankingConfig::default()
+        };
+        assert_eq!(
+            rank_with("playground", "plgnd", &config),
+            Ranking::Matches(1.0 + 2.0_f64.powi(-9))
+        );
+    }
+
+    // --- fuzzy mode tests ---
+
+    fn closeness(candidate: &str, query: &str, mode: FuzzyMode) -> Ranking {
+        get_closeness_ranking_with(candidate, query, mode, false, None)
+    }
+
+    #[test]
+    fn fuzzy_mode_default_is_greedy() {
+        assert_eq!(FuzzyMode::default(), FuzzyMode::Greedy);
+    }
+
+    #[test]
+    fn optimal_finds_tighter_spread() {
+        assert_eq!(
+            closeness("aab", "ab", FuzzyMode::Greedy),
+            Ranking::Matches(1.5)
+        );
+        assert_eq!(
+            closeness("aab", "ab", FuzzyMode::Optimal),
+            Ranking::Matches(2.0)
+        );
+        // Tighter window appears after the greedy one.
+        assert_eq!(
+            closeness("a-b--c---abc", "abc", FuzzyMode::Optimal),
+            Ranking::Matches(1.5)
+        );
+    }
+
+    #[test]
+    fn fuzzy_modes_agree_on_unambiguous_inputs() {
+        let cases = [
+            ("playground", "plgnd"),
+            ("abcdef", "ad"),
+            ("abcdef", "abc"),
+            ("ab", "a"),
+            ("abc", "xyz"),
+            ("abc", ""),
+            ("\u{00e9}abcde", "ad"),
+        ];
+        for (candidate, query) in cases {
+            assert_eq!(
+                closeness(candidate, query, FuzzyMode::Greedy),
+                closeness(candidate, query, FuzzyMode::Optimal),
+                "{candidate:?} / {query:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn optimal_never_widens_spread() {
+        let cases = [
+            ("aab", "ab"),
+            ("abab", "ab"),
+            ("mississippi", "sip"),
+            ("banana", "ana"),
+            ("aaaa", "aa"),
+        ];
+        for (candidate, query) in cases {
+            let greedy = closeness(candidate, query, FuzzyMode::Greedy);
+            let optimal = closeness(candidate, query, FuzzyMode::Optimal);
+            assert!(optimal >= greedy, "{candidate:?} / {query:?}");
+        }
+    }
+
+    #[test]
+    fn optimal_mode_keeps_tier_boundaries() {
+        let optimal = RankingConfig {
+            fuzzy_mode: FuzzyMode::Optimal,
+            ..RankingConfig::default()
+        };
+        let cases = [
+            ("aab", "ab"),
+            ("Green", "green"),
+            ("green", "green"),
+            ("greenhouse", "green"),
+            ("the green", "green"),
+            ("evergreen", "green"),
+            ("north-west", "nw"),
+            ("mississippi", "sip"),
+            ("abc", "xyz"),
+            ("a", "abc"),
+        ];
+        for (candidate, query) in cases {
+            let greedy = rank_with(candidate, query, &RankingConfig::default());
+            let opt = rank_with(candidate, query, &optimal);
+            assert_eq!(
+                std::mem::discriminant(&greedy),
+                std::mem::discriminant(&opt),
+                "{candidate:?} / {query:?}"
+            );
+            if !matches!(greedy, Ranking::Matches(_)) {
+                assert_eq!(greedy, opt);
+            }
+        }
+    }
+
+    // --- ensemble tests ---
+
+    #[test]
+    fn ensemble_single_window_matches_greedy() {
+        let cases = [
+            ("playground", "plgnd"),
+            ("a-------b-ab", "ab"),
+            ("mississippi", "sip"),
+            ("abcdef", "ad"),
+            ("ab", "a"),
+            ("abc", "xyz"),
+            ("abc", ""),
+            ("\u{00e9}a\u{00e9}b", "\u{00e9}b"),
+        ];
+        for (candidate, query) in cases {
+            for n_windows in [0, 1] {
+                assert_eq!(
+                    get_closeness_ranking_ensemble(candidate, query, n_windows),
+                    (get_closeness_ranking(candidate, query), 1.0),
+                    "{candidate:?} / {query:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ensemble_agrees_on_short_candidates() {
+        for (candidate, query) in [("abc", "ac"), ("axb", "ab"), ("ab", "b"), ("plgnd", "pd")] {
+            assert_eq!(
+                get_closeness_ranking_ensemble(candidate, query, 8),
+                (get_closeness_ranking(candidate, query), 1.0),
+                "{candidate:?} / {query:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn ensemble_confidence_is_one_for_unambiguous_alignments() {
+        // Every window finds the same spread.
+        assert_eq!(
+            get_closeness_ranking_ensemble("ab-ab-ab", "ab", 3),
+            (Ranking::Matches(2.0), 1.0)
+        );
+        // Later windows that cannot match the whole query do not count.
+        assert_eq!(
+            get_closeness_ranking_ensemble("axxb-a", "ab", 5),
+            (get_closeness_ranking("axxb-a", "ab"), 1.0)
+        );
+        assert_eq!(
+            get_closeness_ranking_ensemble("abc", "xyz", 4),
+            (Ranking::NoMatch, 1.0)
+        );
+    }
+
+    #[test]
+    fn ensemble_finds_better_later_window() {
+        let (rank, confidence) = get_closeness_ranking_ensemble("a-b--c---abc", "abc", 2);
+        assert_eq!(rank, Ranking::Matches(1.5));
+        assert!(rank > get_closeness_ranking("a-b--c---abc", "abc"));
+        assert!(confidence > 0.0 && confidence < 1.0, "{confidence}");
+
+        // Windows are limited to `n_windows` starts.
+        let candidate = "a----b a---b a-b ab";
+        let ranks: Vec<Ranking> = (1..=4)
+            .map(|n| get_closeness_ranking_ensemble(candidate, "ab", n).0)
+            .collect();
+        assert_eq!(
+            ranks,
+            [
+                Ranking::Matches(1.2),
+                Ranking::Matches(1.25),
+                Ranking::Matches(1.5),
+                Ranking::Matches(2.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn ensemble_windows_threaded_through_config() {
+        let ensemble = RankingConfig {
+            ensemble_windows: Some(4),
+            ..RankingConfig::default()
+        };
+        assert_eq!(
+            rank_with("axxxxxxxbxaxb", "ab", &RankingConfig::default()),
+            Ranking::Matches(1.125)
+        );
+        assert_eq!(
+            rank_with("axxxxxxxbxaxb", "ab", &ensemble),
+            Ranking::Matches(1.5)
+        );
+        assert_eq!(
+            portion_with("axxxxxxxbxaxb", "ab", &ensemble).as_deref(),
+            Some("axb")
+        );
+        // Tiers above `Matches` are unaffected.
+        assert_eq!(rank_with("ab-ab", "ab", &ensemble), Ranking::StartsWith);
+
+        // Optimal mode ignores the windows.
+        let optimal = RankingConfig {
+            fuzzy_mode: FuzzyMode::Optimal,
+            ensemble_windows: Some(1),
+            ..RankingConfig::default()
+        };
+        assert_eq!(
+            rank_with("axxxxxxxbxaxb", "ab", &optimal),
+            Ranking::Matches(1.5)
+        );
     }
 
+    // --- get_match_ranking tests ---
+
     #[test]
-    fn matches_sub_score_ordering() {
-        // Higher sub-score means a better (greater) ranking.
-        assert!(Ranking::Matches(1.9) > Ranking::Matches(1.1));
-        assert!(Ranking::Matches(2.0) > Ranking::Matches(1.5));
-        assert!(Ranking::Matches(1.5) > Ranking::Matches(1.01));
+    fn ranking_equal() {
+        // Case-insensitive full match: "Green" prepared -> "Green",
+        // lowercased -> "green" == "green".
+        assert_eq!(get_match_ranking("Green", "green", false), Ranking::Equal);
     }
 
     #[test]
-    fn matches_below_acronym_above_no_match() {
-        // Any valid Matches sub-score (1.0, 2.0] is below Acronym (2)
-        // and above NoMatch (0).
-        assert!(Ranking::Acronym > Ranking::Matches(2.0));
-        assert!(Ranking::Matches(1.01) > Ranking::NoMatch);
+    fn ranking_case_sensitive_equal() {
+        // Exact byte-for-byte match after diacritics preparation.
+        assert_eq!(
+            get_match_ranking("Green", "Green", false),
+            Ranking::CaseSensitiveEqual
+        );
     }
 
     #[test]
-    fn equality_same_fixed_tiers() {
-        assert_eq!(Ranking::CaseSensitiveEqual, Ranking::CaseSensitiveEqual);
-        assert_eq!(Ranking::Equal, Ranking::Equal);
-        assert_eq!(Ranking::NoMatch, Ranking::NoMatch);
+    fn ranking_starts_with() {
+        // "Greenland" lowercased starts with "green" but is longer.
+        assert_eq!(
+            get_match_ranking("Greenland", "green", false),
+            Ranking::StartsWith
+        );
     }
 
     #[test]
-    fn equality_same_matches_sub_score() {
-        assert_eq!(Ranking::Matches(1.5), Ranking::Matches(1.5));
+    fn ranking_word_starts_with() {
+        // "San Francisco" lowercased contains "fran" at position 4,
+        // preceded by a space at byte position 3.
+        assert_eq!(
+            get_match_ranking("San Francisco", "fran", false),
+            Ranking::WordStartsWith
+        );
     }
 
     #[test]
-    fn inequality_different_tiers() {
-        assert_ne!(Ranking::CaseSensitiveEqual, Ranking::Equal);
-        assert_ne!(Ranking::Matches(1.5), Ranking::NoMatch);
+    fn ranking_contains() {
+        // "abcdef" contains "cde" starting at position 2, not at a word boundary.
+        assert_eq!(get_match_ranking("abcdef", "cde", false), Ranking::Contains);
     }
 
     #[test]
-    fn inequality_different_sub_scores() {
-        assert_ne!(Ranking::Matches(1.2), Ranking::Matches(1.8));
+    fn ranking_acronym() {
+        // "North-West Airlines" -> acronym "nwa" (lowercased), which contains "nwa".
+        assert_eq!(
+            get_match_ranking("North-West Airlines", "nwa", false),
+            Ranking::Acronym
+        );
     }
 
     #[test]
-    fn debug_formatting() {
-        // Verify Debug is derived and produces reasonable output.
-        let debug_str = format!("{:?}", Ranking::Matches(1.5));
-        assert!(debug_str.contains("Matches"));
-        assert!(debug_str.contains("1.5"));
-
-        let debug_str = format!("{:?}", Ranking::CaseSensitiveEqual);
-        assert!(debug_str.contains("CaseSensitiveEqual"));
+    fn ranking_fuzzy_matches() {
+        // "playground" vs "plgnd": no substring match, no acronym match,
+        // but fuzzy closeness finds all chars in order.
+        let rank = get_match_ranking("playground", "plgnd", false);
+        match rank {
+            Ranking::Matches(s) => {
+                assert!(s > 1.0, "sub-score {s} should be > 1.0");
+                assert!(s < 2.0, "sub-score {s} should be < 2.0");
+            }
+            other => panic!("expected Matches, got {other:?}"),
+        }
     }
 
     #[test]
-    fn copy_produces_equal_value() {
-        let original = Ranking::Matches(1.75);
-        let copied = original;
-        assert_eq!(original, copied);
-
-        let original = Ranking::Contains;
-        let copied = original;
-        assert_eq!(original, copied);
+    fn ranking_no_match() {
+        // No characters in common.
+        assert_eq!(get_match_ranking("abc", "xyz", false), Ranking::NoMatch);
     }
 
     #[test]
-    fn matches_at_boundary_values() {
-        // Sub-score at the upper boundary (2.0) is still below Acronym.
-        assert!(Ranking::Acronym > Ranking::Matches(2.0));
-        // Sub-score just above the lower boundary is above NoMatch.
-        assert!(Ranking::Matches(1.001) > Ranking::NoMatch);
+    fn ranking_query_longer_than_candidate() {
+        // Step 1: query has more characters than candidate -> NoMatch.
+        assert_eq!(get_match_ranking("ab", "abcdef", false), Ranking::NoMatch);
     }
 
-    // --- get_acronym tests ---
-
     #[test]
-    fn acronym_hyphen_and_space() {
-        assert_eq!(get_acronym("north-west airlines"), "nwa");
+    fn ranking_single_char_not_substring() {
+        // Step 9: single character query not found as substring -> NoMatch.
+        // "z" is not in "abcdef".
+        assert_eq!(get_match_ranking("abcdef", "z", false), Ranking::NoMatch);
     }
 
     #[test]
-    fn acronym_space_only() {
-        assert_eq!(get_acronym("san francisco"), "sf");
+    fn ranking_single_char_substring_found() {
+        // Single character found as substring should still match (step 4-6).
+        // "a" is found at position 0 and candidate is length 6 -> StartsWith.
+        assert_eq!(get_match_ranking("abcdef", "a", false), Ranking::StartsWith);
     }
 
     #[test]
-    fn acronym_single_word() {
-        assert_eq!(get_acronym("single"), "s");
+    fn ranking_single_char_equal() {
+        // Single character that exactly matches the candidate.
+        assert_eq!(
+            get_match_ranking("a", "a", false),
+            Ranking::CaseSensitiveEqual
+        );
     }
 
     #[test]
-    fn acronym_empty_string() {
-        assert_eq!(get_acronym(""), "");
+    fn ranking_empty_query() {
+        // Empty query against any non-empty string: the empty string is a
+        // substring at position 0, and len("") != len(candidate) -> StartsWith.
+        assert_eq!(
+            get_match_ranking("anything", "", false),
+            Ranking::StartsWith
+        );
     }
 
     #[test]
-    fn acronym_underscores_not_delimiters() {
-        // Underscores do NOT act as word boundaries.
-        assert_eq!(get_acronym("snake_case_word"), "s");
+    fn ranking_both_empty() {
+        // Both empty: after preparation, candidate == query -> CaseSensitiveEqual.
+        assert_eq!(
+            get_match_ranking("", "", false),
+            Ranking::CaseSensitiveEqual
+        );
     }
 
     #[test]
-    fn acronym_consecutive_spaces() {
-        // Consecutive delimiters: only the non-delimiter char after the
-        // last delimiter in a run is included.
-        assert_eq!(get_acronym("hello  world"), "hw");
+    fn ranking_word_boundary_only_spaces() {
+        // Hyphens do NOT act as word boundaries for WordStartsWith (step 7).
+        // "North-West" lowercased contains "west" at position 6, preceded
+        // by '-' which is NOT a space. So it falls through to Contains.
+        assert_eq!(
+            get_match_ranking("North-West", "west", false),
+            Ranking::Contains
+        );
     }
 
     #[test]
-    fn acronym_consecutive_hyphens() {
-        assert_eq!(get_acronym("a--b"), "ab");
+    fn ranking_word_boundary_second_occurrence() {
+        // The first substring match is not at a word boundary, but a later
+        // one is. "foo foobar" contains "foo" at positions 0 and 4.
+        // Position 0 -> StartsWith (steps 5-6 apply before step 7).
+        // Use a different example where first match is not at pos 0:
+        // "xfoo bar foo" -> "foo" at positions 1, 8. Position 1 preceded by
+        // 'x' (not space). Position 8 preceded by ' ' -> WordStartsWith.
+        assert_eq!(
+            get_match_ranking("xfoo bar foo", "foo", false),
+            Ranking::WordStartsWith
+        );
     }
 
     #[test]
-    fn acronym_mixed_delimiters() {
-        assert_eq!(get_acronym("one two-three four"), "ottf");
+    fn ranking_diacritics_stripping() {
+        // "caf\u{00E9}" with keep_diacritics=false is prepared to "cafe".
+        // Query "cafe" is prepared to "cafe". Exact match -> CaseSensitiveEqual.
+        assert_eq!(
+            get_match_ranking("caf\u{00E9}", "cafe", false),
+            Ranking::CaseSensitiveEqual
+        );
     }
 
     #[test]
-    fn acronym_single_char() {
-        assert_eq!(get_acronym("x"), "x");
+    fn ranking_diacritics_kept() {
+        // With keep_diacritics=true, "caf\u{00E9}" != "cafe" (different bytes).
+        // Lowercased: "caf\u{00E9}" does not contain "cafe" as a substring.
+        // Fuzzy match: c(0), a(1), f(2) found, but 'e' in query won't match
+        // '\u{00E9}' in candidate -> depends on the actual chars.
+        // Actually the lowercased \u{00E9} is still \u{00E9}, and 'e' != '\u{00E9}'.
+        // So fuzzy fails too -> NoMatch.
+        assert_eq!(
+            get_match_ranking("caf\u{00E9}", "cafe", true),
+            Ranking::NoMatch
+        );
     }
 
     #[test]
-    fn acronym_trailing_delimiter() {
-        // Trailing delimiter produces no extra character.
-        assert_eq!(get_acronym("hello "), "h");
+    fn ranking_unicode_char_count_vs_byte_count() {
+        // Step 1 compares CHARACTER counts, not byte counts.
+        // "\u{00E9}" is 2 bytes but 1 character. So a candidate of 1 char
+        // with a query of 2 chars should be NoMatch by character count.
+        assert_eq!(get_match_ranking("\u{00E9}", "ab", true), Ranking::NoMatch);
     }
 
-    // --- prepare_value_for_comparison tests ---
-
     #[test]
-    fn strips_combining_acute_accent() {
-        // "cafe" followed by U+0301 COMBINING ACUTE ACCENT -> "cafe"
-        let result = prepare_value_for_comparison("cafe\u{0301}", false);
-        assert_eq!(result, "cafe");
-        assert!(matches!(result, Cow::Owned(_)));
+    fn ranking_acronym_not_reached_for_single_char() {
+        // Step 9 prevents single-char queries from reaching acronym check.
+        // "a b c" has acronym "abc", but query "x" (single char) not found
+        // as substring -> NoMatch at step 9, never reaches acronym.
+        assert_eq!(get_match_ranking("a b c", "x", false), Ranking::NoMatch);
     }
 
     #[test]
-    fn returns_borrowed_for_plain_ascii() {
-        // Pure ASCII with no diacritics should not allocate.
-        let result = prepare_value_for_comparison("cafe", false);
-        assert_eq!(result, "cafe");
-        assert!(matches!(result, Cow::Borrowed(_)));
+    fn ranking_acronym_multi_word() {
+        // "as soon as possible" -> acronym "asap", query "asap" matches.
+        assert_eq!(
+            get_match_ranking("as soon as possible", "asap", false),
+            Ranking::Acronym
+        );
     }
 
     #[test]
-    fn returns_borrowed_when_keep_diacritics_is_true() {
-        // When keep_diacritics is true, the input is returned as-is.
-        let input = "cafe\u{0301}";
-        let result = prepare_value_for_comparison(input, true);
-        assert_eq!(result, input);
-        assert!(matches!(result, Cow::Borrowed(_)));
+    fn ranking_contains_mid_string() {
+        // "hello world" contains "lo w" at position 3. Byte before pos 3
+        // is 'l' (not space) -> Contains.
+        assert_eq!(
+            get_match_ranking("hello world", "lo w", false),
+            Ranking::Contains
+        );
     }
 
     #[test]
-    fn strips_precomposed_accent() {
-        // U+00E9 (LATIN SMALL LETTER E WITH ACUTE) is a single precomposed
-        // codepoint that NFD decomposes into 'e' + U+0301.
-        let result = prepare_value_for_comparison("caf\u{00E9}", false);
-        assert_eq!(result, "cafe");
-        assert!(matches!(result, Cow::Owned(_)));
+    fn ranking_query_longer_than_candidate_unicode() {
+        // Unicode characters: candidate has 2 chars, query has 3 chars.
+        assert_eq!(
+            get_match_ranking("\u{4e16}\u{754c}", "abc", false),
+            Ranking::NoMatch
+        );
     }
 
-    #[test]
-    fn strips_multiple_diacritics() {
-        // U+00FC = u with diaeresis, U+00F1 = n with tilde
-        let result = prepare_value_for_comparison("\u{00FC}ber-ma\u{00F1}ana", false);
-        assert_eq!(result, "uber-manana");
-        assert!(matches!(result, Cow::Owned(_)));
+    // --- acronym match mode tests ---
+
+    /// Rank `candidate` against `query` with an explicit ranking config.
+    fn rank_with(candidate: &str, query: &str, config: &RankingConfig<'_>) -> Ranking {
+        let pq = PreparedQuery::with_diacritics_mode(query, config.diacritics);
+        let finder = memchr::memmem::Finder::new(pq.lower.as_bytes());
+        let mut buf = String::new();
+        get_match_ranking_prepared(candidate, &pq, config, &mut buf, Some(&finder))
     }
 
-    #[test]
-    fn returns_borrowed_for_empty_string() {
-        let result = prepare_value_for_comparison("", false);
-        assert_eq!(result, "");
-        assert!(matches!(result, Cow::Borrowed(_)));
+    fn prefix_config() -> RankingConfig<'static> {
+        RankingConfig {
+            acronym_prefix_match: true,
+            ..RankingConfig::default()
+        }
+    }
+
+    fn exact_config() -> RankingConfig<'static> {
+        RankingConfig {
+            acronym_exact_match: true,
+            ..RankingConfig::default()
+        }
     }
 
     #[test]
-    fn returns_borrowed_for_non_ascii_without_diacritics() {
-        // Early-exit path: CJK characters have no combining marks, so the
-        // pre-scan returns Cow::Borrowed immediately without NFD decomposition
-        // or heap allocation.
-        let result = prepare_value_for_comparison("\u{4e16}\u{754c}", false);
-        assert_eq!(result, "\u{4e16}\u{754c}");
-        assert!(matches!(result, Cow::Borrowed(_)));
+    fn acronym_contains_mode_matches_partial_acronym() {
+        // Default mode: "nw" and "wa" are both substrings of "nwa".
+        let config = RankingConfig::default();
+        assert_eq!(
+            rank_with("North-West Airlines", "nw", &config),
+            Ranking::Acronym
+        );
+        assert_eq!(
+            rank_with("North-West Airlines", "wa", &config),
+            Ranking::Acronym
+        );
+        assert_eq!(
+            rank_with("North-West Airlines", "nwa", &config),
+            Ranking::Acronym
+        );
     }
 
     #[test]
-    fn keep_diacritics_true_with_plain_ascii() {
-        let result = prepare_value_for_comparison("hello", true);
-        assert_eq!(result, "hello");
-        assert!(matches!(result, Cow::Borrowed(_)));
+    fn acronym_prefix_mode_requires_leading_alignment() {
+        // "nw" is a prefix of "nwa"; "wa" is not and falls through to fuzzy.
+        let config = prefix_config();
+        assert_eq!(
+            rank_with("North-West Airlines", "nw", &config),
+            Ranking::Acronym
+        );
+        assert_eq!(
+            rank_with("North-West Airlines", "nwa", &config),
+            Ranking::Acronym
+        );
+        assert!(matches!(
+            rank_with("North-West Airlines", "wa", &config),
+            Ranking::Matches(_)
+        ));
     }
 
     #[test]
-    fn strips_combining_tilde() {
-        // 'n' + U+0303 COMBINING TILDE -> "n"
-        let result = prepare_value_for_comparison("n\u{0303}", false);
-        assert_eq!(result, "n");
-        assert!(matches!(result, Cow::Owned(_)));
+    fn acronym_exact_mode_requires_full_acronym() {
+        let config = exact_config();
+        assert_eq!(
+            rank_with("North-West Airlines", "nwa", &config),
+            Ranking::Acronym
+        );
+        assert!(matches!(
+            rank_with("North-West Airlines", "nw", &config),
+            Ranking::Matches(_)
+        ));
     }
 
     #[test]
-    fn strips_multiple_combining_marks_on_single_base() {
-        // 'a' + U+0300 (grave) + U+0301 (acute) -> "a"
-        // Multiple stacked combining marks should all be removed.
-        let result = prepare_value_for_comparison("a\u{0300}\u{0301}", false);
-        assert_eq!(result, "a");
-        assert!(matches!(result, Cow::Owned(_)));
+    fn acronym_exact_mode_takes_precedence_over_prefix() {
+        let config = RankingConfig {
+            acronym_prefix_match: true,
+            acronym_exact_match: true,
+            ..RankingConfig::default()
+        };
+        assert!(matches!(
+            rank_with("North-West Airlines", "nw", &config),
+            Ranking::Matches(_)
+        ));
     }
 
     #[test]
-    fn preserves_o_stroke_unchanged() {
-        // U+00F8 (LATIN SMALL LETTER O WITH STROKE) does NOT decompose via
-        // NFD -- its NFD form is itself, with no combining marks. The
-        // LATIN1_STRIP table must map it to 0 (preserve) rather than b'o'.
-        let result = prepare_value_for_comparison("\u{00F8}slo", false);
-        assert_eq!(result, "\u{00F8}slo");
-        assert!(
-            matches!(result, Cow::Borrowed(_)),
-            "o-stroke string should be returned borrowed (no allocation)"
+    fn acronym_modes_do_not_affect_higher_tiers() {
+        // "north" is a prefix of the candidate itself, so acronym mode is
+        // never consulted.
+        assert_eq!(
+            rank_with("North-West Airlines", "north", &exact_config()),
+            Ranking::StartsWith
         );
     }
 
-    // --- get_closeness_ranking tests ---
-
-    #[test]
-    fn closeness_fuzzy_match_playground() {
-        // "plgnd" chars found at positions 0, 1, 4, 8, 9 in "playground".
-        // spread = 9 - 0 = 9, score = 1.0 + 1.0/9 ~= 1.111
-        let rank = get_closeness_ranking("playground", "plgnd");
-        match rank {
-            Ranking::Matches(s) => {
-                assert!(s > 1.0, "sub-score {s} should be > 1.0");
-                assert!(s < 2.0, "sub-score {s} should be < 2.0");
-                // Verify the exact expected value.
-                let expected = 1.0 + 1.0 / 9.0;
-                assert!(
-                    (s - expected).abs() < f64::EPSILON,
-                    "expected {expected}, got {s}"
-                );
-            }
-            other => panic!("expected Matches, got {other:?}"),
+    fn bounded_config(max_len: usize) -> RankingConfig<'static> {
+        RankingConfig {
+            acronym_max_length: Some(max_len),
+            ..RankingConfig::default()
         }
     }
 
     #[test]
-    fn closeness_no_match() {
-        assert_eq!(get_closeness_ranking("abc", "xyz"), Ranking::NoMatch);
+    fn acronym_max_length_limits_acronym_tier() {
+        let candidate = "one two three four five";
+        assert_eq!(
+            rank_with(candidate, "ottff", &RankingConfig::default()),
+            Ranking::Acronym
+        );
+        assert!(matches!(
+            rank_with(candidate, "ottff", &bounded_config(3)),
+            Ranking::Matches(_)
+        ));
+        assert!(matches!(
+            rank_with(candidate, "tff", &bounded_config(3)),
+            Ranking::Matches(_)
+        ));
     }
 
     #[test]
-    fn closeness_single_char_match() {
-        // Single char "a" found at position 0 in "ab". spread = 0.
-        assert_eq!(get_closeness_ranking("ab", "a"), Ranking::Matches(2.0));
+    fn acronym_max_length_still_matches_shorter_queries() {
+        let candidate = "one two three four five";
+        assert_eq!(
+            rank_with(candidate, "ott", &bounded_config(3)),
+            Ranking::Acronym
+        );
+        assert_eq!(
+            rank_with(candidate, "tt", &bounded_config(3)),
+            Ranking::Acronym
+        );
+        assert_eq!(
+            rank_with("North-West Airlines", "nw", &bounded_config(2)),
+            Ranking::Acronym
+        );
     }
 
-    #[test]
-    fn closeness_single_char_not_found() {
-        assert_eq!(get_closeness_ranking("ab", "z"), Ranking::NoMatch);
+    // --- hyphen word boundary tests ---
+
+    fn hyphen_config() -> RankingConfig<'static> {
+        RankingConfig {
+            hyphen_is_word_boundary: true,
+            ..RankingConfig::default()
+        }
     }
 
     #[test]
-    fn closeness_adjacent_chars() {
-        // "abc" in "abcdef": positions 0, 1, 2. spread = 2.
-        // score = 1.0 + 1.0/2 = 1.5
-        let rank = get_closeness_ranking("abcdef", "abc");
-        assert_eq!(rank, Ranking::Matches(1.5));
+    fn hyphen_boundary_disabled_by_default() {
+        assert_eq!(
+            rank_with("North-West", "west", &RankingConfig::default()),
+            Ranking::Contains
+        );
     }
 
     #[test]
-    fn closeness_two_char_query() {
-        // "ad" in "abcdef": positions 0, 3. spread = 3.
-        // score = 1.0 + 1.0/3
-        let rank = get_closeness_ranking("abcdef", "ad");
-        match rank {
-            Ranking::Matches(s) => {
-                let expected = 1.0 + 1.0 / 3.0;
-                assert!(
-                    (s - expected).abs() < f64::EPSILON,
-                    "expected {expected}, got {s}"
-                );
-            }
-            other => panic!("expected Matches, got {other:?}"),
-        }
+    fn hyphen_boundary_enabled_produces_word_starts_with() {
+        assert_eq!(
+            rank_with("North-West", "west", &hyphen_config()),
+            Ranking::WordStartsWith
+        );
     }
 
     #[test]
-    fn closeness_partial_mismatch() {
-        // "a" is found but "z" is not.
-        assert_eq!(get_closeness_ranking("abcdef", "az"), Ranking::NoMatch);
+    fn hyphen_boundary_still_accepts_spaces() {
+        assert_eq!(
+            rank_with("San Francisco", "fran", &hyphen_config()),
+            Ranking::WordStartsWith
+        );
     }
 
     #[test]
-    fn closeness_query_longer_than_candidate() {
-        // More query chars than candidate chars: guaranteed NoMatch.
-        assert_eq!(get_closeness_ranking("ab", "abcdef"), Ranking::NoMatch);
+    fn hyphen_boundary_later_occurrence() {
+        // First "west" is mid-word; the second follows a hyphen.
+        assert_eq!(
+            rank_with("midwest north-west", "west", &hyphen_config()),
+            Ranking::WordStartsWith
+        );
     }
 
     #[test]
-    fn closeness_result_always_in_range() {
-        // Test several cases to verify the invariant (1.0, 2.0].
-        let cases = [
-            ("abcdefghijklmnop", "ap"),   // spread = 15
-            ("abcdefghijklmnop", "abop"), // spread = 15
-            ("abcdef", "af"),             // spread = 5
-            ("ab", "ab"),                 // spread = 1
-        ];
-        for (candidate, query) in cases {
-            let rank = get_closeness_ranking(candidate, query);
-            match rank {
-                Ranking::Matches(s) => {
-                    assert!(
-                        s > 1.0 && s <= 2.0,
-                        "score {s} out of range for ({candidate}, {query})"
-                    );
-                }
-                other => panic!("expected Matches for ({candidate}, {query}), got {other:?}"),
-            }
+    fn hyphen_boundary_multi_byte_predecessor() {
+        // The byte before "west" is the trailing continuation byte of a
+        // two-byte character; it must not be mistaken for a boundary.
+        assert_eq!(
+            rank_with("\u{00f8}west", "west", &hyphen_config()),
+            Ranking::Contains
+        );
+        assert_eq!(
+            rank_with("\u{00f8}-west", "west", &hyphen_config()),
+            Ranking::WordStartsWith
+        );
+        assert_eq!(
+            rank_with("\u{4e16}west", "west", &hyphen_config()),
+            Ranking::Contains
+        );
+    }
+
+    // --- unicode word boundary tests ---
+
+    fn unicode_config() -> RankingConfig<'static> {
+        RankingConfig {
+            unicode_word_boundaries: true,
+            ..RankingConfig::default()
         }
     }
 
     #[test]
-    fn closeness_case_sensitive() {
-        // The function does case-sensitive matching; caller is responsible
-        // for lowercasing. 'A' != 'a'.
-        assert_eq!(get_closeness_ranking("abc", "A"), Ranking::NoMatch);
+    fn unicode_boundary_disabled_by_default() {
+        assert_eq!(
+            rank_with("San\u{00a0}Francisco", "fran", &RankingConfig::default()),
+            Ranking::Contains
+        );
     }
 
     #[test]
-    fn closeness_empty_query() {
-        // Empty query: no characters to find, spread = 0.
-        assert_eq!(get_closeness_ranking("anything", ""), Ranking::Matches(2.0));
+    fn unicode_boundary_no_break_space() {
+        assert_eq!(
+            rank_with("San\u{00a0}Francisco", "fran", &unicode_config()),
+            Ranking::WordStartsWith
+        );
     }
 
     #[test]
-    fn closeness_unicode_chars() {
-        // Unicode characters are matched correctly via .chars().
-        // candidate: "a b c" (3 chars), query: "ac" -> positions 0, 2, spread = 2
-        let rank = get_closeness_ranking("a\u{00E9}c", "ac");
-        assert_eq!(rank, Ranking::Matches(1.5));
+    fn unicode_boundary_thin_and_ideographic_space() {
+        assert_eq!(
+            rank_with("San\u{2009}Francisco", "fran", &unicode_config()),
+            Ranking::WordStartsWith
+        );
+        assert_eq!(
+            rank_with("\u{4e16}\u{3000}west", "west", &unicode_config()),
+            Ranking::WordStartsWith
+        );
     }
 
-    // --- get_match_ranking tests ---
-
     #[test]
-    fn ranking_equal() {
-        // Case-insensitive full match: "Green" prepared -> "Green",
-        // lowercased -> "green" == "green".
-        assert_eq!(get_match_ranking("Green", "green", false), Ranking::Equal);
+    fn unicode_boundary_ascii_whitespace() {
+        assert_eq!(
+            rank_with("San\tFrancisco", "fran", &unicode_config()),
+            Ranking::WordStartsWith
+        );
+        // Without the option a tab is not a boundary.
+        assert_eq!(
+            rank_with("San\tFrancisco", "fran", &RankingConfig::default()),
+            Ranking::Contains
+        );
     }
 
     #[test]
-    fn ranking_case_sensitive_equal() {
-        // Exact byte-for-byte match after diacritics preparation.
+    fn unicode_boundary_ascii_fast_path_unchanged() {
+        let config = unicode_config();
         assert_eq!(
-            get_match_ranking("Green", "Green", false),
-            Ranking::CaseSensitiveEqual
+            rank_with("San Francisco", "fran", &config),
+            Ranking::WordStartsWith
         );
+        assert_eq!(rank_with("Midwest", "west", &config), Ranking::Contains);
+        // Hyphens stay governed by `hyphen_is_word_boundary`.
+        assert_eq!(rank_with("North-West", "west", &config), Ranking::Contains);
     }
 
     #[test]
-    fn ranking_starts_with() {
-        // "Greenland" lowercased starts with "green" but is longer.
+    fn unicode_boundary_non_space_multi_byte_predecessor() {
         assert_eq!(
-            get_match_ranking("Greenland", "green", false),
-            Ranking::StartsWith
+            rank_with("\u{00f8}west", "west", &unicode_config()),
+            Ranking::Contains
+        );
+        assert_eq!(
+            rank_with("\u{4e16}west", "west", &unicode_config()),
+            Ranking::Contains
         );
     }
 
     #[test]
-    fn ranking_word_starts_with() {
-        // "San Francisco" lowercased contains "fran" at position 4,
-        // preceded by a space at byte position 3.
+    fn unicode_boundary_later_occurrence() {
+        // First "west" is mid-word; the second follows a no-break space.
         assert_eq!(
-            get_match_ranking("San Francisco", "fran", false),
+            rank_with("midwest\u{00a0}west", "west", &unicode_config()),
             Ranking::WordStartsWith
         );
     }
 
+    // --- word splitter tests ---
+
+    fn words(s: &str) -> Vec<&str> {
+        camel_case_splitter(s).into_iter().map(|r| &s[r]).collect()
+    }
+
+    fn splitter_config(splitter: &WordSplitter) -> RankingConfig<'_> {
+        RankingConfig {
+            word_splitter: Some(splitter),
+            ..RankingConfig::default()
+        }
+    }
+
     #[test]
-    fn ranking_contains() {
-        // "abcdef" contains "cde" starting at position 2, not at a word boundary.
-        assert_eq!(get_match_ranking("abcdef", "cde", false), Ranking::Contains);
+    fn camel_case_splitter_words() {
+        assert_eq!(words("someWordHere"), ["some", "Word", "Here"]);
+        assert_eq!(words("HTMLParser"), ["HTML", "Parser"]);
+        assert_eq!(
+            words("snake_case-and spaces"),
+            ["snake", "case", "and", "spaces"]
+        );
+        assert_eq!(words("v2Api"), ["v2", "Api"]);
+        assert_eq!(words("ÉcoleNormale"), ["École", "Normale"]);
+        assert_eq!(camel_case_splitter("someWordHere")[1], 4..8);
+        assert!(camel_case_splitter("").is_empty());
+        assert!(camel_case_splitter("--").is_empty());
     }
 
     #[test]
-    fn ranking_acronym() {
-        // "North-West Airlines" -> acronym "nwa" (lowercased), which contains "nwa".
+    fn word_splitter_finds_camel_case_word_starts() {
+        let config = splitter_config(&camel_case_splitter);
         assert_eq!(
-            get_match_ranking("North-West Airlines", "nwa", false),
-            Ranking::Acronym
+            rank_with("someWordHere", "word", &config),
+            Ranking::WordStartsWith
+        );
+        assert_eq!(
+            rank_with("someWordHere", "here", &config),
+            Ranking::WordStartsWith
+        );
+        assert_eq!(rank_with("someWordHere", "ord", &config), Ranking::Contains);
+        // Separators still split words.
+        assert_eq!(
+            rank_with("San Francisco", "fran", &config),
+            Ranking::WordStartsWith
+        );
+        assert_eq!(
+            rank_with("someWordHere", "word", &RankingConfig::default()),
+            Ranking::Contains
         );
     }
 
     #[test]
-    fn ranking_fuzzy_matches() {
-        // "playground" vs "plgnd": no substring match, no acronym match,
-        // but fuzzy closeness finds all chars in order.
-        let rank = get_match_ranking("playground", "plgnd", false);
-        match rank {
-            Ranking::Matches(s) => {
-                assert!(s > 1.0, "sub-score {s} should be > 1.0");
-                assert!(s < 2.0, "sub-score {s} should be < 2.0");
-            }
-            other => panic!("expected Matches, got {other:?}"),
-        }
+    fn word_splitter_returning_nothing_disables_word_starts_with() {
+        let none = |_: &str| Vec::new();
+        let config = splitter_config(&none);
+        assert_eq!(
+            rank_with("hello world", "world", &config),
+            Ranking::Contains
+        );
+        assert_eq!(
+            rank_with("hello world", "hello", &config),
+            Ranking::StartsWith
+        );
     }
 
     #[test]
-    fn ranking_no_match() {
-        // No characters in common.
-        assert_eq!(get_match_ranking("abc", "xyz", false), Ranking::NoMatch);
+    fn word_splitter_default_is_space_only() {
+        let config = RankingConfig::default();
+        assert!(config.word_splitter.is_none());
+        assert_eq!(
+            rank_with("hello world", "world", &config),
+            Ranking::WordStartsWith
+        );
+        assert_eq!(
+            rank_with("hello-world", "world", &config),
+            Ranking::Contains
+        );
     }
 
     #[test]
-    fn ranking_query_longer_than_candidate() {
-        // Step 1: query has more characters than candidate -> NoMatch.
-        assert_eq!(get_match_ranking("ab", "abcdef", false), Ranking::NoMatch);
+    fn word_splitter_offsets_follow_lowercasing() {
+        // "\u{130}" lowercases to three bytes from two, shifting every later
+        // word start in the lowercased candidate by one byte.
+        let config = RankingConfig {
+            diacritics: DiacriticsMode::Preserve,
+            ..splitter_config(&camel_case_splitter)
+        };
+        assert_eq!(
+            rank_with("\u{130}stanbulCity", "city", &config),
+            Ranking::WordStartsWith
+        );
+        assert_eq!(
+            portion_with("someWordHere", "word", &config).as_deref(),
+            Some("Word")
+        );
     }
 
-    #[test]
-    fn ranking_single_char_not_substring() {
-        // Step 9: single character query not found as substring -> NoMatch.
-        // "z" is not in "abcdef".
-        assert_eq!(get_match_ranking("abcdef", "z", false), Ranking::NoMatch);
+    // --- matched portion tests ---
+
+    fn portion_with(candidate: &str, query: &str, config: &RankingConfig<'_>) -> Option<String> {
+        let pq = PreparedQuery::with_normalization(query, config.diacritics, config.normalization);
+        let finder =
+            (!pq.lower.is_empty()).then(|| memchr::memmem::Finder::new(pq.lower.as_bytes()));
+        let mut buf = String::new();
+        let rank = get_match_ranking_prepared(candidate, &pq, config, &mut buf, finder.as_ref());
+        get_matched_portion(candidate, rank, &pq, config, &mut buf, finder.as_ref())
+            .map(Cow::into_owned)
     }
 
-    #[test]
-    fn ranking_single_char_substring_found() {
-        // Single character found as substring should still match (step 4-6).
-        // "a" is found at position 0 and candidate is length 6 -> StartsWith.
-        assert_eq!(get_match_ranking("abcdef", "a", false), Ranking::StartsWith);
+    fn portion(candidate: &str, query: &str) -> Option<String> {
+        portion_with(candidate, query, &RankingConfig::default())
     }
 
     #[test]
-    fn ranking_single_char_equal() {
-        // Single character that exactly matches the candidate.
-        assert_eq!(
-            get_match_ranking("a", "a", false),
-            Ranking::CaseSensitiveEqual
-        );
+    fn matched_portion_equal_tiers_are_whole_value() {
+        assert_eq!(portion("Green", "Green").as_deref(), Some("Green"));
+        assert_eq!(portion("Green", "green").as_deref(), Some("Green"));
     }
 
     #[test]
-    fn ranking_empty_query() {
-        // Empty query against any non-empty string: the empty string is a
-        // substring at position 0, and len("") != len(candidate) -> StartsWith.
+    fn matched_portion_substring_tiers() {
+        assert_eq!(portion("Greenland", "green").as_deref(), Some("Green"));
+        // WordStartsWith picks the occurrence at the word boundary.
         assert_eq!(
-            get_match_ranking("anything", "", false),
-            Ranking::StartsWith
+            portion("evergreen Green", "green").as_deref(),
+            Some("Green")
         );
+        assert_eq!(portion("EverGreen", "green").as_deref(), Some("Green"));
+        assert_eq!(portion("Greenland", "").as_deref(), Some(""));
     }
 
     #[test]
-    fn ranking_both_empty() {
-        // Both empty: after preparation, candidate == query -> CaseSensitiveEqual.
+    fn matched_portion_acronym_keeps_case() {
         assert_eq!(
-            get_match_ranking("", "", false),
-            Ranking::CaseSensitiveEqual
+            portion("North-West Airlines", "nwa").as_deref(),
+            Some("NWA")
         );
-    }
-
-    #[test]
-    fn ranking_word_boundary_only_spaces() {
-        // Hyphens do NOT act as word boundaries for WordStartsWith (step 7).
-        // "North-West" lowercased contains "west" at position 6, preceded
-        // by '-' which is NOT a space. So it falls through to Contains.
+        let bounded = RankingConfig {
+            acronym_max_length: Some(2),
+            ..RankingConfig::default()
+        };
         assert_eq!(
-            get_match_ranking("North-West", "west", false),
-            Ranking::Contains
+            portion_with("North West Air Lines", "nw", &bounded).as_deref(),
+            Some("NW")
         );
     }
 
     #[test]
-    fn ranking_word_boundary_second_occurrence() {
-        // The first substring match is not at a word boundary, but a later
-        // one is. "foo foobar" contains "foo" at positions 0 and 4.
-        // Position 0 -> StartsWith (steps 5-6 apply before step 7).
-        // Use a different example where first match is not at pos 0:
-        // "xfoo bar foo" -> "foo" at positions 1, 8. Position 1 preceded by
-        // 'x' (not space). Position 8 preceded by ' ' -> WordStartsWith.
+    fn matched_portion_fuzzy_spans_first_to_last() {
         assert_eq!(
-            get_match_ranking("xfoo bar foo", "foo", false),
-            Ranking::WordStartsWith
+            portion("my playground!", "plgnd").as_deref(),
+            Some("playground")
+        );
+        assert_eq!(portion("axxayb", "ab").as_deref(), Some("axxayb"));
+        let optimal = RankingConfig {
+            fuzzy_mode: FuzzyMode::Optimal,
+            ..RankingConfig::default()
+        };
+        assert_eq!(
+            portion_with("axxayb", "ab", &optimal).as_deref(),
+            Some("ayb")
         );
     }
 
     #[test]
-    fn ranking_diacritics_stripping() {
-        // "caf\u{00E9}" with keep_diacritics=false is prepared to "cafe".
-        // Query "cafe" is prepared to "cafe". Exact match -> CaseSensitiveEqual.
-        assert_eq!(
-            get_match_ranking("caf\u{00E9}", "cafe", false),
-            Ranking::CaseSensitiveEqual
-        );
+    fn matched_portion_no_match_is_none() {
+        assert_eq!(portion("abc", "xyz"), None);
     }
 
     #[test]
-    fn ranking_diacritics_kept() {
-        // With keep_diacritics=true, "caf\u{00E9}" != "cafe" (different bytes).
-        // Lowercased: "caf\u{00E9}" does not contain "cafe" as a substring.
-        // Fuzzy match: c(0), a(1), f(2) found, but 'e' in query won't match
-        // '\u{00E9}' in candidate -> depends on the actual chars.
-        // Actually the lowercased \u{00E9} is still \u{00E9}, and 'e' != '\u{00E9}'.
-        // So fuzzy fails too -> NoMatch.
+    fn matched_portion_multi_byte() {
+        // Stripped diacritics map back to the accented characters.
         assert_eq!(
-            get_match_ranking("caf\u{00E9}", "cafe", true),
-            Ranking::NoMatch
+            portion("Cr\u{00e8}me Br\u{00fb}l\u{00e9}e", "brulee").as_deref(),
+            Some("Br\u{00fb}l\u{00e9}e")
+        );
+        // A trailing combining mark stays with its base character.
+        assert_eq!(
+            portion("Cafe\u{0301} noir", "cafe").as_deref(),
+            Some("Cafe\u{0301}")
+        );
+        assert_eq!(
+            portion(
+                "\u{65e5}\u{672c}\u{8a9e}\u{306e}\u{30c6}\u{30ad}",
+                "\u{672c}\u{30c6}"
+            )
+            .as_deref(),
+            Some("\u{672c}\u{8a9e}\u{306e}\u{30c6}")
+        );
+        // "\u{0130}" lowercases to two characters when diacritics are kept.
+        let keep = RankingConfig::with_diacritics(true);
+        assert_eq!(
+            portion_with("\u{0130}stanbul", "stan", &keep).as_deref(),
+            Some("stan")
+        );
+        assert_eq!(
+            portion_with("\u{0130}stanbul", "\u{0069}\u{0307}s", &keep).as_deref(),
+            Some("\u{0130}s")
         );
     }
 
     #[test]
-    fn ranking_unicode_char_count_vs_byte_count() {
-        // Step 1 compares CHARACTER counts, not byte counts.
-        // "\u{00E9}" is 2 bytes but 1 character. So a candidate of 1 char
-        // with a query of 2 chars should be NoMatch by character count.
-        assert_eq!(get_match_ranking("\u{00E9}", "ab", true), Ranking::NoMatch);
+    fn matched_portion_at_least_query_length() {
+        let cases = [
+            ("Green", "green"),
+            ("evergreen", "green"),
+            ("Cr\u{00e8}me br\u{00fb}l\u{00e9}e", "brul"),
+            ("North-West Airlines", "nwa"),
+            ("playground", "plgnd"),
+            ("Caf\u{00e9} au lait", "cafe"),
+        ];
+        for (candidate, query) in cases {
+            let lower = PreparedQuery::new(query, false).lower;
+            let matched = portion(candidate, query).unwrap();
+            assert!(matched.len() >= lower.len(), "{candidate:?} / {query:?}");
+        }
+    }
+
+    fn ranges(candidate: &str, query: &str) -> Vec<Range<usize>> {
+        let config = RankingConfig::default();
+        let pq = PreparedQuery::new(query, false);
+        let finder =
+            (!pq.lower.is_empty()).then(|| memchr::memmem::Finder::new(pq.lower.as_bytes()));
+        let mut buf = String::new();
+        let rank = get_match_ranking_prepared(candidate, &pq, &config, &mut buf, finder.as_ref());
+        get_matched_ranges(candidate, rank, &pq, &config, &mut buf, finder.as_ref())
     }
 
     #[test]
-    fn ranking_acronym_not_reached_for_single_char() {
-        // Step 9 prevents single-char queries from reaching acronym check.
-        // "a b c" has acronym "abc", but query "x" (single char) not found
-        // as substring -> NoMatch at step 9, never reaches acronym.
-        assert_eq!(get_match_ranking("a b c", "x", false), Ranking::NoMatch);
+    fn matched_ranges_per_tier() {
+        assert_eq!(ranges("Green", "green"), vec![0..5]);
+        assert_eq!(ranges("evergreen Green", "green"), vec![10..15]);
+        assert_eq!(ranges("evergreen", "green"), vec![4..9]);
+        assert_eq!(ranges("grape", "xyz"), Vec::<Range<usize>>::new());
     }
 
     #[test]
-    fn ranking_acronym_multi_word() {
-        // "as soon as possible" -> acronym "asap", query "asap" matches.
+    fn matched_ranges_acronym_letters_spelling_query() {
         assert_eq!(
-            get_match_ranking("as soon as possible", "asap", false),
-            Ranking::Acronym
+            ranges("North-West Airlines", "nwa"),
+            vec![0..1, 6..7, 11..12]
         );
+        // Only the letters spelling the query, not the whole acronym.
+        assert_eq!(ranges("one two three", "tt"), vec![4..5, 8..9]);
     }
 
     #[test]
-    fn ranking_contains_mid_string() {
-        // "hello world" contains "lo w" at position 3. Byte before pos 3
-        // is 'l' (not space) -> Contains.
-        assert_eq!(
-            get_match_ranking("hello world", "lo w", false),
-            Ranking::Contains
-        );
+    fn matched_ranges_fuzzy_per_char_and_merged() {
+        // p-l, g, n-d: adjacent matched characters share a range.
+        assert_eq!(ranges("playground", "plgnd"), vec![0..2, 4..5, 8..10]);
     }
 
     #[test]
-    fn ranking_query_longer_than_candidate_unicode() {
-        // Unicode characters: candidate has 2 chars, query has 3 chars.
-        assert_eq!(
-            get_match_ranking("\u{4e16}\u{754c}", "abc", false),
-            Ranking::NoMatch
-        );
+    fn matched_ranges_map_back_onto_accented_value() {
+        let value = "Cr\u{00e8}me br\u{00fb}l\u{00e9}e";
+        let matched = ranges(value, "brul");
+        assert_eq!(matched.len(), 1);
+        assert_eq!(&value[matched[0].clone()], "br\u{00fb}l");
     }
 
     // --- lowercase_into tests ---
@@ -1345,4 +5751,309 @@ mod tests {
         lowercase_into("Caf\u{00C9}", &mut buf);
         assert_eq!(buf, "caf\u{00E9}");
     }
+
+    // --- normalization form tests ---
+
+    fn rank_with_form(candidate: &str, query: &str, form: NormalizationForm) -> Ranking {
+        PreparedQuery::with_normalization(query, DiacriticsMode::StripAll, form)
+            .apply_batch(&[candidate])[0]
+    }
+
+    #[test]
+    fn nfkc_folds_ligatures_and_full_width() {
+        let nfkc = NormalizationForm::Nfkc;
+        assert_eq!(
+            rank_with_form("\u{fb01}le", "file", nfkc),
+            Ranking::CaseSensitiveEqual
+        );
+        assert_eq!(
+            rank_with_form("file", "\u{fb01}le", nfkc),
+            Ranking::CaseSensitiveEqual
+        );
+        assert_eq!(
+            rank_with_form("\u{ff41}\u{ff50}\u{ff50}", "app", nfkc),
+            Ranking::CaseSensitiveEqual
+        );
+        assert_eq!(
+            rank_with_form("\u{ff21}\u{ff30}\u{ff30}", "app", nfkc),
+            Ranking::Equal
+        );
+        assert_eq!(
+            rank_with_form("\u{210c}ilbert", "hil", nfkc),
+            Ranking::StartsWith
+        );
+        // Diacritics are still stripped after folding.
+        assert_eq!(
+            rank_with_form("\u{fb01}anc\u{e9}", "fiance", nfkc),
+            Ranking::CaseSensitiveEqual
+        );
+    }
+
+    #[test]
+    fn nfd_leaves_compatibility_characters_alone() {
+        let nfd = NormalizationForm::Nfd;
+        assert_eq!(rank_with_form("\u{fb01}le", "file", nfd), Ranking::NoMatch);
+        assert_eq!(
+            rank_with_form("\u{ff41}\u{ff50}\u{ff50}", "app", nfd),
+            Ranking::NoMatch
+        );
+        assert_eq!(
+            rank_with_form("caf\u{e9}", "cafe", nfd),
+            Ranking::CaseSensitiveEqual
+        );
+        for (candidate, query) in [("caf\u{e9}", "cafe"), ("\u{fb01}le", "le"), ("Apple", "ap")] {
+            assert_eq!(
+                rank_with_form(candidate, query, nfd),
+                get_match_ranking(candidate, query, false)
+            );
+        }
+    }
+
+    #[test]
+    fn no_normalization_keeps_diacritics() {
+        let none = NormalizationForm::None;
+        assert_eq!(rank_with_form("caf\u{e9}", "cafe", none), Ranking::NoMatch);
+        assert_eq!(
+            rank_with_form("caf\u{e9}", "caf\u{e9}", none),
+            Ranking::CaseSensitiveEqual
+        );
+        assert_eq!(
+            prepare_value_with_form("e\u{301}", DiacriticsMode::StripAll, none),
+            "e\u{301}"
+        );
+    }
+
+    #[test]
+    fn nfkc_matched_portion_maps_to_original() {
+        let config = RankingConfig {
+            normalization: NormalizationForm::Nfkc,
+            ..RankingConfig::default()
+        };
+        assert_eq!(
+            portion_with("My \u{fb01}le list", "file", &config).as_deref(),
+            Some("\u{fb01}le")
+        );
+        assert_eq!(
+            portion_with("an \u{ff41}\u{ff50}\u{ff50}", "app", &config).as_deref(),
+            Some("\u{ff41}\u{ff50}\u{ff50}")
+        );
+    }
+
+    // --- case folding tests ---
+
+    fn rank_with_folding(candidate: &str, query: &str, folding: UnicodeCaseFolding) -> Ranking {
+        PreparedQuery::with_case_folding(
+            query,
+            DiacriticsMode::Preserve,
+            NormalizationForm::default(),
+            folding,
+        )
+        .apply_batch(&[candidate])[0]
+    }
+
+    fn fold(s: &str, folding: UnicodeCaseFolding) -> String {
+        let mut buf = String::new();
+        folding.fold_into(s, &mut buf);
+        buf
+    }
+
+    #[test]
+    fn simple_folding_is_the_default() {
+        assert_eq!(UnicodeCaseFolding::default(), UnicodeCaseFolding::Simple);
+        assert_eq!(
+            RankingConfig::default().case_folding,
+            UnicodeCaseFolding::Simple
+        );
+        assert_eq!(fold("Ma\u{df}e", UnicodeCaseFolding::Simple), "ma\u{df}e");
+    }
+
+    #[test]
+    fn full_folding_expands_sharp_s() {
+        let full = UnicodeCaseFolding::Full;
+        assert_eq!(
+            rank_with_folding("Ma\u{df}e", "masse", full),
+            Ranking::Equal
+        );
+        assert_eq!(
+            rank_with_folding("MASSE", "ma\u{df}e", full),
+            Ranking::Equal
+        );
+        assert_eq!(
+            rank_with_folding("Stra\u{1e9e}e", "strasse", full),
+            Ranking::Equal
+        );
+        assert!(
+            rank_with_folding("Ma\u{df}e", "masse", UnicodeCaseFolding::Simple) < Ranking::Equal
+        );
+    }
+
+    #[test]
+    fn full_folding_covers_ligatures_and_final_sigma() {
+        let full = UnicodeCaseFolding::Full;
+        assert_eq!(fold("\u{fb01}le", full), "file");
+        assert_eq!(
+            fold("\u{3a3}\u{3bf}\u{3c6}\u{3bf}\u{3c2}", full),
+            "\u{3c3}\u{3bf}\u{3c6}\u{3bf}\u{3c3}"
+        );
+        assert_eq!(fold("\u{1f88}", full), "\u{1f00}\u{3b9}");
+        assert_eq!(fold("Caf\u{c9}", full), "caf\u{e9}");
+    }
+
+    #[test]
+    fn sharp_s_equal_and_case_sensitive_tiers_per_folding() {
+        for folding in [UnicodeCaseFolding::Simple, UnicodeCaseFolding::Full] {
+            assert_eq!(
+                rank_with_folding("stra\u{df}e", "stra\u{df}e", folding),
+                Ranking::CaseSensitiveEqual,
+                "{folding:?}"
+            );
+            // Folding never makes "\u{df}" and "ss" case-sensitively equal.
+            assert!(
+                rank_with_folding("stra\u{df}e", "strasse", folding) < Ranking::CaseSensitiveEqual,
+                "{folding:?}"
+            );
+        }
+        assert_eq!(
+            rank_with_folding("stra\u{df}e", "STRASSE", UnicodeCaseFolding::Full),
+            Ranking::Equal
+        );
+        assert!(
+            rank_with_folding("stra\u{df}e", "STRASSE", UnicodeCaseFolding::Simple)
+                < Ranking::Equal
+        );
+        assert_eq!(fold("\u{1c4}", UnicodeCaseFolding::Full), "\u{1c6}");
+    }
+
+    #[test]
+    fn turkic_folding_maps_dotted_and_dotless_i() {
+        let turkic = UnicodeCaseFolding::Turkic;
+        assert_eq!(fold("\u{130}stanbul", turkic), "istanbul");
+        assert_eq!(fold("ISPARTA", turkic), "\u{131}sparta");
+        assert_eq!(
+            fold("\u{130}stanbul", UnicodeCaseFolding::Simple),
+            "i\u{307}stanbul"
+        );
+        assert_eq!(
+            rank_with_folding("\u{130}stanbul", "istanbul", turkic),
+            Ranking::Equal
+        );
+        assert!(rank_with_folding("ISPARTA", "isparta", turkic) < Ranking::Equal);
+    }
+
+    #[test]
+    fn folded_len_matches_fold_into() {
+        for folding in [
+            UnicodeCaseFolding::Simple,
+            UnicodeCaseFolding::Full,
+            UnicodeCaseFolding::Turkic,
+        ] {
+            for c in [
+                'A', '\u{df}', '\u{130}', 'I', '\u{fb03}', '\u{1f88}', '\u{3c2}',
+            ] {
+                assert_eq!(
+                    folding.folded_len(c),
+                    fold(&c.to_string(), folding).len(),
+                    "{folding:?} {c:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn full_folding_matched_portion_maps_to_original() {
+        let config = RankingConfig {
+            case_folding: UnicodeCaseFolding::Full,
+            ..RankingConfig::default()
+        };
+        let pq = PreparedQuery::with_case_folding(
+            "masse",
+            config.diacritics,
+            config.normalization,
+            config.case_folding,
+        );
+        let finder = memchr::memmem::Finder::new(pq.lower.as_bytes());
+        let mut buf = String::new();
+        let candidate = "Die Ma\u{df}e";
+        let rank = get_match_ranking_prepared(candidate, &pq, &config, &mut buf, Some(&finder));
+        assert_eq!(rank, Ranking::WordStartsWith);
+        assert_eq!(
+            get_matched_portion(candidate, rank, &pq, &config, &mut buf, Some(&finder)).as_deref(),
+            Some("Ma\u{df}e")
+        );
+    }
+
+    #[test]
+    fn turkic_matched_portion_maps_to_original() {
+        let config = RankingConfig {
+            case_folding: UnicodeCaseFolding::Turkic,
+            ..RankingConfig::default()
+        };
+        let pq = PreparedQuery::with_case_folding(
+            "\u{131}sp",
+            config.diacritics,
+            config.normalization,
+            config.case_folding,
+        );
+        let finder = memchr::memmem::Finder::new(pq.lower.as_bytes());
+        let mut buf = String::new();
+        let rank = get_match_ranking_prepared("ISPARTA", &pq, &config, &mut buf, Some(&finder));
+        assert_eq!(rank, Ranking::StartsWith);
+        assert_eq!(
+            get_matched_portion("ISPARTA", rank, &pq, &config, &mut buf, Some(&finder)).as_deref(),
+            Some("ISP")
+        );
+    }
+
+    // --- prefilter tests ---
+
+    fn prefilter_config() -> RankingConfig<'static> {
+        RankingConfig {
+            prefilter: true,
+            ..RankingConfig::default()
+        }
+    }
+
+    #[test]
+    fn prefilter_rejects_candidates_without_first_char() {
+        let pq = PreparedQuery::new("zeta", false);
+        let config = prefilter_config();
+        assert!(prefilter_rejects("item_42", &pq, &config));
+        assert!(!prefilter_rejects("ZETA", &pq, &config));
+        assert!(!prefilter_rejects("size", &pq, &config));
+        // Non-ASCII candidates and queries are left to full ranking.
+        assert!(!prefilter_rejects("caf\u{e9}", &pq, &config));
+        assert!(!prefilter_rejects(
+            "item",
+            &PreparedQuery::new("\u{e9}", false),
+            &config
+        ));
+        assert!(!prefilter_rejects(
+            "item",
+            &PreparedQuery::new("", false),
+            &config
+        ));
+        assert_eq!(rank_with("item_42", "zeta", &config), Ranking::NoMatch);
+    }
+
+    #[test]
+    fn prefilter_never_changes_rank() {
+        let cases = [
+            ("Apple", "ap"),
+            ("New York", "ny"),
+            ("playground", "plgnd"),
+            ("\u{212a}elvin", "kelvin"),
+            ("caf\u{e9}", "cafe"),
+            ("item_42", "zeta"),
+            ("1-2-3", "123"),
+            ("Hello", ""),
+        ];
+        let config = prefilter_config();
+        for (candidate, query) in cases {
+            assert_eq!(
+                rank_with(candidate, query, &config),
+                rank_with(candidate, query, &RankingConfig::default()),
+                "{candidate:?} vs {query:?}"
+            );
+        }
+    }
 }
@@ -5,6 +5,8 @@
 
 use std::cmp::Ordering;
 
+use unicode_normalization::{IsNormalized, UnicodeNormalization, is_nfc_quick};
+
 use crate::options::RankedItem;
 
 /// Alphabetical tiebreaker sort for ranked items.
@@ -40,6 +42,10 @@ use crate::options::RankedItem;
 ///     ranked_value: Cow::Borrowed("apple"),
 ///     key_index: 0,
 ///     key_threshold: None,
+///     is_soft_match: false,
+///     matched_portion: None,
+///     matched_key_alias: None,
+///     tie_priority: None,
 /// };
 /// let b = RankedItem {
 ///     item: &item_b,
@@ -48,6 +54,10 @@ use crate::options::RankedItem;
 ///     ranked_value: Cow::Borrowed("banana"),
 ///     key_index: 0,
 ///     key_threshold: None,
+///     is_soft_match: false,
+///     matched_portion: None,
+///     matched_key_alias: None,
+///     tie_priority: None,
 /// };
 ///
 /// assert_eq!(default_base_sort(&a, &b), Ordering::Less);
@@ -56,6 +66,43 @@ pub fn default_base_sort<T>(a: &RankedItem<T>, b: &RankedItem<T>) -> Ordering {
     a.ranked_value.cmp(&b.ranked_value)
 }
 
+/// Alphabetical tiebreaker that compares `ranked_value`s in Unicode NFC.
+///
+/// [`default_base_sort`] compares bytes, so canonically equivalent strings
+/// with different encodings, such as a precomposed `"\u{e9}"` and a
+/// decomposed `"e\u{301}"` (common in macOS file names), sort apart and
+/// may order differently across platforms. This comparator normalizes
+/// both values to NFC first: equivalent values compare equal and keep
+/// their input order, and other values sort by their NFC form.
+///
+/// Values already in NFC (including all ASCII) are compared as bytes
+/// without normalizing. Otherwise the NFC forms are produced lazily and
+/// compared up to the first difference.
+/// Set it with
+/// [`MatchSorterOptions::unicode_base_sort`](crate::MatchSorterOptions::unicode_base_sort).
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, match_sorter};
+///
+/// let items = ["cafe\u{301} b", "caf\u{e9} a", "cafe\u{301} c"];
+/// let opts = MatchSorterOptions::default().unicode_base_sort();
+/// // All three start with the query; NFC puts "a" before "b" and "c".
+/// assert_eq!(
+///     match_sorter(&items, "caf", &opts),
+///     vec![&"caf\u{e9} a", &"cafe\u{301} b", &"cafe\u{301} c"]
+/// );
+/// ```
+pub fn unicode_base_sort<T>(a: &RankedItem<T>, b: &RankedItem<T>) -> Ordering {
+    let (a, b) = (&*a.ranked_value, &*b.ranked_value);
+    let is_nfc = |s: &str| s.is_ascii() || is_nfc_quick(s.chars()) == IsNormalized::Yes;
+    if is_nfc(a) && is_nfc(b) {
+        return a.cmp(b);
+    }
+    a.nfc().cmp(b.nfc())
+}
+
 /// Three-level comparator for sorting ranked items.
 ///
 /// Implements the same sorting logic as the JS `match-sorter` library:
@@ -63,7 +110,11 @@ pub fn default_base_sort<T>(a: &RankedItem<T>, b: &RankedItem<T>) -> Ordering {
 /// 1. **Higher rank wins** -- items with a better (higher) ranking come first.
 /// 2. **Lower key index wins** -- when ranks are equal, items matched by an
 ///    earlier key come first.
-/// 3. **Base sort tiebreaker** -- when both rank and key index are equal, the
+/// 3. **Higher tie priority wins** -- when both rank and key index are
+///    equal, the item with the higher
+///    [`tie_priority`](RankedItem::tie_priority) comes first; items without
+///    one come after those with one. See [`Key::tie_priority`](crate::Key::tie_priority).
+/// 4. **Base sort tiebreaker** -- when all of the above are equal, the
 ///    provided `base_sort` function breaks the tie (default: alphabetical by
 ///    `ranked_value`).
 ///
@@ -71,7 +122,8 @@ pub fn default_base_sort<T>(a: &RankedItem<T>, b: &RankedItem<T>) -> Ordering {
 ///
 /// * `a` - First ranked item to compare
 /// * `b` - Second ranked item to compare
-/// * `base_sort` - Tiebreaker function called when rank and key index are equal
+/// * `base_sort` - Tiebreaker function called when rank, key index and tie
+///   priority are equal
 ///
 /// # Returns
 ///
@@ -93,6 +145,10 @@ pub fn default_base_sort<T>(a: &RankedItem<T>, b: &RankedItem<T>) -> Ordering {
 ///     ranked_value: Cow::Borrowed("alpha"),
 ///     key_index: 0,
 ///     key_threshold: None,
+///     is_soft_match: false,
+///     matched_portion: None,
+///     matched_key_alias: None,
+///     tie_priority: None,
 /// };
 /// let b = RankedItem {
 ///     item: &items[1],
@@ -101,6 +157,10 @@ pub fn default_base_sort<T>(a: &RankedItem<T>, b: &RankedItem<T>) -> Ordering {
 ///     ranked_value: Cow::Borrowed("beta"),
 ///     key_index: 0,
 ///     key_threshold: None,
+///     is_soft_match: false,
+///     matched_portion: None,
+///     matched_key_alias: None,
+///     tie_priority: None,
 /// };
 ///
 /// // StartsWith > Contains, so `a` comes first (Less).
@@ -119,7 +179,59 @@ pub fn sort_ranked_values<T>(
         .unwrap_or(Ordering::Equal)
         // Level 2: Lower key_index first (ascending).
         .then_with(|| a.key_index.cmp(&b.key_index))
-        // Level 3: Tiebreaker via the caller-supplied base_sort function.
+        // Level 3: Higher tie_priority first (descending); `None` sorts
+        // below every `Some`.
+        .then_with(|| b.tie_priority.cmp(&a.tie_priority))
+        // Level 4: Tiebreaker via the caller-supplied base_sort function.
+        .then_with(|| base_sort(a, b))
+}
+
+/// Like [`sort_ranked_values`], but ranks in the same tier compare equal.
+///
+/// Every `Matches` sub-score counts as the same rank (see
+/// [`Ranking::eq_tier`](crate::Ranking::eq_tier)), so fuzzy matches are
+/// ordered by key index, tie priority and `base_sort` alone rather than by how tightly
+/// their characters cluster. Fixed tiers sort exactly as in
+/// [`sort_ranked_values`]. Use it from a
+/// [`sorter`](crate::MatchSorterOptions::sorter) override.
+///
+/// # Arguments
+///
+/// * `a` - First ranked item to compare
+/// * `b` - Second ranked item to compare
+/// * `base_sort` - Tiebreaker function called when tier, key index and tie
+///   priority are equal
+///
+/// # Examples
+///
+/// ```
+/// use matchsorter::{MatchSorterOptions, default_base_sort, match_sorter, sort_ranked_values_by_tier};
+///
+/// // "a_b_c" is a looser fuzzy match for "abc" than "xabxc", but both
+/// // are in the Matches tier, so they sort alphabetically.
+/// let items = ["xabxc", "a_b_c"];
+/// let opts = MatchSorterOptions {
+///     sorter: Some(Box::new(|mut ranked| {
+///         ranked.sort_by(|a, b| sort_ranked_values_by_tier(a, b, &default_base_sort));
+///         ranked
+///     })),
+///     ..Default::default()
+/// };
+/// assert_eq!(match_sorter(&items, "abc", &opts), vec![&"a_b_c", &"xabxc"]);
+/// ```
+pub fn sort_ranked_values_by_tier<T>(
+    a: &RankedItem<T>,
+    b: &RankedItem<T>,
+    base_sort: &dyn Fn(&RankedItem<T>, &RankedItem<T>) -> Ordering,
+) -> Ordering {
+    let by_rank = if a.rank.eq_tier(b.rank) {
+        Ordering::Equal
+    } else {
+        b.rank.partial_cmp(&a.rank).unwrap_or(Ordering::Equal)
+    };
+    by_rank
+        .then_with(|| a.key_index.cmp(&b.key_index))
+        .then_with(|| b.tie_priority.cmp(&a.tie_priority))
         .then_with(|| base_sort(a, b))
 }
 
@@ -149,6 +261,10 @@ mod tests {
             ranked_value: Cow::Borrowed(ranked_value),
             key_index,
             key_threshold: None,
+            is_soft_match: false,
+            matched_portion: None,
+            matched_key_alias: None,
+            tie_priority: None,
         }
     }
 
@@ -315,6 +431,70 @@ mod tests {
         );
     }
 
+    // --- sort_ranked_values: tie priority tests ---
+
+    fn with_priority(
+        priority: Option<i64>,
+        ranked_value: &'static str,
+    ) -> RankedItem<'static, &'static str> {
+        RankedItem {
+            tie_priority: priority,
+            ..make_ranked(Ranking::Contains, ranked_value, 0)
+        }
+    }
+
+    #[test]
+    fn higher_tie_priority_sorts_first() {
+        let low = with_priority(Some(-1), "same");
+        let high = with_priority(Some(2), "same");
+        assert_eq!(
+            sort_ranked_values(&high, &low, &default_base_sort),
+            Ordering::Less
+        );
+        assert_eq!(
+            sort_ranked_values(&low, &high, &default_base_sort),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn missing_tie_priority_sorts_last() {
+        let none = with_priority(None, "same");
+        let negative = with_priority(Some(i64::MIN), "same");
+        assert_eq!(
+            sort_ranked_values(&negative, &none, &default_base_sort),
+            Ordering::Less
+        );
+        assert_eq!(
+            sort_ranked_values_by_tier(&none, &negative, &default_base_sort),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn tie_priority_precedes_base_sort() {
+        let panic_sort = |_a: &RankedItem<&str>, _b: &RankedItem<&str>| -> Ordering {
+            panic!("base_sort should not be called when tie priorities differ");
+        };
+        // Alphabetically "banana" would sort after "apple".
+        let a = with_priority(Some(0), "apple");
+        let b = with_priority(Some(1), "banana");
+        assert_eq!(sort_ranked_values(&a, &b, &panic_sort), Ordering::Greater);
+    }
+
+    #[test]
+    fn key_index_precedes_tie_priority() {
+        let a = with_priority(Some(0), "same");
+        let b = RankedItem {
+            key_index: 1,
+            ..with_priority(Some(9), "same")
+        };
+        assert_eq!(
+            sort_ranked_values(&a, &b, &default_base_sort),
+            Ordering::Less
+        );
+    }
+
     // --- sort_ranked_values: custom base_sort tests ---
 
     #[test]
@@ -419,4 +599,107 @@ mod tests {
         assert_eq!(ranked[2].ranked_value, "zebra");
         assert_eq!(ranked[3].ranked_value, "mango");
     }
+
+    // --- sort_ranked_values_by_tier tests ---
+
+    #[test]
+    fn by_tier_ignores_matches_sub_score() {
+        let a = make_ranked(Ranking::Matches(1.1), "apple", 0);
+        let b = make_ranked(Ranking::Matches(1.9), "banana", 0);
+        assert_eq!(
+            sort_ranked_values_by_tier(&a, &b, &default_base_sort),
+            Ordering::Less
+        );
+        // The exact comparator still prefers the tighter fuzzy match.
+        assert_eq!(
+            sort_ranked_values(&a, &b, &default_base_sort),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn by_tier_agrees_on_fixed_tiers() {
+        let mut exact: Vec<RankedItem<&str>> = vec![
+            make_ranked(Ranking::Contains, "zebra", 0),
+            make_ranked(Ranking::Matches(1.5), "fuzzy", 0),
+            make_ranked(Ranking::Contains, "mango", 1),
+            make_ranked(Ranking::StartsWith, "banana", 0),
+            make_ranked(Ranking::Acronym, "alpha", 0),
+        ];
+        let mut by_tier = exact.clone();
+        exact.sort_by(|a, b| sort_ranked_values(a, b, &default_base_sort));
+        by_tier.sort_by(|a, b| sort_ranked_values_by_tier(a, b, &default_base_sort));
+        let values = |v: &[RankedItem<&str>]| -> Vec<String> {
+            v.iter().map(|ri| ri.ranked_value.to_string()).collect()
+        };
+        assert_eq!(values(&exact), values(&by_tier));
+    }
+
+    // --- unicode_base_sort tests ---
+
+    #[test]
+    fn unicode_base_sort_nfc_and_nfd_compare_equal() {
+        let nfc = make_ranked(Ranking::Equal, "r\u{e9}sum\u{e9}", 0);
+        let nfd = make_ranked(Ranking::Equal, "re\u{301}sume\u{301}", 0);
+        assert_ne!(default_base_sort(&nfc, &nfd), Ordering::Equal);
+        assert_eq!(unicode_base_sort(&nfc, &nfd), Ordering::Equal);
+        assert_eq!(unicode_base_sort(&nfd, &nfc), Ordering::Equal);
+    }
+
+    #[test]
+    fn unicode_base_sort_orders_by_nfc_form() {
+        // The NFD "e" (0x65) sorts before "\u{e9}" by bytes; in NFC it is
+        // "\u{e9}" itself, which sorts after "f".
+        let nfd = make_ranked(Ranking::Equal, "cafe\u{301}", 0);
+        let f = make_ranked(Ranking::Equal, "caff", 0);
+        assert_eq!(default_base_sort(&nfd, &f), Ordering::Less);
+        assert_eq!(unicode_base_sort(&nfd, &f), Ordering::Greater);
+
+        let a = make_ranked(Ranking::Equal, "apple", 0);
+        let b = make_ranked(Ranking::Equal, "banana", 0);
+        assert_eq!(unicode_base_sort(&a, &b), Ordering::Less);
+        assert_eq!(unicode_base_sort(&a, &a), Ordering::Equal);
+    }
+
+    #[test]
+    fn unicode_base_sort_keeps_equivalent_values_in_input_order() {
+        let mut items = [
+            make_ranked(Ranking::Equal, "re\u{301}sume\u{301}", 0),
+            make_ranked(Ranking::Equal, "r\u{e9}sum\u{e9}", 0),
+            make_ranked(Ranking::Equal, "re\u{301}sum\u{e9}", 0),
+        ];
+        for (i, item) in items.iter_mut().enumerate() {
+            item.index = i;
+        }
+        items.reverse();
+        items.sort_by(|a, b| sort_ranked_values(a, b, &unicode_base_sort));
+        let order: Vec<usize> = items.iter().map(|item| item.index).collect();
+        assert_eq!(order, [2, 1, 0]);
+    }
+
+    // --- stability ---
+
+    #[test]
+    fn sort_is_stable() {
+        let mut ranked: Vec<_> = (0..1000)
+            .map(|index| RankedItem {
+                index,
+                ..make_ranked(Ranking::Contains, "same", 2)
+            })
+            .collect();
+        ranked.sort_by(|a, b| sort_ranked_values(a, b, &default_base_sort));
+        assert!(ranked.iter().map(|ri| ri.index).eq(0..1000));
+
+        // Ties broken by rank or key index leave equal runs in input order.
+        let mut mixed: Vec<_> = (0..1000)
+            .map(|index| RankedItem {
+                index,
+                ..make_ranked(Ranking::Contains, "same", index % 3)
+            })
+            .collect();
+        mixed.sort_by(|a, b| sort_ranked_values(a, b, &default_base_sort));
+        for run in mixed.chunk_by(|a, b| a.key_index == b.key_index) {
+            assert!(run.windows(2).all(|w| w[0].index < w[1].index));
+        }
+    }
 }
@@ -0,0 +1,97 @@
+//! Allocation behaviour of `match_sorter`, observed through a counting
+//! global allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use matchsorter::{MatchSorterOptions, Ranking, match_sorter};
+
+/// Counts `realloc` calls made by the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static REALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // `try_with` fails once the thread-local is destroyed at thread exit.
+        let _ = REALLOCS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Number of reallocations performed by `f` on this thread.
+fn reallocs_during<R>(f: impl FnOnce() -> R) -> usize {
+    let before = REALLOCS.with(Cell::get);
+    let result = f();
+    let after = REALLOCS.with(Cell::get);
+    drop(result);
+    after - before
+}
+
+/// 10,000 short names, 5% of which contain `"match"`.
+fn items() -> Vec<String> {
+    (0..10_000)
+        .map(|i| {
+            if i % 20 == 0 {
+                format!("item {i} match")
+            } else {
+                format!("item {i} other")
+            }
+        })
+        .collect()
+}
+
+fn with_threshold(threshold: Ranking) -> MatchSorterOptions<String> {
+    MatchSorterOptions {
+        threshold,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn heuristic_capacity_avoids_reallocation() {
+    let items = items();
+    let contains =
+        reallocs_during(|| match_sorter(&items, "match", &with_threshold(Ranking::Contains)));
+    assert_eq!(contains, 0);
+    let fuzzy = reallocs_during(|| match_sorter(&items, "item", &MatchSorterOptions::default()));
+    assert_eq!(fuzzy, 0);
+    let exact = reallocs_during(|| {
+        match_sorter(
+            &items,
+            "item 40 match",
+            &with_threshold(Ranking::CaseSensitiveEqual),
+        )
+    });
+    assert_eq!(exact, 0);
+}
+
+#[test]
+fn undersized_hint_reallocates_with_same_results() {
+    let items = items();
+    let expected = match_sorter(&items, "match", &with_threshold(Ranking::Contains));
+    let mut results = Vec::new();
+    let reallocs = reallocs_during(|| {
+        results = match_sorter(
+            &items,
+            "match",
+            &with_threshold(Ranking::Contains).capacity_hint(1),
+        );
+    });
+    assert!(reallocs > 0);
+    assert_eq!(results, expected);
+    assert_eq!(results.len(), 500);
+}
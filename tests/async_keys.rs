@@ -0,0 +1,225 @@
+//! Tests for `AsyncKey` and `match_sorter_async`.
+#![cfg(feature = "async")]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use matchsorter::key::{AsyncKey, ExtractFuture};
+use matchsorter::{
+    AsMatchStr, AsyncMatchSorterOptions, EmptyQueryBehavior, Key, MatchSorterOptions, Ranking,
+    match_sorter, match_sorter_async,
+};
+use tokio::time::{Instant, sleep};
+
+const DELAY: Duration = Duration::from_millis(50);
+
+struct User {
+    id: u32,
+    name: &'static str,
+    email: &'static str,
+}
+
+impl AsMatchStr for User {
+    fn as_match_str(&self) -> &str {
+        self.name
+    }
+}
+
+fn users() -> Vec<User> {
+    [
+        ("Alice", "alice@example.com"),
+        ("Bob", "bob@example.com"),
+        ("Malika", "malika@example.com"),
+        ("Alfred", "fred@example.com"),
+        ("Carol", "carol.ali@example.com"),
+        ("Dave", "dave@example.com"),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(id, (name, email))| User {
+        id: id as u32,
+        name,
+        email,
+    })
+    .collect()
+}
+
+/// Mock lookup that sleeps before returning one field of the user.
+struct SlowField(fn(&User) -> &'static str);
+
+impl AsyncKey<User> for SlowField {
+    fn extract_async<'a>(&'a self, user: &'a User) -> ExtractFuture<'a> {
+        Box::pin(async move {
+            sleep(DELAY).await;
+            vec![(self.0)(user).to_owned()]
+        })
+    }
+}
+
+fn slow_keys() -> Vec<Box<dyn AsyncKey<User>>> {
+    vec![
+        Box::new(SlowField(|u| u.name)),
+        Box::new(SlowField(|u| u.email)),
+    ]
+}
+
+fn ids(results: &[&User]) -> Vec<u32> {
+    results.iter().map(|u| u.id).collect()
+}
+
+#[tokio::test(start_paused = true)]
+async fn extraction_runs_concurrently() {
+    let items = users();
+    let keys = slow_keys();
+
+    let start = Instant::now();
+    for user in &items {
+        for key in &keys {
+            key.extract_async(user).await;
+        }
+    }
+    let sequential = start.elapsed();
+
+    let start = Instant::now();
+    let opts = AsyncMatchSorterOptions {
+        keys: slow_keys(),
+        ..Default::default()
+    };
+    let results = match_sorter_async(&items, "ali", opts).await;
+    let concurrent = start.elapsed();
+
+    assert!(!results.is_empty());
+    assert_eq!(sequential, DELAY * (items.len() * keys.len()) as u32);
+    assert!(concurrent < DELAY * 2, "took {concurrent:?}");
+}
+
+#[tokio::test(start_paused = true)]
+async fn results_match_synchronous_keys() {
+    let items = users();
+    for query in ["ali", "a", "fred", "example", "zzz", ""] {
+        let sync_opts = MatchSorterOptions {
+            keys: vec![
+                Key::from_fn(|u: &User| u.name),
+                Key::from_fn(|u: &User| u.email),
+            ],
+            threshold: Ranking::Acronym,
+            ..Default::default()
+        };
+        let expected = ids(&match_sorter(&items, query, &sync_opts));
+
+        let async_opts = AsyncMatchSorterOptions {
+            keys: slow_keys(),
+            options: MatchSorterOptions {
+                threshold: Ranking::Acronym,
+                ..Default::default()
+            },
+        };
+        let actual = ids(&match_sorter_async(&items, query, async_opts).await);
+        assert_eq!(actual, expected, "query {query:?}");
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn sync_keys_follow_async_keys() {
+    let items = users();
+    let sync_opts = MatchSorterOptions {
+        keys: vec![
+            Key::from_fn(|u: &User| u.name),
+            Key::from_fn(|u: &User| u.email),
+        ],
+        ..Default::default()
+    };
+    let expected = ids(&match_sorter(&items, "al", &sync_opts));
+
+    let async_opts = AsyncMatchSorterOptions {
+        keys: vec![Box::new(SlowField(|u| u.name))],
+        options: MatchSorterOptions {
+            keys: vec![Key::from_fn(|u: &User| u.email)],
+            ..Default::default()
+        },
+    };
+    let actual = ids(&match_sorter_async(&items, "al", async_opts).await);
+    assert_eq!(actual, expected);
+}
+
+#[tokio::test(start_paused = true)]
+async fn no_async_keys_ranks_items_directly() {
+    let items = users();
+    let results = match_sorter_async(&items, "ali", AsyncMatchSorterOptions::default()).await;
+    let expected = match_sorter(&items, "ali", &MatchSorterOptions::default());
+    assert_eq!(ids(&results), ids(&expected));
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancel_token_stops_pending_extractions() {
+    let items = users();
+    let cancel = Arc::new(AtomicBool::new(false));
+
+    // The first item's lookup finishes early and cancels the search; every
+    // other lookup would take ten times as long.
+    let token = Arc::clone(&cancel);
+    let cancelling: Box<dyn AsyncKey<User>> = Box::new(move |user: &User| {
+        let (id, name) = (user.id, user.name);
+        let token = Arc::clone(&token);
+        async move {
+            if id == 0 {
+                sleep(DELAY).await;
+                token.store(true, Ordering::Relaxed);
+            } else {
+                sleep(DELAY * 10).await;
+            }
+            vec![name.to_owned()]
+        }
+    });
+    let opts = AsyncMatchSorterOptions {
+        keys: vec![cancelling],
+        options: MatchSorterOptions {
+            cancel_token: Some(Arc::clone(&cancel)),
+            ..Default::default()
+        },
+    };
+
+    let start = Instant::now();
+    let results = match_sorter_async(&items, "a", opts).await;
+    assert!(results.is_empty());
+    assert!(start.elapsed() < DELAY * 10);
+}
+
+#[tokio::test(start_paused = true)]
+async fn already_cancelled_skips_extraction() {
+    let items = users();
+    let opts = AsyncMatchSorterOptions {
+        keys: slow_keys(),
+        options: MatchSorterOptions {
+            cancel_token: Some(Arc::new(AtomicBool::new(true))),
+            ..Default::default()
+        },
+    };
+    let start = Instant::now();
+    assert!(match_sorter_async(&items, "a", opts).await.is_empty());
+    assert_eq!(start.elapsed(), Duration::ZERO);
+}
+
+#[tokio::test(start_paused = true)]
+async fn empty_query_behavior_skips_extraction() {
+    let items = users();
+    for (behavior, expected) in [
+        (EmptyQueryBehavior::ReturnEmpty, Vec::new()),
+        (
+            EmptyQueryBehavior::ReturnAllUnsorted,
+            vec![0, 1, 2, 3, 4, 5],
+        ),
+    ] {
+        let opts = AsyncMatchSorterOptions {
+            keys: slow_keys(),
+            options: MatchSorterOptions {
+                empty_query_behavior: behavior,
+                ..Default::default()
+            },
+        };
+        let start = Instant::now();
+        assert_eq!(ids(&match_sorter_async(&items, "", opts).await), expected);
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
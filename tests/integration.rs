@@ -4,7 +4,13 @@
 //! covering all 14 scenario categories from PRD-003 Section 12. Each test
 //! uses only the public API re-exported from the `matchsorter` crate root.
 
-use matchsorter::{AsMatchStr, Key, MatchSorterOptions, RankedItem, Ranking, match_sorter};
+use matchsorter::{
+    AsMatchStr, EarlyStopCriteria, EmptyQueryBehavior, Key, MatchSorterOptions, MatchSorterSession,
+    QueryMode, RankedItem, Ranking, UnicodeCaseFolding, default_base_sort, get_highest_ranking,
+    match_sorter, match_sorter_by_score, match_sorter_merge, match_sorter_merge_presorted,
+    match_sorter_ranked, match_sorter_with_annotations, match_sorter_with_snapshot,
+    sort_ranked_values,
+};
 
 // ---------------------------------------------------------------------------
 // Shared test types
@@ -64,7 +70,7 @@ impl AsMatchStr for TaggedItem {
 #[test]
 fn basic_string_array_apple_first() {
     let items = ["apple", "banana", "grape"];
-    let results = match_sorter(&items, "ap", MatchSorterOptions::default());
+    let results = match_sorter(&items, "ap", &MatchSorterOptions::default());
     assert!(!results.is_empty(), "should have at least one match");
     assert_eq!(results[0], &"apple", "apple should be first (StartsWith)");
     // "grape" contains "ap" -> should be included
@@ -82,7 +88,7 @@ fn basic_string_array_rank_ordering() {
     // "applesauce" -> StartsWith for "apple"
     // "pineapple" -> Contains for "apple" (substring at position 4)
     let items = ["pineapple", "apple", "applesauce"];
-    let results = match_sorter(&items, "apple", MatchSorterOptions::default());
+    let results = match_sorter(&items, "apple", &MatchSorterOptions::default());
     assert_eq!(results[0], &"apple", "exact match first");
     assert_eq!(results[1], &"applesauce", "StartsWith second");
     assert_eq!(results[2], &"pineapple", "Contains third");
@@ -97,7 +103,7 @@ fn basic_string_array_rank_ordering() {
 #[test]
 fn case_insensitive_matching() {
     let items = ["Green", "Red", "Blue"];
-    let results = match_sorter(&items, "green", MatchSorterOptions::default());
+    let results = match_sorter(&items, "green", &MatchSorterOptions::default());
     assert_eq!(results.len(), 1);
     assert_eq!(results[0], &"Green");
 }
@@ -106,7 +112,7 @@ fn case_insensitive_matching() {
 #[test]
 fn case_sensitive_beats_insensitive() {
     let items = ["green", "Green"];
-    let results = match_sorter(&items, "green", MatchSorterOptions::default());
+    let results = match_sorter(&items, "green", &MatchSorterOptions::default());
     // "green" -> CaseSensitiveEqual, "Green" -> Equal
     assert_eq!(results[0], &"green", "exact case match should be first");
     assert_eq!(results[1], &"Green", "case-insensitive match second");
@@ -121,7 +127,7 @@ fn case_sensitive_beats_insensitive() {
 #[test]
 fn diacritics_cafe_matches_accented() {
     let items = ["cafe", "caf\u{00e9}", "restaurant"];
-    let results = match_sorter(&items, "cafe", MatchSorterOptions::default());
+    let results = match_sorter(&items, "cafe", &MatchSorterOptions::default());
     assert_eq!(results.len(), 2, "both cafe and cafe should match");
     assert!(results.contains(&&"cafe"));
     assert!(results.contains(&&"caf\u{00e9}"));
@@ -135,7 +141,7 @@ fn diacritics_kept_no_cross_match() {
         keep_diacritics: true,
         ..Default::default()
     };
-    let results = match_sorter(&items, "cafe", opts);
+    let results = match_sorter(&items, "cafe", &opts);
     assert_eq!(results, vec![&"cafe"], "only exact cafe matches");
 }
 
@@ -152,7 +158,7 @@ fn threshold_contains_excludes_fuzzy() {
         threshold: Ranking::Contains,
         ..Default::default()
     };
-    let results = match_sorter(&items, "ap", opts);
+    let results = match_sorter(&items, "ap", &opts);
     // "apple" -> StartsWith (>= Contains), "grape" -> Contains (>= Contains)
     // "banana" -> NoMatch for "ap" substring, possibly fuzzy only -> excluded
     assert_eq!(results, vec![&"apple", &"grape"]);
@@ -166,7 +172,7 @@ fn threshold_case_sensitive_equal_strict() {
         threshold: Ranking::CaseSensitiveEqual,
         ..Default::default()
     };
-    let results = match_sorter(&items, "apple", opts);
+    let results = match_sorter(&items, "apple", &opts);
     assert_eq!(results, vec![&"apple"]);
 }
 
@@ -183,7 +189,7 @@ fn key_based_struct_matching() {
         keys: vec![Key::new(|i: &Item| vec![i.name.clone()])],
         ..Default::default()
     };
-    let results = match_sorter(&items, "ali", opts);
+    let results = match_sorter(&items, "ali", &opts);
     assert!(!results.is_empty());
     assert_eq!(results[0].name, "Alice", "Alice matches via StartsWith");
 }
@@ -196,7 +202,7 @@ fn key_based_from_fn() {
         keys: vec![Key::<Item>::from_fn(|i| i.name.as_str())],
         ..Default::default()
     };
-    let results = match_sorter(&items, "echo", opts);
+    let results = match_sorter(&items, "echo", &opts);
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].name, "Echo");
 }
@@ -218,7 +224,7 @@ fn multi_value_key_best_tag_wins() {
         keys: vec![Key::new(|i: &TaggedItem| i.tags.clone())],
         ..Default::default()
     };
-    let results = match_sorter(&items, "admin", opts);
+    let results = match_sorter(&items, "admin", &opts);
     // Both Alice and Charlie have "admin" tag -> CaseSensitiveEqual
     assert_eq!(results.len(), 2);
     let names: Vec<&str> = results.iter().map(|i| i.name.as_str()).collect();
@@ -239,7 +245,7 @@ fn multi_value_key_from_fn_multi() {
         })],
         ..Default::default()
     };
-    let results = match_sorter(&items, "linux", opts);
+    let results = match_sorter(&items, "linux", &opts);
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].name, "Server");
 }
@@ -260,7 +266,7 @@ fn per_key_max_ranking_clamps_down() {
     // "Alice" queried with "Alice" would normally be CaseSensitiveEqual,
     // but clamped to Contains. Both items need to pass the default threshold
     // (Matches). Only Alice matches.
-    let results = match_sorter(&items, "Alice", opts);
+    let results = match_sorter(&items, "Alice", &opts);
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].name, "Alice");
 }
@@ -275,7 +281,7 @@ fn per_key_min_ranking_promotes() {
     };
     // "playground" queried with "plgnd" -> fuzzy Matches, promoted to Contains.
     // "apple" queried with "plgnd" -> NoMatch (not promoted).
-    let results = match_sorter(&items, "plgnd", opts);
+    let results = match_sorter(&items, "plgnd", &opts);
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].name, "playground");
 }
@@ -289,7 +295,7 @@ fn per_key_min_ranking_does_not_promote_no_match() {
         ..Default::default()
     };
     // "abc" queried with "xyz" -> NoMatch, not promoted.
-    let results = match_sorter(&items, "xyz", opts);
+    let results = match_sorter(&items, "xyz", &opts);
     assert!(results.is_empty());
 }
 
@@ -311,7 +317,7 @@ fn custom_base_sort_preserve_original_order() {
     // Empty query: all items match with the same rank (StartsWith) and
     // same key_index (0). Tiebreaker is base_sort, which preserves
     // original order instead of alphabetical.
-    let results = match_sorter(&items, "", opts);
+    let results = match_sorter(&items, "", &opts);
     assert_eq!(results, vec![&"cherry", &"banana", &"apple"]);
 }
 
@@ -319,7 +325,7 @@ fn custom_base_sort_preserve_original_order() {
 #[test]
 fn default_base_sort_alphabetical() {
     let items = ["cherry", "banana", "apple"];
-    let results = match_sorter(&items, "", MatchSorterOptions::default());
+    let results = match_sorter(&items, "", &MatchSorterOptions::default());
     // All same rank, tiebreak alphabetically: apple, banana, cherry
     assert_eq!(results, vec![&"apple", &"banana", &"cherry"]);
 }
@@ -332,7 +338,7 @@ fn default_base_sort_alphabetical() {
 #[test]
 fn sorter_override_reverse() {
     let items = ["apple", "banana", "grape"];
-    let default_results = match_sorter(&items, "a", MatchSorterOptions::default());
+    let default_results = match_sorter(&items, "a", &MatchSorterOptions::default());
 
     let opts = MatchSorterOptions {
         sorter: Some(Box::new(|mut items: Vec<RankedItem<&str>>| {
@@ -341,7 +347,7 @@ fn sorter_override_reverse() {
         })),
         ..Default::default()
     };
-    let reversed_results = match_sorter(&items, "a", opts);
+    let reversed_results = match_sorter(&items, "a", &opts);
 
     // Reversed results should be the opposite order of the default.
     assert_eq!(reversed_results.len(), default_results.len());
@@ -361,7 +367,7 @@ fn sorter_override_preserve_input_order() {
         })),
         ..Default::default()
     };
-    let results = match_sorter(&items, "", opts);
+    let results = match_sorter(&items, "", &opts);
     // Sorter preserves input order regardless of rank.
     assert_eq!(results, vec![&"grape", &"apple", &"banana"]);
 }
@@ -374,7 +380,7 @@ fn sorter_override_preserve_input_order() {
 #[test]
 fn empty_query_returns_all_sorted() {
     let items = ["banana", "apple", "cherry"];
-    let results = match_sorter(&items, "", MatchSorterOptions::default());
+    let results = match_sorter(&items, "", &MatchSorterOptions::default());
     assert_eq!(results.len(), 3, "all items should be returned");
     assert_eq!(results[0], &"apple");
     assert_eq!(results[1], &"banana");
@@ -385,7 +391,7 @@ fn empty_query_returns_all_sorted() {
 #[test]
 fn empty_query_string_items() {
     let items = vec!["zebra".to_owned(), "mango".to_owned()];
-    let results = match_sorter(&items, "", MatchSorterOptions::default());
+    let results = match_sorter(&items, "", &MatchSorterOptions::default());
     assert_eq!(results.len(), 2);
     assert_eq!(results[0].as_str(), "mango");
     assert_eq!(results[1].as_str(), "zebra");
@@ -400,7 +406,7 @@ fn empty_query_string_items() {
 #[test]
 fn single_char_query_matches_substring() {
     let items = ["apple", "banana", "plum", "grape"];
-    let results = match_sorter(&items, "a", MatchSorterOptions::default());
+    let results = match_sorter(&items, "a", &MatchSorterOptions::default());
     // "apple" -> StartsWith, "banana" -> Contains (a at pos 1),
     // "grape" -> Contains (a at pos 2), "plum" -> NoMatch
     assert!(!results.contains(&&"plum"), "plum has no 'a'");
@@ -413,7 +419,7 @@ fn single_char_query_matches_substring() {
 #[test]
 fn single_char_query_no_match() {
     let items = ["hello", "world"];
-    let results = match_sorter(&items, "z", MatchSorterOptions::default());
+    let results = match_sorter(&items, "z", &MatchSorterOptions::default());
     assert!(results.is_empty());
 }
 
@@ -429,7 +435,7 @@ fn acronym_matching_nwa() {
         "National Weather Association",
         "Something Else",
     ];
-    let results = match_sorter(&items, "nwa", MatchSorterOptions::default());
+    let results = match_sorter(&items, "nwa", &MatchSorterOptions::default());
     assert!(
         results.contains(&&"North-West Airlines"),
         "North-West Airlines should match via Acronym"
@@ -448,11 +454,40 @@ fn acronym_matching_nwa() {
 #[test]
 fn acronym_matching_asap() {
     let items = ["as soon as possible", "something random"];
-    let results = match_sorter(&items, "asap", MatchSorterOptions::default());
+    let results = match_sorter(&items, "asap", &MatchSorterOptions::default());
     assert_eq!(results.len(), 1);
     assert_eq!(results[0], &"as soon as possible");
 }
 
+/// Prefix and exact acronym modes narrow which queries reach the Acronym tier.
+#[test]
+fn acronym_match_modes() {
+    let items = ["North-West Airlines"];
+    let acronym_hit = |query: &str, prefix: bool, exact: bool| {
+        let opts = MatchSorterOptions {
+            threshold: Ranking::Acronym,
+            acronym_prefix_match: prefix,
+            acronym_exact_match: exact,
+            ..Default::default()
+        };
+        !match_sorter(&items, query, &opts).is_empty()
+    };
+
+    // Contains mode (default): any substring of "nwa".
+    assert!(acronym_hit("nw", false, false));
+    assert!(acronym_hit("wa", false, false));
+    assert!(acronym_hit("nwa", false, false));
+
+    // Prefix mode: only leading substrings of "nwa".
+    assert!(acronym_hit("nw", true, false));
+    assert!(!acronym_hit("wa", true, false));
+    assert!(acronym_hit("nwa", true, false));
+
+    // Exact mode: only the full acronym.
+    assert!(!acronym_hit("nw", false, true));
+    assert!(acronym_hit("nwa", false, true));
+}
+
 // ---------------------------------------------------------------------------
 // 13. Word boundary detection
 // ---------------------------------------------------------------------------
@@ -462,7 +497,7 @@ fn acronym_matching_asap() {
 #[test]
 fn word_boundary_fran_matches_san_francisco() {
     let items = ["San Francisco", "New York", "Frankfurt"];
-    let results = match_sorter(&items, "fran", MatchSorterOptions::default());
+    let results = match_sorter(&items, "fran", &MatchSorterOptions::default());
     // "San Francisco" -> WordStartsWith, "Frankfurt" -> StartsWith
     assert!(results.contains(&&"San Francisco"));
     assert!(results.contains(&&"Frankfurt"));
@@ -476,7 +511,7 @@ fn word_boundary_fran_matches_san_francisco() {
 #[test]
 fn word_boundary_hyphen_not_boundary() {
     let items = ["North-West", "South West"];
-    let results = match_sorter(&items, "west", MatchSorterOptions::default());
+    let results = match_sorter(&items, "west", &MatchSorterOptions::default());
     // "North-West" -> Contains (hyphen not a word boundary)
     // "South West" -> WordStartsWith (space is a word boundary)
     assert_eq!(
@@ -489,6 +524,40 @@ fn word_boundary_hyphen_not_boundary() {
     );
 }
 
+/// With `hyphen_is_word_boundary`, "west" matches "North-West" as
+/// WordStartsWith and outranks a plain Contains match.
+#[test]
+fn word_boundary_hyphen_enabled() {
+    let items = ["midwest", "North-West"];
+    let opts = MatchSorterOptions {
+        threshold: Ranking::WordStartsWith,
+        hyphen_is_word_boundary: true,
+        ..Default::default()
+    };
+    let results = match_sorter(&items, "west", &opts);
+    assert_eq!(results, vec![&"North-West"]);
+}
+
+/// With `unicode_word_boundaries`, a no-break space starts a word, lifting
+/// "Francisco" from `Contains` to `WordStartsWith`.
+#[test]
+fn word_boundary_unicode_whitespace() {
+    let items = ["Sanfrancisco", "San\u{00a0}Francisco"];
+    let opts = MatchSorterOptions {
+        threshold: Ranking::WordStartsWith,
+        ..Default::default()
+    };
+    assert!(match_sorter(&items, "fran", &opts).is_empty());
+
+    let opts = MatchSorterOptions {
+        unicode_word_boundaries: true,
+        threshold: Ranking::WordStartsWith,
+        ..Default::default()
+    };
+    let results = match_sorter(&items, "fran", &opts);
+    assert_eq!(results, vec![&"San\u{00a0}Francisco"]);
+}
+
 // ---------------------------------------------------------------------------
 // 14. Edge cases
 // ---------------------------------------------------------------------------
@@ -497,7 +566,7 @@ fn word_boundary_hyphen_not_boundary() {
 #[test]
 fn edge_empty_items() {
     let items: [&str; 0] = [];
-    let results = match_sorter(&items, "test", MatchSorterOptions::default());
+    let results = match_sorter(&items, "test", &MatchSorterOptions::default());
     assert!(results.is_empty());
 }
 
@@ -506,7 +575,7 @@ fn edge_empty_items() {
 fn edge_very_long_strings() {
     let long_string = "a".repeat(10_000);
     let items = [long_string.as_str()];
-    let results = match_sorter(&items, "a", MatchSorterOptions::default());
+    let results = match_sorter(&items, "a", &MatchSorterOptions::default());
     assert_eq!(results.len(), 1);
 }
 
@@ -515,7 +584,7 @@ fn edge_very_long_strings() {
 fn edge_long_query_short_items() {
     let items = ["hi", "ok"];
     let long_query = "a".repeat(1_000);
-    let results = match_sorter(&items, &long_query, MatchSorterOptions::default());
+    let results = match_sorter(&items, &long_query, &MatchSorterOptions::default());
     assert!(results.is_empty());
 }
 
@@ -523,7 +592,7 @@ fn edge_long_query_short_items() {
 #[test]
 fn edge_empty_string_item() {
     let items = ["", "nonempty"];
-    let results = match_sorter(&items, "", MatchSorterOptions::default());
+    let results = match_sorter(&items, "", &MatchSorterOptions::default());
     // Both match: "" -> CaseSensitiveEqual, "nonempty" -> StartsWith
     assert_eq!(results.len(), 2);
     assert_eq!(results[0], &"", "empty string is CaseSensitiveEqual");
@@ -534,7 +603,7 @@ fn edge_empty_string_item() {
 #[test]
 fn edge_unicode_items() {
     let items = ["\u{4e16}\u{754c}", "hello"];
-    let results = match_sorter(&items, "\u{4e16}", MatchSorterOptions::default());
+    let results = match_sorter(&items, "\u{4e16}", &MatchSorterOptions::default());
     // \u{4e16} is a single character, found at position 0 of \u{4e16}\u{754c}
     // -> StartsWith
     assert_eq!(results.len(), 1);
@@ -558,12 +627,137 @@ fn per_key_threshold_override() {
         threshold: Ranking::Matches(1.0), // global is permissive
         ..Default::default()
     };
-    let results = match_sorter(&items, "apple", opts);
+    let results = match_sorter(&items, "apple", &opts);
     // Per-key threshold is CaseSensitiveEqual, so only exact match passes.
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].name, "apple");
 }
 
+/// `key_thresholds` applies per key index, so a weak match on one key is
+/// kept while the same rank on another key is filtered out.
+#[test]
+fn key_thresholds_map_applies_per_key_index() {
+    let items = vec![
+        TaggedItem::new("Red Apple", &["fruit"]),
+        TaggedItem::new("Pineapple", &["tropical"]),
+        TaggedItem::new("Banana", &["snapple"]),
+    ];
+    let opts = MatchSorterOptions {
+        keys: vec![
+            Key::new(|i: &TaggedItem| vec![i.name.clone()]),
+            Key::new(|i: &TaggedItem| i.tags.clone()),
+        ],
+        threshold: Ranking::WordStartsWith,
+        ..Default::default()
+    }
+    .key_threshold(1, Ranking::Contains);
+    let results = match_sorter(&items, "apple", &opts);
+    let names: Vec<&str> = results.iter().map(|i| i.name.as_str()).collect();
+    // "Pineapple" only contains "apple" in its name (key 0), which needs
+    // WordStartsWith; "snapple" contains it in a tag (key 1), which needs
+    // only Contains.
+    assert_eq!(names, vec!["Red Apple", "Banana"]);
+}
+
+/// A key's score modifier can lift a `Contains` match to `StartsWith`,
+/// changing the order.
+#[test]
+fn key_score_modifier_boost_changes_order() {
+    let items = vec![Item::new("Nutmeg"), Item::new("Coconut")];
+    let key = || Key::new(|i: &Item| vec![i.name.clone()]);
+    let names = |opts| -> Vec<String> {
+        match_sorter(&items, "nut", &opts)
+            .iter()
+            .map(|i| i.name.clone())
+            .collect()
+    };
+
+    let plain = MatchSorterOptions {
+        keys: vec![key()],
+        ..Default::default()
+    };
+    assert_eq!(names(plain), vec!["Nutmeg", "Coconut"]);
+
+    let boosted = MatchSorterOptions {
+        keys: vec![key().with_score_modifier(|_, rank| {
+            if rank == Ranking::Contains {
+                Ranking::StartsWith
+            } else {
+                rank
+            }
+        })],
+        ..Default::default()
+    };
+    // Tied at StartsWith, the alphabetical tiebreaker now decides.
+    assert_eq!(names(boosted), vec!["Coconut", "Nutmeg"]);
+}
+
+/// Returning `NoMatch` from a score modifier drops the item, in keys mode
+/// and, via `item_score_modifier`, in no-keys mode.
+#[test]
+fn score_modifier_no_match_excludes_item() {
+    let items = vec![Item::new("Nutmeg"), Item::new("Coconut")];
+    let opts = MatchSorterOptions {
+        keys: vec![
+            Key::new(|i: &Item| vec![i.name.clone()]).with_score_modifier(|item: &Item, rank| {
+                if item.name == "Nutmeg" {
+                    Ranking::NoMatch
+                } else {
+                    rank
+                }
+            }),
+        ],
+        ..Default::default()
+    };
+    let results = match_sorter(&items, "nut", &opts);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].name, "Coconut");
+
+    let words = ["Nutmeg", "Coconut"];
+    let opts = MatchSorterOptions {
+        item_score_modifier: Some(Box::new(|item: &&str, rank| {
+            if *item == "Coconut" {
+                Ranking::NoMatch
+            } else {
+                rank
+            }
+        })),
+        ..Default::default()
+    };
+    assert_eq!(match_sorter(&words, "nut", &opts), vec![&"Nutmeg"]);
+}
+
+/// A key's own score modifier takes precedence over `item_score_modifier`,
+/// which still applies to keys without one.
+#[test]
+fn key_score_modifier_overrides_item_score_modifier() {
+    let item = TaggedItem::new("Coconut", &["walnut"]);
+    let opts = MatchSorterOptions {
+        keys: vec![
+            Key::new(|i: &TaggedItem| vec![i.name.clone()]).with_score_modifier(|_, rank| rank),
+            Key::new(|i: &TaggedItem| i.tags.clone()),
+        ],
+        item_score_modifier: Some(Box::new(|_, _| Ranking::NoMatch)),
+        ..Default::default()
+    };
+    let info = get_highest_ranking(&item, &opts.keys, "nut", &opts);
+    assert_eq!(info.rank, Ranking::Contains);
+    assert_eq!(info.key_index, 0);
+
+    let opts = MatchSorterOptions {
+        keys: vec![
+            Key::new(|i: &TaggedItem| vec![i.name.clone()])
+                .with_score_modifier(|_, _| Ranking::NoMatch),
+            Key::new(|i: &TaggedItem| i.tags.clone()),
+        ],
+        item_score_modifier: Some(Box::new(|_, rank| rank)),
+        ..Default::default()
+    };
+    let info = get_highest_ranking(&item, &opts.keys, "nut", &opts);
+    assert_eq!(info.rank, Ranking::Contains);
+    assert_eq!(info.key_index, 1);
+}
+
 // ---------------------------------------------------------------------------
 // 15. Threshold tier coverage (JS parity tests 22-27)
 // ---------------------------------------------------------------------------
@@ -578,7 +772,7 @@ fn threshold_no_match_returns_all() {
         threshold: Ranking::NoMatch,
         ..Default::default()
     };
-    let results = match_sorter(&items, "ap", opts);
+    let results = match_sorter(&items, "ap", &opts);
     // Every item passes: NoMatch is the lowest possible tier, so rank >= NoMatch
     // is always true. Items that actually match sort first; non-matching items
     // sort after them.
@@ -601,7 +795,7 @@ fn threshold_equal_only_exact() {
         threshold: Ranking::Equal,
         ..Default::default()
     };
-    let results = match_sorter(&items, "app", opts);
+    let results = match_sorter(&items, "app", &opts);
     // Only "app" matches at Equal or above (CaseSensitiveEqual in this case).
     // "apple" and "apply" are StartsWith which is below Equal.
     assert_eq!(results, vec![&"app"]);
@@ -618,7 +812,7 @@ fn threshold_word_starts_with() {
         threshold: Ranking::WordStartsWith,
         ..Default::default()
     };
-    let results = match_sorter(&items, "app", opts);
+    let results = match_sorter(&items, "app", &opts);
     // "app" -> CaseSensitiveEqual (passes)
     // "apple" -> StartsWith (passes)
     // "apply" -> StartsWith (passes)
@@ -653,7 +847,7 @@ fn threshold_word_starts_with_after_suffix() {
         threshold: Ranking::WordStartsWith,
         ..Default::default()
     };
-    let results = match_sorter(&items, "app", opts);
+    let results = match_sorter(&items, "app", &opts);
     // "snappy apple" -> WordStartsWith ("apple" starts at a word boundary)
     assert!(
         results.contains(&&"snappy apple"),
@@ -673,7 +867,7 @@ fn threshold_acronym() {
         threshold: Ranking::Acronym,
         ..Default::default()
     };
-    let results = match_sorter(&items, "ap", opts);
+    let results = match_sorter(&items, "ap", &opts);
     // "apple" -> StartsWith (passes)
     // "atop" -> only fuzzy match for "ap" (a...p) -> Matches, excluded
     // "alpaca" -> only fuzzy match -> Matches, excluded
@@ -696,7 +890,7 @@ fn cyrillic_case_insensitive() {
         "\u{041b}\u{0435}\u{0434}",
     ];
     // Search with lowercase Cyrillic "l" (\u{043B})
-    let results = match_sorter(&items, "\u{043b}", MatchSorterOptions::default());
+    let results = match_sorter(&items, "\u{043b}", &MatchSorterOptions::default());
     // "\u{041b}\u{0435}\u{0434}" (Led) starts with capital L; case-insensitive
     // matching should find the lowercase query in the lowercased candidate.
     assert_eq!(results.len(), 1);
@@ -717,7 +911,7 @@ fn fuzzy_sub_score_ordering() {
         "Bosnia and Herzegovina",
         "Indonesia",
     ];
-    let results = match_sorter(&items, "Ina", MatchSorterOptions::default());
+    let results = match_sorter(&items, "Ina", &MatchSorterOptions::default());
     // "India" -> Contains ("Ina" is NOT a substring of "India" case-sensitively,
     // but "ina" is a substring of "india" -> Contains)
     // "Indonesia" -> Contains ("ina" is a substring of "indonesia")
@@ -790,7 +984,7 @@ fn stable_sort_preserves_insertion_order() {
         keys: vec![Key::new(|i: &CountedItem| vec![i.country.clone()])],
         ..Default::default()
     };
-    let results = match_sorter(&items, "Italy", opts);
+    let results = match_sorter(&items, "Italy", &opts);
     assert_eq!(results.len(), 3);
     // Verify original insertion order is preserved.
     assert_eq!(results[0].counter, 1);
@@ -798,6 +992,100 @@ fn stable_sort_preserves_insertion_order() {
     assert_eq!(results[2].counter, 3);
 }
 
+/// Items whose `(rank, key_index, ranked_value)` tuples tie, in groups of
+/// three, interleaved with weaker matches.
+fn stability_items() -> Vec<TaggedItem> {
+    (0..30)
+        .map(|i| {
+            let name = ["Italy", "Italy by train", "Sicily, Italy"][i % 3];
+            TaggedItem::new(name, &[&i.to_string()])
+        })
+        .collect()
+}
+
+fn stability_options() -> MatchSorterOptions<TaggedItem> {
+    MatchSorterOptions {
+        keys: vec![Key::new(|i: &TaggedItem| vec![i.name.clone()])],
+        ..Default::default()
+    }
+}
+
+/// The insertion position of each result, recovered from its tag.
+fn positions(results: &[&TaggedItem]) -> Vec<usize> {
+    results.iter().map(|i| i.tags[0].parse().unwrap()).collect()
+}
+
+/// Positions grouped by name must ascend within every group.
+fn assert_ties_in_input_order(results: &[&TaggedItem]) {
+    for run in results.chunk_by(|a, b| a.name == b.name) {
+        let run = positions(run);
+        assert!(run.is_sorted(), "tied items reordered: {run:?}");
+    }
+}
+
+#[test]
+fn stable_sort_default_path() {
+    let items = stability_items();
+    let results = match_sorter(&items, "italy", &stability_options());
+    assert_eq!(results.len(), 30);
+    assert_ties_in_input_order(&results);
+    assert_eq!(positions(&results[..3]), vec![0, 3, 6]);
+}
+
+#[test]
+fn stable_sort_custom_base_sort() {
+    let items = stability_items();
+    // A tiebreaker that never breaks ties leaves every tie to the stable sort.
+    let opts = MatchSorterOptions {
+        base_sort: Some(Box::new(|_, _| std::cmp::Ordering::Equal)),
+        ..stability_options()
+    };
+    let results = match_sorter(&items, "ital", &opts);
+    assert_ties_in_input_order(&results);
+    // Reversing the names orders the groups differently but keeps ties.
+    let opts = MatchSorterOptions {
+        base_sort: Some(Box::new(|a, b| b.ranked_value.cmp(&a.ranked_value))),
+        ..stability_options()
+    };
+    let results = match_sorter(&items, "ital", &opts);
+    assert_ties_in_input_order(&results);
+}
+
+#[test]
+fn stable_sort_custom_sorter_receives_input_order() {
+    let items = stability_items();
+    let opts = MatchSorterOptions {
+        sorter: Some(Box::new(|mut ranked| {
+            ranked.sort_by(|a, b| b.rank.partial_cmp(&a.rank).unwrap());
+            ranked
+        })),
+        ..stability_options()
+    };
+    let results = match_sorter(&items, "italy", &opts);
+    assert_ties_in_input_order(&results);
+}
+
+/// The guarantee covers the default sort only: a custom `sorter` decides
+/// the order of ties itself.
+#[test]
+fn custom_sorter_may_break_stability() {
+    let items = stability_items();
+    let opts = MatchSorterOptions {
+        sorter: Some(Box::new(|mut ranked| {
+            ranked.sort_by(|a, b| {
+                b.rank
+                    .partial_cmp(&a.rank)
+                    .unwrap()
+                    .then(b.index.cmp(&a.index))
+            });
+            ranked
+        })),
+        ..stability_options()
+    };
+    let results = match_sorter(&items, "italy", &opts);
+    assert_eq!(positions(&results[..3]), vec![27, 24, 21]);
+}
+
 // ---------------------------------------------------------------------------
 // 19. Per-key threshold more permissive than global (JS test 33)
 // ---------------------------------------------------------------------------
@@ -839,7 +1127,7 @@ fn per_key_threshold_more_permissive_than_global() {
         threshold: Ranking::StartsWith, // global threshold is strict
         ..Default::default()
     };
-    let results = match_sorter(&items, "ed", opts);
+    let results = match_sorter(&items, "ed", &opts);
     // "Fred" + name key: "ed" in "fred" -> Contains, but global threshold
     //   is StartsWith so it fails for the name key (no per-key threshold set).
     //   "Fred" + color key: "ed" not in "orange" at Contains level -> no match.
@@ -875,7 +1163,7 @@ fn diacritics_alphabetical_tiebreaking() {
         threshold: Ranking::NoMatch,
         ..Default::default()
     };
-    let results = match_sorter(&items, "z", opts);
+    let results = match_sorter(&items, "z", &opts);
     assert_eq!(
         results.len(),
         items.len(),
@@ -898,3 +1186,1552 @@ fn diacritics_alphabetical_tiebreaking() {
     // "azure" (Contains) comes last.
     assert_eq!(results[3], &"azure");
 }
+
+// ---------------------------------------------------------------------------
+// Phonetic keys (feature = "phonetic")
+// ---------------------------------------------------------------------------
+
+/// Searching by Soundex code finds sound-alike names via a phonetic key.
+#[cfg(feature = "phonetic")]
+#[test]
+fn phonetic_key_finds_sound_alike_names() {
+    use matchsorter::phonetic::soundex;
+
+    let items = vec![Item::new("Smith"), Item::new("Smythe"), Item::new("Jones")];
+    let opts = MatchSorterOptions {
+        keys: vec![Key::with_soundex(|i: &Item| i.name.as_str())],
+        ..Default::default()
+    };
+    let results = match_sorter(&items, &soundex("smyth"), &opts);
+    let names: Vec<&str> = results.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, vec!["Smith", "Smythe"]);
+}
+
+/// The original spelling keeps its tier, so a literal query still ranks
+/// the exact name above items that only match through their Soundex code.
+#[cfg(feature = "phonetic")]
+#[test]
+fn phonetic_key_original_value_outranks_code() {
+    let items = vec![Item::new("Smythe"), Item::new("Smith")];
+    let opts = MatchSorterOptions {
+        keys: vec![Key::with_soundex(|i: &Item| i.name.as_str())],
+        ..Default::default()
+    };
+    let results = match_sorter(&items, "Smith", &opts);
+    let names: Vec<&str> = results.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, vec!["Smith"]);
+}
+
+// ---------------------------------------------------------------------------
+// Stemming
+// ---------------------------------------------------------------------------
+
+/// Toy stemmer shared by the stemming tests: strips "ning" or "ing".
+fn toy_stem(word: &str) -> String {
+    word.strip_suffix("ning")
+        .or_else(|| word.strip_suffix("ing"))
+        .unwrap_or(word)
+        .to_owned()
+}
+
+/// An inflected query meets its stem once both sides are stemmed, and the
+/// stemmed exact match keeps the top tier.
+#[test]
+fn stemmed_query_matches_stem_candidate() {
+    let items = vec![Item::new("rerun"), Item::new("run"), Item::new("walk")];
+
+    let opts = MatchSorterOptions {
+        keys: vec![Key::from_fn(|i: &Item| i.name.as_str())],
+        threshold: Ranking::Contains,
+        ..Default::default()
+    };
+    assert!(match_sorter(&items, "running", &opts).is_empty());
+
+    let opts = MatchSorterOptions {
+        keys: vec![Key::from_fn(|i: &Item| i.name.as_str()).with_stemmer(toy_stem)],
+        threshold: Ranking::Contains,
+        ..Default::default()
+    }
+    .with_query_stemmer(toy_stem);
+    let results = match_sorter(&items, "Running", &opts);
+    let names: Vec<&str> = results.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, vec!["run", "rerun"]);
+}
+
+/// A panic inside the stem function is not swallowed.
+#[test]
+#[should_panic(expected = "stemmer failed")]
+fn stemmer_panic_propagates() {
+    let items = vec![Item::new("run")];
+    let opts = MatchSorterOptions {
+        keys: vec![
+            Key::from_fn(|i: &Item| i.name.as_str())
+                .with_stemmer(|_: &str| -> String { panic!("stemmer failed") }),
+        ],
+        ..Default::default()
+    };
+    let _ = match_sorter(&items, "run", &opts);
+}
+
+// ---------------------------------------------------------------------------
+// Custom closeness scorer
+// ---------------------------------------------------------------------------
+
+/// An exponential sub-score decays faster than the default, so a fuzzy
+/// threshold that admits a wide match by default rejects it.
+#[test]
+fn closeness_scorer_exponential_changes_results() {
+    // "ad" in "abcd": spread 3 -> default 1.333, exponential 1.125.
+    let items = ["abcd", "adz"];
+    let opts = MatchSorterOptions {
+        threshold: Ranking::Matches(1.2),
+        ..Default::default()
+    };
+    assert_eq!(match_sorter(&items, "ad", &opts), vec![&"adz", &"abcd"]);
+
+    let opts = MatchSorterOptions {
+        threshold: Ranking::Matches(1.2),
+        closeness_scorer: Some(Box::new(|first, last, _, _| {
+            1.0 + 2.0_f64.powi(-((last - first) as i32))
+        })),
+        ..Default::default()
+    };
+    assert_eq!(match_sorter(&items, "ad", &opts), vec![&"adz"]);
+}
+
+/// A length-aware scorer can reorder fuzzy matches that the default scores
+/// identically.
+#[test]
+fn closeness_scorer_linear_decay_reorders() {
+    // Both have spread 2 for "ac"; the default ties them and falls back to
+    // alphabetical order.
+    let items = ["abcxxxxxxx", "abcxx"];
+    let default_order = match_sorter(&items, "ac", &MatchSorterOptions::default());
+    assert_eq!(default_order, vec![&"abcxx", &"abcxxxxxxx"]);
+
+    // Linear decay relative to candidate length favors the longer item.
+    let opts = MatchSorterOptions {
+        closeness_scorer: Some(Box::new(|first, last, len, _| {
+            1.0 + (1.0 - (last - first) as f64 / len as f64)
+        })),
+        ..Default::default()
+    };
+    let results = match_sorter(&items, "ac", &opts);
+    assert_eq!(results, vec![&"abcxxxxxxx", &"abcxx"]);
+}
+
+// ---------------------------------------------------------------------------
+// Query-dependent threshold
+// ---------------------------------------------------------------------------
+
+/// The dynamic threshold is computed from the query, and a per-key
+/// threshold still overrides it.
+#[test]
+fn threshold_fn_with_per_key_threshold() {
+    let items = vec![Item::new("apple"), Item::new("grape")];
+    let opts = || MatchSorterOptions {
+        keys: vec![Key::from_fn(|i: &Item| i.name.as_str()).threshold(Ranking::Matches(1.0))],
+        ..MatchSorterOptions::with_length_adaptive_threshold(
+            Ranking::StartsWith,
+            Ranking::Matches(1.0),
+            3,
+        )
+    };
+
+    // Without the per-key override, the short query "ae" would be held to
+    // StartsWith and match nothing.
+    let strict = MatchSorterOptions {
+        keys: vec![Key::from_fn(|i: &Item| i.name.as_str())],
+        ..MatchSorterOptions::with_length_adaptive_threshold(
+            Ranking::StartsWith,
+            Ranking::Matches(1.0),
+            3,
+        )
+    };
+    assert!(match_sorter(&items, "ae", &strict).is_empty());
+
+    let results = match_sorter(&items, "ae", &opts());
+    assert_eq!(results.len(), 2);
+}
+
+/// When both are set, `threshold_fn` wins over the static `threshold`.
+#[test]
+fn threshold_fn_wins_over_threshold() {
+    let items = ["apple", "grape", "pineapple"];
+    let opts = MatchSorterOptions {
+        threshold: Ranking::CaseSensitiveEqual,
+        threshold_fn: Some(Box::new(|_| Ranking::Contains)),
+        ..Default::default()
+    };
+    let results = match_sorter(&items, "app", &opts);
+    assert_eq!(results, vec![&"apple", &"pineapple"]);
+}
+
+// ---------------------------------------------------------------------------
+// Conditional keys
+// ---------------------------------------------------------------------------
+
+/// A `skip_if` predicate runs once per item per key, and skipped items can
+/// still match through other keys.
+#[test]
+fn skip_if_predicate_runs_once_per_item() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let items = vec![
+        TaggedItem::new("Bob", &["admin"]),
+        TaggedItem::new("Alice", &["bob's friend"]),
+        TaggedItem::new("Carol", &["staff"]),
+    ];
+    let calls = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&calls);
+    let opts = MatchSorterOptions {
+        keys: vec![
+            Key::new(|i: &TaggedItem| i.tags.clone()).skip_if(move |i| {
+                counter.set(counter.get() + 1);
+                i.name == "Alice"
+            }),
+            Key::from_fn(|i: &TaggedItem| i.name.as_str()),
+        ],
+        ..Default::default()
+    };
+    let results = match_sorter(&items, "bob", &opts);
+    assert_eq!(calls.get(), items.len());
+    let names: Vec<&str> = results.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, vec!["Bob"]);
+}
+
+/// The camelCase word splitter ranks a match at an identifier's inner word
+/// above a mid-word match.
+#[test]
+fn camel_case_word_splitter_ranks_inner_words() {
+    let items = ["parseHtml", "reparse", "shtml"];
+    let opts = MatchSorterOptions {
+        word_splitter: Some(Box::new(matchsorter::camel_case_splitter)),
+        threshold: Ranking::Contains,
+        ..Default::default()
+    };
+    let results = match_sorter(&items, "html", &opts);
+    assert_eq!(results, vec![&"parseHtml", &"shtml"]);
+    let plain = MatchSorterOptions {
+        threshold: Ranking::WordStartsWith,
+        ..Default::default()
+    };
+    assert!(match_sorter(&items, "html", &plain).is_empty());
+}
+
+/// Raising the threshold one fixed tier at a time only ever drops matches.
+#[test]
+fn threshold_tiers_give_nested_results() {
+    let items = [
+        "apple",
+        "Apple",
+        "applesauce",
+        "green apple",
+        "pineapple",
+        "a pale pear",
+        "grape",
+    ];
+    let mut previous: Option<Vec<&&str>> = None;
+    for &tier in Ranking::fixed_tiers().iter().rev() {
+        let opts = MatchSorterOptions {
+            threshold: tier,
+            ..Default::default()
+        };
+        let results = match_sorter(&items, "apple", &opts);
+        if let Some(previous) = &previous {
+            assert!(
+                results.iter().all(|item| previous.contains(item)),
+                "{tier:?} matched items not matched at the tier below"
+            );
+        }
+        previous = Some(results);
+    }
+    assert_eq!(previous, Some(vec![&"apple"]));
+}
+
+// ---------------------------------------------------------------------------
+// Early stopping
+// ---------------------------------------------------------------------------
+
+fn early_stop_items() -> Vec<&'static str> {
+    vec![
+        "grape",
+        "applesauce",
+        "apple",
+        "pineapple",
+        "Apple",
+        "apple",
+        "apricot pie",
+    ]
+}
+
+fn stop_after(min_tier: Ranking, min_count: usize) -> MatchSorterOptions<&'static str> {
+    MatchSorterOptions {
+        early_stop_criteria: Some(EarlyStopCriteria {
+            min_tier,
+            min_count,
+        }),
+        ..Default::default()
+    }
+}
+
+/// With a count of one, ranking stops at the first item reaching the tier;
+/// weaker matches before it are kept.
+#[test]
+fn early_stop_at_count_one_keeps_first_hit() {
+    let items = early_stop_items();
+    let results = match_sorter(&items, "apple", &stop_after(Ranking::Equal, 1));
+    assert_eq!(results, vec![&"apple", &"applesauce"]);
+    assert!(std::ptr::eq(results[0], &items[2]));
+
+    let results = match_sorter(&items, "apple", &MatchSorterOptions::stop_at_first_exact());
+    assert_eq!(results, vec![&"apple", &"applesauce"]);
+}
+
+/// A stopped search returns exactly what a full search over the items up
+/// to the stopping point returns.
+#[test]
+fn early_stop_matches_search_of_input_prefix() {
+    let items = early_stop_items();
+    let cases = [
+        (Ranking::Equal, 2, 5),
+        (Ranking::StartsWith, 2, 3),
+        (Ranking::Contains, 3, 4),
+        (Ranking::CaseSensitiveEqual, 10, items.len()),
+    ];
+    for (min_tier, min_count, ranked) in cases {
+        let stopped = match_sorter(&items, "apple", &stop_after(min_tier, min_count));
+        let prefix = match_sorter(&items[..ranked], "apple", &MatchSorterOptions::default());
+        assert_eq!(stopped, prefix, "{min_tier:?} x {min_count}");
+    }
+    assert!(match_sorter(&items, "apple", &stop_after(Ranking::NoMatch, 0)).is_empty());
+}
+
+/// Items after the stopping point are never passed to a key.
+#[test]
+fn early_stop_skips_remaining_items() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let items = early_stop_items();
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let log = Rc::clone(&seen);
+    let opts = MatchSorterOptions {
+        keys: vec![Key::new(move |s: &&str| {
+            log.borrow_mut().push(*s);
+            vec![(*s).to_owned()]
+        })],
+        ..stop_after(Ranking::Equal, 1)
+    };
+    let results = match_sorter(&items, "apple", &opts);
+    assert_eq!(*seen.borrow(), ["grape", "applesauce", "apple"]);
+    assert!(!results.contains(&&"pineapple"));
+    assert!(!results.contains(&&"apricot pie"));
+}
+
+// ---------------------------------------------------------------------------
+// C strings
+// ---------------------------------------------------------------------------
+
+/// A `CStr` borrowed from a raw pointer, as received over FFI, ranks like
+/// its UTF-8 content.
+#[test]
+fn c_str_from_raw_pointer() {
+    use std::ffi::{CStr, CString};
+
+    let owned = CString::new("libmatch.so").unwrap();
+    let ptr = owned.as_ptr();
+    // SAFETY: `ptr` points to `owned`'s nul-terminated buffer, which
+    // outlives `c_str`.
+    let c_str = unsafe { CStr::from_ptr(ptr) };
+    assert_eq!(c_str.as_match_str(), "libmatch.so");
+    assert_eq!(
+        matchsorter::rank_item(c_str, "match", false),
+        Ranking::Contains
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Match annotations
+// ---------------------------------------------------------------------------
+
+/// Each tier reports its rank, the matched value and the ranges to highlight.
+#[test]
+fn annotations_per_tier() {
+    let items = [
+        "playground",
+        "North-West Airlines",
+        "evergreen",
+        "evergreen Green",
+        "Greenland",
+        "green",
+        "Green",
+        "banana",
+    ];
+    let annotated = match_sorter_with_annotations(&items, "Green", &MatchSorterOptions::default());
+    // The value, its rank and the highlighted text.
+    let summary: Vec<(&str, Ranking, Vec<&str>)> = annotated
+        .iter()
+        .map(|a| {
+            let highlighted = a
+                .highlight_ranges
+                .iter()
+                .map(|r| &a.matched_value[r.clone()])
+                .collect();
+            (a.matched_value.as_str(), a.rank, highlighted)
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("Green", Ranking::CaseSensitiveEqual, vec!["Green"]),
+            ("green", Ranking::Equal, vec!["green"]),
+            ("Greenland", Ranking::StartsWith, vec!["Green"]),
+            ("evergreen Green", Ranking::WordStartsWith, vec!["Green"]),
+            ("evergreen", Ranking::Contains, vec!["green"]),
+        ]
+    );
+    assert_eq!(annotated[3].highlight_ranges, vec![10..15]);
+
+    let acronym = match_sorter_with_annotations(&items, "nwa", &MatchSorterOptions::default());
+    assert_eq!(acronym.len(), 1);
+    assert_eq!(acronym[0].rank, Ranking::Acronym);
+    assert_eq!(acronym[0].highlight_ranges, vec![0..1, 6..7, 11..12]);
+
+    let fuzzy = match_sorter_with_annotations(&items, "plgnd", &MatchSorterOptions::default());
+    assert_eq!(fuzzy.len(), 1);
+    assert!(matches!(fuzzy[0].rank, Ranking::Matches(_)));
+    assert_eq!(fuzzy[0].matched_value, "playground");
+    assert_eq!(fuzzy[0].highlight_ranges, vec![0..2, 4..5, 8..10]);
+    assert!(annotated.iter().all(|a| a.matched_field.is_none()));
+}
+
+/// Annotations come back in `match_sorter` order and leave out every item
+/// that `match_sorter` drops.
+#[test]
+fn annotations_match_sorter_order_and_exclusions() {
+    let items = [
+        "pineapple",
+        "banana",
+        "grape",
+        "apple",
+        "apricot",
+        "cherry",
+        "a p",
+    ];
+    for query in ["ap", "a", "apl", "zzz", ""] {
+        let expected = match_sorter(&items, query, &MatchSorterOptions::default());
+        let annotated =
+            match_sorter_with_annotations(&items, query, &MatchSorterOptions::default());
+        let actual: Vec<&&str> = annotated.iter().map(|a| a.item).collect();
+        assert_eq!(actual, expected, "query {query:?}");
+    }
+    let annotated = match_sorter_with_annotations(&items, "ap", &MatchSorterOptions::default());
+    assert!(
+        annotated
+            .iter()
+            .all(|a| *a.item != "banana" && *a.item != "cherry")
+    );
+}
+
+/// `matched_field` is the alias of the key that won.
+#[test]
+fn annotations_report_key_alias() {
+    let items = [
+        TaggedItem::new("Apple", &["fruit"]),
+        TaggedItem::new("Carrot", &["vegetable", "root"]),
+    ];
+    let opts = || MatchSorterOptions {
+        keys: vec![
+            Key::new(|i: &TaggedItem| vec![i.name.clone()]).alias("name"),
+            Key::new(|i: &TaggedItem| i.tags.clone()).alias("tags"),
+        ],
+        ..Default::default()
+    };
+    // "root" (StartsWith) beats "Carrot" (Contains).
+    let annotated = match_sorter_with_annotations(&items, "ro", &opts());
+    assert_eq!(annotated.len(), 1);
+    assert_eq!(annotated[0].item.name, "Carrot");
+    assert_eq!(annotated[0].matched_field.as_deref(), Some("tags"));
+    assert_eq!(annotated[0].matched_value, "root");
+    assert_eq!(annotated[0].highlight_ranges, vec![0..2]);
+
+    let annotated = match_sorter_with_annotations(&items, "pp", &opts());
+    assert_eq!(annotated.len(), 1);
+    assert_eq!(annotated[0].matched_field.as_deref(), Some("name"));
+    assert_eq!(annotated[0].matched_value, "Apple");
+    assert_eq!(annotated[0].highlight_ranges, vec![1..3]);
+}
+
+// ---------------------------------------------------------------------------
+// Strict empty values
+// ---------------------------------------------------------------------------
+
+fn strict_tag_options(strict_empty_values: bool) -> MatchSorterOptions<TaggedItem> {
+    MatchSorterOptions {
+        keys: vec![Key::new(|i: &TaggedItem| i.tags.clone())],
+        strict_empty_values,
+        ..Default::default()
+    }
+}
+
+/// Without the option, empty values are ranked and take a key index.
+#[test]
+fn strict_empty_values_off_ranks_empty_values() {
+    let item = TaggedItem::new("apple", &["", "fruit"]);
+    let opts = strict_tag_options(false);
+    let info = get_highest_ranking(&item, &opts.keys, "fruit", &opts);
+    assert_eq!(info.key_index, 1);
+}
+
+/// With the option, an empty value trips the debug assertion in every key.
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "key extracted empty values")]
+fn strict_empty_values_asserts_in_debug() {
+    let items = [
+        TaggedItem::new("apple", &["fruit"]),
+        TaggedItem::new("carrot", &["", "root"]),
+    ];
+    let _ = match_sorter(&items, "fr", &strict_tag_options(true));
+}
+
+/// In release builds the option drops empty values instead.
+#[cfg(not(debug_assertions))]
+#[test]
+fn strict_empty_values_filters_in_release() {
+    let item = TaggedItem::new("apple", &["", "fruit"]);
+    let opts = strict_tag_options(true);
+    let info = get_highest_ranking(&item, &opts.keys, "fruit", &opts);
+    assert_eq!(info.rank, Ranking::CaseSensitiveEqual);
+    assert_eq!(info.key_index, 0);
+}
+
+// ---------------------------------------------------------------------------
+// Borrowed options
+// ---------------------------------------------------------------------------
+
+/// One options value serves many searches, each returning the same result.
+#[test]
+fn borrowed_options_reused_across_calls() {
+    let items = ["pineapple", "apple", "grape", "apricot", "banana"];
+    let opts = MatchSorterOptions {
+        keys: vec![Key::new(|s: &&str| vec![(*s).to_owned()]).alias("name")],
+        threshold: Ranking::Contains,
+        ..Default::default()
+    };
+    let first = match_sorter(&items, "ap", &opts);
+    assert_eq!(first, vec![&"apple", &"apricot", &"grape", &"pineapple"]);
+    for _ in 0..10 {
+        assert_eq!(match_sorter(&items, "ap", &opts), first);
+    }
+}
+
+/// `base_sort` is called through the borrowed options on every search.
+#[test]
+fn borrowed_options_call_base_sort() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let calls = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&calls);
+    let items = ["b-item", "a-item", "c-item"];
+    let opts = MatchSorterOptions {
+        // Reverse alphabetical tiebreaker.
+        base_sort: Some(Box::new(
+            move |a: &RankedItem<&str>, b: &RankedItem<&str>| {
+                counter.set(counter.get() + 1);
+                b.ranked_value.cmp(&a.ranked_value)
+            },
+        )),
+        ..Default::default()
+    };
+    for _ in 0..3 {
+        let before = calls.get();
+        assert_eq!(
+            match_sorter(&items, "item", &opts),
+            vec![&"c-item", &"b-item", &"a-item"]
+        );
+        assert!(calls.get() > before);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Unicode case folding
+// ---------------------------------------------------------------------------
+
+#[test]
+fn default_case_folding_is_simple() {
+    let opts: MatchSorterOptions<&str> = MatchSorterOptions::default();
+    assert_eq!(opts.unicode_case_folding, UnicodeCaseFolding::Simple);
+}
+
+#[test]
+fn full_case_folding_matches_sharp_s() {
+    let items = ["Ma\u{df}e", "Masche", "MASSE"];
+    let full = MatchSorterOptions {
+        unicode_case_folding: UnicodeCaseFolding::Full,
+        threshold: Ranking::Equal,
+        ..Default::default()
+    };
+    // Both rank Equal; the default tiebreaker orders them by value.
+    assert_eq!(
+        match_sorter(&items, "masse", &full),
+        vec![&"MASSE", &"Ma\u{df}e"]
+    );
+    let simple = MatchSorterOptions {
+        threshold: Ranking::Equal,
+        ..Default::default()
+    };
+    assert_eq!(match_sorter(&items, "masse", &simple), vec![&"MASSE"]);
+}
+
+#[test]
+fn turkic_case_folding_maps_dotted_capital_to_i() {
+    let items = ["\u{130}stanbul", "Isparta"];
+    let opts = MatchSorterOptions {
+        unicode_case_folding: UnicodeCaseFolding::Turkic,
+        keep_diacritics: true,
+        threshold: Ranking::StartsWith,
+        ..Default::default()
+    };
+    assert_eq!(match_sorter(&items, "is", &opts), vec![&"\u{130}stanbul"]);
+    assert_eq!(match_sorter(&items, "\u{131}s", &opts), vec![&"Isparta"]);
+}
+
+// ---------------------------------------------------------------------------
+// Snapshots
+// ---------------------------------------------------------------------------
+
+fn snapshot_items() -> Vec<&'static str> {
+    vec![
+        "pineapple",
+        "apple",
+        "Apple",
+        "grape",
+        "banana",
+        "application",
+        "a pear",
+        "map",
+    ]
+}
+
+#[test]
+fn snapshot_ranked_preserves_input_order() {
+    let items = snapshot_items();
+    let snapshot = match_sorter_with_snapshot(&items, "ap", &MatchSorterOptions::default());
+    let indices: Vec<usize> = snapshot.ranked.iter().map(|ri| ri.index).collect();
+    assert!(indices.windows(2).all(|w| w[0] < w[1]), "{indices:?}");
+    for ri in &snapshot.ranked {
+        assert!(std::ptr::eq(ri.item, &items[ri.index]));
+    }
+}
+
+#[test]
+fn snapshot_sorted_matches_match_sorter() {
+    let items = snapshot_items();
+    for query in ["ap", "apple", "A", "an", "zz", ""] {
+        let snapshot = match_sorter_with_snapshot(&items, query, &MatchSorterOptions::default());
+        assert_eq!(
+            snapshot.sorted,
+            match_sorter(&items, query, &MatchSorterOptions::default()),
+            "query {query:?}"
+        );
+        assert_eq!(snapshot.sorted.len(), snapshot.ranked.len());
+    }
+}
+
+#[test]
+fn snapshot_stats_sum_to_ranked_len() {
+    let items = snapshot_items();
+    for threshold in [Ranking::Matches(1.0), Ranking::NoMatch] {
+        let snapshot = match_sorter_with_snapshot(
+            &items,
+            "ap",
+            &MatchSorterOptions {
+                threshold,
+                ..Default::default()
+            },
+        );
+        assert_eq!(snapshot.stats.total(), snapshot.ranked.len());
+    }
+
+    let snapshot = match_sorter_with_snapshot(&items, "apple", &MatchSorterOptions::default());
+    assert_eq!(snapshot.stats.exact, 1);
+    assert_eq!(snapshot.stats.equal, 1);
+    assert_eq!(snapshot.stats.contains, 1);
+    assert_eq!(snapshot.stats.starts_with, 0);
+}
+
+#[test]
+fn snapshot_into_sorted_returns_sorted() {
+    let items = snapshot_items();
+    let snapshot = match_sorter_with_snapshot(&items, "ap", &MatchSorterOptions::default());
+    let sorted = snapshot.sorted.clone();
+    assert_eq!(snapshot.into_sorted(), sorted);
+}
+
+// ---------------------------------------------------------------------------
+// Lazy keys
+// ---------------------------------------------------------------------------
+
+struct Document {
+    title: &'static str,
+    body: &'static str,
+}
+
+impl AsMatchStr for Document {
+    fn as_match_str(&self) -> &str {
+        self.title
+    }
+}
+
+/// The first sentence of the body that mentions the query.
+fn relevant_sentence(doc: &Document, query: &str) -> Vec<String> {
+    doc.body
+        .split(". ")
+        .find(|sentence| sentence.to_lowercase().contains(query))
+        .map(str::to_owned)
+        .into_iter()
+        .collect()
+}
+
+#[test]
+fn lazy_key_ranks_query_specific_value() {
+    let docs = [
+        Document {
+            title: "Guide",
+            body: "Install the tool. Borrowing rules explained",
+        },
+        Document {
+            title: "Borrowing",
+            body: "Nothing relevant here",
+        },
+    ];
+    let opts = MatchSorterOptions {
+        keys: vec![
+            Key::from_fn(|d: &Document| d.title),
+            Key::from_fn_lazy(relevant_sentence),
+        ],
+        ..Default::default()
+    };
+    let results = match_sorter(&docs, "borrowing", &opts);
+    let titles: Vec<&str> = results.iter().map(|d| d.title).collect();
+    assert_eq!(titles, vec!["Borrowing", "Guide"]);
+
+    let info = get_highest_ranking(&docs[0], &opts.keys, "install", &opts);
+    assert_eq!(info.ranked_value, "Install the tool");
+    assert_eq!(info.rank, Ranking::StartsWith);
+}
+
+#[test]
+fn lazy_key_sees_each_token_in_any_token_mode() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let log = Rc::clone(&seen);
+    let docs = [Document {
+        title: "Guide",
+        body: "Install the tool. Borrowing rules explained",
+    }];
+    let opts = MatchSorterOptions {
+        keys: vec![Key::from_fn_lazy(move |d: &Document, query: &str| {
+            log.borrow_mut().push(query.to_owned());
+            relevant_sentence(d, query)
+        })],
+        query_mode: QueryMode::AnyToken,
+        ..Default::default()
+    };
+    assert_eq!(match_sorter(&docs, "Install Rules", &opts).len(), 1);
+    assert_eq!(*seen.borrow(), vec!["install", "rules"]);
+}
+
+// ---------------------------------------------------------------------------
+// Item filter
+// ---------------------------------------------------------------------------
+
+#[test]
+fn item_filter_excludes_rejected_items() {
+    let items = ["apple", "apricot", "grape", "pineapple"];
+    let opts = MatchSorterOptions {
+        item_filter: Some(Box::new(|item: &&str| !item.starts_with("ap"))),
+        ..Default::default()
+    };
+    assert_eq!(
+        match_sorter(&items, "ap", &opts),
+        vec![&"grape", &"pineapple"]
+    );
+}
+
+#[test]
+fn item_filter_ranks_subset_like_filtered_slice() {
+    let items = ["apple", "banana", "grape", "apricot", "pineapple", "papaya"];
+    let keep = |item: &&str| item.len() > 5;
+    let opts = MatchSorterOptions {
+        item_filter: Some(Box::new(keep)),
+        ..Default::default()
+    };
+    let subset: Vec<&str> = items.iter().copied().filter(keep).collect();
+    for query in ["ap", "a", "pa", "zz", ""] {
+        let filtered = match_sorter(&items, query, &opts);
+        let expected = match_sorter(&subset, query, &MatchSorterOptions::default());
+        assert_eq!(filtered, expected, "query {query:?}");
+    }
+
+    // Indices still refer to the full slice.
+    let snapshot = match_sorter_with_snapshot(&items, "apricot", &opts);
+    assert_eq!(snapshot.ranked[0].index, 3);
+}
+
+#[test]
+fn item_filter_rejecting_everything_returns_empty() {
+    let items = ["apple", "grape"];
+    let opts = MatchSorterOptions {
+        item_filter: Some(Box::new(|_: &&str| false)),
+        ..Default::default()
+    };
+    assert!(match_sorter(&items, "", &opts).is_empty());
+    assert!(match_sorter(&items, "apple", &opts).is_empty());
+}
+
+#[test]
+fn item_filter_called_once_per_item() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let calls = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&calls);
+    let items = ["apple", "grape", "banana", "apricot"];
+    let opts = MatchSorterOptions {
+        keys: vec![
+            Key::from_fn(|s: &&str| *s),
+            Key::from_fn(|s: &&str| &s[1..]),
+        ],
+        item_filter: Some(Box::new(move |_: &&str| {
+            counter.set(counter.get() + 1);
+            true
+        })),
+        ..Default::default()
+    };
+    match_sorter(&items, "ap", &opts);
+    assert_eq!(calls.get(), items.len());
+}
+
+// ---------------------------------------------------------------------------
+// Merging result sets
+// ---------------------------------------------------------------------------
+
+/// The matches of `items` for `query` as sorted `RankedItem`s.
+fn sorted_ranked<'a>(items: &'a [&'a str], query: &str) -> Vec<RankedItem<'a, &'a str>> {
+    let mut ranked =
+        match_sorter_with_snapshot(items, query, &MatchSorterOptions::default()).ranked;
+    ranked.sort_by(|a, b| sort_ranked_values(a, b, &default_base_sort));
+    ranked
+}
+
+#[test]
+fn merge_orders_union_like_single_search() {
+    let local = ["pineapple", "apple", "banana"];
+    let remote = ["grape", "apricot", "ap"];
+    let opts = MatchSorterOptions::default();
+    let merged = match_sorter_merge(
+        match_sorter(&local, "ap", &opts),
+        match_sorter(&remote, "ap", &opts),
+        "ap",
+        &MatchSorterOptions::default(),
+    );
+    let all = ["pineapple", "apple", "banana", "grape", "apricot", "ap"];
+    assert_eq!(merged, match_sorter(&all, "ap", &opts));
+    assert_eq!(
+        merged,
+        vec![&"ap", &"apple", &"apricot", &"grape", &"pineapple"]
+    );
+}
+
+#[test]
+fn merge_dedups_by_address_by_default() {
+    let items = ["apple", "apricot"];
+    let other = ["apple"];
+    let opts = MatchSorterOptions::default();
+    let merged = match_sorter_merge(
+        match_sorter(&items, "ap", &opts),
+        vec![&items[0], &other[0]],
+        "ap",
+        &MatchSorterOptions::default(),
+    );
+    // `items[0]` appears in both sets and is kept once; the equal but
+    // distinct `other[0]` is not a duplicate.
+    assert_eq!(merged.len(), 3);
+    assert!(std::ptr::eq(merged[0], &items[0]));
+    assert!(std::ptr::eq(merged[1], &other[0]));
+}
+
+#[test]
+fn merge_dedups_by_value_with_dedup_key() {
+    let local = [Item::new("Apple"), Item::new("Apricot")];
+    let remote = [Item::new("apple"), Item::new("Grape")];
+    let opts = || MatchSorterOptions {
+        keys: vec![Key::from_fn(|i: &Item| i.name.as_str())],
+        dedup_key: Some(Box::new(|i: &Item| i.name.to_lowercase())),
+        ..Default::default()
+    };
+    let merged = match_sorter_merge(
+        local.iter().collect(),
+        remote.iter().collect(),
+        "ap",
+        &opts(),
+    );
+    // The first occurrence wins: "Apple" from `local`, not "apple".
+    let names: Vec<&str> = merged.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, vec!["Apple", "Apricot", "Grape"]);
+    assert!(std::ptr::eq(merged[0], &local[0]));
+}
+
+#[test]
+fn merge_handles_empty_inputs() {
+    let items = ["apple", "grape"];
+    let opts = MatchSorterOptions::default();
+    let found = match_sorter(&items, "ap", &opts);
+    assert!(
+        match_sorter_merge::<&str>(vec![], vec![], "ap", &MatchSorterOptions::default()).is_empty()
+    );
+    assert_eq!(
+        match_sorter_merge(found.clone(), vec![], "ap", &MatchSorterOptions::default()),
+        found
+    );
+    assert_eq!(
+        match_sorter_merge(vec![], found.clone(), "ap", &MatchSorterOptions::default()),
+        found
+    );
+    assert!(match_sorter_merge_presorted::<&str>(vec![], vec![], &opts).is_empty());
+}
+
+#[test]
+fn merge_of_identical_sets_is_unchanged() {
+    let items = ["pineapple", "apple", "grape", "banana"];
+    let opts = MatchSorterOptions::default();
+    let found = match_sorter(&items, "ap", &opts);
+    assert_eq!(
+        match_sorter_merge(
+            found.clone(),
+            found.clone(),
+            "ap",
+            &MatchSorterOptions::default()
+        ),
+        found
+    );
+    assert_eq!(
+        match_sorter_merge_presorted(
+            sorted_ranked(&items, "ap"),
+            sorted_ranked(&items, "ap"),
+            &opts
+        ),
+        found
+    );
+}
+
+#[test]
+fn merge_presorted_interleaves_without_reranking() {
+    let local = ["pineapple", "ap", "grape"];
+    let remote = ["apricot", "papaya", "apple"];
+    let merged = match_sorter_merge_presorted(
+        sorted_ranked(&local, "ap"),
+        sorted_ranked(&remote, "ap"),
+        &MatchSorterOptions::default(),
+    );
+    assert_eq!(
+        merged,
+        vec![
+            &"ap",
+            &"apple",
+            &"apricot",
+            &"grape",
+            &"papaya",
+            &"pineapple"
+        ]
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tie priority
+// ---------------------------------------------------------------------------
+
+/// A user account whose verified flag breaks ties between equal names.
+struct Account {
+    name: &'static str,
+    verified: Option<bool>,
+}
+
+impl AsMatchStr for Account {
+    fn as_match_str(&self) -> &str {
+        self.name
+    }
+}
+
+#[test]
+fn tie_priority_orders_equal_matches() {
+    let accounts = [
+        Account {
+            name: "alice",
+            verified: Some(false),
+        },
+        Account {
+            name: "alice",
+            verified: Some(true),
+        },
+        Account {
+            name: "alicia",
+            verified: Some(true),
+        },
+    ];
+    let opts = MatchSorterOptions {
+        keys: vec![
+            Key::from_fn(|a: &Account| a.name)
+                .tie_priority(|a| if a.verified == Some(true) { 1 } else { -1 }),
+        ],
+        ..Default::default()
+    };
+    let results = match_sorter(&accounts, "alice", &opts);
+    assert!(std::ptr::eq(results[0], &accounts[1]));
+    assert!(std::ptr::eq(results[1], &accounts[0]));
+
+    // The priority never overrides a better rank.
+    let results = match_sorter(&accounts, "ali", &opts);
+    assert!(std::ptr::eq(results[2], &accounts[0]));
+}
+
+#[test]
+fn tie_priority_missing_sorts_after_present() {
+    let accounts = [
+        Account {
+            name: "bob",
+            verified: None,
+        },
+        Account {
+            name: "bob",
+            verified: Some(false),
+        },
+    ];
+    // Only accounts with a verified flag are matched on the prioritized key.
+    let opts = MatchSorterOptions {
+        keys: vec![
+            Key::new(|a: &Account| match a.verified {
+                Some(_) => vec![a.name.to_owned()],
+                None => Vec::new(),
+            })
+            .tie_priority(|_| i64::MIN),
+            Key::from_fn(|a: &Account| a.name),
+        ],
+        base_sort: Some(Box::new(|a, b| a.index.cmp(&b.index))),
+        ..Default::default()
+    };
+    // Both match at key index 0 (the first value of the item's flattened
+    // values), but only the second carries a tie priority.
+    let ranked = match_sorter_with_snapshot(&accounts, "bob", &opts);
+    assert_eq!(ranked.ranked[0].tie_priority, None);
+    assert_eq!(ranked.ranked[1].tie_priority, Some(i64::MIN));
+    assert!(std::ptr::eq(ranked.sorted[0], &accounts[1]));
+}
+
+// ---------------------------------------------------------------------------
+// Ranked results
+// ---------------------------------------------------------------------------
+
+#[test]
+fn ranked_orders_like_match_sorter() {
+    let items = [
+        "pineapple",
+        "banana",
+        "grape",
+        "apple",
+        "Apple",
+        "ap",
+        "a p",
+    ];
+    let opts = MatchSorterOptions::default();
+    for query in ["ap", "a", "APPLE", "", "zz"] {
+        let ranked: Vec<&&str> = match_sorter_ranked(&items, query, &opts)
+            .into_iter()
+            .map(|ri| ri.item)
+            .collect();
+        assert_eq!(
+            ranked,
+            match_sorter(&items, query, &opts),
+            "query {query:?}"
+        );
+    }
+}
+
+#[test]
+fn ranked_populates_every_field() {
+    let items = [
+        TaggedItem::new("Mango", &["fruit"]),
+        TaggedItem::new("Carrot", &["vegetable", "root"]),
+    ];
+    let opts = MatchSorterOptions {
+        keys: vec![
+            Key::from_fn(|i: &TaggedItem| i.name.as_str()).alias("name"),
+            Key::from_fn_multi(|i: &TaggedItem| i.tags.iter().map(String::as_str).collect())
+                .threshold(Ranking::StartsWith)
+                .tie_priority(|_| 3),
+        ],
+        ..Default::default()
+    };
+    let ranked = match_sorter_ranked(&items, "root", &opts);
+    assert_eq!(ranked.len(), 1);
+    let carrot = &ranked[0];
+    assert!(std::ptr::eq(carrot.item, &items[1]));
+    assert_eq!(carrot.index, 1);
+    assert_eq!(carrot.rank, Ranking::CaseSensitiveEqual);
+    assert_eq!(carrot.ranked_value, "root");
+    // "Carrot" takes index 0, then "vegetable" 1 and "root" 2.
+    assert_eq!(carrot.key_index, 2);
+    assert_eq!(carrot.key_threshold, Some(Ranking::StartsWith));
+    assert!(!carrot.is_soft_match);
+    assert_eq!(carrot.matched_portion.as_deref(), Some("root"));
+    assert_eq!(carrot.matched_key_alias, None);
+    assert_eq!(carrot.tie_priority, Some(3));
+
+    let ranked = match_sorter_ranked(&items, "man", &opts);
+    assert_eq!(ranked[0].matched_key_alias.as_deref(), Some("name"));
+    assert_eq!(ranked[0].tie_priority, None);
+}
+
+#[test]
+fn ranked_values_outlive_the_options() {
+    use std::borrow::Cow;
+
+    let items = vec!["apple".to_owned(), "grape".to_owned()];
+    // No-keys mode borrows the value from `items`.
+    let borrowed = match_sorter_ranked(&items, "ap", &MatchSorterOptions::default());
+    assert!(matches!(borrowed[0].ranked_value, Cow::Borrowed(_)));
+    assert!(std::ptr::eq(
+        borrowed[0].ranked_value.as_ptr(),
+        items[0].as_ptr()
+    ));
+
+    // Keys mode owns the extracted value, which must stay valid after the
+    // options and their extractors are dropped.
+    let owned = {
+        let opts = MatchSorterOptions {
+            keys: vec![Key::new(|s: &String| vec![s.to_uppercase()])],
+            ..Default::default()
+        };
+        match_sorter_ranked(&items, "ap", &opts)
+    };
+    assert!(matches!(owned[0].ranked_value, Cow::Owned(_)));
+    assert_eq!(owned[0].ranked_value, "APPLE");
+    assert_eq!(owned[1].ranked_value, "GRAPE");
+    assert_eq!(owned[1].matched_portion.as_deref(), Some("AP"));
+}
+
+// ---------------------------------------------------------------------------
+// Empty query behavior
+// ---------------------------------------------------------------------------
+
+fn with_empty_query_behavior(behavior: EmptyQueryBehavior) -> MatchSorterOptions<&'static str> {
+    MatchSorterOptions {
+        empty_query_behavior: behavior,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn empty_query_return_all_sorts_every_item() {
+    let items = ["pear", "", "apple", "banana"];
+    let results = match_sorter(
+        &items,
+        "",
+        &with_empty_query_behavior(EmptyQueryBehavior::ReturnAll),
+    );
+    assert_eq!(
+        results,
+        match_sorter(&items, "", &MatchSorterOptions::default())
+    );
+    // The empty item equals the query; the rest start with it.
+    assert_eq!(results, vec![&"", &"apple", &"banana", &"pear"]);
+}
+
+#[test]
+fn empty_query_return_empty_only_for_empty_query() {
+    let items = ["pear", "apple", "banana"];
+    let opts = with_empty_query_behavior(EmptyQueryBehavior::ReturnEmpty);
+    assert!(match_sorter(&items, "", &opts).is_empty());
+    assert!(match_sorter_ranked(&items, "", &opts).is_empty());
+    assert_eq!(match_sorter(&items, "ap", &opts), vec![&"apple"]);
+    assert_eq!(
+        match_sorter(&items, " ", &opts),
+        match_sorter(&items, " ", &MatchSorterOptions::default())
+    );
+}
+
+#[test]
+fn empty_query_return_all_unsorted_keeps_input_order() {
+    let items = ["pear", "", "apple", "banana"];
+    let opts = with_empty_query_behavior(EmptyQueryBehavior::ReturnAllUnsorted);
+    assert_eq!(
+        match_sorter(&items, "", &opts),
+        vec![&"pear", &"", &"apple", &"banana"]
+    );
+    let ranked: Vec<&&str> = match_sorter_ranked(&items, "", &opts)
+        .into_iter()
+        .map(|ri| ri.item)
+        .collect();
+    assert_eq!(ranked, vec![&"pear", &"", &"apple", &"banana"]);
+    // Non-empty queries are ranked and sorted as usual.
+    assert_eq!(
+        match_sorter(&items, "a", &opts),
+        vec![&"apple", &"banana", &"pear"]
+    );
+}
+
+#[test]
+fn empty_query_return_all_unsorted_skips_ranking() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let calls = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&calls);
+    let items = [Item::new("b"), Item::new("a"), Item::new("c")];
+    let opts = MatchSorterOptions {
+        keys: vec![Key::new(move |i: &Item| {
+            counter.set(counter.get() + 1);
+            vec![i.name.clone()]
+        })],
+        empty_query_behavior: EmptyQueryBehavior::ReturnAllUnsorted,
+        item_filter: Some(Box::new(|i: &Item| i.name != "c")),
+        ..Default::default()
+    };
+    let results = match_sorter(&items, "", &opts);
+    assert_eq!(results, vec![&items[0], &items[1]]);
+    assert_eq!(calls.get(), 0);
+}
+
+#[test]
+fn empty_query_return_empty_is_honored_everywhere() {
+    use matchsorter::{
+        IncrementalContext, PaginationCache, match_sorter_all, match_sorter_any, match_sorter_by,
+        match_sorter_incremental, match_sorter_inplace, match_sorter_order, match_sorter_paginate,
+    };
+
+    let items = ["pear", "apple", "banana"];
+    let opts = with_empty_query_behavior(EmptyQueryBehavior::ReturnEmpty);
+    assert!(match_sorter_order(&items, "", &opts).is_empty());
+    assert!(match_sorter_by(&items, "", &opts, |s| s.len()).is_empty());
+    let mut owned = items.to_vec();
+    match_sorter_inplace(&mut owned, "", &opts);
+    assert!(owned.is_empty());
+    assert_eq!(match_sorter_paginate(&items, "", 0, 10, &opts).total, 0);
+    assert_eq!(
+        PaginationCache::new().page(&items, "", 0, 10, &opts).total,
+        0
+    );
+    assert!(match_sorter_with_annotations(&items, "", &opts).is_empty());
+    assert!(
+        match_sorter_with_snapshot(&items, "", &opts)
+            .ranked
+            .is_empty()
+    );
+    assert!(match_sorter_by_score(&items, "", &opts).is_empty());
+    assert!(match_sorter_merge(items.iter().collect(), Vec::new(), "", &opts).is_empty());
+    assert!(!match_sorter_any(&items, "", &opts));
+    assert!(!match_sorter_all(&items, "", &opts, |_| true));
+    assert!(match_sorter_all(&items, "", &opts, |_| false));
+    let mut ctx = IncrementalContext::new();
+    assert!(match_sorter_incremental(&items, "", &opts, &mut ctx).is_empty());
+    assert_eq!(
+        match_sorter_incremental(&items, "ap", &opts, &mut ctx),
+        vec![&"apple"]
+    );
+}
+
+#[test]
+fn empty_query_return_all_unsorted_is_honored_everywhere() {
+    use matchsorter::{
+        IncrementalContext, PaginationCache, match_sorter_all, match_sorter_any, match_sorter_by,
+        match_sorter_incremental, match_sorter_inplace, match_sorter_order, match_sorter_paginate,
+    };
+
+    let items = ["pear", "", "apple", "banana"];
+    let opts = MatchSorterOptions {
+        item_filter: Some(Box::new(|s: &&str| *s != "banana")),
+        ..with_empty_query_behavior(EmptyQueryBehavior::ReturnAllUnsorted)
+    };
+    let expected = vec![&"pear", &"", &"apple"];
+    assert_eq!(match_sorter(&items, "", &opts), expected);
+    assert_eq!(match_sorter_order(&items, "", &opts), vec![0, 1, 2]);
+    assert_eq!(
+        match_sorter_by(&items, "", &opts, |s| s.len()),
+        vec![4, 0, 5]
+    );
+    let mut owned = items.to_vec();
+    match_sorter_inplace(&mut owned, "", &opts);
+    assert_eq!(owned, ["pear", "", "apple"]);
+    assert_eq!(
+        match_sorter_paginate(&items, "", 0, 10, &opts).items,
+        expected
+    );
+    assert_eq!(
+        PaginationCache::new().page(&items, "", 0, 10, &opts).items,
+        expected
+    );
+    let annotated: Vec<&&str> = match_sorter_with_annotations(&items, "", &opts)
+        .into_iter()
+        .map(|a| a.item)
+        .collect();
+    assert_eq!(annotated, expected);
+    assert_eq!(
+        match_sorter_with_snapshot(&items, "", &opts).sorted,
+        expected
+    );
+    let scored: Vec<&&str> = match_sorter_by_score(&items, "", &opts)
+        .into_iter()
+        .map(|s| s.into_item())
+        .collect();
+    assert_eq!(scored, expected);
+    let merged = match_sorter_merge(
+        vec![&items[3], &items[2]],
+        vec![&items[1], &items[0]],
+        "",
+        &opts,
+    );
+    assert_eq!(merged, vec![&"apple", &"", &"pear"]);
+    assert!(match_sorter_any(&items, "", &opts));
+    assert!(!match_sorter_all(&items, "", &opts, |_| true));
+    assert!(match_sorter_all(&items, "", &opts, |s| *s != "banana"));
+    let mut ctx = IncrementalContext::new();
+    assert_eq!(
+        match_sorter_incremental(&items, "", &opts, &mut ctx),
+        expected
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Sessions
+// ---------------------------------------------------------------------------
+
+#[test]
+fn session_pages_match_independent_searches() {
+    let pages = [
+        vec![
+            Item::new("Apple"),
+            Item::new("pineapple"),
+            Item::new("Banana"),
+        ],
+        vec![Item::new("apricot"), Item::new("grape"), Item::new("ap")],
+    ];
+    let opts = || MatchSorterOptions {
+        keys: vec![Key::from_fn(|i: &Item| i.name.as_str())],
+        threshold: Ranking::Contains,
+        ..Default::default()
+    };
+    let session = MatchSorterSession::new("ap", opts());
+    for page in &pages {
+        assert_eq!(session.rank_page(page), match_sorter(page, "ap", &opts()));
+    }
+    // Ranking a page again gives the same result.
+    assert_eq!(session.rank_page(&pages[0]), session.rank_page(&pages[0]));
+}
+
+#[test]
+fn session_update_query_switches_results() {
+    let items = ["apple", "banana", "grape", "apricot"];
+    let opts = MatchSorterOptions::default();
+    let mut session = MatchSorterSession::new("ap", MatchSorterOptions::default());
+    assert_eq!(session.rank_page(&items), match_sorter(&items, "ap", &opts));
+    for query in ["apr", "ban", "", "ban"] {
+        session.update_query(query);
+        assert_eq!(session.query(), query);
+        assert_eq!(
+            session.rank_page(&items),
+            match_sorter(&items, query, &opts),
+            "query {query:?}"
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Discarding slow items
+// ---------------------------------------------------------------------------
+
+/// A key that sleeps for 100ms on items whose name starts with "slow".
+fn sleepy_key() -> Key<Item> {
+    use std::time::Duration;
+
+    Key::new(|i: &Item| {
+        if i.name.starts_with("slow") {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        vec![i.name.clone()]
+    })
+}
+
+#[test]
+fn discard_items_slower_than_drops_slow_items() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    let items = [
+        Item::new("slow apple"),
+        Item::new("apple"),
+        Item::new("pineapple"),
+        Item::new("slow apricot"),
+        Item::new("apricot"),
+    ];
+    let discarded = Rc::new(RefCell::new(Vec::new()));
+    let log = Rc::clone(&discarded);
+    let opts = MatchSorterOptions {
+        keys: vec![sleepy_key()],
+        discard_items_slower_than: Some(Duration::from_millis(10)),
+        on_slow_item: Some(Box::new(move |i: &Item| {
+            log.borrow_mut().push(i.name.clone())
+        })),
+        ..Default::default()
+    };
+    let results = match_sorter(&items, "ap", &opts);
+    let names: Vec<&str> = results.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, ["apple", "apricot", "pineapple"]);
+    assert_eq!(*discarded.borrow(), ["slow apple", "slow apricot"]);
+}
+
+#[test]
+fn discard_items_slower_than_keeps_order_of_remaining_items() {
+    use std::time::Duration;
+
+    let items = [
+        Item::new("grape"),
+        Item::new("slow apple"),
+        Item::new("apple"),
+        Item::new("snapple"),
+        Item::new("apricot"),
+    ];
+    let fast: Vec<Item> = items
+        .iter()
+        .filter(|i| !i.name.starts_with("slow"))
+        .map(|i| Item::new(&i.name))
+        .collect();
+    let opts = MatchSorterOptions {
+        keys: vec![sleepy_key()],
+        discard_items_slower_than: Some(Duration::from_millis(10)),
+        ..Default::default()
+    };
+    let plain = MatchSorterOptions {
+        keys: vec![sleepy_key()],
+        ..Default::default()
+    };
+    assert_eq!(
+        match_sorter(&items, "ap", &opts),
+        match_sorter(&fast, "ap", &plain)
+    );
+}
+
+#[test]
+fn discard_items_slower_than_keeps_fast_items() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    let items = [Item::new("apple"), Item::new("apricot")];
+    let calls = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&calls);
+    let opts = MatchSorterOptions {
+        keys: vec![sleepy_key()],
+        discard_items_slower_than: Some(Duration::from_secs(10)),
+        on_slow_item: Some(Box::new(move |_: &Item| counter.set(counter.get() + 1))),
+        ..Default::default()
+    };
+    assert_eq!(match_sorter(&items, "ap", &opts).len(), 2);
+    assert_eq!(calls.get(), 0);
+}
+
+// ---------------------------------------------------------------------------
+// Trimmed keys
+// ---------------------------------------------------------------------------
+
+#[test]
+fn trimmed_key_matched_portion_is_within_trimmed_value() {
+    let items = [Item::new("  apple pie "), Item::new("\tpineapple")];
+    let opts = MatchSorterOptions {
+        keys: vec![Key::from_fn(|i: &Item| i.name.as_str()).trim()],
+        ..Default::default()
+    };
+    let ranked = match_sorter_ranked(&items, "apple", &opts);
+    let summary: Vec<(&str, Ranking, Option<&str>)> = ranked
+        .iter()
+        .map(|r| {
+            (
+                r.ranked_value.as_ref(),
+                r.rank,
+                r.matched_portion.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("apple pie", Ranking::StartsWith, Some("apple")),
+            ("pineapple", Ranking::Contains, Some("apple")),
+        ]
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Scored results
+// ---------------------------------------------------------------------------
+
+#[test]
+fn by_score_scores_are_normalized() {
+    let items = ["ap", "AP", "apple", "grape", "a-p", "xaxp"];
+    let opts = || MatchSorterOptions {
+        threshold: Ranking::Matches(1.0),
+        ..Default::default()
+    };
+    let scored = match_sorter_by_score(&items, "ap", &opts());
+    assert_eq!(scored.len(), items.len());
+    assert!(scored.iter().all(|s| (0.0..=1.0).contains(&s.score())));
+    assert_eq!((*scored[0].item, scored[0].score), ("ap", 1.0));
+    assert_eq!((*scored[1].item, scored[1].score), ("AP", 6.0 / 7.0));
+    for pair in scored.windows(2) {
+        assert!(pair[0].score >= pair[1].score, "{pair:?}");
+    }
+}
+
+#[test]
+fn by_score_order_matches_match_sorter() {
+    let items = [
+        Item::new("pineapple"),
+        Item::new("apple"),
+        Item::new("Apple"),
+        Item::new("apricot"),
+        Item::new("snap pea"),
+    ];
+    let opts = || MatchSorterOptions {
+        keys: vec![Key::from_fn(|i: &Item| i.name.as_str())],
+        ..Default::default()
+    };
+    let scored: Vec<&Item> = match_sorter_by_score(&items, "ap", &opts())
+        .into_iter()
+        .map(|s| s.into_item())
+        .collect();
+    assert_eq!(scored, match_sorter(&items, "ap", &opts()));
+}
+
+#[test]
+fn by_score_above_filters_strictly() {
+    let items = ["apple", "grape", "ap"];
+    let scored = match_sorter_by_score(&items, "ap", &MatchSorterOptions::default());
+    let kept = |threshold: f64| -> Vec<&str> {
+        scored
+            .iter()
+            .filter(|s| s.above(threshold))
+            .map(|s| *s.item)
+            .collect()
+    };
+    // "grape" is a Contains match: 3/7.
+    assert_eq!(kept(0.0), ["ap", "apple", "grape"]);
+    assert_eq!(kept(3.0 / 7.0), ["ap", "apple"]);
+    assert_eq!(kept(1.0), Vec::<&str>::new());
+}
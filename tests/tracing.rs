@@ -0,0 +1,184 @@
+//! Tests for the `tracing` spans and events emitted by `match_sorter`.
+#![cfg(feature = "tracing")]
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use matchsorter::{Key, MatchSorterOptions, Ranking, match_sorter};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// A span or event as seen by [`Recorder`], with its fields rendered via
+/// `Debug`.
+#[derive(Debug, Clone)]
+struct Captured {
+    name: String,
+    level: Level,
+    /// The innermost span entered when an event was emitted.
+    parent: Option<String>,
+    fields: HashMap<String, String>,
+}
+
+impl Captured {
+    fn field(&self, name: &str) -> &str {
+        self.fields
+            .get(name)
+            .unwrap_or_else(|| panic!("{} has no field {name:?}", self.name))
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor(HashMap<String, String>);
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_owned(), format!("{value:?}"));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_owned(), value.to_owned());
+    }
+}
+
+/// Minimal subscriber recording every span and event at any level.
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<Captured>>>,
+    events: Arc<Mutex<Vec<Captured>>>,
+    stack: Arc<Mutex<Vec<u64>>>,
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(Captured {
+            name: attrs.metadata().name().to_owned(),
+            level: *attrs.metadata().level(),
+            parent: None,
+            fields: visitor.0,
+        });
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let parent = self
+            .stack
+            .lock()
+            .unwrap()
+            .last()
+            .map(|&id| self.spans.lock().unwrap()[id as usize - 1].name.clone());
+        self.events.lock().unwrap().push(Captured {
+            name: visitor.0.get("message").cloned().unwrap_or_default(),
+            level: *event.metadata().level(),
+            parent,
+            fields: visitor.0,
+        });
+    }
+
+    fn enter(&self, span: &Id) {
+        self.stack.lock().unwrap().push(span.into_u64());
+    }
+
+    fn exit(&self, _: &Id) {
+        self.stack.lock().unwrap().pop();
+    }
+}
+
+/// Run `f` with a fresh [`Recorder`] as the default subscriber.
+fn record<R>(f: impl FnOnce() -> R) -> (R, Recorder) {
+    let recorder = Recorder::default();
+    let result = tracing::subscriber::with_default(recorder.clone(), f);
+    (result, recorder)
+}
+
+#[test]
+fn span_records_inputs() {
+    let items = ["apple", "banana", "grape"];
+    let (_, recorder) = record(|| {
+        match_sorter(
+            &items,
+            "ap",
+            &MatchSorterOptions {
+                threshold: Ranking::Contains,
+                ..Default::default()
+            },
+        )
+    });
+    let spans = recorder.spans.lock().unwrap();
+    assert_eq!(spans.len(), 1);
+    let span = &spans[0];
+    assert_eq!(span.name, "match_sorter");
+    assert_eq!(span.level, Level::DEBUG);
+    assert_eq!(span.field("items"), "3");
+    assert_eq!(span.field("value"), "ap");
+    assert_eq!(span.field("threshold"), "Contains");
+}
+
+#[test]
+fn each_match_emits_trace_event_inside_span() {
+    struct Fruit(&'static str, &'static str);
+    impl matchsorter::AsMatchStr for Fruit {
+        fn as_match_str(&self) -> &str {
+            self.0
+        }
+    }
+
+    let items = [
+        Fruit("banana", "yellow"),
+        Fruit("grape", "purple"),
+        Fruit("apple", "red"),
+    ];
+    let opts = MatchSorterOptions {
+        keys: vec![
+            Key::new(|f: &Fruit| vec![f.0.to_owned()]),
+            Key::new(|f: &Fruit| vec![f.1.to_owned()]),
+        ],
+        threshold: Ranking::Contains,
+        ..Default::default()
+    };
+    let (results, recorder) = record(|| match_sorter(&items, "ple", &opts));
+    assert_eq!(results.len(), 2);
+
+    let events = recorder.events.lock().unwrap();
+    let matched: Vec<&Captured> = events.iter().filter(|e| e.name == "item matched").collect();
+    assert_eq!(matched.len(), 2);
+    // Items are ranked in input order; "grape" only matches via its color.
+    assert_eq!(matched[0].level, Level::TRACE);
+    assert_eq!(matched[0].parent.as_deref(), Some("match_sorter"));
+    assert_eq!(matched[0].field("index"), "1");
+    assert_eq!(matched[0].field("ranked_value"), "purple");
+    assert_eq!(matched[0].field("rank"), "Contains");
+    assert_eq!(matched[0].field("key_index"), "1");
+    assert_eq!(matched[1].field("index"), "2");
+    assert_eq!(matched[1].field("ranked_value"), "apple");
+    assert_eq!(matched[1].field("key_index"), "0");
+}
+
+#[test]
+fn summary_event_reports_counts() {
+    let items = ["apple", "banana", "grape", "pineapple"];
+    let (_, recorder) = record(|| match_sorter(&items, "ap", &MatchSorterOptions::default()));
+
+    let events = recorder.events.lock().unwrap();
+    let summary = events.last().unwrap();
+    assert_eq!(summary.name, "match_sorter finished");
+    assert_eq!(summary.level, Level::DEBUG);
+    assert_eq!(summary.parent.as_deref(), Some("match_sorter"));
+    assert_eq!(summary.field("processed"), "4");
+    assert_eq!(summary.field("matched"), "3");
+    assert!(summary.fields.contains_key("sort_duration"));
+}